@@ -0,0 +1,160 @@
+//! Benchmarks of `serde_yml` on large mappings and sequences.
+//!
+//! Run with `cargo bench`. Each benchmark is measured at several sizes so
+//! that work growing faster than the input shows up as a widening gap.
+
+// `criterion_group!` defines an undocumented public function.
+#![allow(missing_docs)]
+
+use criterion::{
+    black_box, criterion_group, criterion_main, BenchmarkId, Criterion,
+};
+use serde::Serialize;
+use serde_yml::{Mapping, Value};
+
+/// Mapping sizes to measure, in number of keys.
+const SIZES: [usize; 3] = [1_000, 10_000, 100_000];
+
+/// Returns a block mapping of `len` keys, one per line.
+fn manifest(len: usize) -> String {
+    (0..len).map(|i| format!("key{}: {}\n", i, i)).collect()
+}
+
+/// Parsing a mapping into a `Value`.
+fn bench_parse_mapping(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_mapping");
+    for len in SIZES {
+        let yaml = manifest(len);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(len),
+            &yaml,
+            |b, yaml| {
+                b.iter(|| {
+                    serde_yml::from_str::<Value>(black_box(yaml))
+                        .unwrap()
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Inserting into and looking up keys of a `Mapping`.
+fn bench_mapping_lookup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mapping_lookup");
+    for len in SIZES {
+        let keys: Vec<String> =
+            (0..len).map(|i| format!("key{}", i)).collect();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(len),
+            &keys,
+            |b, keys| {
+                b.iter(|| {
+                    let mut mapping =
+                        Mapping::with_capacity(keys.len());
+                    for (i, key) in keys.iter().enumerate() {
+                        let _ = mapping.insert(
+                            Value::from(key.as_str()),
+                            Value::from(i),
+                        );
+                    }
+                    keys.iter()
+                        .filter(|key| {
+                            mapping.contains_key(key.as_str())
+                        })
+                        .count()
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Linting a mapping, which looks for keys written twice.
+fn bench_lint_mapping(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lint_mapping");
+    for len in SIZES {
+        let yaml = manifest(len);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(len),
+            &yaml,
+            |b, yaml| {
+                b.iter(|| serde_yml::lint::lint(black_box(yaml)));
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Writing a mapping of `len` keys as a string.
+fn bench_to_string(c: &mut Criterion) {
+    let mut group = c.benchmark_group("to_string");
+    for len in SIZES {
+        let value: Value = serde_yml::from_str(&manifest(len)).unwrap();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(len),
+            &value,
+            |b, value| {
+                b.iter(|| {
+                    serde_yml::to_string(black_box(value)).unwrap()
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+/// An enum whose tuple and struct variants are written as singleton maps.
+#[derive(Serialize)]
+enum Step {
+    Move(i64, i64),
+    Rename { from: String, to: String },
+}
+
+/// A list of steps written through `singleton_map_recursive`.
+#[derive(Serialize)]
+struct Plan {
+    #[serde(with = "serde_yml::with::singleton_map_recursive")]
+    steps: Vec<Step>,
+}
+
+/// Writing tuple and struct variants as singleton maps.
+fn bench_singleton_map(c: &mut Criterion) {
+    let mut group = c.benchmark_group("singleton_map");
+    for len in SIZES {
+        let plan = Plan {
+            steps: (0..len as i64)
+                .map(|i| {
+                    if i % 2 == 0 {
+                        Step::Move(i, -i)
+                    } else {
+                        Step::Rename {
+                            from: format!("old{}", i),
+                            to: format!("new{}", i),
+                        }
+                    }
+                })
+                .collect(),
+        };
+        group.bench_with_input(
+            BenchmarkId::from_parameter(len),
+            &plan,
+            |b, plan| {
+                b.iter(|| {
+                    serde_yml::to_string(black_box(plan)).unwrap()
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_parse_mapping,
+    bench_mapping_lookup,
+    bench_lint_mapping,
+    bench_to_string,
+    bench_singleton_map
+);
+criterion_main!(benches);
@@ -3,7 +3,8 @@
 //! This file demonstrates the creation, usage, and various functionalities of the `Emitter` for emitting YAML events, including scalars, sequences, mappings, and document/stream events.
 
 use serde_yml::libyml::emitter::{
-    Emitter, Event, Mapping, Scalar, ScalarStyle, Sequence,
+    Emitter, EmitterBuilder, Event, Mapping, Scalar, ScalarStyle,
+    Sequence,
 };
 use std::io::Cursor;
 
@@ -14,7 +15,7 @@ pub(crate) fn main() {
     // Example: Emitting a stream start and end event
     let mut buffer = Cursor::new(Vec::new());
     {
-        let mut emitter = Emitter::new(Box::new(&mut buffer));
+        let mut emitter = Emitter::new(&mut buffer);
         emitter.emit(Event::StreamStart).unwrap();
         emitter.emit(Event::StreamEnd).unwrap();
     }
@@ -25,16 +26,10 @@ pub(crate) fn main() {
     // Example: Emitting a document start and end event with a scalar
     let mut buffer = Cursor::new(Vec::new());
     {
-        let mut emitter = Emitter::new(Box::new(&mut buffer));
+        let mut emitter = Emitter::new(&mut buffer);
         emitter.emit(Event::StreamStart).unwrap();
         emitter.emit(Event::DocumentStart).unwrap();
-        emitter
-            .emit(Event::Scalar(Scalar {
-                tag: None,
-                value: "hello",
-                style: ScalarStyle::Plain,
-            }))
-            .unwrap();
+        emitter.emit(Event::Scalar(Scalar::plain("hello"))).unwrap();
         emitter.emit(Event::DocumentEnd).unwrap();
         emitter.emit(Event::StreamEnd).unwrap();
     }
@@ -45,26 +40,12 @@ pub(crate) fn main() {
     // Example: Emitting a sequence
     let mut buffer = Cursor::new(Vec::new());
     {
-        let mut emitter = Emitter::new(Box::new(&mut buffer));
+        let mut emitter = Emitter::new(&mut buffer);
         emitter.emit(Event::StreamStart).unwrap();
         emitter.emit(Event::DocumentStart).unwrap();
-        emitter
-            .emit(Event::SequenceStart(Sequence { tag: None }))
-            .unwrap();
-        emitter
-            .emit(Event::Scalar(Scalar {
-                tag: None,
-                value: "item1",
-                style: ScalarStyle::Plain,
-            }))
-            .unwrap();
-        emitter
-            .emit(Event::Scalar(Scalar {
-                tag: None,
-                value: "item2",
-                style: ScalarStyle::Plain,
-            }))
-            .unwrap();
+        emitter.emit(Event::SequenceStart(Sequence::new())).unwrap();
+        emitter.emit(Event::Scalar(Scalar::plain("item1"))).unwrap();
+        emitter.emit(Event::Scalar(Scalar::plain("item2"))).unwrap();
         emitter.emit(Event::SequenceEnd).unwrap();
         emitter.emit(Event::DocumentEnd).unwrap();
         emitter.emit(Event::StreamEnd).unwrap();
@@ -76,39 +57,17 @@ pub(crate) fn main() {
     // Example: Emitting a mapping
     let mut buffer = Cursor::new(Vec::new());
     {
-        let mut emitter = Emitter::new(Box::new(&mut buffer));
+        let mut emitter = Emitter::new(&mut buffer);
         emitter.emit(Event::StreamStart).unwrap();
         emitter.emit(Event::DocumentStart).unwrap();
+        emitter.emit(Event::MappingStart(Mapping::new())).unwrap();
+        emitter.emit(Event::Scalar(Scalar::plain("key1"))).unwrap();
         emitter
-            .emit(Event::MappingStart(Mapping { tag: None }))
-            .unwrap();
-        emitter
-            .emit(Event::Scalar(Scalar {
-                tag: None,
-                value: "key1",
-                style: ScalarStyle::Plain,
-            }))
+            .emit(Event::Scalar(Scalar::plain("value1")))
             .unwrap();
+        emitter.emit(Event::Scalar(Scalar::plain("key2"))).unwrap();
         emitter
-            .emit(Event::Scalar(Scalar {
-                tag: None,
-                value: "value1",
-                style: ScalarStyle::Plain,
-            }))
-            .unwrap();
-        emitter
-            .emit(Event::Scalar(Scalar {
-                tag: None,
-                value: "key2",
-                style: ScalarStyle::Plain,
-            }))
-            .unwrap();
-        emitter
-            .emit(Event::Scalar(Scalar {
-                tag: None,
-                value: "value2",
-                style: ScalarStyle::Plain,
-            }))
+            .emit(Event::Scalar(Scalar::plain("value2")))
             .unwrap();
         emitter.emit(Event::MappingEnd).unwrap();
         emitter.emit(Event::DocumentEnd).unwrap();
@@ -121,16 +80,10 @@ pub(crate) fn main() {
     // Example: Flushing the emitter
     let mut buffer = Cursor::new(Vec::new());
     {
-        let mut emitter = Emitter::new(Box::new(&mut buffer));
+        let mut emitter = Emitter::new(&mut buffer);
         emitter.emit(Event::StreamStart).unwrap();
         emitter.emit(Event::DocumentStart).unwrap();
-        emitter
-            .emit(Event::Scalar(Scalar {
-                tag: None,
-                value: "hello",
-                style: ScalarStyle::Plain,
-            }))
-            .unwrap();
+        emitter.emit(Event::Scalar(Scalar::plain("hello"))).unwrap();
         emitter.flush().unwrap();
         emitter.emit(Event::DocumentEnd).unwrap();
         emitter.emit(Event::StreamEnd).unwrap();
@@ -142,15 +95,14 @@ pub(crate) fn main() {
     // Example: Emitting scalar with tag
     let mut buffer = Cursor::new(Vec::new());
     {
-        let mut emitter = Emitter::new(Box::new(&mut buffer));
+        let mut emitter = Emitter::new(&mut buffer);
         emitter.emit(Event::StreamStart).unwrap();
         emitter.emit(Event::DocumentStart).unwrap();
         emitter
-            .emit(Event::Scalar(Scalar {
-                tag: Some("!mytag".to_string()),
-                value: "hello",
-                style: ScalarStyle::Plain,
-            }))
+            .emit(Event::Scalar(
+                Scalar::tagged("!mytag", "hello")
+                    .with_style(ScalarStyle::Plain),
+            ))
             .unwrap();
         emitter.emit(Event::DocumentEnd).unwrap();
         emitter.emit(Event::StreamEnd).unwrap();
@@ -162,28 +114,14 @@ pub(crate) fn main() {
     // Example: Emitting sequence with tag
     let mut buffer = Cursor::new(Vec::new());
     {
-        let mut emitter = Emitter::new(Box::new(&mut buffer));
+        let mut emitter = Emitter::new(&mut buffer);
         emitter.emit(Event::StreamStart).unwrap();
         emitter.emit(Event::DocumentStart).unwrap();
         emitter
-            .emit(Event::SequenceStart(Sequence {
-                tag: Some("!mytag".to_string()),
-            }))
-            .unwrap();
-        emitter
-            .emit(Event::Scalar(Scalar {
-                tag: None,
-                value: "item1",
-                style: ScalarStyle::Plain,
-            }))
-            .unwrap();
-        emitter
-            .emit(Event::Scalar(Scalar {
-                tag: None,
-                value: "item2",
-                style: ScalarStyle::Plain,
-            }))
+            .emit(Event::SequenceStart(Sequence::tagged("!mytag")))
             .unwrap();
+        emitter.emit(Event::Scalar(Scalar::plain("item1"))).unwrap();
+        emitter.emit(Event::Scalar(Scalar::plain("item2"))).unwrap();
         emitter.emit(Event::SequenceEnd).unwrap();
         emitter.emit(Event::DocumentEnd).unwrap();
         emitter.emit(Event::StreamEnd).unwrap();
@@ -195,41 +133,19 @@ pub(crate) fn main() {
     // Example: Emitting mapping with tag
     let mut buffer = Cursor::new(Vec::new());
     {
-        let mut emitter = Emitter::new(Box::new(&mut buffer));
+        let mut emitter = Emitter::new(&mut buffer);
         emitter.emit(Event::StreamStart).unwrap();
         emitter.emit(Event::DocumentStart).unwrap();
         emitter
-            .emit(Event::MappingStart(Mapping {
-                tag: Some("!mytag".to_string()),
-            }))
+            .emit(Event::MappingStart(Mapping::tagged("!mytag")))
             .unwrap();
+        emitter.emit(Event::Scalar(Scalar::plain("key1"))).unwrap();
         emitter
-            .emit(Event::Scalar(Scalar {
-                tag: None,
-                value: "key1",
-                style: ScalarStyle::Plain,
-            }))
+            .emit(Event::Scalar(Scalar::plain("value1")))
             .unwrap();
+        emitter.emit(Event::Scalar(Scalar::plain("key2"))).unwrap();
         emitter
-            .emit(Event::Scalar(Scalar {
-                tag: None,
-                value: "value1",
-                style: ScalarStyle::Plain,
-            }))
-            .unwrap();
-        emitter
-            .emit(Event::Scalar(Scalar {
-                tag: None,
-                value: "key2",
-                style: ScalarStyle::Plain,
-            }))
-            .unwrap();
-        emitter
-            .emit(Event::Scalar(Scalar {
-                tag: None,
-                value: "value2",
-                style: ScalarStyle::Plain,
-            }))
+            .emit(Event::Scalar(Scalar::plain("value2")))
             .unwrap();
         emitter.emit(Event::MappingEnd).unwrap();
         emitter.emit(Event::DocumentEnd).unwrap();
@@ -242,12 +158,10 @@ pub(crate) fn main() {
     // Example: Emitting an empty sequence
     let mut buffer = Cursor::new(Vec::new());
     {
-        let mut emitter = Emitter::new(Box::new(&mut buffer));
+        let mut emitter = Emitter::new(&mut buffer);
         emitter.emit(Event::StreamStart).unwrap();
         emitter.emit(Event::DocumentStart).unwrap();
-        emitter
-            .emit(Event::SequenceStart(Sequence { tag: None }))
-            .unwrap();
+        emitter.emit(Event::SequenceStart(Sequence::new())).unwrap();
         emitter.emit(Event::SequenceEnd).unwrap();
         emitter.emit(Event::DocumentEnd).unwrap();
         emitter.emit(Event::StreamEnd).unwrap();
@@ -259,12 +173,10 @@ pub(crate) fn main() {
     // Example: Emitting an empty mapping
     let mut buffer = Cursor::new(Vec::new());
     {
-        let mut emitter = Emitter::new(Box::new(&mut buffer));
+        let mut emitter = Emitter::new(&mut buffer);
         emitter.emit(Event::StreamStart).unwrap();
         emitter.emit(Event::DocumentStart).unwrap();
-        emitter
-            .emit(Event::MappingStart(Mapping { tag: None }))
-            .unwrap();
+        emitter.emit(Event::MappingStart(Mapping::new())).unwrap();
         emitter.emit(Event::MappingEnd).unwrap();
         emitter.emit(Event::DocumentEnd).unwrap();
         emitter.emit(Event::StreamEnd).unwrap();
@@ -276,21 +188,13 @@ pub(crate) fn main() {
     // Example: Emitting a nested sequence
     let mut buffer = Cursor::new(Vec::new());
     {
-        let mut emitter = Emitter::new(Box::new(&mut buffer));
+        let mut emitter = Emitter::new(&mut buffer);
         emitter.emit(Event::StreamStart).unwrap();
         emitter.emit(Event::DocumentStart).unwrap();
+        emitter.emit(Event::SequenceStart(Sequence::new())).unwrap();
+        emitter.emit(Event::SequenceStart(Sequence::new())).unwrap();
         emitter
-            .emit(Event::SequenceStart(Sequence { tag: None }))
-            .unwrap();
-        emitter
-            .emit(Event::SequenceStart(Sequence { tag: None }))
-            .unwrap();
-        emitter
-            .emit(Event::Scalar(Scalar {
-                tag: None,
-                value: "nested",
-                style: ScalarStyle::Plain,
-            }))
+            .emit(Event::Scalar(Scalar::plain("nested")))
             .unwrap();
         emitter.emit(Event::SequenceEnd).unwrap();
         emitter.emit(Event::SequenceEnd).unwrap();
@@ -304,35 +208,17 @@ pub(crate) fn main() {
     // Example: Emitting a nested mapping
     let mut buffer = Cursor::new(Vec::new());
     {
-        let mut emitter = Emitter::new(Box::new(&mut buffer));
+        let mut emitter = Emitter::new(&mut buffer);
         emitter.emit(Event::StreamStart).unwrap();
         emitter.emit(Event::DocumentStart).unwrap();
+        emitter.emit(Event::MappingStart(Mapping::new())).unwrap();
+        emitter.emit(Event::Scalar(Scalar::plain("key"))).unwrap();
+        emitter.emit(Event::MappingStart(Mapping::new())).unwrap();
         emitter
-            .emit(Event::MappingStart(Mapping { tag: None }))
-            .unwrap();
-        emitter
-            .emit(Event::Scalar(Scalar {
-                tag: None,
-                value: "key",
-                style: ScalarStyle::Plain,
-            }))
-            .unwrap();
-        emitter
-            .emit(Event::MappingStart(Mapping { tag: None }))
-            .unwrap();
-        emitter
-            .emit(Event::Scalar(Scalar {
-                tag: None,
-                value: "nested_key",
-                style: ScalarStyle::Plain,
-            }))
+            .emit(Event::Scalar(Scalar::plain("nested_key")))
             .unwrap();
         emitter
-            .emit(Event::Scalar(Scalar {
-                tag: None,
-                value: "nested_value",
-                style: ScalarStyle::Plain,
-            }))
+            .emit(Event::Scalar(Scalar::plain("nested_value")))
             .unwrap();
         emitter.emit(Event::MappingEnd).unwrap();
         emitter.emit(Event::MappingEnd).unwrap();
@@ -342,4 +228,20 @@ pub(crate) fn main() {
     let output =
         String::from_utf8_lossy(&buffer.into_inner()).to_string();
     println!("\n✅ Emitted nested mapping: {}", output);
+
+    // Example: Building an emitter with a wider indent and canonical output
+    let mut emitter = EmitterBuilder::new()
+        .indent(4)
+        .canonical(true)
+        .build(Vec::new());
+    emitter.emit(Event::StreamStart).unwrap();
+    emitter.emit(Event::DocumentStart).unwrap();
+    emitter.emit(Event::SequenceStart(Sequence::new())).unwrap();
+    emitter.emit(Event::Scalar(Scalar::plain("item"))).unwrap();
+    emitter.emit(Event::SequenceEnd).unwrap();
+    emitter.emit(Event::DocumentEnd).unwrap();
+    emitter.emit(Event::StreamEnd).unwrap();
+    let output =
+        String::from_utf8_lossy(&emitter.into_inner()).to_string();
+    println!("\n✅ Emitted canonical YAML: {}", output);
 }
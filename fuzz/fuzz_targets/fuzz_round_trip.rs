@@ -0,0 +1,19 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use serde_yml::Value;
+
+// Any document that can be read must be read back the same after being
+// written.
+fuzz_target!(|data: &[u8]| {
+    if data.len() > 10240 {
+        return;
+    }
+    let value = match serde_yml::from_slice::<Value>(data) {
+        Ok(value) => value,
+        Err(_) => return,
+    };
+    let yaml = serde_yml::to_string(&value).unwrap();
+    let read: Value = serde_yml::from_str(&yaml).unwrap();
+    assert_eq!(read, value, "{}", yaml);
+});
@@ -0,0 +1,96 @@
+//! Runtime report of the YAML features supported by this build.
+
+/// Describes which YAML features this build of the library supports.
+///
+/// Applications can use the report to gate functionality or to explain why
+/// a document using an unsupported construct was rejected. New fields may be
+/// added as features are introduced.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// The YAML specification version targeted by the parser and emitter.
+    pub yaml_version: &'static str,
+    /// Plain scalars are resolved with the YAML 1.2 core schema, so `yes`,
+    /// `on` and similar YAML 1.1 words stay strings.
+    pub yaml_1_2_core_schema: bool,
+    /// `<<` merge keys can be resolved, through
+    /// [`Value::apply_merge`](crate::Value::apply_merge).
+    pub merge_keys: bool,
+    /// Anchors and aliases in the input are resolved when deserializing.
+    pub anchors_on_parse: bool,
+    /// The serializer emits anchors and aliases for repeated values.
+    pub anchors_on_emit: bool,
+    /// `!!binary` scalars are decoded into byte buffers.
+    pub binary_tag: bool,
+    /// Comments survive a round trip through [`Value`](crate::Value).
+    pub comment_preservation: bool,
+    /// Documents can be composed with the `!include` tag, through the
+    /// `include` module, which needs the `std` feature.
+    pub include_tag: bool,
+    /// Documents can be checked against a JSON Schema, through the
+    /// `validate` module, which needs the `std` feature.
+    pub json_schema_validation: bool,
+    /// Integers beyond the range of `u64` and `i64` are kept exactly in
+    /// [`Number`](crate::Number), under the `bigint` feature.
+    pub big_integers: bool,
+    /// `value::CompactValue` is available, under the `compact` feature.
+    pub compact_value: bool,
+    /// Values convert to and from JSON through the `convert` module, under
+    /// the `serde_json` feature.
+    pub json_conversion: bool,
+    /// Values convert to and from TOML through `convert::toml`, under the
+    /// `toml` feature.
+    pub toml_conversion: bool,
+    /// `uuid::Uuid` fields can be read and written through `with::uuid`,
+    /// under the `uuid` feature.
+    pub uuid: bool,
+    /// `chrono` timestamps can be read and written through
+    /// `with::chrono_datetime`, under the `chrono` feature.
+    pub chrono: bool,
+    /// `time` timestamps can be read and written through
+    /// `with::time_offsetdatetime`, under the `time` feature.
+    pub time: bool,
+    /// Durations like `1h 30m` can be read and written through
+    /// `with::duration_humantime`, under the `humantime` feature.
+    pub humantime: bool,
+    /// [`Error`](crate::Error) implements `miette::Diagnostic`, under the
+    /// `miette` feature.
+    pub miette_diagnostics: bool,
+}
+
+/// Returns the YAML features supported by this build of the library.
+///
+/// The features that depend on cargo features are reported from the cargo
+/// features this build was compiled with.
+///
+/// # Examples
+///
+/// ```
+/// let capabilities = serde_yml::capabilities();
+/// if !capabilities.comment_preservation {
+///     eprintln!("note: comments are not kept when rewriting the file");
+/// }
+/// assert_eq!(capabilities.yaml_version, "1.2");
+/// ```
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        yaml_version: "1.2",
+        yaml_1_2_core_schema: true,
+        merge_keys: true,
+        anchors_on_parse: true,
+        anchors_on_emit: false,
+        binary_tag: false,
+        comment_preservation: false,
+        include_tag: cfg!(feature = "std"),
+        json_schema_validation: cfg!(feature = "std"),
+        big_integers: cfg!(feature = "bigint"),
+        compact_value: cfg!(feature = "compact"),
+        json_conversion: cfg!(feature = "serde_json"),
+        toml_conversion: cfg!(feature = "toml"),
+        uuid: cfg!(feature = "uuid"),
+        chrono: cfg!(feature = "chrono"),
+        time: cfg!(feature = "time"),
+        humantime: cfg!(feature = "humantime"),
+        miette_diagnostics: cfg!(feature = "miette"),
+    }
+}
@@ -0,0 +1,91 @@
+//! Compatibility with the APIs of other YAML crates.
+//!
+//! Each submodule is named after a crate and re-exports the items of this
+//! crate under the paths that crate uses, so that code written against it
+//! can be migrated by changing a single import.
+
+/// The public API of `serde_yaml` 0.9.
+///
+/// Every item keeps its `serde_yaml` name and path and behaves the same way:
+/// documents use the same representation of enums, tags, numbers and merge
+/// keys, and the same input is accepted or rejected. Items that this crate
+/// adds on top of that API, such as [`DeserializerOptions`] or the
+/// additional [`with`](crate::with) modules, are deliberately left out, so
+/// that code compiling against this module also compiles against
+/// `serde_yaml`.
+///
+/// Replacing
+///
+/// ```ignore
+/// use serde_yaml::{Mapping, Value};
+/// ```
+///
+/// with
+///
+/// ```
+/// use serde_yml::compat::serde_yaml::{self, Mapping, Value};
+/// ```
+///
+/// keeps the rest of a module working unchanged.
+///
+/// [`DeserializerOptions`]: crate::de::DeserializerOptions
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use serde_yml::compat::serde_yaml;
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// enum Protocol {
+///     Tcp { port: u16 },
+/// }
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Service {
+///     #[serde(with = "serde_yaml::with::singleton_map_recursive")]
+///     protocol: Protocol,
+/// }
+///
+/// let service: Service = serde_yaml::from_str("protocol:\n  Tcp:\n    port: 80\n").unwrap();
+/// assert_eq!(service.protocol, Protocol::Tcp { port: 80 });
+///
+/// let mut value: serde_yaml::Value =
+///     serde_yaml::from_str("base: &base {a: 1}\nderived: {<<: *base, b: 2}\n").unwrap();
+/// value.apply_merge().unwrap();
+/// assert_eq!(value["derived"]["a"], 1);
+/// ```
+pub mod serde_yaml {
+    #[cfg(feature = "std")]
+    pub use crate::de::from_reader;
+    pub use crate::de::{from_slice, from_str, Deserializer};
+    pub use crate::mapping::Mapping;
+    pub use crate::modules::error::{Error, Location, Result};
+    pub use crate::ser::{to_string, to_writer, Serializer};
+    pub use crate::value::{
+        from_value, to_value, Index, Number, Sequence, Value,
+    };
+
+    /// A YAML mapping in which the keys and values are both
+    /// `serde_yaml::Value`.
+    pub mod mapping {
+        pub use crate::mapping::{
+            Entry, IntoIter, IntoKeys, IntoValues, Iter, IterMut, Keys,
+            Mapping, OccupiedEntry, VacantEntry, Values, ValuesMut,
+        };
+    }
+
+    /// The `Value` enum, a loosely typed way of representing any valid
+    /// YAML value.
+    pub mod value {
+        pub use crate::value::{
+            from_value, to_value, Index, Mapping, Number, Sequence,
+            Serializer, Tag, TaggedValue, Value,
+        };
+    }
+
+    /// Customizations to use with Serde's `#[serde(with = …)]` attribute.
+    pub mod with {
+        pub use crate::with::{singleton_map, singleton_map_recursive};
+    }
+}
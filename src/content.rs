@@ -0,0 +1,672 @@
+//! A copy of what a `Serialize` implementation writes, in terms of the serde
+//! data model, to be written again later.
+//!
+//! [`with::singleton_map`](crate::with::singleton_map) holds the fields of
+//! a tuple or struct variant this way until it writes them all as the
+//! value of the singleton map. Unlike a [`Value`](crate::Value), the
+//! copy keeps bytes, integer widths and type names as they were written,
+//! and records them with the human-readability of the serializer they end
+//! up in.
+
+use crate::lib::*;
+use core::marker::PhantomData;
+use serde::ser::{self, Serialize, Serializer};
+
+/// One call of the serde data model, with its arguments.
+#[derive(Clone, Debug)]
+pub(crate) enum Content {
+    Bool(bool),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    F32(f32),
+    F64(f64),
+    Char(char),
+    String(String),
+    Bytes(Vec<u8>),
+    None,
+    Some(Box<Content>),
+    Unit,
+    UnitStruct(&'static str),
+    UnitVariant(&'static str, u32, &'static str),
+    NewtypeStruct(&'static str, Box<Content>),
+    NewtypeVariant(&'static str, u32, &'static str, Box<Content>),
+    Seq(Vec<Content>),
+    Tuple(Vec<Content>),
+    TupleStruct(&'static str, Vec<Content>),
+    TupleVariant(&'static str, u32, &'static str, Vec<Content>),
+    Map(Vec<(Content, Content)>),
+    Struct(&'static str, Vec<(&'static str, Content)>),
+    StructVariant(
+        &'static str,
+        u32,
+        &'static str,
+        Vec<(&'static str, Content)>,
+    ),
+}
+
+impl Serialize for Content {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use serde::ser::{
+            SerializeMap, SerializeSeq, SerializeStruct,
+            SerializeStructVariant, SerializeTuple,
+            SerializeTupleStruct, SerializeTupleVariant,
+        };
+
+        match self {
+            Content::Bool(v) => serializer.serialize_bool(*v),
+            Content::I8(v) => serializer.serialize_i8(*v),
+            Content::I16(v) => serializer.serialize_i16(*v),
+            Content::I32(v) => serializer.serialize_i32(*v),
+            Content::I64(v) => serializer.serialize_i64(*v),
+            Content::I128(v) => serializer.serialize_i128(*v),
+            Content::U8(v) => serializer.serialize_u8(*v),
+            Content::U16(v) => serializer.serialize_u16(*v),
+            Content::U32(v) => serializer.serialize_u32(*v),
+            Content::U64(v) => serializer.serialize_u64(*v),
+            Content::U128(v) => serializer.serialize_u128(*v),
+            Content::F32(v) => serializer.serialize_f32(*v),
+            Content::F64(v) => serializer.serialize_f64(*v),
+            Content::Char(v) => serializer.serialize_char(*v),
+            Content::String(v) => serializer.serialize_str(v),
+            Content::Bytes(v) => serializer.serialize_bytes(v),
+            Content::None => serializer.serialize_none(),
+            Content::Some(v) => serializer.serialize_some(&**v),
+            Content::Unit => serializer.serialize_unit(),
+            Content::UnitStruct(name) => {
+                serializer.serialize_unit_struct(name)
+            }
+            Content::UnitVariant(name, index, variant) => {
+                serializer.serialize_unit_variant(name, *index, variant)
+            }
+            Content::NewtypeStruct(name, v) => {
+                serializer.serialize_newtype_struct(name, &**v)
+            }
+            Content::NewtypeVariant(name, index, variant, v) => {
+                serializer.serialize_newtype_variant(
+                    name, *index, variant, &**v,
+                )
+            }
+            Content::Seq(elements) => {
+                let mut seq =
+                    serializer.serialize_seq(Some(elements.len()))?;
+                for element in elements {
+                    seq.serialize_element(element)?;
+                }
+                seq.end()
+            }
+            Content::Tuple(elements) => {
+                let mut tuple =
+                    serializer.serialize_tuple(elements.len())?;
+                for element in elements {
+                    tuple.serialize_element(element)?;
+                }
+                tuple.end()
+            }
+            Content::TupleStruct(name, fields) => {
+                let mut tuple = serializer
+                    .serialize_tuple_struct(name, fields.len())?;
+                for field in fields {
+                    tuple.serialize_field(field)?;
+                }
+                tuple.end()
+            }
+            Content::TupleVariant(name, index, variant, fields) => {
+                let mut tuple = serializer.serialize_tuple_variant(
+                    name,
+                    *index,
+                    variant,
+                    fields.len(),
+                )?;
+                for field in fields {
+                    tuple.serialize_field(field)?;
+                }
+                tuple.end()
+            }
+            Content::Map(entries) => {
+                let mut map =
+                    serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+            Content::Struct(name, fields) => {
+                let mut object =
+                    serializer.serialize_struct(name, fields.len())?;
+                for (key, value) in fields {
+                    object.serialize_field(key, value)?;
+                }
+                object.end()
+            }
+            Content::StructVariant(name, index, variant, fields) => {
+                let mut object = serializer.serialize_struct_variant(
+                    name,
+                    *index,
+                    variant,
+                    fields.len(),
+                )?;
+                for (key, value) in fields {
+                    object.serialize_field(key, value)?;
+                }
+                object.end()
+            }
+        }
+    }
+}
+
+/// Serializer that copies what it is given into a [`Content`], failing
+/// with the error type `E` of the serializer the copy is meant for.
+#[derive(Debug)]
+pub(crate) struct ContentSerializer<E> {
+    human_readable: bool,
+    error: PhantomData<E>,
+}
+
+impl<E> ContentSerializer<E> {
+    /// Creates a serializer telling `Serialize` implementations that
+    /// the format is human-readable as `human_readable` says.
+    pub(crate) fn new(human_readable: bool) -> Self {
+        ContentSerializer {
+            human_readable,
+            error: PhantomData,
+        }
+    }
+
+    fn nested(&self) -> Self {
+        ContentSerializer::new(self.human_readable)
+    }
+
+    fn content<T>(&self, value: &T) -> Result<Content, E>
+    where
+        T: ?Sized + Serialize,
+        E: ser::Error,
+    {
+        value.serialize(self.nested())
+    }
+}
+
+/// What the elements collected by [`SerializeElements`] belong to.
+#[derive(Debug)]
+enum Elements {
+    Seq,
+    Tuple,
+    TupleStruct(&'static str),
+    TupleVariant(&'static str, u32, &'static str),
+}
+
+/// Collects the elements of a sequence, tuple or tuple struct or variant.
+#[derive(Debug)]
+pub(crate) struct SerializeElements<E> {
+    serializer: ContentSerializer<E>,
+    kind: Elements,
+    elements: Vec<Content>,
+}
+
+/// What the fields collected by [`SerializeFields`] belong to.
+#[derive(Debug)]
+enum Fields {
+    Struct(&'static str),
+    StructVariant(&'static str, u32, &'static str),
+}
+
+/// Collects the fields of a struct or struct variant.
+#[derive(Debug)]
+pub(crate) struct SerializeFields<E> {
+    serializer: ContentSerializer<E>,
+    kind: Fields,
+    fields: Vec<(&'static str, Content)>,
+}
+
+/// Collects the entries of a map.
+#[derive(Debug)]
+pub(crate) struct SerializeEntries<E> {
+    serializer: ContentSerializer<E>,
+    entries: Vec<(Content, Content)>,
+    key: Option<Content>,
+}
+
+impl<E> ContentSerializer<E> {
+    fn elements(
+        self,
+        kind: Elements,
+        len: usize,
+    ) -> SerializeElements<E> {
+        SerializeElements {
+            serializer: self,
+            kind,
+            elements: Vec::with_capacity(len),
+        }
+    }
+
+    fn fields(self, kind: Fields, len: usize) -> SerializeFields<E> {
+        SerializeFields {
+            serializer: self,
+            kind,
+            fields: Vec::with_capacity(len),
+        }
+    }
+}
+
+impl<E> Serializer for ContentSerializer<E>
+where
+    E: ser::Error,
+{
+    type Ok = Content;
+    type Error = E;
+
+    type SerializeSeq = SerializeElements<E>;
+    type SerializeTuple = SerializeElements<E>;
+    type SerializeTupleStruct = SerializeElements<E>;
+    type SerializeTupleVariant = SerializeElements<E>;
+    type SerializeMap = SerializeEntries<E>;
+    type SerializeStruct = SerializeFields<E>;
+    type SerializeStructVariant = SerializeFields<E>;
+
+    fn serialize_bool(self, v: bool) -> Result<Content, E> {
+        Ok(Content::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Content, E> {
+        Ok(Content::I8(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Content, E> {
+        Ok(Content::I16(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Content, E> {
+        Ok(Content::I32(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Content, E> {
+        Ok(Content::I64(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Content, E> {
+        Ok(Content::I128(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Content, E> {
+        Ok(Content::U8(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Content, E> {
+        Ok(Content::U16(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Content, E> {
+        Ok(Content::U32(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Content, E> {
+        Ok(Content::U64(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Content, E> {
+        Ok(Content::U128(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Content, E> {
+        Ok(Content::F32(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Content, E> {
+        Ok(Content::F64(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Content, E> {
+        Ok(Content::Char(v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Content, E> {
+        Ok(Content::String(v.to_owned()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Content, E> {
+        Ok(Content::Bytes(v.to_owned()))
+    }
+
+    fn serialize_none(self) -> Result<Content, E> {
+        Ok(Content::None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Content, E>
+    where
+        T: ?Sized + Serialize,
+    {
+        Ok(Content::Some(Box::new(self.content(value)?)))
+    }
+
+    fn serialize_unit(self) -> Result<Content, E> {
+        Ok(Content::Unit)
+    }
+
+    fn serialize_unit_struct(
+        self,
+        name: &'static str,
+    ) -> Result<Content, E> {
+        Ok(Content::UnitStruct(name))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Content, E> {
+        Ok(Content::UnitVariant(name, variant_index, variant))
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Content, E>
+    where
+        T: ?Sized + Serialize,
+    {
+        Ok(Content::NewtypeStruct(name, Box::new(self.content(value)?)))
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Content, E>
+    where
+        T: ?Sized + Serialize,
+    {
+        Ok(Content::NewtypeVariant(
+            name,
+            variant_index,
+            variant,
+            Box::new(self.content(value)?),
+        ))
+    }
+
+    fn serialize_seq(
+        self,
+        len: Option<usize>,
+    ) -> Result<Self::SerializeSeq, E> {
+        Ok(self.elements(Elements::Seq, len.unwrap_or(0)))
+    }
+
+    fn serialize_tuple(
+        self,
+        len: usize,
+    ) -> Result<Self::SerializeTuple, E> {
+        Ok(self.elements(Elements::Tuple, len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, E> {
+        Ok(self.elements(Elements::TupleStruct(name), len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, E> {
+        let kind = Elements::TupleVariant(name, variant_index, variant);
+        Ok(self.elements(kind, len))
+    }
+
+    fn serialize_map(
+        self,
+        len: Option<usize>,
+    ) -> Result<Self::SerializeMap, E> {
+        Ok(SerializeEntries {
+            serializer: self,
+            entries: Vec::with_capacity(len.unwrap_or(0)),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, E> {
+        Ok(self.fields(Fields::Struct(name), len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, E> {
+        let kind = Fields::StructVariant(name, variant_index, variant);
+        Ok(self.fields(kind, len))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.human_readable
+    }
+}
+
+impl<E> SerializeElements<E>
+where
+    E: ser::Error,
+{
+    fn push<T>(&mut self, value: &T) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        let content = self.serializer.content(value)?;
+        self.elements.push(content);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Content, E> {
+        let elements = self.elements;
+        Ok(match self.kind {
+            Elements::Seq => Content::Seq(elements),
+            Elements::Tuple => Content::Tuple(elements),
+            Elements::TupleStruct(name) => {
+                Content::TupleStruct(name, elements)
+            }
+            Elements::TupleVariant(name, index, variant) => {
+                Content::TupleVariant(name, index, variant, elements)
+            }
+        })
+    }
+}
+
+impl<E> ser::SerializeSeq for SerializeElements<E>
+where
+    E: ser::Error,
+{
+    type Ok = Content;
+    type Error = E;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Content, E> {
+        self.finish()
+    }
+}
+
+impl<E> ser::SerializeTuple for SerializeElements<E>
+where
+    E: ser::Error,
+{
+    type Ok = Content;
+    type Error = E;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Content, E> {
+        self.finish()
+    }
+}
+
+impl<E> ser::SerializeTupleStruct for SerializeElements<E>
+where
+    E: ser::Error,
+{
+    type Ok = Content;
+    type Error = E;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Content, E> {
+        self.finish()
+    }
+}
+
+impl<E> ser::SerializeTupleVariant for SerializeElements<E>
+where
+    E: ser::Error,
+{
+    type Ok = Content;
+    type Error = E;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Content, E> {
+        self.finish()
+    }
+}
+
+impl<E> ser::SerializeMap for SerializeEntries<E>
+where
+    E: ser::Error,
+{
+    type Ok = Content;
+    type Error = E;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.key = Some(self.serializer.content(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        let key = self.key.take().ok_or_else(|| {
+            E::custom("serialize_value called before serialize_key")
+        })?;
+        let value = self.serializer.content(value)?;
+        self.entries.push((key, value));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Content, E> {
+        Ok(Content::Map(self.entries))
+    }
+}
+
+impl<E> SerializeFields<E>
+where
+    E: ser::Error,
+{
+    fn push<T>(&mut self, key: &'static str, value: &T) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        let content = self.serializer.content(value)?;
+        self.fields.push((key, content));
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Content, E> {
+        let fields = self.fields;
+        Ok(match self.kind {
+            Fields::Struct(name) => Content::Struct(name, fields),
+            Fields::StructVariant(name, index, variant) => {
+                Content::StructVariant(name, index, variant, fields)
+            }
+        })
+    }
+}
+
+impl<E> ser::SerializeStruct for SerializeFields<E>
+where
+    E: ser::Error,
+{
+    type Ok = Content;
+    type Error = E;
+
+    fn serialize_field<T>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Content, E> {
+        self.finish()
+    }
+}
+
+impl<E> ser::SerializeStructVariant for SerializeFields<E>
+where
+    E: ser::Error,
+{
+    type Ok = Content;
+    type Error = E;
+
+    fn serialize_field<T>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Content, E> {
+        self.finish()
+    }
+}
@@ -0,0 +1,382 @@
+//! Streaming conversion between YAML and other data formats.
+//!
+//! [`transcode`] feeds the output of any Serde deserializer straight into any
+//! Serde serializer, one scalar at a time, without building a [`Value`] or
+//! any other intermediate tree. The [`Deserializer`](crate::Deserializer) and
+//! [`Serializer`](crate::Serializer) of this crate work with it, and with the `serde-transcode` crate, in either
+//! role.
+//!
+//! [`yaml_to_json`] and [`json_to_yaml`] are ready-made converters built on
+//! it. They are available with the `serde_json` feature, which is enabled by
+//! default.
+//!
+//! The [`toml`](self::toml) module, available with the `toml` feature,
+//! converts between [`Value`] and `toml::Value`.
+//!
+//! A tagged YAML node such as `!Point [1, 2]` becomes a map with a single
+//! entry, `{"!Point": [1, 2]}`, as it does when a [`Value`] is serialized.
+//! Aliases are expanded, and merge keys (`<<`) are passed through as ordinary
+//! keys.
+//!
+//! [`Value`]: crate::Value
+
+/// The `toml` module contains conversions between YAML and TOML values.
+#[cfg(feature = "toml")]
+pub mod toml;
+
+use crate::lib::*;
+#[cfg(feature = "serde_json")]
+use crate::{de::Deserializer, modules::error::Error, ser::Serializer};
+use core::{cell::RefCell, fmt};
+use serde::{
+    de::{self, DeserializeSeed, EnumAccess, MapAccess, SeqAccess},
+    ser::{self, Serialize, SerializeMap, SerializeSeq},
+};
+
+/// Serializes everything produced by `deserializer` with `serializer`.
+///
+/// Values are forwarded as they are read, so the whole input is never held
+/// in memory in another form. Errors of the deserializer are reported
+/// through the error type of the serializer.
+///
+/// # Errors
+///
+/// Returns an error if the deserializer fails to read its input, or if the
+/// serializer does not support the data, such as a JSON serializer given a
+/// map with sequences as keys.
+///
+/// # Examples
+///
+/// ```
+/// let yaml = "name: demo\nports: [80, 443]\n";
+///
+/// let mut json = Vec::new();
+/// serde_yml::convert::transcode(
+///     serde_yml::Deserializer::from_str(yaml),
+///     &mut serde_json::Serializer::new(&mut json),
+/// )
+/// .unwrap();
+/// assert_eq!(json, br#"{"name":"demo","ports":[80,443]}"#);
+/// ```
+pub fn transcode<'de, D, S>(
+    deserializer: D,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    D: de::Deserializer<'de>,
+    S: ser::Serializer,
+{
+    Transcoder::new(deserializer).serialize(serializer)
+}
+
+/// Converts a YAML document into compact JSON text.
+///
+/// # Errors
+///
+/// Returns an error if `yaml` is not a single valid YAML document, or if it
+/// holds data that JSON cannot represent, such as a mapping key that is a
+/// sequence.
+///
+/// # Examples
+///
+/// ```
+/// let json = serde_yml::convert::yaml_to_json("a: [1, true, ~]\n").unwrap();
+/// assert_eq!(json, r#"{"a":[1,true,null]}"#);
+/// ```
+#[cfg(feature = "serde_json")]
+pub fn yaml_to_json(yaml: &str) -> Result<String, Error> {
+    let mut json = Vec::with_capacity(yaml.len());
+    transcode(
+        Deserializer::from_str(yaml),
+        &mut serde_json::Serializer::new(&mut json),
+    )
+    .map_err(<Error as ser::Error>::custom)?;
+    // The JSON serializer writes only valid UTF-8.
+    Ok(String::from_utf8(json).unwrap_or_default())
+}
+
+/// Converts JSON text into a YAML document, formatted as by
+/// [`to_string`](crate::to_string).
+///
+/// # Errors
+///
+/// Returns an error if `json` is not valid JSON.
+///
+/// # Examples
+///
+/// ```
+/// let yaml = serde_yml::convert::json_to_yaml(r#"{"a": [1, "x"]}"#).unwrap();
+/// assert_eq!(yaml, "a:\n- 1\n- x\n");
+/// ```
+#[cfg(feature = "serde_json")]
+pub fn json_to_yaml(json: &str) -> Result<String, Error> {
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let mut yaml = Vec::with_capacity(json.len());
+    transcode(&mut deserializer, &mut Serializer::new(&mut yaml))?;
+    deserializer.end().map_err(<Error as ser::Error>::custom)?;
+    // The YAML serializer writes only valid UTF-8.
+    Ok(String::from_utf8(yaml).unwrap_or_default())
+}
+
+/// Adapts a deserializer into a value that serializes to whatever the
+/// deserializer produces.
+struct Transcoder<D>(RefCell<Option<D>>);
+
+impl<D> Transcoder<D> {
+    fn new(deserializer: D) -> Self {
+        Transcoder(RefCell::new(Some(deserializer)))
+    }
+}
+
+impl<'de, D> Serialize for Transcoder<D>
+where
+    D: de::Deserializer<'de>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        match self.0.borrow_mut().take() {
+            Some(deserializer) => deserializer
+                .deserialize_any(Visitor(serializer))
+                .map_err(ser::Error::custom),
+            None => Err(ser::Error::custom(
+                "a transcoder can only be serialized once",
+            )),
+        }
+    }
+}
+
+/// Serializes each value visited.
+struct Visitor<S>(S);
+
+impl<'de, S> de::Visitor<'de> for Visitor<S>
+where
+    S: ser::Serializer,
+{
+    type Value = S::Ok;
+
+    fn expecting(
+        &self,
+        formatter: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        formatter.write_str("any value to transcode")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<S::Ok, E>
+    where
+        E: de::Error,
+    {
+        self.0.serialize_bool(v).map_err(de::Error::custom)
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<S::Ok, E>
+    where
+        E: de::Error,
+    {
+        self.0.serialize_i64(v).map_err(de::Error::custom)
+    }
+
+    fn visit_i128<E>(self, v: i128) -> Result<S::Ok, E>
+    where
+        E: de::Error,
+    {
+        self.0.serialize_i128(v).map_err(de::Error::custom)
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<S::Ok, E>
+    where
+        E: de::Error,
+    {
+        self.0.serialize_u64(v).map_err(de::Error::custom)
+    }
+
+    fn visit_u128<E>(self, v: u128) -> Result<S::Ok, E>
+    where
+        E: de::Error,
+    {
+        self.0.serialize_u128(v).map_err(de::Error::custom)
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<S::Ok, E>
+    where
+        E: de::Error,
+    {
+        self.0.serialize_f64(v).map_err(de::Error::custom)
+    }
+
+    fn visit_char<E>(self, v: char) -> Result<S::Ok, E>
+    where
+        E: de::Error,
+    {
+        self.0.serialize_char(v).map_err(de::Error::custom)
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<S::Ok, E>
+    where
+        E: de::Error,
+    {
+        self.0.serialize_str(v).map_err(de::Error::custom)
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<S::Ok, E>
+    where
+        E: de::Error,
+    {
+        self.0.serialize_bytes(v).map_err(de::Error::custom)
+    }
+
+    fn visit_none<E>(self) -> Result<S::Ok, E>
+    where
+        E: de::Error,
+    {
+        self.0.serialize_none().map_err(de::Error::custom)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<S::Ok, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.0
+            .serialize_some(&Transcoder::new(deserializer))
+            .map_err(de::Error::custom)
+    }
+
+    fn visit_unit<E>(self) -> Result<S::Ok, E>
+    where
+        E: de::Error,
+    {
+        self.0.serialize_unit().map_err(de::Error::custom)
+    }
+
+    fn visit_newtype_struct<D>(
+        self,
+        deserializer: D,
+    ) -> Result<S::Ok, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        // The deserializers of this crate pass integers outside the range
+        // of `i128` and `u128` as a newtype holding their digits.
+        #[cfg(feature = "bigint")]
+        {
+            use crate::{de::parse_big_int, Number, Value};
+            use serde::Deserialize;
+
+            let value = Value::deserialize(deserializer)?;
+            match value {
+                Value::String(digits) => match parse_big_int(&digits) {
+                    Some(int) => Number::from(int).serialize(self.0),
+                    None => self.0.serialize_str(&digits),
+                },
+                value => value.serialize(self.0),
+            }
+            .map_err(de::Error::custom)
+        }
+        #[cfg(not(feature = "bigint"))]
+        Transcoder::new(deserializer)
+            .serialize(self.0)
+            .map_err(de::Error::custom)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<S::Ok, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut out = self
+            .0
+            .serialize_seq(seq.size_hint())
+            .map_err(de::Error::custom)?;
+        while seq.next_element_seed(ElementSeed(&mut out))?.is_some() {}
+        out.end().map_err(de::Error::custom)
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<S::Ok, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut out = self
+            .0
+            .serialize_map(map.size_hint())
+            .map_err(de::Error::custom)?;
+        while map.next_key_seed(KeySeed(&mut out))?.is_some() {
+            map.next_value_seed(ValueSeed(&mut out))?;
+        }
+        out.end().map_err(de::Error::custom)
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<S::Ok, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        // Only YAML tags reach this point: `!Tag value` is written as the
+        // map `{"!Tag": value}`.
+        let (tag, variant): (String, _) = data.variant()?;
+        let mut out =
+            self.0.serialize_map(Some(1)).map_err(de::Error::custom)?;
+        out.serialize_key(&format!("!{}", tag))
+            .map_err(de::Error::custom)?;
+        de::VariantAccess::newtype_variant_seed(
+            variant,
+            ValueSeed(&mut out),
+        )?;
+        out.end().map_err(de::Error::custom)
+    }
+}
+
+/// Serializes the next element of a sequence.
+struct ElementSeed<'a, S>(&'a mut S);
+
+impl<'de, S> DeserializeSeed<'de> for ElementSeed<'_, S>
+where
+    S: SerializeSeq,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.0
+            .serialize_element(&Transcoder::new(deserializer))
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Serializes the next key of a map.
+struct KeySeed<'a, S>(&'a mut S);
+
+impl<'de, S> DeserializeSeed<'de> for KeySeed<'_, S>
+where
+    S: SerializeMap,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.0
+            .serialize_key(&Transcoder::new(deserializer))
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Serializes the next value of a map.
+struct ValueSeed<'a, S>(&'a mut S);
+
+impl<'de, S> DeserializeSeed<'de> for ValueSeed<'_, S>
+where
+    S: SerializeMap,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.0
+            .serialize_value(&Transcoder::new(deserializer))
+            .map_err(de::Error::custom)
+    }
+}
@@ -0,0 +1,187 @@
+//! Conversions between [`Value`] and `toml::Value`.
+//!
+//! The two data models mostly overlap, and the differences are handled
+//! explicitly rather than guessed at:
+//!
+//! - TOML dates and times become strings tagged [`DATETIME_TAG`], such as
+//!   `!datetime '1979-05-27T07:32:00Z'`, and only strings with that tag
+//!   become TOML datetimes again. Untagged strings stay strings even when
+//!   they look like dates.
+//! - TOML integers are 64-bit signed, so YAML integers above `i64::MAX` are
+//!   rejected rather than converted to floats.
+//! - YAML values without a TOML equivalent, namely null, mapping keys that
+//!   are not strings and tags other than [`DATETIME_TAG`], are rejected.
+//!
+//! Errors name the path of the offending node, such as `servers[1].port`.
+
+use crate::{
+    mapping::Mapping,
+    modules::error::Error,
+    path::Path,
+    value::{Tag, TaggedValue, Value},
+};
+use ::toml::value::{Datetime, Table, Value as TomlValue};
+use core::fmt::Display;
+use serde::de::Error as _;
+
+/// The tag marking a string in a [`Value`] that holds a TOML date or time.
+pub const DATETIME_TAG: &str = "!datetime";
+
+/// Converts a TOML value into a [`Value`].
+///
+/// The conversion cannot fail; datetimes are converted as described in the
+/// [module documentation](self).
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::convert::toml::from_toml_value;
+///
+/// let toml: toml::Value = toml::from_str("created = 1979-05-27\nport = 80\n").unwrap();
+/// let yaml = serde_yml::to_string(&from_toml_value(toml)).unwrap();
+/// assert_eq!(yaml, "created: !datetime '1979-05-27'\nport: 80\n");
+/// ```
+pub fn from_toml_value(value: TomlValue) -> Value {
+    match value {
+        TomlValue::String(string) => Value::String(string),
+        TomlValue::Integer(integer) => Value::Number(integer.into()),
+        TomlValue::Float(float) => Value::Number(float.into()),
+        TomlValue::Boolean(boolean) => Value::Bool(boolean),
+        TomlValue::Datetime(datetime) => {
+            Value::Tagged(Box::new(TaggedValue {
+                tag: Tag::new(DATETIME_TAG),
+                value: Value::String(datetime.to_string()),
+            }))
+        }
+        TomlValue::Array(array) => Value::Sequence(
+            array.into_iter().map(from_toml_value).collect(),
+        ),
+        TomlValue::Table(table) => Value::Mapping(
+            table
+                .into_iter()
+                .map(|(key, value)| {
+                    (Value::String(key), from_toml_value(value))
+                })
+                .collect::<Mapping>(),
+        ),
+    }
+}
+
+/// Converts a [`Value`] into a TOML value.
+///
+/// # Errors
+///
+/// Returns an error naming the path of the first node that has no TOML
+/// equivalent, as described in the [module documentation](self).
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::convert::toml::to_toml_value;
+///
+/// let value: serde_yml::Value =
+///     serde_yml::from_str("created: !datetime 1979-05-27\nports: [80]\n").unwrap();
+/// let toml = toml::to_string(&to_toml_value(&value).unwrap()).unwrap();
+/// assert_eq!(toml, "created = 1979-05-27\nports = [80]\n");
+///
+/// let value: serde_yml::Value = serde_yml::from_str("limits: {max: ~}").unwrap();
+/// let error = to_toml_value(&value).unwrap_err();
+/// assert_eq!(error.to_string(), "limits.max: null has no TOML equivalent");
+/// ```
+pub fn to_toml_value(value: &Value) -> Result<TomlValue, Error> {
+    convert(value, &mut Path::root())
+}
+
+fn convert(value: &Value, path: &mut Path) -> Result<TomlValue, Error> {
+    Ok(match value {
+        Value::Null => {
+            return Err(error(path, "null has no TOML equivalent"))
+        }
+        Value::Bool(boolean) => TomlValue::Boolean(*boolean),
+        Value::Number(number) => match number.as_i64() {
+            Some(integer) => TomlValue::Integer(integer),
+            None if number.is_f64() => {
+                TomlValue::Float(number.as_f64().unwrap_or_default())
+            }
+            None => {
+                return Err(error(
+                    path,
+                    format_args!(
+                        "integer {} is out of range for TOML",
+                        number
+                    ),
+                ))
+            }
+        },
+        Value::String(string) => TomlValue::String(string.clone()),
+        Value::Sequence(sequence) => {
+            let mut array = Vec::with_capacity(sequence.len());
+            for (index, element) in sequence.iter().enumerate() {
+                path.push(index);
+                array.push(convert(element, path)?);
+                let _ = path.pop();
+            }
+            TomlValue::Array(array)
+        }
+        Value::Mapping(mapping) => {
+            let mut table = Table::new();
+            for (key, value) in mapping {
+                let key = match key {
+                    Value::String(key) => key,
+                    _ => {
+                        return Err(error(
+                            path,
+                            "mapping key that is not a string has no TOML equivalent",
+                        ))
+                    }
+                };
+                path.push(key.as_str());
+                let value = convert(value, path)?;
+                let _ = path.pop();
+                let _ = table.insert(key.clone(), value);
+            }
+            TomlValue::Table(table)
+        }
+        Value::Tagged(tagged) if tagged.tag == DATETIME_TAG => {
+            match &tagged.value {
+                Value::String(string) => TomlValue::Datetime(
+                    string.parse::<Datetime>().map_err(|err| {
+                        error(
+                            path,
+                            format_args!(
+                                "invalid datetime `{}`: {}",
+                                string, err
+                            ),
+                        )
+                    })?,
+                ),
+                _ => {
+                    return Err(error(
+                        path,
+                        format_args!(
+                            "{} must tag a string",
+                            DATETIME_TAG
+                        ),
+                    ))
+                }
+            }
+        }
+        Value::Tagged(tagged) => {
+            return Err(error(
+                path,
+                format_args!(
+                    "tag {} has no TOML equivalent",
+                    tagged.tag
+                ),
+            ))
+        }
+    })
+}
+
+fn error(path: &Path, message: impl Display) -> Error {
+    if path.is_root() {
+        Error::custom(message)
+    } else {
+        Error::custom(format_args!("{}: {}", path, message))
+    }
+}
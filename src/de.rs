@@ -1,23 +1,40 @@
 use crate::{
+    lib::*,
     libyml::{
         error::Mark,
-        parser::{MappingStart, Scalar, ScalarStyle, SequenceStart},
+        parser::{
+            Event as YamlEvent, MappingStart, Parser, Scalar,
+            ScalarStyle, SequenceStart,
+        },
         tag::Tag,
     },
-    loader::{Document, Loader},
-    modules::error::{self, Error, ErrorImpl},
+    loader::{
+        line_break_end, Diagnostic, Document, LineIndex, Loader,
+        Severity,
+    },
+    modules::error::{self, Error, ErrorImpl, Location},
     modules::path::Path,
+    path::{Path as OwnedPath, Segment},
+    untagged::{Failures, UNTAGGED},
+    value::{BorrowedValue, StyleMap, RAW_YAML},
+};
+use alloc::{borrow::Cow, collections::BTreeMap, rc::Rc, sync::Arc};
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use core::fmt::Result as FmtResult;
+use core::{
+    cell::RefCell, fmt, mem, num::ParseIntError, ops::Range, str,
 };
 use serde::de::{
     self, value::StrDeserializer, Deserialize, DeserializeOwned,
     DeserializeSeed, Expected, IgnoredAny, Unexpected, Visitor,
 };
-use std::fmt::Debug;
-use std::fmt::Formatter;
-use std::fmt::Result as FmtResult;
-use std::{fmt, io, mem, num::ParseIntError, str, sync::Arc};
+#[cfg(feature = "std")]
+use std::io;
 
-type Result<T, E = Error> = std::result::Result<T, E>;
+pub use crate::loader::{DocumentAnchor, DocumentMetadata};
+
+type Result<T, E = Error> = core::result::Result<T, E>;
 
 /// A structure that deserializes YAML into Rust values.
 ///
@@ -57,9 +74,337 @@ type Result<T, E = Error> = std::result::Result<T, E>;
 ///     Ok(())
 /// }
 /// ```
+///
+/// Mapping keys may be sequences or mappings written as complex keys, such
+/// as `? [web, 80]`, which deserialize into maps keyed by tuples, structs
+/// or enums:
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// let input = "? [web, 80]\n: http\n? [web, 443]\n: https\n";
+/// let ports: HashMap<(String, u16), String> = serde_yml::from_str(input).unwrap();
+/// assert_eq!(ports[&("web".to_owned(), 443)], "https");
+/// ```
+///
+/// Structs with a `#[serde(flatten)]` field only accept scalar keys, as
+/// the field names generated by `serde_derive` cannot hold a sequence or
+/// a mapping.
+///
+/// Tagged enum values such as `!Variant 1` work inside flattened structs
+/// and untagged or internally tagged enums. Serde buffers those inputs in
+/// a form that has no room for a YAML tag, so a [`Value`](crate::Value)
+/// captured through such a buffer, for example by a flattened
+/// [`Mapping`](crate::Mapping), holds `{Variant: 1}` rather than a
+/// [`TaggedValue`](crate::value::TaggedValue).
 #[derive(Debug)]
 pub struct Deserializer<'de> {
     progress: Progress<'de>,
+    options: DeserializerOptions,
+    warnings: Option<Rc<RefCell<Vec<Warning>>>>,
+}
+
+/// Options that tune how a [`Deserializer`] interprets its input.
+///
+/// The defaults match the behaviour of [`from_str`], [`from_slice`] and
+/// [`from_reader`]. Options are attached to a deserializer with
+/// [`Deserializer::with_options`].
+///
+/// # Examples
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_yml::de::DeserializerOptions;
+/// use serde_yml::Deserializer;
+///
+/// let options = DeserializerOptions {
+///     compose_graphemes: true,
+///     ..DeserializerOptions::default()
+/// };
+/// let de = Deserializer::from_str("e\u{301}").with_options(options);
+/// assert_eq!(char::deserialize(de).unwrap(), '\u{e9}');
+/// ```
+#[allow(missing_copy_implementations)]
+#[derive(Clone, Debug, Default)]
+pub struct DeserializerOptions {
+    /// Keep deserializing after a value fails and report every failure at
+    /// once, as an [`Error`] whose [`errors`](Error::errors) list them in
+    /// the order they were found. A sequence element or mapping value that
+    /// fails, such as a field of the wrong type or a struct missing a
+    /// field, is skipped and read as the simplest value of its type, such
+    /// as zero or an empty string, so that the values after it are still
+    /// checked. A struct missing several fields reports the first of them.
+    pub collect_errors: bool,
+
+    /// Accept a base character followed by a combining mark (for example
+    /// `e` + U+0301) when deserializing a `char`, composing it into its
+    /// precomposed form (`é`). Composition covers the Latin-1 Supplement
+    /// and Latin Extended-A blocks.
+    pub compose_graphemes: bool,
+
+    /// Reject null mapping keys, written as `~`, `null` or `!!null` or
+    /// left empty, with an error giving their location. YAML allows them,
+    /// and they are accepted by default.
+    pub deny_null_keys: bool,
+
+    /// Reject mapping keys that do not name a field of the struct being
+    /// deserialized, as if every struct carried
+    /// `#[serde(deny_unknown_fields)]`. The error names the offending key
+    /// and its location. Structs containing a `#[serde(flatten)]` field are
+    /// deserialized as maps by serde and are not checked.
+    pub deny_unknown_fields: bool,
+
+    /// How iterating over a multi-document stream treats documents without
+    /// content, such as the first one in `---\n---\nfoo: 1`. The default is
+    /// [`EmptyDocuments::Null`].
+    pub empty_documents: EmptyDocuments,
+
+    /// Mapping entries to skip, given by their paths from the root of the
+    /// document such as `metadata.managedFields` or `/status`. A skipped
+    /// entry is read as if it were absent: its events are passed over
+    /// without deserializing anything below it, which saves the cost of
+    /// large subtrees that are never read, and it is not checked by
+    /// [`deny_unknown_fields`](Self::deny_unknown_fields). Paths through
+    /// sequences name the entries of one element, as in `items[0].status`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use serde_yml::de::DeserializerOptions;
+    /// use serde_yml::{Deserializer, Value};
+    ///
+    /// let yaml = "
+    /// metadata:
+    ///   name: web
+    ///   managedFields: [{ manager: kubectl }]
+    /// status: { phase: Running }
+    /// ";
+    /// let options = DeserializerOptions {
+    ///     ignore_paths: vec![
+    ///         "status".parse().unwrap(),
+    ///         "metadata.managedFields".parse().unwrap(),
+    ///     ],
+    ///     ..DeserializerOptions::default()
+    /// };
+    /// let de = Deserializer::from_str(yaml).with_options(options);
+    /// let value = Value::deserialize(de).unwrap();
+    /// let expected: Value = serde_yml::from_str("metadata: { name: web }").unwrap();
+    /// assert_eq!(value, expected);
+    /// ```
+    pub ignore_paths: Vec<OwnedPath>,
+
+    /// Accept nodes carrying more than one tag, such as `!outer !inner x`,
+    /// keeping the first tag and ignoring the others as described for
+    /// [`Loader::with_lenient_tags`]. Use [`from_str_lenient_tags`] to find
+    /// out which tags were ignored.
+    pub lenient_tags: bool,
+
+    /// Accept strings holding a number, such as `"8080"`, `'0x2A'` or
+    /// `1_000`, wherever the type being deserialized expects a number.
+    /// Underscores between digits, leading zeros and surrounding whitespace
+    /// are ignored. Untyped values such as [`Value`](crate::Value) are not
+    /// affected.
+    pub lenient_numbers: bool,
+
+    /// How a mapping value that is left out, as in `key:`, is read. The
+    /// default is [`MissingValues::Null`]. An explicit `~` or `null` is
+    /// always null.
+    pub missing_values: MissingValues,
+
+    /// Reject input in which a line ends with a lone carriage return (CR),
+    /// as in files saved by classic Mac OS, with an error giving the
+    /// location of the first one. YAML reads CR as a line break, so such
+    /// input is accepted by default.
+    pub reject_cr_line_endings: bool,
+
+    /// Reject NaN and the infinities, such as `.nan`, `-.inf` or
+    /// `!!float .Inf`, with an error giving their location, for input
+    /// that is passed on as JSON, which has no way to write them. They are
+    /// accepted by default. Quoted scalars without a tag are strings and
+    /// are not checked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_yml::de::DeserializerOptions;
+    /// use serde_yml::{Deserializer, Value};
+    /// use serde::Deserialize;
+    ///
+    /// let options = DeserializerOptions {
+    ///     reject_non_finite_floats: true,
+    ///     ..DeserializerOptions::default()
+    /// };
+    /// let de = Deserializer::from_str("ratio: .nan\n").with_options(options);
+    /// let err = Value::deserialize(de).unwrap_err();
+    /// assert_eq!(err.location().unwrap().column(), 8);
+    /// ```
+    pub reject_non_finite_floats: bool,
+
+    /// Reject plain scalars that YAML 1.1 reads as something other than
+    /// the string they are read as here, following YAML 1.2, with an error
+    /// giving their location. These are octal integers without the `0o`
+    /// prefix such as `0777`, sexagesimal numbers such as `1:30:00`,
+    /// numbers with `_` separators such as `1_000` and booleans such as
+    /// `yes` or `off`, as well as spellings of NaN and infinity outside
+    /// YAML 1.2 such as `NaN` or `-inf`, which some parsers read as floats.
+    /// Quoted and tagged scalars are not checked.
+    pub reject_yaml_1_1_scalars: bool,
+}
+
+/// The treatment of empty documents when iterating over a multi-document
+/// stream with a [`Deserializer`].
+///
+/// A document is empty if it has no node at all: nothing but whitespace
+/// and comments between its markers. A document containing `~`, `null` or
+/// `""` is not empty.
+///
+/// # Examples
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_yml::de::{DeserializerOptions, EmptyDocuments};
+/// use serde_yml::{Deserializer, Value};
+///
+/// let options = DeserializerOptions {
+///     empty_documents: EmptyDocuments::Skip,
+///     ..DeserializerOptions::default()
+/// };
+/// let values = Deserializer::from_str("---\n---\nfoo: 1\n---\n")
+///     .with_options(options)
+///     .map(Value::deserialize)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(values.len(), 1);
+/// assert_eq!(values[0]["foo"], 1);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmptyDocuments {
+    /// Yield empty documents, which deserialize like `null`.
+    Null,
+    /// Leave empty documents out of the iteration.
+    Skip,
+    /// Yield an error in place of each empty document.
+    Error,
+}
+
+impl Default for EmptyDocuments {
+    fn default() -> Self {
+        EmptyDocuments::Null
+    }
+}
+
+/// The treatment of mapping values left out of the input, as in `key:`.
+///
+/// Producers of YAML disagree on what such a value means: PyYAML reads it
+/// as `None`, like YAML itself, while StrictYAML reads it as an empty
+/// string.
+///
+/// # Examples
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_yml::de::{DeserializerOptions, MissingValues};
+/// use serde_yml::{Deserializer, Value};
+///
+/// let options = DeserializerOptions {
+///     missing_values: MissingValues::EmptyString,
+///     ..DeserializerOptions::default()
+/// };
+/// let de = Deserializer::from_str("name:\nnote: ~\n").with_options(options);
+/// let value = Value::deserialize(de).unwrap();
+/// assert_eq!(value["name"], "");
+/// assert_eq!(value["note"], Value::Null);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MissingValues {
+    /// Read a missing value as null.
+    Null,
+    /// Read a missing value as an empty string.
+    EmptyString,
+    /// Reject a missing value with an error giving its location.
+    Error,
+}
+
+impl Default for MissingValues {
+    fn default() -> Self {
+        MissingValues::Null
+    }
+}
+
+/// The kind of a [`Warning`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum WarningKind {
+    /// A key appears more than once in the same mapping. Depending on the
+    /// type being deserialized, the later value overrides the earlier one
+    /// or deserialization fails.
+    DuplicateKey,
+    /// A plain scalar that YAML 1.1 reads as something other than the
+    /// string YAML 1.2 reads, as described for
+    /// [`DeserializerOptions::reject_yaml_1_1_scalars`].
+    Yaml11Scalar,
+    /// An anchor that no alias refers to.
+    UnusedAnchor,
+    /// An integer read as a float that does not hold it exactly, such as
+    /// one outside the range of `i128` and `u128` without the `bigint`
+    /// feature.
+    LossyNumber,
+    /// A mapping value or sequence element that the type being
+    /// deserialized skipped, typically the value of a key that names no
+    /// struct field.
+    UnusedValue,
+    /// A tag removed from a node that carried more than one tag, such as
+    /// `!inner` in `!outer !inner x`.
+    DroppedTag,
+}
+
+/// A problem found in the input that does not stop deserialization.
+///
+/// Returned in the [`ParseOutput`] of [`from_str_with_warnings`] and
+/// [`from_slice_with_warnings`], and by [`from_str_with_unused`] and
+/// [`from_str_lenient_tags`].
+#[derive(Clone, Debug)]
+pub struct Warning {
+    /// What kind of problem this is.
+    pub kind: WarningKind,
+    /// The description of the problem.
+    pub message: String,
+    /// The path of the node with the problem, or `None` if the node is
+    /// below a mapping key that is not a string. For a duplicate key, this
+    /// is the path of the later entry. Tags are dropped from the text
+    /// before it is parsed, so the path of a dropped tag is the root.
+    pub path: Option<OwnedPath>,
+    /// The position of the node with the problem in the input.
+    pub location: Location,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) if path.is_root() => {}
+            Some(path) => write!(f, "{}: ", path)?,
+            None => f.write_str("?: ")?,
+        }
+        write!(
+            f,
+            "{} at line {} column {}",
+            self.message,
+            self.location.line(),
+            self.location.column(),
+        )
+    }
+}
+
+/// A deserialized value together with the warnings found in its input.
+///
+/// Returned by [`from_str_with_warnings`] and [`from_slice_with_warnings`].
+#[derive(Clone, Debug)]
+pub struct ParseOutput<T> {
+    /// The deserialized value.
+    pub value: T,
+    /// The warnings, in the order of the input.
+    pub warnings: Vec<Warning>,
 }
 
 /// Represents the progress of parsing a YAML document.
@@ -79,13 +424,15 @@ pub enum Progress<'de> {
     /// The `Box<dyn io::Read + 'de>` represents a boxed trait object that implements the `Read` trait
     /// and has a lifetime `'de`. This allows for reading the YAML input from various sources,
     /// such as files, network streams, or any other type that implements `Read`.
+    #[cfg(feature = "std")]
     Read(Box<dyn io::Read + 'de>),
 
     /// Indicates that the YAML input is provided through an iterator of `Loader` instances.
     ///
     /// The `Loader<'de>` represents a YAML loader that iterates over the YAML documents.
     /// The `'de` lifetime indicates the lifetime of the borrowed data within the loader.
-    Iterable(Loader<'de>),
+    /// It is boxed, as it is much larger than the other variants.
+    Iterable(Box<Loader<'de>>),
 
     /// Indicates that the YAML input is a single `Document` instance.
     ///
@@ -107,6 +454,7 @@ impl Debug for Progress<'_> {
             Progress::Slice(slice) => {
                 write!(f, "Progress::Slice({:?})", slice)
             }
+            #[cfg(feature = "std")]
             Progress::Read(_) => {
                 write!(f, "Progress::Read(Box<dyn io::Read>)")
             }
@@ -159,7 +507,11 @@ impl<'de> Deserializer<'de> {
     #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &'de str) -> Self {
         let progress = Progress::Str(s);
-        Deserializer { progress }
+        Deserializer {
+            progress,
+            options: DeserializerOptions::default(),
+            warnings: None,
+        }
     }
 
     /// Deserializes an instance of type `T` from bytes of YAML text.
@@ -190,7 +542,11 @@ impl<'de> Deserializer<'de> {
     ///
     pub fn from_slice(v: &'de [u8]) -> Self {
         let progress = Progress::Slice(v);
-        Deserializer { progress }
+        Deserializer {
+            progress,
+            options: DeserializerOptions::default(),
+            warnings: None,
+        }
     }
 
     /// Deserializes an instance of type `T` from an IO stream of YAML.
@@ -226,12 +582,72 @@ impl<'de> Deserializer<'de> {
     /// println!("{:?}", config); // Config { name: "Jane Doe", age: 25 }
     /// ```
     ///
+    #[cfg(feature = "std")]
     pub fn from_reader<R>(rdr: R) -> Self
     where
         R: io::Read + 'de,
     {
         let progress = Progress::Read(Box::new(rdr));
-        Deserializer { progress }
+        Deserializer {
+            progress,
+            options: DeserializerOptions::default(),
+            warnings: None,
+        }
+    }
+
+    /// Creates a deserializer for a document that has already been loaded.
+    pub(crate) fn from_document(document: Document<'de>) -> Self {
+        Deserializer {
+            progress: Progress::Document(document),
+            options: DeserializerOptions::default(),
+            warnings: None,
+        }
+    }
+
+    /// Replaces the options used by this deserializer.
+    ///
+    /// When iterating over a multi-document stream, every yielded document
+    /// inherits the options of the stream deserializer.
+    pub fn with_options(
+        mut self,
+        options: DeserializerOptions,
+    ) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Returns the options used by this deserializer.
+    pub fn options(&self) -> &DeserializerOptions {
+        &self.options
+    }
+
+    /// Returns the anchors, tags, version directive and span of the
+    /// document held by this deserializer.
+    ///
+    /// Only the deserializers yielded by iterating over a multi-document
+    /// stream hold a parsed document; a deserializer created directly from
+    /// the input has not parsed it yet and returns `None`. Use
+    /// [`document_metadata`] to gather the metadata of every document of
+    /// an input without deserializing them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use serde_yml::{Deserializer, Value};
+    ///
+    /// let input = "a: &x 1\n---\nb: !big 2\n";
+    /// for document in Deserializer::from_str(input) {
+    ///     let metadata = document.metadata().unwrap();
+    ///     let value = Value::deserialize(document).unwrap();
+    ///     println!("{:?}: {:?} {:?}", metadata.span, metadata.tags, value);
+    /// }
+    /// ```
+    pub fn metadata(&self) -> Option<DocumentMetadata> {
+        match &self.progress {
+            Progress::Document(document) => Some(document.metadata()),
+            _ => None,
+        }
     }
 
     fn de<T>(
@@ -242,20 +658,28 @@ impl<'de> Deserializer<'de> {
     ) -> Result<T> {
         let mut pos = 0;
         let mut jumpcount = 0;
+        let warnings = self.warnings.as_deref();
+        let collected = RefCell::new(Vec::new());
+        let errors = self.options.collect_errors.then(|| &collected);
 
         match self.progress {
             Progress::Iterable(_) => {
                 return Err(error::new(ErrorImpl::MoreThanOneDocument))
             }
-            Progress::Document(document) => {
-                let t = f(&mut DeserializerFromEvents {
+            Progress::Document(mut document) => {
+                check_document(&mut document, &self.options)?;
+                let result = f(&mut DeserializerFromEvents {
                     document: &document,
+                    options: &self.options,
+                    warnings,
+                    errors,
                     pos: &mut pos,
                     jumpcount: &mut jumpcount,
                     path: Path::Root,
                     remaining_depth: 128,
                     current_enum: None,
-                })?;
+                });
+                let t = error::collect(collected.into_inner(), result)?;
                 if let Some(parse_error) = document.error {
                     return Err(error::shared(parse_error));
                 }
@@ -264,26 +688,43 @@ impl<'de> Deserializer<'de> {
             _ => {}
         }
 
-        let mut loader = Loader::new(self.progress)?;
-        let document = match loader.next_document() {
+        let mut loader = if self.options.lenient_tags {
+            Loader::with_lenient_tags(self.progress)?
+        } else {
+            Loader::new(self.progress)?
+        };
+        if self.options.reject_cr_line_endings {
+            loader.check_line_endings()?;
+        }
+        let mut document = match loader.next_document() {
             Some(document) => document,
             None => return Err(error::new(ErrorImpl::EndOfStream)),
         };
-        let t = f(&mut DeserializerFromEvents {
+        check_document(&mut document, &self.options)?;
+        let result = f(&mut DeserializerFromEvents {
             document: &document,
+            options: &self.options,
+            warnings,
+            errors,
             pos: &mut pos,
             jumpcount: &mut jumpcount,
             path: Path::Root,
             remaining_depth: 128,
             current_enum: None,
-        })?;
+        });
+        let t = error::collect(collected.into_inner(), result)?;
         if let Some(parse_error) = document.error {
             return Err(error::shared(parse_error));
         }
-        if loader.next_document().is_none() {
-            Ok(t)
-        } else {
-            Err(error::new(ErrorImpl::MoreThanOneDocument))
+        match loader.next_document() {
+            None => Ok(t),
+            // A node with two tags, `!a !b x`, parses as a document
+            // holding `!a` followed by an error at `!b`.
+            Some(Document {
+                error: Some(parse_error),
+                ..
+            }) => Err(error::shared(parse_error)),
+            Some(_) => Err(error::new(ErrorImpl::MoreThanOneDocument)),
         }
     }
 }
@@ -294,15 +735,40 @@ impl Iterator for Deserializer<'_> {
     fn next(&mut self) -> Option<Self> {
         match &mut self.progress {
             Progress::Iterable(loader) => {
-                let document = loader.next_document()?;
+                let progress = loop {
+                    let document = loader.next_document()?;
+                    if document.error.is_some() || !document.is_empty()
+                    {
+                        break Progress::Document(document);
+                    }
+                    match self.options.empty_documents {
+                        EmptyDocuments::Null => {
+                            break Progress::Document(document)
+                        }
+                        EmptyDocuments::Skip => {}
+                        EmptyDocuments::Error => {
+                            let mark = document.events[0].1;
+                            let err = error::fix_mark(
+                                de::Error::custom("empty document"),
+                                mark,
+                                Path::Root,
+                            );
+                            break Progress::Fail(err.shared());
+                        }
+                    }
+                };
                 return Some(Deserializer {
-                    progress: Progress::Document(document),
+                    progress,
+                    options: self.options.clone(),
+                    warnings: self.warnings.clone(),
                 });
             }
             Progress::Document(_) => return None,
             Progress::Fail(err) => {
                 return Some(Deserializer {
                     progress: Progress::Fail(Arc::clone(err)),
+                    options: self.options.clone(),
+                    warnings: self.warnings.clone(),
                 });
             }
             _ => {}
@@ -310,9 +776,20 @@ impl Iterator for Deserializer<'_> {
 
         let dummy = Progress::Str("");
         let input = mem::replace(&mut self.progress, dummy);
-        match Loader::new(input) {
+        let loader = if self.options.lenient_tags {
+            Loader::with_lenient_tags(input)
+        } else {
+            Loader::new(input)
+        };
+        let loader = match loader {
+            Ok(loader) if self.options.reject_cr_line_endings => {
+                loader.check_line_endings().map(|()| loader)
+            }
+            loader => loader,
+        };
+        match loader {
             Ok(loader) => {
-                self.progress = Progress::Iterable(loader);
+                self.progress = Progress::Iterable(Box::new(loader));
                 self.next()
             }
             Err(err) => {
@@ -320,6 +797,8 @@ impl Iterator for Deserializer<'_> {
                 self.progress = Progress::Fail(Arc::clone(&fail));
                 Some(Deserializer {
                     progress: Progress::Fail(fail),
+                    options: self.options.clone(),
+                    warnings: self.warnings.clone(),
                 })
             }
         }
@@ -576,6 +1055,147 @@ impl<'de> de::Deserializer<'de> for Deserializer<'de> {
     }
 }
 
+/// A deserializer for parsing many inputs one after the other, keeping its
+/// allocations from one input to the next.
+///
+/// Each input is deserialized like [`from_str`] would, but the parser, the
+/// buffer holding a copy of the input and the buffers holding the parsed
+/// events are reused rather than allocated again. This makes a difference
+/// when deserializing a large number of small messages. The internal
+/// buffers of libyml are still set up anew for every input.
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::de::ReusableDeserializer;
+/// use std::collections::BTreeMap;
+///
+/// let mut deserializer = ReusableDeserializer::new();
+/// for (i, message) in ["a: 1", "b: 2", "c: 3"].iter().enumerate() {
+///     let map: BTreeMap<String, usize> = deserializer.parse(message).unwrap();
+///     assert_eq!(map.values().next(), Some(&(i + 1)));
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ReusableDeserializer {
+    loader: Loader<'static>,
+    spare: Vec<u8>,
+    options: DeserializerOptions,
+}
+
+impl ReusableDeserializer {
+    /// Creates a reusable deserializer with the default options.
+    pub fn new() -> Self {
+        ReusableDeserializer::with_options(
+            DeserializerOptions::default(),
+        )
+    }
+
+    /// Creates a reusable deserializer with the given options.
+    pub fn with_options(options: DeserializerOptions) -> Self {
+        ReusableDeserializer {
+            loader: Loader::from_input(
+                Cow::Borrowed(&[]),
+                options.lenient_tags,
+            ),
+            spare: Vec::new(),
+            options,
+        }
+    }
+
+    /// Starts over on a new input, to be deserialized by
+    /// [`deserialize`](ReusableDeserializer::deserialize).
+    pub fn reset(&mut self, input: impl AsRef<[u8]>) {
+        let mut buffer = mem::take(&mut self.spare);
+        buffer.clear();
+        buffer.extend_from_slice(input.as_ref());
+        if let Some(Cow::Owned(previous)) =
+            self.loader.reset_input(Cow::Owned(buffer))
+        {
+            self.spare = previous;
+        }
+    }
+
+    /// Deserializes the input given to the last call to
+    /// [`reset`](ReusableDeserializer::reset) into an instance of type `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`from_str`]. Deserializing the same input
+    /// a second time fails with an end of stream error.
+    pub fn deserialize<T>(&mut self) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        if self.options.reject_cr_line_endings {
+            self.loader.check_line_endings()?;
+        }
+        let mut document = match self.loader.next_document() {
+            Some(document) => document,
+            None => return Err(error::new(ErrorImpl::EndOfStream)),
+        };
+        if let Err(err) = check_document(&mut document, &self.options) {
+            self.loader.recycle(document);
+            return Err(err);
+        }
+        let mut pos = 0;
+        let mut jumpcount = 0;
+        let collected = RefCell::new(Vec::new());
+        let result = T::deserialize(&mut DeserializerFromEvents {
+            document: &document,
+            options: &self.options,
+            warnings: None,
+            errors: self.options.collect_errors.then(|| &collected),
+            pos: &mut pos,
+            jumpcount: &mut jumpcount,
+            path: Path::Root,
+            remaining_depth: 128,
+            current_enum: None,
+        });
+        let result = error::collect(collected.into_inner(), result);
+        let parse_error = document.error.clone();
+        self.loader.recycle(document);
+        let t = result?;
+        if let Some(parse_error) = parse_error {
+            return Err(error::shared(parse_error));
+        }
+        match self.loader.next_document() {
+            None => Ok(t),
+            Some(document) => {
+                let error = match &document.error {
+                    Some(parse_error) => {
+                        error::shared(Arc::clone(parse_error))
+                    }
+                    None => error::new(ErrorImpl::MoreThanOneDocument),
+                };
+                self.loader.recycle(document);
+                Err(error)
+            }
+        }
+    }
+
+    /// Deserializes `input` into an instance of type `T`, the same as
+    /// calling [`reset`](ReusableDeserializer::reset) followed by
+    /// [`deserialize`](ReusableDeserializer::deserialize).
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`from_str`].
+    pub fn parse<T>(&mut self, input: &str) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        self.reset(input);
+        self.deserialize()
+    }
+}
+
+impl Default for ReusableDeserializer {
+    fn default() -> Self {
+        ReusableDeserializer::new()
+    }
+}
+
 /// Represents the different events that can occur during YAML parsing.
 #[derive(Debug)]
 pub enum Event<'de> {
@@ -608,6 +1228,9 @@ pub enum Event<'de> {
 
 struct DeserializerFromEvents<'de, 'document> {
     document: &'document Document<'de>,
+    options: &'document DeserializerOptions,
+    warnings: Option<&'document RefCell<Vec<Warning>>>,
+    errors: Option<&'document RefCell<Vec<Error>>>,
     pos: &'document mut usize,
     jumpcount: &'document mut usize,
     path: Path<'document>,
@@ -642,6 +1265,63 @@ impl<'de, 'document> DeserializerFromEvents<'de, 'document> {
         self.next_event_mark().map(|(event, _mark)| event)
     }
 
+    /// Returns the normalized text of an untagged scalar when
+    /// [`DeserializerOptions::lenient_numbers`] is set.
+    fn lenient_number(&self, event: &Event<'de>) -> Option<String> {
+        match event {
+            Event::Scalar(scalar)
+                if self.options.lenient_numbers
+                    && scalar.tag.is_none() =>
+            {
+                str::from_utf8(&scalar.value)
+                    .ok()
+                    .and_then(normalize_numeric_string)
+            }
+            _ => None,
+        }
+    }
+
+    /// Warns about the untagged plain scalar `value` if it is an integer
+    /// that `deserialize_any` reads as a float, which happens without the
+    /// `bigint` feature to integers outside the range of `i128` and `u128`.
+    fn check_lossy_number(&self, value: &[u8], mark: Mark) {
+        let v = match str::from_utf8(value) {
+            Ok(v) => v,
+            Err(_) => return,
+        };
+        if cfg!(feature = "bigint")
+            || parse_unsigned_int(v, u128::from_str_radix).is_some()
+            || parse_negative_int(v, i128::from_str_radix).is_some()
+            || digits_but_not_number(v)
+        {
+            return;
+        }
+        if let Some(float) = parse_f64(v) {
+            self.warn_lossy_number(v, float, mark);
+        }
+    }
+
+    /// Records a [`WarningKind::LossyNumber`] warning if `v`, a decimal
+    /// integer read as `float`, does not survive the conversion. Nothing
+    /// is recorded with the `bigint` feature.
+    fn warn_lossy_number(&self, v: &str, float: f64, mark: Mark) {
+        let warnings = match self.warnings {
+            Some(warnings) if !cfg!(feature = "bigint") => warnings,
+            _ => return,
+        };
+        if loses_precision(v, float) {
+            warnings.borrow_mut().push(Warning {
+                kind: WarningKind::LossyNumber,
+                message: format!(
+                    "integer {} loses precision when read as the float {:.0}",
+                    v, float,
+                ),
+                path: OwnedPath::try_from(&self.path).ok(),
+                location: Location::from_mark(mark),
+            });
+        }
+    }
+
     fn next_event_mark(
         &mut self,
     ) -> Result<(&'document Event<'de>, Mark)> {
@@ -665,6 +1345,9 @@ impl<'de, 'document> DeserializerFromEvents<'de, 'document> {
                 *pos = *found;
                 Ok(DeserializerFromEvents {
                     document: self.document,
+                    options: self.options,
+                    warnings: self.warnings,
+                    errors: self.errors,
                     pos,
                     jumpcount: self.jumpcount,
                     path: Path::Alias { parent: &self.path },
@@ -712,6 +1395,187 @@ impl<'de, 'document> DeserializerFromEvents<'de, 'document> {
         }
     }
 
+    /// Moves to the node at `segments` below the next one, skipping the
+    /// nodes around it event by event, and runs `f` on a deserializer of
+    /// that node.
+    fn descend<T>(
+        &mut self,
+        segments: &[Segment],
+        f: impl for<'a> FnOnce(
+            &mut DeserializerFromEvents<'de, 'a>,
+        ) -> Result<T>,
+    ) -> Result<T> {
+        let (segment, rest) = match segments.split_first() {
+            Some(split) => split,
+            None => return f(self),
+        };
+        let (next, mark) = self.next_event_mark()?;
+        match (next, segment) {
+            (Event::Alias(mut pos), _) => {
+                self.jump(&mut pos)?.descend(segments, f)
+            }
+            (Event::MappingStart(_), Segment::Key(key)) => loop {
+                match self.peek_event()? {
+                    Event::Scalar(scalar)
+                        if *scalar.value == *key.as_bytes() =>
+                    {
+                        *self.pos += 1;
+                        let mut value = DeserializerFromEvents {
+                            document: self.document,
+                            options: self.options,
+                            warnings: self.warnings,
+                            errors: self.errors,
+                            pos: self.pos,
+                            jumpcount: self.jumpcount,
+                            path: Path::Map {
+                                parent: &self.path,
+                                key,
+                            },
+                            remaining_depth: self.remaining_depth,
+                            current_enum: None,
+                        };
+                        return value.descend(rest, f);
+                    }
+                    Event::MappingEnd | Event::Void => {
+                        return Err(error::fix_mark(
+                            de::Error::custom(format_args!(
+                                "missing key `{}`",
+                                key
+                            )),
+                            mark,
+                            self.path,
+                        ));
+                    }
+                    _ => {
+                        self.ignore_any()?;
+                        self.ignore_any()?;
+                    }
+                }
+            },
+            (Event::SequenceStart(_), &Segment::Index(index)) => {
+                for len in 0..=index {
+                    if let Event::SequenceEnd | Event::Void =
+                        self.peek_event()?
+                    {
+                        return Err(error::fix_mark(
+                            de::Error::invalid_length(
+                                len,
+                                &format!("a sequence with more than {} elements", index).as_str(),
+                            ),
+                            mark,
+                            self.path,
+                        ));
+                    }
+                    if len < index {
+                        self.ignore_any()?;
+                    }
+                }
+                let mut element = DeserializerFromEvents {
+                    document: self.document,
+                    options: self.options,
+                    warnings: self.warnings,
+                    errors: self.errors,
+                    pos: self.pos,
+                    jumpcount: self.jumpcount,
+                    path: Path::Seq {
+                        parent: &self.path,
+                        index,
+                    },
+                    remaining_depth: self.remaining_depth,
+                    current_enum: None,
+                };
+                element.descend(rest, f)
+            }
+            (_, Segment::Key(_)) => Err(error::fix_mark(
+                invalid_type(next, &"a mapping"),
+                mark,
+                self.path,
+            )),
+            (_, Segment::Index(_)) => Err(error::fix_mark(
+                invalid_type(next, &"a sequence"),
+                mark,
+                self.path,
+            )),
+        }
+    }
+
+    /// Deserializes the next node as a `T`. When errors are being
+    /// collected, a node that fails to deserialize is skipped and read as
+    /// a [`Placeholder`] instead, and its error is collected, unless the
+    /// placeholder fails as well.
+    fn deserialize_or_collect<T>(&mut self) -> Result<T>
+    where
+        T: Deserialize<'de>,
+    {
+        let errors = match self.errors {
+            Some(errors) => errors,
+            None => return T::deserialize(self),
+        };
+        let start = *self.pos;
+        let err = match T::deserialize(&mut *self) {
+            Ok(value) => return Ok(value),
+            Err(err) => err,
+        };
+        match T::deserialize(Placeholder) {
+            Ok(value) => {
+                *self.pos = start;
+                self.ignore_any()?;
+                errors.borrow_mut().push(err);
+                Ok(value)
+            }
+            Err(_) => Err(err),
+        }
+    }
+
+    /// Visits the text of the next node, as written in the input.
+    fn deserialize_raw<V>(&mut self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        if let Event::Alias(mut pos) = *self.peek_event()? {
+            *self.pos += 1;
+            return self.jump(&mut pos)?.deserialize_raw(visitor);
+        }
+        let first = *self.pos;
+        self.ignore_any()?;
+        visitor
+            .visit_string(self.document.node_text(first, *self.pos - 1))
+    }
+
+    /// Lets the visitor of an enum declared with
+    /// [`untagged!`](crate::untagged!) try each variant on the events of
+    /// the next node, presented as a sequence repeating the node.
+    fn deserialize_untagged<V>(
+        &mut self,
+        names: &'static str,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let (_event, mark) = self.peek_event_mark()?;
+        let start = *self.pos;
+        let path = OwnedPath::try_from(&self.path).ok();
+        let failures = Failures::new(names, path);
+        let mut attempts = UntaggedAttempts {
+            warnings: self
+                .warnings
+                .map_or(0, |warnings| warnings.borrow().len()),
+            start,
+            de: self,
+            failures,
+        };
+        let result = visitor.visit_seq(&mut attempts);
+        let value = attempts
+            .failures
+            .finish(result)
+            .map_err(|err| error::fix_mark(err, mark, self.path))?;
+        if *self.pos == start {
+            self.ignore_any()?;
+        }
+        Ok(value)
+    }
+
     fn visit_sequence<V>(
         &mut self,
         visitor: V,
@@ -737,6 +1601,7 @@ impl<'de, 'document> DeserializerFromEvents<'de, 'document> {
         &mut self,
         visitor: V,
         mark: Mark,
+        fields: Option<&'static [&'static str]>,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
@@ -747,6 +1612,7 @@ impl<'de, 'document> DeserializerFromEvents<'de, 'document> {
                 de,
                 len: 0,
                 key: None,
+                fields,
             };
             let value = visitor.visit_map(&mut map)?;
             Ok((value, map.len))
@@ -755,19 +1621,65 @@ impl<'de, 'document> DeserializerFromEvents<'de, 'document> {
         Ok(value)
     }
 
-    fn end_sequence(&mut self, len: usize) -> Result<()> {
-        let total = {
-            let mut seq = SeqAccess {
-                empty: false,
-                de: self,
-                len,
-            };
-            while de::SeqAccess::next_element::<IgnoredAny>(&mut seq)?
-                .is_some()
-            {}
-            seq.len
-        };
-        match self.next_event()? {
+    /// Deserializes a mapping, rejecting keys outside of `fields` when
+    /// they are given.
+    fn deserialize_mapping<V>(
+        &mut self,
+        visitor: V,
+        fields: Option<&'static [&'static str]>,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let (next, mark) = self.next_event_mark()?;
+        match next {
+            Event::Alias(mut pos) => self
+                .jump(&mut pos)?
+                .deserialize_mapping(visitor, fields),
+            Event::MappingStart(_) => {
+                self.visit_mapping(visitor, mark, fields)
+            }
+            other => {
+                if match other {
+                    Event::Void => true,
+                    Event::Scalar(scalar) => {
+                        scalar.value.is_empty()
+                            && scalar.style == ScalarStyle::Plain
+                    }
+                    _ => false,
+                } {
+                    visitor.visit_map(MapAccess {
+                        empty: true,
+                        de: self,
+                        len: 0,
+                        key: None,
+                        fields,
+                    })
+                } else {
+                    Err(invalid_type(other, &visitor))
+                }
+            }
+        }
+        .map_err(|err| error::fix_mark(err, mark, self.path))
+    }
+
+    fn end_sequence(&mut self, len: usize) -> Result<()> {
+        // Elements skipped here were never offered to the visitor, so they
+        // are not reported as unused values.
+        let warnings = self.warnings.take();
+        let total = {
+            let mut seq = SeqAccess {
+                empty: false,
+                de: self,
+                len,
+            };
+            while de::SeqAccess::next_element::<IgnoredAny>(&mut seq)?
+                .is_some()
+            {}
+            seq.len
+        };
+        self.warnings = warnings;
+        match self.next_event()? {
             Event::SequenceEnd | Event::Void => {}
             _ => panic!("expected a SequenceEnd event"),
         }
@@ -796,12 +1708,14 @@ impl<'de, 'document> DeserializerFromEvents<'de, 'document> {
     }
 
     fn end_mapping(&mut self, len: usize) -> Result<()> {
+        let warnings = self.warnings.take();
         let total = {
             let mut map = MapAccess {
                 empty: false,
                 de: self,
                 len,
                 key: None,
+                fields: None,
             };
             while de::MapAccess::next_entry::<IgnoredAny, IgnoredAny>(
                 &mut map,
@@ -810,6 +1724,7 @@ impl<'de, 'document> DeserializerFromEvents<'de, 'document> {
             {}
             map.len
         };
+        self.warnings = warnings;
         match self.next_event()? {
             Event::MappingEnd | Event::Void => {}
             _ => panic!("expected a MappingEnd event"),
@@ -858,13 +1773,18 @@ impl<'de, 'document> DeserializerFromEvents<'de, 'document> {
     }
 }
 
-struct SeqAccess<'de, 'document, 'seq> {
-    empty: bool,
+/// The node of an untagged enum, presented as a sequence repeating it,
+/// one element for each variant to try.
+struct UntaggedAttempts<'de, 'document, 'seq> {
     de: &'seq mut DeserializerFromEvents<'de, 'document>,
-    len: usize,
+    /// The position of the first event of the node.
+    start: usize,
+    /// The number of warnings recorded before the node.
+    warnings: usize,
+    failures: Failures,
 }
 
-impl<'de> de::SeqAccess<'de> for SeqAccess<'de, '_, '_> {
+impl<'de> de::SeqAccess<'de> for UntaggedAttempts<'de, '_, '_> {
     type Error = Error;
 
     fn next_element_seed<T>(
@@ -874,14 +1794,55 @@ impl<'de> de::SeqAccess<'de> for SeqAccess<'de, '_, '_> {
     where
         T: DeserializeSeed<'de>,
     {
+        // Start over at the node, forgetting what an earlier attempt read.
+        *self.de.pos = self.start;
+        if let Some(warnings) = self.de.warnings {
+            warnings.borrow_mut().truncate(self.warnings);
+        }
+        let mut attempt = DeserializerFromEvents {
+            document: self.de.document,
+            options: self.de.options,
+            warnings: self.de.warnings,
+            // A variant that does not match must fail rather than have
+            // its errors collected and its values replaced by defaults.
+            errors: None,
+            pos: self.de.pos,
+            jumpcount: self.de.jumpcount,
+            path: self.de.path,
+            remaining_depth: self.de.remaining_depth,
+            current_enum: None,
+        };
+        match seed.deserialize(&mut attempt) {
+            Ok(value) => Ok(Some(value)),
+            Err(err) => {
+                Err(self.failures.push(*self.de.pos - self.start, err))
+            }
+        }
+    }
+}
+
+struct SeqAccess<'de, 'document, 'seq> {
+    empty: bool,
+    de: &'seq mut DeserializerFromEvents<'de, 'document>,
+    len: usize,
+}
+
+impl<'de> SeqAccess<'de, '_, '_> {
+    /// Returns a deserializer for the next element, if there is one.
+    fn element(
+        &mut self,
+    ) -> Result<Option<DeserializerFromEvents<'de, '_>>> {
         if self.empty {
             return Ok(None);
         }
         match self.de.peek_event()? {
             Event::SequenceEnd | Event::Void => Ok(None),
             _ => {
-                let mut element_de = DeserializerFromEvents {
+                let element_de = DeserializerFromEvents {
                     document: self.de.document,
+                    options: self.de.options,
+                    warnings: self.de.warnings,
+                    errors: self.de.errors,
                     pos: self.de.pos,
                     jumpcount: self.de.jumpcount,
                     path: Path::Seq {
@@ -892,8 +1853,39 @@ impl<'de> de::SeqAccess<'de> for SeqAccess<'de, '_, '_> {
                     current_enum: None,
                 };
                 self.len += 1;
+                Ok(Some(element_de))
+            }
+        }
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for SeqAccess<'de, '_, '_> {
+    type Error = Error;
+
+    fn next_element_seed<T>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.element()? {
+            Some(mut element_de) => {
                 seed.deserialize(&mut element_de).map(Some)
             }
+            None => Ok(None),
+        }
+    }
+
+    fn next_element<T>(&mut self) -> Result<Option<T>>
+    where
+        T: Deserialize<'de>,
+    {
+        match self.element()? {
+            Some(mut element_de) => {
+                element_de.deserialize_or_collect().map(Some)
+            }
+            None => Ok(None),
         }
     }
 }
@@ -903,6 +1895,57 @@ struct MapAccess<'de, 'document, 'map> {
     de: &'map mut DeserializerFromEvents<'de, 'document>,
     len: usize,
     key: Option<&'document [u8]>,
+    /// Field names of the struct being deserialized when unknown keys are
+    /// to be rejected.
+    fields: Option<&'static [&'static str]>,
+}
+
+impl<'de> MapAccess<'de, '_, '_> {
+    /// Returns whether the entry whose key is the scalar `key` is skipped,
+    /// as asked by [`DeserializerOptions::ignore_paths`].
+    fn is_ignored(&self, key: &[u8]) -> bool {
+        let ignore_paths = &self.de.options.ignore_paths;
+        if ignore_paths.is_empty() {
+            return false;
+        }
+        match str::from_utf8(key) {
+            Ok(key) => {
+                let path = Path::Map {
+                    parent: &self.de.path,
+                    key,
+                };
+                OwnedPath::try_from(&path)
+                    .map_or(false, |path| ignore_paths.contains(&path))
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Returns a deserializer for the value of the current entry.
+    fn value(&mut self) -> DeserializerFromEvents<'de, '_> {
+        DeserializerFromEvents {
+            document: self.de.document,
+            options: self.de.options,
+            warnings: self.de.warnings,
+            errors: self.de.errors,
+            pos: self.de.pos,
+            jumpcount: self.de.jumpcount,
+            path: if let Some(key) =
+                self.key.and_then(|key| str::from_utf8(key).ok())
+            {
+                Path::Map {
+                    parent: &self.de.path,
+                    key,
+                }
+            } else {
+                Path::Unknown {
+                    parent: &self.de.path,
+                }
+            },
+            remaining_depth: self.de.remaining_depth,
+            current_enum: None,
+        }
+    }
 }
 
 impl<'de> de::MapAccess<'de> for MapAccess<'de, '_, '_> {
@@ -915,14 +1958,35 @@ impl<'de> de::MapAccess<'de> for MapAccess<'de, '_, '_> {
         if self.empty {
             return Ok(None);
         }
-        match self.de.peek_event()? {
-            Event::MappingEnd | Event::Void => Ok(None),
-            Event::Scalar(scalar) => {
+        while let Event::Scalar(scalar) = self.de.peek_event()? {
+            if !self.is_ignored(&scalar.value) {
+                break;
+            }
+            self.de.ignore_any()?;
+            self.de.ignore_any()?;
+        }
+        match self.de.peek_event_mark()? {
+            (Event::MappingEnd | Event::Void, _) => Ok(None),
+            (Event::Scalar(scalar), mark) => {
                 self.len += 1;
                 self.key = Some(&scalar.value);
+                if let Some(fields) = self.fields {
+                    let key = String::from_utf8_lossy(&scalar.value);
+                    if !fields.contains(&&*key) {
+                        let path = Path::Map {
+                            parent: &self.de.path,
+                            key: &key,
+                        };
+                        return Err(error::fix_mark(
+                            de::Error::unknown_field(&key, fields),
+                            mark,
+                            path,
+                        ));
+                    }
+                }
                 seed.deserialize(&mut *self.de).map(Some)
             }
-            _ => {
+            (_, _) => {
                 self.len += 1;
                 self.key = None;
                 seed.deserialize(&mut *self.de).map(Some)
@@ -934,26 +1998,25 @@ impl<'de> de::MapAccess<'de> for MapAccess<'de, '_, '_> {
     where
         V: DeserializeSeed<'de>,
     {
-        let mut value_de = DeserializerFromEvents {
-            document: self.de.document,
-            pos: self.de.pos,
-            jumpcount: self.de.jumpcount,
-            path: if let Some(key) =
-                self.key.and_then(|key| str::from_utf8(key).ok())
-            {
-                Path::Map {
-                    parent: &self.de.path,
-                    key,
-                }
-            } else {
-                Path::Unknown {
-                    parent: &self.de.path,
-                }
-            },
-            remaining_depth: self.de.remaining_depth,
-            current_enum: None,
-        };
-        seed.deserialize(&mut value_de)
+        seed.deserialize(&mut self.value())
+    }
+
+    fn next_value<V>(&mut self) -> Result<V>
+    where
+        V: Deserialize<'de>,
+    {
+        self.value().deserialize_or_collect()
+    }
+
+    fn next_entry<K, V>(&mut self) -> Result<Option<(K, V)>>
+    where
+        K: Deserialize<'de>,
+        V: Deserialize<'de>,
+    {
+        match self.next_key()? {
+            Some(key) => Ok(Some((key, self.next_value()?))),
+            None => Ok(None),
+        }
     }
 }
 
@@ -980,6 +2043,9 @@ impl<'de, 'variant> de::EnumAccess<'de>
         let variant = seed.deserialize(str_de)?;
         let visitor = DeserializerFromEvents {
             document: self.de.document,
+            options: self.de.options,
+            warnings: self.de.warnings,
+            errors: self.de.errors,
             pos: self.de.pos,
             jumpcount: self.de.jumpcount,
             path: self.de.path,
@@ -1000,100 +2066,511 @@ impl<'de> de::VariantAccess<'de> for DeserializerFromEvents<'de, '_> {
         Deserialize::deserialize(&mut self)
     }
 
-    fn newtype_variant_seed<T>(mut self, seed: T) -> Result<T::Value>
+    fn newtype_variant_seed<T>(mut self, seed: T) -> Result<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(&mut self)
+    }
+
+    fn tuple_variant<V>(
+        mut self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_seq(&mut self, visitor)
+    }
+
+    fn struct_variant<V>(
+        mut self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_struct(
+            &mut self, "", fields, visitor,
+        )
+    }
+}
+
+/// Visits a tagged node that is not the input of a known enum.
+///
+/// Serde buffers the input of `#[serde(flatten)]` fields and of untagged
+/// and internally tagged enums before deciding what it is, and that buffer
+/// rejects enums. For it the node is presented as a mapping with a single
+/// entry from the tag to the untagged node instead, which is how serde
+/// represents an enum in buffered content.
+fn visit_tagged<'de, V>(
+    visitor: V,
+    access: EnumAccess<'de, '_, '_>,
+) -> Result<V::Value>
+where
+    V: Visitor<'de>,
+{
+    if buffers_content(&visitor) {
+        visitor.visit_map(TaggedMapAccess {
+            access: Some(access),
+            variant: None,
+        })
+    } else {
+        visitor.visit_enum(access)
+    }
+}
+
+/// Returns whether `visitor` is the one serde uses to buffer content.
+pub(crate) fn buffers_content(visitor: &dyn Expected) -> bool {
+    visitor.to_string() == "any value"
+}
+
+struct TaggedMapAccess<'de, 'document, 'variant> {
+    access: Option<EnumAccess<'de, 'document, 'variant>>,
+    variant: Option<DeserializerFromEvents<'de, 'variant>>,
+}
+
+impl<'de> de::MapAccess<'de> for TaggedMapAccess<'de, '_, '_> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match self.access.take() {
+            Some(access) => {
+                let (key, variant) =
+                    de::EnumAccess::variant_seed(access, seed)?;
+                self.variant = Some(variant);
+                Ok(Some(key))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        match self.variant.take() {
+            Some(mut variant) => seed.deserialize(&mut variant),
+            None => panic!("value requested before key"),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(usize::from(self.access.is_some()))
+    }
+}
+
+struct UnitVariantAccess<'de, 'document, 'variant> {
+    de: &'variant mut DeserializerFromEvents<'de, 'document>,
+}
+
+impl<'de> de::EnumAccess<'de> for UnitVariantAccess<'de, '_, '_> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant)>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        Ok((seed.deserialize(&mut *self.de)?, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for UnitVariantAccess<'de, '_, '_> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, _seed: T) -> Result<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        Err(de::Error::invalid_type(
+            Unexpected::UnitVariant,
+            &"newtype variant",
+        ))
+    }
+
+    fn tuple_variant<V>(
+        self,
+        _len: usize,
+        _visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        Err(de::Error::invalid_type(
+            Unexpected::UnitVariant,
+            &"tuple variant",
+        ))
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        Err(de::Error::invalid_type(
+            Unexpected::UnitVariant,
+            &"struct variant",
+        ))
+    }
+}
+
+/// The simplest value of whatever type asks for one: `false`, zero, an
+/// empty string, `None`, an empty sequence or map, a tuple or struct made
+/// of placeholders or the first variant of an enum. It stands in for a
+/// node that failed to deserialize when errors are being collected.
+struct Placeholder;
+
+impl<'de> de::Deserializer<'de> for Placeholder {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_bool(false)
+    }
+
+    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_i64(0)
+    }
+
+    fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_i128(0)
+    }
+
+    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_u64(0)
+    }
+
+    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_u128(0)
+    }
+
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_f64(visitor)
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_f64(0.0)
+    }
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_char('\0')
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_str("")
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_bytes(&[])
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_none()
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(PlaceholderSeq(0))
+    }
+
+    fn deserialize_tuple<V>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(PlaceholderSeq(len))
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(PlaceholderSeq(len))
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(PlaceholderMap(&[]))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(PlaceholderMap(fields))
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match variants.first() {
+            Some(variant) => {
+                visitor.visit_enum(PlaceholderVariant(variant))
+            }
+            None => {
+                Err(de::Error::invalid_type(Unexpected::Unit, &visitor))
+            }
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        unit unit_struct identifier ignored_any
+    }
+}
+
+/// A sequence of the given number of placeholders.
+struct PlaceholderSeq(usize);
+
+impl<'de> de::SeqAccess<'de> for PlaceholderSeq {
+    type Error = Error;
+
+    fn next_element_seed<T>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.0 == 0 {
+            return Ok(None);
+        }
+        self.0 -= 1;
+        seed.deserialize(Placeholder).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0)
+    }
+}
+
+/// A mapping of each of the given fields to a placeholder.
+struct PlaceholderMap(&'static [&'static str]);
+
+impl<'de> de::MapAccess<'de> for PlaceholderMap {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
     where
-        T: DeserializeSeed<'de>,
+        K: DeserializeSeed<'de>,
     {
-        seed.deserialize(&mut self)
+        match self.0.split_first() {
+            Some((field, rest)) => {
+                self.0 = rest;
+                seed.deserialize(StrDeserializer::<Error>::new(field))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
     }
 
-    fn tuple_variant<V>(
-        mut self,
-        _len: usize,
-        visitor: V,
-    ) -> Result<V::Value>
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
     where
-        V: Visitor<'de>,
+        V: DeserializeSeed<'de>,
     {
-        de::Deserializer::deserialize_seq(&mut self, visitor)
+        seed.deserialize(Placeholder)
     }
 
-    fn struct_variant<V>(
-        mut self,
-        fields: &'static [&'static str],
-        visitor: V,
-    ) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        de::Deserializer::deserialize_struct(
-            &mut self, "", fields, visitor,
-        )
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
     }
 }
 
-struct UnitVariantAccess<'de, 'document, 'variant> {
-    de: &'variant mut DeserializerFromEvents<'de, 'document>,
-}
+/// The given variant of an enum, holding placeholders.
+struct PlaceholderVariant(&'static str);
 
-impl<'de> de::EnumAccess<'de> for UnitVariantAccess<'de, '_, '_> {
+impl<'de> de::EnumAccess<'de> for PlaceholderVariant {
     type Error = Error;
     type Variant = Self;
 
-    fn variant_seed<V>(
-        self,
-        seed: V,
-    ) -> Result<(V::Value, Self::Variant)>
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self)>
     where
         V: DeserializeSeed<'de>,
     {
-        Ok((seed.deserialize(&mut *self.de)?, self))
+        let variant =
+            seed.deserialize(StrDeserializer::<Error>::new(self.0))?;
+        Ok((variant, self))
     }
 }
 
-impl<'de> de::VariantAccess<'de> for UnitVariantAccess<'de, '_, '_> {
+impl<'de> de::VariantAccess<'de> for PlaceholderVariant {
     type Error = Error;
 
     fn unit_variant(self) -> Result<()> {
         Ok(())
     }
 
-    fn newtype_variant_seed<T>(self, _seed: T) -> Result<T::Value>
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
     where
         T: DeserializeSeed<'de>,
     {
-        Err(de::Error::invalid_type(
-            Unexpected::UnitVariant,
-            &"newtype variant",
-        ))
+        seed.deserialize(Placeholder)
     }
 
     fn tuple_variant<V>(
         self,
-        _len: usize,
-        _visitor: V,
+        len: usize,
+        visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        Err(de::Error::invalid_type(
-            Unexpected::UnitVariant,
-            &"tuple variant",
-        ))
+        visitor.visit_seq(PlaceholderSeq(len))
     }
 
     fn struct_variant<V>(
         self,
-        _fields: &'static [&'static str],
-        _visitor: V,
+        fields: &'static [&'static str],
+        visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        Err(de::Error::invalid_type(
-            Unexpected::UnitVariant,
-            &"struct variant",
-        ))
+        visitor.visit_map(PlaceholderMap(fields))
     }
 }
 
@@ -1116,7 +2593,9 @@ where
     };
     if let (Some(tag), false) = (&scalar.tag, tagged_already) {
         if tag == Tag::BOOL {
-            return match parse_bool(v) {
+            return match parse_bool(v)
+                .or_else(|| parse_yaml_1_1_bool(v))
+            {
                 Some(v) => visitor.visit_bool(v),
                 None => Err(de::Error::invalid_value(
                     Unexpected::Str(v),
@@ -1198,7 +2677,415 @@ fn parse_borrowed_str<'de>(
     None
 }
 
-fn parse_null(scalar: &[u8]) -> Option<()> {
+/// Applies the options that check or rewrite the events of `document`
+/// before it is deserialized.
+fn check_document(
+    document: &mut Document<'_>,
+    options: &DeserializerOptions,
+) -> Result<()> {
+    check_nulls(document, options)?;
+    if options.reject_yaml_1_1_scalars {
+        check_yaml_1_1_scalars(document)?;
+    }
+    if options.reject_non_finite_floats {
+        check_non_finite_floats(document)?;
+    }
+    Ok(())
+}
+
+/// Applies [`DeserializerOptions::deny_null_keys`] and
+/// [`DeserializerOptions::missing_values`] to the events of `document`,
+/// turning missing mapping values into empty strings or rejecting them.
+fn check_nulls(
+    document: &mut Document<'_>,
+    options: &DeserializerOptions,
+) -> Result<()> {
+    if !options.deny_null_keys
+        && options.missing_values == MissingValues::Null
+    {
+        return Ok(());
+    }
+    // Whether each enclosing collection is a mapping, and how many of its
+    // nodes have been seen.
+    let mut stack: Vec<(bool, usize)> = Vec::new();
+    for (event, mark) in &mut document.events {
+        let (key, value) = match stack.last() {
+            Some(&(true, nodes)) => (nodes % 2 == 0, nodes % 2 == 1),
+            _ => (false, false),
+        };
+        match event {
+            Event::SequenceStart(_) => {
+                stack.push((false, 0));
+                continue;
+            }
+            Event::MappingStart(_) => {
+                stack.push((true, 0));
+                continue;
+            }
+            Event::SequenceEnd | Event::MappingEnd => {
+                stack.pop();
+            }
+            Event::Scalar(scalar) => {
+                let missing = scalar.style == ScalarStyle::Plain
+                    && scalar.tag.is_none()
+                    && scalar.value.is_empty();
+                if key && options.deny_null_keys && is_null(scalar) {
+                    return Err(error::fix_mark(
+                        de::Error::custom("null mapping key"),
+                        *mark,
+                        Path::Root,
+                    ));
+                }
+                if value && missing {
+                    match options.missing_values {
+                        MissingValues::Null => {}
+                        MissingValues::EmptyString => {
+                            scalar.style = ScalarStyle::SingleQuoted;
+                        }
+                        MissingValues::Error => {
+                            return Err(error::fix_mark(
+                                de::Error::custom(
+                                    "missing mapping value",
+                                ),
+                                *mark,
+                                Path::Root,
+                            ));
+                        }
+                    }
+                }
+            }
+            Event::Alias(_) | Event::Void => {}
+        }
+        if let Some((_, nodes)) = stack.last_mut() {
+            *nodes += 1;
+        }
+    }
+    Ok(())
+}
+
+/// Returns whether `scalar` reads as null when untyped.
+fn is_null(scalar: &Scalar<'_>) -> bool {
+    if scalar.style != ScalarStyle::Plain {
+        return false;
+    }
+    match &scalar.tag {
+        Some(tag) => tag == Tag::NULL,
+        None => {
+            scalar.value.is_empty()
+                || parse_null(&scalar.value).is_some()
+        }
+    }
+}
+
+/// Rejects the first plain scalar of `document` that YAML 1.1 reads as
+/// something other than a string, as described for
+/// [`DeserializerOptions::reject_yaml_1_1_scalars`].
+fn check_yaml_1_1_scalars(document: &Document<'_>) -> Result<()> {
+    for (event, mark) in &document.events {
+        let scalar = match event {
+            Event::Scalar(scalar)
+                if scalar.style == ScalarStyle::Plain
+                    && scalar.tag.is_none() =>
+            {
+                scalar
+            }
+            _ => continue,
+        };
+        let v = match str::from_utf8(&scalar.value) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        if let Some(reading) = yaml_1_1_reading(v) {
+            return Err(error::fix_mark(
+                de::Error::custom(format_args!(
+                    "found {:?}, which {} but YAML 1.2 reads as a string; quote it or write it in YAML 1.2 form",
+                    v, reading,
+                )),
+                *mark,
+                Path::Root,
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Rejects the first scalar of `document` that reads as NaN or an
+/// infinity, as described for
+/// [`DeserializerOptions::reject_non_finite_floats`].
+fn check_non_finite_floats(document: &Document<'_>) -> Result<()> {
+    for (event, mark) in &document.events {
+        let scalar = match event {
+            Event::Scalar(scalar) => scalar,
+            _ => continue,
+        };
+        let float = match &scalar.tag {
+            Some(tag) => tag == Tag::FLOAT,
+            None => scalar.style == ScalarStyle::Plain,
+        };
+        let v = match str::from_utf8(&scalar.value) {
+            Ok(v) if float => v,
+            _ => continue,
+        };
+        if parse_f64(v).map_or(false, |float| !float.is_finite()) {
+            return Err(error::fix_mark(
+                de::Error::custom(format_args!(
+                    "found {:?}, which is not a finite number and cannot be represented in JSON",
+                    v,
+                )),
+                *mark,
+                Path::Root,
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Collects the [`Warning`]s of `document`, in the order of the input.
+fn document_warnings(document: &Document<'_>) -> Vec<Warning> {
+    /// A collection enclosing the current node.
+    struct Frame<'a> {
+        mapping: bool,
+        /// The number of nodes of the collection seen so far.
+        nodes: usize,
+        /// The first position of each scalar key of a mapping.
+        keys: BTreeMap<&'a [u8], Mark>,
+        /// The segment of the value of the current mapping entry.
+        key: Option<Segment>,
+        /// Whether the collection added a segment to the path.
+        pushed: bool,
+        /// Whether the collection is below a key that is not a string.
+        unknown: bool,
+    }
+
+    let unused_anchors: BTreeMap<usize, String> = document
+        .metadata()
+        .anchors
+        .into_iter()
+        .filter(|anchor| anchor.aliases == 0)
+        .map(|anchor| (anchor.location.index(), anchor.name))
+        .collect();
+    let mut warnings = Vec::new();
+    let mut warn =
+        |kind, message: String, path: Option<OwnedPath>, mark: Mark| {
+            warnings.push(Warning {
+                kind,
+                message,
+                path,
+                location: Location::from_mark(mark),
+            });
+        };
+    let mut path = OwnedPath::root();
+    // The number of enclosing nodes that have no segment because they sit
+    // below a key that is not a string. The path is only known at zero.
+    let mut unknown = 0;
+    let mut stack: Vec<Frame<'_>> = Vec::new();
+    for (event, mark) in &document.events {
+        if let Event::SequenceEnd | Event::MappingEnd = event {
+            if let Some(frame) = stack.pop() {
+                if frame.pushed {
+                    let _ = path.pop();
+                }
+                if frame.unknown {
+                    unknown -= 1;
+                }
+            }
+            if let Some(parent) = stack.last_mut() {
+                parent.nodes += 1;
+            }
+            continue;
+        }
+        if let Event::Void = event {
+            continue;
+        }
+
+        // The segment of this node below its parent.
+        let scalar = match event {
+            Event::Scalar(scalar) => Some(scalar),
+            _ => None,
+        };
+        let segment = match stack.last_mut() {
+            None => None,
+            Some(frame) if !frame.mapping => {
+                Some(Segment::Index(frame.nodes))
+            }
+            Some(frame) if frame.nodes % 2 == 1 => frame.key.take(),
+            Some(frame) => {
+                let key = scalar.and_then(|scalar| {
+                    Some((
+                        &*scalar.value,
+                        str::from_utf8(&scalar.value).ok()?,
+                    ))
+                });
+                frame.key = key.map(|(_, key)| Segment::from(key));
+                if let Some((bytes, text)) = key {
+                    if let Some(first) = frame.keys.insert(bytes, *mark)
+                    {
+                        let _ = frame.keys.insert(bytes, first);
+                        let entry =
+                            (unknown == 0).then(|| path.join(text));
+                        warn(
+                            WarningKind::DuplicateKey,
+                            format!(
+                                "duplicate key `{}`, first found at line {} column {}",
+                                text,
+                                first.line() + 1,
+                                first.column() + 1,
+                            ),
+                            entry,
+                            *mark,
+                        );
+                    }
+                }
+                frame.key.clone()
+            }
+        };
+        let pushed = segment.is_some();
+        let below_unknown = !pushed && !stack.is_empty();
+        if let Some(segment) = segment {
+            path.push(segment);
+        }
+        if below_unknown {
+            unknown += 1;
+        }
+        let known = (unknown == 0).then(|| path.clone());
+
+        if let Some(name) = unused_anchors.get(&(mark.index() as usize))
+        {
+            warn(
+                WarningKind::UnusedAnchor,
+                format!("anchor `{}` is never used", name),
+                known.clone(),
+                *mark,
+            );
+        }
+        match event {
+            Event::SequenceStart(_) | Event::MappingStart(_) => {
+                stack.push(Frame {
+                    mapping: matches!(event, Event::MappingStart(_)),
+                    nodes: 0,
+                    keys: BTreeMap::new(),
+                    key: None,
+                    pushed,
+                    unknown: below_unknown,
+                });
+                continue;
+            }
+            Event::Scalar(scalar)
+                if scalar.style == ScalarStyle::Plain
+                    && scalar.tag.is_none() =>
+            {
+                if let Ok(v) = str::from_utf8(&scalar.value) {
+                    if let Some(reading) = yaml_1_1_reading(v) {
+                        warn(
+                            WarningKind::Yaml11Scalar,
+                            format!(
+                                "found {:?}, which {} but YAML 1.2 reads as a string",
+                                v, reading,
+                            ),
+                            known.clone(),
+                            *mark,
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+        if pushed {
+            let _ = path.pop();
+        }
+        if below_unknown {
+            unknown -= 1;
+        }
+        if let Some(parent) = stack.last_mut() {
+            parent.nodes += 1;
+        }
+    }
+    warnings
+}
+
+/// Returns whether `v` is a decimal integer that `float` does not hold
+/// exactly.
+fn loses_precision(v: &str, float: f64) -> bool {
+    let digits = v.strip_prefix(['-', '+']).unwrap_or(v);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit())
+    {
+        return false;
+    }
+    let digits = digits.trim_start_matches('0');
+    let digits = if digits.is_empty() { "0" } else { digits };
+    format!("{:.0}", float.abs()) != digits
+}
+
+/// Returns how legacy YAML parsers read an untagged plain scalar that is a
+/// string under YAML 1.2.
+fn yaml_1_1_reading(v: &str) -> Option<&'static str> {
+    let unsigned = v.strip_prefix(['-', '+']).unwrap_or(v);
+    let digits = |s: &str| {
+        !s.is_empty()
+            && s.bytes().all(|b| b.is_ascii_digit() || b == b'_')
+    };
+    if unsigned.len() > 1
+        && unsigned.starts_with('0')
+        && unsigned[1..].bytes().any(|b| b.is_ascii_digit())
+        && unsigned[1..]
+            .bytes()
+            .all(|b| matches!(b, b'0'..=b'7' | b'_'))
+    {
+        return Some("YAML 1.1 reads as an octal integer");
+    }
+    let (whole, fraction) = match unsigned.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (unsigned, None),
+    };
+    let mut parts = whole.split(':');
+    let first = parts.next().unwrap_or("");
+    // The integer form may not start with a zero, the float form may.
+    let mut sexagesimal = digits(first)
+        && match fraction {
+            Some(fraction) => fraction.is_empty() || digits(fraction),
+            None => !first.starts_with(['0', '_']),
+        };
+    let mut colons = 0;
+    for part in parts {
+        colons += 1;
+        sexagesimal &= matches!(
+            part.as_bytes(),
+            [b'0'..=b'9'] | [b'0'..=b'5', b'0'..=b'9']
+        );
+    }
+    if sexagesimal && colons > 0 {
+        return Some("YAML 1.1 reads as a sexagesimal number");
+    }
+    if v.contains('_')
+        && unsigned
+            .starts_with(|c: char| c.is_ascii_digit() || c == '.')
+    {
+        let plain: String = v.chars().filter(|&c| c != '_').collect();
+        if parse_signed_int(&plain, i128::from_str_radix).is_some()
+            || parse_unsigned_int(&plain, u128::from_str_radix)
+                .is_some()
+            || parse_f64(&plain).is_some()
+        {
+            return Some("YAML 1.1 reads as a number");
+        }
+    }
+    if parse_yaml_1_1_bool(v).is_some() {
+        return Some("YAML 1.1 reads as a boolean");
+    }
+    let lower = unsigned.strip_prefix('.').unwrap_or(unsigned);
+    let lower = lower.to_ascii_lowercase();
+    if parse_f64(v).is_none() {
+        if lower == "nan" {
+            return Some("some YAML parsers read as NaN");
+        }
+        if lower == "inf" || lower == "infinity" {
+            return Some("some YAML parsers read as infinity");
+        }
+    }
+    None
+}
+
+pub(crate) fn parse_null(scalar: &[u8]) -> Option<()> {
     match scalar {
         b"null" | b"Null" | b"NULL" | b"~" => Some(()),
         _ => None,
@@ -1213,6 +3100,21 @@ fn parse_bool(scalar: &str) -> Option<bool> {
     }
 }
 
+/// Parses the YAML 1.1 spellings of a boolean, such as `yes` and `off`.
+/// They are strings under YAML 1.2 and read as booleans only under an
+/// explicit `!!bool` tag.
+fn parse_yaml_1_1_bool(scalar: &str) -> Option<bool> {
+    match scalar {
+        "y" | "Y" | "yes" | "Yes" | "YES" | "on" | "On" | "ON" => {
+            Some(true)
+        }
+        "n" | "N" | "no" | "No" | "NO" | "off" | "Off" | "OFF" => {
+            Some(false)
+        }
+        _ => None,
+    }
+}
+
 fn parse_unsigned_int<T>(
     scalar: &str,
     from_str_radix: fn(&str, radix: u32) -> Result<T, ParseIntError>,
@@ -1367,6 +3269,39 @@ pub(crate) fn parse_f64(scalar: &str) -> Option<f64> {
     None
 }
 
+/// Rewrites a string holding a number in the loose forms accepted by
+/// [`DeserializerOptions::lenient_numbers`] into a form the number parsers
+/// accept, or returns `None` if it has misplaced underscores.
+pub(crate) fn normalize_numeric_string(scalar: &str) -> Option<String> {
+    let scalar = scalar.trim();
+    let bytes = scalar.as_bytes();
+    let mut normalized = String::with_capacity(scalar.len());
+    for (i, ch) in scalar.char_indices() {
+        if ch == '_' {
+            let between_digits = i > 0
+                && bytes[i - 1].is_ascii_alphanumeric()
+                && bytes
+                    .get(i + 1)
+                    .map_or(false, u8::is_ascii_alphanumeric);
+            if !between_digits {
+                return None;
+            }
+        } else {
+            normalized.push(ch);
+        }
+    }
+    if digits_but_not_number(&normalized) {
+        let digits = normalized.trim_start_matches(['-', '+']);
+        let sign = &normalized[..normalized.len() - digits.len()];
+        let digits = match digits.trim_start_matches('0') {
+            "" => "0",
+            digits => digits,
+        };
+        normalized = format!("{}{}", sign, digits);
+    }
+    Some(normalized)
+}
+
 pub(crate) fn digits_but_not_number(scalar: &str) -> bool {
     // Leading zero(s) followed by numeric characters is a string according to
     // the YAML 1.2 spec. https://yaml.org/spec/1.2/spec.html#id2761292
@@ -1428,9 +3363,44 @@ where
     if let Some(int) = parse_negative_int(v, i128::from_str_radix) {
         return Ok(visitor.visit_i128(int));
     }
+    #[cfg(feature = "bigint")]
+    if let Some(int) = parse_big_int(v) {
+        return Ok(crate::number::visit_big_integer(&int, visitor));
+    }
     Err(visitor)
 }
 
+/// Parses an integer of any size, in the forms accepted for `i128`.
+#[cfg(feature = "bigint")]
+pub(crate) fn parse_big_int(
+    scalar: &str,
+) -> Option<num_bigint::BigInt> {
+    let (negative, unsigned) = match scalar.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, scalar.strip_prefix('+').unwrap_or(scalar)),
+    };
+    let (radix, digits) =
+        if let Some(rest) = unsigned.strip_prefix("0x") {
+            (16, rest)
+        } else if let Some(rest) = unsigned.strip_prefix("0o") {
+            (8, rest)
+        } else if let Some(rest) = unsigned.strip_prefix("0b") {
+            (2, rest)
+        } else if digits_but_not_number(unsigned) {
+            return None;
+        } else {
+            (10, unsigned)
+        };
+    if digits.is_empty()
+        || !digits.chars().all(|digit| digit.is_digit(radix))
+    {
+        return None;
+    }
+    let int =
+        num_bigint::BigInt::parse_bytes(digits.as_bytes(), radix)?;
+    Some(if negative { -int } else { int })
+}
+
 pub(crate) fn visit_untagged_scalar<'de, V>(
     visitor: V,
     v: &str,
@@ -1462,18 +3432,240 @@ where
     }
 }
 
-fn is_plain_or_tagged_literal_scalar(
+/// Visits a scalar that is expected to hold exactly one `char`.
+///
+/// Scalars made of several characters are rejected with an error naming the
+/// offending value and its length. When `compose_graphemes` is enabled, a
+/// base character followed by a single combining mark is composed into its
+/// precomposed form first.
+pub(crate) fn visit_char<'de, V>(
+    visitor: V,
+    v: &str,
+    compose_graphemes: bool,
+) -> Result<V::Value>
+where
+    V: Visitor<'de>,
+{
+    let mut chars = v.chars();
+    match (chars.next(), chars.next(), chars.next()) {
+        (Some(ch), None, _) => visitor.visit_char(ch),
+        (Some(base), Some(mark), None) if compose_graphemes => {
+            match compose_pair(base, mark) {
+                Some(ch) => visitor.visit_char(ch),
+                None => Err(de::Error::custom(format_args!(
+                    "invalid value: string {:?} is not a single grapheme that can be composed into a character, expected a character",
+                    v,
+                ))),
+            }
+        }
+        (None, _, _) => Err(de::Error::invalid_value(
+            Unexpected::Str(v),
+            &"a character",
+        )),
+        _ => Err(de::Error::custom(format_args!(
+            "invalid value: string {:?} has length {}, expected a single character",
+            v,
+            v.chars().count(),
+        ))),
+    }
+}
+
+fn compose_pair(base: char, mark: char) -> Option<char> {
+    LATIN_COMPOSITIONS
+        .iter()
+        .find(|&&(b, m, _)| b == base && m == mark)
+        .map(|&(_, _, composed)| composed)
+}
+
+/// Canonical compositions of a base character and a combining mark for the
+/// Latin-1 Supplement and Latin Extended-A blocks.
+const LATIN_COMPOSITIONS: &[(char, char, char)] = &[
+    ('A', '\u{300}', '\u{c0}'),
+    ('A', '\u{301}', '\u{c1}'),
+    ('A', '\u{302}', '\u{c2}'),
+    ('A', '\u{303}', '\u{c3}'),
+    ('A', '\u{308}', '\u{c4}'),
+    ('A', '\u{30a}', '\u{c5}'),
+    ('C', '\u{327}', '\u{c7}'),
+    ('E', '\u{300}', '\u{c8}'),
+    ('E', '\u{301}', '\u{c9}'),
+    ('E', '\u{302}', '\u{ca}'),
+    ('E', '\u{308}', '\u{cb}'),
+    ('I', '\u{300}', '\u{cc}'),
+    ('I', '\u{301}', '\u{cd}'),
+    ('I', '\u{302}', '\u{ce}'),
+    ('I', '\u{308}', '\u{cf}'),
+    ('N', '\u{303}', '\u{d1}'),
+    ('O', '\u{300}', '\u{d2}'),
+    ('O', '\u{301}', '\u{d3}'),
+    ('O', '\u{302}', '\u{d4}'),
+    ('O', '\u{303}', '\u{d5}'),
+    ('O', '\u{308}', '\u{d6}'),
+    ('U', '\u{300}', '\u{d9}'),
+    ('U', '\u{301}', '\u{da}'),
+    ('U', '\u{302}', '\u{db}'),
+    ('U', '\u{308}', '\u{dc}'),
+    ('Y', '\u{301}', '\u{dd}'),
+    ('a', '\u{300}', '\u{e0}'),
+    ('a', '\u{301}', '\u{e1}'),
+    ('a', '\u{302}', '\u{e2}'),
+    ('a', '\u{303}', '\u{e3}'),
+    ('a', '\u{308}', '\u{e4}'),
+    ('a', '\u{30a}', '\u{e5}'),
+    ('c', '\u{327}', '\u{e7}'),
+    ('e', '\u{300}', '\u{e8}'),
+    ('e', '\u{301}', '\u{e9}'),
+    ('e', '\u{302}', '\u{ea}'),
+    ('e', '\u{308}', '\u{eb}'),
+    ('i', '\u{300}', '\u{ec}'),
+    ('i', '\u{301}', '\u{ed}'),
+    ('i', '\u{302}', '\u{ee}'),
+    ('i', '\u{308}', '\u{ef}'),
+    ('n', '\u{303}', '\u{f1}'),
+    ('o', '\u{300}', '\u{f2}'),
+    ('o', '\u{301}', '\u{f3}'),
+    ('o', '\u{302}', '\u{f4}'),
+    ('o', '\u{303}', '\u{f5}'),
+    ('o', '\u{308}', '\u{f6}'),
+    ('u', '\u{300}', '\u{f9}'),
+    ('u', '\u{301}', '\u{fa}'),
+    ('u', '\u{302}', '\u{fb}'),
+    ('u', '\u{308}', '\u{fc}'),
+    ('y', '\u{301}', '\u{fd}'),
+    ('y', '\u{308}', '\u{ff}'),
+    ('A', '\u{304}', '\u{100}'),
+    ('a', '\u{304}', '\u{101}'),
+    ('A', '\u{306}', '\u{102}'),
+    ('a', '\u{306}', '\u{103}'),
+    ('A', '\u{328}', '\u{104}'),
+    ('a', '\u{328}', '\u{105}'),
+    ('C', '\u{301}', '\u{106}'),
+    ('c', '\u{301}', '\u{107}'),
+    ('C', '\u{302}', '\u{108}'),
+    ('c', '\u{302}', '\u{109}'),
+    ('C', '\u{307}', '\u{10a}'),
+    ('c', '\u{307}', '\u{10b}'),
+    ('C', '\u{30c}', '\u{10c}'),
+    ('c', '\u{30c}', '\u{10d}'),
+    ('D', '\u{30c}', '\u{10e}'),
+    ('d', '\u{30c}', '\u{10f}'),
+    ('E', '\u{304}', '\u{112}'),
+    ('e', '\u{304}', '\u{113}'),
+    ('E', '\u{306}', '\u{114}'),
+    ('e', '\u{306}', '\u{115}'),
+    ('E', '\u{307}', '\u{116}'),
+    ('e', '\u{307}', '\u{117}'),
+    ('E', '\u{328}', '\u{118}'),
+    ('e', '\u{328}', '\u{119}'),
+    ('E', '\u{30c}', '\u{11a}'),
+    ('e', '\u{30c}', '\u{11b}'),
+    ('G', '\u{302}', '\u{11c}'),
+    ('g', '\u{302}', '\u{11d}'),
+    ('G', '\u{306}', '\u{11e}'),
+    ('g', '\u{306}', '\u{11f}'),
+    ('G', '\u{307}', '\u{120}'),
+    ('g', '\u{307}', '\u{121}'),
+    ('G', '\u{327}', '\u{122}'),
+    ('g', '\u{327}', '\u{123}'),
+    ('H', '\u{302}', '\u{124}'),
+    ('h', '\u{302}', '\u{125}'),
+    ('I', '\u{303}', '\u{128}'),
+    ('i', '\u{303}', '\u{129}'),
+    ('I', '\u{304}', '\u{12a}'),
+    ('i', '\u{304}', '\u{12b}'),
+    ('I', '\u{306}', '\u{12c}'),
+    ('i', '\u{306}', '\u{12d}'),
+    ('I', '\u{328}', '\u{12e}'),
+    ('i', '\u{328}', '\u{12f}'),
+    ('I', '\u{307}', '\u{130}'),
+    ('J', '\u{302}', '\u{134}'),
+    ('j', '\u{302}', '\u{135}'),
+    ('K', '\u{327}', '\u{136}'),
+    ('k', '\u{327}', '\u{137}'),
+    ('L', '\u{301}', '\u{139}'),
+    ('l', '\u{301}', '\u{13a}'),
+    ('L', '\u{327}', '\u{13b}'),
+    ('l', '\u{327}', '\u{13c}'),
+    ('L', '\u{30c}', '\u{13d}'),
+    ('l', '\u{30c}', '\u{13e}'),
+    ('N', '\u{301}', '\u{143}'),
+    ('n', '\u{301}', '\u{144}'),
+    ('N', '\u{327}', '\u{145}'),
+    ('n', '\u{327}', '\u{146}'),
+    ('N', '\u{30c}', '\u{147}'),
+    ('n', '\u{30c}', '\u{148}'),
+    ('O', '\u{304}', '\u{14c}'),
+    ('o', '\u{304}', '\u{14d}'),
+    ('O', '\u{306}', '\u{14e}'),
+    ('o', '\u{306}', '\u{14f}'),
+    ('O', '\u{30b}', '\u{150}'),
+    ('o', '\u{30b}', '\u{151}'),
+    ('R', '\u{301}', '\u{154}'),
+    ('r', '\u{301}', '\u{155}'),
+    ('R', '\u{327}', '\u{156}'),
+    ('r', '\u{327}', '\u{157}'),
+    ('R', '\u{30c}', '\u{158}'),
+    ('r', '\u{30c}', '\u{159}'),
+    ('S', '\u{301}', '\u{15a}'),
+    ('s', '\u{301}', '\u{15b}'),
+    ('S', '\u{302}', '\u{15c}'),
+    ('s', '\u{302}', '\u{15d}'),
+    ('S', '\u{327}', '\u{15e}'),
+    ('s', '\u{327}', '\u{15f}'),
+    ('S', '\u{30c}', '\u{160}'),
+    ('s', '\u{30c}', '\u{161}'),
+    ('T', '\u{327}', '\u{162}'),
+    ('t', '\u{327}', '\u{163}'),
+    ('T', '\u{30c}', '\u{164}'),
+    ('t', '\u{30c}', '\u{165}'),
+    ('U', '\u{303}', '\u{168}'),
+    ('u', '\u{303}', '\u{169}'),
+    ('U', '\u{304}', '\u{16a}'),
+    ('u', '\u{304}', '\u{16b}'),
+    ('U', '\u{306}', '\u{16c}'),
+    ('u', '\u{306}', '\u{16d}'),
+    ('U', '\u{30a}', '\u{16e}'),
+    ('u', '\u{30a}', '\u{16f}'),
+    ('U', '\u{30b}', '\u{170}'),
+    ('u', '\u{30b}', '\u{171}'),
+    ('U', '\u{328}', '\u{172}'),
+    ('u', '\u{328}', '\u{173}'),
+    ('W', '\u{302}', '\u{174}'),
+    ('w', '\u{302}', '\u{175}'),
+    ('Y', '\u{302}', '\u{176}'),
+    ('y', '\u{302}', '\u{177}'),
+    ('Y', '\u{308}', '\u{178}'),
+    ('Z', '\u{301}', '\u{179}'),
+    ('z', '\u{301}', '\u{17a}'),
+    ('Z', '\u{307}', '\u{17b}'),
+    ('z', '\u{307}', '\u{17c}'),
+    ('Z', '\u{30c}', '\u{17d}'),
+    ('z', '\u{30c}', '\u{17e}'),
+];
+
+/// Returns whether `scalar` may be read as the core type whose tag is
+/// `expected`. A scalar with an explicit core tag, such as `!!int "42"`, is
+/// read as the type of its tag whatever its style, so `!!str 123` is not an
+/// integer. Other scalars are read as the type only when plain.
+fn is_plain_or_core_tagged_scalar(
     expected: &str,
     scalar: &Scalar<'_>,
     tagged_already: bool,
 ) -> bool {
-    match (scalar.style, &scalar.tag, tagged_already) {
-        (ScalarStyle::Plain, _, _) => true,
-        (ScalarStyle::Literal, Some(tag), false) => tag == expected,
-        _ => false,
+    match (&scalar.tag, tagged_already) {
+        (Some(tag), false) if is_core_tag(tag) => {
+            tag == expected
+                || (expected == Tag::FLOAT && tag == Tag::INT)
+        }
+        _ => scalar.style == ScalarStyle::Plain,
     }
 }
 
+/// Returns whether `tag` belongs to the YAML core schema, as `!!int` does.
+fn is_core_tag(tag: &Tag) -> bool {
+    tag.starts_with(Tag::CORE_PREFIX).unwrap_or(false)
+}
+
 fn invalid_type(event: &Event<'_>, exp: &dyn Expected) -> Error {
     enum Void {}
 
@@ -1556,11 +3748,19 @@ impl<'de> de::Deserializer<'de>
                         enum_tag(&scalar.tag, tagged_already)
                     {
                         *self.pos -= 1;
-                        break visitor.visit_enum(EnumAccess {
-                            de: self,
-                            name: None,
-                            tag,
-                        });
+                        break visit_tagged(
+                            visitor,
+                            EnumAccess {
+                                de: self,
+                                name: None,
+                                tag,
+                            },
+                        );
+                    }
+                    if scalar.style == ScalarStyle::Plain
+                        && scalar.tag.is_none()
+                    {
+                        self.check_lossy_number(&scalar.value, mark);
                     }
                     break visit_scalar(
                         visitor,
@@ -1573,11 +3773,14 @@ impl<'de> de::Deserializer<'de>
                         enum_tag(&sequence.tag, tagged_already)
                     {
                         *self.pos -= 1;
-                        break visitor.visit_enum(EnumAccess {
-                            de: self,
-                            name: None,
-                            tag,
-                        });
+                        break visit_tagged(
+                            visitor,
+                            EnumAccess {
+                                de: self,
+                                name: None,
+                                tag,
+                            },
+                        );
                     }
                     break self.visit_sequence(visitor, mark);
                 }
@@ -1586,13 +3789,16 @@ impl<'de> de::Deserializer<'de>
                         enum_tag(&mapping.tag, tagged_already)
                     {
                         *self.pos -= 1;
-                        break visitor.visit_enum(EnumAccess {
-                            de: self,
-                            name: None,
-                            tag,
-                        });
+                        break visit_tagged(
+                            visitor,
+                            EnumAccess {
+                                de: self,
+                                name: None,
+                                tag,
+                            },
+                        );
                     }
-                    break self.visit_mapping(visitor, mark);
+                    break self.visit_mapping(visitor, mark, None);
                 }
                 Event::SequenceEnd => {
                     panic!("unexpected end of sequence")
@@ -1623,14 +3829,20 @@ impl<'de> de::Deserializer<'de>
                         .deserialize_bool(visitor)
                 }
                 Event::Scalar(scalar)
-                    if is_plain_or_tagged_literal_scalar(
+                    if is_plain_or_core_tagged_scalar(
                         Tag::BOOL,
                         scalar,
                         tagged_already,
                     ) =>
                 {
                     if let Ok(value) = str::from_utf8(&scalar.value) {
-                        if let Some(boolean) = parse_bool(value) {
+                        let explicit = !tagged_already
+                            && matches!(&scalar.tag, Some(tag) if tag == Tag::BOOL);
+                        let boolean = match parse_bool(value) {
+                            None if explicit => parse_yaml_1_1_bool(value),
+                            boolean => boolean,
+                        };
+                        if let Some(boolean) = boolean {
                             break visitor.visit_bool(boolean);
                         }
                     }
@@ -1676,7 +3888,7 @@ impl<'de> de::Deserializer<'de>
                     break self.jump(&mut pos)?.deserialize_i64(visitor)
                 }
                 Event::Scalar(scalar)
-                    if is_plain_or_tagged_literal_scalar(
+                    if is_plain_or_core_tagged_scalar(
                         Tag::INT,
                         scalar,
                         tagged_already,
@@ -1692,6 +3904,13 @@ impl<'de> de::Deserializer<'de>
                 }
                 _ => {}
             }
+            if let Some(value) = self.lenient_number(next) {
+                if let Some(number) =
+                    parse_signed_int(&value, i64::from_str_radix)
+                {
+                    break visitor.visit_i64(number);
+                }
+            }
             break Err(invalid_type(next, &visitor));
         }
         .map_err(|err| error::fix_mark(err, mark, self.path))
@@ -1712,7 +3931,7 @@ impl<'de> de::Deserializer<'de>
                         .deserialize_i128(visitor)
                 }
                 Event::Scalar(scalar)
-                    if is_plain_or_tagged_literal_scalar(
+                    if is_plain_or_core_tagged_scalar(
                         Tag::INT,
                         scalar,
                         tagged_already,
@@ -1729,6 +3948,13 @@ impl<'de> de::Deserializer<'de>
                 }
                 _ => {}
             }
+            if let Some(value) = self.lenient_number(next) {
+                if let Some(number) =
+                    parse_signed_int(&value, i128::from_str_radix)
+                {
+                    break visitor.visit_i128(number);
+                }
+            }
             break Err(invalid_type(next, &visitor));
         }
         .map_err(|err| error::fix_mark(err, mark, self.path))
@@ -1768,7 +3994,7 @@ impl<'de> de::Deserializer<'de>
                     break self.jump(&mut pos)?.deserialize_u64(visitor)
                 }
                 Event::Scalar(scalar)
-                    if is_plain_or_tagged_literal_scalar(
+                    if is_plain_or_core_tagged_scalar(
                         Tag::INT,
                         scalar,
                         tagged_already,
@@ -1785,6 +4011,13 @@ impl<'de> de::Deserializer<'de>
                 }
                 _ => {}
             }
+            if let Some(value) = self.lenient_number(next) {
+                if let Some(number) =
+                    parse_unsigned_int(&value, u64::from_str_radix)
+                {
+                    break visitor.visit_u64(number);
+                }
+            }
             break Err(invalid_type(next, &visitor));
         }
         .map_err(|err| error::fix_mark(err, mark, self.path))
@@ -1805,7 +4038,7 @@ impl<'de> de::Deserializer<'de>
                         .deserialize_u128(visitor)
                 }
                 Event::Scalar(scalar)
-                    if is_plain_or_tagged_literal_scalar(
+                    if is_plain_or_core_tagged_scalar(
                         Tag::INT,
                         scalar,
                         tagged_already,
@@ -1822,6 +4055,13 @@ impl<'de> de::Deserializer<'de>
                 }
                 _ => {}
             }
+            if let Some(value) = self.lenient_number(next) {
+                if let Some(number) =
+                    parse_unsigned_int(&value, u128::from_str_radix)
+                {
+                    break visitor.visit_u128(number);
+                }
+            }
             break Err(invalid_type(next, &visitor));
         }
         .map_err(|err| error::fix_mark(err, mark, self.path))
@@ -1847,7 +4087,7 @@ impl<'de> de::Deserializer<'de>
                     break self.jump(&mut pos)?.deserialize_f64(visitor)
                 }
                 Event::Scalar(scalar)
-                    if is_plain_or_tagged_literal_scalar(
+                    if is_plain_or_core_tagged_scalar(
                         Tag::FLOAT,
                         scalar,
                         tagged_already,
@@ -1855,12 +4095,18 @@ impl<'de> de::Deserializer<'de>
                 {
                     if let Ok(value) = str::from_utf8(&scalar.value) {
                         if let Some(float) = parse_f64(value) {
+                            self.warn_lossy_number(value, float, mark);
                             break visitor.visit_f64(float);
                         }
                     }
                 }
                 _ => {}
             }
+            if let Some(value) = self.lenient_number(next) {
+                if let Some(number) = parse_f64(&value) {
+                    break visitor.visit_f64(number);
+                }
+            }
             break Err(invalid_type(next, &visitor));
         }
         .map_err(|err| error::fix_mark(err, mark, self.path))
@@ -1870,7 +4116,25 @@ impl<'de> de::Deserializer<'de>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_str(visitor)
+        let (next, mark) = self.next_event_mark()?;
+        match next {
+            Event::Scalar(scalar) => {
+                if let Ok(v) = str::from_utf8(&scalar.value) {
+                    visit_char(
+                        visitor,
+                        v,
+                        self.options.compose_graphemes,
+                    )
+                } else {
+                    Err(invalid_type(next, &visitor))
+                }
+            }
+            Event::Alias(mut pos) => {
+                self.jump(&mut pos)?.deserialize_char(visitor)
+            }
+            other => Err(invalid_type(other, &visitor)),
+        }
+        .map_err(|err: Error| error::fix_mark(err, mark, self.path))
     }
 
     fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
@@ -2034,12 +4298,18 @@ impl<'de> de::Deserializer<'de>
     /// Parses a newtype struct as the underlying value.
     fn deserialize_newtype_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if name == RAW_YAML {
+            return self.deserialize_raw(visitor);
+        }
+        if let Some(names) = name.strip_prefix(UNTAGGED) {
+            return self.deserialize_untagged(names, visitor);
+        }
         let (_event, mark) = self.peek_event_mark()?;
         self.recursion_check(mark, |de| {
             visitor.visit_newtype_struct(de)
@@ -2107,45 +4377,24 @@ impl<'de> de::Deserializer<'de>
     where
         V: Visitor<'de>,
     {
-        let (next, mark) = self.next_event_mark()?;
-        match next {
-            Event::Alias(mut pos) => {
-                self.jump(&mut pos)?.deserialize_map(visitor)
-            }
-            Event::MappingStart(_) => self.visit_mapping(visitor, mark),
-            other => {
-                if match other {
-                    Event::Void => true,
-                    Event::Scalar(scalar) => {
-                        scalar.value.is_empty()
-                            && scalar.style == ScalarStyle::Plain
-                    }
-                    _ => false,
-                } {
-                    visitor.visit_map(MapAccess {
-                        empty: true,
-                        de: self,
-                        len: 0,
-                        key: None,
-                    })
-                } else {
-                    Err(invalid_type(other, &visitor))
-                }
-            }
-        }
-        .map_err(|err| error::fix_mark(err, mark, self.path))
+        self.deserialize_mapping(visitor, None)
     }
 
     fn deserialize_struct<V>(
         self,
         _name: &'static str,
-        _fields: &'static [&'static str],
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_map(visitor)
+        let fields = if self.options.deny_unknown_fields {
+            Some(fields)
+        } else {
+            None
+        };
+        self.deserialize_mapping(visitor, fields)
     }
 
     /// Parses an enum as a single key:value pair where the key identifies the
@@ -2241,6 +4490,17 @@ impl<'de> de::Deserializer<'de>
     where
         V: Visitor<'de>,
     {
+        if let Some(warnings) = self.warnings {
+            if self.path != Path::Root {
+                let (_, mark) = self.peek_event_mark()?;
+                warnings.borrow_mut().push(Warning {
+                    kind: WarningKind::UnusedValue,
+                    message: "value is not used".to_owned(),
+                    path: OwnedPath::try_from(&self.path).ok(),
+                    location: Location::from_mark(mark),
+                });
+            }
+        }
         self.ignore_any()?;
         visitor.visit_unit()
     }
@@ -2287,12 +4547,387 @@ where
     T::deserialize(Deserializer::from_str(s))
 }
 
+/// Deserialize an instance of type `T` from the node at `path` within a
+/// string of YAML text.
+///
+/// The path is written in either form accepted by
+/// [`Path`](crate::path::Path), such as `spec.template` or
+/// `/spec/template`. Only the node at the path is deserialized: the nodes
+/// around it are skipped event by event, without building a
+/// [`Value`](crate::Value) of the document, which makes this cheap for one
+/// section of a large document. Errors within the node report their path
+/// from the root of the document.
+///
+/// # Errors
+///
+/// Returns an error if the path is malformed, if the document has no node
+/// at the path, or for the same reasons as [`from_str`].
+///
+/// # Examples
+///
+/// ```
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Container {
+///     image: String,
+/// }
+///
+/// let yaml = "
+/// metadata: { name: web }
+/// spec:
+///   containers:
+///     - image: nginx
+///     - image: redis
+/// ";
+/// let container: Container =
+///     serde_yml::from_str_at(yaml, "spec.containers[1]").unwrap();
+/// assert_eq!(container.image, "redis");
+///
+/// let err = serde_yml::from_str_at::<Container>(yaml, "spec.volumes");
+/// assert_eq!(
+///     err.unwrap_err().to_string(),
+///     "spec: missing key `volumes` at line 4 column 3",
+/// );
+/// ```
+pub fn from_str_at<'de, T>(s: &'de str, path: &str) -> Result<T>
+where
+    T: Deserialize<'de>,
+{
+    let path: OwnedPath = path.parse()?;
+    Deserializer::from_str(s).de(|state| {
+        state.descend(path.segments(), |node| T::deserialize(node))
+    })
+}
+
+/// Loads a single YAML document into a [`BorrowedValue`] whose strings
+/// borrow from `s` where possible.
+///
+/// This reads the same documents as `from_str::<Value>`, with the same
+/// errors, but without copying plain and quoted scalars that need no
+/// unescaping. Convert the result into a [`Value`](crate::Value) with
+/// `From` once it needs to outlive `s`.
+///
+/// # Errors
+///
+/// Returns an error if `s` is not valid YAML, holds more than one
+/// document, or has a mapping with a duplicate key.
+///
+/// # Examples
+///
+/// ```
+/// let value = serde_yml::from_str_borrowed("[a, 'b c', 1]\n").unwrap();
+/// let items = value.as_sequence().unwrap();
+/// assert_eq!(items[1].as_str(), Some("b c"));
+/// ```
+pub fn from_str_borrowed(s: &str) -> Result<BorrowedValue<'_>> {
+    BorrowedValue::deserialize(Deserializer::from_str(s))
+}
+
+/// Deserialize an instance of type `T` from a string of YAML text, and report
+/// the keys that `T` did not consume.
+///
+/// Deserialization succeeds exactly when [`from_str`] would. In addition,
+/// every mapping value or sequence element that the target type skipped,
+/// typically the value of a key that does not name any struct field, is
+/// returned as a [`Warning`] of kind [`WarningKind::UnusedValue`].
+/// Only the outermost skipped node is reported; its children are not listed
+/// separately. Keys collected by `#[serde(flatten)]` or examined by
+/// `#[serde(untagged)]` enums are buffered by serde and are not reported.
+///
+/// # Errors
+///
+/// Returns the same errors as [`from_str`].
+///
+/// # Examples
+///
+/// ```
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Config {
+///     retries: u32,
+/// }
+///
+/// let yaml = "retries: 3\nretrys: 5\n";
+/// let (config, unused) = serde_yml::from_str_with_unused::<Config>(yaml).unwrap();
+/// assert_eq!(config.retries, 3);
+/// assert_eq!(unused[0].to_string(), "retrys: value is not used at line 2 column 9");
+/// ```
+pub fn from_str_with_unused<'de, T>(
+    s: &'de str,
+) -> Result<(T, Vec<Warning>)>
+where
+    T: Deserialize<'de>,
+{
+    let warnings = Rc::new(RefCell::new(Vec::new()));
+    let mut deserializer = Deserializer::from_str(s);
+    deserializer.warnings = Some(Rc::clone(&warnings));
+    let value = T::deserialize(deserializer)?;
+    let mut unused = mem::take(&mut *warnings.borrow_mut());
+    unused.retain(|warning| warning.kind == WarningKind::UnusedValue);
+    Ok((value, unused))
+}
+
+/// Deserialize an instance of type `T` from a string of YAML text, and report
+/// the [`Warning`]s found in it.
+///
+/// Deserialization succeeds exactly when [`from_str`] would. The warnings
+/// point out input that is accepted but likely not what its author meant:
+/// duplicate keys, plain scalars that YAML 1.1 reads differently, anchors
+/// that are never used, values that `T` does not use and integers that
+/// lose precision when read as floats. They are meant to be surfaced, for
+/// example in CI, without making parsing stricter.
+///
+/// # Errors
+///
+/// Returns the same errors as [`from_str`].
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::de::WarningKind;
+/// use std::collections::HashMap;
+///
+/// let yaml = "
+/// base: &base 1
+/// enabled: yes
+/// enabled: no
+/// ";
+/// let output =
+///     serde_yml::from_str_with_warnings::<HashMap<String, String>>(yaml).unwrap();
+/// assert_eq!(output.value["enabled"], "no");
+///
+/// let warnings: Vec<_> = output.warnings.iter().map(ToString::to_string).collect();
+/// assert_eq!(
+///     warnings,
+///     [
+///         "base: anchor `base` is never used at line 2 column 7",
+///         "enabled: found \"yes\", which YAML 1.1 reads as a boolean but YAML 1.2 reads as a string at line 3 column 10",
+///         "enabled: duplicate key `enabled`, first found at line 3 column 1 at line 4 column 1",
+///         "enabled: found \"no\", which YAML 1.1 reads as a boolean but YAML 1.2 reads as a string at line 4 column 10",
+///     ],
+/// );
+/// assert_eq!(output.warnings[2].kind, WarningKind::DuplicateKey);
+/// ```
+pub fn from_str_with_warnings<'de, T>(
+    s: &'de str,
+) -> Result<ParseOutput<T>>
+where
+    T: Deserialize<'de>,
+{
+    with_warnings(Progress::Str(s))
+}
+
+/// Deserialize an instance of type `T` from bytes of YAML text, and report
+/// the [`Warning`]s found in it, as [`from_str_with_warnings`] does.
+///
+/// # Errors
+///
+/// Returns the same errors as [`from_slice`].
+pub fn from_slice_with_warnings<'de, T>(
+    v: &'de [u8],
+) -> Result<ParseOutput<T>>
+where
+    T: Deserialize<'de>,
+{
+    with_warnings(Progress::Slice(v))
+}
+
+/// Loads the single document of `progress`, collects its warnings and
+/// deserializes it.
+fn with_warnings<'de, T>(
+    progress: Progress<'de>,
+) -> Result<ParseOutput<T>>
+where
+    T: Deserialize<'de>,
+{
+    let mut loader = Loader::new(progress)?;
+    let document = match loader.next_document() {
+        Some(document) => document,
+        None => return Err(error::new(ErrorImpl::EndOfStream)),
+    };
+    let mut warnings = document_warnings(&document);
+    let found = Rc::new(RefCell::new(Vec::new()));
+    let value = T::deserialize(Deserializer {
+        progress: Progress::Document(document),
+        options: DeserializerOptions::default(),
+        warnings: Some(Rc::clone(&found)),
+    })?;
+    warnings.append(&mut found.borrow_mut());
+    warnings.sort_by_key(|warning| warning.location.index());
+    match loader.next_document() {
+        None => Ok(ParseOutput { value, warnings }),
+        Some(Document {
+            error: Some(parse_error),
+            ..
+        }) => Err(error::shared(parse_error)),
+        Some(_) => Err(error::new(ErrorImpl::MoreThanOneDocument)),
+    }
+}
+
+/// Deserialize an instance of type `T` from a string of YAML text, accepting
+/// nodes that carry more than one tag.
+///
+/// YAML allows a single tag per node, and [`from_str`] rejects input such as
+/// `!outer !inner x`. This function keeps the first tag of each such node,
+/// ignores the others and returns them as warnings of kind
+/// [`WarningKind::DroppedTag`].
+///
+/// # Errors
+///
+/// Returns the same errors as [`from_str`], other than for nodes with
+/// several tags.
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::Value;
+///
+/// let yaml = "shape: !circle !legacy {radius: 1}\n";
+/// let (value, dropped) = serde_yml::from_str_lenient_tags::<Value>(yaml).unwrap();
+/// assert_eq!(value["shape"]["radius"], 1);
+/// assert_eq!(
+///     dropped[0].to_string(),
+///     "dropped tag `!legacy` after tag `!circle` at line 1 column 16",
+/// );
+/// ```
+pub fn from_str_lenient_tags<T>(s: &str) -> Result<(T, Vec<Warning>)>
+where
+    T: DeserializeOwned,
+{
+    let mut loader = Loader::with_lenient_tags(Progress::Str(s))?;
+    let document = match loader.next_document() {
+        Some(document) => document,
+        None => return Err(error::new(ErrorImpl::EndOfStream)),
+    };
+    if loader.next_document().is_some() {
+        return Err(error::new(ErrorImpl::MoreThanOneDocument));
+    }
+    let value = T::deserialize(Deserializer::from_document(document))?;
+    Ok((value, loader.dropped_tags().to_vec()))
+}
+
+/// Parses a string of YAML text into a [`Value`](crate::Value), recovering
+/// from errors instead of failing on the first one.
+///
+/// Lines the parser cannot make sense of are skipped as described for
+/// [`Loader::recovering`], and so are lines holding a value that cannot be
+/// built, such as a duplicate key. The result is the value of whatever
+/// remains, or null if nothing does, together with a [`Diagnostic`] for
+/// each problem. Only the first document is kept; later documents are
+/// reported as a warning. This suits tools such as editors that need an
+/// outline of a file while it is being written.
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::loader::Severity;
+///
+/// let yaml = "name: demo\nitems: - 1\nport: 8080\n";
+/// let (value, diagnostics) = serde_yml::from_str_lossy(yaml);
+/// assert_eq!(value["name"], "demo");
+/// assert_eq!(value["port"], 8080);
+/// assert_eq!(diagnostics.len(), 1);
+/// assert_eq!(diagnostics[0].severity, Severity::Error);
+/// ```
+pub fn from_str_lossy(s: &str) -> (crate::Value, Vec<Diagnostic>) {
+    let mut loader = match Loader::recovering(Progress::Str(s)) {
+        Ok(loader) => loader,
+        Err(error) => {
+            let diagnostic = Diagnostic {
+                location: error.location(),
+                message: error.to_string(),
+                severity: Severity::Error,
+            };
+            return (crate::Value::Null, vec![diagnostic]);
+        }
+    };
+    let value = loop {
+        let document = match loader.next_document() {
+            Some(document) if document.error.is_none() => document,
+            _ => break crate::Value::Null,
+        };
+        match crate::Value::deserialize(Deserializer::from_document(
+            document,
+        )) {
+            Ok(value) => break value,
+            Err(error) => {
+                if !loader.skip_error(&error) {
+                    break crate::Value::Null;
+                }
+            }
+        }
+    };
+    let extra = loader.next_document().map(|document| Diagnostic {
+        location: document
+            .events
+            .first()
+            .map(|(_, mark)| Location::from_mark(*mark)),
+        message: "the input holds more than one document; only the first one was kept".to_owned(),
+        severity: Severity::Warning,
+    });
+    let mut diagnostics = loader.diagnostics().to_vec();
+    diagnostics.extend(extra);
+    (value, diagnostics)
+}
+
+/// Deserialize an instance of type `T` from a string of YAML text, recording
+/// the style in which each scalar was written.
+///
+/// Deserialization succeeds exactly when [`from_str`] would. In addition,
+/// the plain, single-quoted, double-quoted, literal or folded style of every
+/// scalar in the document is returned in a [`StyleMap`] keyed by its
+/// [`Path`](crate::path::Path), so that a validator can require that some
+/// values are quoted, or that others are not, while still deserializing
+/// into a typed structure.
+///
+/// # Errors
+///
+/// Returns the same errors as [`from_str`].
+///
+/// # Examples
+///
+/// ```
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Database {
+///     user: String,
+///     password: String,
+/// }
+///
+/// let yaml = "user: admin\npassword: 0123\n";
+/// let (database, styles) = serde_yml::from_str_with_styles::<Database>(yaml).unwrap();
+/// assert_eq!(database.password, "0123");
+/// assert!(!styles.is_quoted(&"password".parse().unwrap()));
+/// ```
+pub fn from_str_with_styles<T>(s: &str) -> Result<(T, StyleMap)>
+where
+    T: DeserializeOwned,
+{
+    let mut loader = Loader::new(Progress::Str(s))?;
+    let document = match loader.next_document() {
+        Some(document) => document,
+        None => return Err(error::new(ErrorImpl::EndOfStream)),
+    };
+    if loader.next_document().is_some() {
+        return Err(error::new(ErrorImpl::MoreThanOneDocument));
+    }
+    let styles = StyleMap::from_document(&document);
+    let value = T::deserialize(Deserializer::from_document(document))?;
+    Ok((value, styles))
+}
+
 /// Deserialize an instance of type `T` from an IO stream of YAML.
 ///
 /// This function reads YAML data from an IO stream and attempts to parse and deserialize it
 /// into an instance of the type `T`. The type must implement the `DeserializeOwned` trait
 /// from Serde, which means it must be able to be deserialized without any borrowed data.
 ///
+/// The stream may be encoded in UTF-8, UTF-16LE or UTF-16BE, with or
+/// without a byte order mark, as with [`from_slice`].
+///
 /// # Errors
 ///
 /// This conversion can fail if the structure of the YAML does not match the structure expected
@@ -2323,6 +4958,7 @@ where
 /// let config: Config = serde_yml::from_reader(reader).unwrap();
 /// println!("{:?}", config);
 /// ```
+#[cfg(feature = "std")]
 pub fn from_reader<R, T>(rdr: R) -> Result<T>
 where
     R: io::Read,
@@ -2331,12 +4967,65 @@ where
     T::deserialize(Deserializer::from_reader(rdr))
 }
 
+/// Deserializes an instance of type `T` from the YAML file at `path`.
+///
+/// The file is read whole and decoded as [`from_slice`] does, so a byte
+/// order mark is skipped and UTF-16 files are accepted. Every error,
+/// whether reading the file failed or its contents do not match `T`, names
+/// the file: its message starts with the path and [`Error::file`] returns
+/// it.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read, if it is not valid YAML,
+/// or if its contents cannot be deserialized into `T`.
+///
+/// # Examples
+///
+/// ```
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Config {
+///     port: u16,
+/// }
+///
+/// let path = std::env::temp_dir().join("serde_yml_doc_from_file.yaml");
+/// std::fs::write(&path, "port: eighty\n").unwrap();
+/// let err = serde_yml::from_file::<Config, _>(&path).unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     format!(
+///         "{}: port: invalid type: string \"eighty\", expected u16 at line 1 column 7",
+///         path.display(),
+///     ),
+/// );
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[cfg(feature = "std")]
+pub fn from_file<T, P>(path: P) -> Result<T>
+where
+    T: DeserializeOwned,
+    P: AsRef<std::path::Path>,
+{
+    let path = path.as_ref();
+    std::fs::read(path)
+        .map_err(|err| error::new(ErrorImpl::IoError(err)))
+        .and_then(|bytes| from_slice(&bytes))
+        .map_err(|err| error::in_file(err, path))
+}
+
 /// Deserialize an instance of type `T` from bytes of YAML text.
 ///
 /// This function takes a byte slice containing YAML data and attempts to parse and
 /// deserialize it into an instance of the type `T`. The type must implement the `Deserialize`
 /// trait from Serde.
 ///
+/// The bytes may be UTF-8, UTF-16LE or UTF-16BE. The encoding is taken from the byte order
+/// mark, or without one from where the null bytes fall in the first characters, and a UTF-8
+/// byte order mark is skipped. UTF-16 input is converted to UTF-8 first, so strings cannot be
+/// borrowed from it.
+///
 /// # Errors
 ///
 /// This conversion can fail if the structure of the YAML does not match the structure expected
@@ -2344,7 +5033,8 @@ where
 /// mapping. It can also fail if the structure is correct but `T`'s implementation of
 /// `Deserialize` decides that something is wrong with the data, for example required struct
 /// fields are missing from the YAML mapping or some number is too big to fit in the expected
-/// primitive type.
+/// primitive type. UTF-32 input and UTF-16 input with an odd number of bytes or an unpaired
+/// surrogate are rejected.
 ///
 /// # Examples
 ///
@@ -2371,3 +5061,160 @@ where
 {
     T::deserialize(Deserializer::from_slice(v))
 }
+
+/// Counts the documents of a stream of YAML text.
+///
+/// The input is parsed, so a `---` inside a block scalar or a quoted string
+/// is not mistaken for a document marker, but no document is deserialized.
+/// A stream holding nothing but comments has no documents, while a lone
+/// `---` starts an empty one.
+///
+/// # Errors
+///
+/// Returns an error if the input is not well-formed YAML.
+///
+/// # Examples
+///
+/// ```
+/// let yaml = "a: 1\n---\nscript: |\n  ---\n  echo\n";
+/// assert_eq!(serde_yml::de::document_count(yaml).unwrap(), 2);
+/// assert_eq!(serde_yml::de::document_count("# empty\n").unwrap(), 0);
+/// ```
+pub fn document_count(input: &str) -> Result<usize> {
+    Ok(document_spans(input)?.len())
+}
+
+/// Splits a stream of YAML text into the text of each of its documents.
+///
+/// Like [`document_count`], the boundaries come from parsing the input
+/// rather than from searching it for `---`. Each piece holds everything
+/// from the end of the previous document up to the end of the line on
+/// which its own ends, so the comments and directives before a document
+/// travel with it, and each piece parses on its own into the same
+/// document. Comments after the last
+/// document belong to it as well.
+///
+/// # Errors
+///
+/// Returns an error if the input is not well-formed YAML.
+///
+/// # Examples
+///
+/// ```
+/// let yaml = "a: 1\n---\nscript: |\n  ---\n  echo\n...\n# next\n--- b\n";
+/// let documents: Vec<&str> = serde_yml::de::split_documents(yaml).unwrap().collect();
+/// assert_eq!(
+///     documents,
+///     ["a: 1\n", "---\nscript: |\n  ---\n  echo\n...\n", "# next\n--- b\n"],
+/// );
+/// ```
+pub fn split_documents(input: &str) -> Result<SplitDocuments<'_>> {
+    let spans = document_spans(input)?;
+    Ok(SplitDocuments {
+        input,
+        spans: spans.into_iter(),
+    })
+}
+
+/// An iterator over the text of each document of a YAML stream.
+///
+/// This struct is created by [`split_documents`].
+#[derive(Debug)]
+pub struct SplitDocuments<'input> {
+    input: &'input str,
+    spans: vec::IntoIter<Range<usize>>,
+}
+
+impl<'input> Iterator for SplitDocuments<'input> {
+    type Item = &'input str;
+
+    fn next(&mut self) -> Option<&'input str> {
+        let span = self.spans.next()?;
+        Some(&self.input[span])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.spans.size_hint()
+    }
+}
+
+impl<'input> DoubleEndedIterator for SplitDocuments<'input> {
+    fn next_back(&mut self) -> Option<&'input str> {
+        let span = self.spans.next_back()?;
+        Some(&self.input[span])
+    }
+}
+
+impl ExactSizeIterator for SplitDocuments<'_> {}
+
+/// Gathers the anchors, tags, version directive and span of each document
+/// of a stream of YAML text, without deserializing the documents.
+///
+/// Like [`document_count`], a stream holding nothing but comments has no
+/// documents.
+///
+/// # Errors
+///
+/// Returns an error if the input is not well-formed YAML or if an alias
+/// refers to an anchor that is not defined before it.
+///
+/// # Examples
+///
+/// ```
+/// let input = "base: &base {retries: 3}\nold: &old 1\njob: *base\n---\n!pin {a: 1}\n";
+/// let metadata = serde_yml::de::document_metadata(input).unwrap();
+/// assert_eq!(metadata.len(), 2);
+///
+/// for anchor in &metadata[0].anchors {
+///     if anchor.aliases == 0 {
+///         // Prints "document 1 defines anchor `old` (unused) at line 2"
+///         println!(
+///             "document 1 defines anchor `{}` (unused) at line {}",
+///             anchor.name,
+///             anchor.location.line(),
+///         );
+///     }
+/// }
+/// assert_eq!(metadata[0].anchors[0].aliases, 1);
+/// assert_eq!(metadata[0].anchors[1].aliases, 0);
+/// assert_eq!(metadata[1].tags, ["!pin"]);
+/// ```
+pub fn document_metadata(input: &str) -> Result<Vec<DocumentMetadata>> {
+    let mut loader = Loader::new(Progress::Str(input))?;
+    let mut metadata = Vec::new();
+    while let Some(document) = loader.next_document() {
+        if let Some(error) = document.error {
+            return Err(error::shared(error));
+        }
+        if !document.is_placeholder() {
+            metadata.push(document.metadata());
+        }
+        loader.recycle(document);
+    }
+    Ok(metadata)
+}
+
+/// Returns the byte range of each document of `input`, from the end of the
+/// previous document to the end of the line on which its own ends.
+fn document_spans(input: &str) -> Result<Vec<Range<usize>>> {
+    let lines = LineIndex::new(input.as_bytes());
+    let mut parser = Parser::new(Cow::Borrowed(input.as_bytes()));
+    let mut spans = Vec::new();
+    let mut start = 0;
+    loop {
+        let (event, _, end) = parser.parse_next_event_with_end()?;
+        match event {
+            YamlEvent::DocumentEnd => {
+                let mut end = lines.offset(end).max(start);
+                if lines.starts.binary_search(&end).is_err() {
+                    end = line_break_end(lines.input, end)
+                        .unwrap_or(input.len());
+                }
+                spans.push(start..end);
+                start = end;
+            }
+            YamlEvent::StreamEnd => return Ok(spans),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,212 @@
+//! Loading a configuration split across a directory of YAML files.
+//!
+//! Servers commonly read their settings from a `conf.d` directory, in which
+//! each file holds part of the configuration and later files override
+//! earlier ones:
+//!
+//! ```text
+//! conf.d/
+//!   00-defaults.yaml
+//!   10-database.yaml
+//!   50-local.yml
+//! ```
+//!
+//! [`from_dir`] reads the `.yaml` and `.yml` files of such a directory in
+//! lexical order of their names, merges their documents as a
+//! [`MergeStrategy`] says, and deserializes the result. Errors name the
+//! file they come from, including errors about a value of the merged
+//! document, which are reported at that value in the last file that sets
+//! it.
+
+use crate::{
+    de::Deserializer,
+    loader::Document,
+    mapping::Entry,
+    modules::error::{self, Error, ErrorImpl, Result},
+    validate::locate_slice,
+    value::Value,
+};
+use serde::de::DeserializeOwned;
+use std::{
+    ffi::OsStr,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// How [`from_dir`] combines the documents of the files it reads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum MergeStrategy {
+    /// Mappings are merged key by key at every level, and any other value
+    /// of a later file replaces the earlier one. This is the default.
+    Deep,
+    /// The top-level keys of a later file replace the earlier values of
+    /// those keys whole.
+    Shallow,
+    /// Like [`Deep`](MergeStrategy::Deep), except that a sequence of a
+    /// later file is appended to the earlier sequence instead of
+    /// replacing it.
+    Append,
+}
+
+impl Default for MergeStrategy {
+    fn default() -> Self {
+        MergeStrategy::Deep
+    }
+}
+
+/// Deserializes an instance of type `T` from the YAML files of the
+/// directory at `path`, merged as `strategy` says.
+///
+/// Files with the `yaml` or `yml` extension are read like
+/// [`from_file`](crate::from_file) in lexical order of their names, so
+/// that later files override earlier ones. Hidden files, whose names start
+/// with `.`, subdirectories and files with other extensions are skipped,
+/// and so are empty files. A directory without any YAML file reads as an
+/// empty document.
+///
+/// # Errors
+///
+/// Returns an error if the directory or one of its files cannot be read,
+/// if a file is not valid YAML, or if the merged document cannot be
+/// deserialized into `T`. Every error names the file it comes from: an
+/// error about a value of the merged document is placed at that value in
+/// the last file that sets it.
+///
+/// # Examples
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_yml::dir::MergeStrategy;
+/// use std::fs;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Config {
+///     name: String,
+///     database: Database,
+/// }
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Database {
+///     host: String,
+///     port: u16,
+/// }
+///
+/// let dir = std::env::temp_dir().join("serde_yml_doc_conf.d");
+/// fs::create_dir_all(&dir).unwrap();
+/// fs::write(dir.join("00-defaults.yaml"), "name: api\ndatabase: {host: localhost, port: 5432}\n").unwrap();
+/// fs::write(dir.join("50-local.yml"), "database:\n  host: db.internal\n").unwrap();
+///
+/// let config: Config = serde_yml::from_dir(&dir, MergeStrategy::Deep).unwrap();
+/// assert_eq!(config.name, "api");
+/// assert_eq!(config.database.host, "db.internal");
+/// assert_eq!(config.database.port, 5432);
+///
+/// fs::write(dir.join("50-local.yml"), "database:\n  port: default\n").unwrap();
+/// let err = serde_yml::from_dir::<Config, _>(&dir, MergeStrategy::Deep).unwrap_err();
+/// assert_eq!(err.file(), Some(dir.join("50-local.yml").display().to_string().as_str()));
+/// assert_eq!(err.location().unwrap().line(), 2);
+/// # fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn from_dir<T, P>(path: P, strategy: MergeStrategy) -> Result<T>
+where
+    T: DeserializeOwned,
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let files = yaml_files(path).map_err(|err| {
+        error::in_file(error::new(ErrorImpl::IoError(err)), path)
+    })?;
+    let mut merged = Value::Null;
+    let mut sources = Vec::with_capacity(files.len());
+    for file in files {
+        let bytes = fs::read(&file)
+            .map_err(|err| error::new(ErrorImpl::IoError(err)))
+            .map_err(|err| error::in_file(err, &file))?;
+        let document: Value = crate::from_slice(&bytes)
+            .map_err(|err| error::in_file(err, &file))?;
+        if !document.is_null() {
+            merge(&mut merged, document, strategy);
+        }
+        sources.push((file, bytes));
+    }
+    let deserializer =
+        Deserializer::from_document(Document::from_value(&merged));
+    T::deserialize(deserializer).map_err(|err| place(err, &sources))
+}
+
+/// Lists the YAML files of `dir` in lexical order of their names.
+fn yaml_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let hidden = path.file_name().map_or(true, |name| {
+            name.to_string_lossy().starts_with('.')
+        });
+        let yaml = matches!(
+            path.extension().and_then(OsStr::to_str),
+            Some("yaml" | "yml")
+        );
+        if !hidden && yaml && path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Merges `overlay`, the document of a later file, into `base`.
+pub(crate) fn merge(
+    base: &mut Value,
+    overlay: Value,
+    strategy: MergeStrategy,
+) {
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.entry(key) {
+                    Entry::Occupied(mut entry)
+                        if strategy != MergeStrategy::Shallow =>
+                    {
+                        merge(entry.get_mut(), value, strategy);
+                    }
+                    Entry::Occupied(mut entry) => {
+                        let _ = entry.insert(value);
+                    }
+                    Entry::Vacant(entry) => {
+                        let _ = entry.insert(value);
+                    }
+                }
+            }
+        }
+        (Value::Sequence(base), Value::Sequence(overlay))
+            if strategy == MergeStrategy::Append =>
+        {
+            base.extend(overlay);
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Places an error about a value of the merged document at that value in
+/// the last of the files read into `sources` that sets it, or drops its
+/// position, which is only meaningful in the merged document, if no file
+/// does.
+fn place(err: Error, sources: &[(PathBuf, Vec<u8>)]) -> Error {
+    let path = match err.path() {
+        Some(path) => path.clone(),
+        None => return error::relocate(err, None),
+    };
+    for (file, bytes) in sources.iter().rev() {
+        let mark = locate_slice(bytes)
+            .ok()
+            .and_then(|marks| marks.get(&path).copied());
+        if let Some(mark) = mark {
+            return error::in_file(
+                error::relocate(err, Some(mark)),
+                file,
+            );
+        }
+    }
+    error::relocate(err, None)
+}
@@ -0,0 +1,298 @@
+//! Dispatching heterogeneous documents on a discriminator field.
+//!
+//! Streams of YAML documents often mix several kinds of objects, each
+//! identified by a field such as `kind: Deployment`. The usual approach of
+//! deserializing every document into a [`Value`](crate::Value), inspecting the
+//! field and then deserializing again parses each document twice.
+//!
+//! [`DynamicDeserializer`] instead looks the discriminator up directly in the
+//! parsed events of the top-level mapping and hands the same events to the
+//! deserializer registered for that value. The output type is chosen by the
+//! caller, typically a boxed trait object.
+
+use crate::{
+    de::{Deserializer, Event, Progress},
+    lib::*,
+    libyml::error::Mark,
+    loader::{Document, Loader},
+    modules::error::{
+        self, Error, ErrorImpl, ErrorKind, OneOf, Result,
+    },
+    modules::path::Path,
+};
+use alloc::collections::BTreeMap;
+use core::{fmt, str};
+use serde::de::{DeserializeOwned, Error as _};
+
+type Handler<O> = Box<dyn for<'de> Fn(Deserializer<'de>) -> Result<O>>;
+
+/// Deserializes documents into a type selected by the value of a
+/// discriminator field of their top-level mapping.
+///
+/// # Examples
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_yml::dynamic::DynamicDeserializer;
+///
+/// trait Shape {
+///     fn area(&self) -> f64;
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Square {
+///     side: f64,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Circle {
+///     radius: f64,
+/// }
+///
+/// impl Shape for Square {
+///     fn area(&self) -> f64 {
+///         self.side * self.side
+///     }
+/// }
+///
+/// impl Shape for Circle {
+///     fn area(&self) -> f64 {
+///         3.0 * self.radius * self.radius
+///     }
+/// }
+///
+/// let mut shapes = DynamicDeserializer::<Box<dyn Shape>>::new("kind");
+/// shapes
+///     .register("square", |square: Square| -> Box<dyn Shape> { Box::new(square) })
+///     .register("circle", |circle: Circle| -> Box<dyn Shape> { Box::new(circle) });
+///
+/// let yaml = "kind: square\nside: 2\n---\nkind: circle\nradius: 1\n";
+/// let areas: Vec<f64> = shapes
+///     .from_str_all(yaml)
+///     .unwrap()
+///     .iter()
+///     .map(|shape| shape.area())
+///     .collect();
+/// assert_eq!(areas, [4.0, 3.0]);
+/// ```
+pub struct DynamicDeserializer<O> {
+    field: String,
+    handlers: BTreeMap<String, Handler<O>>,
+}
+
+impl<O> DynamicDeserializer<O> {
+    /// Creates a deserializer that dispatches on the top-level key `field`.
+    pub fn new(field: impl Into<String>) -> Self {
+        DynamicDeserializer {
+            field: field.into(),
+            handlers: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the name of the discriminator field.
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    /// Registers the type to deserialize when the discriminator equals
+    /// `value`, along with a conversion into the output type.
+    ///
+    /// The discriminator field itself is passed on to `T` like any other
+    /// key; structs that do not declare it simply ignore it. Registering
+    /// the same value twice replaces the earlier registration.
+    pub fn register<T, F>(
+        &mut self,
+        value: impl Into<String>,
+        convert: F,
+    ) -> &mut Self
+    where
+        T: DeserializeOwned,
+        F: Fn(T) -> O + 'static,
+    {
+        let handler: Handler<O> =
+            Box::new(move |de| T::deserialize(de).map(&convert));
+        let _ = self.handlers.insert(value.into(), handler);
+        self
+    }
+
+    /// Deserializes a single YAML document.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is not exactly one valid document, if
+    /// its top level is not a mapping containing the discriminator as a
+    /// scalar, if no type is registered for the discriminator value, or if
+    /// the document cannot be deserialized into the registered type.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(&self, s: &str) -> Result<O> {
+        let mut loader = Loader::new(Progress::Str(s))?;
+        let document = match loader.next_document() {
+            Some(document) => document,
+            None => return Err(error::new(ErrorImpl::EndOfStream)),
+        };
+        if loader.next_document().is_some() {
+            return Err(error::new(ErrorImpl::MoreThanOneDocument));
+        }
+        self.dispatch(document)
+    }
+
+    /// Deserializes every document of a multi-document YAML stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error encountered, as described for
+    /// [`DynamicDeserializer::from_str`].
+    pub fn from_str_all(&self, s: &str) -> Result<Vec<O>> {
+        let mut loader = Loader::new(Progress::Str(s))?;
+        let mut outputs = Vec::new();
+        while let Some(document) = loader.next_document() {
+            outputs.push(self.dispatch(document)?);
+        }
+        Ok(outputs)
+    }
+
+    fn dispatch(&self, document: Document<'_>) -> Result<O> {
+        if let Some(parse_error) = &document.error {
+            return Err(error::shared(parse_error.clone()));
+        }
+        let (value, mark) = self.discriminator(&document)?;
+        let path = Path::Map {
+            parent: &Path::Root,
+            key: &self.field,
+        };
+        let value = str::from_utf8(value).map_err(|_| {
+            error::fix_mark(
+                Error::custom("discriminator is not valid UTF-8"),
+                mark,
+                path,
+            )
+        })?;
+        match self.handlers.get(value) {
+            Some(handler) => {
+                handler(Deserializer::from_document(document))
+            }
+            None => {
+                let expected: Vec<&str> =
+                    self.handlers.keys().map(String::as_str).collect();
+                let message = if expected.is_empty() {
+                    format!(
+                        "unknown {} `{}`, there are no registered values",
+                        self.field, value,
+                    )
+                } else {
+                    format!(
+                        "unknown {} `{}`, expected {}",
+                        self.field,
+                        value,
+                        OneOf(&expected),
+                    )
+                };
+                Err(error::fix_mark(
+                    error::invalid(ErrorKind::UnknownVariant, message),
+                    mark,
+                    path,
+                ))
+            }
+        }
+    }
+
+    /// Finds the scalar value of the discriminator field in the top-level
+    /// mapping without deserializing the rest of the document.
+    fn discriminator<'a>(
+        &self,
+        document: &'a Document<'_>,
+    ) -> Result<(&'a [u8], Mark)> {
+        let events = &document.events;
+        let missing = |mark| {
+            error::fix_mark(
+                Error::custom(format_args!(
+                    "missing discriminator field `{}`",
+                    self.field,
+                )),
+                mark,
+                Path::Root,
+            )
+        };
+        let start = match events.first() {
+            Some((Event::MappingStart(_), mark)) => *mark,
+            Some((_, mark)) => {
+                return Err(error::fix_mark(
+                    Error::custom(format_args!(
+                        "expected a mapping with a `{}` field",
+                        self.field,
+                    )),
+                    *mark,
+                    Path::Root,
+                ));
+            }
+            None => return Err(error::new(ErrorImpl::EndOfStream)),
+        };
+        let mut pos = 1;
+        loop {
+            match events.get(pos) {
+                Some((Event::Scalar(key), _))
+                    if *key.value == *self.field.as_bytes() =>
+                {
+                    let mut value_pos = pos + 1;
+                    if let Some((Event::Alias(id), _)) =
+                        events.get(value_pos)
+                    {
+                        value_pos =
+                            match document.anchor_event_map.get(id) {
+                                Some(target) => *target,
+                                None => return Err(missing(start)),
+                            };
+                    }
+                    return match events.get(value_pos) {
+                        Some((Event::Scalar(value), mark)) => {
+                            Ok((&value.value, *mark))
+                        }
+                        Some((_, mark)) => Err(error::fix_mark(
+                            Error::custom(format_args!(
+                                "discriminator field `{}` must be a scalar",
+                                self.field,
+                            )),
+                            *mark,
+                            Path::Root,
+                        )),
+                        None => Err(missing(start)),
+                    };
+                }
+                Some((Event::MappingEnd, _)) | None => {
+                    return Err(missing(start));
+                }
+                Some(_) => {
+                    pos = skip_node(events, skip_node(events, pos))
+                }
+            }
+        }
+    }
+}
+
+impl<O> fmt::Debug for DynamicDeserializer<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynamicDeserializer")
+            .field("field", &self.field)
+            .field("kinds", &self.handlers.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// Returns the position just past the node starting at `pos`.
+fn skip_node(events: &[(Event<'_>, Mark)], mut pos: usize) -> usize {
+    let mut depth = 0usize;
+    while let Some((event, _)) = events.get(pos) {
+        pos += 1;
+        match event {
+            Event::SequenceStart(_) | Event::MappingStart(_) => {
+                depth += 1
+            }
+            Event::SequenceEnd | Event::MappingEnd => depth -= 1,
+            Event::Alias(_) | Event::Scalar(_) | Event::Void => {}
+        }
+        if depth == 0 {
+            break;
+        }
+    }
+    pos
+}
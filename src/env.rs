@@ -0,0 +1,373 @@
+//! Environment-variable interpolation for YAML scalars.
+//!
+//! This module expands `${VAR}` and `${VAR:-default}` references found in
+//! the scalars of a YAML document before it is deserialized into a Rust
+//! type. Expansion happens on the parsed scalars rather than on the raw
+//! text, so a substituted value can never change the structure of the
+//! document, and the usual YAML typing rules still apply afterwards: a plain
+//! `port: ${PORT}` becomes an integer when `PORT=8080`, whereas a quoted
+//! `'${PORT}'` stays a string.
+//!
+//! The following forms are recognised inside any scalar:
+//!
+//! - `${VAR}` is replaced by the value of `VAR`. It is an error for `VAR` to
+//!   be unset.
+//! - `${VAR:-default}` is replaced by the value of `VAR`, or by `default`
+//!   when `VAR` is unset or empty.
+//! - `$${` produces a literal `${`, which allows escaping a reference.
+//! - A `$` that is not followed by `{` is left untouched.
+
+use crate::{
+    de::{parse_null, Deserializer, Event, Progress},
+    loader::Loader,
+    modules::error::{self, Error, ErrorImpl, ErrorKind, Result},
+    modules::path::Path,
+    value::Value,
+};
+use serde::de::{
+    self, value::StrDeserializer, DeserializeOwned, Error as _, Visitor,
+};
+use std::env;
+
+/// Deserializes an instance of type `T` from a string of YAML text after
+/// expanding environment variable references in its scalars.
+///
+/// Variables are looked up in the process environment with
+/// [`std::env::var`]. Use [`from_str_expanded_with`] to supply a custom
+/// lookup instead.
+///
+/// # Errors
+///
+/// Returns an error if the YAML is invalid, if a referenced variable without
+/// a default is not set, if a reference is malformed, or if the expanded
+/// document cannot be deserialized into `T`.
+///
+/// # Examples
+///
+/// ```
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Config {
+///     home: String,
+///     level: String,
+/// }
+///
+/// std::env::set_var("SERDE_YML_DOC_HOME", "/srv/app");
+/// let yaml = "home: ${SERDE_YML_DOC_HOME}\nlevel: ${SERDE_YML_DOC_LEVEL:-info}\n";
+/// let config: Config = serde_yml::env::from_str_expanded(yaml).unwrap();
+/// assert_eq!(config.home, "/srv/app");
+/// assert_eq!(config.level, "info");
+/// ```
+pub fn from_str_expanded<T>(s: &str) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    from_str_expanded_with(s, |name| env::var(name).ok())
+}
+
+/// Deserializes an instance of type `T` from a string of YAML text after
+/// expanding variable references in its scalars with a user-supplied
+/// lookup.
+///
+/// The `lookup` closure receives the name of each referenced variable and
+/// returns its value, or `None` if it is not defined.
+///
+/// # Errors
+///
+/// Returns an error if the YAML is invalid, if a referenced variable without
+/// a default is not defined, if a reference is malformed, or if the expanded
+/// document cannot be deserialized into `T`.
+///
+/// # Examples
+///
+/// ```
+/// let yaml = "port: ${PORT}\nname: '${PORT}'\n";
+/// let lookup = |name: &str| (name == "PORT").then(|| "8080".to_owned());
+/// let value: serde_yml::Value =
+///     serde_yml::env::from_str_expanded_with(yaml, lookup).unwrap();
+/// assert_eq!(value["port"], 8080);
+/// assert_eq!(value["name"], "8080");
+/// ```
+pub fn from_str_expanded_with<T, F>(s: &str, mut lookup: F) -> Result<T>
+where
+    T: DeserializeOwned,
+    F: FnMut(&str) -> Option<String>,
+{
+    let mut loader = Loader::new(Progress::Str(s))?;
+    let mut document = match loader.next_document() {
+        Some(document) => document,
+        None => return Err(error::new(ErrorImpl::EndOfStream)),
+    };
+    if let Some(parse_error) = document.error {
+        return Err(error::shared(parse_error));
+    }
+    if loader.next_document().is_some() {
+        return Err(error::new(ErrorImpl::MoreThanOneDocument));
+    }
+    for (event, mark) in &mut document.events {
+        if let Event::Scalar(scalar) = event {
+            let value = match std::str::from_utf8(&scalar.value) {
+                Ok(value) if value.contains('$') => value,
+                _ => continue,
+            };
+            let expanded =
+                expand(value, &mut lookup).map_err(|err| {
+                    error::fix_mark(err, *mark, Path::Root)
+                })?;
+            scalar.value = expanded.into_bytes().into_boxed_slice();
+        }
+    }
+    T::deserialize(Deserializer::from_document(document))
+}
+
+/// Deserializes an instance of type `T` from the value of the environment
+/// variable `name`, or returns `None` if it is not set.
+///
+/// A string takes the value exactly as it is set, so passwords and URLs
+/// such as `#secret`, `&amp` or `p@ss: word` are kept whole. Any other type
+/// reads the value as YAML, so `8080` becomes an integer and `[a, b]` a
+/// sequence. A type that accepts anything, such as [`Value`], reads the
+/// value as YAML unless it is not valid YAML or is read as a string or a
+/// null not spelled as one, in which case it takes the text as it is. An
+/// empty value is read as an empty string.
+///
+/// # Errors
+///
+/// Returns an error naming the variable if its value is not valid
+/// Unicode, not valid YAML, or cannot be deserialized into `T`.
+///
+/// # Examples
+///
+/// ```
+/// std::env::set_var("SERDE_YML_DOC_WORKERS", "4");
+/// let workers: Option<u32> =
+///     serde_yml::env::from_var("SERDE_YML_DOC_WORKERS").unwrap();
+/// assert_eq!(workers, Some(4));
+///
+/// std::env::set_var("SERDE_YML_DOC_WORKERS", "many");
+/// let err = serde_yml::env::from_var::<u32>("SERDE_YML_DOC_WORKERS").unwrap_err();
+/// assert!(err.to_string().starts_with("environment variable `SERDE_YML_DOC_WORKERS`: "));
+/// ```
+pub fn from_var<T>(name: &str) -> Result<Option<T>>
+where
+    T: DeserializeOwned,
+{
+    let value = match env::var(name) {
+        Ok(value) => value,
+        Err(env::VarError::NotPresent) => return Ok(None),
+        Err(err) => {
+            return Err(error::invalid(
+                ErrorKind::InvalidValue,
+                format_args!(
+                    "environment variable `{}`: {}",
+                    name, err
+                ),
+            ))
+        }
+    };
+    T::deserialize(VarDeserializer(&value))
+        .map(Some)
+        .map_err(|err| {
+            error::invalid(
+                err.kind(),
+                format_args!(
+                    "environment variable `{}`: {}",
+                    name, err
+                ),
+            )
+        })
+}
+
+/// Deserializes the value of an environment variable, handing strings the
+/// text as it is and parsing it as YAML for other types.
+struct VarDeserializer<'a>(&'a str);
+
+macro_rules! forward_to_yaml {
+    ($($method:ident($($arg:ident: $ty:ty),*))*) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value>
+            where
+                V: Visitor<'de>,
+            {
+                if self.0.is_empty() {
+                    StrDeserializer::new("").$method($($arg,)* visitor)
+                } else {
+                    Deserializer::from_str(self.0).$method($($arg,)* visitor)
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for VarDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        // Text that YAML would drop or alter, such as `&amp` or ` lead`,
+        // is kept as it is.
+        match crate::from_str::<Value>(self.0) {
+            Ok(Value::Null)
+                if parse_null(self.0.as_bytes()).is_some() =>
+            {
+                visitor.visit_unit()
+            }
+            Ok(Value::Null) | Ok(Value::String(_)) | Err(_) => {
+                visitor.visit_borrowed_str(self.0)
+            }
+            Ok(value) => {
+                de::Deserializer::deserialize_any(value, visitor)
+            }
+        }
+    }
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        StrDeserializer::new(self.0).deserialize_char(visitor)
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_borrowed_str(self.0)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_borrowed_str(self.0)
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_borrowed_str(self.0)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_yaml! {
+        deserialize_bool()
+        deserialize_i8()
+        deserialize_i16()
+        deserialize_i32()
+        deserialize_i64()
+        deserialize_i128()
+        deserialize_u8()
+        deserialize_u16()
+        deserialize_u32()
+        deserialize_u64()
+        deserialize_u128()
+        deserialize_f32()
+        deserialize_f64()
+        deserialize_bytes()
+        deserialize_byte_buf()
+        deserialize_unit()
+        deserialize_unit_struct(name: &'static str)
+        deserialize_seq()
+        deserialize_tuple(len: usize)
+        deserialize_tuple_struct(name: &'static str, len: usize)
+        deserialize_map()
+        deserialize_struct(name: &'static str, fields: &'static [&'static str])
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+        deserialize_ignored_any()
+    }
+}
+
+/// Expands the variable references in a single string.
+///
+/// This applies the same rules as [`from_str_expanded_with`] to `input` and
+/// returns the expanded string.
+///
+/// # Errors
+///
+/// Returns an error if a referenced variable without a default is not
+/// defined, or if a reference is unterminated or has an empty name.
+///
+/// # Examples
+///
+/// ```
+/// let mut lookup = |name: &str| (name == "USER").then(|| "ferris".to_owned());
+/// let expanded = serde_yml::env::expand("hi ${USER}, $${USER}", &mut lookup).unwrap();
+/// assert_eq!(expanded, "hi ferris, ${USER}");
+/// ```
+pub fn expand<F>(input: &str, lookup: &mut F) -> Result<String>
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(dollar) = rest.find('$') {
+        output.push_str(&rest[..dollar]);
+        let after = &rest[dollar + 1..];
+        if let Some(escaped) = after.strip_prefix("${") {
+            output.push_str("${");
+            rest = escaped;
+        } else if let Some(reference) = after.strip_prefix('{') {
+            let end = reference.find('}').ok_or_else(|| {
+                Error::custom(format_args!(
+                    "unterminated variable reference in {:?}",
+                    input,
+                ))
+            })?;
+            output.push_str(&resolve(&reference[..end], lookup)?);
+            rest = &reference[end + 1..];
+        } else {
+            output.push('$');
+            rest = after;
+        }
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+fn resolve<F>(reference: &str, lookup: &mut F) -> Result<String>
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let (name, default) = match reference.split_once(":-") {
+        Some((name, default)) => (name, Some(default)),
+        None => (reference, None),
+    };
+    if name.is_empty() {
+        return Err(Error::custom(format_args!(
+            "empty variable name in reference `${{{}}}`",
+            reference,
+        )));
+    }
+    match (lookup(name), default) {
+        (Some(value), Some(default)) if value.is_empty() => {
+            Ok(default.to_owned())
+        }
+        (Some(value), _) => Ok(value),
+        (None, Some(default)) => Ok(default.to_owned()),
+        (None, None) => Err(Error::custom(format_args!(
+            "environment variable `{}` is not set",
+            name,
+        ))),
+    }
+}
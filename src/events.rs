@@ -0,0 +1,257 @@
+//! A safe, stable view of the parser's event stream.
+//!
+//! [`Parser`] reads YAML text and yields one [`Event`] per node boundary,
+//! in the order the parser meets them, without building a
+//! [`Value`](crate::Value). Each event carries its byte range in the input
+//! along with the anchor, tag and scalar style written there, which is
+//! what syntax highlighters, outline views and other editor tooling need.
+//!
+//! The types of this module are part of the stable API: they own their
+//! data, expose no raw pointers and do not change with the YAML backend.
+//! The lower-level [`libyml::parser`](crate::libyml::parser) stays
+//! available but follows the backend more closely.
+//!
+//! ```
+//! use serde_yml::events::{EventKind, Parser};
+//!
+//! let yaml = "name: &n demo\n";
+//! let scalars: Vec<_> = Parser::new(yaml)
+//!     .filter_map(Result::ok)
+//!     .filter_map(|event| match event.kind() {
+//!         EventKind::Scalar(scalar) => {
+//!             Some((scalar.value().to_owned(), event.span()))
+//!         }
+//!         _ => None,
+//!     })
+//!     .collect();
+//!
+//! assert_eq!(scalars, [("name".to_owned(), 0..4), ("demo".to_owned(), 6..13)]);
+//! ```
+
+use crate::{
+    lib::*,
+    libyml::{
+        self,
+        error::Mark,
+        parser::{Anchor, Parser as YamlParser},
+        tag::Tag,
+    },
+    loader::LineIndex,
+    modules::error::{Error, Location, Result},
+};
+use alloc::borrow::Cow;
+use core::ops::Range;
+
+pub use crate::libyml::parser::ScalarStyle;
+
+/// The UTF-8 byte order mark, which the parser would otherwise read as
+/// part of the first line.
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// An iterator over the events of a YAML stream.
+///
+/// The iterator yields [`EventKind::StreamStart`] first and ends after
+/// [`EventKind::StreamEnd`]. If the input is not valid YAML, it yields
+/// the events before the problem, then the error, and then ends.
+#[derive(Debug)]
+pub struct Parser<'input> {
+    parser: Option<YamlParser<'input>>,
+    lines: LineIndex<'input>,
+    bom: usize,
+}
+
+impl<'input> Parser<'input> {
+    /// Creates a parser over YAML text.
+    pub fn new(input: &'input str) -> Self {
+        Parser::from_slice(input.as_bytes())
+    }
+
+    /// Creates a parser over YAML bytes, which must be UTF-8.
+    pub fn from_slice(input: &'input [u8]) -> Self {
+        let bom = if input.starts_with(UTF8_BOM) {
+            UTF8_BOM.len()
+        } else {
+            0
+        };
+        let input = &input[bom..];
+        Parser {
+            parser: Some(YamlParser::new(Cow::Borrowed(input))),
+            lines: LineIndex::new(input),
+            bom,
+        }
+    }
+
+    fn offset(&self, mark: Mark) -> usize {
+        self.lines.offset(mark) + self.bom
+    }
+}
+
+impl Iterator for Parser<'_> {
+    type Item = Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let parser = self.parser.as_mut()?;
+        let (event, start, end) =
+            match parser.parse_next_event_with_end() {
+                Ok(event) => event,
+                Err(err) => {
+                    self.parser = None;
+                    return Some(Err(Error::from(err)));
+                }
+            };
+        let kind = event_kind(event);
+        if let EventKind::StreamEnd = kind {
+            self.parser = None;
+        }
+        let offset = self.offset(start);
+        Some(Ok(Event {
+            kind,
+            span: offset..self.offset(end).max(offset),
+            start: Location::from_mark(start),
+        }))
+    }
+}
+
+/// An event of a YAML stream, with where it was written.
+#[derive(Clone, Debug)]
+pub struct Event {
+    kind: EventKind,
+    span: Range<usize>,
+    start: Location,
+}
+
+impl Event {
+    /// Returns what the event is.
+    pub fn kind(&self) -> &EventKind {
+        &self.kind
+    }
+
+    /// Returns the event, dropping where it was written.
+    pub fn into_kind(self) -> EventKind {
+        self.kind
+    }
+
+    /// Returns the byte range of the event within the input.
+    ///
+    /// A scalar or alias spans its text, including any anchor, tag and
+    /// quotes. A collection start spans its anchor, tag and opening `[`
+    /// or `{`, and a collection end its closing bracket. Events with
+    /// nothing written for them, such as the start of an implicit
+    /// document or the end of a block collection, have an empty range.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Returns the line and column at which the event starts.
+    pub fn location(&self) -> Location {
+        self.start
+    }
+}
+
+/// The kinds of [`Event`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EventKind {
+    /// The start of the stream, before any document.
+    StreamStart,
+    /// The end of the stream, after every document.
+    StreamEnd,
+    /// The start of a document.
+    DocumentStart,
+    /// The end of a document.
+    DocumentEnd,
+    /// An alias, such as `*name`, holding the name of its anchor.
+    Alias(String),
+    /// A scalar.
+    Scalar(Scalar),
+    /// The start of a sequence.
+    SequenceStart(Properties),
+    /// The end of a sequence.
+    SequenceEnd,
+    /// The start of a mapping.
+    MappingStart(Properties),
+    /// The end of a mapping.
+    MappingEnd,
+}
+
+/// A scalar of a YAML stream.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Scalar {
+    properties: Properties,
+    value: String,
+    style: ScalarStyle,
+}
+
+impl Scalar {
+    /// Returns the value of the scalar, with escapes and line folding
+    /// resolved.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Returns how the scalar was written.
+    pub fn style(&self) -> ScalarStyle {
+        self.style
+    }
+
+    /// Returns the anchor and tag of the scalar.
+    pub fn properties(&self) -> &Properties {
+        &self.properties
+    }
+}
+
+/// The anchor and tag written before a node.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Properties {
+    anchor: Option<String>,
+    tag: Option<String>,
+}
+
+impl Properties {
+    /// Returns the name of the anchor, such as `name` for `&name`.
+    pub fn anchor(&self) -> Option<&str> {
+        self.anchor.as_deref()
+    }
+
+    /// Returns the tag with its handle resolved, such as
+    /// `tag:yaml.org,2002:str` for `!!str`.
+    pub fn tag(&self) -> Option<&str> {
+        self.tag.as_deref()
+    }
+
+    fn new(anchor: Option<Anchor>, tag: Option<Tag>) -> Self {
+        Properties {
+            anchor: anchor.map(|anchor| lossy(&anchor)),
+            tag: tag.map(|tag| lossy(&tag)),
+        }
+    }
+}
+
+fn event_kind(event: libyml::parser::Event<'_>) -> EventKind {
+    use libyml::parser::Event as YamlEvent;
+
+    match event {
+        YamlEvent::StreamStart => EventKind::StreamStart,
+        YamlEvent::StreamEnd => EventKind::StreamEnd,
+        YamlEvent::DocumentStart => EventKind::DocumentStart,
+        YamlEvent::DocumentEnd => EventKind::DocumentEnd,
+        YamlEvent::Alias(anchor) => EventKind::Alias(lossy(&anchor)),
+        YamlEvent::Scalar(scalar) => EventKind::Scalar(Scalar {
+            properties: Properties::new(scalar.anchor, scalar.tag),
+            value: lossy(&scalar.value),
+            style: scalar.style,
+        }),
+        YamlEvent::SequenceStart(start) => EventKind::SequenceStart(
+            Properties::new(start.anchor, start.tag),
+        ),
+        YamlEvent::SequenceEnd => EventKind::SequenceEnd,
+        YamlEvent::MappingStart(start) => EventKind::MappingStart(
+            Properties::new(start.anchor, start.tag),
+        ),
+        YamlEvent::MappingEnd => EventKind::MappingEnd,
+    }
+}
+
+fn lossy(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
@@ -0,0 +1,827 @@
+//! Reformatting of YAML text.
+//!
+//! [`format_str`] parses YAML text and writes it out again through the same
+//! emitter as [`to_string`](crate::to_string), so that every file ends up
+//! with the same layout: block collections with one entry per line, nested
+//! collections indented by [`FormatOptions::indent`] spaces and sequences
+//! inside mappings written at the indentation of their key. Flow
+//! collections such as `[1, 2]` are rewritten in block style, except empty
+//! ones.
+//!
+//! Unlike a round trip through [`Value`](crate::Value), formatting keeps
+//! what the data model drops:
+//!
+//! - Comments, each next to the node it was written next to. A comment at
+//!   the end of a line stays at the end of the line holding that node, and
+//!   a comment on a line of its own stays on a line of its own before the
+//!   node that followed it.
+//! - Blank lines between entries, with runs of them reduced to one.
+//! - Anchors, aliases, tags and, by default, the style of each scalar.
+//! - The order of mapping keys, unless [`FormatOptions::sort_keys`] is set.
+//! - Every document of a stream, the `---` marker of the first one and the
+//!   `...` marker at the end of any of them.
+//! - Directives such as `%YAML 1.2` and `%TAG`, along with each tag as it
+//!   was written, so that `!e!point` stays a shorthand for its `%TAG`
+//!   prefix rather than becoming `!<tag:example.com,2000:point>`.
+//! - The line breaks at the end of the input, or their absence.
+//!
+//! Formatting text that is already formatted gives it back unchanged, so
+//! that a tool can parse a file, edit some of it and write it out again
+//! without touching the documents it did not edit.
+
+use crate::{
+    lib::*,
+    libyml::{
+        emitter::{self, Emitter},
+        parser::{Event, Parser, ScalarStyle},
+        tag::Tag,
+    },
+    loader::{has_text, line_end, LineIndex},
+    modules::error::{self, Error, ErrorImpl, Result},
+    ser::infer_scalar_style,
+};
+use alloc::borrow::Cow;
+use core::ops::Range;
+
+/// How [`format_str`] quotes scalars written in quotes.
+///
+/// Plain scalars and block scalars keep their style, unless the emitter
+/// cannot write the text in that style.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Quotes {
+    /// Keep single and double quotes as written. This is the default.
+    Preserve,
+    /// Remove the quotes from strings that read back as the same string
+    /// without them, such as `"name"`, and keep the others as written.
+    Minimal,
+    /// Write quoted scalars in single quotes, or in double quotes if they
+    /// hold characters that need an escape.
+    Single,
+    /// Write quoted scalars in double quotes.
+    Double,
+}
+
+/// Settings for [`format_str`].
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::fmt::{format_str, FormatOptions, Quotes};
+///
+/// let options = FormatOptions {
+///     indent: 4,
+///     sort_keys: true,
+///     quotes: Quotes::Minimal,
+/// };
+/// let formatted = format_str("b: {c: 'x'}\na: 1\n", options).unwrap();
+/// assert_eq!(formatted, "a: 1\nb:\n    c: x\n");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// The number of spaces by which nested block collections are
+    /// indented, from 2 to 9. Other values are treated as 2, the default.
+    pub indent: usize,
+    /// Whether to sort the entries of every mapping by their key. Keys that
+    /// are scalars are compared by their text and come before the others,
+    /// which keep their order. Comments move with their entry, except
+    /// those before the first node of a document, which stay at the top.
+    /// The default is to keep the order of the input.
+    pub sort_keys: bool,
+    /// How scalars written in quotes are quoted. The default is
+    /// [`Quotes::Preserve`].
+    pub quotes: Quotes,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            indent: 2,
+            sort_keys: false,
+            quotes: Quotes::Preserve,
+        }
+    }
+}
+
+/// Reformats a string of YAML text, keeping its comments and document
+/// structure as described in the [module documentation](self).
+///
+/// # Errors
+///
+/// Returns an error if `input` is not valid YAML, or if sorting the keys
+/// would move an alias before the anchor it refers to.
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::fmt::{format_str, FormatOptions};
+///
+/// let input = "\
+/// ## Service settings
+/// name:   api   # public name
+/// ports: [80,   443]
+///
+/// limits: {cpu: 2}
+/// ";
+/// let formatted = format_str(input, FormatOptions::default()).unwrap();
+/// assert_eq!(
+///     formatted,
+///     "\
+/// ## Service settings
+/// name: api # public name
+/// ports:
+/// - 80
+/// - 443
+///
+/// limits:
+///   cpu: 2
+/// ",
+/// );
+/// ```
+pub fn format_str(
+    input: &str,
+    options: FormatOptions,
+) -> Result<String> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let lines = LineIndex::new(input.as_bytes());
+    let mut items = parse(&lines)?;
+    attach_comments(&lines, &mut items);
+
+    let mut order = Vec::with_capacity(items.len());
+    let mut pos = 0;
+    while pos < items.len() {
+        pos = arrange(&items, pos, options.sort_keys, &mut order);
+    }
+    if options.sort_keys {
+        check_aliases(&items, &order)?;
+    }
+
+    let mut emitter = Emitter::new(Vec::with_capacity(input.len()));
+    emitter.set_indent(options.indent);
+    for &index in &order {
+        let item = &items[index];
+        if let Event::DocumentEnd = item.event {
+            emitter.set_explicit_document_end(!item.span.is_empty());
+        }
+        emit(&mut emitter, &item.event, options.quotes)?;
+    }
+    emitter.flush()?;
+    let output = String::from_utf8(emitter.into_inner())
+        .map_err(|error| error::new(ErrorImpl::FromUtf8(error)))?;
+    let mut formatted = place_comments(input, &items, &order, &output)?;
+
+    // End with as many line breaks as the input.
+    let breaks = input
+        .bytes()
+        .rev()
+        .take_while(u8::is_ascii_whitespace)
+        .filter(|&byte| byte == b'\n')
+        .count();
+    if formatted.ends_with('\n') {
+        if breaks == 0 {
+            let _ = formatted.pop();
+        }
+        for _ in 1..breaks {
+            formatted.push('\n');
+        }
+    }
+    Ok(formatted)
+}
+
+/// An event of the input along with the comments next to it.
+struct Item<'input> {
+    event: Event<'input>,
+    /// The byte range of the event within the input.
+    span: Range<usize>,
+    /// The byte range of the tag of the event within the input, as written.
+    tag: Option<Range<usize>>,
+    /// Comments and directives on lines of their own before the event, and
+    /// empty strings for blank lines.
+    before: Vec<String>,
+    /// A comment at the end of the line on which the event ends.
+    after: Option<String>,
+}
+
+fn parse<'input>(
+    lines: &LineIndex<'input>,
+) -> Result<Vec<Item<'input>>> {
+    let mut parser = Parser::new(Cow::Borrowed(lines.input));
+    let mut items = Vec::new();
+    loop {
+        let (event, start, end) = parser.parse_next_event_with_end()?;
+        let done = matches!(event, Event::StreamEnd);
+        let start = lines.offset(start);
+        let end = lines.offset(end).max(start);
+        let tagged = match &event {
+            Event::Scalar(scalar) => scalar.tag.is_some(),
+            Event::SequenceStart(sequence) => sequence.tag.is_some(),
+            Event::MappingStart(mapping) => mapping.tag.is_some(),
+            _ => false,
+        };
+        items.push(Item {
+            tag: if tagged {
+                tag_range(lines.input, start)
+            } else {
+                None
+            },
+            event,
+            span: start..end,
+            before: Vec::new(),
+            after: None,
+        });
+        if done {
+            return Ok(items);
+        }
+    }
+}
+
+/// Returns the byte range of the tag among the properties of the node
+/// starting at `pos`.
+fn tag_range(input: &[u8], mut pos: usize) -> Option<Range<usize>> {
+    let is_end = |byte: &u8| {
+        matches!(
+            byte,
+            b' ' | b'\t'
+                | b'\r'
+                | b'\n'
+                | b','
+                | b'['
+                | b']'
+                | b'{'
+                | b'}'
+        )
+    };
+    // A tag may come after an anchor.
+    for _ in 0..2 {
+        let rest = input.get(pos..)?;
+        let len = match rest.first()? {
+            b'!' if rest.get(1) == Some(&b'<') => {
+                rest.iter().position(|&byte| byte == b'>')? + 1
+            }
+            b'!' | b'&' => {
+                rest.iter().position(is_end).unwrap_or(rest.len())
+            }
+            _ => return None,
+        };
+        if rest[0] == b'!' {
+            return Some(pos..pos + len);
+        }
+        pos += len;
+        pos += input[pos..]
+            .iter()
+            .take_while(|&&byte| matches!(byte, b' ' | b'\t'))
+            .count();
+    }
+    None
+}
+
+/// Returns the byte ranges in which `#` and blank lines are part of a
+/// scalar rather than comments. The header line of a block scalar, which
+/// may end with a comment, is left out.
+fn scalar_ranges(
+    lines: &LineIndex<'_>,
+    items: &[Item<'_>],
+) -> Vec<Range<usize>> {
+    items
+        .iter()
+        .filter_map(|item| match &item.event {
+            Event::Scalar(scalar)
+                if matches!(
+                    scalar.style,
+                    ScalarStyle::Literal | ScalarStyle::Folded
+                ) =>
+            {
+                Some(
+                    line_end(lines.input, item.span.start)
+                        ..item.span.end,
+                )
+            }
+            Event::Scalar(_) => Some(item.span.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Finds the comments, directives and blank lines of the input and hands
+/// each to the event it belongs with.
+fn attach_comments(lines: &LineIndex<'_>, items: &mut [Item<'_>]) {
+    let input = lines.input;
+    let scalars = scalar_ranges(lines, items);
+    let columns = closed_columns(lines, items);
+    for &start in &lines.starts {
+        let end = line_end(input, start);
+        if !has_text(&input[start..end]) {
+            if start == input.len()
+                || enclosing(&scalars, start).is_some()
+            {
+                continue;
+            }
+            // The event after a blank line starts after it.
+            let next = next_item(items, &columns, start, None);
+            if next.before.last().map_or(true, |last| !last.is_empty())
+            {
+                next.before.push(String::new());
+            }
+            continue;
+        }
+        // A directive goes before the `---` marker that follows it.
+        if input[start] == b'%' && enclosing(&scalars, start).is_none()
+        {
+            let text = String::from_utf8_lossy(&input[start..end])
+                .trim_end()
+                .to_owned();
+            if let Some(document) = items.iter_mut().find(|item| {
+                matches!(item.event, Event::DocumentStart)
+                    && item.span.end > start
+            }) {
+                document.before.push(text);
+            }
+            continue;
+        }
+        let comment = (start..end).find(|&pos| {
+            input[pos] == b'#'
+                && (pos == start
+                    || matches!(input[pos - 1], b' ' | b'\t'))
+                && enclosing(&scalars, pos).is_none()
+        });
+        let pos = match comment {
+            Some(pos) => pos,
+            None => continue,
+        };
+        let text = String::from_utf8_lossy(&input[pos..end])
+            .trim_end()
+            .to_owned();
+        if has_text(&input[start..pos]) {
+            if let Some(owner) = owner(items, start, pos) {
+                owner.after = Some(text);
+                continue;
+            }
+        }
+        let column = pos - start;
+        next_item(items, &columns, pos, Some(column))
+            .before
+            .push(text);
+    }
+}
+
+/// Returns, for each event that ends a block collection, the column at
+/// which the collection starts in the input.
+fn closed_columns(
+    lines: &LineIndex<'_>,
+    items: &[Item<'_>],
+) -> Vec<Option<usize>> {
+    let mut open = Vec::new();
+    items
+        .iter()
+        .map(|item| match item.event {
+            Event::SequenceStart(_) | Event::MappingStart(_) => {
+                let start = item.span.start;
+                let flow =
+                    matches!(lines.input.get(start), Some(b'[' | b'{'));
+                open.push(if flow {
+                    None
+                } else {
+                    Some(start - lines.starts[lines.line(start)])
+                });
+                None
+            }
+            Event::SequenceEnd | Event::MappingEnd => {
+                open.pop().flatten()
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the first event that starts after `pos`, or the first entry of
+/// the block collection starting there so that the comment moves with the
+/// entry when keys are sorted. Where several block collections end at the
+/// same place, a comment at `column` goes with the innermost one that it
+/// is not indented less than.
+fn next_item<'a, 'input>(
+    items: &'a mut [Item<'input>],
+    columns: &[Option<usize>],
+    pos: usize,
+    column: Option<usize>,
+) -> &'a mut Item<'input> {
+    let last = items.len() - 1;
+    let mut index = items
+        .partition_point(|item| item.span.start <= pos)
+        .min(last);
+    while index < last
+        && matches!(
+            items[index].event,
+            Event::SequenceStart(_) | Event::MappingStart(_)
+        )
+        && items[index + 1].span.start == items[index].span.start
+    {
+        index += 1;
+    }
+    if let Some(column) = column {
+        while index < last
+            && items[index + 1].span.start == items[index].span.start
+            && matches!(columns[index], Some(open) if column < open)
+        {
+            index += 1;
+        }
+    }
+    &mut items[index]
+}
+
+/// Returns the scalar or alias that ends last on the line starting at
+/// `line_start` before a comment at `pos`, which the comment is about, or
+/// the block scalar whose header is on that line.
+fn owner<'a, 'input>(
+    items: &'a mut [Item<'input>],
+    line_start: usize,
+    pos: usize,
+) -> Option<&'a mut Item<'input>> {
+    let count = items.partition_point(|item| item.span.start < pos);
+    items[..count]
+        .iter_mut()
+        .rev()
+        .find(|item| {
+            matches!(item.event, Event::Scalar(_) | Event::Alias(_))
+        })
+        .filter(|item| match &item.event {
+            // A comment on the header line of a block scalar.
+            Event::Scalar(scalar)
+                if matches!(
+                    scalar.style,
+                    ScalarStyle::Literal | ScalarStyle::Folded
+                ) =>
+            {
+                item.span.start >= line_start
+            }
+            _ => item.span.end >= line_start && item.span.end <= pos,
+        })
+}
+
+/// Returns the index just past the node that starts at `pos`.
+fn skip_node(items: &[Item<'_>], mut pos: usize) -> usize {
+    let mut depth = 0;
+    loop {
+        match items[pos].event {
+            Event::SequenceStart(_) | Event::MappingStart(_) => {
+                depth += 1
+            }
+            Event::SequenceEnd | Event::MappingEnd => depth -= 1,
+            _ => {}
+        }
+        pos += 1;
+        if depth == 0 {
+            return pos;
+        }
+    }
+}
+
+/// Appends the indices of the events from `pos` to the end of the node
+/// starting there to `order`, in the order they are emitted, and returns
+/// the index just past the node.
+fn arrange(
+    items: &[Item<'_>],
+    pos: usize,
+    sort_keys: bool,
+    order: &mut Vec<usize>,
+) -> usize {
+    order.push(pos);
+    match items[pos].event {
+        Event::SequenceStart(_) => {
+            let mut next = pos + 1;
+            while !matches!(items[next].event, Event::SequenceEnd) {
+                next = arrange(items, next, sort_keys, order);
+            }
+            order.push(next);
+            next + 1
+        }
+        Event::MappingStart(_) => {
+            let mut entries = Vec::new();
+            let mut next = pos + 1;
+            while !matches!(items[next].event, Event::MappingEnd) {
+                let value = skip_node(items, next);
+                entries.push((next, value));
+                next = skip_node(items, value);
+            }
+            if sort_keys {
+                entries.sort_by_key(|&(key, _)| {
+                    match &items[key].event {
+                        Event::Scalar(scalar) => {
+                            (false, Some(&scalar.value))
+                        }
+                        _ => (true, None),
+                    }
+                });
+            }
+            for (key, value) in entries {
+                let _ = arrange(items, key, sort_keys, order);
+                let _ = arrange(items, value, sort_keys, order);
+            }
+            order.push(next);
+            next + 1
+        }
+        _ => pos + 1,
+    }
+}
+
+/// Checks that every alias still comes after its anchor once the keys are
+/// sorted.
+fn check_aliases(items: &[Item<'_>], order: &[usize]) -> Result<()> {
+    let mut anchors: Vec<&[u8]> = Vec::new();
+    for &index in order {
+        let anchor = match &items[index].event {
+            Event::Alias(alias) => {
+                if anchors.contains(&&**alias) {
+                    continue;
+                }
+                return Err(error::new(ErrorImpl::Message(
+                    format!(
+                        "cannot sort the keys: alias *{} would come before its anchor",
+                        String::from_utf8_lossy(alias),
+                    ),
+                    None,
+                )));
+            }
+            Event::Scalar(scalar) => scalar.anchor.as_ref(),
+            Event::SequenceStart(sequence) => sequence.anchor.as_ref(),
+            Event::MappingStart(mapping) => mapping.anchor.as_ref(),
+            _ => None,
+        };
+        if let Some(anchor) = anchor {
+            anchors.push(anchor);
+        }
+    }
+    Ok(())
+}
+
+fn emit<W: crate::io::Write>(
+    emitter: &mut Emitter<W>,
+    event: &Event<'_>,
+    quotes: Quotes,
+) -> Result<()> {
+    fn text(bytes: &[u8]) -> Cow<'_, str> {
+        String::from_utf8_lossy(bytes)
+    }
+
+    fn tag(tag: &Option<Tag>) -> Option<String> {
+        tag.as_ref().map(|tag| text(tag).into_owned())
+    }
+
+    let anchor = |anchor: &Option<_>| {
+        anchor
+            .as_deref()
+            .map(|anchor: &[u8]| text(anchor).into_owned())
+    };
+    match event {
+        Event::StreamStart => emitter.emit(emitter::Event::StreamStart),
+        Event::StreamEnd => emitter.emit(emitter::Event::StreamEnd),
+        Event::DocumentStart => {
+            emitter.emit(emitter::Event::DocumentStart)
+        }
+        Event::DocumentEnd => emitter.emit(emitter::Event::DocumentEnd),
+        Event::Alias(alias) => {
+            emitter.emit(emitter::Event::Alias(&text(alias)))
+        }
+        Event::Scalar(scalar) => {
+            let value = text(&scalar.value);
+            let quoted = match quotes {
+                Quotes::Preserve => None,
+                Quotes::Minimal
+                    if scalar.tag.is_none()
+                        && matches!(
+                            infer_scalar_style(&value),
+                            emitter::ScalarStyle::Any
+                        ) =>
+                {
+                    Some(emitter::ScalarStyle::Any)
+                }
+                Quotes::Minimal => None,
+                Quotes::Single => {
+                    Some(emitter::ScalarStyle::SingleQuoted)
+                }
+                Quotes::Double => {
+                    Some(emitter::ScalarStyle::DoubleQuoted)
+                }
+            };
+            let style = match (scalar.style, quoted) {
+                (ScalarStyle::Plain, _) => emitter::ScalarStyle::Plain,
+                (ScalarStyle::Literal, _) => {
+                    emitter::ScalarStyle::Literal
+                }
+                (ScalarStyle::Folded, _) => {
+                    emitter::ScalarStyle::Folded
+                }
+                (_, Some(style)) => style,
+                (ScalarStyle::SingleQuoted, None) => {
+                    emitter::ScalarStyle::SingleQuoted
+                }
+                (ScalarStyle::DoubleQuoted, None) => {
+                    emitter::ScalarStyle::DoubleQuoted
+                }
+            };
+            let mut event =
+                emitter::Scalar::new(&value).with_style(style);
+            event.anchor = anchor(&scalar.anchor);
+            event.tag = tag(&scalar.tag);
+            emitter.emit(emitter::Event::Scalar(event))
+        }
+        Event::SequenceStart(sequence) => {
+            let mut event = emitter::Sequence::new();
+            event.anchor = anchor(&sequence.anchor);
+            event.tag = tag(&sequence.tag);
+            emitter.emit(emitter::Event::SequenceStart(event))
+        }
+        Event::SequenceEnd => emitter.emit(emitter::Event::SequenceEnd),
+        Event::MappingStart(mapping) => {
+            let mut event = emitter::Mapping::new();
+            event.anchor = anchor(&mapping.anchor);
+            event.tag = tag(&mapping.tag);
+            emitter.emit(emitter::Event::MappingStart(event))
+        }
+        Event::MappingEnd => emitter.emit(emitter::Event::MappingEnd),
+    }
+    .map_err(Error::from)
+}
+
+/// Writes the comments of each input event next to the same event in
+/// `output`, found by parsing `output` again, and each tag as it is written
+/// in `input`.
+fn place_comments(
+    input: &str,
+    items: &[Item<'_>],
+    order: &[usize],
+    output: &str,
+) -> Result<String> {
+    let lines = LineIndex::new(output.as_bytes());
+    let emitted = parse(&lines)?;
+    if emitted.len() != order.len() {
+        return Err(error::new(ErrorImpl::Message(
+            "the formatted output does not match the input".to_owned(),
+            None,
+        )));
+    }
+    let scalars = scalar_ranges(&lines, &emitted);
+    let columns = closed_columns(&lines, &emitted);
+    let count = lines.starts.len();
+    let mut before: Vec<Vec<String>> = vec![Vec::new(); count];
+    let mut after: Vec<Vec<&str>> = vec![Vec::new(); count];
+    let mut header = Vec::new();
+    let mut explicit_start = false;
+    let mut tags = Vec::new();
+    for (k, (&index, placed)) in order.iter().zip(&emitted).enumerate()
+    {
+        let item = &items[index];
+        let start = placed.span.start;
+        if let (Some(written), Some(emitted)) = (&item.tag, &placed.tag)
+        {
+            let written = &input[written.clone()];
+            if *written != output[emitted.clone()] {
+                tags.push((emitted.clone(), written));
+            }
+        }
+        let mut comments = item.before.as_slice();
+        let indent = match item.event {
+            Event::StreamStart
+            | Event::StreamEnd
+            | Event::DocumentStart
+            | Event::DocumentEnd => {
+                // No blank lines at the end of a document.
+                let blank = comments
+                    .iter()
+                    .rev()
+                    .take_while(|comment| comment.is_empty())
+                    .count();
+                comments = &comments[..comments.len() - blank];
+                0
+            }
+            Event::SequenceEnd | Event::MappingEnd => {
+                columns[k].unwrap_or_else(|| indentation(&lines, start))
+            }
+            _ => indentation(&lines, start),
+        };
+        // Nor at the start of one.
+        let first = k == 1
+            || k > 0
+                && match emitted[k - 1].event {
+                    Event::DocumentStart => true,
+                    Event::SequenceStart(_)
+                    | Event::MappingStart(_) => {
+                        emitted[k - 1].span.start == start
+                    }
+                    _ => false,
+                };
+        if first {
+            let blank = comments
+                .iter()
+                .take_while(|comment| comment.is_empty())
+                .count();
+            comments = &comments[blank..];
+        }
+        // The first document starts at the top, with its `---` marker
+        // after its comments.
+        if k == 1 {
+            header.extend_from_slice(comments);
+            explicit_start = !item.span.is_empty();
+            continue;
+        }
+        let mut line = lines.line(start);
+        while let Some(scalar) = enclosing(&scalars, lines.starts[line])
+        {
+            line = lines.line(scalar.start);
+        }
+        before[line].extend(comments.iter().map(|comment| {
+            if comment.is_empty() {
+                String::new()
+            } else {
+                format!("{}{}", " ".repeat(indent), comment)
+            }
+        }));
+        if let Some(comment) = &item.after {
+            // Not where it would become part of a multi-line scalar.
+            let mut line = trailing_line(&lines, placed);
+            while let Some(scalar) = enclosing(
+                &scalars,
+                line_end(lines.input, lines.starts[line]),
+            ) {
+                line = lines.line(scalar.end - 1);
+            }
+            after[line].push(comment);
+        }
+    }
+
+    let mut formatted = String::with_capacity(output.len() * 2);
+    for comment in header {
+        formatted.push_str(&comment);
+        formatted.push('\n');
+    }
+    if explicit_start {
+        formatted.push_str("---\n");
+    }
+    let mut next_tag = 0;
+    for (line, &start) in lines.starts.iter().enumerate() {
+        for comment in &before[line] {
+            formatted.push_str(comment);
+            formatted.push('\n');
+        }
+        if start == output.len() {
+            break;
+        }
+        let end = line_end(lines.input, start);
+        let mut pos = start;
+        while let Some((tag, written)) =
+            tags.get(next_tag).filter(|(tag, _)| tag.start < end)
+        {
+            formatted.push_str(&output[pos..tag.start]);
+            formatted.push_str(written);
+            pos = tag.end;
+            next_tag += 1;
+        }
+        formatted.push_str(&output[pos..end]);
+        for comment in &after[line] {
+            formatted.push(' ');
+            formatted.push_str(comment);
+        }
+        formatted.push('\n');
+    }
+    while formatted.ends_with("\n\n") {
+        let _ = formatted.pop();
+    }
+    Ok(formatted)
+}
+
+/// Returns the number of spaces at the start of the line holding `pos`.
+fn indentation(lines: &LineIndex<'_>, pos: usize) -> usize {
+    let start = lines.starts[lines.line(pos)];
+    lines.input[start..]
+        .iter()
+        .take_while(|&&byte| byte == b' ')
+        .count()
+}
+
+/// Returns the scalar range that `pos` lies strictly within.
+fn enclosing(
+    ranges: &[Range<usize>],
+    pos: usize,
+) -> Option<Range<usize>> {
+    let index = ranges.partition_point(|range| range.start < pos);
+    ranges[..index]
+        .last()
+        .filter(|range| pos < range.end)
+        .cloned()
+}
+
+/// Returns the line at whose end a comment about `placed` goes: the header
+/// line of a block scalar, or else the line on which the event ends.
+fn trailing_line(lines: &LineIndex<'_>, placed: &Item<'_>) -> usize {
+    match &placed.event {
+        Event::Scalar(scalar)
+            if matches!(
+                scalar.style,
+                ScalarStyle::Literal | ScalarStyle::Folded
+            ) =>
+        {
+            lines.line(placed.span.start)
+        }
+        _ => lines.line(
+            placed.span.end.saturating_sub(1).max(placed.span.start),
+        ),
+    }
+}
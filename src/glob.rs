@@ -0,0 +1,349 @@
+//! Loading every YAML document of the files matching a glob pattern.
+//!
+//! Tools that scan a repository for manifests, such as CI definitions or
+//! Kubernetes resources, need the same loop again and again: find the
+//! files, read them, and split them into their documents. [`load_glob`]
+//! does all three, yielding each document with the path of its file:
+//!
+//! ```no_run
+//! for document in serde_yml::load_glob("deploy/**/*.yaml") {
+//!     let (path, value) = document?;
+//!     println!("{}: {}", path.display(), value["kind"]);
+//! }
+//! # Ok::<(), serde_yml::Error>(())
+//! ```
+//!
+//! Patterns are split on `/` and each component is matched against the
+//! names of the entries of a directory:
+//!
+//! - `*` matches any run of characters, and `?` any single character.
+//! - `[abc]` matches one of the listed characters, `[a-z]` one in the
+//!   range, and `[!abc]` one that is not listed.
+//! - A component that is exactly `**` matches any number of directories,
+//!   including none. At the end of a pattern, as in `deploy/**`, it
+//!   matches every file below the directory.
+//!
+//! As in a shell, wildcards do not match a `.` at the start of a name, so
+//! hidden files and directories such as `.git` are only visited when the
+//! pattern names them. `**` does not descend into symbolic links to
+//! directories, which could otherwise lead back to a directory already
+//! walked. This module is available with the `fs` feature.
+
+use crate::{
+    de::{Deserializer, DeserializerOptions, EmptyDocuments},
+    modules::error::{self, ErrorImpl, Result},
+    value::Value,
+};
+use serde::Deserialize;
+use std::{
+    collections::VecDeque,
+    fs, io,
+    path::{Path, PathBuf},
+    vec,
+};
+
+/// Returns an iterator over the YAML documents of the files matching
+/// `pattern`, each with the path of its file.
+///
+/// Files are visited in lexical order of their paths, each once even when
+/// several parts of the pattern match it, and their documents in the
+/// order they appear, so a file holding several documents
+/// separated by `---` yields one item for each. Empty documents, and so
+/// empty files, are left out. Directories are walked when the iterator is first advanced, and
+/// each file is only read once the documents before it have been taken.
+///
+/// Errors do not end the iteration. A directory that cannot be read, a
+/// file that cannot be read and a file that is not valid YAML each yield
+/// one error naming the path, after the documents of that file read up to
+/// the error, and the iteration moves on to the next file.
+///
+/// # Examples
+///
+/// ```
+/// use std::fs;
+///
+/// let dir = std::env::temp_dir().join("serde_yml_doc_load_glob");
+/// fs::create_dir_all(dir.join("apps")).unwrap();
+/// fs::write(dir.join("apps/web.yaml"), "kind: Service\n---\nkind: Deployment\n").unwrap();
+/// fs::write(dir.join("apps/notes.txt"), "not yaml: [\n").unwrap();
+///
+/// let pattern = format!("{}/**/*.yaml", dir.display());
+/// let kinds: Vec<String> = serde_yml::load_glob(&pattern)
+///     .map(|document| {
+///         let (path, value) = document.unwrap();
+///         assert_eq!(path, dir.join("apps/web.yaml"));
+///         value["kind"].as_str().unwrap().to_owned()
+///     })
+///     .collect();
+/// assert_eq!(kinds, ["Service", "Deployment"]);
+/// # fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn load_glob(pattern: &str) -> LoadGlob {
+    LoadGlob {
+        pattern: pattern.to_owned(),
+        files: None,
+        documents: VecDeque::new(),
+    }
+}
+
+/// The iterator returned by [`load_glob`].
+#[derive(Debug)]
+pub struct LoadGlob {
+    /// The pattern the files are matched against.
+    pattern: String,
+    /// The matching files not read yet, found on the first call to
+    /// `next`.
+    files: Option<vec::IntoIter<Result<PathBuf>>>,
+    /// The documents of the file read last that were not taken yet.
+    documents: VecDeque<Result<(PathBuf, Value)>>,
+}
+
+impl Iterator for LoadGlob {
+    type Item = Result<(PathBuf, Value)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(document) = self.documents.pop_front() {
+                return Some(document);
+            }
+            let pattern = &self.pattern;
+            let files = self
+                .files
+                .get_or_insert_with(|| find(pattern).into_iter());
+            match files.next()? {
+                Ok(path) => self.documents = read(path),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+/// Finds the files matching `pattern`, in lexical order of their paths,
+/// along with the errors met while walking the directories.
+fn find(pattern: &str) -> Vec<Result<PathBuf>> {
+    let components: Vec<&str> =
+        pattern.split('/').filter(|c| !c.is_empty()).collect();
+    let literal = components
+        .iter()
+        .take_while(|component| !is_wildcard(component))
+        .count();
+    let mut base = PathBuf::new();
+    if pattern.starts_with('/') {
+        base.push("/");
+    }
+    base.extend(&components[..literal]);
+
+    let mut found = Vec::new();
+    if literal == components.len() {
+        if base.is_file() {
+            found.push(Ok(base));
+        }
+    } else {
+        walk(&base, &components[literal..], &mut found);
+    }
+    // Errors sort first, in the order they were met.
+    found.sort_by(|a, b| a.as_ref().ok().cmp(&b.as_ref().ok()));
+    // A pattern such as `**/**` reaches the same file in several ways.
+    found.dedup_by(|a, b| matches!((a, b), (Ok(a), Ok(b)) if a == b));
+    found
+}
+
+/// Collects the files below `dir` matching `pattern`, whose components
+/// are matched against the path of each file relative to `dir`.
+fn walk(
+    dir: &Path,
+    pattern: &[&str],
+    found: &mut Vec<Result<PathBuf>>,
+) {
+    let (first, rest) = match pattern.split_first() {
+        Some(split) => split,
+        None => return,
+    };
+    if *first == "**" && rest.is_empty() {
+        // A trailing `**` matches every file below `dir`.
+        return walk(dir, &["**", "*"], found);
+    }
+    let entries = match read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            let dir = if dir.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                dir
+            };
+            found.push(Err(error::in_file(
+                error::new(ErrorImpl::IoError(err)),
+                dir,
+            )));
+            return;
+        }
+    };
+    if *first == "**" {
+        // `**` matching no directory at all.
+        walk(dir, rest, found);
+    }
+    for (name, path, is_dir) in entries {
+        if *first == "**" {
+            if !name.starts_with('.') && is_dir {
+                walk(&path, pattern, found);
+            }
+        } else if matches(first, &name) {
+            if rest.is_empty() {
+                if path.is_file() {
+                    found.push(Ok(path));
+                }
+            } else if path.is_dir() {
+                walk(&path, rest, found);
+            }
+        }
+    }
+}
+
+/// Lists the names and paths of the entries of `dir`, the current
+/// directory if it is empty, along with whether each is a directory rather
+/// than a symbolic link to one.
+fn read_dir(dir: &Path) -> io::Result<Vec<(String, PathBuf, bool)>> {
+    let read = if dir.as_os_str().is_empty() {
+        fs::read_dir(".")?
+    } else {
+        fs::read_dir(dir)?
+    };
+    let mut entries = Vec::new();
+    for entry in read {
+        let entry = entry?;
+        let is_dir = entry.file_type()?.is_dir();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let path = dir.join(&name);
+        entries.push((name, path, is_dir));
+    }
+    Ok(entries)
+}
+
+/// Reads the documents of the file at `path`, stopping at the first
+/// error.
+fn read(path: PathBuf) -> VecDeque<Result<(PathBuf, Value)>> {
+    let mut documents = VecDeque::new();
+    let bytes = match fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            let err = error::new(ErrorImpl::IoError(err));
+            documents.push_back(Err(error::in_file(err, &path)));
+            return documents;
+        }
+    };
+    let options = DeserializerOptions {
+        empty_documents: EmptyDocuments::Skip,
+        ..DeserializerOptions::default()
+    };
+    for document in
+        Deserializer::from_slice(&bytes).with_options(options)
+    {
+        match Value::deserialize(document) {
+            Ok(value) => documents.push_back(Ok((path.clone(), value))),
+            Err(err) => {
+                documents.push_back(Err(error::in_file(err, &path)));
+                break;
+            }
+        }
+    }
+    documents
+}
+
+/// Returns whether a component of a pattern holds a wildcard.
+fn is_wildcard(component: &str) -> bool {
+    component.contains(['*', '?', '['])
+}
+
+/// Matches the name of a directory entry against a component of a
+/// pattern.
+fn matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    if name.first() == Some(&'.') && pattern.first() != Some(&'.') {
+        return false;
+    }
+    matches_from(&pattern, &name)
+}
+
+/// Matches `name` against `pattern` from left to right. On a mismatch the
+/// last `*` seen takes one more character and matching resumes after it,
+/// so the work stays proportional to the product of the two lengths
+/// however many `*`s the pattern holds.
+fn matches_from(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // The position after the last `*` and the name position it resumes at.
+    let mut star = None;
+    while n < name.len() {
+        if pattern.get(p) == Some(&'*') {
+            p += 1;
+            star = Some((p, n));
+            continue;
+        }
+        if let Some(len) = step(&pattern[p..], name[n]) {
+            p += len;
+            n += 1;
+            continue;
+        }
+        match star {
+            Some((after, resume)) => {
+                p = after;
+                n = resume + 1;
+                star = Some((after, n));
+            }
+            None => return false,
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Returns the length of the token at the start of `pattern` if it matches
+/// the character `c`, for any token but `*`.
+fn step(pattern: &[char], c: char) -> Option<usize> {
+    let (len, matched) = match pattern.first()? {
+        '?' => (1, true),
+        '[' => match class(&pattern[1..]) {
+            Some((in_class, len)) => (len + 1, in_class(c)),
+            // A `[` without a closing `]` stands for itself.
+            None => (1, c == '['),
+        },
+        &literal => (1, c == literal),
+    };
+    if matched {
+        Some(len)
+    } else {
+        None
+    }
+}
+
+/// Parses the character class that follows a `[`, returning whether a
+/// character is in the class and the length of the class up to and
+/// including its `]`.
+fn class(
+    pattern: &[char],
+) -> Option<(impl Fn(char) -> bool + '_, usize)> {
+    let negated = matches!(pattern.first(), Some('!'));
+    let start = usize::from(negated);
+    // A `]` right after the `[` is a member of the class.
+    let end = pattern
+        .iter()
+        .enumerate()
+        .skip(start + 1)
+        .find(|(_, &c)| c == ']')
+        .map(|(i, _)| i)?;
+    let members = &pattern[start..end];
+    let matched = move |c: char| {
+        let mut i = 0;
+        let mut found = false;
+        while i < members.len() {
+            if i + 2 < members.len() && members[i + 1] == '-' {
+                found |= (members[i]..=members[i + 2]).contains(&c);
+                i += 3;
+            } else {
+                found |= members[i] == c;
+                i += 1;
+            }
+        }
+        found != negated
+    };
+    Some((matched, end + 1))
+}
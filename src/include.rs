@@ -0,0 +1,363 @@
+//! Composition of YAML documents through an `!include` tag.
+//!
+//! A scalar tagged with `!include` is replaced by the document found at the
+//! path it names:
+//!
+//! ```yaml
+//! name: api
+//! database: !include shared/database.yaml
+//! replicas:
+//!   - !include replicas/eu.yaml
+//!   - !include replicas/us.yaml
+//! ```
+//!
+//! Paths are never opened directly. Every include goes through a
+//! [`Resolver`], which decides what a path means and which sources may be
+//! read, so untrusted documents cannot reach outside the sandbox the
+//! resolver enforces. [`DirResolver`] is provided for the common case of a
+//! directory of configuration files.
+//!
+//! Included documents may themselves contain `!include` tags. Expansion fails
+//! when a document includes itself, directly or indirectly, and when the
+//! nesting exceeds the depth limit configured on the [`Includer`]. An error
+//! about an `!include` tag gives the path and position of the tag, and an
+//! error found in an included document names that document.
+
+use crate::{
+    modules::error::{self, Error, Result},
+    path::{Path as OwnedPath, Segment},
+    validate::locate,
+    value::{TaggedValue, Value},
+};
+use serde::de::{DeserializeOwned, Error as _};
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+/// The tag recognised by the include expansion, without its leading `!`.
+pub const INCLUDE_TAG: &str = "include";
+
+/// The default limit on how deeply includes may be nested.
+pub const DEFAULT_MAX_DEPTH: usize = 32;
+
+/// A source of YAML text returned by a [`Resolver`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Source {
+    /// A stable identifier for the source, such as its canonical path.
+    ///
+    /// Identifiers are used for cycle detection, so two includes referring
+    /// to the same document must resolve to the same identifier. They are
+    /// also passed back to the resolver as the parent of nested includes.
+    pub id: String,
+    /// The YAML text of the source.
+    pub contents: String,
+}
+
+/// Resolves the target of an `!include` tag to YAML text.
+///
+/// Implementations act as the sandbox for include expansion: they decide
+/// how a path is interpreted relative to the including document and may
+/// refuse any path by returning an error.
+pub trait Resolver {
+    /// Resolves `path`, as written after `!include`, to a [`Source`].
+    ///
+    /// `parent` is the [`Source::id`] of the document containing the tag, or
+    /// `None` for the top-level document.
+    fn resolve(
+        &mut self,
+        parent: Option<&str>,
+        path: &str,
+    ) -> Result<Source>;
+}
+
+impl<F> Resolver for F
+where
+    F: FnMut(Option<&str>, &str) -> Result<Source>,
+{
+    fn resolve(
+        &mut self,
+        parent: Option<&str>,
+        path: &str,
+    ) -> Result<Source> {
+        self(parent, path)
+    }
+}
+
+/// A [`Resolver`] that reads files from within a single root directory.
+///
+/// Include paths are interpreted relative to the directory of the including
+/// file, or relative to the root for the top-level document. Absolute paths
+/// and paths that would leave the root directory are rejected, including
+/// paths that only leave it through a symbolic link.
+#[derive(Clone, Debug)]
+pub struct DirResolver {
+    root: PathBuf,
+}
+
+impl DirResolver {
+    /// Creates a resolver confined to `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        DirResolver { root: root.into() }
+    }
+
+    /// Returns the root directory of this resolver.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+impl Resolver for DirResolver {
+    fn resolve(
+        &mut self,
+        parent: Option<&str>,
+        path: &str,
+    ) -> Result<Source> {
+        let base = parent
+            .and_then(|parent| Path::new(parent).parent())
+            .unwrap_or_else(|| Path::new(""));
+        let mut relative = base.to_path_buf();
+        for component in Path::new(path).components() {
+            match component {
+                Component::Normal(part) => relative.push(part),
+                Component::CurDir => {}
+                Component::ParentDir if relative.pop() => {}
+                Component::ParentDir
+                | Component::RootDir
+                | Component::Prefix(_) => {
+                    return Err(Error::custom(format_args!(
+                        "include path {:?} escapes the root directory",
+                        path,
+                    )));
+                }
+            }
+        }
+        let read_error = |err| {
+            Error::custom(format_args!(
+                "failed to read included file {:?}: {}",
+                path, err,
+            ))
+        };
+        let root = self.root.canonicalize().map_err(read_error)?;
+        let file = self
+            .root
+            .join(&relative)
+            .canonicalize()
+            .map_err(read_error)?;
+        if !file.starts_with(&root) {
+            return Err(Error::custom(format_args!(
+                "include path {:?} escapes the root directory",
+                path,
+            )));
+        }
+        let contents = fs::read_to_string(file).map_err(read_error)?;
+        let id = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        Ok(Source { id, contents })
+    }
+}
+
+/// Expands `!include` tags using a [`Resolver`].
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::include::{Includer, Source};
+///
+/// let resolver = |_parent: Option<&str>, path: &str| -> serde_yml::Result<Source> {
+///     match path {
+///         "db.yaml" => Ok(Source {
+///             id: path.to_owned(),
+///             contents: "host: localhost\nport: 5432\n".to_owned(),
+///         }),
+///         _ => Err(serde::de::Error::custom("not found")),
+///     }
+/// };
+///
+/// let mut includer = Includer::new(resolver);
+/// let value: serde_yml::Value =
+///     includer.from_str("database: !include db.yaml\n").unwrap();
+/// assert_eq!(value["database"]["port"], 5432);
+/// ```
+#[derive(Debug)]
+pub struct Includer<R> {
+    resolver: R,
+    max_depth: usize,
+    stack: Vec<String>,
+}
+
+impl<R> Includer<R>
+where
+    R: Resolver,
+{
+    /// Creates an includer that resolves paths with `resolver`.
+    pub fn new(resolver: R) -> Self {
+        Includer {
+            resolver,
+            max_depth: DEFAULT_MAX_DEPTH,
+            stack: Vec::new(),
+        }
+    }
+
+    /// Sets how deeply includes may be nested.
+    ///
+    /// A limit of zero rejects every `!include` tag.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Deserializes an instance of type `T` from YAML text after expanding
+    /// its `!include` tags.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any document is invalid YAML, if the resolver
+    /// fails, if includes form a cycle or nest too deeply, or if the result
+    /// cannot be deserialized into `T`.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str<T>(&mut self, s: &str) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let value = crate::from_str(s)?;
+        let value = self.expand_source(value, Some(s))?;
+        T::deserialize(value)
+    }
+
+    /// Deserializes an instance of type `T` from the document that
+    /// `path` resolves to, expanding its `!include` tags.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Includer::from_str`].
+    pub fn from_path<T>(&mut self, path: &str) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let source = self.resolver.resolve(None, path)?;
+        let value = self.load(source)?;
+        T::deserialize(value)
+    }
+
+    /// Expands the `!include` tags of a value that has already been loaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an included document is invalid YAML, if the
+    /// resolver fails, or if includes form a cycle or nest too deeply.
+    pub fn expand(&mut self, value: Value) -> Result<Value> {
+        self.expand_source(value, None)
+    }
+
+    /// Expands the `!include` tags of a value loaded from `text`, in which
+    /// errors about the tags are located.
+    fn expand_source(
+        &mut self,
+        mut value: Value,
+        text: Option<&str>,
+    ) -> Result<Value> {
+        let mut path = OwnedPath::root();
+        match self.expand_in_place(&mut value, &mut path) {
+            Ok(()) => Ok(value),
+            Err(err) if err.file().is_none() => {
+                let mark = err.path().and_then(|path| {
+                    locate(text?).ok()?.get(path).copied()
+                });
+                Err(error::relocate(err, mark))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    fn expand_in_place(
+        &mut self,
+        value: &mut Value,
+        path: &mut OwnedPath,
+    ) -> Result<()> {
+        match value {
+            Value::Sequence(sequence) => {
+                for (index, element) in sequence.iter_mut().enumerate()
+                {
+                    path.push(index);
+                    let result = self.expand_in_place(element, path);
+                    let _ = path.pop();
+                    result?;
+                }
+            }
+            Value::Mapping(mapping) => {
+                for (key, element) in mapping.iter_mut() {
+                    path.push(match key.as_str() {
+                        Some(key) => Segment::from(key),
+                        None => Segment::from("?"),
+                    });
+                    let result = self.expand_in_place(element, path);
+                    let _ = path.pop();
+                    result?;
+                }
+            }
+            Value::Tagged(tagged) if tagged.tag == INCLUDE_TAG => {
+                let target = match &tagged.value {
+                    Value::String(target) => target.clone(),
+                    other => {
+                        return Err(error::with_path(
+                            Error::custom(format_args!(
+                                "!{} expects a path string, found {:?}",
+                                INCLUDE_TAG, other,
+                            )),
+                            path,
+                        ));
+                    }
+                };
+                *value = self
+                    .include(&target)
+                    .map_err(|err| error::with_path(err, path))?;
+            }
+            Value::Tagged(tagged) => {
+                let TaggedValue { value, .. } = &mut **tagged;
+                self.expand_in_place(value, path)?;
+            }
+            Value::Null
+            | Value::Bool(_)
+            | Value::Number(_)
+            | Value::String(_) => {}
+        }
+        Ok(())
+    }
+
+    fn include(&mut self, path: &str) -> Result<Value> {
+        if self.stack.len() >= self.max_depth {
+            return Err(Error::custom(format_args!(
+                "include depth limit of {} exceeded while including {:?}",
+                self.max_depth, path,
+            )));
+        }
+        let parent = self.stack.last().map(String::as_str);
+        let source = self.resolver.resolve(parent, path)?;
+        self.load(source)
+    }
+
+    fn load(&mut self, source: Source) -> Result<Value> {
+        if let Some(start) =
+            self.stack.iter().position(|id| *id == source.id)
+        {
+            let mut chain = self.stack[start..].to_vec();
+            chain.push(source.id);
+            return Err(Error::custom(format_args!(
+                "include cycle detected: {}",
+                chain.join(" -> "),
+            )));
+        }
+        let in_source =
+            |err| error::in_file(err, Path::new(&source.id));
+        let value: Value =
+            crate::from_str(&source.contents).map_err(in_source)?;
+        self.stack.push(source.id.clone());
+        let result = self.expand_source(value, Some(&source.contents));
+        let _ = self.stack.pop();
+        result.map_err(in_source)
+    }
+}
@@ -0,0 +1,279 @@
+//! The writer trait accepted by the serializer.
+//!
+//! With the `std` feature, which is enabled by default, this module
+//! re-exports [`std::io::Write`] and its error type, so the serializer
+//! writes to anything implementing `std::io::Write`.
+//!
+//! Without the `std` feature the crate builds with `#![no_std]`, using only
+//! `alloc`, and this module provides a minimal [`Write`] trait in its place.
+//! It is implemented for `Vec<u8>`, for mutable references and boxes of
+//! writers and for [`Sink`], which discards its input; other
+//! destinations, such as a UART or a fixed buffer, can implement it
+//! directly.
+//!
+//! [`FmtWriter`] adapts a [`core::fmt::Write`] destination, such as a
+//! `String` or a `fmt::Formatter`, to the writer trait.
+
+#[cfg(feature = "std")]
+pub use std::io::{sink, Error, Result, Sink, Write};
+
+#[cfg(not(feature = "std"))]
+pub use self::core_io::{sink, Error, Result, Sink, Write};
+
+use crate::lib::Vec;
+use core::{fmt, str};
+
+#[cfg(not(feature = "std"))]
+mod core_io {
+    use crate::lib::{Box, StdError, Vec};
+    use core::fmt::{self, Display};
+
+    /// An error reported by a [`Write`] implementation.
+    // Not `Copy`, like `std::io::Error`, so that code handling it builds
+    // the same way with and without `std`.
+    #[allow(missing_copy_implementations)]
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct Error {
+        message: &'static str,
+    }
+
+    impl Error {
+        /// Creates an error with a static description.
+        pub const fn new(message: &'static str) -> Self {
+            Error { message }
+        }
+
+        /// Returns the description of the error.
+        pub fn message(&self) -> &'static str {
+            self.message
+        }
+    }
+
+    impl Display for Error {
+        fn fmt(
+            &self,
+            formatter: &mut fmt::Formatter<'_>,
+        ) -> fmt::Result {
+            formatter.write_str(self.message)
+        }
+    }
+
+    impl StdError for Error {}
+
+    /// The result of a [`Write`] operation.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// A destination for the bytes written by the serializer.
+    pub trait Write {
+        /// Writes the whole buffer.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the buffer could not be written entirely.
+        fn write_all(&mut self, buf: &[u8]) -> Result<()>;
+
+        /// Flushes any buffered output to its destination.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the output could not be flushed.
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Write for Vec<u8> {
+        fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+            self.extend_from_slice(buf);
+            Ok(())
+        }
+    }
+
+    impl<W> Write for &mut W
+    where
+        W: ?Sized + Write,
+    {
+        fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+            (**self).write_all(buf)
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            (**self).flush()
+        }
+    }
+
+    impl<W> Write for Box<W>
+    where
+        W: ?Sized + Write,
+    {
+        fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+            (**self).write_all(buf)
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            (**self).flush()
+        }
+    }
+
+    /// A writer that discards everything written to it, created by [`sink`].
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct Sink;
+
+    /// Creates a writer that discards everything written to it.
+    pub const fn sink() -> Sink {
+        Sink
+    }
+
+    impl Write for Sink {
+        fn write_all(&mut self, _buf: &[u8]) -> Result<()> {
+            Ok(())
+        }
+    }
+}
+
+/// Adapts a [`fmt::Write`] destination, such as a `String` or the
+/// `fmt::Formatter` of a `Display` implementation, to the [`Write`] trait
+/// accepted by the serializer.
+///
+/// The serializer writes UTF-8, which is passed on to the destination as
+/// `str` chunks as it arrives. A character split between two writes is
+/// held back until its last byte is written.
+///
+/// # Examples
+///
+/// ```
+/// use serde::Serialize;
+/// use serde_yml::io::FmtWriter;
+/// use serde_yml::ser::SerializerOptions;
+///
+/// let mut yaml = String::new();
+/// let options = SerializerOptions {
+///     explicit_document_start: true,
+///     ..SerializerOptions::default()
+/// };
+/// let mut ser =
+///     serde_yml::Serializer::new(FmtWriter::new(&mut yaml)).with_options(options);
+/// vec!["a", "é"].serialize(&mut ser).unwrap();
+/// drop(ser);
+/// assert_eq!(yaml, "---\n- a\n- é\n");
+/// ```
+#[derive(Debug)]
+pub struct FmtWriter<W> {
+    inner: W,
+    /// The leading bytes of a character whose last byte is yet to come.
+    pending: Vec<u8>,
+}
+
+impl<W> FmtWriter<W>
+where
+    W: fmt::Write,
+{
+    /// Creates a writer passing what is written to it on to `inner`.
+    pub fn new(inner: W) -> Self {
+        FmtWriter {
+            inner,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Returns the destination of the writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// Writes the characters of `buf`, holding back a trailing incomplete
+    /// character.
+    fn write_utf8(&mut self, mut buf: &[u8]) -> Result<()> {
+        while !self.pending.is_empty() {
+            let (&byte, rest) = match buf.split_first() {
+                Some(split) => split,
+                None => return Ok(()),
+            };
+            self.pending.push(byte);
+            buf = rest;
+            match str::from_utf8(&self.pending) {
+                Ok(s) => {
+                    self.inner.write_str(s).map_err(fmt_error)?;
+                    self.pending.clear();
+                }
+                Err(error) if error.error_len().is_some() => {
+                    return Err(utf8_error());
+                }
+                Err(_) => {}
+            }
+        }
+        let valid = match str::from_utf8(buf) {
+            Ok(s) => s,
+            Err(error) => {
+                if error.error_len().is_some() {
+                    return Err(utf8_error());
+                }
+                let (valid, rest) = buf.split_at(error.valid_up_to());
+                self.pending.extend_from_slice(rest);
+                str::from_utf8(valid).map_err(|_| utf8_error())?
+            }
+        };
+        self.inner.write_str(valid).map_err(fmt_error)
+    }
+
+    /// Fails if a character is left incomplete.
+    fn check_complete(&self) -> Result<()> {
+        if self.pending.is_empty() {
+            Ok(())
+        } else {
+            Err(utf8_error())
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W> Write for FmtWriter<W>
+where
+    W: fmt::Write,
+{
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.write_utf8(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.check_complete()
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<W> Write for FmtWriter<W>
+where
+    W: fmt::Write,
+{
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        self.write_utf8(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.check_complete()
+    }
+}
+
+#[cfg(feature = "std")]
+fn fmt_error(error: fmt::Error) -> Error {
+    Error::new(std::io::ErrorKind::Other, error)
+}
+
+#[cfg(not(feature = "std"))]
+fn fmt_error(_: fmt::Error) -> Error {
+    Error::new("an error occurred when formatting an argument")
+}
+
+#[cfg(feature = "std")]
+fn utf8_error() -> Error {
+    Error::new(
+        std::io::ErrorKind::InvalidData,
+        "stream did not contain valid UTF-8",
+    )
+}
+
+#[cfg(not(feature = "std"))]
+fn utf8_error() -> Error {
+    Error::new("stream did not contain valid UTF-8")
+}
@@ -32,12 +32,90 @@
 //! - Support for serializing/deserializing enums using a YAML map with a single key-value pair through the `singleton_map` module
 //! - Recursive application of `singleton_map` serialization/deserialization to all enums within a data structure using the `singleton_map_recursive` module
 //! - Serialization and deserialization of optional enum fields using the `singleton_map_optional` module
+//! - Serialization and deserialization of optional fields whose nested enums all use the singleton map representation through the `singleton_map_recursive_optional` module
 //! - Handling of nested enum structures with optional inner enums using the `singleton_map_recursive` module
+//! - Serialization and deserialization of maps keyed by enums, with keys in the singleton map representation, through the `enum_keyed_map` module
+//! - Deserialization of fields given either as a shorthand scalar parsed with `FromStr` or as a full mapping through the `string_or_struct` module
+//! - Serialization and deserialization of enums as a mapping holding the variant name next to the variant fields, like Kubernetes `kind:` documents, through the `flatten_tagged` and `flatten_tagged_recursive` modules
+//! - Deserialization of numbers written as strings, with underscores or in hexadecimal, octal or binary, through the `number_from_string` module and the `lenient_numbers` option, and of strings written as numbers through the `string_from_number` module
+//! - Deterministic output for `HashSet`, `BinaryHeap` and other unordered collections, written in ascending order, through the `sorted` module
+//! - Deserialization of the YAML 1.1 booleans `yes`, `no`, `on`, `off`, `y` and `n` for selected fields through the `bool_yaml11` module
+//! - Adapters for common external types behind optional features: `uuid` for `uuid::Uuid`, `chrono_datetime` and `time_offsetdatetime` for RFC 3339 timestamps under the `chrono` and `time` features, and `duration_humantime` for durations like `30s` or `1h 30m` under the `humantime` feature
+//! - Human-friendly byte sizes like `10MiB` or `512k` and durations like `1h30m` or `250ms` through the `byte_size` and `duration` modules
+//! - A shared `Path` type naming nodes by mapping keys and sequence indices, in dotted or JSON pointer form, returned by `Error::path` and accepted by `Value::get_path` and the typed getters `Value::get_as` and `Value::get_or`, whose errors name the path
+//! - Sequences and mappings as mapping keys, written as complex keys like `? [web, 80]`, for maps keyed by tuples, structs or enums such as `HashMap<(String, u16), V>`, and lookup of such keys and of boolean and integer keys through `Mapping::get`
+//! - Tagged enum values like `!Variant 1` inside `#[serde(flatten)]` structs and untagged or internally tagged enums
+//! - Fallible `Value::try_as_bool`, `try_as_str` and other `try_as_*` getters returning an `Error` that describes the value found instead of `None`
 //! - Customization of serialization and deserialization logic for enums using the `singleton_map_with` module and custom helper functions
+//! - Dispatch of heterogeneous documents to registered types by a discriminator field, without parsing twice, through the `dynamic` module
+//! - Counting and splitting the documents of a multi-document stream with `de::document_count` and `de::split_documents`, using the parser so that `---` inside block scalars is not taken for a document marker
+//! - Per-document metadata, with the anchors each document defines and how often they are aliased, its `%YAML` version, the tags it uses and its byte span, through `de::document_metadata`, `Deserializer::metadata` and `Document::metadata`
+//! - Options for mapping values left out of the input, read as null, as an empty string or rejected through `de::MissingValues`, and for rejecting null mapping keys through `deny_null_keys`
+//! - A `reject_yaml_1_1_scalars` option that rejects, with their location, plain scalars that YAML 1.1 reads differently, such as `0777` octals, `1:30:00` sexagesimals, `yes`/`off` booleans and `NaN` spellings, to keep documents YAML 1.2-clean
+//! - Exact integers of any size in `Number` under the `bigint` feature, so values beyond `u64` and `i64`, such as `340282366920938463463374607431768211456`, round-trip instead of becoming floats, with `Number::as_big_int` and `From<BigInt>`
+//! - A documented total order with `Ord` and `Eq`, consistent with `Hash`, for `Value`, `Number`, `Mapping` and `TaggedValue`, with NaN sorting above every other float, so values can be sorted and used as `BTreeMap` keys
+//! - Explicit `---` and `...` document markers, for every document or changed between documents, through `ser::SerializerOptions`, `Serializer::with_options` and `ser::to_string_with_options`
+//! - Null values written as `null`, `~` or nothing after the colon, chosen with `ser::NullStyle`, to match the output of other tools
+//! - A `skip_none_fields` serializer option that leaves out `None` struct fields without `#[serde(skip_serializing_if)]` on each of them
+//! - Empty sequences and mappings written as `[]` and `{}`, as an empty block node or left out, chosen with `ser::EmptyCollections`
+//! - Control over the order in which struct fields and mapping keys are written, such as `apiVersion`, `kind` and `metadata` first for Kubernetes manifests, through the `key_order` serializer option
+//! - A low-level `libyml::emitter::Emitter`, generic over its writer, for custom serializers and streaming generators, configured through `EmitterBuilder` with the indent, line width, canonical output, Unicode escaping, line break and encoding
+//! - A stable, safe `events::Parser` iterating over the parser's events with their byte spans, anchors, tags and scalar styles, for syntax highlighters and editor tooling
+//! - Loading multi-document streams one `Value` at a time through `Loader::next_value`, with the span and anchors of each document, so that a document that fails to load does not stop the ones after it
+//! - Detection of alias cycles such as `a: &x {b: *x}`, reported as an error naming the anchor and the position of the alias
+//! - A `CompactValue` under the `compact` feature for large documents, storing mapping keys once through an `Interner` and collections as boxed slices, loaded directly with `Interner::parse` or converted to and from `Value`
+//! - Opt-in `${VAR}` / `${VAR:-default}` environment-variable interpolation in scalars through the `env` module
+//! - Composition of documents from multiple files with a sandboxed `!include` tag through the `include` module
+//! - Validation of documents against a JSON Schema, with the line and column of every violation, through the `validate` module
+//! - Folding and unfolding of folded block scalar text following the YAML line folding rules through the `utilities::folding` module
+//! - Lenient parsing of nodes carrying more than one tag, keeping the first and reporting the others, through `from_str_lenient_tags`
+//! - Fault-tolerant parsing for editors and other tooling through `from_str_lossy` and `Loader::recovering`, which skip broken lines and return a best-effort `Value` with a `Diagnostic` for each problem
+//! - Linting of YAML text with `yamllint`-style rules for trailing spaces, line length, indentation, duplicate keys, truthy values and document markers, reporting each problem with its span, through the `lint` module
+//! - Reformatting of YAML text with consistent indentation, optional key sorting and normalized quoting, keeping comments, blank lines, anchors, tags as written, directives and document markers, through `fmt::format_str`, which gives already formatted text back unchanged
+//! - Migration from `serde_yaml` 0.9 by changing a single import path to the `compat::serde_yaml` module
+//! - Round-tripping of hand-written files with the plain, quoted, literal or folded style of every scalar preserved through `value::StyledValue`, which also writes integers like `0x00FF`, `0o755` or `0b1010` back in their original radix, width and case
+//! - Typed deserialization that also reports whether each scalar was plain, quoted or a block scalar, through `from_str_with_styles` and `value::StyleMap`
+//! - Control over block scalars, with the `multiline_style` serializer option choosing literal, folded or double-quoted output for multi-line strings and `with::literal_block` and `with::folded_block` forcing a block scalar for one field
+//! - ASCII-only and UTF-16 output, with the `escape_unicode` serializer option escaping characters outside ASCII and the `encoding` option writing UTF-16LE or UTF-16BE with a byte order mark
+//! - Input in UTF-8, UTF-16LE or UTF-16BE, told apart by the byte order mark or the placement of null bytes, for `from_slice` and `from_reader`, with a clear error for UTF-32 and malformed UTF-16
+//! - Deterministic, versioned SHA-256 hashing of a `Value` through `Value::canonical_hash`, independent of key order, scalar style and anchors, for drift detection and signing
+//! - Pass-through of document fragments without building values through `value::RawYaml`, which captures the text of a node as written and writes its scalars back with their tags and styles
+//! - Repeated deserialization of many small inputs through `de::ReusableDeserializer`, which keeps its parser, input buffer and event buffers between inputs
+//! - Streaming conversion between formats through `convert::transcode`, with `convert::yaml_to_json` and `convert::json_to_yaml` under the default `serde_json` feature, without building an intermediate `Value`, and conversion between `Value` and `toml::Value` through `convert::toml` under the `toml` feature
+//! - Versioned documents through `with::versioned` and its `Versioned` wrapper, which read the version field first, dispatch to the layout of that version and write the latest version on serialize
+//! - `no_std` builds that need only `alloc` when the default `std` feature is disabled, with the serializer writing to the minimal `io::Write` trait and the reader, file and schema APIs left out
+//! - Output with LF, CRLF or classic Mac OS CR line endings through the `line_break` serializer option, and rejection of CR line endings in input through the `reject_cr_line_endings` option
+//! - A `pure-rust` feature that parses and emits YAML with a safe Rust port of libyaml instead of the `libyml` C port, producing the same events, output and error messages
+//! - Programmatic matching of errors through `Error::kind`, which sorts every error into an `ErrorKind` such as a parse error, a type mismatch, a missing field or an exceeded limit
+//! - Friendly error reports through `Error::render_snippet`, which prints the offending lines of the input with a caret under the error and a marker under the node the parser was reading
+//! - `miette::Diagnostic` for `Error` under the `miette` feature, with an error code from its `ErrorKind` and labels at the byte offsets of the error and of the node the parser was reading
+//! - Reporting every error of a document at once through the `collect_errors` option, which reads a failing field or element as a placeholder and goes on, returning all failures through `Error::errors`
+//! - Public `value::Serializer` and `value::Deserializer` building blocks converting to and from `Value`, with a `with_human_readable` option controlling how types such as `IpAddr` are represented
+//! - Honoring explicit core tags on scalars, so that `!!str 123` stays a string and `!!int "42"` reads as an integer, and writing them on every scalar through the `explicit_core_tags` serializer option
+//! - Canonical YAML output through the `canonical` serializer option, tagging and double-quoting every node as `yaml --canonical` does
+//! - Deserializing one node of a document by path through `from_str_at`, skipping the nodes around it without building a `Value`
+//! - Skipping heavy subtrees such as `metadata.managedFields` while deserializing through the `ignore_paths` option
+//! - Serializing into any `fmt::Write` destination, such as a `String` or a `Display` implementation, through `to_fmt_writer` and `io::FmtWriter`
+//! - `Display` and `FromStr` for `Value`, so that `value.to_string()` writes YAML without a final newline, padded like a string for scalars, and `"a: 1".parse::<Value>()` reads it
+//! - Soft warnings through `from_str_with_warnings`, which returns the value together with duplicate keys, YAML 1.1 scalars, unused anchors and integers too large for 64 bits found in the input
+//! - Applying a `with` module to the elements of a container through the `with::SeqOf`, `with::MapValues` and `with::OptionOf` combinators, which take the module as a `WithModule` type such as `with::SingletonMap`
+//! - Generating configuration structs with per-field defaults, environment variable overrides and a `load` function through the `yaml_config!` macro, with errors that name the file, line and column
+//! - Reading and writing files with `from_file` and `to_file`, which handle opening, buffering and byte order marks and name the file in every error they return
+//! - Loading a `conf.d` directory with `from_dir`, which merges its YAML files in lexical order as a `MergeStrategy` says and reports errors at the file and line that set the offending value
+//! - Loading every document of the files matching a glob pattern such as `configs/**/*.yaml` as `(path, Value)` pairs with `load_glob`, behind the `fs` feature
+//! - A low-allocation `value::BorrowedValue` whose strings borrow from the input where possible, loaded with `from_str_borrowed` and convertible into an owned `Value`
+//! - Configurable float output through `ser::FloatFormat`: a cap on significant digits, the range written in fixed notation, and YAML 1.1 style `1.0e+17` exponents, with the shortest round-trip digits as the default
+//! - Configurable spellings of NaN and infinity on output through `ser::SpecialFloatStyle`, and `DeserializerOptions::reject_non_finite_floats` to reject them on input for pipelines that forward to JSON
+//! - Checked indexing with `Value::try_index` and `try_index_mut`, whose errors name the index and list the available keys, and panic messages for `value["a"]["b"][0] = ...` that name the whole path
+//! - Deterministic output for snapshot tests: `Mapping::sorted_iter` iterating in insertion, reversed, key or key text order, and `Value::canonicalize` sorting every mapping by key
+//! - Updating an already populated value in place with `update_from_str`, `update_from_value` or the `update::Update` seed, overwriting only the keys present in the YAML
+//! - Untagged enums declared with `untagged!`, whose variants each hold a single value, trying each variant on the same YAML events and reporting why every variant failed; enums deriving `#[serde(untagged)]` still get serde's `data did not match any variant` error
 //!
 //! ## Rust Version Compatibility
 //!
 //! This library is compatible with Rust 1.60 and above.
+//! Builds without the `std` feature require Rust 1.81 and above, for
+//! `core::error::Error`.
 //!
 //! ## Installation
 //!
@@ -48,6 +126,14 @@
 //! serde_yml = "0.0.10"
 //! ```
 //!
+//! To build without the `libyml` dependency and its `unsafe` code, switch
+//! to the safe Rust backend:
+//!
+//! ```toml
+//! [dependencies]
+//! serde_yml = { version = "0.0.10", default-features = false, features = ["std", "pure-rust"] }
+//! ```
+//!
 //! ## Usage
 //!
 //! Serde YML offers a straightforward and intuitive API for working with YAML data in Rust. Here's a quick example of how to serialize and deserialize a Rust type:
@@ -123,11 +209,37 @@
 )]
 #![crate_name = "serde_yml"]
 #![crate_type = "lib"]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(not(any(feature = "libyml", feature = "pure-rust")))]
+compile_error!(
+    "serde_yml needs a YAML backend: enable the `libyml` feature (on by default) or the `pure-rust` feature"
+);
 
 // Re-export commonly used items from other modules
-pub use crate::de::{from_reader, from_slice, from_str, Deserializer}; // Deserialization functions
-pub use crate::modules::error::{Error, Location, Result}; // Error handling types
-pub use crate::ser::{to_string, to_writer, Serializer, State}; // Serialization functions
+pub use crate::capabilities::{capabilities, Capabilities}; // Runtime feature report
+#[cfg(feature = "std")]
+pub use crate::de::{from_file, from_reader}; // Deserialization from files and `std::io::Read`
+pub use crate::de::{
+    from_slice, from_slice_with_warnings, from_str, from_str_at,
+    from_str_borrowed, from_str_lenient_tags, from_str_lossy,
+    from_str_with_styles, from_str_with_unused, from_str_with_warnings,
+    Deserializer,
+}; // Deserialization functions
+#[cfg(feature = "std")]
+pub use crate::dir::{from_dir, MergeStrategy}; // Deserialization from a directory of files
+#[cfg(feature = "fs")]
+pub use crate::glob::load_glob; // Documents of the files matching a glob pattern
+pub use crate::modules::error::{Error, ErrorKind, Location, Result}; // Error handling types
+#[cfg(feature = "std")]
+pub use crate::ser::to_file; // Serialization into files
+pub use crate::ser::{
+    to_fmt_writer, to_string, to_writer, Serializer, State,
+}; // Serialization functions
+#[cfg(feature = "std")]
+pub use crate::update::{update_from_str, update_from_value}; // Updating existing values in place
 #[doc(inline)]
 pub use crate::value::{
     from_value, to_value, Index, Number, Sequence, Value,
@@ -136,12 +248,55 @@ pub use crate::value::{
 #[doc(inline)]
 pub use crate::mapping::Mapping; // Re-export the Mapping type for YAML mappings
 
+/// The `capabilities` module contains the runtime report of supported YAML features.
+pub mod capabilities;
+
+/// The `compat` module contains import paths matching the APIs of other YAML crates.
+pub mod compat;
+
+/// The `content` module holds serialized data to be written again later.
+mod content;
+
+/// The `convert` module contains streaming conversion between YAML and other formats.
+pub mod convert;
+
 /// The `de` module contains the library's YAML deserializer.
 pub mod de;
 
+/// The `dir` module contains the loader merging the YAML files of a directory.
+#[cfg(feature = "std")]
+pub mod dir;
+
+/// The `dynamic` module contains discriminator-based dispatch of heterogeneous documents.
+pub mod dynamic;
+
+/// The `env` module contains environment-variable interpolation helpers.
+#[cfg(feature = "std")]
+pub mod env;
+
+/// The `events` module contains a safe parser yielding YAML events with their spans.
+pub mod events;
+
+/// The `fmt` module contains the formatter that rewrites YAML text while keeping its comments.
+pub mod fmt;
+
+/// The `glob` module contains the loader of the YAML documents of the files matching a glob pattern.
+#[cfg(feature = "fs")]
+pub mod glob;
+
+/// The `include` module contains the `!include` tag expansion for composing documents.
+#[cfg(feature = "std")]
+pub mod include;
+
+/// The `io` module contains the writer trait accepted by the serializer.
+pub mod io;
+
 /// The `libyml` module contains the library's YAML parser and emitter.
 pub mod libyml;
 
+/// The `lint` module contains `yamllint`-style checks of YAML text.
+pub mod lint;
+
 /// The `loader` module contains the `Loader` type for YAML loading.
 pub mod loader;
 
@@ -157,24 +312,71 @@ pub mod modules;
 /// The `number` module contains the `Number` type for YAML numbers.
 pub mod number;
 
+/// The `path` module contains the owned `Path` type naming nodes within a document.
+pub mod path;
+
 /// The `ser` module contains the library's YAML serializer.
 pub mod ser;
 
+/// The `untagged` module contains the support for untagged enums declared with `untagged!`.
+pub mod untagged;
+
+/// The `update` module contains the functions updating existing values in place.
+#[cfg(feature = "std")]
+pub mod update;
+
 /// The `utilities` module contains utility functions for the library.
+#[cfg(feature = "std")]
 pub mod utilities;
 
+/// The `validate` module contains JSON Schema validation for YAML documents.
+#[cfg(feature = "std")]
+pub mod validate;
+
 /// The `value` module contains the `Value` type for YAML values.
 pub mod value;
 
 /// The `with` module contains the `With` type for YAML values.
 pub mod with;
 
+// Items of the standard prelude that are not in the core prelude, for
+// modules that also build without the `std` feature. Without `std`, the
+// error trait comes from `core`, which requires Rust 1.81.
+mod lib {
+    pub(crate) use alloc::{
+        borrow::ToOwned,
+        boxed::Box,
+        format,
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+    #[cfg(not(feature = "std"))]
+    pub(crate) use core::error::Error as StdError;
+    #[cfg(feature = "std")]
+    pub(crate) use std::error::Error as StdError;
+}
+
 // Prevent downstream code from implementing the Index trait.
 mod private {
+    use crate::lib::*;
+
     pub trait Sealed {}
+    impl Sealed for bool {}
+    impl Sealed for i8 {}
+    impl Sealed for i16 {}
+    impl Sealed for i32 {}
+    impl Sealed for i64 {}
+    impl Sealed for isize {}
+    impl Sealed for u8 {}
+    impl Sealed for u16 {}
+    impl Sealed for u32 {}
+    impl Sealed for u64 {}
     impl Sealed for usize {}
     impl Sealed for str {}
     impl Sealed for String {}
+    impl Sealed for [crate::Value] {}
+    impl Sealed for Vec<crate::Value> {}
     impl Sealed for crate::Value {}
     impl<T> Sealed for &T where T: ?Sized + Sealed {}
 }
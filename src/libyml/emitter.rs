@@ -1,27 +1,50 @@
-use crate::libyml::{self, util::Owned};
+//! Low-level YAML emitter.
+//!
+//! The [`Emitter`] turns a stream of [`Event`]s into YAML text written to
+//! any [`io::Write`], with its indent, line width, canonical form and other
+//! output options set through [`EmitterBuilder`].
+//!
+//! The event payload types ([`Scalar`], [`Sequence`] and [`Mapping`]) are
+//! `#[non_exhaustive]`: construct them through their constructors and
+//! builder methods, which remain source compatible when new fields or
+//! styles are added.
+
+use crate::libyml;
+#[cfg(feature = "pure-rust")]
+use crate::libyml::pure;
+#[cfg(not(feature = "pure-rust"))]
+use crate::libyml::util::Owned;
+use crate::{io, lib::*};
+#[cfg(not(feature = "pure-rust"))]
 use ::libyml::api::ScalarEventData;
+#[cfg(not(feature = "pure-rust"))]
 use ::libyml::YamlEventT;
+#[cfg(not(feature = "pure-rust"))]
 use ::libyml::YamlScalarStyleT::YamlLiteralScalarStyle;
+#[cfg(not(feature = "pure-rust"))]
 use ::libyml::{
-    yaml_document_end_event_initialize,
+    yaml_alias_event_initialize, yaml_document_end_event_initialize,
     yaml_document_start_event_initialize, yaml_emitter_delete,
     yaml_emitter_emit, yaml_emitter_flush, yaml_emitter_initialize,
-    yaml_emitter_set_output, yaml_emitter_set_unicode,
-    yaml_emitter_set_width, yaml_mapping_end_event_initialize,
+    yaml_emitter_set_break, yaml_emitter_set_canonical,
+    yaml_emitter_set_indent, yaml_emitter_set_output,
+    yaml_emitter_set_unicode, yaml_emitter_set_width,
+    yaml_mapping_end_event_initialize,
     yaml_mapping_start_event_initialize, yaml_scalar_event_initialize,
     yaml_sequence_end_event_initialize,
     yaml_sequence_start_event_initialize,
     yaml_stream_end_event_initialize,
     yaml_stream_start_event_initialize, YamlAnyMappingStyle,
-    YamlAnySequenceStyle, YamlEmitterT, YamlScalarStyleT,
-    YamlSingleQuotedScalarStyle, YamlUtf8Encoding,
+    YamlAnySequenceStyle, YamlCrBreak, YamlCrlnBreak, YamlEmitterT,
+    YamlLnBreak, YamlScalarStyleT, YamlSingleQuotedScalarStyle,
+    YamlUtf16beEncoding, YamlUtf16leEncoding, YamlUtf8Encoding,
 };
-use std::fmt::Debug;
+use core::fmt::Debug;
+#[cfg(not(feature = "pure-rust"))]
 #[allow(clippy::unsafe_removed_from_name)]
-use std::{
+use core::{
     ffi::c_void,
-    io,
-    mem::{self, MaybeUninit},
+    mem::MaybeUninit,
     ptr::{self, addr_of_mut},
     slice,
 };
@@ -35,44 +58,64 @@ pub enum Error {
     Io(io::Error),
 }
 
-/// A YAML emitter.
+/// A YAML emitter writing to `W`.
+///
+/// The emitter owns its writer for as long as it runs and hands it back
+/// from [`Emitter::into_inner`], so `W` may borrow from the caller, as in
+/// `Emitter::new(&mut buffer)`.
+#[cfg(not(feature = "pure-rust"))]
 #[derive(Debug)]
-pub struct Emitter<'a> {
-    pin: Owned<EmitterPinned<'a>>,
+pub struct Emitter<W> {
+    pin: Owned<EmitterPinned>,
+    write: W,
+    encoding: Encoding,
+    explicit_start: bool,
+    explicit_end: bool,
+}
+
+/// A YAML emitter writing to `W`.
+///
+/// With the `pure-rust` feature this runs the safe Rust port of the libyaml
+/// emitter, which writes the same output.
+#[cfg(feature = "pure-rust")]
+#[derive(Debug)]
+pub struct Emitter<W> {
+    inner: pure::emitter::Emitter<W>,
+    last_error: Option<libyml::error::Error>,
+    explicit_start: bool,
+    explicit_end: bool,
 }
 
 /// Represents a pinned emitter for YAML serialization.
 ///
 /// The `EmitterPinned` struct contains the necessary state and resources
-/// for emitting YAML documents. It is pinned to a specific lifetime `'a`
-/// to ensure that the `write` field remains valid throughout the lifetime
-/// of the emitter.
+/// for emitting YAML documents. It lives at a fixed heap address because
+/// libyml keeps a pointer to it for the write callback.
 ///
 /// # Fields
 ///
 /// - `sys`: An instance of `YamlEmitterT` representing the underlying
 ///   emitter system.
-/// - `write`: A boxed trait object implementing the `io::Write` trait,
-///   used for writing the emitted YAML data. It is pinned to the lifetime
-///   `'a` to ensure it remains valid for the duration of the emitter's
-///   lifetime.
+/// - `write`: The writer of the owning [`Emitter`], lent to the write
+///   callback only for the duration of a call into libyml and null
+///   otherwise. Keeping the writer out of this struct means dropping it
+///   never needs the writer, so `Emitter<&mut Vec<u8>>` can be dropped
+///   after the buffer is moved.
 /// - `write_error`: An optional `io::Error` used to store any errors that
 ///   occur during the writing process.
-///
-/// # Lifetime
-///
-/// The `EmitterPinned` struct is parameterized by a lifetime `'a`, which
-/// represents the lifetime of the `write` field. This ensures that the
-/// `write` field remains valid for the entire lifetime of the `EmitterPinned`
-/// instance.
-pub struct EmitterPinned<'a> {
+#[cfg(not(feature = "pure-rust"))]
+pub struct EmitterPinned {
     sys: YamlEmitterT,
-    write: Box<dyn io::Write + 'a>,
+    write: *mut c_void,
     write_error: Option<io::Error>,
 }
 
-impl Debug for EmitterPinned<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+#[cfg(not(feature = "pure-rust"))]
+impl Debug for EmitterPinned {
+    fn fmt(
+        &self,
+        f: &mut core::fmt::Formatter<'_>,
+    ) -> core::fmt::Result {
         f.debug_struct("EmitterPinned")
             .field("sys", &self.sys)
             .field("write_error", &self.write_error)
@@ -81,7 +124,13 @@ impl Debug for EmitterPinned<'_> {
 }
 
 /// YAML event types.
+///
+/// Events are fed to [`Emitter::emit`] in document order. The enum is
+/// `#[non_exhaustive]` so that new kinds of events (for example comments or
+/// directives) can be added without a breaking release; code matching on it
+/// outside this crate needs a wildcard arm.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Event<'a> {
     /// Start of a YAML stream.
     StreamStart,
@@ -91,6 +140,8 @@ pub enum Event<'a> {
     DocumentStart,
     /// End of a YAML document.
     DocumentEnd,
+    /// Alias to the node written with the given anchor.
+    Alias(&'a str),
     /// Scalar value.
     Scalar(Scalar<'a>),
     /// Start of a sequence.
@@ -104,8 +155,29 @@ pub enum Event<'a> {
 }
 
 /// Represents a scalar value in YAML.
+///
+/// The fields stay public for reading, but the struct is `#[non_exhaustive]`:
+/// outside this crate build it with the constructors and adjust it with the
+/// builder methods, which are guaranteed to keep working when fields are
+/// added.
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::libyml::emitter::{Scalar, ScalarStyle};
+///
+/// let scalar = Scalar::plain("x");
+/// assert!(matches!(scalar.style, ScalarStyle::Plain));
+///
+/// let scalar = Scalar::tagged("!custom", "x").double_quoted();
+/// assert_eq!(scalar.tag.as_deref(), Some("!custom"));
+/// assert!(matches!(scalar.style, ScalarStyle::DoubleQuoted));
+/// ```
 #[derive(Debug)]
+#[non_exhaustive]
 pub struct Scalar<'a> {
+    /// Optional anchor for the scalar.
+    pub anchor: Option<String>,
     /// Optional tag for the scalar.
     pub tag: Option<String>,
     /// Value of the scalar.
@@ -114,8 +186,73 @@ pub struct Scalar<'a> {
     pub style: ScalarStyle,
 }
 
+impl<'a> Scalar<'a> {
+    /// Creates an untagged scalar whose style is chosen by the emitter.
+    pub fn new(value: &'a str) -> Self {
+        Scalar {
+            anchor: None,
+            tag: None,
+            value,
+            style: ScalarStyle::Any,
+        }
+    }
+
+    /// Creates an untagged scalar in plain style.
+    pub fn plain(value: &'a str) -> Self {
+        Scalar::new(value).with_style(ScalarStyle::Plain)
+    }
+
+    /// Creates a tagged scalar whose style is chosen by the emitter.
+    pub fn tagged(tag: impl Into<String>, value: &'a str) -> Self {
+        Scalar::new(value).with_tag(tag)
+    }
+
+    /// Sets the anchor of the scalar, so that later nodes can refer to it
+    /// through an [`Event::Alias`].
+    pub fn with_anchor(mut self, anchor: impl Into<String>) -> Self {
+        self.anchor = Some(anchor.into());
+        self
+    }
+
+    /// Sets the tag of the scalar.
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    /// Sets the style of the scalar.
+    pub fn with_style(mut self, style: ScalarStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Emits the scalar in single-quoted style.
+    pub fn single_quoted(self) -> Self {
+        self.with_style(ScalarStyle::SingleQuoted)
+    }
+
+    /// Emits the scalar in double-quoted style.
+    pub fn double_quoted(self) -> Self {
+        self.with_style(ScalarStyle::DoubleQuoted)
+    }
+
+    /// Emits the scalar as a literal block scalar (`|`).
+    pub fn literal(self) -> Self {
+        self.with_style(ScalarStyle::Literal)
+    }
+
+    /// Emits the scalar as a folded block scalar (`>`).
+    pub fn folded(self) -> Self {
+        self.with_style(ScalarStyle::Folded)
+    }
+}
+
 /// Styles for YAML scalars.
+///
+/// New styles may be added in minor releases, so the enum is
+/// `#[non_exhaustive]`.
 #[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
 pub enum ScalarStyle {
     /// Any scalar style.
     Any,
@@ -123,28 +260,242 @@ pub enum ScalarStyle {
     Plain,
     /// Single quoted scalar style.
     SingleQuoted,
+    /// Double quoted scalar style.
+    DoubleQuoted,
     /// Literal scalar style.
     Literal,
+    /// Folded scalar style.
+    Folded,
+}
+
+/// Line breaks written by an [`Emitter`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineBreak {
+    /// Line feed (`\n`), as on Unix. This is the default.
+    Lf,
+    /// Carriage return followed by line feed (`\r\n`), as on Windows.
+    CrLf,
+    /// Carriage return alone (`\r`), as in files from classic Mac OS.
+    Cr,
+}
+
+impl Default for LineBreak {
+    fn default() -> Self {
+        LineBreak::Lf
+    }
+}
+
+/// Character encodings an [`Emitter`] can write.
+///
+/// The UTF-16 encodings start the stream with a byte order mark.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-8 without a byte order mark. This is the default.
+    Utf8,
+    /// UTF-16, little-endian.
+    Utf16Le,
+    /// UTF-16, big-endian.
+    Utf16Be,
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Encoding::Utf8
+    }
 }
 
 /// Represents a YAML sequence.
-#[derive(Debug)]
+///
+/// Build it with [`Sequence::new`] or [`Sequence::tagged`] outside this
+/// crate; the struct is `#[non_exhaustive]` so that fields such as a flow
+/// style can be added later.
+#[derive(Debug, Default)]
+#[non_exhaustive]
 pub struct Sequence {
+    /// Optional anchor for the sequence.
+    pub anchor: Option<String>,
     /// Optional tag for the sequence.
     pub tag: Option<String>,
 }
 
+impl Sequence {
+    /// Creates an untagged sequence start.
+    pub fn new() -> Self {
+        Sequence {
+            anchor: None,
+            tag: None,
+        }
+    }
+
+    /// Creates a tagged sequence start.
+    pub fn tagged(tag: impl Into<String>) -> Self {
+        Sequence {
+            anchor: None,
+            tag: Some(tag.into()),
+        }
+    }
+
+    /// Sets the anchor of the sequence, so that later nodes can refer to it
+    /// through an [`Event::Alias`].
+    pub fn with_anchor(mut self, anchor: impl Into<String>) -> Self {
+        self.anchor = Some(anchor.into());
+        self
+    }
+}
+
 /// Represents a YAML mapping.
-#[derive(Debug)]
+///
+/// Build it with [`Mapping::new`] or [`Mapping::tagged`] outside this
+/// crate; the struct is `#[non_exhaustive]` so that fields such as a flow
+/// style can be added later.
+#[derive(Debug, Default)]
+#[non_exhaustive]
 pub struct Mapping {
+    /// Optional anchor for the mapping.
+    pub anchor: Option<String>,
     /// Optional tag for the mapping.
     pub tag: Option<String>,
 }
 
-impl<'a> Emitter<'a> {
+impl Mapping {
+    /// Creates an untagged mapping start.
+    pub fn new() -> Self {
+        Mapping {
+            anchor: None,
+            tag: None,
+        }
+    }
+
+    /// Creates a tagged mapping start.
+    pub fn tagged(tag: impl Into<String>) -> Self {
+        Mapping {
+            anchor: None,
+            tag: Some(tag.into()),
+        }
+    }
+
+    /// Sets the anchor of the mapping, so that later nodes can refer to it
+    /// through an [`Event::Alias`].
+    pub fn with_anchor(mut self, anchor: impl Into<String>) -> Self {
+        self.anchor = Some(anchor.into());
+        self
+    }
+}
+
+/// A builder for [`Emitter`] that sets its output options up front.
+///
+/// The defaults are those of [`Emitter::new`]: an indent of 2, no line
+/// width limit, non-canonical output, characters outside ASCII written as
+/// they are, LF line breaks and UTF-8.
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::libyml::emitter::{EmitterBuilder, Event, Mapping, Scalar};
+///
+/// let mut emitter = EmitterBuilder::new().indent(4).build(Vec::new());
+/// emitter.emit(Event::StreamStart).unwrap();
+/// emitter.emit(Event::DocumentStart).unwrap();
+/// emitter.emit(Event::MappingStart(Mapping::new())).unwrap();
+/// emitter.emit(Event::Scalar(Scalar::plain("outer"))).unwrap();
+/// emitter.emit(Event::MappingStart(Mapping::new())).unwrap();
+/// emitter.emit(Event::Scalar(Scalar::plain("inner"))).unwrap();
+/// emitter.emit(Event::Scalar(Scalar::plain("1"))).unwrap();
+/// emitter.emit(Event::MappingEnd).unwrap();
+/// emitter.emit(Event::MappingEnd).unwrap();
+/// emitter.emit(Event::DocumentEnd).unwrap();
+/// emitter.emit(Event::StreamEnd).unwrap();
+///
+/// let yaml = emitter.into_inner();
+/// assert_eq!(yaml, b"outer:\n    inner: 1\n");
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct EmitterBuilder {
+    indent: usize,
+    width: Option<usize>,
+    canonical: bool,
+    unicode: bool,
+    line_break: LineBreak,
+    encoding: Encoding,
+}
+
+impl Default for EmitterBuilder {
+    fn default() -> Self {
+        EmitterBuilder {
+            indent: 2,
+            width: None,
+            canonical: false,
+            unicode: true,
+            line_break: LineBreak::Lf,
+            encoding: Encoding::Utf8,
+        }
+    }
+}
+
+impl EmitterBuilder {
+    /// Creates a builder with the default options.
+    pub fn new() -> Self {
+        EmitterBuilder::default()
+    }
+
+    /// Sets the number of spaces by which nested block collections are
+    /// indented. Values outside `2..=9` are ignored, leaving the default
+    /// of 2.
+    pub fn indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Sets the preferred line width, beyond which plain and quoted
+    /// scalars are folded at spaces, or `None` for no limit.
+    pub fn width(mut self, width: Option<usize>) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Sets whether the output is canonical YAML.
+    pub fn canonical(mut self, canonical: bool) -> Self {
+        self.canonical = canonical;
+        self
+    }
+
+    /// Sets whether characters outside ASCII are written as they are or
+    /// escaped.
+    pub fn unicode(mut self, unicode: bool) -> Self {
+        self.unicode = unicode;
+        self
+    }
+
+    /// Sets the line break written at the end of each line.
+    pub fn line_break(mut self, line_break: LineBreak) -> Self {
+        self.line_break = line_break;
+        self
+    }
+
+    /// Sets the encoding of the output.
+    pub fn encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Builds an emitter writing to `write`.
+    pub fn build<W: io::Write>(self, write: W) -> Emitter<W> {
+        let mut emitter = Emitter::new(write);
+        emitter.set_indent(self.indent);
+        emitter.set_width(self.width);
+        emitter.set_canonical(self.canonical);
+        emitter.set_unicode(self.unicode);
+        emitter.set_line_break(self.line_break);
+        emitter.set_encoding(self.encoding);
+        emitter
+    }
+}
+
+#[cfg(not(feature = "pure-rust"))]
+impl<W: io::Write> Emitter<W> {
     /// Creates a new YAML emitter.
-    pub fn new(write: Box<dyn io::Write + 'a>) -> Emitter<'a> {
-        let owned = Owned::<EmitterPinned<'a>>::new_uninit();
+    pub fn new(write: W) -> Emitter<W> {
+        let owned = Owned::<EmitterPinned>::new_uninit();
         let pin = unsafe {
             let emitter = addr_of_mut!((*owned.ptr).sys);
             if yaml_emitter_initialize(emitter).fail {
@@ -155,16 +506,113 @@ impl<'a> Emitter<'a> {
             }
             yaml_emitter_set_unicode(emitter, true);
             yaml_emitter_set_width(emitter, -1);
-            addr_of_mut!((*owned.ptr).write).write(write);
+            addr_of_mut!((*owned.ptr).write).write(ptr::null_mut());
             addr_of_mut!((*owned.ptr).write_error).write(None);
             yaml_emitter_set_output(
                 emitter,
-                write_handler,
+                write_handler::<W>,
                 owned.ptr.cast(),
             );
             Owned::assume_init(owned)
         };
-        Emitter { pin }
+        Emitter {
+            pin,
+            write,
+            encoding: Encoding::Utf8,
+            explicit_start: false,
+            explicit_end: false,
+        }
+    }
+
+    /// Runs `f` on the libyml emitter with the writer lent to the write
+    /// callback.
+    fn with_write<T>(
+        &mut self,
+        f: impl FnOnce(*mut YamlEmitterT) -> T,
+    ) -> T {
+        unsafe {
+            (*self.pin.ptr).write = addr_of_mut!(self.write).cast();
+            let result = f(addr_of_mut!((*self.pin.ptr).sys));
+            (*self.pin.ptr).write = ptr::null_mut();
+            result
+        }
+    }
+
+    /// Sets the line break written at the end of each line.
+    pub fn set_line_break(&mut self, line_break: LineBreak) {
+        let line_break = match line_break {
+            LineBreak::Lf => YamlLnBreak,
+            LineBreak::CrLf => YamlCrlnBreak,
+            LineBreak::Cr => YamlCrBreak,
+        };
+        unsafe {
+            let emitter = addr_of_mut!((*self.pin.ptr).sys);
+            yaml_emitter_set_break(emitter, line_break);
+        }
+    }
+
+    /// Sets the number of spaces by which nested block collections are
+    /// indented. Values outside `2..=9` are ignored, leaving the default
+    /// of 2.
+    pub fn set_indent(&mut self, indent: usize) {
+        if (2..=9).contains(&indent) {
+            unsafe {
+                let emitter = addr_of_mut!((*self.pin.ptr).sys);
+                yaml_emitter_set_indent(emitter, indent as i32);
+            }
+        }
+    }
+
+    /// Sets the encoding of the output. It takes effect at the next
+    /// [`Event::StreamStart`], so set it before emitting one.
+    pub fn set_encoding(&mut self, encoding: Encoding) {
+        self.encoding = encoding;
+    }
+
+    /// Sets whether characters outside ASCII are written as they are,
+    /// which is the default, or as `\x`, `\u` and `\U` escapes inside
+    /// double-quoted scalars.
+    pub fn set_unicode(&mut self, unicode: bool) {
+        unsafe {
+            let emitter = addr_of_mut!((*self.pin.ptr).sys);
+            yaml_emitter_set_unicode(emitter, unicode);
+        }
+    }
+
+    /// Sets the preferred line width, beyond which plain and quoted
+    /// scalars are folded at spaces, or `None` for no limit, which is the
+    /// default. Widths of at most twice the indent are taken as 80. It
+    /// takes effect at the next [`Event::StreamStart`].
+    pub fn set_width(&mut self, width: Option<usize>) {
+        let width = width.map_or(-1, |width| {
+            i32::try_from(width).unwrap_or(i32::MAX)
+        });
+        unsafe {
+            let emitter = addr_of_mut!((*self.pin.ptr).sys);
+            yaml_emitter_set_width(emitter, width);
+        }
+    }
+
+    /// Sets whether the output is canonical YAML, with every collection in
+    /// flow style, every scalar double-quoted, explicit `?` keys and a
+    /// `---` marker on every document. The default is `false`.
+    pub fn set_canonical(&mut self, canonical: bool) {
+        unsafe {
+            let emitter = addr_of_mut!((*self.pin.ptr).sys);
+            yaml_emitter_set_canonical(emitter, canonical);
+        }
+    }
+
+    /// Sets whether documents emitted from now on start with `---`. By
+    /// default only documents after the first one do.
+    pub fn set_explicit_document_start(&mut self, explicit: bool) {
+        self.explicit_start = explicit;
+    }
+
+    /// Sets whether documents emitted from now on end with `...`. By
+    /// default they end without a marker.
+    pub fn set_explicit_document_end(&mut self, explicit: bool) {
+        self.explicit_end = explicit;
     }
 
     /// Emits a YAML event.
@@ -175,9 +623,13 @@ impl<'a> Emitter<'a> {
             let emitter = addr_of_mut!((*self.pin.ptr).sys);
             let initialize_status = match event {
                 Event::StreamStart => {
+                    let encoding = match self.encoding {
+                        Encoding::Utf8 => YamlUtf8Encoding,
+                        Encoding::Utf16Le => YamlUtf16leEncoding,
+                        Encoding::Utf16Be => YamlUtf16beEncoding,
+                    };
                     yaml_stream_start_event_initialize(
-                        sys_event,
-                        YamlUtf8Encoding,
+                        sys_event, encoding,
                     )
                 }
                 Event::StreamEnd => {
@@ -187,7 +639,7 @@ impl<'a> Emitter<'a> {
                     let version_directive = ptr::null_mut();
                     let tag_directives_start = ptr::null_mut();
                     let tag_directives_end = ptr::null_mut();
-                    let implicit = true;
+                    let implicit = !self.explicit_start;
                     yaml_document_start_event_initialize(
                         sys_event,
                         version_directive,
@@ -197,19 +649,19 @@ impl<'a> Emitter<'a> {
                     )
                 }
                 Event::DocumentEnd => {
-                    let implicit = true;
+                    let implicit = !self.explicit_end;
                     yaml_document_end_event_initialize(
                         sys_event, implicit,
                     )
                 }
+                Event::Alias(anchor) => {
+                    let mut anchor = Some(anchor.to_owned());
+                    let anchor_ptr = nul_terminated(&mut anchor);
+                    yaml_alias_event_initialize(sys_event, anchor_ptr)
+                }
                 Event::Scalar(mut scalar) => {
-                    let tag_ptr = scalar.tag.as_mut().map_or_else(
-                        ptr::null,
-                        |tag| {
-                            tag.push('\0');
-                            tag.as_ptr()
-                        },
-                    );
+                    let anchor_ptr = nul_terminated(&mut scalar.anchor);
+                    let tag_ptr = nul_terminated(&mut scalar.tag);
                     let value_ptr = scalar.value.as_ptr();
                     let length = scalar.value.len() as i32;
                     let plain_implicit = tag_ptr.is_null();
@@ -224,10 +676,16 @@ impl<'a> Emitter<'a> {
                         ScalarStyle::SingleQuoted => {
                             YamlSingleQuotedScalarStyle
                         }
+                        ScalarStyle::DoubleQuoted => {
+                            YamlScalarStyleT::YamlDoubleQuotedScalarStyle
+                        }
                         ScalarStyle::Literal => YamlLiteralScalarStyle,
+                        ScalarStyle::Folded => {
+                            YamlScalarStyleT::YamlFoldedScalarStyle
+                        }
                     };
                     let event_data = ScalarEventData {
-                        anchor: ptr::null(),
+                        anchor: anchor_ptr,
                         tag: tag_ptr,
                         value: value_ptr,
                         length,
@@ -239,42 +697,26 @@ impl<'a> Emitter<'a> {
                     yaml_scalar_event_initialize(sys_event, event_data)
                 }
                 Event::SequenceStart(mut sequence) => {
-                    let tag_ptr = sequence.tag.as_mut().map_or_else(
-                        ptr::null,
-                        |tag| {
-                            tag.push('\0');
-                            tag.as_ptr()
-                        },
-                    );
+                    let anchor_ptr =
+                        nul_terminated(&mut sequence.anchor);
+                    let tag_ptr = nul_terminated(&mut sequence.tag);
                     let implicit = tag_ptr.is_null();
                     let style = YamlAnySequenceStyle;
                     yaml_sequence_start_event_initialize(
-                        sys_event,
-                        ptr::null(),
-                        tag_ptr,
-                        implicit,
-                        style,
+                        sys_event, anchor_ptr, tag_ptr, implicit, style,
                     )
                 }
                 Event::SequenceEnd => {
                     yaml_sequence_end_event_initialize(sys_event)
                 }
                 Event::MappingStart(mut mapping) => {
-                    let tag_ptr = mapping.tag.as_mut().map_or_else(
-                        ptr::null,
-                        |tag| {
-                            tag.push('\0');
-                            tag.as_ptr()
-                        },
-                    );
+                    let anchor_ptr =
+                        nul_terminated(&mut mapping.anchor);
+                    let tag_ptr = nul_terminated(&mut mapping.tag);
                     let implicit = tag_ptr.is_null();
                     let style = YamlAnyMappingStyle;
                     yaml_mapping_start_event_initialize(
-                        sys_event,
-                        ptr::null(),
-                        tag_ptr,
-                        implicit,
-                        style,
+                        sys_event, anchor_ptr, tag_ptr, implicit, style,
                     )
                 }
                 Event::MappingEnd => {
@@ -286,7 +728,12 @@ impl<'a> Emitter<'a> {
                     emitter,
                 )));
             }
-            if yaml_emitter_emit(emitter, sys_event).fail {
+            if self
+                .with_write(|emitter| {
+                    yaml_emitter_emit(emitter, sys_event)
+                })
+                .fail
+            {
                 return Err(self.error());
             }
         }
@@ -295,20 +742,23 @@ impl<'a> Emitter<'a> {
 
     /// Flushes the YAML emitter.
     pub fn flush(&mut self) -> Result<(), Error> {
-        unsafe {
-            let emitter = addr_of_mut!((*self.pin.ptr).sys);
-            if yaml_emitter_flush(emitter).fail {
-                return Err(self.error());
-            }
+        if self
+            .with_write(|emitter| unsafe {
+                yaml_emitter_flush(emitter)
+            })
+            .fail
+        {
+            return Err(self.error());
         }
         Ok(())
     }
 
     /// Retrieves the inner writer from the YAML emitter.
-    #[allow(unused_mut)]
-    pub fn into_inner(mut self) -> Box<dyn io::Write + 'a> {
-        let sink = Box::new(io::sink());
-        unsafe { mem::replace(&mut (*self.pin.ptr).write, sink) }
+    ///
+    /// Output still buffered by the emitter is discarded; call
+    /// [`flush`](Self::flush) first to keep it.
+    pub fn into_inner(self) -> W {
+        self.write
     }
 
     /// Retrieves the error from the YAML emitter.
@@ -324,27 +774,183 @@ impl<'a> Emitter<'a> {
     }
 }
 
+#[cfg(feature = "pure-rust")]
+impl<W: io::Write> Emitter<W> {
+    /// Creates a new YAML emitter.
+    pub fn new(write: W) -> Emitter<W> {
+        Emitter {
+            inner: pure::emitter::Emitter::new(write),
+            last_error: None,
+            explicit_start: false,
+            explicit_end: false,
+        }
+    }
+
+    /// Sets the line break written at the end of each line.
+    pub fn set_line_break(&mut self, line_break: LineBreak) {
+        self.inner.set_line_break(line_break);
+    }
+
+    /// Sets the number of spaces by which nested block collections are
+    /// indented. Values outside `2..=9` are ignored, leaving the default
+    /// of 2.
+    pub fn set_indent(&mut self, indent: usize) {
+        if (2..=9).contains(&indent) {
+            self.inner.set_indent(indent);
+        }
+    }
+
+    /// Sets the encoding of the output. It takes effect at the next
+    /// [`Event::StreamStart`], so set it before emitting one.
+    pub fn set_encoding(&mut self, encoding: Encoding) {
+        self.inner.set_encoding(encoding);
+    }
+
+    /// Sets whether characters outside ASCII are written as they are,
+    /// which is the default, or as `\x`, `\u` and `\U` escapes inside
+    /// double-quoted scalars.
+    pub fn set_unicode(&mut self, unicode: bool) {
+        self.inner.set_unicode(unicode);
+    }
+
+    /// Sets the preferred line width, beyond which plain and quoted
+    /// scalars are folded at spaces, or `None` for no limit, which is the
+    /// default. Widths of at most twice the indent are taken as 80. It
+    /// takes effect at the next [`Event::StreamStart`].
+    pub fn set_width(&mut self, width: Option<usize>) {
+        self.inner.set_width(width);
+    }
+
+    /// Sets whether the output is canonical YAML, with every collection in
+    /// flow style, every scalar double-quoted, explicit `?` keys and a
+    /// `---` marker on every document. The default is `false`.
+    pub fn set_canonical(&mut self, canonical: bool) {
+        self.inner.set_canonical(canonical);
+    }
+
+    /// Sets whether documents emitted from now on start with `---`. By
+    /// default only documents after the first one do.
+    pub fn set_explicit_document_start(&mut self, explicit: bool) {
+        self.explicit_start = explicit;
+    }
+
+    /// Sets whether documents emitted from now on end with `...`. By
+    /// default they end without a marker.
+    pub fn set_explicit_document_end(&mut self, explicit: bool) {
+        self.explicit_end = explicit;
+    }
+
+    /// Emits a YAML event.
+    pub fn emit(&mut self, event: Event<'_>) -> Result<(), Error> {
+        let event = match event {
+            Event::StreamStart => pure::emitter::Event::StreamStart,
+            Event::StreamEnd => pure::emitter::Event::StreamEnd,
+            Event::DocumentStart => {
+                pure::emitter::Event::DocumentStart {
+                    implicit: !self.explicit_start,
+                }
+            }
+            Event::DocumentEnd => pure::emitter::Event::DocumentEnd {
+                implicit: !self.explicit_end,
+            },
+            Event::Alias(anchor) => pure::emitter::Event::Alias {
+                anchor: anchor.to_owned(),
+            },
+            Event::Scalar(scalar) => pure::emitter::Event::Scalar {
+                anchor: scalar.anchor,
+                tag: scalar.tag,
+                value: scalar.value.to_owned(),
+                style: scalar.style,
+            },
+            Event::SequenceStart(sequence) => {
+                pure::emitter::Event::SequenceStart {
+                    anchor: sequence.anchor,
+                    tag: sequence.tag,
+                }
+            }
+            Event::SequenceEnd => pure::emitter::Event::SequenceEnd,
+            Event::MappingStart(mapping) => {
+                pure::emitter::Event::MappingStart {
+                    anchor: mapping.anchor,
+                    tag: mapping.tag,
+                }
+            }
+            Event::MappingEnd => pure::emitter::Event::MappingEnd,
+        };
+        self.inner.emit(event).map_err(|error| self.record(error))
+    }
+
+    /// Flushes the YAML emitter.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.inner.flush().map_err(|error| self.record(error))
+    }
+
+    /// Retrieves the inner writer from the YAML emitter.
+    ///
+    /// Output still buffered by the emitter is discarded; call
+    /// [`flush`](Self::flush) first to keep it.
+    pub fn into_inner(self) -> W {
+        self.inner.write
+    }
+
+    /// Retrieves the error from the YAML emitter.
+    ///
+    /// Write errors are handed out by the call that hit them, so this
+    /// returns the last error about the order of events.
+    pub fn error(&mut self) -> Error {
+        Error::Libyml(self.last_error.unwrap_or_else(|| {
+            pure::error(
+                libyml::error::ErrorKind::Emitter,
+                None,
+                b"emitter failed but there is no error\0",
+                libyml::error::Mark::new(0, 0, 0),
+            )
+        }))
+    }
+
+    fn record(&mut self, error: Error) -> Error {
+        if let Error::Libyml(error) = &error {
+            self.last_error = Some(*error);
+        }
+        error
+    }
+}
+
+/// Appends a NUL to `string` for libyml and returns a pointer to it, or
+/// null if there is no string.
+#[cfg(not(feature = "pure-rust"))]
+fn nul_terminated(string: &mut Option<String>) -> *const u8 {
+    string.as_mut().map_or_else(ptr::null, |string| {
+        string.push('\0');
+        string.as_ptr()
+    })
+}
+
 /// Writes data to a buffer using a provided callback function.
-unsafe fn write_handler(
+#[cfg(not(feature = "pure-rust"))]
+unsafe fn write_handler<W: io::Write>(
     data: *mut c_void,
     buffer: *mut u8,
     size: u64,
 ) -> i32 {
-    let data = data.cast::<EmitterPinned<'_>>();
-    match io::Write::write_all(unsafe { &mut *(*data).write }, unsafe {
+    let data = unsafe { &mut *data.cast::<EmitterPinned>() };
+    if data.write.is_null() {
+        return 0;
+    }
+    let write = unsafe { &mut *data.write.cast::<W>() };
+    match write.write_all(unsafe {
         slice::from_raw_parts(buffer, size as usize)
     }) {
         Ok(()) => 1,
         Err(err) => {
-            unsafe {
-                (*data).write_error = Some(err);
-            }
+            data.write_error = Some(err);
             0
         }
     }
 }
 
-impl Drop for EmitterPinned<'_> {
+#[cfg(not(feature = "pure-rust"))]
+impl Drop for EmitterPinned {
     /// Drops the YAML emitter, deallocating resources.
     fn drop(&mut self) {
         unsafe { yaml_emitter_delete(&mut self.sys) }
@@ -1,14 +1,13 @@
 use crate::libyml::safe_cstr::CStr;
+use core::fmt::{self, Debug, Display};
+#[cfg(not(feature = "pure-rust"))]
+use core::{mem::MaybeUninit, ptr::NonNull};
+#[cfg(not(feature = "pure-rust"))]
 #[allow(clippy::unsafe_removed_from_name)]
 use libyml as sys;
-use std::{
-    fmt::{self, Debug, Display},
-    mem::MaybeUninit,
-    ptr::NonNull,
-};
 
 /// A type alias for a `Result` with an `Error` as the error type.
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
 /// Represents an error that occurred during YAML processing.
 #[derive(Clone, Copy)]
@@ -17,8 +16,13 @@ pub struct Error {
     ///
     /// This field uses the `yaml_error_type_t` type from the `libyml` crate,
     /// which represents different types of errors.
+    #[cfg(not(feature = "pure-rust"))]
     pub kind: sys::YamlErrorTypeT,
 
+    /// The kind of error that occurred.
+    #[cfg(feature = "pure-rust")]
+    pub kind: ErrorKind,
+
     /// A null-terminated string describing the problem that caused the error.
     ///
     /// The `CStr<'static>` type represents a borrowed C-style string with a static lifetime.
@@ -43,6 +47,21 @@ pub struct Error {
     pub context_mark: Mark,
 }
 
+/// The stage of YAML processing that failed, as reported by the pure Rust
+/// backend.
+#[cfg(feature = "pure-rust")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The input is not valid UTF-8 or contains characters YAML forbids.
+    Reader,
+    /// The input could not be split into tokens.
+    Scanner,
+    /// The tokens do not form a valid YAML document.
+    Parser,
+    /// The events given to the emitter are out of order.
+    Emitter,
+}
+
 impl Error {
     /// Constructs an `Error` from a `YamlParserT` pointer.
     ///
@@ -50,6 +69,7 @@ impl Error {
     ///
     /// This function is unsafe because it dereferences raw pointers and assumes
     /// the validity of the `YamlParserT` pointer.
+    #[cfg(not(feature = "pure-rust"))]
     pub unsafe fn parse_error(parser: *const sys::YamlParserT) -> Self {
         Error {
             kind: unsafe { (*parser).error },
@@ -85,6 +105,7 @@ impl Error {
     ///
     /// This function is unsafe because it dereferences raw pointers and assumes
     /// the validity of the `YamlEmitterT` pointer.
+    #[cfg(not(feature = "pure-rust"))]
     pub unsafe fn emit_error(
         emitter: *const sys::YamlEmitterT,
     ) -> Self {
@@ -120,13 +141,37 @@ impl Error {
     pub fn mark(&self) -> Mark {
         self.problem_mark
     }
+
+    #[cfg(not(feature = "pure-rust"))]
+    fn kind_name(&self) -> Option<&'static str> {
+        match self.kind {
+            sys::YamlMemoryError => Some("MEMORY"),
+            sys::YamlReaderError => Some("READER"),
+            sys::YamlScannerError => Some("SCANNER"),
+            sys::YamlParserError => Some("PARSER"),
+            sys::YamlComposerError => Some("COMPOSER"),
+            sys::YamlWriterError => Some("WRITER"),
+            sys::YamlEmitterError => Some("EMITTER"),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "pure-rust")]
+    fn kind_name(&self) -> Option<&'static str> {
+        Some(match self.kind {
+            ErrorKind::Reader => "READER",
+            ErrorKind::Scanner => "SCANNER",
+            ErrorKind::Parser => "PARSER",
+            ErrorKind::Emitter => "EMITTER",
+        })
+    }
 }
 
 impl Display for Error {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(formatter, "{}", self.problem)?;
-        if self.problem_mark.sys.line != 0
-            || self.problem_mark.sys.column != 0
+        if self.problem_mark.line() != 0
+            || self.problem_mark.column() != 0
         {
             write!(formatter, " at {}", self.problem_mark)?;
         } else if self.problem_offset != 0 {
@@ -134,12 +179,12 @@ impl Display for Error {
         }
         if let Some(context) = &self.context {
             write!(formatter, ", {}", context)?;
-            if (self.context_mark.sys.line != 0
-                || self.context_mark.sys.column != 0)
-                && (self.context_mark.sys.line
-                    != self.problem_mark.sys.line
-                    || self.context_mark.sys.column
-                        != self.problem_mark.sys.column)
+            if (self.context_mark.line() != 0
+                || self.context_mark.column() != 0)
+                && (self.context_mark.line()
+                    != self.problem_mark.line()
+                    || self.context_mark.column()
+                        != self.problem_mark.column())
             {
                 write!(formatter, " at {}", self.context_mark)?;
             }
@@ -151,21 +196,12 @@ impl Display for Error {
 impl Debug for Error {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut formatter = formatter.debug_struct("Error");
-        if let Some(kind) = match self.kind {
-            sys::YamlMemoryError => Some("MEMORY"),
-            sys::YamlReaderError => Some("READER"),
-            sys::YamlScannerError => Some("SCANNER"),
-            sys::YamlParserError => Some("PARSER"),
-            sys::YamlComposerError => Some("COMPOSER"),
-            sys::YamlWriterError => Some("WRITER"),
-            sys::YamlEmitterError => Some("EMITTER"),
-            _ => None,
-        } {
+        if let Some(kind) = self.kind_name() {
             formatter.field("kind", &format_args!("{}", kind));
         }
         formatter.field("problem", &self.problem);
-        if self.problem_mark.sys.line != 0
-            || self.problem_mark.sys.column != 0
+        if self.problem_mark.line() != 0
+            || self.problem_mark.column() != 0
         {
             formatter.field("problem_mark", &self.problem_mark);
         } else if self.problem_offset != 0 {
@@ -173,8 +209,8 @@ impl Debug for Error {
         }
         if let Some(context) = &self.context {
             formatter.field("context", context);
-            if self.context_mark.sys.line != 0
-                || self.context_mark.sys.column != 0
+            if self.context_mark.line() != 0
+                || self.context_mark.column() != 0
             {
                 formatter.field("context_mark", &self.context_mark);
             }
@@ -185,6 +221,7 @@ impl Debug for Error {
 
 /// Represents a mark in a YAML document.
 /// A mark indicates a specific position or location within the document.
+#[cfg(not(feature = "pure-rust"))]
 #[derive(Copy, Clone)]
 pub struct Mark {
     /// The underlying system representation of the mark.
@@ -194,7 +231,54 @@ pub struct Mark {
     pub sys: sys::YamlMarkT,
 }
 
+/// Represents a mark in a YAML document.
+/// A mark indicates a specific position or location within the document.
+#[cfg(feature = "pure-rust")]
+#[derive(Copy, Clone)]
+pub struct Mark {
+    index: u64,
+    line: u64,
+    column: u64,
+}
+
+#[cfg(feature = "pure-rust")]
 impl Mark {
+    /// Creates a mark from a byte index and a 0-based line and column.
+    pub(crate) fn new(index: u64, line: u64, column: u64) -> Self {
+        Mark {
+            index,
+            line,
+            column,
+        }
+    }
+
+    /// Retrieves the index of the mark.
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// Retrieves the line number of the mark.
+    pub fn line(&self) -> u64 {
+        self.line
+    }
+
+    /// Retrieves the column number of the mark.
+    pub fn column(&self) -> u64 {
+        self.column
+    }
+}
+
+#[cfg(not(feature = "pure-rust"))]
+impl Mark {
+    /// Creates a mark from a character index and a 0-based line and column.
+    pub(crate) fn new(index: u64, line: u64, column: u64) -> Self {
+        let mut sys = sys::YamlMarkT::default();
+        sys.index = index;
+        sys.line = line;
+        sys.column = column;
+        Mark { sys }
+    }
+
     /// Retrieves the index of the mark.
     ///
     /// The index represents the position of the mark within the YAML input.
@@ -243,15 +327,15 @@ impl Display for Mark {
     ///
     /// Returns `Ok(())` if the formatting was successful, or an error otherwise.
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.sys.line != 0 || self.sys.column != 0 {
+        if self.line() != 0 || self.column() != 0 {
             write!(
                 formatter,
                 "line {} column {}",
-                self.sys.line + 1,
-                self.sys.column + 1,
+                self.line() + 1,
+                self.column() + 1,
             )
         } else {
-            write!(formatter, "position {}", self.sys.index)
+            write!(formatter, "position {}", self.index())
         }
     }
 }
@@ -271,11 +355,11 @@ impl Debug for Mark {
     /// Returns `Ok(())` if the formatting was successful, or an error otherwise.
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut formatter = formatter.debug_struct("Mark");
-        if self.sys.line != 0 || self.sys.column != 0 {
-            formatter.field("line", &(self.sys.line + 1));
-            formatter.field("column", &(self.sys.column + 1));
+        if self.line() != 0 || self.column() != 0 {
+            formatter.field("line", &(self.line() + 1));
+            formatter.field("column", &(self.column() + 1));
         } else {
-            formatter.field("index", &self.sys.index);
+            formatter.field("index", &self.index());
         }
         formatter.finish()
     }
@@ -4,6 +4,8 @@ pub mod emitter;
 pub mod error;
 /// YAML parser.
 pub mod parser;
+#[cfg(feature = "pure-rust")]
+mod pure;
 /// C string handling.
 pub mod safe_cstr;
 /// Tag directive handling.
@@ -11,4 +13,5 @@ pub mod tag;
 /// Utility types.
 pub mod util;
 
+#[cfg(not(feature = "pure-rust"))]
 use self::error::Error;
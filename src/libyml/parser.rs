@@ -1,18 +1,30 @@
-use crate::libyml::{
-    error::{Error, Mark, Result},
-    safe_cstr::{self, CStr},
-    tag::Tag,
-    util::Owned,
+#[cfg(feature = "pure-rust")]
+use crate::libyml::pure;
+#[cfg(not(feature = "pure-rust"))]
+use crate::libyml::{error::Error, safe_cstr::CStr, util::Owned};
+use crate::{
+    lib::*,
+    libyml::{
+        error::{Mark, Result},
+        safe_cstr,
+        tag::Tag,
+    },
 };
-#[allow(clippy::unsafe_removed_from_name)]
-use libyml as sys;
-use std::{
-    borrow::Cow,
+use alloc::borrow::Cow;
+use core::{
     fmt::{self, Debug},
+    mem,
+    ops::Deref,
+};
+#[cfg(not(feature = "pure-rust"))]
+use core::{
     mem::MaybeUninit,
     ptr::{addr_of_mut, NonNull},
     slice,
 };
+#[cfg(not(feature = "pure-rust"))]
+#[allow(clippy::unsafe_removed_from_name)]
+use libyml as sys;
 
 /// Represents a YAML parser.
 ///
@@ -22,6 +34,7 @@ use std::{
 ///
 /// The `'input` lifetime parameter indicates the lifetime of the input data being parsed.
 /// It ensures that the `Parser` does not outlive the input data.
+#[cfg(not(feature = "pure-rust"))]
 #[derive(Debug)]
 pub struct Parser<'input> {
     /// The pinned parser state.
@@ -38,12 +51,27 @@ pub struct Parser<'input> {
     pub pin: Owned<ParserPinned<'input>>,
 }
 
+/// Represents a YAML parser.
+///
+/// The `Parser` struct is responsible for parsing YAML input and generating a sequence
+/// of YAML events. With the `pure-rust` feature it runs the safe Rust port of the
+/// libyaml parser, which produces the same events, marks and errors.
+///
+/// The `'input` lifetime parameter indicates the lifetime of the input data being parsed.
+/// It ensures that the `Parser` does not outlive the input data.
+#[cfg(feature = "pure-rust")]
+#[derive(Debug)]
+pub struct Parser<'input> {
+    inner: pure::parser::Parser<'input>,
+}
+
 /// Represents a pinned parser for YAML deserialization.
 ///
 /// The `ParserPinned` struct contains the necessary state and resources
 /// for parsing YAML documents. It is pinned to a specific lifetime `'input`
 /// to ensure that the borrowed input data remains valid throughout the
 /// lifetime of the parser.
+#[cfg(not(feature = "pure-rust"))]
 #[derive(Debug, Clone)]
 pub struct ParserPinned<'input> {
     /// The underlying `YamlParserT` struct from the `libyml` library.
@@ -112,7 +140,7 @@ pub struct MappingStart {
 
 /// Represents an anchor in a YAML document.
 #[derive(Ord, PartialOrd, Eq, PartialEq)]
-pub struct Anchor(Box<[u8]>);
+pub struct Anchor(pub(in crate::libyml) Box<[u8]>);
 
 /// Represents the style of a scalar value in a YAML document.
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
@@ -129,6 +157,7 @@ pub enum ScalarStyle {
     Folded,
 }
 
+#[cfg(not(feature = "pure-rust"))]
 impl<'input> Parser<'input> {
     /// Creates a new `Parser` instance with the given input data.
     ///
@@ -170,6 +199,15 @@ impl<'input> Parser<'input> {
     pub fn parse_next_event(
         &mut self,
     ) -> Result<(Event<'input>, Mark)> {
+        self.parse_next_event_with_end()
+            .map(|(event, mark, _end)| (event, mark))
+    }
+
+    /// Parses the next YAML event along with the marks at its start and
+    /// just past its end.
+    pub(crate) fn parse_next_event_with_end(
+        &mut self,
+    ) -> Result<(Event<'input>, Mark, Mark)> {
         let mut event = MaybeUninit::<sys::YamlEventT>::uninit();
         unsafe {
             let parser = addr_of_mut!((*self.pin.ptr).sys);
@@ -191,7 +229,7 @@ impl<'input> Parser<'input> {
                     sys: (*event).start_mark,
                 };
                 sys::yaml_event_delete(event);
-                return Ok((Event::StreamEnd, mark));
+                return Ok((Event::StreamEnd, mark, mark));
             }
 
             if event_type == sys::YamlScalarEvent
@@ -201,17 +239,57 @@ impl<'input> Parser<'input> {
                     sys: (*event).start_mark,
                 };
                 sys::yaml_event_delete(event);
-                return Ok((Event::StreamEnd, mark));
+                return Ok((Event::StreamEnd, mark, mark));
             }
 
             let ret = convert_event(&*event, &(*self.pin.ptr).input);
             let mark = Mark {
                 sys: (*event).start_mark,
             };
+            let end = Mark {
+                sys: (*event).end_mark,
+            };
             sys::yaml_event_delete(event);
-            Ok((ret, mark))
+            Ok((ret, mark, end))
         }
     }
+
+    /// Starts over on a new input, keeping the allocation of the parser,
+    /// and returns the previous input.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if there is an error initializing the underlying `libyml` parser.
+    pub fn reset(
+        &mut self,
+        input: Cow<'input, [u8]>,
+    ) -> Cow<'input, [u8]> {
+        unsafe {
+            let parser = addr_of_mut!((*self.pin.ptr).sys);
+            sys::yaml_parser_delete(parser);
+            if sys::yaml_parser_initialize(parser).fail {
+                panic!(
+                    "Failed to initialize YAML parser: {}",
+                    Error::parse_error(parser)
+                );
+            }
+            sys::yaml_parser_set_encoding(
+                parser,
+                sys::YamlUtf8Encoding,
+            );
+            sys::yaml_parser_set_input_string(
+                parser,
+                input.as_ptr(),
+                input.len() as u64,
+            );
+            mem::replace(&mut (*self.pin.ptr).input, input)
+        }
+    }
+
+    /// Returns the complete input being parsed.
+    pub(crate) fn input(&self) -> &Cow<'input, [u8]> {
+        unsafe { &(*self.pin.ptr).input }
+    }
     /// Checks if the parser is initialized and ready to parse YAML.
     ///
     /// This function returns `true` if the parser is initialized and ready to parse YAML, and `false` otherwise.
@@ -234,6 +312,80 @@ impl<'input> Parser<'input> {
         }
     }
 }
+
+#[cfg(feature = "pure-rust")]
+impl<'input> Parser<'input> {
+    /// Creates a new `Parser` instance with the given input data.
+    ///
+    /// The `input` parameter is of type `Cow<'input, [u8]>`, which allows the parser
+    /// to accept both borrowed slices and owned vectors of bytes as input.
+    pub fn new(input: Cow<'input, [u8]>) -> Parser<'input> {
+        Parser {
+            inner: pure::parser::Parser::new(input),
+        }
+    }
+
+    /// Parses the next YAML event from the input.
+    ///
+    /// Returns a `Result` containing the parsed `Event` and its corresponding `Mark` on success,
+    /// or an `Error` if parsing fails.
+    pub fn parse_next_event(
+        &mut self,
+    ) -> Result<(Event<'input>, Mark)> {
+        self.parse_next_event_with_end()
+            .map(|(event, mark, _end)| (event, mark))
+    }
+
+    /// Parses the next YAML event along with the marks at its start and
+    /// just past its end.
+    pub(crate) fn parse_next_event_with_end(
+        &mut self,
+    ) -> Result<(Event<'input>, Mark, Mark)> {
+        let (event, mark, end) = self.inner.parse()?;
+        let mut event: Event<'input> = event;
+        match &mut event {
+            Event::StreamEnd => return Ok((event, mark, mark)),
+            Event::Scalar(scalar) => {
+                // Only borrowed input outlives the parser, so scalars of
+                // owned input carry no representation to borrow from.
+                if let Cow::Borrowed(input) = self.inner.scanner.input {
+                    let start = mark.index() as usize;
+                    scalar.repr =
+                        input.get(start..end.index() as usize);
+                }
+            }
+            _ => {}
+        }
+        Ok((event, mark, end))
+    }
+
+    /// Starts over on a new input and returns the previous input.
+    pub fn reset(
+        &mut self,
+        input: Cow<'input, [u8]>,
+    ) -> Cow<'input, [u8]> {
+        let previous = mem::replace(
+            &mut self.inner,
+            pure::parser::Parser::new(input),
+        );
+        previous.scanner.input
+    }
+
+    /// Returns the complete input being parsed.
+    pub(crate) fn input(&self) -> &Cow<'input, [u8]> {
+        &self.inner.scanner.input
+    }
+
+    /// Checks if the parser is initialized and ready to parse YAML.
+    ///
+    /// The pure Rust parser cannot fail to initialize, so this always
+    /// returns `true`.
+    pub fn is_ok(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(not(feature = "pure-rust"))]
 unsafe fn convert_event<'input>(
     sys: &sys::YamlEventT,
     input: &'input Cow<'input, [u8]>,
@@ -287,6 +439,7 @@ unsafe fn convert_event<'input>(
     }
 }
 
+#[cfg(not(feature = "pure-rust"))]
 unsafe fn optional_anchor(anchor: *const u8) -> Option<Anchor> {
     if anchor.is_null() {
         return None;
@@ -296,6 +449,7 @@ unsafe fn optional_anchor(anchor: *const u8) -> Option<Anchor> {
     Some(Anchor(Box::from(cstr.to_bytes())))
 }
 
+#[cfg(not(feature = "pure-rust"))]
 unsafe fn optional_tag(tag: *const u8) -> Option<Tag> {
     if tag.is_null() {
         return None;
@@ -305,13 +459,16 @@ unsafe fn optional_tag(tag: *const u8) -> Option<Tag> {
     Some(Tag(Box::from(cstr.to_bytes())))
 }
 
+#[cfg(not(feature = "pure-rust"))]
 unsafe fn optional_repr<'input>(
     sys: &sys::YamlEventT,
     input: &'input Cow<'input, [u8]>,
 ) -> Option<&'input [u8]> {
     let start = sys.start_mark.index as usize;
     let end = sys.end_mark.index as usize;
-    Some(&input[start..end])
+    // The marks count characters, and some inputs leave them past the end
+    // of the bytes, so the range is checked rather than trusted.
+    input.get(start..end)
 }
 
 impl Debug for Scalar<'_> {
@@ -351,6 +508,16 @@ impl Debug for Anchor {
     }
 }
 
+impl Deref for Anchor {
+    type Target = [u8];
+
+    /// Dereferences the anchor to the bytes of its name.
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(not(feature = "pure-rust"))]
 impl Drop for ParserPinned<'_> {
     fn drop(&mut self) {
         unsafe { sys::yaml_parser_delete(&mut self.sys) }
@@ -0,0 +1,1454 @@
+//! Event emitter of the pure Rust backend.
+//!
+//! Follows the libyaml emitter without directives.
+//! Output is built as UTF-8 and transcoded when it is handed to the writer.
+
+use super::error;
+use crate::{
+    io,
+    lib::*,
+    libyml::{
+        emitter::{Encoding, Error, LineBreak, ScalarStyle},
+        error::{ErrorKind, Mark},
+    },
+};
+use alloc::collections::VecDeque;
+use core::fmt::{self, Debug};
+
+/// Owned copy of an event, kept while the emitter looks ahead.
+#[derive(Debug)]
+pub(crate) enum Event {
+    StreamStart,
+    StreamEnd,
+    DocumentStart {
+        implicit: bool,
+    },
+    DocumentEnd {
+        implicit: bool,
+    },
+    Alias {
+        anchor: String,
+    },
+    Scalar {
+        anchor: Option<String>,
+        tag: Option<String>,
+        value: String,
+        style: ScalarStyle,
+    },
+    SequenceStart {
+        anchor: Option<String>,
+        tag: Option<String>,
+    },
+    SequenceEnd,
+    MappingStart {
+        anchor: Option<String>,
+        tag: Option<String>,
+    },
+    MappingEnd,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    StreamStart,
+    FirstDocumentStart,
+    DocumentStart,
+    DocumentContent,
+    DocumentEnd,
+    FlowSequenceFirstItem,
+    FlowSequenceItem,
+    FlowMappingFirstKey,
+    FlowMappingKey,
+    FlowMappingSimpleValue,
+    FlowMappingValue,
+    BlockSequenceFirstItem,
+    BlockSequenceItem,
+    BlockMappingFirstKey,
+    BlockMappingKey,
+    BlockMappingSimpleValue,
+    BlockMappingValue,
+    End,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Style {
+    Plain,
+    SingleQuoted,
+    DoubleQuoted,
+    Literal,
+    Folded,
+}
+
+#[derive(Debug, Default)]
+struct ScalarAnalysis {
+    multiline: bool,
+    flow_plain_allowed: bool,
+    block_plain_allowed: bool,
+    single_quoted_allowed: bool,
+    block_allowed: bool,
+}
+
+#[derive(Debug, Default)]
+struct AnchorAnalysis {
+    anchor: Option<String>,
+    alias: bool,
+}
+
+#[derive(Debug, Default)]
+struct TagAnalysis {
+    handle: Option<&'static str>,
+    suffix: Option<String>,
+}
+
+const DEFAULT_TAG_DIRECTIVES: [(&str, &str); 2] =
+    [("!", "!"), ("!!", "tag:yaml.org,2002:")];
+
+/// Size at which buffered output is handed to the writer.
+const BUFFER_SIZE: usize = 16384;
+
+pub(crate) struct Emitter<W> {
+    pub(crate) write: W,
+    buffer: Vec<u8>,
+    line_break: LineBreak,
+    encoding: Encoding,
+    unicode: bool,
+    canonical: bool,
+    best_indent: i64,
+    best_width: i64,
+    state: State,
+    states: Vec<State>,
+    events: VecDeque<Event>,
+    indents: Vec<i64>,
+    indent: i64,
+    flow_level: usize,
+    mapping_context: bool,
+    simple_key_context: bool,
+    column: i64,
+    whitespace: bool,
+    indention: bool,
+    open_ended: i32,
+    anchor_data: AnchorAnalysis,
+    tag_data: TagAnalysis,
+    scalar_data: ScalarAnalysis,
+}
+
+impl<W> Debug for Emitter<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Emitter")
+            .field("state", &self.state)
+            .field("line_break", &self.line_break)
+            .field("buffered", &self.buffer.len())
+            .finish()
+    }
+}
+
+impl<W: io::Write> Emitter<W> {
+    pub(crate) fn new(write: W) -> Self {
+        Emitter {
+            write,
+            buffer: Vec::new(),
+            line_break: LineBreak::Lf,
+            encoding: Encoding::Utf8,
+            unicode: true,
+            canonical: false,
+            best_indent: 2,
+            best_width: -1,
+            state: State::StreamStart,
+            states: Vec::new(),
+            events: VecDeque::new(),
+            indents: Vec::new(),
+            indent: -1,
+            flow_level: 0,
+            mapping_context: false,
+            simple_key_context: false,
+            column: 0,
+            whitespace: true,
+            indention: true,
+            open_ended: 0,
+            anchor_data: AnchorAnalysis::default(),
+            tag_data: TagAnalysis::default(),
+            scalar_data: ScalarAnalysis::default(),
+        }
+    }
+
+    pub(crate) fn set_line_break(&mut self, line_break: LineBreak) {
+        self.line_break = line_break;
+    }
+
+    pub(crate) fn set_indent(&mut self, indent: usize) {
+        self.best_indent = indent as i64;
+    }
+
+    pub(crate) fn set_encoding(&mut self, encoding: Encoding) {
+        self.encoding = encoding;
+    }
+
+    pub(crate) fn set_unicode(&mut self, unicode: bool) {
+        self.unicode = unicode;
+    }
+
+    pub(crate) fn set_width(&mut self, width: Option<usize>) {
+        self.best_width = width.map_or(-1, |width| {
+            i64::try_from(width).unwrap_or(i64::MAX)
+        });
+    }
+
+    pub(crate) fn set_canonical(&mut self, canonical: bool) {
+        self.canonical = canonical;
+    }
+
+    pub(crate) fn emit(&mut self, event: Event) -> Result<(), Error> {
+        self.events.push_back(event);
+        while !self.need_more_events() {
+            let event = match self.events.pop_front() {
+                Some(event) => event,
+                None => break,
+            };
+            self.analyze_event(&event)?;
+            self.state_machine(&event)?;
+        }
+        Ok(())
+    }
+
+    /// Hands the buffered output to the writer.
+    pub(crate) fn flush(&mut self) -> Result<(), Error> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let result = match self.encoding {
+            Encoding::Utf8 => self.write.write_all(&self.buffer),
+            Encoding::Utf16Le | Encoding::Utf16Be => {
+                let text = String::from_utf8_lossy(&self.buffer);
+                let mut raw = Vec::with_capacity(self.buffer.len() * 2);
+                for unit in text.encode_utf16() {
+                    raw.extend_from_slice(&match self.encoding {
+                        Encoding::Utf16Le => unit.to_le_bytes(),
+                        _ => unit.to_be_bytes(),
+                    });
+                }
+                self.write.write_all(&raw)
+            }
+        };
+        self.buffer.clear();
+        result.map_err(Error::Io)
+    }
+
+    fn need_more_events(&self) -> bool {
+        let accumulate = match self.events.front() {
+            None => return true,
+            Some(Event::DocumentStart { .. }) => 1,
+            Some(Event::SequenceStart { .. }) => 2,
+            Some(Event::MappingStart { .. }) => 3,
+            Some(_) => return false,
+        };
+        if self.events.len() > accumulate {
+            return false;
+        }
+        let mut level = 0;
+        for event in &self.events {
+            match event {
+                Event::StreamStart
+                | Event::DocumentStart { .. }
+                | Event::SequenceStart { .. }
+                | Event::MappingStart { .. } => level += 1,
+                Event::StreamEnd
+                | Event::DocumentEnd { .. }
+                | Event::SequenceEnd
+                | Event::MappingEnd => level -= 1,
+                Event::Alias { .. } | Event::Scalar { .. } => {}
+            }
+            if level == 0 {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn emitter_error(&self, problem: &'static [u8]) -> Error {
+        let mark = Mark::new(0, 0, 0);
+        Error::Libyml(error(ErrorKind::Emitter, None, problem, mark))
+    }
+
+    fn state_machine(&mut self, event: &Event) -> Result<(), Error> {
+        match self.state {
+            State::StreamStart => self.emit_stream_start(event),
+            State::FirstDocumentStart => {
+                self.emit_document_start(event, true)
+            }
+            State::DocumentStart => {
+                self.emit_document_start(event, false)
+            }
+            State::DocumentContent => self.emit_document_content(event),
+            State::DocumentEnd => self.emit_document_end(event),
+            State::FlowSequenceFirstItem => {
+                self.emit_flow_sequence_item(event, true)
+            }
+            State::FlowSequenceItem => {
+                self.emit_flow_sequence_item(event, false)
+            }
+            State::FlowMappingFirstKey => {
+                self.emit_flow_mapping_key(event, true)
+            }
+            State::FlowMappingKey => {
+                self.emit_flow_mapping_key(event, false)
+            }
+            State::FlowMappingSimpleValue => {
+                self.emit_flow_mapping_value(event, true)
+            }
+            State::FlowMappingValue => {
+                self.emit_flow_mapping_value(event, false)
+            }
+            State::BlockSequenceFirstItem => {
+                self.emit_block_sequence_item(event, true)
+            }
+            State::BlockSequenceItem => {
+                self.emit_block_sequence_item(event, false)
+            }
+            State::BlockMappingFirstKey => {
+                self.emit_block_mapping_key(event, true)
+            }
+            State::BlockMappingKey => {
+                self.emit_block_mapping_key(event, false)
+            }
+            State::BlockMappingSimpleValue => {
+                self.emit_block_mapping_value(event, true)
+            }
+            State::BlockMappingValue => {
+                self.emit_block_mapping_value(event, false)
+            }
+            State::End => Err(self
+                .emitter_error(b"expected nothing after STREAM-END\0")),
+        }
+    }
+
+    fn pop_state(&mut self) {
+        self.state = self.states.pop().unwrap_or(State::End);
+    }
+
+    fn pop_indent(&mut self) {
+        self.indent = self.indents.pop().unwrap_or(-1);
+    }
+
+    fn emit_stream_start(
+        &mut self,
+        event: &Event,
+    ) -> Result<(), Error> {
+        if let Event::StreamStart = event {
+            if self.encoding != Encoding::Utf8 {
+                self.buffer.extend_from_slice("\u{FEFF}".as_bytes());
+            }
+            if self.best_width >= 0
+                && self.best_width <= self.best_indent * 2
+            {
+                self.best_width = 80;
+            }
+            if self.best_width < 0 {
+                self.best_width = i64::MAX;
+            }
+            self.open_ended = 0;
+            self.indent = -1;
+            self.column = 0;
+            self.whitespace = true;
+            self.indention = true;
+            self.state = State::FirstDocumentStart;
+            Ok(())
+        } else {
+            Err(self.emitter_error(b"expected STREAM-START\0"))
+        }
+    }
+
+    fn emit_document_start(
+        &mut self,
+        event: &Event,
+        first: bool,
+    ) -> Result<(), Error> {
+        match event {
+            Event::DocumentStart { implicit } => {
+                if !first || !implicit || self.canonical {
+                    self.write_indent()?;
+                    self.write_indicator("---", true, false, false)?;
+                    if self.canonical {
+                        self.write_indent()?;
+                    }
+                }
+                self.state = State::DocumentContent;
+                self.open_ended = 0;
+                Ok(())
+            }
+            Event::StreamEnd => {
+                if self.open_ended == 2 {
+                    self.write_indicator("...", true, false, false)?;
+                    self.open_ended = 0;
+                    self.write_indent()?;
+                }
+                self.flush()?;
+                self.state = State::End;
+                Ok(())
+            }
+            _ => Err(self.emitter_error(
+                b"expected DOCUMENT-START or STREAM-END\0",
+            )),
+        }
+    }
+
+    fn emit_document_content(
+        &mut self,
+        event: &Event,
+    ) -> Result<(), Error> {
+        self.states.push(State::DocumentEnd);
+        self.emit_node(event, false, false)
+    }
+
+    fn emit_document_end(
+        &mut self,
+        event: &Event,
+    ) -> Result<(), Error> {
+        if let Event::DocumentEnd { implicit } = *event {
+            self.write_indent()?;
+            if !implicit {
+                self.write_indicator("...", true, false, false)?;
+                self.open_ended = 0;
+                self.write_indent()?;
+            } else if self.open_ended == 0 {
+                self.open_ended = 1;
+            }
+            self.flush()?;
+            self.state = State::DocumentStart;
+            Ok(())
+        } else {
+            Err(self.emitter_error(b"expected DOCUMENT-END\0"))
+        }
+    }
+
+    fn emit_flow_sequence_item(
+        &mut self,
+        event: &Event,
+        first: bool,
+    ) -> Result<(), Error> {
+        if first {
+            self.write_indicator("[", true, true, false)?;
+            self.increase_indent(true, false);
+            self.flow_level += 1;
+        }
+        if let Event::SequenceEnd = event {
+            self.flow_level -= 1;
+            self.pop_indent();
+            if self.canonical && !first {
+                self.write_indicator(",", false, false, false)?;
+                self.write_indent()?;
+            }
+            self.write_indicator("]", false, false, false)?;
+            self.pop_state();
+            return Ok(());
+        }
+        if !first {
+            self.write_indicator(",", false, false, false)?;
+        }
+        if self.canonical || self.column > self.best_width {
+            self.write_indent()?;
+        }
+        self.states.push(State::FlowSequenceItem);
+        self.emit_node(event, false, false)
+    }
+
+    fn emit_flow_mapping_key(
+        &mut self,
+        event: &Event,
+        first: bool,
+    ) -> Result<(), Error> {
+        if first {
+            self.write_indicator("{", true, true, false)?;
+            self.increase_indent(true, false);
+            self.flow_level += 1;
+        }
+        if let Event::MappingEnd = event {
+            self.flow_level -= 1;
+            self.pop_indent();
+            if self.canonical && !first {
+                self.write_indicator(",", false, false, false)?;
+                self.write_indent()?;
+            }
+            self.write_indicator("}", false, false, false)?;
+            self.pop_state();
+            return Ok(());
+        }
+        if !first {
+            self.write_indicator(",", false, false, false)?;
+        }
+        if self.canonical || self.column > self.best_width {
+            self.write_indent()?;
+        }
+        if !self.canonical && self.check_simple_key(event) {
+            self.states.push(State::FlowMappingSimpleValue);
+            self.emit_node(event, true, true)
+        } else {
+            self.write_indicator("?", true, false, false)?;
+            self.states.push(State::FlowMappingValue);
+            self.emit_node(event, true, false)
+        }
+    }
+
+    fn emit_flow_mapping_value(
+        &mut self,
+        event: &Event,
+        simple: bool,
+    ) -> Result<(), Error> {
+        if simple {
+            self.write_indicator(":", false, false, false)?;
+        } else {
+            if self.canonical || self.column > self.best_width {
+                self.write_indent()?;
+            }
+            self.write_indicator(":", true, false, false)?;
+        }
+        self.states.push(State::FlowMappingKey);
+        self.emit_node(event, true, false)
+    }
+
+    fn emit_block_sequence_item(
+        &mut self,
+        event: &Event,
+        first: bool,
+    ) -> Result<(), Error> {
+        if first {
+            let indentless = self.mapping_context && !self.indention;
+            self.increase_indent(false, indentless);
+        }
+        if let Event::SequenceEnd = event {
+            self.pop_indent();
+            self.pop_state();
+            return Ok(());
+        }
+        self.write_indent()?;
+        self.write_indicator("-", true, false, true)?;
+        self.states.push(State::BlockSequenceItem);
+        self.emit_node(event, false, false)
+    }
+
+    fn emit_block_mapping_key(
+        &mut self,
+        event: &Event,
+        first: bool,
+    ) -> Result<(), Error> {
+        if first {
+            self.increase_indent(false, false);
+        }
+        if let Event::MappingEnd = event {
+            self.pop_indent();
+            self.pop_state();
+            return Ok(());
+        }
+        self.write_indent()?;
+        if self.check_simple_key(event) {
+            self.states.push(State::BlockMappingSimpleValue);
+            self.emit_node(event, true, true)
+        } else {
+            self.write_indicator("?", true, false, true)?;
+            self.states.push(State::BlockMappingValue);
+            self.emit_node(event, true, false)
+        }
+    }
+
+    fn emit_block_mapping_value(
+        &mut self,
+        event: &Event,
+        simple: bool,
+    ) -> Result<(), Error> {
+        if simple {
+            self.write_indicator(":", false, false, false)?;
+        } else {
+            self.write_indent()?;
+            self.write_indicator(":", true, false, true)?;
+        }
+        self.states.push(State::BlockMappingKey);
+        self.emit_node(event, true, false)
+    }
+
+    fn emit_node(
+        &mut self,
+        event: &Event,
+        mapping: bool,
+        simple_key: bool,
+    ) -> Result<(), Error> {
+        self.mapping_context = mapping;
+        self.simple_key_context = simple_key;
+        match event {
+            Event::Alias { .. } => self.emit_alias(),
+            Event::Scalar { value, style, .. } => {
+                self.emit_scalar(value, *style)
+            }
+            Event::SequenceStart { .. } => {
+                self.process_anchor()?;
+                self.process_tag()?;
+                self.state = if self.flow_level > 0
+                    || self.canonical
+                    || self.check_empty_sequence(event)
+                {
+                    State::FlowSequenceFirstItem
+                } else {
+                    State::BlockSequenceFirstItem
+                };
+                Ok(())
+            }
+            Event::MappingStart { .. } => {
+                self.process_anchor()?;
+                self.process_tag()?;
+                self.state = if self.flow_level > 0
+                    || self.canonical
+                    || self.check_empty_mapping(event)
+                {
+                    State::FlowMappingFirstKey
+                } else {
+                    State::BlockMappingFirstKey
+                };
+                Ok(())
+            }
+            _ => Err(self.emitter_error(
+                b"expected SCALAR, SEQUENCE-START, MAPPING-START, or ALIAS\0",
+            )),
+        }
+    }
+
+    fn emit_alias(&mut self) -> Result<(), Error> {
+        self.process_anchor()?;
+        if self.simple_key_context {
+            self.put(b' ')?;
+        }
+        self.pop_state();
+        Ok(())
+    }
+
+    fn emit_scalar(
+        &mut self,
+        value: &str,
+        style: ScalarStyle,
+    ) -> Result<(), Error> {
+        let style = self.select_scalar_style(value, style);
+        self.process_anchor()?;
+        self.process_tag()?;
+        self.increase_indent(true, false);
+        match style {
+            Style::Plain => self
+                .write_plain_scalar(value, !self.simple_key_context)?,
+            Style::SingleQuoted => {
+                self.write_single_quoted_scalar(
+                    value,
+                    !self.simple_key_context,
+                )?;
+            }
+            Style::DoubleQuoted => {
+                self.write_double_quoted_scalar(
+                    value,
+                    !self.simple_key_context,
+                )?;
+            }
+            Style::Literal => self.write_literal_scalar(value)?,
+            Style::Folded => self.write_folded_scalar(value)?,
+        }
+        self.pop_indent();
+        self.pop_state();
+        Ok(())
+    }
+
+    fn check_empty_sequence(&self, event: &Event) -> bool {
+        matches!(event, Event::SequenceStart { .. })
+            && matches!(self.events.front(), Some(Event::SequenceEnd))
+    }
+
+    fn check_empty_mapping(&self, event: &Event) -> bool {
+        matches!(event, Event::MappingStart { .. })
+            && matches!(self.events.front(), Some(Event::MappingEnd))
+    }
+
+    fn check_simple_key(&self, event: &Event) -> bool {
+        let tag_length = self.tag_data.handle.map_or(0, str::len)
+            + self.tag_data.suffix.as_ref().map_or(0, String::len)
+            + self.anchor_data.anchor.as_ref().map_or(0, String::len);
+        let length = match event {
+            Event::Alias { .. } => tag_length,
+            Event::Scalar { value, .. } => {
+                if self.scalar_data.multiline {
+                    return false;
+                }
+                tag_length + value.len()
+            }
+            Event::SequenceStart { .. } => {
+                if !self.check_empty_sequence(event) {
+                    return false;
+                }
+                tag_length
+            }
+            Event::MappingStart { .. } => {
+                if !self.check_empty_mapping(event) {
+                    return false;
+                }
+                tag_length
+            }
+            _ => return false,
+        };
+        length <= 128
+    }
+
+    fn increase_indent(&mut self, flow: bool, indentless: bool) {
+        self.indents.push(self.indent);
+        if self.indent < 0 {
+            self.indent = if flow { self.best_indent } else { 0 };
+        } else if !indentless {
+            self.indent += self.best_indent;
+        }
+    }
+
+    fn select_scalar_style(
+        &self,
+        value: &str,
+        style: ScalarStyle,
+    ) -> Style {
+        let analysis = &self.scalar_data;
+        let mut style = match style {
+            ScalarStyle::Any | ScalarStyle::Plain => Style::Plain,
+            ScalarStyle::SingleQuoted => Style::SingleQuoted,
+            ScalarStyle::DoubleQuoted => Style::DoubleQuoted,
+            ScalarStyle::Literal => Style::Literal,
+            ScalarStyle::Folded => Style::Folded,
+        };
+        if self.canonical {
+            style = Style::DoubleQuoted;
+        }
+        if self.simple_key_context && analysis.multiline {
+            style = Style::DoubleQuoted;
+        }
+        if style == Style::Plain
+            && (self.flow_level > 0 && !analysis.flow_plain_allowed
+                || self.flow_level == 0
+                    && !analysis.block_plain_allowed
+                || value.is_empty()
+                    && (self.flow_level > 0 || self.simple_key_context))
+        {
+            style = Style::SingleQuoted;
+        }
+        if style == Style::SingleQuoted
+            && !analysis.single_quoted_allowed
+        {
+            style = Style::DoubleQuoted;
+        }
+        if (style == Style::Literal || style == Style::Folded)
+            && (!analysis.block_allowed
+                || self.flow_level > 0
+                || self.simple_key_context)
+        {
+            style = Style::DoubleQuoted;
+        }
+        style
+    }
+
+    fn process_anchor(&mut self) -> Result<(), Error> {
+        let anchor = match self.anchor_data.anchor.take() {
+            Some(anchor) => anchor,
+            None => return Ok(()),
+        };
+        let indicator = if self.anchor_data.alias { "*" } else { "&" };
+        self.write_indicator(indicator, true, false, false)?;
+        self.write_str(&anchor)?;
+        self.whitespace = false;
+        self.indention = false;
+        Ok(())
+    }
+
+    fn process_tag(&mut self) -> Result<(), Error> {
+        let suffix = match self.tag_data.suffix.take() {
+            Some(suffix) => suffix,
+            None => return Ok(()),
+        };
+        if let Some(handle) = self.tag_data.handle {
+            self.write_tag_handle(handle)?;
+            self.write_tag_content(&suffix, false)?;
+        } else {
+            self.write_indicator("!<", true, false, false)?;
+            self.write_tag_content(&suffix, false)?;
+            self.write_indicator(">", false, false, false)?;
+        }
+        Ok(())
+    }
+
+    fn analyze_event(&mut self, event: &Event) -> Result<(), Error> {
+        self.anchor_data = AnchorAnalysis::default();
+        self.tag_data = TagAnalysis::default();
+        self.scalar_data = ScalarAnalysis::default();
+        match event {
+            Event::Alias { anchor } => {
+                self.analyze_anchor(anchor, true)?
+            }
+            Event::Scalar {
+                anchor, tag, value, ..
+            } => {
+                if let Some(anchor) = anchor {
+                    self.analyze_anchor(anchor, false)?;
+                }
+                if let Some(tag) = tag {
+                    self.analyze_tag(tag)?;
+                }
+                self.analyze_scalar(value);
+            }
+            Event::SequenceStart { anchor, tag }
+            | Event::MappingStart { anchor, tag } => {
+                if let Some(anchor) = anchor {
+                    self.analyze_anchor(anchor, false)?;
+                }
+                if let Some(tag) = tag {
+                    self.analyze_tag(tag)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn analyze_anchor(
+        &mut self,
+        anchor: &str,
+        alias: bool,
+    ) -> Result<(), Error> {
+        if anchor.is_empty() {
+            return Err(self.emitter_error(if alias {
+                b"alias value must not be empty\0"
+            } else {
+                b"anchor value must not be empty\0"
+            }));
+        }
+        if !anchor
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(self.emitter_error(if alias {
+                b"alias value must contain alphanumerical characters only\0"
+            } else {
+                b"anchor value must contain alphanumerical characters only\0"
+            }));
+        }
+        self.anchor_data.anchor = Some(anchor.to_owned());
+        self.anchor_data.alias = alias;
+        Ok(())
+    }
+
+    fn analyze_tag(&mut self, tag: &str) -> Result<(), Error> {
+        if tag.is_empty() {
+            return Err(
+                self.emitter_error(b"tag value must not be empty\0")
+            );
+        }
+        for (handle, prefix) in DEFAULT_TAG_DIRECTIVES {
+            if prefix.len() < tag.len() && tag.starts_with(prefix) {
+                self.tag_data.handle = Some(handle);
+                self.tag_data.suffix =
+                    Some(tag[prefix.len()..].to_owned());
+                return Ok(());
+            }
+        }
+        self.tag_data.suffix = Some(tag.to_owned());
+        Ok(())
+    }
+
+    fn analyze_scalar(&mut self, value: &str) {
+        let analysis = &mut self.scalar_data;
+        if value.is_empty() {
+            analysis.multiline = false;
+            analysis.flow_plain_allowed = false;
+            analysis.block_plain_allowed = true;
+            analysis.single_quoted_allowed = true;
+            analysis.block_allowed = false;
+            return;
+        }
+
+        let mut block_indicators = false;
+        let mut flow_indicators = false;
+        let mut line_breaks = false;
+        let mut special_characters = false;
+        let mut leading_space = false;
+        let mut leading_break = false;
+        let mut trailing_space = false;
+        let mut trailing_break = false;
+        let mut break_space = false;
+        let mut space_break = false;
+        let mut preceded_by_whitespace = true;
+        let mut previous_space = false;
+        let mut previous_break = false;
+
+        if value.starts_with("---") || value.starts_with("...") {
+            block_indicators = true;
+            flow_indicators = true;
+        }
+
+        let mut chars = value.char_indices().peekable();
+        while let Some((index, c)) = chars.next() {
+            let next = chars.peek().map(|&(_, next)| next);
+            let followed_by_whitespace =
+                next.map_or(true, is_blank_or_break);
+            if index == 0 {
+                match c {
+                    '#' | ',' | '[' | ']' | '{' | '}' | '&' | '*'
+                    | '!' | '|' | '>' | '\'' | '"' | '%' | '@'
+                    | '`' => {
+                        flow_indicators = true;
+                        block_indicators = true;
+                    }
+                    '?' | ':' => {
+                        flow_indicators = true;
+                        if followed_by_whitespace {
+                            block_indicators = true;
+                        }
+                    }
+                    '-' if followed_by_whitespace => {
+                        flow_indicators = true;
+                        block_indicators = true;
+                    }
+                    _ => {}
+                }
+            } else {
+                match c {
+                    ',' | '?' | '[' | ']' | '{' | '}' => {
+                        flow_indicators = true;
+                    }
+                    ':' => {
+                        flow_indicators = true;
+                        if followed_by_whitespace {
+                            block_indicators = true;
+                        }
+                    }
+                    '#' if preceded_by_whitespace => {
+                        flow_indicators = true;
+                        block_indicators = true;
+                    }
+                    _ => {}
+                }
+            }
+
+            if !is_printable(c) || !self.unicode && !c.is_ascii() {
+                special_characters = true;
+            }
+            if is_break(c) {
+                line_breaks = true;
+            }
+
+            let last = next.is_none();
+            if c == ' ' {
+                if index == 0 {
+                    leading_space = true;
+                }
+                if last {
+                    trailing_space = true;
+                }
+                if previous_break {
+                    break_space = true;
+                }
+                previous_space = true;
+                previous_break = false;
+            } else if is_break(c) {
+                if index == 0 {
+                    leading_break = true;
+                }
+                if last {
+                    trailing_break = true;
+                }
+                if previous_space {
+                    space_break = true;
+                }
+                previous_space = false;
+                previous_break = true;
+            } else {
+                previous_space = false;
+                previous_break = false;
+            }
+
+            preceded_by_whitespace = is_blank_or_break(c);
+        }
+
+        analysis.multiline = line_breaks;
+        analysis.flow_plain_allowed = true;
+        analysis.block_plain_allowed = true;
+        analysis.single_quoted_allowed = true;
+        analysis.block_allowed = true;
+        if leading_space
+            || leading_break
+            || trailing_space
+            || trailing_break
+        {
+            analysis.flow_plain_allowed = false;
+            analysis.block_plain_allowed = false;
+        }
+        if trailing_space {
+            analysis.block_allowed = false;
+        }
+        if break_space {
+            analysis.flow_plain_allowed = false;
+            analysis.block_plain_allowed = false;
+            analysis.single_quoted_allowed = false;
+        }
+        if space_break || special_characters {
+            analysis.flow_plain_allowed = false;
+            analysis.block_plain_allowed = false;
+            analysis.single_quoted_allowed = false;
+            analysis.block_allowed = false;
+        }
+        if line_breaks {
+            analysis.flow_plain_allowed = false;
+            analysis.block_plain_allowed = false;
+        }
+        if flow_indicators {
+            analysis.flow_plain_allowed = false;
+        }
+        if block_indicators {
+            analysis.block_plain_allowed = false;
+        }
+    }
+
+    // Low-level writing. Columns count characters, as in libyaml.
+
+    fn put(&mut self, byte: u8) -> Result<(), Error> {
+        self.buffer.push(byte);
+        self.column += 1;
+        self.flush_if_full()
+    }
+
+    fn put_break(&mut self) -> Result<(), Error> {
+        match self.line_break {
+            LineBreak::Lf => self.buffer.push(b'\n'),
+            LineBreak::CrLf => self.buffer.extend_from_slice(b"\r\n"),
+            LineBreak::Cr => self.buffer.push(b'\r'),
+        }
+        self.column = 0;
+        self.flush_if_full()
+    }
+
+    fn write_char(&mut self, c: char) -> Result<(), Error> {
+        let mut bytes = [0; 4];
+        self.buffer
+            .extend_from_slice(c.encode_utf8(&mut bytes).as_bytes());
+        self.column += 1;
+        self.flush_if_full()
+    }
+
+    fn write_str(&mut self, s: &str) -> Result<(), Error> {
+        for c in s.chars() {
+            self.write_char(c)?;
+        }
+        Ok(())
+    }
+
+    fn write_break(&mut self, c: char) -> Result<(), Error> {
+        if c == '\n' {
+            self.put_break()
+        } else {
+            self.write_char(c)?;
+            self.column = 0;
+            Ok(())
+        }
+    }
+
+    fn flush_if_full(&mut self) -> Result<(), Error> {
+        if self.buffer.len() >= BUFFER_SIZE {
+            self.flush()
+        } else {
+            Ok(())
+        }
+    }
+
+    fn write_indent(&mut self) -> Result<(), Error> {
+        let indent = self.indent.max(0);
+        if !self.indention
+            || self.column > indent
+            || self.column == indent && !self.whitespace
+        {
+            self.put_break()?;
+        }
+        while self.column < indent {
+            self.put(b' ')?;
+        }
+        self.whitespace = true;
+        self.indention = true;
+        Ok(())
+    }
+
+    fn write_indicator(
+        &mut self,
+        indicator: &str,
+        need_whitespace: bool,
+        is_whitespace: bool,
+        is_indention: bool,
+    ) -> Result<(), Error> {
+        if need_whitespace && !self.whitespace {
+            self.put(b' ')?;
+        }
+        self.write_str(indicator)?;
+        self.whitespace = is_whitespace;
+        self.indention = self.indention && is_indention;
+        Ok(())
+    }
+
+    fn write_tag_handle(&mut self, handle: &str) -> Result<(), Error> {
+        if !self.whitespace {
+            self.put(b' ')?;
+        }
+        self.write_str(handle)?;
+        self.whitespace = false;
+        self.indention = false;
+        Ok(())
+    }
+
+    fn write_tag_content(
+        &mut self,
+        content: &str,
+        need_whitespace: bool,
+    ) -> Result<(), Error> {
+        if need_whitespace && !self.whitespace {
+            self.put(b' ')?;
+        }
+        for c in content.chars() {
+            if c.is_ascii_alphanumeric()
+                || matches!(
+                    c,
+                    '_' | '-'
+                        | ';'
+                        | '/'
+                        | '?'
+                        | ':'
+                        | '@'
+                        | '&'
+                        | '='
+                        | '+'
+                        | '$'
+                        | ','
+                        | '.'
+                        | '~'
+                        | '*'
+                        | '\''
+                        | '('
+                        | ')'
+                        | '['
+                        | ']'
+                )
+            {
+                self.write_char(c)?;
+            } else {
+                let mut bytes = [0; 4];
+                for byte in c.encode_utf8(&mut bytes).bytes() {
+                    self.put(b'%')?;
+                    self.put(hex_digit(byte >> 4))?;
+                    self.put(hex_digit(byte & 0x0F))?;
+                }
+            }
+        }
+        self.whitespace = false;
+        self.indention = false;
+        Ok(())
+    }
+
+    fn write_plain_scalar(
+        &mut self,
+        value: &str,
+        allow_breaks: bool,
+    ) -> Result<(), Error> {
+        let mut spaces = false;
+        let mut breaks = false;
+        if !self.whitespace
+            && (!value.is_empty() || self.flow_level > 0)
+        {
+            self.put(b' ')?;
+        }
+        let mut chars = value.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c == ' ' {
+                if allow_breaks
+                    && !spaces
+                    && self.column > self.best_width
+                    && chars.peek() != Some(&' ')
+                {
+                    self.write_indent()?;
+                } else {
+                    self.write_char(c)?;
+                }
+                spaces = true;
+            } else if is_break(c) {
+                if !breaks && c == '\n' {
+                    self.put_break()?;
+                }
+                self.write_break(c)?;
+                self.indention = true;
+                breaks = true;
+            } else {
+                if breaks {
+                    self.write_indent()?;
+                }
+                self.write_char(c)?;
+                self.indention = false;
+                spaces = false;
+                breaks = false;
+            }
+        }
+        self.whitespace = false;
+        self.indention = false;
+        Ok(())
+    }
+
+    fn write_single_quoted_scalar(
+        &mut self,
+        value: &str,
+        allow_breaks: bool,
+    ) -> Result<(), Error> {
+        let mut spaces = false;
+        let mut breaks = false;
+        self.write_indicator("'", true, false, false)?;
+        let mut chars = value.char_indices().peekable();
+        while let Some((index, c)) = chars.next() {
+            let next = chars.peek().map(|&(_, next)| next);
+            if c == ' ' {
+                if allow_breaks
+                    && !spaces
+                    && self.column > self.best_width
+                    && index != 0
+                    && next.is_some()
+                    && next != Some(' ')
+                {
+                    self.write_indent()?;
+                } else {
+                    self.write_char(c)?;
+                }
+                spaces = true;
+            } else if is_break(c) {
+                if !breaks && c == '\n' {
+                    self.put_break()?;
+                }
+                self.write_break(c)?;
+                self.indention = true;
+                breaks = true;
+            } else {
+                if breaks {
+                    self.write_indent()?;
+                }
+                if c == '\'' {
+                    self.put(b'\'')?;
+                }
+                self.write_char(c)?;
+                self.indention = false;
+                spaces = false;
+                breaks = false;
+            }
+        }
+        if breaks {
+            self.write_indent()?;
+        }
+        self.write_indicator("'", false, false, false)
+    }
+
+    fn write_double_quoted_scalar(
+        &mut self,
+        value: &str,
+        allow_breaks: bool,
+    ) -> Result<(), Error> {
+        let mut spaces = false;
+        self.write_indicator("\"", true, false, false)?;
+        let mut chars = value.char_indices().peekable();
+        while let Some((index, c)) = chars.next() {
+            let next = chars.peek().map(|&(_, next)| next);
+            if !is_printable(c)
+                || !self.unicode && !c.is_ascii()
+                || c == '\u{FEFF}'
+                || is_break(c)
+                || c == '"'
+                || c == '\\'
+            {
+                self.put(b'\\')?;
+                match c {
+                    '\0' => self.put(b'0')?,
+                    '\x07' => self.put(b'a')?,
+                    '\x08' => self.put(b'b')?,
+                    '\t' => self.put(b't')?,
+                    '\n' => self.put(b'n')?,
+                    '\x0B' => self.put(b'v')?,
+                    '\x0C' => self.put(b'f')?,
+                    '\r' => self.put(b'r')?,
+                    '\x1B' => self.put(b'e')?,
+                    '"' => self.put(b'"')?,
+                    '\\' => self.put(b'\\')?,
+                    '\u{85}' => self.put(b'N')?,
+                    '\u{A0}' => self.put(b'_')?,
+                    '\u{2028}' => self.put(b'L')?,
+                    '\u{2029}' => self.put(b'P')?,
+                    _ => {
+                        let value = u32::from(c);
+                        let (prefix, width) = if value <= 0xFF {
+                            (b'x', 2)
+                        } else if value <= 0xFFFF {
+                            (b'u', 4)
+                        } else {
+                            (b'U', 8)
+                        };
+                        self.put(prefix)?;
+                        for k in (0..width).rev() {
+                            self.put(hex_digit(
+                                ((value >> (k * 4)) & 0x0F) as u8,
+                            ))?;
+                        }
+                    }
+                }
+                spaces = false;
+            } else if c == ' ' {
+                if allow_breaks
+                    && !spaces
+                    && self.column > self.best_width
+                    && index != 0
+                    && next.is_some()
+                {
+                    self.write_indent()?;
+                    if next == Some(' ') {
+                        self.put(b'\\')?;
+                    }
+                } else {
+                    self.write_char(c)?;
+                }
+                spaces = true;
+            } else {
+                self.write_char(c)?;
+                spaces = false;
+            }
+        }
+        self.write_indicator("\"", false, false, false)
+    }
+
+    fn write_block_scalar_hints(
+        &mut self,
+        value: &str,
+    ) -> Result<(), Error> {
+        if value.starts_with(' ') || value.starts_with(is_break) {
+            let indent_hint = self.best_indent.to_string();
+            self.write_indicator(&indent_hint, false, false, false)?;
+        }
+        self.open_ended = 0;
+        let mut chars = value.chars().rev();
+        let chomp_hint = match chars.next() {
+            None => Some("-"),
+            Some(last) if !is_break(last) => Some("-"),
+            Some(_) => match chars.next() {
+                None => {
+                    self.open_ended = 2;
+                    Some("+")
+                }
+                Some(previous) if is_break(previous) => {
+                    self.open_ended = 2;
+                    Some("+")
+                }
+                Some(_) => None,
+            },
+        };
+        if let Some(chomp_hint) = chomp_hint {
+            self.write_indicator(chomp_hint, false, false, false)?;
+        }
+        Ok(())
+    }
+
+    fn write_literal_scalar(
+        &mut self,
+        value: &str,
+    ) -> Result<(), Error> {
+        let mut breaks = true;
+        self.write_indicator("|", true, false, false)?;
+        self.write_block_scalar_hints(value)?;
+        self.put_break()?;
+        self.indention = true;
+        self.whitespace = true;
+        for c in value.chars() {
+            if is_break(c) {
+                self.write_break(c)?;
+                self.indention = true;
+                breaks = true;
+            } else {
+                if breaks {
+                    self.write_indent()?;
+                }
+                self.write_char(c)?;
+                self.indention = false;
+                breaks = false;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_folded_scalar(
+        &mut self,
+        value: &str,
+    ) -> Result<(), Error> {
+        let mut breaks = true;
+        let mut leading_spaces = true;
+        self.write_indicator(">", true, false, false)?;
+        self.write_block_scalar_hints(value)?;
+        self.put_break()?;
+        self.indention = true;
+        self.whitespace = true;
+        let mut chars = value.char_indices().peekable();
+        while let Some((index, c)) = chars.next() {
+            if is_break(c) {
+                if !breaks && !leading_spaces && c == '\n' {
+                    let rest = &value[index..];
+                    let after_breaks =
+                        rest.trim_start_matches(is_break);
+                    if !after_breaks.starts_with(is_blank)
+                        && !after_breaks.is_empty()
+                    {
+                        self.put_break()?;
+                    }
+                }
+                self.write_break(c)?;
+                self.indention = true;
+                breaks = true;
+            } else {
+                if breaks {
+                    self.write_indent()?;
+                    leading_spaces = is_blank(c);
+                }
+                let next = chars.peek().map(|&(_, next)| next);
+                if !breaks
+                    && c == ' '
+                    && next != Some(' ')
+                    && self.column > self.best_width
+                {
+                    self.write_indent()?;
+                } else {
+                    self.write_char(c)?;
+                }
+                self.indention = false;
+                breaks = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_blank(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
+
+fn is_break(c: char) -> bool {
+    matches!(c, '\r' | '\n' | '\u{85}' | '\u{2028}' | '\u{2029}')
+}
+
+fn is_blank_or_break(c: char) -> bool {
+    is_blank(c) || is_break(c)
+}
+
+/// Characters written as they are; everything else is escaped in double
+/// quoted scalars and rules out the other styles.
+fn is_printable(c: char) -> bool {
+    matches!(
+        c,
+        '\n' | '\x20'..='\x7E'
+            | '\u{A0}'..='\u{D7FF}'
+            | '\u{E000}'..='\u{FFFD}'
+            | '\u{10000}'..='\u{10FFFF}'
+    ) && c != '\u{FEFF}'
+}
+
+fn hex_digit(nibble: u8) -> u8 {
+    if nibble < 10 {
+        b'0' + nibble
+    } else {
+        b'A' + nibble - 10
+    }
+}
@@ -0,0 +1,43 @@
+//! Safe Rust port of the libyaml scanner, parser and emitter.
+//!
+//! Selected by the `pure-rust` feature in place of the `libyml` C port. The
+//! state machines follow the reference implementation step by step, so
+//! events, marks and error messages match the other backend.
+
+#![forbid(unsafe_code)]
+
+pub(crate) mod emitter;
+pub(crate) mod parser;
+pub(crate) mod scanner;
+
+use crate::libyml::{
+    error::{Error, ErrorKind, Mark},
+    safe_cstr::CStr,
+};
+
+/// Builds an error with an optional context, both given as nul-terminated
+/// byte strings.
+pub(crate) fn error(
+    kind: ErrorKind,
+    context: Option<(&'static [u8], Mark)>,
+    problem: &'static [u8],
+    problem_mark: Mark,
+) -> Error {
+    let (context, context_mark) = match context {
+        Some((context, mark)) => (Some(cstr(context)), mark),
+        None => (None, Mark::new(0, 0, 0)),
+    };
+    Error {
+        kind,
+        problem: cstr(problem),
+        problem_offset: 0,
+        problem_mark,
+        context,
+        context_mark,
+    }
+}
+
+fn cstr(bytes: &'static [u8]) -> CStr<'static> {
+    CStr::from_bytes_with_nul(bytes)
+        .expect("error messages are nul-terminated")
+}
@@ -0,0 +1,817 @@
+//! Event parser of the pure Rust backend.
+//!
+//! Drives the libyaml parser state machine over the tokens of the
+//! [`Scanner`], producing the same events with the same marks.
+
+use super::{
+    error,
+    scanner::{Scanner, Token, TokenKind},
+};
+use crate::{
+    lib::*,
+    libyml::{
+        error::{Error, ErrorKind, Mark, Result},
+        parser::{
+            Anchor, Event, MappingStart, Scalar, ScalarStyle,
+            SequenceStart,
+        },
+        tag::Tag,
+    },
+};
+use alloc::borrow::Cow;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    StreamStart,
+    ImplicitDocumentStart,
+    DocumentStart,
+    DocumentContent,
+    DocumentEnd,
+    BlockNode,
+    BlockSequenceFirstEntry,
+    BlockSequenceEntry,
+    IndentlessSequenceEntry,
+    BlockMappingFirstKey,
+    BlockMappingKey,
+    BlockMappingValue,
+    FlowSequenceFirstEntry,
+    FlowSequenceEntry,
+    FlowSequenceEntryMappingKey,
+    FlowSequenceEntryMappingValue,
+    FlowSequenceEntryMappingEnd,
+    FlowMappingFirstKey,
+    FlowMappingKey,
+    FlowMappingValue,
+    FlowMappingEmptyValue,
+    End,
+}
+
+type Parsed = Result<(Event<'static>, Mark, Mark)>;
+
+#[derive(Debug)]
+pub(crate) struct Parser<'input> {
+    pub(crate) scanner: Scanner<'input>,
+    state: State,
+    states: Vec<State>,
+    marks: Vec<Mark>,
+    tag_directives: Vec<(Vec<u8>, Vec<u8>)>,
+    error: Option<Error>,
+}
+
+const DEFAULT_TAG_DIRECTIVES: [(&[u8], &[u8]); 2] =
+    [(b"!", b"!"), (b"!!", b"tag:yaml.org,2002:")];
+
+impl<'input> Parser<'input> {
+    pub(crate) fn new(input: Cow<'input, [u8]>) -> Self {
+        Parser {
+            scanner: Scanner::new(input),
+            state: State::StreamStart,
+            states: Vec::new(),
+            marks: Vec::new(),
+            tag_directives: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Produces the next event with the marks at its start and end. Once an
+    /// error has been returned, every later call returns it again.
+    pub(crate) fn parse(&mut self) -> Parsed {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        if self.scanner.stream_end_produced || self.state == State::End
+        {
+            return Err(error(
+                ErrorKind::Parser,
+                None,
+                b"no more events after the end of the stream\0",
+                Mark::new(0, 0, 0),
+            ));
+        }
+        let result = self.state_machine();
+        if let Err(error) = result {
+            self.error = Some(error);
+        }
+        result
+    }
+
+    fn state_machine(&mut self) -> Parsed {
+        match self.state {
+            State::StreamStart => self.parse_stream_start(),
+            State::ImplicitDocumentStart => {
+                self.parse_document_start(true)
+            }
+            State::DocumentStart => self.parse_document_start(false),
+            State::DocumentContent => self.parse_document_content(),
+            State::DocumentEnd => self.parse_document_end(),
+            State::BlockNode => self.parse_node(true, false),
+            State::BlockSequenceFirstEntry => {
+                self.parse_block_sequence_entry(true)
+            }
+            State::BlockSequenceEntry => {
+                self.parse_block_sequence_entry(false)
+            }
+            State::IndentlessSequenceEntry => {
+                self.parse_indentless_sequence_entry()
+            }
+            State::BlockMappingFirstKey => {
+                self.parse_block_mapping_key(true)
+            }
+            State::BlockMappingKey => {
+                self.parse_block_mapping_key(false)
+            }
+            State::BlockMappingValue => {
+                self.parse_block_mapping_value()
+            }
+            State::FlowSequenceFirstEntry => {
+                self.parse_flow_sequence_entry(true)
+            }
+            State::FlowSequenceEntry => {
+                self.parse_flow_sequence_entry(false)
+            }
+            State::FlowSequenceEntryMappingKey => {
+                self.parse_flow_sequence_entry_mapping_key()
+            }
+            State::FlowSequenceEntryMappingValue => {
+                self.parse_flow_sequence_entry_mapping_value()
+            }
+            State::FlowSequenceEntryMappingEnd => {
+                self.parse_flow_sequence_entry_mapping_end()
+            }
+            State::FlowMappingFirstKey => {
+                self.parse_flow_mapping_key(true)
+            }
+            State::FlowMappingKey => self.parse_flow_mapping_key(false),
+            State::FlowMappingValue => {
+                self.parse_flow_mapping_value(false)
+            }
+            State::FlowMappingEmptyValue => {
+                self.parse_flow_mapping_value(true)
+            }
+            State::End => Err(error(
+                ErrorKind::Parser,
+                None,
+                b"no more events after the end of the stream\0",
+                Mark::new(0, 0, 0),
+            )),
+        }
+    }
+
+    fn peek(&mut self) -> Result<&Token> {
+        self.scanner.peek_token()
+    }
+
+    fn skip(&mut self) -> Result<Token> {
+        match self.scanner.skip_token() {
+            Some(token) => Ok(token),
+            None => Err(error(
+                ErrorKind::Parser,
+                None,
+                b"no more tokens after the end of the stream\0",
+                Mark::new(0, 0, 0),
+            )),
+        }
+    }
+
+    fn pop_state(&mut self) -> State {
+        self.states.pop().unwrap_or(State::End)
+    }
+
+    fn pop_mark(&mut self) -> Mark {
+        self.marks.pop().unwrap_or_else(|| Mark::new(0, 0, 0))
+    }
+
+    fn parse_stream_start(&mut self) -> Parsed {
+        let token = self.peek()?;
+        let mark = token.start_mark;
+        if !matches!(token.kind, TokenKind::StreamStart) {
+            return Err(error(
+                ErrorKind::Parser,
+                None,
+                b"did not find expected <stream-start>\0",
+                mark,
+            ));
+        }
+        let token = self.skip()?;
+        self.state = State::ImplicitDocumentStart;
+        Ok((Event::StreamStart, token.start_mark, token.end_mark))
+    }
+
+    fn parse_document_start(&mut self, implicit: bool) -> Parsed {
+        if !implicit {
+            while matches!(self.peek()?.kind, TokenKind::DocumentEnd) {
+                self.skip()?;
+            }
+        }
+        let token = self.peek()?;
+        let start_mark = token.start_mark;
+        let is_directive_or_end = matches!(
+            token.kind,
+            TokenKind::VersionDirective { .. }
+                | TokenKind::TagDirective { .. }
+                | TokenKind::DocumentStart
+                | TokenKind::StreamEnd
+        );
+        if implicit && !is_directive_or_end {
+            self.process_directives()?;
+            self.states.push(State::DocumentEnd);
+            self.state = State::BlockNode;
+            Ok((Event::DocumentStart, start_mark, start_mark))
+        } else if !matches!(token.kind, TokenKind::StreamEnd) {
+            self.process_directives()?;
+            let token = self.peek()?;
+            if !matches!(token.kind, TokenKind::DocumentStart) {
+                let mark = token.start_mark;
+                return Err(error(
+                    ErrorKind::Parser,
+                    None,
+                    b"did not find expected <document start>\0",
+                    mark,
+                ));
+            }
+            let token = self.skip()?;
+            self.states.push(State::DocumentEnd);
+            self.state = State::DocumentContent;
+            Ok((Event::DocumentStart, start_mark, token.end_mark))
+        } else {
+            let token = self.skip()?;
+            self.state = State::End;
+            Ok((Event::StreamEnd, token.start_mark, token.end_mark))
+        }
+    }
+
+    fn parse_document_content(&mut self) -> Parsed {
+        let token = self.peek()?;
+        if matches!(
+            token.kind,
+            TokenKind::VersionDirective { .. }
+                | TokenKind::TagDirective { .. }
+                | TokenKind::DocumentStart
+                | TokenKind::DocumentEnd
+                | TokenKind::StreamEnd
+        ) {
+            let mark = token.start_mark;
+            self.state = self.pop_state();
+            Ok(empty_scalar(mark))
+        } else {
+            self.parse_node(true, false)
+        }
+    }
+
+    fn parse_document_end(&mut self) -> Parsed {
+        let token = self.peek()?;
+        let start_mark = token.start_mark;
+        let mut end_mark = start_mark;
+        if matches!(token.kind, TokenKind::DocumentEnd) {
+            end_mark = token.end_mark;
+            self.skip()?;
+        }
+        self.tag_directives.clear();
+        self.state = State::DocumentStart;
+        Ok((Event::DocumentEnd, start_mark, end_mark))
+    }
+
+    fn parse_node(
+        &mut self,
+        block: bool,
+        indentless_sequence: bool,
+    ) -> Parsed {
+        let token = self.peek()?;
+        if matches!(token.kind, TokenKind::Alias(_)) {
+            self.state = self.pop_state();
+            let token = self.skip()?;
+            let anchor = match token.kind {
+                TokenKind::Alias(value) => value,
+                _ => Vec::new(),
+            };
+            return Ok((
+                Event::Alias(Anchor(anchor.into_boxed_slice())),
+                token.start_mark,
+                token.end_mark,
+            ));
+        }
+
+        let mut start_mark = token.start_mark;
+        let mut end_mark = token.start_mark;
+        let mut tag_mark = token.start_mark;
+        let mut anchor = None;
+        let mut tag = None;
+        if matches!(token.kind, TokenKind::Anchor(_)) {
+            let token = self.skip()?;
+            start_mark = token.start_mark;
+            end_mark = token.end_mark;
+            if let TokenKind::Anchor(value) = token.kind {
+                anchor = Some(value);
+            }
+            if matches!(self.peek()?.kind, TokenKind::Tag { .. }) {
+                let token = self.skip()?;
+                tag_mark = token.start_mark;
+                end_mark = token.end_mark;
+                if let TokenKind::Tag { handle, suffix } = token.kind {
+                    tag = Some((handle, suffix));
+                }
+            }
+        } else if matches!(token.kind, TokenKind::Tag { .. }) {
+            let token = self.skip()?;
+            start_mark = token.start_mark;
+            tag_mark = token.start_mark;
+            end_mark = token.end_mark;
+            if let TokenKind::Tag { handle, suffix } = token.kind {
+                tag = Some((handle, suffix));
+            }
+            if matches!(self.peek()?.kind, TokenKind::Anchor(_)) {
+                let token = self.skip()?;
+                end_mark = token.end_mark;
+                if let TokenKind::Anchor(value) = token.kind {
+                    anchor = Some(value);
+                }
+            }
+        }
+
+        let tag = match tag {
+            None => None,
+            Some((handle, suffix)) if handle.is_empty() => Some(suffix),
+            Some((handle, suffix)) => {
+                match self
+                    .tag_directives
+                    .iter()
+                    .find(|(directive, _)| *directive == handle)
+                {
+                    Some((_, prefix)) => {
+                        let mut tag = prefix.clone();
+                        tag.extend_from_slice(&suffix);
+                        Some(tag)
+                    }
+                    None => {
+                        return Err(error(
+                            ErrorKind::Parser,
+                            Some((
+                                b"while parsing a node\0",
+                                start_mark,
+                            )),
+                            b"found undefined tag handle\0",
+                            tag_mark,
+                        ));
+                    }
+                }
+            }
+        };
+        let anchor =
+            anchor.map(|anchor| Anchor(anchor.into_boxed_slice()));
+        let tag = tag.map(|tag| Tag(tag.into_boxed_slice()));
+
+        let token = self.peek()?;
+        if indentless_sequence
+            && matches!(token.kind, TokenKind::BlockEntry)
+        {
+            end_mark = token.end_mark;
+            self.state = State::IndentlessSequenceEntry;
+            return Ok((
+                Event::SequenceStart(SequenceStart { anchor, tag }),
+                start_mark,
+                end_mark,
+            ));
+        }
+        if matches!(token.kind, TokenKind::Scalar { .. }) {
+            self.state = self.pop_state();
+            let token = self.skip()?;
+            let (value, style) = match token.kind {
+                TokenKind::Scalar { value, style } => (value, style),
+                _ => (Vec::new(), ScalarStyle::Plain),
+            };
+            return Ok((
+                Event::Scalar(Scalar {
+                    anchor,
+                    tag,
+                    value: value.into_boxed_slice(),
+                    style,
+                    repr: None,
+                }),
+                start_mark,
+                token.end_mark,
+            ));
+        }
+        let next_state = match token.kind {
+            TokenKind::FlowSequenceStart => {
+                Some(State::FlowSequenceFirstEntry)
+            }
+            TokenKind::FlowMappingStart => {
+                Some(State::FlowMappingFirstKey)
+            }
+            TokenKind::BlockSequenceStart if block => {
+                Some(State::BlockSequenceFirstEntry)
+            }
+            TokenKind::BlockMappingStart if block => {
+                Some(State::BlockMappingFirstKey)
+            }
+            _ => None,
+        };
+        if let Some(next_state) = next_state {
+            end_mark = token.end_mark;
+            let event = match next_state {
+                State::FlowSequenceFirstEntry
+                | State::BlockSequenceFirstEntry => {
+                    Event::SequenceStart(SequenceStart { anchor, tag })
+                }
+                _ => Event::MappingStart(MappingStart { anchor, tag }),
+            };
+            self.state = next_state;
+            return Ok((event, start_mark, end_mark));
+        }
+        if anchor.is_some() || tag.is_some() {
+            self.state = self.pop_state();
+            return Ok((
+                Event::Scalar(Scalar {
+                    anchor,
+                    tag,
+                    value: Box::default(),
+                    style: ScalarStyle::Plain,
+                    repr: None,
+                }),
+                start_mark,
+                end_mark,
+            ));
+        }
+        let context: &'static [u8] = if block {
+            b"while parsing a block node\0"
+        } else {
+            b"while parsing a flow node\0"
+        };
+        Err(error(
+            ErrorKind::Parser,
+            Some((context, start_mark)),
+            b"did not find expected node content\0",
+            token.start_mark,
+        ))
+    }
+
+    fn parse_block_sequence_entry(&mut self, first: bool) -> Parsed {
+        if first {
+            let token = self.skip()?;
+            self.marks.push(token.start_mark);
+        }
+        let token = self.peek()?;
+        if matches!(token.kind, TokenKind::BlockEntry) {
+            let mark = token.end_mark;
+            self.skip()?;
+            let token = self.peek()?;
+            if matches!(
+                token.kind,
+                TokenKind::BlockEntry | TokenKind::BlockEnd
+            ) {
+                self.state = State::BlockSequenceEntry;
+                Ok(empty_scalar(mark))
+            } else {
+                self.states.push(State::BlockSequenceEntry);
+                self.parse_node(true, false)
+            }
+        } else if matches!(token.kind, TokenKind::BlockEnd) {
+            self.state = self.pop_state();
+            self.pop_mark();
+            let token = self.skip()?;
+            Ok((Event::SequenceEnd, token.start_mark, token.end_mark))
+        } else {
+            let mark = token.start_mark;
+            let context_mark = self.pop_mark();
+            Err(error(
+                ErrorKind::Parser,
+                Some((
+                    b"while parsing a block collection\0",
+                    context_mark,
+                )),
+                b"did not find expected '-' indicator\0",
+                mark,
+            ))
+        }
+    }
+
+    fn parse_indentless_sequence_entry(&mut self) -> Parsed {
+        let token = self.peek()?;
+        if matches!(token.kind, TokenKind::BlockEntry) {
+            let mark = token.end_mark;
+            self.skip()?;
+            let token = self.peek()?;
+            if matches!(
+                token.kind,
+                TokenKind::BlockEntry
+                    | TokenKind::Key
+                    | TokenKind::Value
+                    | TokenKind::BlockEnd
+            ) {
+                self.state = State::IndentlessSequenceEntry;
+                Ok(empty_scalar(mark))
+            } else {
+                self.states.push(State::IndentlessSequenceEntry);
+                self.parse_node(true, false)
+            }
+        } else {
+            let mark = token.start_mark;
+            self.state = self.pop_state();
+            Ok((Event::SequenceEnd, mark, mark))
+        }
+    }
+
+    fn parse_block_mapping_key(&mut self, first: bool) -> Parsed {
+        if first {
+            let token = self.skip()?;
+            self.marks.push(token.start_mark);
+        }
+        let token = self.peek()?;
+        if matches!(token.kind, TokenKind::Key) {
+            let mark = token.end_mark;
+            self.skip()?;
+            let token = self.peek()?;
+            if matches!(
+                token.kind,
+                TokenKind::Key | TokenKind::Value | TokenKind::BlockEnd
+            ) {
+                self.state = State::BlockMappingValue;
+                Ok(empty_scalar(mark))
+            } else {
+                self.states.push(State::BlockMappingValue);
+                self.parse_node(true, true)
+            }
+        } else if matches!(token.kind, TokenKind::BlockEnd) {
+            self.state = self.pop_state();
+            self.pop_mark();
+            let token = self.skip()?;
+            Ok((Event::MappingEnd, token.start_mark, token.end_mark))
+        } else {
+            let mark = token.start_mark;
+            let context_mark = self.pop_mark();
+            Err(error(
+                ErrorKind::Parser,
+                Some((
+                    b"while parsing a block mapping\0",
+                    context_mark,
+                )),
+                b"did not find expected key\0",
+                mark,
+            ))
+        }
+    }
+
+    fn parse_block_mapping_value(&mut self) -> Parsed {
+        let token = self.peek()?;
+        if matches!(token.kind, TokenKind::Value) {
+            let mark = token.end_mark;
+            self.skip()?;
+            let token = self.peek()?;
+            if matches!(
+                token.kind,
+                TokenKind::Key | TokenKind::Value | TokenKind::BlockEnd
+            ) {
+                self.state = State::BlockMappingKey;
+                Ok(empty_scalar(mark))
+            } else {
+                self.states.push(State::BlockMappingKey);
+                self.parse_node(true, true)
+            }
+        } else {
+            let mark = token.start_mark;
+            self.state = State::BlockMappingKey;
+            Ok(empty_scalar(mark))
+        }
+    }
+
+    fn parse_flow_sequence_entry(&mut self, first: bool) -> Parsed {
+        if first {
+            let token = self.skip()?;
+            self.marks.push(token.start_mark);
+        }
+        let token = self.peek()?;
+        if !matches!(token.kind, TokenKind::FlowSequenceEnd) {
+            if !first {
+                if matches!(token.kind, TokenKind::FlowEntry) {
+                    self.skip()?;
+                } else {
+                    let mark = token.start_mark;
+                    let context_mark = self.pop_mark();
+                    return Err(error(
+                        ErrorKind::Parser,
+                        Some((
+                            b"while parsing a flow sequence\0",
+                            context_mark,
+                        )),
+                        b"did not find expected ',' or ']'\0",
+                        mark,
+                    ));
+                }
+            }
+            let token = self.peek()?;
+            if matches!(token.kind, TokenKind::Key) {
+                let token = self.skip()?;
+                self.state = State::FlowSequenceEntryMappingKey;
+                return Ok((
+                    Event::MappingStart(MappingStart {
+                        anchor: None,
+                        tag: None,
+                    }),
+                    token.start_mark,
+                    token.end_mark,
+                ));
+            } else if !matches!(token.kind, TokenKind::FlowSequenceEnd)
+            {
+                self.states.push(State::FlowSequenceEntry);
+                return self.parse_node(false, false);
+            }
+        }
+        self.state = self.pop_state();
+        self.pop_mark();
+        let token = self.skip()?;
+        Ok((Event::SequenceEnd, token.start_mark, token.end_mark))
+    }
+
+    fn parse_flow_sequence_entry_mapping_key(&mut self) -> Parsed {
+        let token = self.peek()?;
+        if matches!(
+            token.kind,
+            TokenKind::Value
+                | TokenKind::FlowEntry
+                | TokenKind::FlowSequenceEnd
+        ) {
+            let mark = token.end_mark;
+            self.skip()?;
+            self.state = State::FlowSequenceEntryMappingValue;
+            Ok(empty_scalar(mark))
+        } else {
+            self.states.push(State::FlowSequenceEntryMappingValue);
+            self.parse_node(false, false)
+        }
+    }
+
+    fn parse_flow_sequence_entry_mapping_value(&mut self) -> Parsed {
+        let token = self.peek()?;
+        if matches!(token.kind, TokenKind::Value) {
+            self.skip()?;
+            let token = self.peek()?;
+            if !matches!(
+                token.kind,
+                TokenKind::FlowEntry | TokenKind::FlowSequenceEnd
+            ) {
+                self.states.push(State::FlowSequenceEntryMappingEnd);
+                return self.parse_node(false, false);
+            }
+        }
+        let mark = self.peek()?.start_mark;
+        self.state = State::FlowSequenceEntryMappingEnd;
+        Ok(empty_scalar(mark))
+    }
+
+    fn parse_flow_sequence_entry_mapping_end(&mut self) -> Parsed {
+        let mark = self.peek()?.start_mark;
+        self.state = State::FlowSequenceEntry;
+        Ok((Event::MappingEnd, mark, mark))
+    }
+
+    fn parse_flow_mapping_key(&mut self, first: bool) -> Parsed {
+        if first {
+            let token = self.skip()?;
+            self.marks.push(token.start_mark);
+        }
+        let token = self.peek()?;
+        if !matches!(token.kind, TokenKind::FlowMappingEnd) {
+            if !first {
+                if matches!(token.kind, TokenKind::FlowEntry) {
+                    self.skip()?;
+                } else {
+                    let mark = token.start_mark;
+                    let context_mark = self.pop_mark();
+                    return Err(error(
+                        ErrorKind::Parser,
+                        Some((
+                            b"while parsing a flow mapping\0",
+                            context_mark,
+                        )),
+                        b"did not find expected ',' or '}'\0",
+                        mark,
+                    ));
+                }
+            }
+            let token = self.peek()?;
+            if matches!(token.kind, TokenKind::Key) {
+                self.skip()?;
+                let token = self.peek()?;
+                if matches!(
+                    token.kind,
+                    TokenKind::Value
+                        | TokenKind::FlowEntry
+                        | TokenKind::FlowMappingEnd
+                ) {
+                    let mark = token.start_mark;
+                    self.state = State::FlowMappingValue;
+                    return Ok(empty_scalar(mark));
+                }
+                self.states.push(State::FlowMappingValue);
+                return self.parse_node(false, false);
+            } else if !matches!(token.kind, TokenKind::FlowMappingEnd) {
+                self.states.push(State::FlowMappingEmptyValue);
+                return self.parse_node(false, false);
+            }
+        }
+        self.state = self.pop_state();
+        self.pop_mark();
+        let token = self.skip()?;
+        Ok((Event::MappingEnd, token.start_mark, token.end_mark))
+    }
+
+    fn parse_flow_mapping_value(&mut self, empty: bool) -> Parsed {
+        let token = self.peek()?;
+        if empty {
+            let mark = token.start_mark;
+            self.state = State::FlowMappingKey;
+            return Ok(empty_scalar(mark));
+        }
+        if matches!(token.kind, TokenKind::Value) {
+            self.skip()?;
+            let token = self.peek()?;
+            if !matches!(
+                token.kind,
+                TokenKind::FlowEntry | TokenKind::FlowMappingEnd
+            ) {
+                self.states.push(State::FlowMappingKey);
+                return self.parse_node(false, false);
+            }
+        }
+        let mark = self.peek()?.start_mark;
+        self.state = State::FlowMappingKey;
+        Ok(empty_scalar(mark))
+    }
+
+    fn process_directives(&mut self) -> Result<()> {
+        let mut version_seen = false;
+        loop {
+            let token = self.peek()?;
+            let mark = token.start_mark;
+            match &token.kind {
+                TokenKind::VersionDirective { major, minor } => {
+                    if version_seen {
+                        return Err(error(
+                            ErrorKind::Parser,
+                            None,
+                            b"found duplicate %YAML directive\0",
+                            mark,
+                        ));
+                    }
+                    if *major != 1 || *minor != 1 && *minor != 2 {
+                        return Err(error(
+                            ErrorKind::Parser,
+                            None,
+                            b"found incompatible YAML document\0",
+                            mark,
+                        ));
+                    }
+                    version_seen = true;
+                }
+                TokenKind::TagDirective { handle, prefix } => {
+                    let directive = (handle.clone(), prefix.clone());
+                    self.append_tag_directive(directive, false, mark)?;
+                }
+                _ => break,
+            }
+            self.skip()?;
+        }
+        let mark = self.peek()?.start_mark;
+        for (handle, prefix) in DEFAULT_TAG_DIRECTIVES {
+            let directive = (handle.to_vec(), prefix.to_vec());
+            self.append_tag_directive(directive, true, mark)?;
+        }
+        Ok(())
+    }
+
+    fn append_tag_directive(
+        &mut self,
+        directive: (Vec<u8>, Vec<u8>),
+        allow_duplicates: bool,
+        mark: Mark,
+    ) -> Result<()> {
+        if self
+            .tag_directives
+            .iter()
+            .any(|(handle, _)| *handle == directive.0)
+        {
+            if allow_duplicates {
+                return Ok(());
+            }
+            return Err(error(
+                ErrorKind::Parser,
+                None,
+                b"found duplicate %TAG directive\0",
+                mark,
+            ));
+        }
+        self.tag_directives.push(directive);
+        Ok(())
+    }
+}
+
+fn empty_scalar(mark: Mark) -> (Event<'static>, Mark, Mark) {
+    let scalar = Scalar {
+        anchor: None,
+        tag: None,
+        value: Box::default(),
+        style: ScalarStyle::Plain,
+        repr: None,
+    };
+    (Event::Scalar(scalar), mark, mark)
+}
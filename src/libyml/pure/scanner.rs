@@ -0,0 +1,1678 @@
+//! Tokenizer of the pure Rust backend.
+//!
+//! Turns the input into tokens the way the libyaml scanner does, including
+//! its bookkeeping of simple keys and block indentation.
+
+use super::error;
+use crate::{
+    lib::*,
+    libyml::{
+        error::{Error, ErrorKind, Mark, Result},
+        parser::ScalarStyle,
+    },
+};
+use alloc::{borrow::Cow, collections::VecDeque};
+use core::mem;
+
+#[derive(Debug)]
+pub(crate) enum TokenKind {
+    StreamStart,
+    StreamEnd,
+    VersionDirective { major: u32, minor: u32 },
+    TagDirective { handle: Vec<u8>, prefix: Vec<u8> },
+    DocumentStart,
+    DocumentEnd,
+    BlockSequenceStart,
+    BlockMappingStart,
+    BlockEnd,
+    FlowSequenceStart,
+    FlowSequenceEnd,
+    FlowMappingStart,
+    FlowMappingEnd,
+    BlockEntry,
+    FlowEntry,
+    Key,
+    Value,
+    Alias(Vec<u8>),
+    Anchor(Vec<u8>),
+    Tag { handle: Vec<u8>, suffix: Vec<u8> },
+    Scalar { value: Vec<u8>, style: ScalarStyle },
+}
+
+#[derive(Debug)]
+pub(crate) struct Token {
+    pub(crate) kind: TokenKind,
+    pub(crate) start_mark: Mark,
+    pub(crate) end_mark: Mark,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct SimpleKey {
+    possible: bool,
+    required: bool,
+    token_number: usize,
+    mark: Mark,
+}
+
+impl SimpleKey {
+    fn empty() -> Self {
+        SimpleKey {
+            possible: false,
+            required: false,
+            token_number: 0,
+            mark: Mark::new(0, 0, 0),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Scanner<'input> {
+    pub(crate) input: Cow<'input, [u8]>,
+    pos: usize,
+    line: u64,
+    column: u64,
+    input_checked: bool,
+    stream_start_produced: bool,
+    pub(crate) stream_end_produced: bool,
+    flow_level: usize,
+    tokens: VecDeque<Token>,
+    tokens_parsed: usize,
+    token_available: bool,
+    indent: i64,
+    indents: Vec<i64>,
+    simple_key_allowed: bool,
+    simple_keys: Vec<SimpleKey>,
+}
+
+impl<'input> Scanner<'input> {
+    pub(crate) fn new(input: Cow<'input, [u8]>) -> Self {
+        Scanner {
+            input,
+            pos: 0,
+            line: 0,
+            column: 0,
+            input_checked: false,
+            stream_start_produced: false,
+            stream_end_produced: false,
+            flow_level: 0,
+            tokens: VecDeque::new(),
+            tokens_parsed: 0,
+            token_available: false,
+            indent: 0,
+            indents: Vec::new(),
+            simple_key_allowed: false,
+            simple_keys: Vec::new(),
+        }
+    }
+
+    /// Returns the next token without consuming it.
+    pub(crate) fn peek_token(&mut self) -> Result<&Token> {
+        if !self.token_available {
+            self.fetch_more_tokens()?;
+        }
+        match self.tokens.front() {
+            Some(token) => Ok(token),
+            None => Err(self.scanner_error(
+                None,
+                b"no more tokens after the end of the stream\0",
+            )),
+        }
+    }
+
+    /// Consumes the token returned by the last call to `peek_token`.
+    pub(crate) fn skip_token(&mut self) -> Option<Token> {
+        let token = self.tokens.pop_front()?;
+        self.token_available = false;
+        self.tokens_parsed += 1;
+        if let TokenKind::StreamEnd = token.kind {
+            self.stream_end_produced = true;
+        }
+        Some(token)
+    }
+
+    fn mark(&self) -> Mark {
+        Mark::new(self.pos as u64, self.line, self.column)
+    }
+
+    fn scanner_error(
+        &self,
+        context: Option<(&'static [u8], Mark)>,
+        problem: &'static [u8],
+    ) -> Error {
+        error(ErrorKind::Scanner, context, problem, self.mark())
+    }
+
+    // Character classes, looking `k` bytes ahead of the current position.
+    // Reading past the end yields a nul byte, which counts as the end of
+    // the input.
+
+    fn byte(&self, k: usize) -> u8 {
+        self.input.get(self.pos + k).copied().unwrap_or(0)
+    }
+
+    fn check(&self, c: u8) -> bool {
+        self.byte(0) == c
+    }
+
+    fn check_at(&self, c: u8, k: usize) -> bool {
+        self.byte(k) == c
+    }
+
+    fn is_alpha(&self) -> bool {
+        let c = self.byte(0);
+        c.is_ascii_alphanumeric() || c == b'_' || c == b'-'
+    }
+
+    fn is_digit(&self) -> bool {
+        self.byte(0).is_ascii_digit()
+    }
+
+    fn is_hex_at(&self, k: usize) -> bool {
+        self.byte(k).is_ascii_hexdigit()
+    }
+
+    fn as_hex_at(&self, k: usize) -> u32 {
+        let c = self.byte(k);
+        match c {
+            b'A'..=b'F' => u32::from(c - b'A') + 10,
+            b'a'..=b'f' => u32::from(c - b'a') + 10,
+            _ => u32::from(c - b'0'),
+        }
+    }
+
+    fn is_bom(&self) -> bool {
+        self.check_at(0xEF, 0)
+            && self.check_at(0xBB, 1)
+            && self.check_at(0xBF, 2)
+    }
+
+    fn is_z_at(&self, k: usize) -> bool {
+        self.byte(k) == 0
+    }
+
+    fn is_blank_at(&self, k: usize) -> bool {
+        self.check_at(b' ', k) || self.check_at(b'\t', k)
+    }
+
+    fn is_blank(&self) -> bool {
+        self.is_blank_at(0)
+    }
+
+    fn is_break_at(&self, k: usize) -> bool {
+        self.check_at(b'\r', k)
+            || self.check_at(b'\n', k)
+            || self.check_at(0xC2, k) && self.check_at(0x85, k + 1)
+            || self.check_at(0xE2, k)
+                && self.check_at(0x80, k + 1)
+                && (self.check_at(0xA8, k + 2)
+                    || self.check_at(0xA9, k + 2))
+    }
+
+    fn is_break(&self) -> bool {
+        self.is_break_at(0)
+    }
+
+    fn is_breakz(&self) -> bool {
+        self.is_break_at(0) || self.is_z_at(0)
+    }
+
+    fn is_blankz_at(&self, k: usize) -> bool {
+        self.is_blank_at(k) || self.is_break_at(k) || self.is_z_at(k)
+    }
+
+    fn is_blankz(&self) -> bool {
+        self.is_blankz_at(0)
+    }
+
+    fn width(&self) -> usize {
+        utf8_width(self.byte(0)).max(1)
+    }
+
+    fn is_document_indicator(&self) -> bool {
+        self.column == 0
+            && (self.check_at(b'-', 0)
+                && self.check_at(b'-', 1)
+                && self.check_at(b'-', 2)
+                || self.check_at(b'.', 0)
+                    && self.check_at(b'.', 1)
+                    && self.check_at(b'.', 2))
+            && self.is_blankz_at(3)
+    }
+
+    // Movement over the input.
+
+    fn skip(&mut self) {
+        self.pos += self.width();
+        self.column += 1;
+    }
+
+    fn skip_line(&mut self) {
+        if self.check_at(b'\r', 0) && self.check_at(b'\n', 1) {
+            self.pos += 2;
+            self.line += 1;
+            self.column = 0;
+        } else if self.is_break() {
+            self.pos += self.width();
+            self.line += 1;
+            self.column = 0;
+        }
+    }
+
+    fn read(&mut self, string: &mut Vec<u8>) {
+        let width = self.width();
+        let end = (self.pos + width).min(self.input.len());
+        string.extend_from_slice(&self.input[self.pos..end]);
+        self.pos += width;
+        self.column += 1;
+    }
+
+    fn read_line(&mut self, string: &mut Vec<u8>) {
+        if self.check_at(b'\r', 0) && self.check_at(b'\n', 1) {
+            string.push(b'\n');
+            self.pos += 2;
+        } else if self.check(b'\r') || self.check(b'\n') {
+            string.push(b'\n');
+            self.pos += 1;
+        } else if self.check_at(0xC2, 0) && self.check_at(0x85, 1) {
+            string.push(b'\n');
+            self.pos += 2;
+        } else if self.is_break() {
+            string
+                .extend_from_slice(&self.input[self.pos..self.pos + 3]);
+            self.pos += 3;
+        } else {
+            return;
+        }
+        self.line += 1;
+        self.column = 0;
+    }
+
+    /// Rejects input that is not valid UTF-8 or that contains characters
+    /// YAML does not allow, before any token is produced.
+    fn check_input(&self) -> Result<()> {
+        let input = &*self.input;
+        let mut offset = 0;
+        while offset < input.len() {
+            let octet = input[offset];
+            let width = utf8_width(octet);
+            if width == 0 {
+                return Err(reader_error(
+                    b"invalid leading UTF-8 octet\0",
+                    offset,
+                ));
+            }
+            if offset + width > input.len() {
+                return Err(reader_error(
+                    b"incomplete UTF-8 octet sequence\0",
+                    offset,
+                ));
+            }
+            let mut value = u32::from(match width {
+                1 => octet & 0x7F,
+                2 => octet & 0x1F,
+                3 => octet & 0x0F,
+                _ => octet & 0x07,
+            });
+            for k in 1..width {
+                let octet = input[offset + k];
+                if octet & 0xC0 != 0x80 {
+                    return Err(reader_error(
+                        b"invalid trailing UTF-8 octet\0",
+                        offset + k,
+                    ));
+                }
+                value = (value << 6) + u32::from(octet & 0x3F);
+            }
+            if !(width == 1
+                || width == 2 && value >= 0x80
+                || width == 3 && value >= 0x800
+                || width == 4 && value >= 0x10000)
+            {
+                return Err(reader_error(
+                    b"invalid length of a UTF-8 sequence\0",
+                    offset,
+                ));
+            }
+            if (0xD800..=0xDFFF).contains(&value) || value > 0x10FFFF {
+                return Err(reader_error(
+                    b"invalid Unicode character\0",
+                    offset,
+                ));
+            }
+            if !(value == 0x09
+                || value == 0x0A
+                || value == 0x0D
+                || (0x20..=0x7E).contains(&value)
+                || value == 0x85
+                || (0xA0..=0xD7FF).contains(&value)
+                || (0xE000..=0xFFFD).contains(&value)
+                || (0x10000..=0x10FFFF).contains(&value))
+            {
+                return Err(reader_error(
+                    b"control characters are not allowed\0",
+                    offset,
+                ));
+            }
+            offset += width;
+        }
+        Ok(())
+    }
+
+    fn fetch_more_tokens(&mut self) -> Result<()> {
+        loop {
+            let mut need_more_tokens = false;
+            if self.tokens.is_empty() {
+                need_more_tokens = true;
+            } else {
+                self.stale_simple_keys()?;
+                for simple_key in &self.simple_keys {
+                    if simple_key.possible
+                        && simple_key.token_number == self.tokens_parsed
+                    {
+                        need_more_tokens = true;
+                        break;
+                    }
+                }
+            }
+            if !need_more_tokens {
+                break;
+            }
+            self.fetch_next_token()?;
+        }
+        self.token_available = true;
+        Ok(())
+    }
+
+    fn fetch_next_token(&mut self) -> Result<()> {
+        if !self.input_checked {
+            self.check_input()?;
+            self.input_checked = true;
+        }
+        if !self.stream_start_produced {
+            self.fetch_stream_start();
+            return Ok(());
+        }
+        self.scan_to_next_token();
+        self.stale_simple_keys()?;
+        self.unroll_indent(self.column as i64);
+        if self.is_z_at(0) {
+            return self.fetch_stream_end();
+        }
+        if self.column == 0 && self.check(b'%') {
+            return self.fetch_directive();
+        }
+        if self.is_document_indicator() {
+            return self.fetch_document_indicator(
+                if self.check(b'-') {
+                    TokenKind::DocumentStart
+                } else {
+                    TokenKind::DocumentEnd
+                },
+            );
+        }
+        match self.byte(0) {
+            b'[' => {
+                return self.fetch_flow_collection_start(
+                    TokenKind::FlowSequenceStart,
+                )
+            }
+            b'{' => {
+                return self.fetch_flow_collection_start(
+                    TokenKind::FlowMappingStart,
+                )
+            }
+            b']' => {
+                return self.fetch_flow_collection_end(
+                    TokenKind::FlowSequenceEnd,
+                )
+            }
+            b'}' => {
+                return self.fetch_flow_collection_end(
+                    TokenKind::FlowMappingEnd,
+                )
+            }
+            b',' => return self.fetch_flow_entry(),
+            _ => {}
+        }
+        if self.check(b'-') && self.is_blankz_at(1) {
+            return self.fetch_block_entry();
+        }
+        if self.check(b'?')
+            && (self.flow_level > 0 || self.is_blankz_at(1))
+        {
+            return self.fetch_key();
+        }
+        if self.check(b':')
+            && (self.flow_level > 0 || self.is_blankz_at(1))
+        {
+            return self.fetch_value();
+        }
+        match self.byte(0) {
+            b'*' => return self.fetch_anchor(true),
+            b'&' => return self.fetch_anchor(false),
+            b'!' => return self.fetch_tag(),
+            b'|' if self.flow_level == 0 => {
+                return self.fetch_block_scalar(true)
+            }
+            b'>' if self.flow_level == 0 => {
+                return self.fetch_block_scalar(false)
+            }
+            b'\'' => return self.fetch_flow_scalar(true),
+            b'"' => return self.fetch_flow_scalar(false),
+            _ => {}
+        }
+        if !(self.is_blankz()
+            || matches!(
+                self.byte(0),
+                b'-' | b'?'
+                    | b':'
+                    | b','
+                    | b'['
+                    | b']'
+                    | b'{'
+                    | b'}'
+                    | b'#'
+                    | b'&'
+                    | b'*'
+                    | b'!'
+                    | b'|'
+                    | b'>'
+                    | b'\''
+                    | b'"'
+                    | b'%'
+                    | b'@'
+                    | b'`'
+            ))
+            || self.check(b'-') && !self.is_blank_at(1)
+            || self.flow_level == 0
+                && (self.check(b'?') || self.check(b':'))
+                && !self.is_blankz_at(1)
+        {
+            return self.fetch_plain_scalar();
+        }
+        Err(self.scanner_error(
+            Some((b"while scanning for the next token\0", self.mark())),
+            b"found character that cannot start any token\0",
+        ))
+    }
+
+    fn stale_simple_keys(&mut self) -> Result<()> {
+        let mark = self.mark();
+        for simple_key in &mut self.simple_keys {
+            if simple_key.possible
+                && (simple_key.mark.line() < mark.line()
+                    || simple_key.mark.index() + 1024 < mark.index())
+            {
+                if simple_key.required {
+                    return Err(error(
+                        ErrorKind::Scanner,
+                        Some((
+                            b"while scanning a simple key\0",
+                            simple_key.mark,
+                        )),
+                        b"could not find expected ':'\0",
+                        mark,
+                    ));
+                }
+                simple_key.possible = false;
+            }
+        }
+        Ok(())
+    }
+
+    fn save_simple_key(&mut self) -> Result<()> {
+        let required =
+            self.flow_level == 0 && self.indent == self.column as i64;
+        if self.simple_key_allowed {
+            let simple_key = SimpleKey {
+                possible: true,
+                required,
+                token_number: self.tokens_parsed + self.tokens.len(),
+                mark: self.mark(),
+            };
+            self.remove_simple_key()?;
+            if let Some(last) = self.simple_keys.last_mut() {
+                *last = simple_key;
+            }
+        }
+        Ok(())
+    }
+
+    fn remove_simple_key(&mut self) -> Result<()> {
+        let mark = self.mark();
+        if let Some(simple_key) = self.simple_keys.last_mut() {
+            if simple_key.possible && simple_key.required {
+                return Err(error(
+                    ErrorKind::Scanner,
+                    Some((
+                        b"while scanning a simple key\0",
+                        simple_key.mark,
+                    )),
+                    b"could not find expected ':'\0",
+                    mark,
+                ));
+            }
+            simple_key.possible = false;
+        }
+        Ok(())
+    }
+
+    fn increase_flow_level(&mut self) {
+        self.simple_keys.push(SimpleKey::empty());
+        self.flow_level += 1;
+    }
+
+    fn decrease_flow_level(&mut self) {
+        if self.flow_level > 0 {
+            self.flow_level -= 1;
+            self.simple_keys.pop();
+        }
+    }
+
+    fn roll_indent(
+        &mut self,
+        column: i64,
+        number: Option<usize>,
+        kind: TokenKind,
+        mark: Mark,
+    ) {
+        if self.flow_level > 0 {
+            return;
+        }
+        if self.indent < column {
+            self.indents.push(self.indent);
+            self.indent = column;
+            let token = Token {
+                kind,
+                start_mark: mark,
+                end_mark: mark,
+            };
+            match number {
+                Some(number) => {
+                    self.tokens
+                        .insert(number - self.tokens_parsed, token);
+                }
+                None => self.tokens.push_back(token),
+            }
+        }
+    }
+
+    fn unroll_indent(&mut self, column: i64) {
+        if self.flow_level > 0 {
+            return;
+        }
+        while self.indent > column {
+            let mark = self.mark();
+            self.tokens.push_back(Token {
+                kind: TokenKind::BlockEnd,
+                start_mark: mark,
+                end_mark: mark,
+            });
+            self.indent = self.indents.pop().unwrap_or(-1);
+        }
+    }
+
+    fn push_token(&mut self, kind: TokenKind, start_mark: Mark) {
+        self.tokens.push_back(Token {
+            kind,
+            start_mark,
+            end_mark: self.mark(),
+        });
+    }
+
+    fn fetch_stream_start(&mut self) {
+        self.indent = -1;
+        self.simple_keys.push(SimpleKey::empty());
+        self.simple_key_allowed = true;
+        self.stream_start_produced = true;
+        let mark = self.mark();
+        self.push_token(TokenKind::StreamStart, mark);
+    }
+
+    fn fetch_stream_end(&mut self) -> Result<()> {
+        if self.column != 0 {
+            self.column = 0;
+            self.line += 1;
+        }
+        self.unroll_indent(-1);
+        self.remove_simple_key()?;
+        self.simple_key_allowed = false;
+        let mark = self.mark();
+        self.push_token(TokenKind::StreamEnd, mark);
+        Ok(())
+    }
+
+    fn fetch_directive(&mut self) -> Result<()> {
+        self.unroll_indent(-1);
+        self.remove_simple_key()?;
+        self.simple_key_allowed = false;
+        let token = self.scan_directive()?;
+        self.tokens.push_back(token);
+        Ok(())
+    }
+
+    fn fetch_document_indicator(
+        &mut self,
+        kind: TokenKind,
+    ) -> Result<()> {
+        self.unroll_indent(-1);
+        self.remove_simple_key()?;
+        self.simple_key_allowed = false;
+        let start_mark = self.mark();
+        self.skip();
+        self.skip();
+        self.skip();
+        self.push_token(kind, start_mark);
+        Ok(())
+    }
+
+    fn fetch_flow_collection_start(
+        &mut self,
+        kind: TokenKind,
+    ) -> Result<()> {
+        self.save_simple_key()?;
+        self.increase_flow_level();
+        self.simple_key_allowed = true;
+        let start_mark = self.mark();
+        self.skip();
+        self.push_token(kind, start_mark);
+        Ok(())
+    }
+
+    fn fetch_flow_collection_end(
+        &mut self,
+        kind: TokenKind,
+    ) -> Result<()> {
+        self.remove_simple_key()?;
+        self.decrease_flow_level();
+        self.simple_key_allowed = false;
+        let start_mark = self.mark();
+        self.skip();
+        self.push_token(kind, start_mark);
+        Ok(())
+    }
+
+    fn fetch_flow_entry(&mut self) -> Result<()> {
+        self.remove_simple_key()?;
+        self.simple_key_allowed = true;
+        let start_mark = self.mark();
+        self.skip();
+        self.push_token(TokenKind::FlowEntry, start_mark);
+        Ok(())
+    }
+
+    fn fetch_block_entry(&mut self) -> Result<()> {
+        if self.flow_level == 0 {
+            if !self.simple_key_allowed {
+                return Err(self.scanner_error(
+                    None,
+                    b"block sequence entries are not allowed in this context\0",
+                ));
+            }
+            self.roll_indent(
+                self.column as i64,
+                None,
+                TokenKind::BlockSequenceStart,
+                self.mark(),
+            );
+        }
+        self.remove_simple_key()?;
+        self.simple_key_allowed = true;
+        let start_mark = self.mark();
+        self.skip();
+        self.push_token(TokenKind::BlockEntry, start_mark);
+        Ok(())
+    }
+
+    fn fetch_key(&mut self) -> Result<()> {
+        if self.flow_level == 0 {
+            if !self.simple_key_allowed {
+                return Err(self.scanner_error(
+                    None,
+                    b"mapping keys are not allowed in this context\0",
+                ));
+            }
+            self.roll_indent(
+                self.column as i64,
+                None,
+                TokenKind::BlockMappingStart,
+                self.mark(),
+            );
+        }
+        self.remove_simple_key()?;
+        self.simple_key_allowed = self.flow_level == 0;
+        let start_mark = self.mark();
+        self.skip();
+        self.push_token(TokenKind::Key, start_mark);
+        Ok(())
+    }
+
+    fn fetch_value(&mut self) -> Result<()> {
+        let simple_key = self
+            .simple_keys
+            .last()
+            .copied()
+            .unwrap_or_else(SimpleKey::empty);
+        if simple_key.possible {
+            self.tokens.insert(
+                simple_key.token_number - self.tokens_parsed,
+                Token {
+                    kind: TokenKind::Key,
+                    start_mark: simple_key.mark,
+                    end_mark: simple_key.mark,
+                },
+            );
+            self.roll_indent(
+                simple_key.mark.column() as i64,
+                Some(simple_key.token_number),
+                TokenKind::BlockMappingStart,
+                simple_key.mark,
+            );
+            if let Some(last) = self.simple_keys.last_mut() {
+                last.possible = false;
+            }
+            self.simple_key_allowed = false;
+        } else {
+            if self.flow_level == 0 {
+                if !self.simple_key_allowed {
+                    return Err(self.scanner_error(
+                        None,
+                        b"mapping values are not allowed in this context\0",
+                    ));
+                }
+                self.roll_indent(
+                    self.column as i64,
+                    None,
+                    TokenKind::BlockMappingStart,
+                    self.mark(),
+                );
+            }
+            self.simple_key_allowed = self.flow_level == 0;
+        }
+        let start_mark = self.mark();
+        self.skip();
+        self.push_token(TokenKind::Value, start_mark);
+        Ok(())
+    }
+
+    fn fetch_anchor(&mut self, alias: bool) -> Result<()> {
+        self.save_simple_key()?;
+        self.simple_key_allowed = false;
+        let token = self.scan_anchor(alias)?;
+        self.tokens.push_back(token);
+        Ok(())
+    }
+
+    fn fetch_tag(&mut self) -> Result<()> {
+        self.save_simple_key()?;
+        self.simple_key_allowed = false;
+        let token = self.scan_tag()?;
+        self.tokens.push_back(token);
+        Ok(())
+    }
+
+    fn fetch_block_scalar(&mut self, literal: bool) -> Result<()> {
+        self.remove_simple_key()?;
+        self.simple_key_allowed = true;
+        let token = self.scan_block_scalar(literal)?;
+        self.tokens.push_back(token);
+        Ok(())
+    }
+
+    fn fetch_flow_scalar(&mut self, single: bool) -> Result<()> {
+        self.save_simple_key()?;
+        self.simple_key_allowed = false;
+        let token = self.scan_flow_scalar(single)?;
+        self.tokens.push_back(token);
+        Ok(())
+    }
+
+    fn fetch_plain_scalar(&mut self) -> Result<()> {
+        self.save_simple_key()?;
+        self.simple_key_allowed = false;
+        let token = self.scan_plain_scalar()?;
+        self.tokens.push_back(token);
+        Ok(())
+    }
+
+    fn scan_to_next_token(&mut self) {
+        loop {
+            if self.column == 0 && self.is_bom() {
+                self.skip();
+            }
+            while self.check(b' ')
+                || (self.flow_level > 0 || !self.simple_key_allowed)
+                    && self.check(b'\t')
+            {
+                self.skip();
+            }
+            if self.check(b'#') {
+                while !self.is_breakz() {
+                    self.skip();
+                }
+            }
+            if !self.is_break() {
+                break;
+            }
+            self.skip_line();
+            if self.flow_level == 0 {
+                self.simple_key_allowed = true;
+            }
+        }
+    }
+
+    fn scan_directive(&mut self) -> Result<Token> {
+        const CONTEXT: &[u8] = b"while scanning a directive\0";
+        let start_mark = self.mark();
+        self.skip();
+        let name = self.scan_directive_name(start_mark)?;
+        let kind = if name == b"YAML" {
+            let (major, minor) =
+                self.scan_version_directive_value(start_mark)?;
+            TokenKind::VersionDirective { major, minor }
+        } else if name == b"TAG" {
+            let (handle, prefix) =
+                self.scan_tag_directive_value(start_mark)?;
+            TokenKind::TagDirective { handle, prefix }
+        } else {
+            return Err(self.scanner_error(
+                Some((CONTEXT, start_mark)),
+                b"found unknown directive name\0",
+            ));
+        };
+        let end_mark = self.mark();
+        while self.is_blank() {
+            self.skip();
+        }
+        if self.check(b'#') {
+            while !self.is_breakz() {
+                self.skip();
+            }
+        }
+        if !self.is_breakz() {
+            return Err(self.scanner_error(
+                Some((CONTEXT, start_mark)),
+                b"did not find expected comment or line break\0",
+            ));
+        }
+        self.skip_line();
+        Ok(Token {
+            kind,
+            start_mark,
+            end_mark,
+        })
+    }
+
+    fn scan_directive_name(
+        &mut self,
+        start_mark: Mark,
+    ) -> Result<Vec<u8>> {
+        const CONTEXT: &[u8] = b"while scanning a directive\0";
+        let mut name = Vec::new();
+        while self.is_alpha() {
+            self.read(&mut name);
+        }
+        if name.is_empty() {
+            return Err(self.scanner_error(
+                Some((CONTEXT, start_mark)),
+                b"could not find expected directive name\0",
+            ));
+        }
+        if !self.is_blankz() {
+            return Err(self.scanner_error(
+                Some((CONTEXT, start_mark)),
+                b"found unexpected non-alphabetical character\0",
+            ));
+        }
+        Ok(name)
+    }
+
+    fn scan_version_directive_value(
+        &mut self,
+        start_mark: Mark,
+    ) -> Result<(u32, u32)> {
+        while self.is_blank() {
+            self.skip();
+        }
+        let major = self.scan_version_directive_number(start_mark)?;
+        if !self.check(b'.') {
+            return Err(self.scanner_error(
+                Some((
+                    b"while scanning a %YAML directive\0",
+                    start_mark,
+                )),
+                b"did not find expected digit or '.' character\0",
+            ));
+        }
+        self.skip();
+        let minor = self.scan_version_directive_number(start_mark)?;
+        Ok((major, minor))
+    }
+
+    fn scan_version_directive_number(
+        &mut self,
+        start_mark: Mark,
+    ) -> Result<u32> {
+        const CONTEXT: &[u8] = b"while scanning a %YAML directive\0";
+        let mut value: u32 = 0;
+        let mut length = 0;
+        while self.is_digit() {
+            length += 1;
+            if length > 9 {
+                return Err(self.scanner_error(
+                    Some((CONTEXT, start_mark)),
+                    b"found extremely long version number\0",
+                ));
+            }
+            value = value * 10 + u32::from(self.byte(0) - b'0');
+            self.skip();
+        }
+        if length == 0 {
+            return Err(self.scanner_error(
+                Some((CONTEXT, start_mark)),
+                b"did not find expected version number\0",
+            ));
+        }
+        Ok(value)
+    }
+
+    fn scan_tag_directive_value(
+        &mut self,
+        start_mark: Mark,
+    ) -> Result<(Vec<u8>, Vec<u8>)> {
+        const CONTEXT: &[u8] = b"while scanning a %TAG directive\0";
+        while self.is_blank() {
+            self.skip();
+        }
+        let handle = self.scan_tag_handle(true, start_mark)?;
+        if !self.is_blank() {
+            return Err(self.scanner_error(
+                Some((CONTEXT, start_mark)),
+                b"did not find expected whitespace\0",
+            ));
+        }
+        while self.is_blank() {
+            self.skip();
+        }
+        let prefix = self.scan_tag_uri(true, true, None, start_mark)?;
+        if !self.is_blankz() {
+            return Err(self.scanner_error(
+                Some((CONTEXT, start_mark)),
+                b"did not find expected whitespace or line break\0",
+            ));
+        }
+        Ok((handle, prefix))
+    }
+
+    fn scan_anchor(&mut self, alias: bool) -> Result<Token> {
+        let start_mark = self.mark();
+        self.skip();
+        let mut value = Vec::new();
+        while self.is_alpha() {
+            self.read(&mut value);
+        }
+        if value.is_empty()
+            || !(self.is_blankz()
+                || matches!(
+                    self.byte(0),
+                    b'?' | b':'
+                        | b','
+                        | b']'
+                        | b'}'
+                        | b'%'
+                        | b'@'
+                        | b'`'
+                ))
+        {
+            let context: &'static [u8] = if alias {
+                b"while scanning an alias\0"
+            } else {
+                b"while scanning an anchor\0"
+            };
+            return Err(self.scanner_error(
+                Some((context, start_mark)),
+                b"did not find expected alphabetic or numeric character\0",
+            ));
+        }
+        Ok(Token {
+            kind: if alias {
+                TokenKind::Alias(value)
+            } else {
+                TokenKind::Anchor(value)
+            },
+            start_mark,
+            end_mark: self.mark(),
+        })
+    }
+
+    fn scan_tag(&mut self) -> Result<Token> {
+        const CONTEXT: &[u8] = b"while scanning a tag\0";
+        let start_mark = self.mark();
+        let mut handle;
+        let mut suffix;
+        if self.check_at(b'<', 1) {
+            handle = Vec::new();
+            self.skip();
+            self.skip();
+            suffix =
+                self.scan_tag_uri(true, false, None, start_mark)?;
+            if !self.check(b'>') {
+                return Err(self.scanner_error(
+                    Some((CONTEXT, start_mark)),
+                    b"did not find the expected '>'\0",
+                ));
+            }
+            self.skip();
+        } else {
+            handle = self.scan_tag_handle(false, start_mark)?;
+            if handle.len() > 1 && handle.last() == Some(&b'!') {
+                suffix =
+                    self.scan_tag_uri(false, false, None, start_mark)?;
+            } else {
+                suffix = self.scan_tag_uri(
+                    false,
+                    false,
+                    Some(&handle),
+                    start_mark,
+                )?;
+                handle = b"!".to_vec();
+                if suffix.is_empty() {
+                    mem::swap(&mut handle, &mut suffix);
+                }
+            }
+        }
+        if !self.is_blankz()
+            && (self.flow_level == 0 || !self.check(b','))
+        {
+            return Err(self.scanner_error(
+                Some((CONTEXT, start_mark)),
+                b"did not find expected whitespace or line break\0",
+            ));
+        }
+        Ok(Token {
+            kind: TokenKind::Tag { handle, suffix },
+            start_mark,
+            end_mark: self.mark(),
+        })
+    }
+
+    fn scan_tag_handle(
+        &mut self,
+        directive: bool,
+        start_mark: Mark,
+    ) -> Result<Vec<u8>> {
+        if !self.check(b'!') {
+            let context: &'static [u8] = if directive {
+                b"while scanning a tag directive\0"
+            } else {
+                b"while scanning a tag\0"
+            };
+            return Err(self.scanner_error(
+                Some((context, start_mark)),
+                b"did not find expected '!'\0",
+            ));
+        }
+        let mut handle = Vec::new();
+        self.read(&mut handle);
+        while self.is_alpha() {
+            self.read(&mut handle);
+        }
+        if self.check(b'!') {
+            self.read(&mut handle);
+        } else if directive && handle != b"!" {
+            return Err(self.scanner_error(
+                Some((b"while parsing a tag directive\0", start_mark)),
+                b"did not find expected '!'\0",
+            ));
+        }
+        Ok(handle)
+    }
+
+    fn scan_tag_uri(
+        &mut self,
+        uri_char: bool,
+        directive: bool,
+        head: Option<&[u8]>,
+        start_mark: Mark,
+    ) -> Result<Vec<u8>> {
+        let mut length = head.map_or(0, <[u8]>::len);
+        let mut uri = Vec::new();
+        if let Some(head) = head {
+            if head.len() > 1 {
+                uri.extend_from_slice(&head[1..]);
+            }
+        }
+        while self.is_alpha()
+            || matches!(
+                self.byte(0),
+                b';' | b'/'
+                    | b'?'
+                    | b':'
+                    | b'@'
+                    | b'&'
+                    | b'='
+                    | b'+'
+                    | b'$'
+                    | b'.'
+                    | b'%'
+                    | b'!'
+                    | b'~'
+                    | b'*'
+                    | b'\''
+                    | b'('
+                    | b')'
+            )
+            || uri_char && matches!(self.byte(0), b',' | b'[' | b']')
+        {
+            if self.check(b'%') {
+                self.scan_uri_escapes(directive, start_mark, &mut uri)?;
+            } else {
+                self.read(&mut uri);
+            }
+            length += 1;
+        }
+        if length == 0 {
+            return Err(self.scanner_error(
+                Some((tag_context(directive), start_mark)),
+                b"did not find expected tag URI\0",
+            ));
+        }
+        Ok(uri)
+    }
+
+    fn scan_uri_escapes(
+        &mut self,
+        directive: bool,
+        start_mark: Mark,
+        uri: &mut Vec<u8>,
+    ) -> Result<()> {
+        let mut width = 0;
+        loop {
+            if !(self.check(b'%')
+                && self.is_hex_at(1)
+                && self.is_hex_at(2))
+            {
+                return Err(self.scanner_error(
+                    Some((tag_context(directive), start_mark)),
+                    b"did not find URI escaped octet\0",
+                ));
+            }
+            let octet =
+                ((self.as_hex_at(1) << 4) + self.as_hex_at(2)) as u8;
+            if width == 0 {
+                width = utf8_width(octet);
+                if width == 0 {
+                    return Err(self.scanner_error(
+                        Some((tag_context(directive), start_mark)),
+                        b"found an incorrect leading UTF-8 octet\0",
+                    ));
+                }
+            } else if octet & 0xC0 != 0x80 {
+                return Err(self.scanner_error(
+                    Some((tag_context(directive), start_mark)),
+                    b"found an incorrect trailing UTF-8 octet\0",
+                ));
+            }
+            uri.push(octet);
+            self.skip();
+            self.skip();
+            self.skip();
+            width -= 1;
+            if width == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    fn scan_block_scalar(&mut self, literal: bool) -> Result<Token> {
+        const CONTEXT: &[u8] = b"while scanning a block scalar\0";
+        let start_mark = self.mark();
+        self.skip();
+        let mut chomping = 0;
+        let mut increment = 0;
+        if self.check(b'+') || self.check(b'-') {
+            chomping = if self.check(b'+') { 1 } else { -1 };
+            self.skip();
+            if self.is_digit() {
+                if self.check(b'0') {
+                    return Err(self.scanner_error(
+                        Some((CONTEXT, start_mark)),
+                        b"found an indentation indicator equal to 0\0",
+                    ));
+                }
+                increment = i64::from(self.byte(0) - b'0');
+                self.skip();
+            }
+        } else if self.is_digit() {
+            if self.check(b'0') {
+                return Err(self.scanner_error(
+                    Some((CONTEXT, start_mark)),
+                    b"found an indentation indicator equal to 0\0",
+                ));
+            }
+            increment = i64::from(self.byte(0) - b'0');
+            self.skip();
+            if self.check(b'+') || self.check(b'-') {
+                chomping = if self.check(b'+') { 1 } else { -1 };
+                self.skip();
+            }
+        }
+        while self.is_blank() {
+            self.skip();
+        }
+        if self.check(b'#') {
+            while !self.is_breakz() {
+                self.skip();
+            }
+        }
+        if !self.is_breakz() {
+            return Err(self.scanner_error(
+                Some((CONTEXT, start_mark)),
+                b"did not find expected comment or line break\0",
+            ));
+        }
+        self.skip_line();
+        let mut end_mark = self.mark();
+        let mut indent = 0;
+        if increment != 0 {
+            indent = if self.indent >= 0 {
+                self.indent + increment
+            } else {
+                increment
+            };
+        }
+        let mut string = Vec::new();
+        let mut leading_break = Vec::new();
+        let mut trailing_breaks = Vec::new();
+        let mut leading_blank = false;
+        self.scan_block_scalar_breaks(
+            &mut indent,
+            &mut trailing_breaks,
+            start_mark,
+            &mut end_mark,
+        )?;
+        while self.column as i64 == indent && !self.is_z_at(0) {
+            let trailing_blank = self.is_blank();
+            if !literal
+                && leading_break.first() == Some(&b'\n')
+                && !leading_blank
+                && !trailing_blank
+            {
+                if trailing_breaks.is_empty() {
+                    string.push(b' ');
+                }
+                leading_break.clear();
+            } else {
+                string.append(&mut leading_break);
+            }
+            string.append(&mut trailing_breaks);
+            leading_blank = self.is_blank();
+            while !self.is_breakz() {
+                self.read(&mut string);
+            }
+            self.read_line(&mut leading_break);
+            self.scan_block_scalar_breaks(
+                &mut indent,
+                &mut trailing_breaks,
+                start_mark,
+                &mut end_mark,
+            )?;
+        }
+        if chomping != -1 {
+            string.append(&mut leading_break);
+        }
+        if chomping == 1 {
+            string.append(&mut trailing_breaks);
+        }
+        Ok(Token {
+            kind: TokenKind::Scalar {
+                value: string,
+                style: if literal {
+                    ScalarStyle::Literal
+                } else {
+                    ScalarStyle::Folded
+                },
+            },
+            start_mark,
+            end_mark,
+        })
+    }
+
+    fn scan_block_scalar_breaks(
+        &mut self,
+        indent: &mut i64,
+        breaks: &mut Vec<u8>,
+        start_mark: Mark,
+        end_mark: &mut Mark,
+    ) -> Result<()> {
+        let mut max_indent = 0;
+        *end_mark = self.mark();
+        loop {
+            while (*indent == 0 || (self.column as i64) < *indent)
+                && self.check(b' ')
+            {
+                self.skip();
+            }
+            if self.column as i64 > max_indent {
+                max_indent = self.column as i64;
+            }
+            if (*indent == 0 || (self.column as i64) < *indent)
+                && self.check(b'\t')
+            {
+                return Err(self.scanner_error(
+                    Some((b"while scanning a block scalar\0", start_mark)),
+                    b"found a tab character where an indentation space is expected\0",
+                ));
+            }
+            if !self.is_break() {
+                break;
+            }
+            self.read_line(breaks);
+            *end_mark = self.mark();
+        }
+        if *indent == 0 {
+            *indent = max_indent.max(self.indent + 1).max(1);
+        }
+        Ok(())
+    }
+
+    fn scan_flow_scalar(&mut self, single: bool) -> Result<Token> {
+        const CONTEXT: &[u8] = b"while scanning a quoted scalar\0";
+        const ESCAPE_CONTEXT: &[u8] =
+            b"while parsing a quoted scalar\0";
+        let quote = if single { b'\'' } else { b'"' };
+        let start_mark = self.mark();
+        self.skip();
+        let mut string = Vec::new();
+        let mut leading_break = Vec::new();
+        let mut trailing_breaks = Vec::new();
+        let mut whitespaces = Vec::new();
+        loop {
+            if self.is_document_indicator() {
+                return Err(self.scanner_error(
+                    Some((CONTEXT, start_mark)),
+                    b"found unexpected document indicator\0",
+                ));
+            }
+            if self.is_z_at(0) {
+                return Err(self.scanner_error(
+                    Some((CONTEXT, start_mark)),
+                    b"found unexpected end of stream\0",
+                ));
+            }
+            let mut leading_blanks = false;
+            while !self.is_blankz() {
+                if single
+                    && self.check(b'\'')
+                    && self.check_at(b'\'', 1)
+                {
+                    string.push(b'\'');
+                    self.skip();
+                    self.skip();
+                } else if self.check(quote) {
+                    break;
+                } else if !single
+                    && self.check(b'\\')
+                    && self.is_break_at(1)
+                {
+                    self.skip();
+                    self.skip_line();
+                    leading_blanks = true;
+                    break;
+                } else if !single && self.check(b'\\') {
+                    let mut code_length = 0;
+                    match self.byte(1) {
+                        b'0' => string.push(b'\0'),
+                        b'a' => string.push(b'\x07'),
+                        b'b' => string.push(b'\x08'),
+                        b't' | b'\t' => string.push(b'\t'),
+                        b'n' => string.push(b'\n'),
+                        b'v' => string.push(b'\x0B'),
+                        b'f' => string.push(b'\x0C'),
+                        b'r' => string.push(b'\r'),
+                        b'e' => string.push(b'\x1B'),
+                        b' ' => string.push(b' '),
+                        b'"' => string.push(b'"'),
+                        b'/' => string.push(b'/'),
+                        b'\\' => string.push(b'\\'),
+                        b'N' => string.extend_from_slice(b"\xC2\x85"),
+                        b'_' => string.extend_from_slice(b"\xC2\xA0"),
+                        b'L' => {
+                            string.extend_from_slice(b"\xE2\x80\xA8")
+                        }
+                        b'P' => {
+                            string.extend_from_slice(b"\xE2\x80\xA9")
+                        }
+                        b'x' => code_length = 2,
+                        b'u' => code_length = 4,
+                        b'U' => code_length = 8,
+                        _ => {
+                            return Err(self.scanner_error(
+                                Some((ESCAPE_CONTEXT, start_mark)),
+                                b"found unknown escape character\0",
+                            ));
+                        }
+                    }
+                    self.skip();
+                    self.skip();
+                    if code_length != 0 {
+                        let mut value: u32 = 0;
+                        for k in 0..code_length {
+                            if !self.is_hex_at(k) {
+                                return Err(self.scanner_error(
+                                    Some((ESCAPE_CONTEXT, start_mark)),
+                                    b"did not find expected hexadecimal number\0",
+                                ));
+                            }
+                            value = (value << 4) + self.as_hex_at(k);
+                        }
+                        let c = match char::from_u32(value) {
+                            Some(c) => c,
+                            None => {
+                                return Err(self.scanner_error(
+                                    Some((ESCAPE_CONTEXT, start_mark)),
+                                    b"found invalid Unicode character escape code\0",
+                                ));
+                            }
+                        };
+                        let mut buffer = [0; 4];
+                        string.extend_from_slice(
+                            c.encode_utf8(&mut buffer).as_bytes(),
+                        );
+                        for _ in 0..code_length {
+                            self.skip();
+                        }
+                    }
+                } else {
+                    self.read(&mut string);
+                }
+            }
+            if self.check(quote) {
+                break;
+            }
+            while self.is_blank() || self.is_break() {
+                if self.is_blank() {
+                    if leading_blanks {
+                        self.skip();
+                    } else {
+                        self.read(&mut whitespaces);
+                    }
+                } else if leading_blanks {
+                    self.read_line(&mut trailing_breaks);
+                } else {
+                    whitespaces.clear();
+                    self.read_line(&mut leading_break);
+                    leading_blanks = true;
+                }
+            }
+            if leading_blanks {
+                fold_breaks(
+                    &mut string,
+                    &mut leading_break,
+                    &mut trailing_breaks,
+                );
+            } else {
+                string.append(&mut whitespaces);
+            }
+        }
+        self.skip();
+        Ok(Token {
+            kind: TokenKind::Scalar {
+                value: string,
+                style: if single {
+                    ScalarStyle::SingleQuoted
+                } else {
+                    ScalarStyle::DoubleQuoted
+                },
+            },
+            start_mark,
+            end_mark: self.mark(),
+        })
+    }
+
+    fn scan_plain_scalar(&mut self) -> Result<Token> {
+        const CONTEXT: &[u8] = b"while scanning a plain scalar\0";
+        let mut string = Vec::new();
+        let mut leading_break = Vec::new();
+        let mut trailing_breaks = Vec::new();
+        let mut whitespaces = Vec::new();
+        let mut leading_blanks = false;
+        let indent = self.indent + 1;
+        let start_mark = self.mark();
+        let mut end_mark = start_mark;
+        loop {
+            if self.is_document_indicator() || self.check(b'#') {
+                break;
+            }
+            while !self.is_blankz() {
+                if self.flow_level > 0
+                    && self.check(b':')
+                    && matches!(
+                        self.byte(1),
+                        b',' | b'?' | b'[' | b']' | b'{' | b'}'
+                    )
+                {
+                    return Err(self.scanner_error(
+                        Some((CONTEXT, start_mark)),
+                        b"found unexpected ':'\0",
+                    ));
+                }
+                if self.check(b':') && self.is_blankz_at(1)
+                    || self.flow_level > 0
+                        && matches!(
+                            self.byte(0),
+                            b',' | b'[' | b']' | b'{' | b'}'
+                        )
+                {
+                    break;
+                }
+                if leading_blanks || !whitespaces.is_empty() {
+                    if leading_blanks {
+                        fold_breaks(
+                            &mut string,
+                            &mut leading_break,
+                            &mut trailing_breaks,
+                        );
+                        leading_blanks = false;
+                    } else {
+                        string.append(&mut whitespaces);
+                    }
+                }
+                self.read(&mut string);
+                end_mark = self.mark();
+            }
+            if !(self.is_blank() || self.is_break()) {
+                break;
+            }
+            while self.is_blank() || self.is_break() {
+                if self.is_blank() {
+                    if leading_blanks
+                        && (self.column as i64) < indent
+                        && self.check(b'\t')
+                    {
+                        return Err(self.scanner_error(
+                            Some((CONTEXT, start_mark)),
+                            b"found a tab character that violates indentation\0",
+                        ));
+                    }
+                    if leading_blanks {
+                        self.skip();
+                    } else {
+                        self.read(&mut whitespaces);
+                    }
+                } else if leading_blanks {
+                    self.read_line(&mut trailing_breaks);
+                } else {
+                    whitespaces.clear();
+                    self.read_line(&mut leading_break);
+                    leading_blanks = true;
+                }
+            }
+            if self.flow_level == 0 && (self.column as i64) < indent {
+                break;
+            }
+        }
+        if leading_blanks {
+            self.simple_key_allowed = true;
+        }
+        Ok(Token {
+            kind: TokenKind::Scalar {
+                value: string,
+                style: ScalarStyle::Plain,
+            },
+            start_mark,
+            end_mark,
+        })
+    }
+}
+
+/// Joins the line breaks collected between two lines of a flow or plain
+/// scalar: a single line feed folds into a space, more are kept minus one.
+fn fold_breaks(
+    string: &mut Vec<u8>,
+    leading_break: &mut Vec<u8>,
+    trailing_breaks: &mut Vec<u8>,
+) {
+    if leading_break.first() == Some(&b'\n') {
+        if trailing_breaks.is_empty() {
+            string.push(b' ');
+        } else {
+            string.append(trailing_breaks);
+        }
+        leading_break.clear();
+    } else {
+        string.append(leading_break);
+        string.append(trailing_breaks);
+    }
+}
+
+fn tag_context(directive: bool) -> &'static [u8] {
+    if directive {
+        b"while parsing a %TAG directive\0"
+    } else {
+        b"while parsing a tag\0"
+    }
+}
+
+/// Returns the length of the UTF-8 sequence starting with `octet`, or 0 if
+/// it cannot start one.
+pub(crate) fn utf8_width(octet: u8) -> usize {
+    if octet & 0x80 == 0x00 {
+        1
+    } else if octet & 0xE0 == 0xC0 {
+        2
+    } else if octet & 0xF0 == 0xE0 {
+        3
+    } else if octet & 0xF8 == 0xF0 {
+        4
+    } else {
+        0
+    }
+}
+
+fn reader_error(problem: &'static [u8], offset: usize) -> Error {
+    let mut error =
+        error(ErrorKind::Reader, None, problem, Mark::new(0, 0, 0));
+    error.problem_offset = offset as u64;
+    error
+}
@@ -1,4 +1,5 @@
-use std::{
+use crate::lib::*;
+use core::{
     fmt::{self, Debug, Display, Write as _},
     marker::PhantomData,
     ptr::NonNull,
@@ -23,7 +24,7 @@ impl Display for CStrError {
     }
 }
 
-impl std::error::Error for CStrError {}
+impl StdError for CStrError {}
 
 #[derive(Copy, Clone)]
 /// Struct representing a C string.
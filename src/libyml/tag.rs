@@ -1,9 +1,9 @@
-use crate::libyml::safe_cstr;
-use memchr::memchr;
-use std::{
+use crate::{lib::*, libyml::safe_cstr};
+use core::{
     fmt::{self, Debug, Display},
     ops::Deref,
 };
+use memchr::memchr;
 
 /// Custom error type for Tag operations.
 #[derive(Clone, Copy, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
@@ -24,7 +24,7 @@ impl Display for TagFormatError {
     }
 }
 
-impl std::error::Error for TagFormatError {}
+impl StdError for TagFormatError {}
 
 /// Represents a tag in a YAML document.
 /// A tag specifies the data type or semantic meaning of a value.
@@ -44,6 +44,19 @@ impl Tag {
     /// The float tag, representing a floating-point value.
     pub const FLOAT: &'static str = "tag:yaml.org,2002:float";
 
+    /// The str tag, representing a string value.
+    pub const STR: &'static str = "tag:yaml.org,2002:str";
+
+    /// The seq tag, representing a sequence.
+    pub const SEQ: &'static str = "tag:yaml.org,2002:seq";
+
+    /// The map tag, representing a mapping.
+    pub const MAP: &'static str = "tag:yaml.org,2002:map";
+
+    /// The prefix shared by the tags of the YAML core schema, which a
+    /// document writes as `!!`.
+    pub const CORE_PREFIX: &'static str = "tag:yaml.org,2002:";
+
     /// Checks if the tag starts with the given prefix.
     ///
     /// # Arguments
@@ -1,4 +1,5 @@
-use std::{
+use crate::lib::*;
+use core::{
     marker::PhantomData,
     mem::{self, MaybeUninit},
     ops::Deref,
@@ -0,0 +1,519 @@
+//! Style checks for YAML text in the spirit of `yamllint`.
+//!
+//! [`lint`] runs a set of rules over the source text of a YAML stream and
+//! returns a [`Problem`] for every place that breaks one, with its location
+//! and the byte range of the offending text. Rules look at both the raw
+//! lines and the parser events, so they catch things a [`Value`] no longer
+//! shows, such as a key written twice in the same mapping or a `yes` that
+//! YAML 1.1 tools read as a boolean.
+//!
+//! The rules, named as in `yamllint`, are:
+//!
+//! - `trailing-spaces`: spaces or tabs at the end of a line.
+//! - `line-length`: lines longer than a maximum, except lines holding a
+//!   single word such as a long URL.
+//! - `indentation`: nested block collections indented by a different
+//!   number of spaces than the rest of the stream, or than configured. A
+//!   block sequence may be written at the same indentation as the key of
+//!   its mapping.
+//! - `key-duplicates`: a scalar key that appears twice in a mapping, other
+//!   than the merge key `<<`.
+//! - `truthy`: plain scalars that YAML 1.1 reads as booleans, like `yes`,
+//!   `Off` or `TRUE`, other than `true` and `false`.
+//! - `document-start` and `document-end`: a missing or forbidden `---` or
+//!   `...` marker.
+//!
+//! Input that is not valid YAML gets a single `syntax` problem at the
+//! first error; the rules that need parser events do not look past it.
+//!
+//! [`Value`]: crate::Value
+
+use crate::{
+    lib::*,
+    libyml::{
+        error::Mark,
+        parser::{Event, Parser, ScalarStyle},
+    },
+    loader::{line_end, LineIndex, Severity},
+    modules::error::Location,
+};
+use alloc::{borrow::Cow, collections::BTreeSet};
+use core::{fmt, ops::Range};
+
+/// Plain scalars that YAML 1.1 reads as booleans, other than `true` and
+/// `false`.
+const TRUTHY: [&str; 20] = [
+    "y", "Y", "yes", "Yes", "YES", "n", "N", "no", "No", "NO", "True",
+    "TRUE", "False", "FALSE", "on", "On", "ON", "off", "Off", "OFF",
+];
+
+/// A rule checked by [`lint`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum Rule {
+    /// The input is not valid YAML.
+    Syntax,
+    /// Spaces or tabs at the end of a line.
+    TrailingSpaces,
+    /// A line longer than [`LintOptions::line_length`].
+    LineLength,
+    /// A nested block collection indented differently from the others.
+    Indentation,
+    /// A key written twice in the same mapping.
+    KeyDuplicates,
+    /// A plain scalar that YAML 1.1 reads as a boolean.
+    Truthy,
+    /// A missing or forbidden `---` marker.
+    DocumentStart,
+    /// A missing or forbidden `...` marker.
+    DocumentEnd,
+}
+
+impl Rule {
+    /// Returns the name of the rule, as used by `yamllint`, such as
+    /// `trailing-spaces`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Rule::Syntax => "syntax",
+            Rule::TrailingSpaces => "trailing-spaces",
+            Rule::LineLength => "line-length",
+            Rule::Indentation => "indentation",
+            Rule::KeyDuplicates => "key-duplicates",
+            Rule::Truthy => "truthy",
+            Rule::DocumentStart => "document-start",
+            Rule::DocumentEnd => "document-end",
+        }
+    }
+
+    /// Returns the severity of the problems the rule reports. Problems
+    /// that leave the meaning of the input in doubt are errors, while
+    /// `truthy`, `document-start` and `document-end` report warnings.
+    pub fn severity(self) -> Severity {
+        match self {
+            Rule::Truthy | Rule::DocumentStart | Rule::DocumentEnd => {
+                Severity::Warning
+            }
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// How nested block collections must be indented.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Indentation {
+    /// By the same number of spaces throughout the stream, taken from the
+    /// first nested collection.
+    Consistent,
+    /// By exactly this number of spaces.
+    Spaces(usize),
+}
+
+/// Whether a document marker must be written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Presence {
+    /// Every document must have the marker.
+    Required,
+    /// No document may have the marker.
+    Forbidden,
+}
+
+/// The rules checked by [`lint_with`] and their settings.
+///
+/// The default enables every rule with the settings `yamllint` uses by
+/// default: lines of at most 80 characters, consistent indentation, and a
+/// `---` at the start of every document, while `...` is not checked.
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::lint::{self, LintOptions};
+///
+/// let options = LintOptions {
+///     document_start: None,
+///     line_length: Some(120),
+///     ..LintOptions::default()
+/// };
+/// assert!(lint::lint_with("name: demo\n", &options).is_empty());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LintOptions {
+    /// Check for spaces and tabs at the end of lines.
+    pub trailing_spaces: bool,
+    /// The maximum number of characters in a line, or `None` to allow
+    /// lines of any length.
+    pub line_length: Option<usize>,
+    /// The indentation of nested block collections, or `None` to allow
+    /// any.
+    pub indentation: Option<Indentation>,
+    /// Check for keys written twice in the same mapping.
+    pub key_duplicates: bool,
+    /// Check for plain scalars that YAML 1.1 reads as booleans.
+    pub truthy: bool,
+    /// Whether documents must start with `---`, or `None` to allow both.
+    pub document_start: Option<Presence>,
+    /// Whether documents must end with `...`, or `None` to allow both.
+    pub document_end: Option<Presence>,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        LintOptions {
+            trailing_spaces: true,
+            line_length: Some(80),
+            indentation: Some(Indentation::Consistent),
+            key_duplicates: true,
+            truthy: true,
+            document_start: Some(Presence::Required),
+            document_end: None,
+        }
+    }
+}
+
+/// A place where the input breaks a rule.
+#[derive(Clone, Debug)]
+pub struct Problem {
+    /// The rule that was broken.
+    pub rule: Rule,
+    /// The severity of the problem, as given by [`Rule::severity`].
+    pub severity: Severity,
+    /// A human-readable description of the problem.
+    pub message: String,
+    /// The position of the start of [`Problem::span`].
+    pub location: Location,
+    /// The byte range of the offending text within the input. It is empty
+    /// for something missing, such as a document marker.
+    pub span: Range<usize>,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}: {} ({})",
+            self.location.line(),
+            self.location.column(),
+            self.severity,
+            self.message,
+            self.rule,
+        )
+    }
+}
+
+/// Checks `yaml` against the default [`LintOptions`] and returns every
+/// problem found, in the order they appear in the input.
+///
+/// # Examples
+///
+/// ```
+/// let yaml = "---\nenabled: yes\nname: api \n";
+/// let problems = serde_yml::lint::lint(yaml);
+/// let messages: Vec<String> =
+///     problems.iter().map(ToString::to_string).collect();
+/// assert_eq!(
+///     messages,
+///     [
+///         "2:10: warning: truthy value should be one of [false, true] (truthy)",
+///         "3:10: error: trailing spaces (trailing-spaces)",
+///     ],
+/// );
+/// ```
+pub fn lint(yaml: &str) -> Vec<Problem> {
+    lint_with(yaml, &LintOptions::default())
+}
+
+/// Checks `yaml` against the rules enabled in `options` and returns every
+/// problem found, in the order they appear in the input.
+pub fn lint_with(yaml: &str, options: &LintOptions) -> Vec<Problem> {
+    let mut linter = Linter {
+        lines: LineIndex::new(yaml.as_bytes()),
+        options,
+        problems: Vec::new(),
+        stack: Vec::new(),
+        spaces: match options.indentation {
+            Some(Indentation::Spaces(spaces)) => Some(spaces as u64),
+            _ => None,
+        },
+    };
+    linter.check_lines();
+    linter.check_events();
+    let mut problems = linter.problems;
+    problems.sort_by_key(|problem| (problem.span.start, problem.rule));
+    problems
+}
+
+/// A collection that the parser is inside of.
+struct Frame {
+    mapping: bool,
+    block: bool,
+    column: u64,
+    /// Whether the next node of a mapping is a key.
+    expect_key: bool,
+    /// The scalar keys of a mapping seen so far, kept sorted so that a
+    /// mapping with thousands of keys is checked in `O(n log n)`.
+    keys: BTreeSet<Box<[u8]>>,
+}
+
+struct Linter<'a> {
+    lines: LineIndex<'a>,
+    options: &'a LintOptions,
+    problems: Vec<Problem>,
+    stack: Vec<Frame>,
+    /// The number of spaces nested block collections are indented by, once
+    /// known.
+    spaces: Option<u64>,
+}
+
+impl Linter<'_> {
+    fn report(
+        &mut self,
+        rule: Rule,
+        span: Range<usize>,
+        message: String,
+    ) {
+        self.problems.push(Problem {
+            rule,
+            severity: rule.severity(),
+            message,
+            location: self.lines.location(span.start),
+            span,
+        });
+    }
+
+    /// Runs the rules that look at the text of each line.
+    fn check_lines(&mut self) {
+        let input = self.lines.input;
+        for index in 0..self.lines.starts.len() {
+            let start = self.lines.starts[index];
+            let end = line_end(input, start);
+            let text = String::from_utf8_lossy(&input[start..end]);
+            if self.options.trailing_spaces {
+                let trimmed = text.trim_end_matches([' ', '\t']).len();
+                if trimmed < text.len() {
+                    let span = start + trimmed..end;
+                    self.report(
+                        Rule::TrailingSpaces,
+                        span,
+                        "trailing spaces".to_owned(),
+                    );
+                }
+            }
+            if let Some(max) = self.options.line_length {
+                let length = text.chars().count();
+                // A line holding a single word, such as a URL, cannot be
+                // broken up.
+                let word = text
+                    .trim_start()
+                    .trim_start_matches(['#', '-'])
+                    .trim_start();
+                if length > max && word.contains([' ', '\t']) {
+                    let span = start
+                        + text
+                            .char_indices()
+                            .nth(max)
+                            .map_or(0, |(offset, _)| offset)
+                        ..end;
+                    self.report(
+                        Rule::LineLength,
+                        span,
+                        format!(
+                            "line too long ({} > {} characters)",
+                            length, max
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Runs the rules that look at the parser events.
+    fn check_events(&mut self) {
+        let mut parser = Parser::new(Cow::Borrowed(self.lines.input));
+        loop {
+            let (event, mark, end) = match parser
+                .parse_next_event_with_end()
+            {
+                Ok(event) => event,
+                Err(err) => {
+                    let start = self.lines.offset(err.mark());
+                    let message =
+                        format!("syntax error: {}", err.problem);
+                    self.report(Rule::Syntax, start..start, message);
+                    return;
+                }
+            };
+            let start = self.lines.offset(mark);
+            let span = start..self.lines.offset(end).max(start);
+            match event {
+                Event::StreamStart => {}
+                Event::StreamEnd => return,
+                Event::DocumentStart => self.check_marker(
+                    Rule::DocumentStart,
+                    self.options.document_start,
+                    span,
+                    "---",
+                ),
+                Event::DocumentEnd => self.check_marker(
+                    Rule::DocumentEnd,
+                    self.options.document_end,
+                    span,
+                    "...",
+                ),
+                Event::Alias(_) => {
+                    let _ = self.enter_node();
+                }
+                Event::Scalar(scalar) => {
+                    let key = self.enter_node();
+                    if key {
+                        self.check_key(&scalar.value, span.clone());
+                    }
+                    if self.options.truthy
+                        && scalar.style == ScalarStyle::Plain
+                        && scalar.tag.is_none()
+                        && TRUTHY.iter().any(|truthy| {
+                            truthy.as_bytes() == &*scalar.value
+                        })
+                    {
+                        self.report(
+                            Rule::Truthy,
+                            span,
+                            "truthy value should be one of [false, true]"
+                                .to_owned(),
+                        );
+                    }
+                }
+                Event::SequenceStart(_) => {
+                    self.enter_collection(false, mark, start);
+                }
+                Event::MappingStart(_) => {
+                    self.enter_collection(true, mark, start);
+                }
+                Event::SequenceEnd | Event::MappingEnd => {
+                    let _ = self.stack.pop();
+                }
+            }
+        }
+    }
+
+    /// Checks a `---` or `...` marker, which is written when the event
+    /// spans any text.
+    fn check_marker(
+        &mut self,
+        rule: Rule,
+        presence: Option<Presence>,
+        span: Range<usize>,
+        marker: &str,
+    ) {
+        let name = &rule.name()["document-".len()..];
+        match presence {
+            Some(Presence::Required) if span.is_empty() => {
+                self.report(
+                    rule,
+                    span,
+                    format!("missing document {} \"{}\"", name, marker),
+                );
+            }
+            Some(Presence::Forbidden) if !span.is_empty() => {
+                // The start of a document with directives spans them too.
+                let span =
+                    span.end.saturating_sub(marker.len())..span.end;
+                self.report(
+                    rule,
+                    span,
+                    format!(
+                        "found forbidden document {} \"{}\"",
+                        name, marker
+                    ),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    /// Records the start of a node in the enclosing collection and returns
+    /// whether the node is a mapping key.
+    fn enter_node(&mut self) -> bool {
+        match self.stack.last_mut() {
+            Some(frame) if frame.mapping => {
+                frame.expect_key = !frame.expect_key;
+                !frame.expect_key
+            }
+            _ => false,
+        }
+    }
+
+    fn check_key(&mut self, key: &[u8], span: Range<usize>) {
+        if !self.options.key_duplicates || key == b"<<" {
+            return;
+        }
+        let frame = match self.stack.last_mut() {
+            Some(frame) => frame,
+            None => return,
+        };
+        if !frame.keys.insert(Box::from(key)) {
+            let message = format!(
+                "duplication of key \"{}\" in mapping",
+                String::from_utf8_lossy(key)
+            );
+            self.report(Rule::KeyDuplicates, span, message);
+        }
+    }
+
+    fn enter_collection(
+        &mut self,
+        mapping: bool,
+        mark: Mark,
+        start: usize,
+    ) {
+        let _ = self.enter_node();
+        let input = self.lines.input;
+        let block = !matches!(input.get(start), Some(b'[' | b'{'));
+        if block && self.options.indentation.is_some() {
+            self.check_indentation(mapping, mark, start);
+        }
+        self.stack.push(Frame {
+            mapping,
+            block,
+            column: mark.column(),
+            expect_key: true,
+            keys: BTreeSet::new(),
+        });
+    }
+
+    /// Checks the indentation of a block collection that starts its line,
+    /// relative to the collection it is nested in.
+    fn check_indentation(
+        &mut self,
+        mapping: bool,
+        mark: Mark,
+        start: usize,
+    ) {
+        let parent = match self.stack.last() {
+            Some(parent) if parent.block => parent,
+            _ => return,
+        };
+        let line_start = self.lines.starts[mark.line() as usize];
+        let indent = &self.lines.input[line_start..start];
+        if indent.iter().any(|&byte| byte != b' ') {
+            return;
+        }
+        let found = mark.column().saturating_sub(parent.column);
+        if found == 0 && !mapping && parent.mapping {
+            return;
+        }
+        let expected = *self.spaces.get_or_insert(found);
+        if found != expected {
+            let message = format!(
+                "wrong indentation: expected {} but found {}",
+                parent.column + expected,
+                mark.column(),
+            );
+            self.report(Rule::Indentation, line_start..start, message);
+        }
+    }
+}
@@ -1,14 +1,31 @@
+#[cfg(feature = "std")]
+use crate::libyml::{
+    parser::{MappingStart, Scalar, SequenceStart},
+    tag::Tag,
+};
 use crate::{
-    de::{Event, Progress},
+    de::{Deserializer, Event, Progress, Warning, WarningKind},
+    lib::*,
     libyml::{
         error::Mark,
-        parser::{Event as YamlEvent, Parser},
+        parser::{Anchor, Event as YamlEvent, Parser, ScalarStyle},
     },
-    modules::error::{self, Error, ErrorImpl, Result},
+    modules::error::{self, Error, ErrorImpl, Location, Result},
+    modules::path::Path,
+    path::Path as OwnedPath,
+    value::Value,
 };
-use std::{borrow::Cow, collections::BTreeMap, io::Read, sync::Arc};
+use alloc::{borrow::Cow, collections::BTreeMap, sync::Arc};
+use core::{fmt, mem, ops::Range};
+use serde::{de::Error as _, Deserialize};
+#[cfg(feature = "std")]
+use std::io::Read;
 
 /// Represents a YAML loader.
+///
+/// A loader splits a stream into documents. [`Loader::next_value`] loads
+/// each of them as a [`Value`], while [`Loader::next_document`] returns
+/// the parsed events for the deserializer.
 #[derive(Debug)]
 pub struct Loader<'input> {
     /// The YAML parser used to parse the input.
@@ -24,6 +41,117 @@ pub struct Loader<'input> {
     ///
     /// This field keeps track of the number of YAML documents encountered during parsing.
     pub parsed_document_count: usize,
+
+    /// Converts parser marks into byte offsets of the input.
+    offsets: Offsets,
+
+    /// Tags removed from the input by [`Loader::with_lenient_tags`].
+    dropped_tags: Vec<Warning>,
+
+    /// Whether the loader was created by [`Loader::with_lenient_tags`].
+    lenient_tags: bool,
+
+    /// Length of the UTF-8 byte order mark removed from the start of the
+    /// input, added back to the spans of documents.
+    bom: usize,
+
+    /// Whether the loader was created by [`Loader::recovering`].
+    recovering: bool,
+
+    /// Problems skipped by a recovering loader.
+    diagnostics: Vec<Diagnostic>,
+
+    /// The parser, once it has reached the end of the input, kept for
+    /// [`Loader::reset`].
+    idle: Option<Parser<'input>>,
+
+    /// Event buffers of a document given back by [`Loader::recycle`].
+    spare_events: Vec<(Event<'input>, Mark)>,
+    spare_ends: Vec<Mark>,
+}
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    /// The input is not valid YAML, and part of it was skipped.
+    Error,
+    /// The input is valid, but not all of it was used.
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        })
+    }
+}
+
+/// A problem found in the input while loading it with
+/// [`Loader::recovering`] or [`from_str_lossy`](crate::de::from_str_lossy).
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    /// The position of the problem in the input, if known.
+    pub location: Option<Location>,
+    /// The description of the problem, as given by the error it replaces.
+    pub message: String,
+    /// Whether the problem made the loader skip part of the input.
+    pub severity: Severity,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+/// An anchor defined in a document, as reported by [`DocumentMetadata`].
+#[derive(Clone, Debug)]
+pub struct DocumentAnchor {
+    /// The name of the anchor, without the leading `&`.
+    pub name: String,
+    /// The position of the node that defines the anchor.
+    pub location: Location,
+    /// The number of aliases that refer to this definition of the anchor.
+    pub aliases: usize,
+}
+
+/// Information about a document of a stream, gathered while parsing it,
+/// for tools that report on documents without deserializing them.
+///
+/// Returned by [`Document::metadata`], by
+/// [`Deserializer::metadata`](crate::de::Deserializer::metadata) and by
+/// [`document_metadata`](crate::de::document_metadata).
+#[derive(Clone, Debug)]
+pub struct DocumentMetadata {
+    /// The byte range of the document within the input, as returned by
+    /// [`Document::span`].
+    pub span: Range<usize>,
+    /// The version given by the `%YAML` directive of the document, as
+    /// `(major, minor)`.
+    pub version: Option<(u32, u32)>,
+    /// The anchors defined in the document, in the order of definition.
+    /// An anchor defined twice is listed twice, and each alias is counted
+    /// against the definition before it.
+    pub anchors: Vec<DocumentAnchor>,
+    /// The distinct tags of the nodes of the document, in order of first
+    /// use, with shorthands such as `!!str` expanded to their full form.
+    pub tags: Vec<String>,
+}
+
+/// A document of a stream loaded as a [`Value`], returned by
+/// [`Loader::next_value`].
+#[derive(Debug)]
+pub struct LoadedDocument {
+    /// The value of the document, or the error that stopped it from
+    /// loading, such as a duplicate key or a syntax error.
+    pub value: Result<Value>,
+    /// The byte range of the document within the input, as returned by
+    /// [`Document::span`].
+    pub span: Range<usize>,
+    /// The anchors defined in the document, in the order of definition.
+    pub anchors: Vec<DocumentAnchor>,
 }
 
 /// Represents a YAML document.
@@ -57,8 +185,254 @@ pub struct Document<'input> {
     /// encountered during parsing, its id is used to look up the index of the corresponding
     /// event in the `events` vector.
     pub anchor_event_map: BTreeMap<usize, usize>,
+
+    /// Byte range of the document within the input.
+    span: Range<usize>,
+
+    /// Text of the document, borrowed from the input when possible.
+    raw: Cow<'input, str>,
+
+    /// Position of the start of [`Document::raw`].
+    start: Option<Mark>,
+
+    /// Position of the end of each event in `events`.
+    ends: Vec<Mark>,
+
+    /// Anchors defined in the document, indexed by the ids of
+    /// [`Document::anchor_event_map`].
+    anchors: Vec<DocumentAnchor>,
+}
+
+impl Document<'_> {
+    /// Returns the original text of the document.
+    ///
+    /// The text starts at the document's directives or `---` marker, or at
+    /// its first node if it has neither, and extends through its `...`
+    /// marker if it has one, or up to the start of the next document.
+    /// After a parse error, it extends to the end of the input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_yml::de::Progress;
+    /// use serde_yml::loader::Loader;
+    ///
+    /// let input = "a: 1\n---\nb: 2\n";
+    /// let mut loader = Loader::new(Progress::Str(input)).unwrap();
+    /// assert_eq!(loader.next_document().unwrap().raw(), "a: 1\n");
+    /// assert_eq!(loader.next_document().unwrap().raw(), "---\nb: 2\n");
+    /// ```
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    /// Returns whether the document has no content, like the documents
+    /// between the markers of `---\n---\n`.
+    ///
+    /// A document consisting of `~` or `""` is not empty.
+    pub fn is_empty(&self) -> bool {
+        match self.events.as_slice() {
+            [(Event::Void, _)] => true,
+            [(Event::Scalar(scalar), _)] => {
+                scalar.value.is_empty()
+                    && scalar.tag.is_none()
+                    && scalar.style == ScalarStyle::Plain
+                    && self.anchor_event_map.is_empty()
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the byte range of [`Document::raw`] within the input.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Returns the anchors, tags, version directive and span of the
+    /// document.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_yml::de::Progress;
+    /// use serde_yml::loader::Loader;
+    ///
+    /// let input = "%YAML 1.1\n---\nbase: &defaults {retries: 3}\nlocal: &unused !env HOME\nprod: *defaults\n";
+    /// let mut loader = Loader::new(Progress::Str(input)).unwrap();
+    /// let metadata = loader.next_document().unwrap().metadata();
+    ///
+    /// assert_eq!(metadata.version, Some((1, 1)));
+    /// assert_eq!(metadata.tags, ["!env"]);
+    /// let unused: Vec<&str> = metadata
+    ///     .anchors
+    ///     .iter()
+    ///     .filter(|anchor| anchor.aliases == 0)
+    ///     .map(|anchor| anchor.name.as_str())
+    ///     .collect();
+    /// assert_eq!(unused, ["unused"]);
+    /// ```
+    pub fn metadata(&self) -> DocumentMetadata {
+        let mut tags: Vec<String> = Vec::new();
+        for (event, _) in &self.events {
+            let tag = match event {
+                Event::Scalar(scalar) => scalar.tag.as_ref(),
+                Event::SequenceStart(sequence) => sequence.tag.as_ref(),
+                Event::MappingStart(mapping) => mapping.tag.as_ref(),
+                _ => None,
+            };
+            if let Some(tag) = tag {
+                let tag = String::from_utf8_lossy(tag);
+                if !tags.iter().any(|known| *known == tag) {
+                    tags.push(tag.into_owned());
+                }
+            }
+        }
+        DocumentMetadata {
+            span: self.span(),
+            version: version_directive(&self.raw),
+            anchors: self.anchors.clone(),
+            tags,
+        }
+    }
+
+    /// Returns whether the document only stands in for a stream without
+    /// documents, which deserializes as a single null.
+    pub(crate) fn is_placeholder(&self) -> bool {
+        self.start.is_none()
+    }
+
+    /// Returns the text of the node spanning `events[first..=last]`, with
+    /// the indentation of the node removed from every line after the first
+    /// so that the text is a document of its own.
+    pub(crate) fn node_text(
+        &self,
+        first: usize,
+        last: usize,
+    ) -> String {
+        let start = self.events.get(first).map(|(_, mark)| *mark);
+        let end = self.ends.get(last).copied();
+        let (start, end) = match (start, end) {
+            (Some(start), Some(end)) => (start, end),
+            _ => return String::new(),
+        };
+        let from = self.raw_offset(start);
+        let to = self.raw_offset(end).max(from);
+        let indent = start.column() as usize;
+        let mut text = String::with_capacity(to - from);
+        let raw = self.raw.get(from..to).unwrap_or_default();
+        for (i, line) in raw.split_inclusive('\n').enumerate() {
+            let spaces = if i == 0 {
+                0
+            } else {
+                line.bytes()
+                    .take(indent)
+                    .take_while(|&b| b == b' ')
+                    .count()
+            };
+            text.push_str(&line[spaces..]);
+        }
+        let len = text.trim_end_matches(BLANKS).len();
+        text.truncate(len);
+        text
+    }
+
+    /// Converts a mark within the document into a byte offset of
+    /// [`Document::raw`].
+    fn raw_offset(&self, mark: Mark) -> usize {
+        let start = match self.start {
+            Some(start) => start,
+            None => return 0,
+        };
+        let raw = self.raw.as_bytes();
+        if mark.line() <= start.line() {
+            let columns = mark.column().saturating_sub(start.column());
+            return advance(raw, 0, columns);
+        }
+        let mut offsets = Offsets {
+            line: start.line(),
+            line_start: 0,
+        };
+        offsets.offset(raw, mark)
+    }
 }
 
+#[cfg(feature = "std")]
+impl Document<'static> {
+    /// Builds the events of a document holding `value`, so that the value
+    /// can be deserialized with the path of every node tracked as if it
+    /// had been parsed. The document has no text, and every event is at
+    /// the start of the input.
+    pub(crate) fn from_value(value: &Value) -> Self {
+        let mut document = Document {
+            events: Vec::new(),
+            error: None,
+            anchor_event_map: BTreeMap::new(),
+            span: 0..0,
+            raw: Cow::Borrowed(""),
+            start: None,
+            ends: Vec::new(),
+            anchors: Vec::new(),
+        };
+        push_value(&mut document, value, None);
+        document
+    }
+}
+
+/// Pushes the events of `value`, whose node carries `tag`, to `document`.
+#[cfg(feature = "std")]
+fn push_value(
+    document: &mut Document<'static>,
+    value: &Value,
+    tag: Option<Tag>,
+) {
+    let (text, style) = match value {
+        Value::Null => ("null".to_owned(), ScalarStyle::Plain),
+        Value::Bool(b) => (b.to_string(), ScalarStyle::Plain),
+        Value::Number(n) => (n.to_string(), ScalarStyle::Plain),
+        Value::String(s) => (s.clone(), ScalarStyle::SingleQuoted),
+        Value::Sequence(sequence) => {
+            let start = SequenceStart { anchor: None, tag };
+            push_event(document, Event::SequenceStart(start));
+            for element in sequence {
+                push_value(document, element, None);
+            }
+            return push_event(document, Event::SequenceEnd);
+        }
+        Value::Mapping(mapping) => {
+            let start = MappingStart { anchor: None, tag };
+            push_event(document, Event::MappingStart(start));
+            for (key, value) in mapping {
+                push_value(document, key, None);
+                push_value(document, value, None);
+            }
+            return push_event(document, Event::MappingEnd);
+        }
+        Value::Tagged(tagged) => {
+            let tag = Tag::new(&tagged.tag.to_string());
+            return push_value(document, &tagged.value, Some(tag));
+        }
+    };
+    let scalar = Scalar {
+        anchor: None,
+        tag,
+        value: text.into_bytes().into_boxed_slice(),
+        style,
+        repr: None,
+    };
+    push_event(document, Event::Scalar(scalar));
+}
+
+/// Pushes `event` to `document` at the start of the input.
+#[cfg(feature = "std")]
+fn push_event(document: &mut Document<'static>, event: Event<'static>) {
+    let mark = Mark::new(0, 0, 0);
+    document.events.push((event, mark));
+    document.ends.push(mark);
+}
+
+/// Whitespace removed from the end of the text of a node.
+const BLANKS: [char; 2] = [' ', '\t'];
+
 impl<'input> Loader<'input> {
     /// Constructs a new `Loader` instance from the given progress.
     ///
@@ -83,28 +457,307 @@ impl<'input> Loader<'input> {
     /// assert!(loader_result.is_ok());
     /// ```
     pub fn new(progress: Progress<'input>) -> Result<Self> {
-        let input = match progress {
-            Progress::Str(s) => Cow::Borrowed(s.as_bytes()),
-            Progress::Slice(bytes) => Cow::Borrowed(bytes),
-            Progress::Read(mut rdr) => {
-                let mut buffer = Vec::new();
-                if let Err(io_error) = rdr.read_to_end(&mut buffer) {
-                    return Err(error::new(ErrorImpl::IoError(
-                        io_error,
-                    )));
+        Ok(Loader::from_input(read_input(progress)?, false))
+    }
+
+    /// Constructs a `Loader` that accepts nodes carrying more than one tag,
+    /// such as `!outer !inner x`.
+    ///
+    /// YAML allows a single tag per node, so [`Loader::new`] reports such
+    /// nodes as errors. This loader keeps the first tag of each node and
+    /// removes the others, which are listed by [`Loader::dropped_tags`].
+    /// Scalars are always copied out of the input rather than borrowed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is an issue reading the input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_yml::de::Progress;
+    /// use serde_yml::loader::Loader;
+    ///
+    /// let input = "point: !outer !inner [1, 2]\n";
+    /// let loader = Loader::with_lenient_tags(Progress::Str(input)).unwrap();
+    /// let dropped = &loader.dropped_tags()[0];
+    /// assert_eq!(dropped.message, "dropped tag `!inner` after tag `!outer`");
+    /// ```
+    pub fn with_lenient_tags(
+        progress: Progress<'input>,
+    ) -> Result<Self> {
+        Ok(Loader::from_input(read_input(progress)?, true))
+    }
+
+    /// Constructs a `Loader` that recovers from syntax errors.
+    ///
+    /// When a document fails to parse, the loader blanks out the line the
+    /// error points at, or the nearest line above it with any text, and
+    /// parses the document again, until it parses or no text is left to
+    /// remove. Each error is listed by [`Loader::diagnostics`], and the
+    /// documents returned hold whatever could be parsed. Blanking keeps the
+    /// line and column of everything else in the input, so locations in
+    /// later errors still point into the original text.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is an issue reading the input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_yml::de::Progress;
+    /// use serde_yml::loader::Loader;
+    ///
+    /// let input = "a: 1\nb: 2: 3\nc: 4\n";
+    /// let mut loader = Loader::recovering(Progress::Str(input)).unwrap();
+    /// let document = loader.next_document().unwrap();
+    /// assert!(document.error.is_none());
+    /// assert_eq!(loader.diagnostics().len(), 1);
+    /// assert_eq!(loader.diagnostics()[0].location.unwrap().line(), 2);
+    /// ```
+    pub fn recovering(progress: Progress<'input>) -> Result<Self> {
+        let mut loader =
+            Loader::from_input(read_input(progress)?, false);
+        loader.recovering = true;
+        Ok(loader)
+    }
+
+    pub(crate) fn from_input(
+        input: Cow<'input, [u8]>,
+        lenient_tags: bool,
+    ) -> Self {
+        let mut loader = Loader {
+            offsets: Offsets::default(),
+            parser: None,
+            parsed_document_count: 0,
+            dropped_tags: Vec::new(),
+            lenient_tags,
+            bom: 0,
+            recovering: false,
+            diagnostics: Vec::new(),
+            idle: None,
+            spare_events: Vec::new(),
+            spare_ends: Vec::new(),
+        };
+        let _ = loader.reset_input(input);
+        loader
+    }
+
+    /// Starts over on a new input, in the same mode as the loader was
+    /// created with.
+    ///
+    /// The parser and the buffers of documents given back through
+    /// [`Loader::recycle`] are reused, which saves allocations when many
+    /// small inputs are loaded one after the other.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is an issue reading the input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_yml::de::Progress;
+    /// use serde_yml::loader::Loader;
+    ///
+    /// let mut loader = Loader::new(Progress::Str("a: 1")).unwrap();
+    /// let document = loader.next_document().unwrap();
+    /// loader.recycle(document);
+    ///
+    /// loader.reset(Progress::Str("b: 2")).unwrap();
+    /// assert_eq!(loader.next_document().unwrap().raw(), "b: 2");
+    /// assert!(loader.next_document().is_none());
+    /// ```
+    pub fn reset(&mut self, progress: Progress<'input>) -> Result<()> {
+        let _ = self.reset_input(read_input(progress)?);
+        Ok(())
+    }
+
+    /// Starts over on `input`, returning the previous input if the parser
+    /// was reused.
+    pub(crate) fn reset_input(
+        &mut self,
+        mut input: Cow<'input, [u8]>,
+    ) -> Option<Cow<'input, [u8]>> {
+        self.offsets = Offsets::default();
+        self.parsed_document_count = 0;
+        self.dropped_tags.clear();
+        // The parser reads the mark as a character of the first line, which
+        // would put the keys of that line one column deeper than the rest.
+        self.bom = 0;
+        if input.starts_with(UTF8_BOM) {
+            self.bom = UTF8_BOM.len();
+            input = match input {
+                Cow::Borrowed(bytes) => {
+                    Cow::Borrowed(&bytes[self.bom..])
+                }
+                Cow::Owned(mut bytes) => {
+                    let _ = bytes.drain(..self.bom);
+                    Cow::Owned(bytes)
+                }
+            };
+        }
+        if self.lenient_tags {
+            while let Some(chain) = find_tag_chain(&input) {
+                self.dropped_tags.push(Warning {
+                    kind: WarningKind::DroppedTag,
+                    message: format!(
+                        "dropped tag `{}` after tag `{}`",
+                        lossy(&input[chain.later.clone()]),
+                        lossy(&input[chain.earlier]),
+                    ),
+                    path: Some(OwnedPath::root()),
+                    location: Location::from_mark(chain.mark),
+                });
+                for byte in &mut input.to_mut()[chain.later] {
+                    *byte = b' ';
                 }
-                Cow::Owned(buffer)
             }
-            Progress::Iterable(_) | Progress::Document(_) => {
-                unreachable!()
+        }
+        match self.parser.take().or_else(|| self.idle.take()) {
+            Some(mut parser) => {
+                let previous = parser.reset(input);
+                self.parser = Some(parser);
+                Some(previous)
             }
-            Progress::Fail(err) => return Err(error::shared(err)),
+            None => {
+                self.parser = Some(Parser::new(input));
+                None
+            }
+        }
+    }
+
+    /// Gives the buffers of a document that is no longer needed back to
+    /// the loader, to hold the events of the next document.
+    pub fn recycle(&mut self, document: Document<'input>) {
+        let Document {
+            mut events,
+            mut ends,
+            ..
+        } = document;
+        events.clear();
+        ends.clear();
+        self.spare_events = events;
+        self.spare_ends = ends;
+    }
+
+    /// Checks that no line of the input ends with a lone carriage return
+    /// (CR), as in files saved by classic Mac OS.
+    ///
+    /// YAML reads CR, LF and CRLF alike as line breaks, so such input
+    /// parses, but tools that only recognize LF see the whole file as a
+    /// single line. The error locates the first CR line ending.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a line ends with a lone CR.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_yml::de::Progress;
+    /// use serde_yml::loader::Loader;
+    ///
+    /// let loader = Loader::new(Progress::Str("a: 1\r\nb: 2\rc: 3\r")).unwrap();
+    /// let err = loader.check_line_endings().unwrap_err();
+    /// assert_eq!(err.location().unwrap().index(), 10);
+    /// assert_eq!(err.location().unwrap().line(), 2);
+    /// ```
+    pub fn check_line_endings(&self) -> Result<()> {
+        let mark = match &self.parser {
+            Some(parser) => find_cr_line_ending(parser.input()),
+            None => None,
         };
+        match mark {
+            Some(mark) => Err(error::fix_mark(
+                Error::custom(
+                    "found a line ending written as a lone carriage return (CR), as in classic Mac OS files; convert the line endings to LF or CRLF",
+                ),
+                mark,
+                Path::Root,
+            )),
+            None => Ok(()),
+        }
+    }
 
-        Ok(Loader {
-            parser: Some(Parser::new(input)),
-            parsed_document_count: 0,
-        })
+    /// Returns the tags removed by [`Loader::with_lenient_tags`], as
+    /// warnings of kind [`WarningKind::DroppedTag`] in the order they
+    /// appear in the input.
+    pub fn dropped_tags(&self) -> &[Warning] {
+        &self.dropped_tags
+    }
+
+    /// Returns the errors skipped by a loader created with
+    /// [`Loader::recovering`], in the order they were found.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Records `error`, found in the document last returned, blanks out
+    /// the line it points at and starts over so that the next call to
+    /// [`Loader::next_document`] returns that document again.
+    ///
+    /// Returns false, leaving the loader as it was, if the error has no
+    /// location or there is no text left to blank out above it.
+    pub(crate) fn skip_error(&mut self, error: &Error) -> bool {
+        let location = error.location();
+        let message = error.to_string();
+        // Blanking a line can leave the same error in place, as when the
+        // input ends inside a quoted scalar.
+        if self.diagnostics.last().map(|last| &last.message)
+            != Some(&message)
+        {
+            self.diagnostics.push(Diagnostic {
+                location,
+                message,
+                severity: Severity::Error,
+            });
+        }
+        let mut line = match location {
+            Some(location) => location.line().saturating_sub(1),
+            None => return false,
+        };
+        let mut input =
+            match self.parser.as_ref().or(self.idle.as_ref()) {
+                Some(parser) => parser.input().to_vec(),
+                None => return false,
+            };
+        // A flow collection or quoted scalar left open makes the parser
+        // fail wherever the input stops making sense, often lines later or
+        // at the end of the input, so the line that opens it is the one to
+        // blank. The same goes for any error at a line without text.
+        if let Some(context) = error.context_location() {
+            let context_line = context.line().saturating_sub(1);
+            let opener =
+                line_text(&input, context_line).and_then(|text| {
+                    let column =
+                        context.column().saturating_sub(1) as u64;
+                    input.get(advance(&input, text.start, column))
+                });
+            let blank = line_text(&input, line)
+                .map_or(true, |text| !has_text(&input[text]));
+            if blank
+                || matches!(opener, Some(b'[' | b'{' | b'\'' | b'"'))
+            {
+                line = context_line;
+            }
+        }
+        if !blank_line(&mut input, line) {
+            return false;
+        }
+        let skip = self.parsed_document_count.saturating_sub(1);
+        let bom = self.bom;
+        let dropped_tags = mem::take(&mut self.dropped_tags);
+        let _ = self.reset_input(Cow::Owned(input));
+        self.bom = bom;
+        self.dropped_tags = dropped_tags;
+        for _ in 0..skip {
+            if let Some(document) = self.load_document() {
+                self.recycle(document);
+            }
+        }
+        true
     }
 
     /// Advances the loader to the next document and returns it.
@@ -127,6 +780,67 @@ impl<'input> Loader<'input> {
     /// assert_eq!(document.events.len(), 4);
     /// ```
     pub fn next_document(&mut self) -> Option<Document<'input>> {
+        let mut document = self.load_document()?;
+        while self.recovering {
+            let error = match &document.error {
+                Some(error) => error::shared(Arc::clone(error)),
+                None => break,
+            };
+            if !self.skip_error(&error) {
+                break;
+            }
+            document = self.load_document()?;
+        }
+        Some(document)
+    }
+
+    /// Advances the loader to the next document and loads it as a
+    /// [`Value`].
+    ///
+    /// A document that fails to load is returned with its error, so that
+    /// the documents after it can still be loaded. A syntax error, or an
+    /// alias to an unknown anchor or to a node that contains it, ends the
+    /// stream after the document it is found in, as the parser cannot tell
+    /// where the next document starts; a loader created with
+    /// [`Loader::recovering`] skips over syntax errors instead. A stream without any document returns `None`
+    /// straight away.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_yml::de::Progress;
+    /// use serde_yml::loader::Loader;
+    ///
+    /// let input = "a: 1\n---\nb: 1\nb: 2\n---\nc: 3\n";
+    /// let mut loader = Loader::new(Progress::Str(input)).unwrap();
+    /// let mut loaded = Vec::new();
+    /// while let Some(document) = loader.next_value() {
+    ///     loaded.push((document.span, document.value.is_ok()));
+    /// }
+    /// assert_eq!(loaded, [(0..5, true), (5..19, false), (19..28, true)]);
+    /// ```
+    pub fn next_value(&mut self) -> Option<LoadedDocument> {
+        let document = self.next_document()?;
+        if document.is_placeholder() && document.error.is_none() {
+            return None;
+        }
+        let syntax_error = document.error.is_some();
+        let span = document.span();
+        let anchors = document.anchors.clone();
+        let value =
+            Value::deserialize(Deserializer::from_document(document));
+        if syntax_error {
+            self.parser = None;
+        }
+        Some(LoadedDocument {
+            value,
+            span,
+            anchors,
+        })
+    }
+
+    /// Parses the next document of the input.
+    fn load_document(&mut self) -> Option<Document<'input>> {
         let parser = match &mut self.parser {
             Some(parser) => parser,
             None => return None,
@@ -135,80 +849,557 @@ impl<'input> Loader<'input> {
         let first = self.parsed_document_count == 0;
         self.parsed_document_count += 1;
 
-        let mut anchors = BTreeMap::new();
+        let mut anchors: BTreeMap<Anchor, usize> = BTreeMap::new();
+        // The anchor id, if any, of each collection not yet ended, to catch
+        // an alias to a node from within that node.
+        let mut open: Vec<Option<usize>> = Vec::new();
         let mut document = Document {
-            events: Vec::new(),
+            events: mem::take(&mut self.spare_events),
             error: None,
             anchor_event_map: BTreeMap::new(),
+            span: 0..0,
+            raw: Cow::Borrowed(""),
+            start: None,
+            ends: mem::take(&mut self.spare_ends),
+            anchors: Vec::new(),
         };
+        let offsets = &mut self.offsets;
+        let bom = self.bom;
+        // A rewritten input is owned by the parser, which may not outlive
+        // the deserialized value, so scalars must not borrow from it.
+        let rewritten = !self.dropped_tags.is_empty();
+        let mut start = None;
 
         loop {
-            let (event, mark) = match parser.parse_next_event() {
-                Ok((event, mark)) => (event, mark),
+            let (event, mark, end) = match parser
+                .parse_next_event_with_end()
+            {
+                Ok(event) => event,
                 Err(err) => {
-                    document.error = Some(Error::from(err).shared());
+                    let err =
+                        match tag_chain(parser.input(), err.mark()) {
+                            Some(chain) => chain.error(parser.input()),
+                            None => Error::from(err),
+                        };
+                    document.error = Some(err.shared());
+                    let len = parser.input().len();
+                    set_raw(&mut document, parser, start, len, bom);
                     return Some(document);
                 }
             };
             let event = match event {
                 YamlEvent::StreamStart => continue,
                 YamlEvent::StreamEnd => {
-                    self.parser = None;
+                    self.idle = self.parser.take();
                     return if first {
                         if document.events.is_empty() {
                             document.events.push((Event::Void, mark));
+                            document.ends.push(mark);
                         }
                         Some(document)
                     } else {
                         None
                     };
                 }
-                YamlEvent::DocumentStart => continue,
-                YamlEvent::DocumentEnd => return Some(document),
+                YamlEvent::DocumentStart => {
+                    start = Some(offsets.offset(parser.input(), mark));
+                    document.start = Some(mark);
+                    continue;
+                }
+                YamlEvent::DocumentEnd => {
+                    let end = offsets.offset(parser.input(), end);
+                    set_raw(&mut document, parser, start, end, bom);
+                    return Some(document);
+                }
                 YamlEvent::Alias(alias) => match anchors.get(&alias) {
-                    Some(id) => Event::Alias(*id),
+                    Some(&id) if open.contains(&Some(id)) => {
+                        let name = lossy(&alias);
+                        document.error = Some(
+                            error::new(ErrorImpl::AliasCycle(
+                                name, mark,
+                            ))
+                            .shared(),
+                        );
+                        let len = parser.input().len();
+                        set_raw(&mut document, parser, start, len, bom);
+                        return Some(document);
+                    }
+                    Some(&id) => {
+                        document.anchors[id].aliases += 1;
+                        Event::Alias(id)
+                    }
                     None => {
                         document.error = Some(
                             error::new(ErrorImpl::UnknownAnchor(mark))
                                 .shared(),
                         );
+                        let len = parser.input().len();
+                        set_raw(&mut document, parser, start, len, bom);
                         return Some(document);
                     }
                 },
                 YamlEvent::Scalar(mut scalar) => {
+                    if rewritten {
+                        scalar.repr = None;
+                    }
                     if let Some(anchor) = scalar.anchor.take() {
-                        let id = anchors.len();
-                        anchors.insert(anchor, id);
-                        document
-                            .anchor_event_map
-                            .insert(id, document.events.len());
+                        define_anchor(
+                            &mut document,
+                            &mut anchors,
+                            anchor,
+                            mark,
+                        );
                     }
                     Event::Scalar(scalar)
                 }
                 YamlEvent::SequenceStart(mut sequence_start) => {
-                    if let Some(anchor) = sequence_start.anchor.take() {
-                        let id = anchors.len();
-                        anchors.insert(anchor, id);
-                        document
-                            .anchor_event_map
-                            .insert(id, document.events.len());
-                    }
+                    open.push(sequence_start.anchor.take().map(
+                        |anchor| {
+                            define_anchor(
+                                &mut document,
+                                &mut anchors,
+                                anchor,
+                                mark,
+                            )
+                        },
+                    ));
                     Event::SequenceStart(sequence_start)
                 }
-                YamlEvent::SequenceEnd => Event::SequenceEnd,
+                YamlEvent::SequenceEnd => {
+                    open.pop();
+                    Event::SequenceEnd
+                }
                 YamlEvent::MappingStart(mut mapping_start) => {
-                    if let Some(anchor) = mapping_start.anchor.take() {
-                        let id = anchors.len();
-                        anchors.insert(anchor, id);
-                        document
-                            .anchor_event_map
-                            .insert(id, document.events.len());
-                    }
+                    open.push(mapping_start.anchor.take().map(
+                        |anchor| {
+                            define_anchor(
+                                &mut document,
+                                &mut anchors,
+                                anchor,
+                                mark,
+                            )
+                        },
+                    ));
                     Event::MappingStart(mapping_start)
                 }
-                YamlEvent::MappingEnd => Event::MappingEnd,
+                YamlEvent::MappingEnd => {
+                    open.pop();
+                    Event::MappingEnd
+                }
             };
             document.events.push((event, mark));
+            document.ends.push(end);
+        }
+    }
+}
+
+/// Records the definition of `anchor` by the node whose event is pushed to
+/// `document` next, and returns its id.
+fn define_anchor(
+    document: &mut Document<'_>,
+    anchors: &mut BTreeMap<Anchor, usize>,
+    anchor: Anchor,
+    mark: Mark,
+) -> usize {
+    let id = document.anchors.len();
+    document.anchors.push(DocumentAnchor {
+        name: String::from_utf8_lossy(&anchor).into_owned(),
+        location: Location::from_mark(mark),
+        aliases: 0,
+    });
+    anchors.insert(anchor, id);
+    document.anchor_event_map.insert(id, document.events.len());
+    id
+}
+
+/// Returns the version of the `%YAML` directive among the directives,
+/// comments and blank lines at the start of a document's text.
+fn version_directive(raw: &str) -> Option<(u32, u32)> {
+    for line in raw.lines() {
+        if let Some(rest) = line.strip_prefix("%YAML") {
+            let version = rest.split_whitespace().next()?;
+            let (major, minor) = version.split_once('.')?;
+            return Some((major.parse().ok()?, minor.parse().ok()?));
+        }
+        let line = line.trim_start();
+        if !(line.is_empty() || line.starts_with(['%', '#'])) {
+            return None;
+        }
+    }
+    None
+}
+
+/// Records the text of `document` from `start`, or from the end position
+/// when the document never started, up to `end`.
+fn set_raw<'input>(
+    document: &mut Document<'input>,
+    parser: &Parser<'input>,
+    start: Option<usize>,
+    end: usize,
+    bom: usize,
+) {
+    let input = parser.input();
+    let end = end.min(input.len());
+    let span = start.map_or(end, |start| start.min(end))..end;
+    document.raw = match input {
+        Cow::Borrowed(input) => {
+            let input: &'input [u8] = input;
+            String::from_utf8_lossy(&input[span.clone()])
+        }
+        Cow::Owned(input) => Cow::Owned(
+            String::from_utf8_lossy(&input[span.clone()]).into_owned(),
+        ),
+    };
+    document.span = span.start + bom..span.end + bom;
+}
+
+fn read_input(progress: Progress<'_>) -> Result<Cow<'_, [u8]>> {
+    match progress {
+        Progress::Str(s) => Ok(Cow::Borrowed(s.as_bytes())),
+        Progress::Slice(bytes) => decode(Cow::Borrowed(bytes)),
+        #[cfg(feature = "std")]
+        Progress::Read(mut rdr) => {
+            let mut buffer = Vec::new();
+            if let Err(io_error) = rdr.read_to_end(&mut buffer) {
+                return Err(error::new(ErrorImpl::IoError(io_error)));
+            }
+            decode(Cow::Owned(buffer))
+        }
+        Progress::Iterable(_) | Progress::Document(_) => {
+            unreachable!()
+        }
+        Progress::Fail(err) => Err(error::shared(err)),
+    }
+}
+
+/// Byte order mark of UTF-8 input.
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Byte order of UTF-16 input.
+#[derive(Clone, Copy)]
+enum ByteOrder {
+    Little,
+    Big,
+}
+
+/// Converts bytes of YAML text to UTF-8.
+///
+/// The encoding is told by the byte order mark or, when there is none, by
+/// the null bytes among the first characters, as in section 5.2 of the
+/// YAML specification. UTF-8 input is passed through unchanged, so that
+/// document spans stay offsets of the input, and left for the parser to
+/// validate. The byte order mark of UTF-16 input is dropped with the
+/// conversion.
+fn decode(input: Cow<'_, [u8]>) -> Result<Cow<'_, [u8]>> {
+    let (order, start) = match *input {
+        [0, 0, 0xFE, 0xFF, ..] | [0, 0, 0, _, ..] => {
+            return Err(unsupported_encoding("UTF-32BE"))
+        }
+        [0xFF, 0xFE, 0, 0, ..] | [_, 0, 0, 0, ..] => {
+            return Err(unsupported_encoding("UTF-32LE"))
+        }
+        [0xEF, 0xBB, 0xBF, ..] => return Ok(input),
+        [0xFE, 0xFF, ..] => (ByteOrder::Big, 2),
+        [0xFF, 0xFE, ..] => (ByteOrder::Little, 2),
+        [0, _, ..] => (ByteOrder::Big, 0),
+        [_, 0, ..] => (ByteOrder::Little, 0),
+        _ => return Ok(input),
+    };
+    let name = match order {
+        ByteOrder::Little => "UTF-16LE",
+        ByteOrder::Big => "UTF-16BE",
+    };
+    let body = &input[start..];
+    if body.len() % 2 != 0 {
+        return Err(error::new(ErrorImpl::Message(
+            format!("invalid {} input: odd number of bytes", name),
+            None,
+        )));
+    }
+    let units = body.chunks_exact(2).map(|pair| match order {
+        ByteOrder::Little => u16::from_le_bytes([pair[0], pair[1]]),
+        ByteOrder::Big => u16::from_be_bytes([pair[0], pair[1]]),
+    });
+    let mut text = String::with_capacity(body.len());
+    let mut offset = start;
+    for ch in char::decode_utf16(units) {
+        match ch {
+            Ok(ch) => {
+                text.push(ch);
+                offset += ch.len_utf16() * 2;
+            }
+            Err(err) => {
+                return Err(error::new(ErrorImpl::Message(
+                    format!(
+                        "invalid {} input: unpaired surrogate {:04X} at byte {}",
+                        name,
+                        err.unpaired_surrogate(),
+                        offset,
+                    ),
+                    None,
+                )));
+            }
+        }
+    }
+    Ok(Cow::Owned(text.into_bytes()))
+}
+
+fn unsupported_encoding(name: &str) -> Error {
+    error::new(ErrorImpl::Message(
+        format!(
+            "{} input is not supported; convert it to UTF-8 or UTF-16",
+            name
+        ),
+        None,
+    ))
+}
+
+/// Two tags written on the same node, as byte ranges of the input.
+struct TagChain {
+    earlier: Range<usize>,
+    later: Range<usize>,
+    mark: Mark,
+}
+
+impl TagChain {
+    fn error(&self, input: &[u8]) -> Error {
+        error::fix_mark(
+            Error::custom(format_args!(
+                "found tag `{}` after tag `{}`; YAML allows only one tag per node",
+                lossy(&input[self.later.clone()]),
+                lossy(&input[self.earlier.clone()]),
+            )),
+            self.mark,
+            Path::Root,
+        )
+    }
+}
+
+/// Parses `input` up to its first error and returns the tag chain that
+/// caused it, if any.
+fn find_tag_chain(input: &[u8]) -> Option<TagChain> {
+    let mut parser = Parser::new(Cow::Borrowed(input));
+    loop {
+        match parser.parse_next_event() {
+            Ok((YamlEvent::StreamEnd, _)) => return None,
+            Ok(_) => {}
+            Err(err) => return tag_chain(input, err.mark()),
+        }
+    }
+}
+
+/// Returns the tag chain whose second tag starts at `mark`, where the
+/// parser stops on such input. An anchor between the two tags is skipped.
+fn tag_chain(input: &[u8], mark: Mark) -> Option<TagChain> {
+    let offset = Offsets::default().offset(input, mark);
+    if input.get(offset) != Some(&b'!') {
+        return None;
+    }
+    let later_end = input[offset..]
+        .iter()
+        .position(u8::is_ascii_whitespace)
+        .map_or(input.len(), |len| offset + len);
+    let mut end = offset;
+    loop {
+        while end > 0 && input[end - 1].is_ascii_whitespace() {
+            end -= 1;
+        }
+        let start = input[..end]
+            .iter()
+            .rposition(u8::is_ascii_whitespace)
+            .map_or(0, |pos| pos + 1);
+        match input[start..end].first() {
+            Some(b'&') => end = start,
+            Some(b'!') => {
+                return Some(TagChain {
+                    earlier: start..end,
+                    later: offset..later_end,
+                    mark,
+                })
+            }
+            _ => return None,
+        }
+    }
+}
+
+fn lossy(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// Returns the position of the first line break written as a lone carriage
+/// return.
+fn find_cr_line_ending(input: &[u8]) -> Option<Mark> {
+    fn chars(bytes: &[u8]) -> u64 {
+        bytes.iter().filter(|&&byte| byte & 0xC0 != 0x80).count() as u64
+    }
+
+    let mut line = 0;
+    let mut line_start = 0;
+    while let Some(next_line) = line_break_end(input, line_start) {
+        let end = next_line - 1;
+        if input[end] == b'\r' {
+            return Some(Mark::new(
+                chars(&input[..end]),
+                line,
+                chars(&input[line_start..end]),
+            ));
+        }
+        line += 1;
+        line_start = next_line;
+    }
+    None
+}
+
+/// Tracks the byte offset of the start of a line so that marks, whose line
+/// and column count characters, can be converted into byte offsets.
+///
+/// Marks must be converted in increasing order; the scan only moves forward.
+#[derive(Debug, Default)]
+struct Offsets {
+    line: u64,
+    line_start: usize,
+}
+
+impl Offsets {
+    fn offset(&mut self, input: &[u8], mark: Mark) -> usize {
+        while self.line < mark.line() {
+            match line_break_end(input, self.line_start) {
+                Some(next_line) => {
+                    self.line += 1;
+                    self.line_start = next_line;
+                }
+                None => return input.len(),
+            }
+        }
+        advance(input, self.line_start, mark.column())
+    }
+}
+
+/// The byte offset of the start of every line of an input, for converting
+/// between parser marks, byte offsets and locations in any order.
+#[derive(Debug)]
+pub(crate) struct LineIndex<'input> {
+    pub(crate) input: &'input [u8],
+    pub(crate) starts: Vec<usize>,
+}
+
+impl<'input> LineIndex<'input> {
+    pub(crate) fn new(input: &'input [u8]) -> Self {
+        let mut starts = vec![0];
+        while let Some(next) =
+            line_break_end(input, starts[starts.len() - 1])
+        {
+            starts.push(next);
+        }
+        LineIndex { input, starts }
+    }
+
+    /// Converts a parser mark into a byte offset of the input.
+    pub(crate) fn offset(&self, mark: Mark) -> usize {
+        match self.starts.get(mark.line() as usize) {
+            Some(&start) => advance(self.input, start, mark.column()),
+            None => self.input.len(),
+        }
+    }
+
+    /// Returns the index of the line holding the byte at `offset`.
+    pub(crate) fn line(&self, offset: usize) -> usize {
+        self.starts.partition_point(|&start| start <= offset) - 1
+    }
+
+    /// Returns the location of the byte at `offset`.
+    pub(crate) fn location(&self, offset: usize) -> Location {
+        fn chars(bytes: &[u8]) -> u64 {
+            bytes.iter().filter(|&&byte| byte & 0xC0 != 0x80).count()
+                as u64
+        }
+
+        let line = self.line(offset);
+        let start = self.starts[line];
+        Location::from_mark(Mark::new(
+            chars(&self.input[..offset]),
+            line as u64,
+            chars(&self.input[start..offset]),
+        ))
+    }
+}
+
+/// Returns the byte offset `columns` characters after `pos`.
+pub(crate) fn advance(
+    input: &[u8],
+    mut pos: usize,
+    columns: u64,
+) -> usize {
+    for _ in 0..columns {
+        match input.get(pos) {
+            Some(&byte) if byte < 0x80 => pos += 1,
+            Some(&byte) if byte >= 0xF0 => pos += 4,
+            Some(&byte) if byte >= 0xE0 => pos += 3,
+            Some(_) => pos += 2,
+            None => break,
+        }
+    }
+    pos.min(input.len())
+}
+
+/// Replaces the text of line `line` of `input`, or of the nearest line
+/// above it that has any, with spaces, keeping the position of every other
+/// character. Returns false if no line up to `line` has any text.
+fn blank_line(input: &mut [u8], line: usize) -> bool {
+    for line in (0..=line).rev() {
+        if let Some(text) = line_text(input, line) {
+            if has_text(&input[text.clone()]) {
+                input[text].fill(b' ');
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Returns the byte range of line `line` of `input`, without its line
+/// break.
+fn line_text(input: &[u8], line: usize) -> Option<Range<usize>> {
+    let mut start = 0;
+    for _ in 0..line {
+        start = line_break_end(input, start)?;
+    }
+    Some(start..line_end(input, start))
+}
+
+/// Returns whether `text` holds anything other than spaces and tabs.
+pub(crate) fn has_text(text: &[u8]) -> bool {
+    text.iter().any(|byte| !matches!(byte, b' ' | b'\t'))
+}
+
+/// Returns the position of the first line break at or after `pos`, or the
+/// end of the input.
+pub(crate) fn line_end(input: &[u8], mut pos: usize) -> usize {
+    while pos < input.len() {
+        match &input[pos..] {
+            [b'\r' | b'\n', ..]
+            | [0xC2, 0x85, ..]
+            | [0xE2, 0x80, 0xA8 | 0xA9, ..] => break,
+            _ => pos += 1,
+        }
+    }
+    pos
+}
+
+/// Returns the position just past the first line break at or after `pos`,
+/// counting the same line breaks as the parser.
+pub(crate) fn line_break_end(
+    input: &[u8],
+    mut pos: usize,
+) -> Option<usize> {
+    while pos < input.len() {
+        match &input[pos..] {
+            [b'\r', b'\n', ..] => return Some(pos + 2),
+            [b'\r' | b'\n', ..] => return Some(pos + 1),
+            [0xC2, 0x85, ..] => return Some(pos + 2),
+            [0xE2, 0x80, 0xA8 | 0xA9, ..] => return Some(pos + 3),
+            _ => pos += 1,
         }
     }
+    None
 }
@@ -0,0 +1,74 @@
+#[macro_export]
+/// A macro that generates a `#[serde(with = ...)]` module treating both a
+/// missing key and an explicit `null` as "use this default".
+///
+/// The macro expands to a module containing `serialize`, `deserialize` and
+/// `default` functions. Reference the module from `with` and its `default`
+/// function from `default`, so that absent keys are covered as well. The
+/// default expression is evaluated inside the generated module, which
+/// imports everything from the enclosing module, so the macro must be
+/// invoked at module level rather than inside a function body.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+///
+/// fn default_port() -> u16 {
+///     8080
+/// }
+///
+/// serde_yml::defaulted!(port: u16 = default_port());
+/// serde_yml::defaulted!(host: String = "localhost".to_owned());
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Server {
+///     #[serde(with = "host", default = "host::default")]
+///     host: String,
+///     #[serde(with = "port", default = "port::default")]
+///     port: u16,
+/// }
+///
+/// fn main() {
+///     let server: Server = serde_yml::from_str("port: ~\n").unwrap();
+///     assert_eq!(server.host, "localhost");
+///     assert_eq!(server.port, 8080);
+/// }
+/// ```
+macro_rules! defaulted {
+    ($vis:vis $name:ident : $ty:ty = $default:expr) => {
+        $vis mod $name {
+            #[allow(unused_imports)]
+            use super::*;
+
+            /// Returns the default value of the field.
+            pub fn default() -> $ty {
+                $default
+            }
+
+            /// Serializes the field unchanged.
+            pub fn serialize<S>(
+                value: &$ty,
+                serializer: S,
+            ) -> ::core::result::Result<S::Ok, S::Error>
+            where
+                S: ::serde::Serializer,
+            {
+                $crate::with::defaulted::serialize(value, serializer)
+            }
+
+            /// Deserializes the field, using the default for `null`.
+            pub fn deserialize<'de, D>(
+                deserializer: D,
+            ) -> ::core::result::Result<$ty, D::Error>
+            where
+                D: ::serde::Deserializer<'de>,
+            {
+                $crate::with::defaulted::deserialize_or_else(
+                    deserializer,
+                    default,
+                )
+            }
+        }
+    };
+}
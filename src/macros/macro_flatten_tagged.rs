@@ -0,0 +1,90 @@
+#[macro_export]
+/// A macro that generates a `#[serde(with = ...)]` module writing enums in
+/// the [`flatten_tagged`](crate::with::flatten_tagged) representation with
+/// custom keys.
+///
+/// The first key holds the variant name and the second one holds the data
+/// of variants whose data is not a mapping. Prefix the module name with
+/// `recursive` to apply the representation to every enum within the field,
+/// as [`flatten_tagged_recursive`](crate::with::flatten_tagged_recursive)
+/// does.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+///
+/// serde_yml::flatten_tagged!(by_kind: tag = "kind", content = "spec");
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// enum Resource {
+///     ConfigMap { data: Vec<String> },
+///     Replicas(u32),
+/// }
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Document {
+///     #[serde(with = "by_kind")]
+///     resource: Resource,
+/// }
+///
+/// fn main() {
+///     let yaml = "resource:\n  kind: Replicas\n  spec: 3\n";
+///     let document: Document = serde_yml::from_str(yaml).unwrap();
+///     assert_eq!(document.resource, Resource::Replicas(3));
+///     assert_eq!(serde_yml::to_string(&document).unwrap(), yaml);
+/// }
+/// ```
+macro_rules! flatten_tagged {
+    ($vis:vis recursive $name:ident : tag = $tag:expr, content = $content:expr) => {
+        $crate::flatten_tagged!(
+            @module $vis $name, flatten_tagged_recursive, $tag, $content
+        );
+    };
+    ($vis:vis $name:ident : tag = $tag:expr, content = $content:expr) => {
+        $crate::flatten_tagged!(
+            @module $vis $name, flatten_tagged, $tag, $content
+        );
+    };
+    (@module $vis:vis $name:ident, $with:ident, $tag:expr, $content:expr) => {
+        $vis mod $name {
+            /// Returns the keys of the representation.
+            pub fn keys() -> $crate::with::flatten_tagged::Keys {
+                $crate::with::flatten_tagged::Keys {
+                    tag: $tag,
+                    content: $content,
+                }
+            }
+
+            /// Serializes the field with the keys of this module.
+            pub fn serialize<T, S>(
+                value: &T,
+                serializer: S,
+            ) -> ::core::result::Result<S::Ok, S::Error>
+            where
+                T: ::serde::Serialize,
+                S: ::serde::Serializer,
+            {
+                $crate::with::$with::serialize_with_keys(
+                    value,
+                    serializer,
+                    keys(),
+                )
+            }
+
+            /// Deserializes the field with the keys of this module.
+            pub fn deserialize<'de, T, D>(
+                deserializer: D,
+            ) -> ::core::result::Result<T, D::Error>
+            where
+                T: ::serde::de::DeserializeOwned,
+                D: ::serde::Deserializer<'de>,
+            {
+                $crate::with::$with::deserialize_with_keys(
+                    deserializer,
+                    keys(),
+                )
+            }
+        }
+    };
+}
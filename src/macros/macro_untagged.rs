@@ -0,0 +1,139 @@
+#[macro_export]
+/// A macro that declares an enum deserialized like a
+/// `#[serde(untagged)]` one, trying each variant in turn, but without
+/// losing tags, locations and errors on the way.
+///
+/// Only tuple variants holding a single value are accepted; unit, struct
+/// and multi-field variants have to wrap their fields in a type of their
+/// own. The first variant whose type deserializes from the input is the
+/// one returned. Enums deriving `Deserialize` with `#[serde(untagged)]`
+/// are not affected by this macro and keep serde's error. With this crate's
+/// [`Deserializer`](crate::Deserializer), each variant is tried on the
+/// YAML events of the node themselves, and the error of an input that no
+/// variant matches lists the error of every attempt, as explained in the
+/// [`untagged`](crate::untagged) module. Other deserializers first buffer
+/// the input in a [`Value`](crate::Value).
+///
+/// Attributes written before the enum and its variants are kept, so the
+/// enum can still derive `Serialize`, with `#[serde(untagged)]` to write
+/// only the value of the variant.
+///
+/// # Examples
+///
+/// ```
+/// use serde::Serialize;
+///
+/// serde_yml::untagged! {
+///     #[derive(Serialize, PartialEq, Debug)]
+///     #[serde(untagged)]
+///     pub enum Port {
+///         Number(u16),
+///         Named(String),
+///     }
+/// }
+///
+/// fn main() {
+///     let ports: Vec<Port> = serde_yml::from_str("[80, http]").unwrap();
+///     assert_eq!(ports, [Port::Number(80), Port::Named("http".to_owned())]);
+///     assert_eq!(serde_yml::to_string(&ports).unwrap(), "- 80\n- http\n");
+/// }
+/// ```
+macro_rules! untagged {
+    (
+        $(#[$attr:meta])*
+        $vis:vis enum $name:ident {
+            $(
+                $(#[$variant_attr:meta])*
+                $variant:ident($ty:ty)
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        $vis enum $name {
+            $(
+                $(#[$variant_attr])*
+                $variant($ty),
+            )*
+        }
+
+        const _: () = {
+            struct __Visitor;
+
+            impl<'de> ::serde::de::Visitor<'de> for __Visitor {
+                type Value = $name;
+
+                fn expecting(
+                    &self,
+                    formatter: &mut ::core::fmt::Formatter<'_>,
+                ) -> ::core::fmt::Result {
+                    formatter.write_str(concat!(
+                        "untagged enum ",
+                        stringify!($name),
+                    ))
+                }
+
+                // Each element of the sequence is another attempt at the
+                // same node, one for each variant in order.
+                fn visit_seq<__A>(
+                    self,
+                    mut attempts: __A,
+                ) -> ::core::result::Result<$name, __A::Error>
+                where
+                    __A: ::serde::de::SeqAccess<'de>,
+                {
+                    $(
+                        if let ::core::result::Result::Ok(
+                            ::core::option::Option::Some(value),
+                        ) = attempts.next_element::<$ty>()
+                        {
+                            return ::core::result::Result::Ok(
+                                $name::$variant(value),
+                            );
+                        }
+                    )*
+                    ::core::result::Result::Err(
+                        ::serde::de::Error::custom(concat!(
+                            "data did not match any variant of untagged enum ",
+                            stringify!($name),
+                        )),
+                    )
+                }
+
+                fn visit_newtype_struct<__D>(
+                    self,
+                    deserializer: __D,
+                ) -> ::core::result::Result<$name, __D::Error>
+                where
+                    __D: ::serde::de::Deserializer<'de>,
+                {
+                    $crate::untagged::visit_buffered(
+                        deserializer,
+                        concat!(
+                            stringify!($name),
+                            $("::", stringify!($variant),)*
+                        ),
+                        self,
+                    )
+                }
+            }
+
+            impl<'de> ::serde::Deserialize<'de> for $name {
+                fn deserialize<__D>(
+                    deserializer: __D,
+                ) -> ::core::result::Result<Self, __D::Error>
+                where
+                    __D: ::serde::Deserializer<'de>,
+                {
+                    deserializer.deserialize_newtype_struct(
+                        concat!(
+                            "$serde_yml::private::Untagged::",
+                            stringify!($name),
+                            $("::", stringify!($variant),)*
+                        ),
+                        __Visitor,
+                    )
+                }
+            }
+        };
+    };
+}
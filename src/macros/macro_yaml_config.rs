@@ -0,0 +1,102 @@
+#[macro_export]
+/// A macro that generates a configuration struct with a default for each
+/// field, overrides from environment variables and a `load` function
+/// reading it from a YAML file.
+///
+/// Each field is written as `name: Type = default`, optionally followed by
+/// `, env "VARIABLE"`, and fields are separated by `;`. The struct gets
+/// `#[serde(default)]` and an implementation of `Default` built from the
+/// field defaults, so keys missing from the file keep their default. The
+/// struct must derive `Deserialize` itself, along with anything else it
+/// needs.
+///
+/// The generated functions are:
+///
+/// - `load(path)`, which reads the file with [`from_file`](crate::from_file)
+///   and then calls `apply_env`. Errors name the file and, for a value that
+///   does not fit its field, the line and column.
+/// - `apply_env(&mut self)`, which replaces each field that names an
+///   environment variable with the value of that variable, read as YAML
+///   with [`env::from_var`](crate::env::from_var), if it is set.
+///
+/// # Examples
+///
+/// ```
+/// use serde::Deserialize;
+///
+/// serde_yml::yaml_config! {
+///     #[derive(Deserialize, Debug)]
+///     pub struct AppConfig {
+///         pub host: String = "localhost".to_owned();
+///         pub port: u16 = 8080, env "SERDE_YML_DOC_APP_PORT";
+///         pub workers: usize = 4;
+///     }
+/// }
+///
+/// fn main() {
+///     let path = std::env::temp_dir().join("serde_yml_doc_yaml_config.yaml");
+///     std::fs::write(&path, "host: example.com\n").unwrap();
+///     std::env::set_var("SERDE_YML_DOC_APP_PORT", "9090");
+///
+///     let config = AppConfig::load(&path).unwrap();
+///     assert_eq!(config.host, "example.com");
+///     assert_eq!(config.port, 9090);
+///     assert_eq!(config.workers, 4);
+///     # std::fs::remove_file(&path).unwrap();
+/// }
+/// ```
+macro_rules! yaml_config {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$field_attr:meta])*
+                $field_vis:vis $field:ident : $ty:ty = $default:expr
+                $(, env $var:literal)?
+            );* $(;)?
+        }
+    ) => {
+        $(#[$attr])*
+        #[serde(default)]
+        $vis struct $name {
+            $(
+                $(#[$field_attr])*
+                $field_vis $field: $ty,
+            )*
+        }
+
+        impl ::std::default::Default for $name {
+            fn default() -> Self {
+                $name {
+                    $($field: $default,)*
+                }
+            }
+        }
+
+        impl $name {
+            /// Reads the configuration from the YAML file at `path`, then
+            /// applies the environment variable overrides.
+            pub fn load<P>(path: P) -> $crate::Result<Self>
+            where
+                P: ::std::convert::AsRef<::std::path::Path>,
+            {
+                let mut config: Self = $crate::from_file(path)?;
+                config.apply_env()?;
+                ::std::result::Result::Ok(config)
+            }
+
+            /// Replaces each field that names an environment variable
+            /// with the value of that variable, if it is set.
+            pub fn apply_env(&mut self) -> $crate::Result<()> {
+                $($(
+                    if let ::std::option::Option::Some(value) =
+                        $crate::env::from_var($var)?
+                    {
+                        self.$field = value;
+                    }
+                )?)*
+                ::std::result::Result::Ok(())
+            }
+        }
+    };
+}
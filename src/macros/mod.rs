@@ -1,7 +1,13 @@
+/// The `macro_defaulted` module contains the macro generating field adapters with custom defaults.
+pub mod macro_defaulted;
+
 /// The `directory_macros` module contains macros related to directory
 /// operations.
 pub mod macro_directory;
 
+/// The `macro_flatten_tagged` module contains the macro generating field adapters for flattened enums with custom keys.
+pub mod macro_flatten_tagged;
+
 /// The `file_macros` module contains macros related to file operations.
 pub mod macro_file;
 
@@ -19,3 +25,9 @@ pub mod macro_nested_enum_serde;
 
 /// The `replace_placeholder_macros` module contains macros related to replacing placeholders in a line with values from parameters.
 pub mod macro_replace_placeholder;
+
+/// The `macro_yaml_config` module contains the macro generating configuration structs with defaults and environment variable overrides.
+pub mod macro_yaml_config;
+
+/// The `macro_untagged` module contains the macro declaring untagged enums that try each variant on the same YAML events.
+pub mod macro_untagged;
@@ -1,21 +1,67 @@
 //! A YAML mapping and its iterator types.
 
-use crate::{private, Value};
-use indexmap::IndexMap;
-use serde::{Deserialize, Deserializer, Serialize};
-use std::{
+use crate::{lib::*, private, Value};
+use core::{
     cmp::Ordering,
-    collections::hash_map::DefaultHasher,
     fmt::{self, Display},
     hash::{Hash, Hasher},
     mem,
 };
+use indexmap::IndexMap;
+use serde::{Deserialize, Deserializer, Serialize};
+#[cfg(feature = "std")]
+use std::collections::hash_map::{DefaultHasher, RandomState};
+
+/// The hasher of the map underlying a [`Mapping`].
+#[cfg(feature = "std")]
+type State = RandomState;
+#[cfg(not(feature = "std"))]
+type State = core::hash::BuildHasherDefault<FnvHasher>;
+
+/// The hasher combining the entries of a [`Mapping`] into its hash, which
+/// must not depend on the instance.
+#[cfg(not(feature = "std"))]
+type DefaultHasher = FnvHasher;
+
+/// The 64-bit FNV-1a hash function, used by [`Mapping`] in place of the
+/// randomly seeded hasher of `std` when the `std` feature is disabled.
+#[cfg(not(feature = "std"))]
+#[derive(Clone, Copy, Debug)]
+pub struct FnvHasher(u64);
+
+#[cfg(not(feature = "std"))]
+impl FnvHasher {
+    fn new() -> Self {
+        FnvHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Default for FnvHasher {
+    fn default() -> Self {
+        FnvHasher::new()
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Hasher for FnvHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
 
 /// A YAML mapping in which the keys and values are both `serde_yml::Value`.
 #[derive(Clone, Default, Eq, PartialEq)]
 pub struct Mapping {
     /// The underlying map.
-    pub map: IndexMap<Value, Value>,
+    pub map: IndexMap<Value, Value, State>,
 }
 
 impl Mapping {
@@ -32,7 +78,10 @@ impl Mapping {
     #[inline]
     pub fn with_capacity(capacity: usize) -> Self {
         Mapping {
-            map: IndexMap::with_capacity(capacity),
+            map: IndexMap::with_capacity_and_hasher(
+                capacity,
+                State::default(),
+            ),
         }
     }
 
@@ -80,7 +129,19 @@ impl Mapping {
     /// Returns a reference to the value corresponding to the key.
     ///
     /// The key may be any borrowed form of the mapping's key type, but the ordering
-    /// on the borrowed form *must* match the key type's ordering.
+    /// on the borrowed form *must* match the key type's ordering. Besides a
+    /// `Value`, it may be a string, a boolean, an integer or a slice of
+    /// values standing for a sequence key.
+    ///
+    /// ```
+    /// use serde_yml::{Mapping, Value};
+    ///
+    /// let map: Mapping = serde_yml::from_str("5: five\ntrue: yes\n? [1, 2]\n: pair\n").unwrap();
+    /// assert_eq!(map.get(5).unwrap(), "five");
+    /// assert_eq!(map[true], "yes");
+    /// assert_eq!(map[&[Value::from(1), Value::from(2)][..]], "pair");
+    /// assert_eq!(map.get("5"), None);
+    /// ```
     #[inline]
     pub fn get<I: Index>(&self, index: I) -> Option<&Value> {
         index.index_into(self)
@@ -302,6 +363,87 @@ impl Mapping {
             iter: self.map.into_values(),
         }
     }
+
+    /// Returns an iterator over the key-value pairs of the mapping in the
+    /// given order, whatever order they were inserted in.
+    ///
+    /// The iterator element type is `(&'a Value, &'a Value)`. Iterating in
+    /// a sorted order is meant for output that must not change between
+    /// runs, such as snapshot tests.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_yml::mapping::EntryOrder;
+    /// use serde_yml::{Mapping, Value};
+    ///
+    /// let mapping: Mapping = serde_yml::from_str("b: 1\n10: 2\n9: 3\n").unwrap();
+    /// let keys = |order| {
+    ///     mapping
+    ///         .sorted_iter(order)
+    ///         .map(|(key, _)| key.clone())
+    ///         .collect::<Vec<Value>>()
+    /// };
+    /// assert_eq!(keys(EntryOrder::Reversed), [Value::from(9), Value::from(10), Value::from("b")]);
+    /// assert_eq!(keys(EntryOrder::Key), [Value::from(9), Value::from(10), Value::from("b")]);
+    /// assert_eq!(keys(EntryOrder::KeyText), [Value::from(10), Value::from(9), Value::from("b")]);
+    /// ```
+    pub fn sorted_iter(&self, order: EntryOrder) -> SortedIter<'_> {
+        let mut entries = Vec::from_iter(self);
+        match order {
+            EntryOrder::Insertion => {}
+            EntryOrder::Reversed => entries.reverse(),
+            // Keys are distinct within a mapping, so the sorted order is
+            // unique.
+            EntryOrder::Key => {
+                entries.sort_unstable_by_key(|&(key, _)| key);
+            }
+            EntryOrder::KeyText => {
+                entries.sort_by_cached_key(|&(key, _)| {
+                    (key_text(key), key)
+                });
+            }
+        }
+        SortedIter {
+            iter: entries.into_iter(),
+        }
+    }
+
+    /// Sorts the entries of the mapping by their keys, in the order of
+    /// [`EntryOrder::Key`].
+    pub fn sort_keys(&mut self) {
+        self.map.sort_keys();
+    }
+}
+
+/// The order in which [`Mapping::sorted_iter`] yields entries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EntryOrder {
+    /// The order of the mapping, in which entries were inserted.
+    Insertion,
+    /// The reverse of the order of the mapping.
+    Reversed,
+    /// Sorted by key in the order of [`Value`]: null, booleans, numbers by
+    /// value, strings by their bytes, then sequences, mappings and tagged
+    /// values.
+    Key,
+    /// Sorted by the text of the keys: strings by their bytes and other
+    /// keys by the YAML they are written as, so that `10` comes before `9`
+    /// and numbers mix with strings. Keys with the same text, such as `1`
+    /// and `'1'`, are sorted in the order of [`Value`].
+    KeyText,
+}
+
+/// Returns the text [`EntryOrder::KeyText`] sorts `key` by.
+fn key_text(key: &Value) -> String {
+    match key.untag_ref() {
+        Value::String(key) => key.clone(),
+        key => {
+            let mut text = key.to_string();
+            text.truncate(text.trim_end().len());
+            text
+        }
+    }
 }
 
 /// A trait for types that can be used to index into a `serde_yml::Mapping`.
@@ -533,6 +675,148 @@ where
     }
 }
 
+// Implements `Index` for scalar types by looking up the `Value` they
+// convert into, which does not allocate.
+macro_rules! index_by_value {
+    ($($ty:ty)*) => {
+        $(
+            impl Index for $ty {
+                fn is_key_into(&self, v: &Mapping) -> bool {
+                    Value::from(*self).is_key_into(v)
+                }
+                fn index_into<'a>(&self, v: &'a Mapping) -> Option<&'a Value> {
+                    Value::from(*self).index_into(v)
+                }
+                fn index_into_mut<'a>(
+                    &self,
+                    v: &'a mut Mapping,
+                ) -> Option<&'a mut Value> {
+                    Value::from(*self).index_into_mut(v)
+                }
+                fn swap_remove_from(&self, v: &mut Mapping) -> Option<Value> {
+                    Value::from(*self).swap_remove_from(v)
+                }
+                fn swap_remove_entry_from(
+                    &self,
+                    v: &mut Mapping,
+                ) -> Option<(Value, Value)> {
+                    Value::from(*self).swap_remove_entry_from(v)
+                }
+                fn shift_remove_from(&self, v: &mut Mapping) -> Option<Value> {
+                    Value::from(*self).shift_remove_from(v)
+                }
+                fn shift_remove_entry_from(
+                    &self,
+                    v: &mut Mapping,
+                ) -> Option<(Value, Value)> {
+                    Value::from(*self).shift_remove_entry_from(v)
+                }
+            }
+        )*
+    };
+}
+
+// Booleans and integers look up keys written as YAML booleans and integers,
+// such as `true: on` or `5: five`.
+index_by_value! {
+    bool
+    i8 i16 i32 i64 isize
+    u8 u16 u32 u64 usize
+}
+
+/// A newtype wrapper for `&[Value]` that implements
+/// `indexmap::Equivalent<Value>` to allow indexing into a `Mapping` with
+/// sequence keys, such as `? [a, b]`, without building a `Value`.
+struct HashLikeSequence<'a>(&'a [Value]);
+
+impl indexmap::Equivalent<Value> for HashLikeSequence<'_> {
+    fn equivalent(&self, key: &Value) -> bool {
+        match key {
+            Value::Sequence(sequence) => self.0 == sequence.as_slice(),
+            _ => false,
+        }
+    }
+}
+
+// NOTE: This impl must be consistent with Value's Hash impl.
+impl Hash for HashLikeSequence<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        const SEQUENCE: Value = Value::Sequence(Vec::new());
+        mem::discriminant(&SEQUENCE).hash(state);
+        self.0.hash(state);
+    }
+}
+
+/// Implements the `Index` trait for `[Value]`, allowing a slice of values
+/// to look up a key that is a sequence of the same values.
+impl Index for [Value] {
+    fn is_key_into(&self, v: &Mapping) -> bool {
+        v.map.contains_key(&HashLikeSequence(self))
+    }
+    fn index_into<'a>(&self, v: &'a Mapping) -> Option<&'a Value> {
+        v.map.get(&HashLikeSequence(self))
+    }
+    fn index_into_mut<'a>(
+        &self,
+        v: &'a mut Mapping,
+    ) -> Option<&'a mut Value> {
+        v.map.get_mut(&HashLikeSequence(self))
+    }
+    fn swap_remove_from(&self, v: &mut Mapping) -> Option<Value> {
+        v.map.swap_remove(&HashLikeSequence(self))
+    }
+    fn swap_remove_entry_from(
+        &self,
+        v: &mut Mapping,
+    ) -> Option<(Value, Value)> {
+        v.map.swap_remove_entry(&HashLikeSequence(self))
+    }
+    fn shift_remove_from(&self, v: &mut Mapping) -> Option<Value> {
+        v.map.shift_remove(&HashLikeSequence(self))
+    }
+    fn shift_remove_entry_from(
+        &self,
+        v: &mut Mapping,
+    ) -> Option<(Value, Value)> {
+        v.map.shift_remove_entry(&HashLikeSequence(self))
+    }
+}
+
+/// Implements the `Index` trait for `Vec<Value>`, allowing owned sequences
+/// to be used as keys for indexing into a `Mapping`.
+impl Index for Vec<Value> {
+    fn is_key_into(&self, v: &Mapping) -> bool {
+        self.as_slice().is_key_into(v)
+    }
+    fn index_into<'a>(&self, v: &'a Mapping) -> Option<&'a Value> {
+        self.as_slice().index_into(v)
+    }
+    fn index_into_mut<'a>(
+        &self,
+        v: &'a mut Mapping,
+    ) -> Option<&'a mut Value> {
+        self.as_slice().index_into_mut(v)
+    }
+    fn swap_remove_from(&self, v: &mut Mapping) -> Option<Value> {
+        self.as_slice().swap_remove_from(v)
+    }
+    fn swap_remove_entry_from(
+        &self,
+        v: &mut Mapping,
+    ) -> Option<(Value, Value)> {
+        self.as_slice().swap_remove_entry_from(v)
+    }
+    fn shift_remove_from(&self, v: &mut Mapping) -> Option<Value> {
+        self.as_slice().shift_remove_from(v)
+    }
+    fn shift_remove_entry_from(
+        &self,
+        v: &mut Mapping,
+    ) -> Option<(Value, Value)> {
+        self.as_slice().shift_remove_entry_from(v)
+    }
+}
+
 #[allow(clippy::derived_hash_with_manual_eq)]
 /// `Mapping` is hashable if its keys and values are hashable.
 impl Hash for Mapping {
@@ -549,98 +833,27 @@ impl Hash for Mapping {
     }
 }
 
-/// `Mapping` is ordered if its keys and values are ordered.
-impl PartialOrd for Mapping {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+/// Mappings are ordered by their entries taken in key order, consistently
+/// with their equality, which ignores the order of entries.
+impl Ord for Mapping {
+    fn cmp(&self, other: &Self) -> Ordering {
         let mut self_entries = Vec::from_iter(self);
         let mut other_entries = Vec::from_iter(other);
+        // Keys are distinct within a mapping, so the sorted order is unique.
+        self_entries.sort_unstable_by_key(|&(key, _)| key);
+        other_entries.sort_unstable_by_key(|&(key, _)| key);
+        self_entries.cmp(&other_entries)
+    }
+}
 
-        // Sort in an arbitrary order that is consistent with Value's PartialOrd
-        // impl.
-        fn total_cmp(a: &Value, b: &Value) -> Ordering {
-            match (a, b) {
-                (Value::Null, Value::Null) => Ordering::Equal,
-                (Value::Null, _) => Ordering::Less,
-                (_, Value::Null) => Ordering::Greater,
-
-                (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
-                (Value::Bool(_), _) => Ordering::Less,
-                (_, Value::Bool(_)) => Ordering::Greater,
-
-                (Value::Number(a), Value::Number(b)) => a.total_cmp(b),
-                (Value::Number(_), _) => Ordering::Less,
-                (_, Value::Number(_)) => Ordering::Greater,
-
-                (Value::String(a), Value::String(b)) => a.cmp(b),
-                (Value::String(_), _) => Ordering::Less,
-                (_, Value::String(_)) => Ordering::Greater,
-
-                (Value::Sequence(a), Value::Sequence(b)) => {
-                    iter_cmp_by(a, b, total_cmp)
-                }
-                (Value::Sequence(_), _) => Ordering::Less,
-                (_, Value::Sequence(_)) => Ordering::Greater,
-
-                (Value::Mapping(a), Value::Mapping(b)) => {
-                    iter_cmp_by(a, b, |(ak, av), (bk, bv)| {
-                        total_cmp(ak, bk)
-                            .then_with(|| total_cmp(av, bv))
-                    })
-                }
-                (Value::Mapping(_), _) => Ordering::Less,
-                (_, Value::Mapping(_)) => Ordering::Greater,
-
-                (Value::Tagged(a), Value::Tagged(b)) => a
-                    .tag
-                    .cmp(&b.tag)
-                    .then_with(|| total_cmp(&a.value, &b.value)),
-            }
-        }
-
-        fn iter_cmp_by<I, F>(this: I, other: I, mut cmp: F) -> Ordering
-        where
-            I: IntoIterator,
-            F: FnMut(I::Item, I::Item) -> Ordering,
-        {
-            let mut this = this.into_iter();
-            let mut other = other.into_iter();
-
-            loop {
-                let x = match this.next() {
-                    None => {
-                        if other.next().is_none() {
-                            return Ordering::Equal;
-                        } else {
-                            return Ordering::Less;
-                        }
-                    }
-                    Some(val) => val,
-                };
-
-                let y = match other.next() {
-                    None => return Ordering::Greater,
-                    Some(val) => val,
-                };
-
-                match cmp(x, y) {
-                    Ordering::Equal => {}
-                    non_eq => return non_eq,
-                }
-            }
-        }
-
-        // While sorting by map key, we get to assume that no two keys are
-        // equal, otherwise they wouldn't both be in the map. This is not a safe
-        // assumption outside of this situation.
-        let total_cmp = |&(a, _): &_, &(b, _): &_| total_cmp(a, b);
-        self_entries.sort_by(total_cmp);
-        other_entries.sort_by(total_cmp);
-        self_entries.partial_cmp(&other_entries)
+impl PartialOrd for Mapping {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
 /// `Mapping` is ordered if its keys and values are ordered.
-impl<I> std::ops::Index<I> for Mapping
+impl<I> core::ops::Index<I> for Mapping
 where
     I: Index,
 {
@@ -654,7 +867,7 @@ where
 }
 
 /// `Mapping` is ordered if its keys and values are ordered.
-impl<I> std::ops::IndexMut<I> for Mapping
+impl<I> core::ops::IndexMut<I> for Mapping
 where
     I: Index,
 {
@@ -768,6 +981,15 @@ impl IntoIterator for Mapping {
     }
 }
 
+/// Iterator over `&serde_yml::Mapping` in a chosen order, returned by
+/// [`Mapping::sorted_iter`].
+#[derive(Debug)]
+pub struct SortedIter<'a> {
+    iter: vec::IntoIter<(&'a Value, &'a Value)>,
+}
+
+delegate_iterator!((SortedIter<'a>) => (&'a Value, &'a Value));
+
 /// Iterator of the keys of a `&serde_yml::Mapping`.
 #[derive(Debug)]
 pub struct Keys<'a> {
@@ -1051,3 +1273,19 @@ impl Display for DuplicateKeyError<'_> {
         }
     }
 }
+
+/// Builds the error for a mapping that holds `key` twice, worded as the
+/// `Deserialize` implementation of `Mapping` words it.
+pub(crate) fn duplicate_key_error<E>(key: Value) -> E
+where
+    E: serde::de::Error,
+{
+    let mut mapping = Mapping::new();
+    let _ = mapping.insert(key.clone(), Value::Null);
+    match mapping.entry(key) {
+        Entry::Occupied(entry) => {
+            E::custom(DuplicateKeyError { entry })
+        }
+        Entry::Vacant(_) => E::custom("duplicate entry in YAML map"),
+    }
+}
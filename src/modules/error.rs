@@ -1,22 +1,26 @@
 use crate::{
+    io,
+    lib::*,
     libyml::{emitter, error as libyml},
+    mapping::DuplicateKeyError,
     modules::path::Path,
 };
-use serde::{de, ser};
-use std::{
-    error::Error as StdError,
+use alloc::{string, sync::Arc};
+use core::{
     fmt::{self, Debug, Display},
-    io, result, string,
-    sync::Arc,
+    result, slice,
 };
+use serde::{de, ser};
 
 /// Represents a position in the YAML input.
 #[derive(Debug)]
 pub struct Pos {
-    /// The mark representing the position.
-    mark: libyml::Mark,
-    /// The path to the position.
-    path: String,
+    /// The mark representing the position, if the error has a place in
+    /// the input.
+    mark: Option<libyml::Mark>,
+    /// The path to the position, or `None` if it goes through a mapping
+    /// key that is not a string.
+    path: Option<crate::path::Path>,
 }
 
 /// The input location where an error occurred.
@@ -48,7 +52,7 @@ impl Location {
 
     // This function is intended for internal use only to maintain decoupling with the yaml crate.
     #[doc(hidden)]
-    fn from_mark(mark: libyml::Mark) -> Self {
+    pub(crate) fn from_mark(mark: libyml::Mark) -> Self {
         Location {
             index: mark.index() as usize,
             // `line` and `column` returned from libyml are 0-indexed but all error messages add +1 to this value.
@@ -67,6 +71,80 @@ pub struct Error(Box<ErrorImpl>);
 /// Alias for a `Result` with the error type `serde_yml::Error`.
 pub type Result<T> = result::Result<T, Error>;
 
+/// The category of an [`Error`], returned by [`Error::kind`].
+///
+/// Kinds can be matched on instead of the text of the error, which may
+/// change between releases. More kinds may be added, so matches need a
+/// wildcard arm.
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_yml::ErrorKind;
+///
+/// #[derive(Deserialize, Debug)]
+/// #[serde(deny_unknown_fields)]
+/// struct Config {
+///     port: u16,
+/// }
+///
+/// let err = serde_yml::from_str::<Config>("port: 80\nhost: a\n").unwrap_err();
+/// assert_eq!(err.kind(), ErrorKind::UnknownField);
+///
+/// let err = serde_yml::from_str::<Config>("port: [80]\n").unwrap_err();
+/// assert_eq!(err.kind(), ErrorKind::InvalidType);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The input is not well-formed YAML.
+    Parse,
+    /// Reading or writing failed.
+    Io,
+    /// The output is not valid UTF-8.
+    Utf8,
+    /// The input ended before a value was complete.
+    EndOfStream,
+    /// The input holds more than one document where one was expected.
+    MoreThanOneDocument,
+    /// A value has a different type than expected, such as a sequence
+    /// where a number was expected.
+    InvalidType,
+    /// A value has the expected type but not an accepted value.
+    InvalidValue,
+    /// A sequence or mapping has a different number of entries than
+    /// expected.
+    InvalidLength,
+    /// A mapping has a field the struct does not know.
+    UnknownField,
+    /// An enum variant is not one of the variants of the enum.
+    UnknownVariant,
+    /// A field of a struct is missing.
+    MissingField,
+    /// A field of a struct is given twice.
+    DuplicateField,
+    /// A key is given twice in a mapping.
+    DuplicateKey,
+    /// Values are nested deeper than the recursion limit.
+    RecursionLimit,
+    /// Aliases expand to more nodes than the repetition limit.
+    AliasLimit,
+    /// An alias refers to an anchor that is not defined.
+    UnknownAnchor,
+    /// An alias refers to a node that contains it.
+    AliasCycle,
+    /// A merge key (`<<`) is given something other than mappings.
+    Merge,
+    /// The value cannot be written as or read from YAML, such as bytes or
+    /// nested enums.
+    Unsupported,
+    /// Any other error, such as one raised by a `Serialize` or
+    /// `Deserialize` implementation with a message of its own.
+    Custom,
+    /// Several errors found in one pass over a document, listed by
+    /// [`Error::errors`].
+    Multiple,
+}
+
 /// The internal representation of an error.
 ///
 /// This enum represents various errors that can occur during YAML serialization or deserialization,
@@ -75,6 +153,9 @@ pub type Result<T> = result::Result<T, Error>;
 pub enum ErrorImpl {
     /// A generic error message with an optional position.
     Message(String, Option<Pos>),
+    /// An error about the data being deserialized, of the given kind, with
+    /// its message and an optional position.
+    Invalid(ErrorKind, String, Option<Pos>),
     /// An error originating from the `libyml` library.
     Libyml(libyml::Error),
     /// An I/O error.
@@ -93,6 +174,10 @@ pub enum ErrorImpl {
     BytesUnsupported,
     /// An error indicating that an unknown anchor was encountered.
     UnknownAnchor(libyml::Mark),
+    /// An error indicating that an alias refers to a node that contains it,
+    /// such as `*x` in `&x {b: *x}`, with the name of the anchor and the
+    /// position of the alias.
+    AliasCycle(String, libyml::Mark),
     /// An error indicating that serializing a nested enum is not supported.
     SerializeNestedEnum,
     /// An error indicating that a scalar value was encountered in a merge operation.
@@ -107,6 +192,11 @@ pub enum ErrorImpl {
     EmptyTag,
     /// An error indicating that parsing a number failed.
     FailedToParseNumber,
+    /// Several errors found in one pass over a document, in the order
+    /// they were found.
+    Multiple(Vec<Error>),
+    /// An error that occurred while reading or writing the named file.
+    File(String, Error),
     /// A shared error implementation.
     Shared(Arc<ErrorImpl>),
 }
@@ -114,7 +204,7 @@ pub enum ErrorImpl {
 impl Display for ErrorImpl {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ErrorImpl::Message(msg, _) => write!(f, "Error: {}", msg),
+            ErrorImpl::Message(msg, _) | ErrorImpl::Invalid(_, msg, _) => write!(f, "Error: {}", msg),
             ErrorImpl::Libyml(_) => write!(f, "Error: An error occurred in the Libyml library"),
             ErrorImpl::IoError(err) => write!(f, "I/O Error: {}", err),
             ErrorImpl::FromUtf8(err) => write!(f, "UTF-8 Conversion Error: {}", err),
@@ -124,6 +214,7 @@ impl Display for ErrorImpl {
             ErrorImpl::RepetitionLimitExceeded => write!(f, "Repetition Limit Exceeded: The repetition limit was exceeded while parsing the YAML"),
             ErrorImpl::BytesUnsupported => write!(f, "Unsupported Bytes Error: Serialization and deserialization of bytes in YAML is not implemented"),
             ErrorImpl::UnknownAnchor(_) => write!(f, "Unknown Anchor Error: An unknown anchor was encountered in the YAML"),
+            ErrorImpl::AliasCycle(name, _) => write!(f, "Alias Cycle Error: The alias *{} refers to a node that contains it", name),
             ErrorImpl::SerializeNestedEnum => write!(f, "Nested Enum Serialization Error: Serializing nested enums in YAML is not supported"),
             ErrorImpl::ScalarInMerge => write!(f, "Invalid Merge Error: Expected a mapping or list of mappings for merging, but found a scalar value"),
             ErrorImpl::TaggedInMerge => write!(f, "Invalid Merge Error: Unexpected tagged value encountered in a merge operation"),
@@ -131,26 +222,123 @@ impl Display for ErrorImpl {
             ErrorImpl::SequenceInMergeElement => write!(f, "Invalid Merge Element Error: Expected a mapping for merging, but found a sequence"),
             ErrorImpl::EmptyTag => write!(f, "Empty Tag Error: Empty YAML tags are not allowed"),
             ErrorImpl::FailedToParseNumber => write!(f, "Number Parsing Error: Failed to parse the YAML number"),
+            ErrorImpl::Multiple(errors) => write!(f, "Multiple Errors: {} errors were found", errors.len()),
+            ErrorImpl::File(file, err) => write!(f, "{}: {}", file, err),
             ErrorImpl::Shared(_) => write!(f, "Shared Error: An error occurred in the shared error implementation"),
         }
     }
 }
 
 impl Error {
+    /// Returns the category of the error.
+    pub fn kind(&self) -> ErrorKind {
+        self.0.kind()
+    }
+
     /// Returns the I/O error that caused this error, if available.
     pub fn io_error(&self) -> Option<&io::Error> {
-        if let ErrorImpl::IoError(err) = &*self.0 {
-            Some(err)
-        } else {
-            None
+        match &*self.0 {
+            ErrorImpl::IoError(err) => Some(err),
+            ErrorImpl::File(_, err) => err.io_error(),
+            _ => None,
         }
     }
 
+    /// Returns the path of the file the error occurred in, as it was
+    /// given to a function such as [`from_file`](crate::from_file), if
+    /// the error comes from one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let err = serde_yml::from_file::<serde_yml::Value, _>("missing.yaml")
+    ///     .unwrap_err();
+    /// assert_eq!(err.file(), Some("missing.yaml"));
+    /// assert!(err.io_error().is_some());
+    /// ```
+    pub fn file(&self) -> Option<&str> {
+        self.0.file()
+    }
+
+    /// Returns the errors found in one pass over a document when
+    /// [`DeserializerOptions::collect_errors`] is set, or this error alone
+    /// otherwise.
+    ///
+    /// [`DeserializerOptions::collect_errors`]: crate::de::DeserializerOptions::collect_errors
+    pub fn errors(&self) -> &[Error] {
+        self.0.multiple().unwrap_or_else(|| slice::from_ref(self))
+    }
+
     /// Returns the location where the error occurred, if available.
     pub fn location(&self) -> Option<Location> {
         self.0.location()
     }
 
+    /// Renders the error with an excerpt of `source`, the input it was
+    /// read from, marking where it occurred.
+    ///
+    /// The excerpt shows the line of the error with a `^` under its
+    /// column. A parser error that names the node it was reading, such as
+    /// the flow sequence left open, also shows the line of that node with
+    /// a `-` under it and what was being read. An error without a location
+    /// renders as its message alone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let yaml = "name: demo\nlist: [1, 2\n";
+    /// let err = serde_yml::from_str::<serde_yml::Value>(yaml).unwrap_err();
+    /// assert_eq!(
+    ///     err.render_snippet(yaml),
+    ///     "\
+    /// error: did not find expected ',' or ']'
+    ///  --> 3:1
+    ///   |
+    /// 2 | list: [1, 2
+    ///   |       - while parsing a flow sequence
+    /// 3 |
+    ///   | ^
+    /// ",
+    /// );
+    /// ```
+    ///
+    /// An error holding several [`errors`](Error::errors) renders as the
+    /// excerpts of each in turn.
+    pub fn render_snippet(&self, source: &str) -> String {
+        self.errors()
+            .iter()
+            .map(|error| {
+                Snippet {
+                    error: &error.0,
+                    source,
+                }
+                .to_string()
+            })
+            .collect()
+    }
+
+    /// Returns the location of the node the parser was reading when it
+    /// failed, such as the opening quote of an unterminated string, if the
+    /// error comes from the parser and names one.
+    pub(crate) fn context_location(&self) -> Option<Location> {
+        self.0.context_mark().map(Location::from_mark)
+    }
+
+    /// Returns the path to the value that caused the error, if the error
+    /// occurred while deserializing a value within a document.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_yml::path::Path;
+    ///
+    /// let err = serde_yml::from_str::<Vec<(u8, bool)>>("- [1, true]\n- [2, maybe]\n").unwrap_err();
+    /// assert_eq!(err.path().map(Path::to_string).as_deref(), Some("[1][1]"));
+    /// ```
+    pub fn path(&self) -> Option<&crate::path::Path> {
+        self.0.path()
+    }
+
     /// Returns a shared reference to the internal error representation.
     ///
     /// This method is useful when you need to share an error between multiple threads or for
@@ -164,6 +352,25 @@ impl Error {
     }
 }
 
+/// Combines the errors collected while deserializing a document with the
+/// result of deserializing it.
+pub(crate) fn collect<T>(
+    mut errors: Vec<Error>,
+    result: Result<T>,
+) -> Result<T> {
+    if errors.is_empty() {
+        return result;
+    }
+    if let Err(err) = result {
+        errors.push(err);
+    }
+    if errors.len() == 1 {
+        Err(errors.remove(0))
+    } else {
+        Err(new(ErrorImpl::Multiple(errors)))
+    }
+}
+
 /// Creates a new `Error` from the given `ErrorImpl`.
 pub fn new(inner: ErrorImpl) -> Error {
     Error(Box::new(inner))
@@ -174,16 +381,60 @@ pub fn shared(shared: Arc<ErrorImpl>) -> Error {
     Error(Box::new(ErrorImpl::Shared(shared)))
 }
 
+/// Names the file an error occurred in, unless it already names one.
+#[cfg(feature = "std")]
+pub(crate) fn in_file(error: Error, file: &std::path::Path) -> Error {
+    if error.file().is_some() {
+        return error;
+    }
+    new(ErrorImpl::File(file.display().to_string(), error))
+}
+
+/// Moves an error about a value of a document assembled from several
+/// sources to `mark`, its position in the source it came from, or drops
+/// its position if it is not known.
+#[cfg(feature = "std")]
+pub(crate) fn relocate(
+    mut error: Error,
+    mark: Option<libyml::Mark>,
+) -> Error {
+    if let ErrorImpl::Message(_, Some(pos))
+    | ErrorImpl::Invalid(_, _, Some(pos)) = error.0.as_mut()
+    {
+        pos.mark = mark;
+    }
+    error
+}
+
 /// Fixes the mark and path in an error.
 pub fn fix_mark(
     mut error: Error,
     mark: libyml::Mark,
     path: Path<'_>,
 ) -> Error {
-    if let ErrorImpl::Message(_, none @ None) = error.0.as_mut() {
+    if let ErrorImpl::Message(_, none @ None)
+    | ErrorImpl::Invalid(_, _, none @ None) = error.0.as_mut()
+    {
         *none = Some(Pos {
-            mark,
-            path: path.to_string(),
+            mark: Some(mark),
+            path: crate::path::Path::try_from(&path).ok(),
+        });
+    }
+    error
+}
+
+/// Sets the path in an error about a value that has no place in the
+/// input, such as one looked up in a `Value`.
+pub(crate) fn with_path(
+    mut error: Error,
+    path: &crate::path::Path,
+) -> Error {
+    if let ErrorImpl::Message(_, none @ None)
+    | ErrorImpl::Invalid(_, _, none @ None) = error.0.as_mut()
+    {
+        *none = Some(Pos {
+            mark: None,
+            path: Some(path.clone()),
         });
     }
     error
@@ -232,11 +483,361 @@ impl ser::Error for Error {
 
 impl de::Error for Error {
     fn custom<T: Display>(msg: T) -> Self {
+        // `Mapping` reports its duplicate keys through `custom`, as its
+        // `Deserialize` implementation works with any deserializer.
+        if core::any::type_name::<T>()
+            == core::any::type_name::<DuplicateKeyError<'static>>()
+        {
+            return invalid(ErrorKind::DuplicateKey, msg);
+        }
         Error(Box::new(ErrorImpl::Message(msg.to_string(), None)))
     }
+
+    fn invalid_type(
+        unexp: de::Unexpected<'_>,
+        exp: &dyn de::Expected,
+    ) -> Self {
+        invalid(
+            ErrorKind::InvalidType,
+            format_args!("invalid type: {}, expected {}", unexp, exp),
+        )
+    }
+
+    fn invalid_value(
+        unexp: de::Unexpected<'_>,
+        exp: &dyn de::Expected,
+    ) -> Self {
+        invalid(
+            ErrorKind::InvalidValue,
+            format_args!("invalid value: {}, expected {}", unexp, exp),
+        )
+    }
+
+    fn invalid_length(len: usize, exp: &dyn de::Expected) -> Self {
+        invalid(
+            ErrorKind::InvalidLength,
+            format_args!("invalid length {}, expected {}", len, exp),
+        )
+    }
+
+    fn unknown_variant(
+        variant: &str,
+        expected: &'static [&'static str],
+    ) -> Self {
+        unknown_name(ErrorKind::UnknownVariant, variant, expected)
+    }
+
+    fn unknown_field(
+        field: &str,
+        expected: &'static [&'static str],
+    ) -> Self {
+        unknown_name(ErrorKind::UnknownField, field, expected)
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        invalid(
+            ErrorKind::MissingField,
+            format_args!("missing field `{}`", field),
+        )
+    }
+
+    fn duplicate_field(field: &'static str) -> Self {
+        invalid(
+            ErrorKind::DuplicateField,
+            format_args!("duplicate field `{}`", field),
+        )
+    }
+}
+
+/// Builds an error about the data being deserialized.
+pub(crate) fn invalid<T: Display>(kind: ErrorKind, msg: T) -> Error {
+    Error(Box::new(ErrorImpl::Invalid(kind, msg.to_string(), None)))
+}
+
+/// Builds the error for an unknown variant or field, with the same wording
+/// as serde's default and a suggestion when one of the expected names is
+/// close to the one that was found.
+fn unknown_name(
+    kind: ErrorKind,
+    name: &str,
+    expected: &'static [&'static str],
+) -> Error {
+    let what = if kind == ErrorKind::UnknownVariant {
+        "variant"
+    } else {
+        "field"
+    };
+    let mut message = if expected.is_empty() {
+        format!("unknown {} `{}`, there are no {}s", what, name, what)
+    } else {
+        format!(
+            "unknown {} `{}`, expected {}",
+            what,
+            name,
+            OneOf(expected)
+        )
+    };
+    if expected.len() > 1 {
+        if let Some(suggestion) = did_you_mean(name, expected) {
+            message
+                .push_str(&format!("; did you mean `{}`?", suggestion));
+        }
+    }
+    invalid(kind, message)
+}
+
+/// Returns the candidate closest to `name` by edit distance, if it is close
+/// enough to be a plausible typo.
+pub(crate) fn did_you_mean<'a>(
+    name: &str,
+    candidates: &[&'a str],
+) -> Option<&'a str> {
+    let limit = (name.chars().count() / 3).max(1);
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Computes the edit distance between two strings, counting insertions,
+/// deletions, substitutions and transpositions of adjacent characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let width = b.len() + 1;
+    let mut d: Vec<usize> = vec![0; (a.len() + 1) * width];
+    for i in 0..=a.len() {
+        d[i * width] = i;
+    }
+    for (j, cell) in d.iter_mut().enumerate().take(width) {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (d[(i - 1) * width + j] + 1)
+                .min(d[i * width + j - 1] + 1)
+                .min(d[(i - 1) * width + j - 1] + cost);
+            if i > 1
+                && j > 1
+                && a[i - 1] == b[j - 2]
+                && a[i - 2] == b[j - 1]
+            {
+                best = best.min(d[(i - 2) * width + j - 2] + 1);
+            }
+            d[i * width + j] = best;
+        }
+    }
+    d[a.len() * width + b.len()]
+}
+
+/// Formats a list of expected names the way serde does.
+pub(crate) struct OneOf<'a>(pub(crate) &'a [&'a str]);
+
+impl Display for OneOf<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            [] => Ok(()),
+            [only] => write!(f, "`{}`", only),
+            [first, second] => write!(f, "`{}` or `{}`", first, second),
+            names => {
+                f.write_str("one of ")?;
+                for (i, name) in names.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "`{}`", name)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Formats the path an error message starts with, as `a.b.\[1\]`.
+struct Prefix<'a>(&'a [crate::path::Segment]);
+
+impl Display for Prefix<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(".")?;
+            }
+            match segment {
+                crate::path::Segment::Key(key) => f.write_str(key)?,
+                crate::path::Segment::Index(index) => {
+                    write!(f, r"\[{}\]", index)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Formats an error about the node at `base`, or below it, with only the
+/// part of its path below `base`, followed by its location.
+///
+/// An error without a known path, or with a path outside of `base`, is
+/// formatted as it is displayed.
+pub(crate) struct Relative<'a> {
+    pub(crate) err: &'a Error,
+    pub(crate) base: Option<&'a crate::path::Path>,
+}
+
+impl Display for Relative<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut inner = &*self.err.0;
+        while let ErrorImpl::Shared(err) = inner {
+            inner = err;
+        }
+        let (description, path) = match (inner, self.base) {
+            (
+                ErrorImpl::Message(
+                    description,
+                    Some(Pos {
+                        path: Some(path), ..
+                    }),
+                )
+                | ErrorImpl::Invalid(
+                    _,
+                    description,
+                    Some(Pos {
+                        path: Some(path), ..
+                    }),
+                ),
+                Some(base),
+            ) if path.starts_with(base) => {
+                (description, &path.segments()[base.segments().len()..])
+            }
+            _ => return Display::fmt(self.err, f),
+        };
+        if !path.is_empty() {
+            write!(f, "{}: ", Prefix(path))?;
+        }
+        f.write_str(description)?;
+        inner.write_location(f)
+    }
+}
+
+/// An error with an excerpt of the input it was read from.
+struct Snippet<'a> {
+    error: &'a ErrorImpl,
+    source: &'a str,
+}
+
+impl Display for Snippet<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("error: ")?;
+        self.error.headline(f)?;
+        f.write_str("\n")?;
+        let location = match self.error.location() {
+            Some(location) => location,
+            None => return Ok(()),
+        };
+
+        let mut marks = vec![(location, '^', None)];
+        if let Some((mark, context)) = self.error.context() {
+            let context_location = Location::from_mark(mark);
+            if (context_location.line, context_location.column)
+                != (location.line, location.column)
+            {
+                marks.push((context_location, '-', Some(context)));
+            }
+        }
+        marks.sort_by_key(|(location, ..)| {
+            (location.line, location.column)
+        });
+
+        let width = marks[marks.len() - 1].0.line.to_string().len();
+        write!(f, "{:w$}--> ", "", w = width)?;
+        if let Some(file) = self.error.file() {
+            write!(f, "{}:", file)?;
+        }
+        writeln!(f, "{}:{}", location.line, location.column)?;
+        writeln!(f, "{:w$} |", "", w = width)?;
+        let lines: Vec<&str> = self.source.split('\n').collect();
+        let mut previous = None;
+        for (i, &(location, ..)) in marks.iter().enumerate() {
+            let line = location.line;
+            if previous == Some(line) {
+                continue;
+            }
+            if previous.map_or(false, |previous| line > previous + 1) {
+                writeln!(f, "{:w$} ...", "", w = width)?;
+            }
+            previous = Some(line);
+
+            let text = lines.get(line - 1).map_or("", |text| {
+                text.strip_suffix('\r').unwrap_or(text)
+            });
+            if text.is_empty() {
+                writeln!(f, "{:w$} |", line, w = width)?;
+            } else {
+                writeln!(f, "{:w$} | {}", line, text, w = width)?;
+            }
+            write!(f, "{:w$} | ", "", w = width)?;
+            let mut column = 1;
+            let mut chars = text.chars();
+            for (location, marker, label) in
+                marks[i..].iter().take_while(|mark| mark.0.line == line)
+            {
+                while column < location.column {
+                    // Tabs are kept so the marker lines up however wide
+                    // they are shown.
+                    f.write_str(match chars.next() {
+                        Some('\t') => "\t",
+                        _ => " ",
+                    })?;
+                    column += 1;
+                }
+                write!(f, "{}", marker)?;
+                chars.next();
+                column += 1;
+                if let Some(label) = label {
+                    write!(f, " {}", label)?;
+                }
+            }
+            f.write_str("\n")?;
+        }
+        Ok(())
+    }
 }
 
 impl ErrorImpl {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            ErrorImpl::Message(..) => ErrorKind::Custom,
+            ErrorImpl::Invalid(kind, ..) => *kind,
+            ErrorImpl::Libyml(_) => ErrorKind::Parse,
+            ErrorImpl::IoError(_) => ErrorKind::Io,
+            ErrorImpl::FromUtf8(_) => ErrorKind::Utf8,
+            ErrorImpl::EndOfStream => ErrorKind::EndOfStream,
+            ErrorImpl::MoreThanOneDocument => {
+                ErrorKind::MoreThanOneDocument
+            }
+            ErrorImpl::RecursionLimitExceeded(_) => {
+                ErrorKind::RecursionLimit
+            }
+            ErrorImpl::RepetitionLimitExceeded => ErrorKind::AliasLimit,
+            ErrorImpl::BytesUnsupported
+            | ErrorImpl::SerializeNestedEnum => ErrorKind::Unsupported,
+            ErrorImpl::UnknownAnchor(_) => ErrorKind::UnknownAnchor,
+            ErrorImpl::AliasCycle(..) => ErrorKind::AliasCycle,
+            ErrorImpl::ScalarInMerge
+            | ErrorImpl::TaggedInMerge
+            | ErrorImpl::ScalarInMergeElement
+            | ErrorImpl::SequenceInMergeElement => ErrorKind::Merge,
+            ErrorImpl::EmptyTag | ErrorImpl::FailedToParseNumber => {
+                ErrorKind::InvalidValue
+            }
+            ErrorImpl::Multiple(_) => ErrorKind::Multiple,
+            ErrorImpl::File(_, err) => err.kind(),
+            ErrorImpl::Shared(err) => err.kind(),
+        }
+    }
+
     fn location(&self) -> Option<Location> {
         self.mark().map(Location::from_mark)
     }
@@ -245,28 +846,110 @@ impl ErrorImpl {
         match self {
             ErrorImpl::IoError(err) => err.source(),
             ErrorImpl::FromUtf8(err) => err.source(),
+            ErrorImpl::File(_, err) => err.0.source(),
             ErrorImpl::Shared(err) => err.source(),
             _ => None,
         }
     }
 
+    fn context_mark(&self) -> Option<libyml::Mark> {
+        self.context().map(|(mark, _)| mark)
+    }
+
+    fn multiple(&self) -> Option<&[Error]> {
+        match self {
+            ErrorImpl::Multiple(errors) => Some(errors),
+            ErrorImpl::File(_, err) => err.0.multiple(),
+            ErrorImpl::Shared(err) => err.multiple(),
+            _ => None,
+        }
+    }
+
+    fn file(&self) -> Option<&str> {
+        match self {
+            ErrorImpl::File(file, _) => Some(file),
+            ErrorImpl::Shared(err) => err.file(),
+            _ => None,
+        }
+    }
+
+    /// Writes the message of the error without its location.
+    fn headline(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorImpl::Libyml(err) => Display::fmt(&err.problem, f),
+            ErrorImpl::File(_, err) => err.0.headline(f),
+            ErrorImpl::Shared(err) => err.headline(f),
+            _ => self.message(f),
+        }
+    }
+
+    /// Returns where the parser was reading when it failed and what it
+    /// was reading, if the error comes from the parser and names it.
+    fn context(&self) -> Option<(libyml::Mark, String)> {
+        match self {
+            ErrorImpl::Libyml(err) => err
+                .context
+                .as_ref()
+                .map(|context| (err.context_mark, context.to_string())),
+            ErrorImpl::File(_, err) => err.0.context(),
+            ErrorImpl::Shared(err) => err.context(),
+            _ => None,
+        }
+    }
+
     fn mark(&self) -> Option<libyml::Mark> {
         match self {
-            ErrorImpl::Message(_, Some(Pos { mark, path: _ }))
+            ErrorImpl::Message(
+                _,
+                Some(Pos {
+                    mark: Some(mark), ..
+                }),
+            )
+            | ErrorImpl::Invalid(
+                _,
+                _,
+                Some(Pos {
+                    mark: Some(mark), ..
+                }),
+            )
             | ErrorImpl::RecursionLimitExceeded(mark)
-            | ErrorImpl::UnknownAnchor(mark) => Some(*mark),
+            | ErrorImpl::UnknownAnchor(mark)
+            | ErrorImpl::AliasCycle(_, mark) => Some(*mark),
             ErrorImpl::Libyml(err) => Some(err.mark()),
+            ErrorImpl::Multiple(errors) => errors[0].0.mark(),
+            ErrorImpl::File(_, err) => err.0.mark(),
             ErrorImpl::Shared(err) => err.mark(),
             _ => None,
         }
     }
 
+    fn path(&self) -> Option<&crate::path::Path> {
+        match self {
+            ErrorImpl::Message(_, Some(Pos { path, .. }))
+            | ErrorImpl::Invalid(_, _, Some(Pos { path, .. })) => {
+                path.as_ref()
+            }
+            ErrorImpl::Multiple(errors) => errors[0].path(),
+            ErrorImpl::File(_, err) => err.path(),
+            ErrorImpl::Shared(err) => err.path(),
+            _ => None,
+        }
+    }
+
     fn message(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ErrorImpl::Message(description, None) => f.write_str(description),
-            ErrorImpl::Message(description, Some(Pos { mark: _, path })) => {
-                if path != "." {
-                    write!(f, "{}: ", path)?;
+            ErrorImpl::Message(description, None)
+            | ErrorImpl::Invalid(_, description, None) => {
+                f.write_str(description)
+            }
+            ErrorImpl::Message(description, Some(Pos { path, .. }))
+            | ErrorImpl::Invalid(_, description, Some(Pos { path, .. })) => {
+                match path {
+                    Some(path) if path.is_root() => {}
+                    Some(path) => {
+                        write!(f, "{}: ", Prefix(path.segments()))?;
+                    }
+                    None => f.write_str("?: ")?,
                 }
                 f.write_str(description)
             }
@@ -287,6 +970,11 @@ impl ErrorImpl {
                 f.write_str("serialization and deserialization of bytes in YAML is not implemented")
             }
             ErrorImpl::UnknownAnchor(_mark) => f.write_str("unknown anchor"),
+            ErrorImpl::AliasCycle(name, _mark) => write!(
+                f,
+                "alias cycle: *{} refers to a node that contains it",
+                name
+            ),
             ErrorImpl::SerializeNestedEnum => {
                 f.write_str("serializing nested enums in YAML is not supported yet")
             }
@@ -306,29 +994,52 @@ impl ErrorImpl {
             ErrorImpl::FailedToParseNumber => {
                 f.write_str("failed to parse YAML number")
             }
-            ErrorImpl::Shared(_) => unreachable!(),
+            ErrorImpl::Multiple(errors) => {
+                write!(f, "found {} errors", errors.len())?;
+                for error in errors {
+                    write!(f, "\n{}", error)?;
+                }
+                Ok(())
+            }
+            ErrorImpl::File(..) | ErrorImpl::Shared(_) => unreachable!(),
         }
     }
 
     fn display(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ErrorImpl::Libyml(err) => Display::fmt(err, f),
+            ErrorImpl::Multiple(_) => self.message(f),
+            ErrorImpl::File(file, err) => {
+                write!(f, "{}: ", file)?;
+                err.0.display(f)
+            }
             ErrorImpl::Shared(err) => err.display(f),
             _ => {
                 self.message(f)?;
-                if let Some(location) = self.mark() {
-                    if location.line() != 0 || location.column() != 0 {
-                        write!(f, " at {}", location)?;
-                    }
-                }
-                Ok(())
+                self.write_location(f)
+            }
+        }
+    }
+
+    /// Writes where the error occurred, if it is known, after its message.
+    fn write_location(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        if let Some(location) = self.mark() {
+            if location.line() != 0 || location.column() != 0 {
+                write!(f, " at {}", location)?;
             }
         }
+        Ok(())
     }
 
     fn debug(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ErrorImpl::Libyml(err) => Debug::fmt(err, f),
+            ErrorImpl::File(file, err) => {
+                f.debug_tuple("File").field(file).field(err).finish()
+            }
             ErrorImpl::Shared(err) => err.debug(f),
             _ => {
                 f.write_str("Error(")?;
@@ -356,3 +1067,50 @@ impl ErrorImpl {
         }
     }
 }
+
+#[cfg(feature = "miette")]
+impl miette::Diagnostic for Error {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(format!("serde_yml::{:?}", self.kind())))
+    }
+
+    /// Labels the byte offsets of the error and, for a parser error that
+    /// names it, of the node the parser was reading. The offsets are into
+    /// the input, which the application attaches as the source code. An
+    /// error holding several errors leaves the labels to each of them.
+    fn labels(
+        &self,
+    ) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>>
+    {
+        if self.0.multiple().is_some() {
+            return None;
+        }
+        let location = self.location()?;
+        let mut labels =
+            vec![miette::LabeledSpan::new_primary_with_span(
+                None,
+                location.index(),
+            )];
+        if let Some((mark, context)) = self.0.context() {
+            let index = Location::from_mark(mark).index();
+            if index != location.index() {
+                labels.push(miette::LabeledSpan::at_offset(
+                    index, context,
+                ));
+            }
+        }
+        Some(Box::new(labels.into_iter()))
+    }
+
+    fn related<'a>(
+        &'a self,
+    ) -> Option<Box<dyn Iterator<Item = &'a dyn miette::Diagnostic> + 'a>>
+    {
+        let errors = self.0.multiple()?;
+        Some(Box::new(
+            errors
+                .iter()
+                .map(|error| -> &dyn miette::Diagnostic { error }),
+        ))
+    }
+}
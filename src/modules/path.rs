@@ -1,5 +1,5 @@
+use core::fmt::{self, Display};
 use serde::Serialize;
-use std::fmt::{self, Display};
 
 /// `Path` represents the path to the current value in the input, like `dependencies.serde.typo1`.
 ///
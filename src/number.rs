@@ -2,26 +2,44 @@ use crate::{
     de,
     modules::error::{self, Error, ErrorImpl},
 };
+use core::{
+    cmp::Ordering,
+    fmt::{self, Display},
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
 use serde::{
     de::{Unexpected, Visitor},
     forward_to_deserialize_any, Deserialize, Deserializer, Serialize,
     Serializer,
 };
-use std::{
-    cmp::Ordering,
-    fmt::{self, Display},
-    hash::{Hash, Hasher},
-    str::FromStr,
+#[cfg(feature = "bigint")]
+use {
+    crate::{lib::*, value::RAW_YAML},
+    num_bigint::{BigInt, Sign},
+    serde::de::{value::StringDeserializer, Error as _},
 };
 
 /// Represents a YAML number, whether integer or floating point.
-#[derive(Copy, Clone, PartialEq, PartialOrd)]
+///
+/// With the `bigint` feature, integers outside the range of `i64` and `u64`
+/// are kept exactly, as a boxed `num_bigint::BigInt`, instead of being read
+/// as floats. `Number` is then no longer `Copy`.
+///
+/// Numbers are totally ordered, so that they can be sorted and used as keys
+/// of a `BTreeMap`. Integers are ordered by value and sort below every
+/// float. Floats are ordered by value, with `-0.0` equal to `0.0`, and NaN,
+/// which is equal to itself as YAML has a single NaN, sorts above every
+/// other float.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(not(feature = "bigint"), derive(Copy))]
 pub struct Number {
     n: N,
 }
 
 /// Enum representing different variants of numbers.
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
+#[cfg_attr(not(feature = "bigint"), derive(Copy))]
 #[repr(C)]
 enum N {
     /// Represents a positive integer.
@@ -30,6 +48,9 @@ enum N {
     NegativeInteger(i64),
     /// Represents a floating point number.
     Float(f64),
+    /// Represents an integer outside the range of `i64` and `u64`.
+    #[cfg(feature = "bigint")]
+    BigInteger(Box<BigInt>),
 }
 
 impl Number {
@@ -44,6 +65,8 @@ impl Number {
             N::PositiveInteger(v) => v <= i64::MAX as u64,
             N::NegativeInteger(_) => true,
             N::Float(_) => false,
+            #[cfg(feature = "bigint")]
+            N::BigInteger(_) => false,
         }
     }
 
@@ -56,6 +79,8 @@ impl Number {
         match self.n {
             N::PositiveInteger(_) => true,
             N::NegativeInteger(_) | N::Float(_) => false,
+            #[cfg(feature = "bigint")]
+            N::BigInteger(_) => false,
         }
     }
 
@@ -68,6 +93,8 @@ impl Number {
         match self.n {
             N::Float(_) => true,
             N::PositiveInteger(_) | N::NegativeInteger(_) => false,
+            #[cfg(feature = "bigint")]
+            N::BigInteger(_) => false,
         }
     }
 
@@ -85,6 +112,8 @@ impl Number {
             }
             N::NegativeInteger(n) => Some(n),
             N::Float(_) => None,
+            #[cfg(feature = "bigint")]
+            N::BigInteger(_) => None,
         }
     }
 
@@ -95,6 +124,8 @@ impl Number {
         match self.n {
             N::PositiveInteger(n) => Some(n),
             N::NegativeInteger(_) | N::Float(_) => None,
+            #[cfg(feature = "bigint")]
+            N::BigInteger(_) => None,
         }
     }
 
@@ -105,6 +136,32 @@ impl Number {
             N::PositiveInteger(n) => Some(n as f64),
             N::NegativeInteger(n) => Some(n as f64),
             N::Float(n) => Some(n),
+            #[cfg(feature = "bigint")]
+            N::BigInteger(ref n) => n.to_string().parse().ok(),
+        }
+    }
+
+    /// If the `Number` is an integer, represent it as a `BigInt`, however
+    /// large it is. Returns None otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use num_bigint::BigInt;
+    /// use serde_yml::Number;
+    ///
+    /// let n: Number = "340282366920938463463374607431768211456".parse().unwrap();
+    /// assert!(n.as_u64().is_none());
+    /// let expected = BigInt::from(u128::MAX) + 1u8;
+    /// assert_eq!(n.as_big_int(), Some(expected));
+    /// ```
+    #[cfg(feature = "bigint")]
+    pub fn as_big_int(&self) -> Option<BigInt> {
+        match self.n {
+            N::PositiveInteger(n) => Some(n.into()),
+            N::NegativeInteger(n) => Some(n.into()),
+            N::Float(_) => None,
+            N::BigInteger(ref n) => Some((**n).clone()),
         }
     }
 
@@ -114,6 +171,8 @@ impl Number {
         match self.n {
             N::PositiveInteger(_) | N::NegativeInteger(_) => false,
             N::Float(f) => f.is_nan(),
+            #[cfg(feature = "bigint")]
+            N::BigInteger(_) => false,
         }
     }
 
@@ -124,6 +183,8 @@ impl Number {
         match self.n {
             N::PositiveInteger(_) | N::NegativeInteger(_) => false,
             N::Float(f) => f.is_infinite(),
+            #[cfg(feature = "bigint")]
+            N::BigInteger(_) => false,
         }
     }
 
@@ -133,6 +194,8 @@ impl Number {
         match self.n {
             N::PositiveInteger(_) | N::NegativeInteger(_) => true,
             N::Float(f) => f.is_finite(),
+            #[cfg(feature = "bigint")]
+            N::BigInteger(_) => true,
         }
     }
 }
@@ -153,6 +216,8 @@ impl Display for Number {
             N::Float(f) => {
                 write!(formatter, "{}", ryu::Buffer::new().format(f))
             }
+            #[cfg(feature = "bigint")]
+            N::BigInteger(ref i) => write!(formatter, "{}", i),
         }
     }
 }
@@ -175,7 +240,7 @@ impl FromStr for Number {
 
 impl PartialEq for N {
     fn eq(&self, other: &N) -> bool {
-        match (*self, *other) {
+        match (self, other) {
             (N::PositiveInteger(a), N::PositiveInteger(b)) => a == b,
             (N::NegativeInteger(a), N::NegativeInteger(b)) => a == b,
             (N::Float(a), N::Float(b)) => {
@@ -187,32 +252,18 @@ impl PartialEq for N {
                     a == b
                 }
             }
+            #[cfg(feature = "bigint")]
+            (N::BigInteger(a), N::BigInteger(b)) => a == b,
             _ => false,
         }
     }
 }
 
-impl PartialOrd for N {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        match (*self, *other) {
-            (N::Float(a), N::Float(b)) => {
-                if a.is_nan() && b.is_nan() {
-                    // YAML only has one NaN
-                    Some(Ordering::Equal)
-                } else {
-                    a.partial_cmp(&b)
-                }
-            }
-            _ => Some(self.total_cmp(other)),
-        }
-    }
-}
-
 impl N {
     fn total_cmp(&self, other: &Self) -> Ordering {
-        match (*self, *other) {
-            (N::PositiveInteger(a), N::PositiveInteger(b)) => a.cmp(&b),
-            (N::NegativeInteger(a), N::NegativeInteger(b)) => a.cmp(&b),
+        match (self, other) {
+            (N::PositiveInteger(a), N::PositiveInteger(b)) => a.cmp(b),
+            (N::NegativeInteger(a), N::NegativeInteger(b)) => a.cmp(b),
             // negint is always less than zero
             (N::NegativeInteger(_), N::PositiveInteger(_)) => {
                 Ordering::Less
@@ -220,8 +271,33 @@ impl N {
             (N::PositiveInteger(_), N::NegativeInteger(_)) => {
                 Ordering::Greater
             }
+            #[cfg(feature = "bigint")]
+            (N::BigInteger(a), N::BigInteger(b)) => a.cmp(b),
+            // big integers lie beyond every other integer
+            #[cfg(feature = "bigint")]
+            (
+                N::BigInteger(a),
+                N::PositiveInteger(_) | N::NegativeInteger(_),
+            ) => {
+                if a.sign() == Sign::Minus {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                }
+            }
+            #[cfg(feature = "bigint")]
+            (
+                N::PositiveInteger(_) | N::NegativeInteger(_),
+                N::BigInteger(b),
+            ) => {
+                if b.sign() == Sign::Minus {
+                    Ordering::Greater
+                } else {
+                    Ordering::Less
+                }
+            }
             (N::Float(a), N::Float(b)) => {
-                a.partial_cmp(&b).unwrap_or_else(|| {
+                a.partial_cmp(b).unwrap_or_else(|| {
                     // arbitrarily sort the NaN last
                     if !a.is_nan() {
                         Ordering::Less
@@ -239,12 +315,20 @@ impl N {
     }
 }
 
-impl Number {
-    pub(crate) fn total_cmp(&self, other: &Self) -> Ordering {
+impl Eq for Number {}
+
+impl Ord for Number {
+    fn cmp(&self, other: &Self) -> Ordering {
         self.n.total_cmp(&other.n)
     }
 }
 
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Serialize for Number {
     #[inline]
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -255,13 +339,29 @@ impl Serialize for Number {
             N::PositiveInteger(i) => serializer.serialize_u64(i),
             N::NegativeInteger(i) => serializer.serialize_i64(i),
             N::Float(f) => serializer.serialize_f64(f),
+            #[cfg(feature = "bigint")]
+            N::BigInteger(ref i) => {
+                if let Ok(i) = u128::try_from(&**i) {
+                    serializer.serialize_u128(i)
+                } else if let Ok(i) = i128::try_from(&**i) {
+                    serializer.serialize_i128(i)
+                } else {
+                    // Written as a plain scalar by this crate, and as a
+                    // string by other data formats.
+                    serializer.serialize_newtype_struct(
+                        RAW_YAML,
+                        i.to_string().as_str(),
+                    )
+                }
+            }
         }
     }
 }
 
 struct NumberVisitor;
 
-impl Visitor<'_> for NumberVisitor {
+#[cfg_attr(not(feature = "bigint"), allow(single_use_lifetimes))]
+impl<'de> Visitor<'de> for NumberVisitor {
     type Value = Number;
 
     fn expecting(
@@ -285,6 +385,56 @@ impl Visitor<'_> for NumberVisitor {
     fn visit_f64<E>(self, value: f64) -> Result<Number, E> {
         Ok(value.into())
     }
+
+    #[cfg(feature = "bigint")]
+    fn visit_i128<E>(self, value: i128) -> Result<Number, E> {
+        Ok(value.into())
+    }
+
+    #[cfg(feature = "bigint")]
+    fn visit_u128<E>(self, value: u128) -> Result<Number, E> {
+        Ok(value.into())
+    }
+
+    #[cfg(feature = "bigint")]
+    fn visit_newtype_struct<D>(
+        self,
+        deserializer: D,
+    ) -> Result<Number, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let digits = String::deserialize(deserializer)?;
+        match de::parse_big_int(&digits) {
+            Some(int) => Ok(int.into()),
+            None => Err(D::Error::invalid_value(
+                Unexpected::Str(&digits),
+                &self,
+            )),
+        }
+    }
+}
+
+/// Visits an integer outside the range of `i64` and `u64`: as an `i128` or
+/// `u128` if it fits, and otherwise as a newtype holding its digits, which
+/// the `Number` and `Value` visitors of this crate turn back into a number.
+#[cfg(feature = "bigint")]
+pub(crate) fn visit_big_integer<'de, V>(
+    int: &BigInt,
+    visitor: V,
+) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    if let Ok(int) = u128::try_from(int) {
+        visitor.visit_u128(int)
+    } else if let Ok(int) = i128::try_from(int) {
+        visitor.visit_i128(int)
+    } else {
+        visitor.visit_newtype_struct(StringDeserializer::<Error>::new(
+            int.to_string(),
+        ))
+    }
 }
 
 impl<'de> Deserialize<'de> for Number {
@@ -309,6 +459,8 @@ impl<'de> Deserializer<'de> for Number {
             N::PositiveInteger(i) => visitor.visit_u64(i),
             N::NegativeInteger(i) => visitor.visit_i64(i),
             N::Float(f) => visitor.visit_f64(f),
+            #[cfg(feature = "bigint")]
+            N::BigInteger(ref i) => visit_big_integer(i, visitor),
         }
     }
 
@@ -331,6 +483,8 @@ impl<'de> Deserializer<'de> for &Number {
             N::PositiveInteger(i) => visitor.visit_u64(i),
             N::NegativeInteger(i) => visitor.visit_i64(i),
             N::Float(f) => visitor.visit_f64(f),
+            #[cfg(feature = "bigint")]
+            N::BigInteger(ref i) => visit_big_integer(i, visitor),
         }
     }
 
@@ -375,6 +529,34 @@ macro_rules! from_unsigned {
 from_signed!(i8 i16 i32 i64 isize);
 from_unsigned!(u8 u16 u32 u64 usize);
 
+#[cfg(feature = "bigint")]
+impl From<BigInt> for Number {
+    fn from(i: BigInt) -> Self {
+        let n = if let Ok(u) = u64::try_from(&i) {
+            N::PositiveInteger(u)
+        } else if let Ok(i) = i64::try_from(&i) {
+            N::NegativeInteger(i)
+        } else {
+            N::BigInteger(Box::new(i))
+        };
+        Number { n }
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl From<i128> for Number {
+    fn from(i: i128) -> Self {
+        Number::from(BigInt::from(i))
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl From<u128> for Number {
+    fn from(u: u128) -> Self {
+        Number::from(BigInt::from(u))
+    }
+}
+
 impl From<f32> for Number {
     fn from(f: f32) -> Self {
         Number::from(f as f64)
@@ -401,8 +583,21 @@ impl Hash for Number {
                 i.hash(state);
             }
             N::Float(f) => {
+                // Equal floats must hash equally: 0.0 == -0.0, and all NaNs
+                // compare equal to each other.
+                let f = if *f == 0.0 {
+                    0.0
+                } else if f.is_nan() {
+                    f64::NAN
+                } else {
+                    *f
+                };
                 f.to_bits().hash(state);
             }
+            #[cfg(feature = "bigint")]
+            N::BigInteger(i) => {
+                i.hash(state);
+            }
         }
     }
 }
@@ -413,5 +608,7 @@ pub(crate) fn unexpected(number: &Number) -> Unexpected<'_> {
         N::PositiveInteger(u) => Unexpected::Unsigned(u),
         N::NegativeInteger(i) => Unexpected::Signed(i),
         N::Float(f) => Unexpected::Float(f),
+        #[cfg(feature = "bigint")]
+        N::BigInteger(_) => Unexpected::Other("big integer"),
     }
 }
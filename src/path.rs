@@ -0,0 +1,475 @@
+//! Owned paths to nodes within a YAML document.
+//!
+//! A [`Path`] is a list of [`Segment`]s leading from the root of a document
+//! to one of its nodes: mapping keys and sequence indices. It is the path
+//! type returned by [`Error::path`](crate::Error::path) and accepted by
+//! [`Value::get_path`](crate::Value::get_path), and is meant to be used by
+//! anything else that needs to name a node.
+//!
+//! Paths have two text forms:
+//!
+//! - The dotted form, such as `servers[0].port`, used in error messages.
+//!   The root is written `.`. Keys that are empty or contain any of
+//!   `.[]"\` are written in double quotes, with `"` and `\` escaped by a
+//!   backslash.
+//! - The [JSON pointer] form, such as `/servers/0/port`. The root is the
+//!   empty string.
+//!
+//! [`Display`] writes the dotted form, or the JSON pointer form with the
+//! alternate flag (`{:#}`), and [`FromStr`] reads either of them.
+//!
+//! [JSON pointer]: https://datatracker.ietf.org/doc/html/rfc6901
+//!
+//! # Examples
+//!
+//! ```
+//! use serde_yml::path::{Path, Segment};
+//!
+//! let path: Path = "servers[0].port".parse().unwrap();
+//! assert_eq!(path.segments(), [Segment::from("servers"), Segment::from(0), Segment::from("port")]);
+//! assert_eq!(path.to_pointer(), "/servers/0/port");
+//! assert_eq!("/servers/0/port".parse::<Path>().unwrap(), path);
+//! ```
+
+use crate::{
+    lib::*,
+    modules::{error::Error, path::Path as BorrowedPath},
+};
+use alloc::sync::Arc;
+use core::{
+    fmt::{self, Display},
+    iter::FromIterator,
+    str::FromStr,
+};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// A single step of a [`Path`].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Segment {
+    /// A mapping key.
+    Key(String),
+    /// A sequence index.
+    Index(usize),
+}
+
+impl Display for Segment {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Segment::Key(key) => formatter.write_str(key),
+            Segment::Index(index) => Display::fmt(index, formatter),
+        }
+    }
+}
+
+impl From<&str> for Segment {
+    fn from(key: &str) -> Self {
+        Segment::Key(key.to_owned())
+    }
+}
+
+impl From<String> for Segment {
+    fn from(key: String) -> Self {
+        Segment::Key(key)
+    }
+}
+
+impl From<usize> for Segment {
+    fn from(index: usize) -> Self {
+        Segment::Index(index)
+    }
+}
+
+/// The path to a node within a YAML document.
+///
+/// Cloning a `Path` is cheap: the segments are shared until one of the
+/// clones is modified.
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::path::Path;
+///
+/// let servers = Path::root().join("servers");
+/// let port = servers.join(0).join("port");
+/// assert_eq!(port.to_string(), "servers[0].port");
+/// assert_eq!(format!("{:#}", port), "/servers/0/port");
+/// assert!(port.starts_with(&servers));
+/// assert_eq!(port.parent().unwrap().parent().unwrap(), servers);
+/// ```
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub struct Path {
+    segments: Arc<Vec<Segment>>,
+}
+
+impl Path {
+    /// Returns the path of the document root, which has no segments.
+    pub fn root() -> Self {
+        Path::default()
+    }
+
+    /// Returns whether this is the path of the document root.
+    pub fn is_root(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Returns the segments of the path, from the root down.
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// Returns the last segment of the path, or `None` for the root.
+    pub fn last(&self) -> Option<&Segment> {
+        self.segments.last()
+    }
+
+    /// Returns the path of the parent node, or `None` for the root.
+    pub fn parent(&self) -> Option<Path> {
+        let mut parent = self.clone();
+        parent.pop().map(|_| parent)
+    }
+
+    /// Returns the path of a child of this node.
+    pub fn join(&self, segment: impl Into<Segment>) -> Path {
+        let mut child = self.clone();
+        child.push(segment);
+        child
+    }
+
+    /// Appends a segment to the path.
+    pub fn push(&mut self, segment: impl Into<Segment>) {
+        Arc::make_mut(&mut self.segments).push(segment.into());
+    }
+
+    /// Removes and returns the last segment of the path, or returns `None`
+    /// for the root.
+    pub fn pop(&mut self) -> Option<Segment> {
+        if self.is_root() {
+            return None;
+        }
+        Arc::make_mut(&mut self.segments).pop()
+    }
+
+    /// Returns whether `prefix` is this path or one of its ancestors.
+    pub fn starts_with(&self, prefix: &Path) -> bool {
+        self.segments.starts_with(&prefix.segments)
+    }
+
+    /// Returns the [JSON pointer] form of the path, such as
+    /// `/servers/0/port`.
+    ///
+    /// [JSON pointer]: https://datatracker.ietf.org/doc/html/rfc6901
+    pub fn to_pointer(&self) -> String {
+        format!("{:#}", self)
+    }
+
+    /// Parses the [JSON pointer] form of a path.
+    ///
+    /// A JSON pointer does not say whether a token is a mapping key or a
+    /// sequence index. Tokens written as an unsigned integer without
+    /// leading zeros are read as [`Segment::Index`], all others as
+    /// [`Segment::Key`].
+    ///
+    /// [JSON pointer]: https://datatracker.ietf.org/doc/html/rfc6901
+    pub fn from_pointer(pointer: &str) -> Result<Path, Error> {
+        if pointer.is_empty() {
+            return Ok(Path::root());
+        }
+        let tokens = match pointer.strip_prefix('/') {
+            Some(tokens) => tokens,
+            None => {
+                return Err(invalid(
+                    pointer,
+                    "a JSON pointer starts with `/`",
+                ))
+            }
+        };
+        tokens
+            .split('/')
+            .map(|token| {
+                if is_index(token) {
+                    if let Ok(index) = token.parse() {
+                        return Ok(Segment::Index(index));
+                    }
+                }
+                let mut key = String::with_capacity(token.len());
+                let mut chars = token.chars();
+                while let Some(ch) = chars.next() {
+                    key.push(match ch {
+                        '~' => match chars.next() {
+                            Some('0') => '~',
+                            Some('1') => '/',
+                            _ => return Err(invalid(
+                                pointer,
+                                "`~` must be followed by `0` or `1`",
+                            )),
+                        },
+                        ch => ch,
+                    });
+                }
+                Ok(Segment::Key(key))
+            })
+            .collect()
+    }
+
+    /// Parses the dotted form of a path, such as `servers[0].port`.
+    fn from_dotted(text: &str) -> Result<Path, Error> {
+        let mut path = Path::root();
+        if text == "." {
+            return Ok(path);
+        }
+        let mut rest = text.strip_prefix('.').unwrap_or(text);
+        let mut expect_key = true;
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix('[') {
+                let end = after.find(']').ok_or_else(|| {
+                    invalid(text, "`[` must be closed by `]`")
+                })?;
+                let index = after[..end]
+                    .parse()
+                    .ok()
+                    .filter(|_| is_index(&after[..end]))
+                    .ok_or_else(|| {
+                        invalid(
+                            text,
+                            "expected an index between `[` and `]`",
+                        )
+                    })?;
+                path.push(Segment::Index(index));
+                rest = &after[end + 1..];
+                expect_key = false;
+            } else if let Some(after) = rest.strip_prefix('.') {
+                if expect_key {
+                    return Err(invalid(
+                        text,
+                        "expected a key after `.`",
+                    ));
+                }
+                rest = after;
+                expect_key = true;
+                if rest.is_empty() {
+                    return Err(invalid(
+                        text,
+                        "expected a key after `.`",
+                    ));
+                }
+            } else if !expect_key {
+                return Err(invalid(
+                    text,
+                    "expected `.` or `[` after a key",
+                ));
+            } else if let Some(after) = rest.strip_prefix('"') {
+                let mut key = String::new();
+                let mut chars = after.char_indices();
+                let end = loop {
+                    match chars.next() {
+                        Some((i, '"')) => break i,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, ch @ ('"' | '\\'))) => key.push(ch),
+                            _ => {
+                                return Err(invalid(
+                                    text,
+                                    "only `\"` and `\\` can be escaped in a quoted key",
+                                ))
+                            }
+                        },
+                        Some((_, ch)) => key.push(ch),
+                        None => {
+                            return Err(invalid(
+                                text,
+                                "a quoted key must end with `\"`",
+                            ))
+                        }
+                    }
+                };
+                path.push(Segment::Key(key));
+                rest = &after[end + 1..];
+                expect_key = false;
+            } else {
+                let end = rest.find(SPECIAL).unwrap_or(rest.len());
+                if end == 0 {
+                    return Err(invalid(
+                        text,
+                        "keys containing `.[]\"\\` must be quoted",
+                    ));
+                }
+                path.push(Segment::Key(rest[..end].to_owned()));
+                rest = &rest[end..];
+                expect_key = false;
+            }
+        }
+        Ok(path)
+    }
+}
+
+/// Characters that must be quoted in the keys of a dotted path.
+const SPECIAL: [char; 5] = ['.', '[', ']', '"', '\\'];
+
+/// Returns whether `token` is an unsigned integer without leading zeros.
+fn is_index(token: &str) -> bool {
+    !token.is_empty()
+        && token.bytes().all(|b| b.is_ascii_digit())
+        && (token == "0" || !token.starts_with('0'))
+}
+
+/// Returns whether a key must be quoted in the dotted form of a path.
+fn needs_quotes(key: &str, first: bool) -> bool {
+    key.is_empty()
+        || key.contains(SPECIAL)
+        || first && key.starts_with('/')
+}
+
+fn invalid(path: &str, reason: &str) -> Error {
+    de::Error::custom(format_args!(
+        "invalid path `{}`: {}",
+        path, reason
+    ))
+}
+
+impl Display for Path {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if formatter.alternate() {
+            for segment in self.segments() {
+                formatter.write_str("/")?;
+                match segment {
+                    Segment::Key(key) => {
+                        for ch in key.chars() {
+                            match ch {
+                                '~' => formatter.write_str("~0")?,
+                                '/' => formatter.write_str("~1")?,
+                                ch => fmt::Write::write_char(
+                                    formatter, ch,
+                                )?,
+                            }
+                        }
+                    }
+                    Segment::Index(index) => {
+                        write!(formatter, "{}", index)?
+                    }
+                }
+            }
+            return Ok(());
+        }
+        if self.is_root() {
+            return formatter.write_str(".");
+        }
+        for (i, segment) in self.segments().iter().enumerate() {
+            match segment {
+                Segment::Index(index) => {
+                    write!(formatter, "[{}]", index)?
+                }
+                Segment::Key(key) => {
+                    if i > 0 {
+                        formatter.write_str(".")?;
+                    }
+                    if needs_quotes(key, i == 0) {
+                        formatter.write_str("\"")?;
+                        for ch in key.chars() {
+                            if ch == '"' || ch == '\\' {
+                                formatter.write_str("\\")?;
+                            }
+                            fmt::Write::write_char(formatter, ch)?;
+                        }
+                        formatter.write_str("\"")?;
+                    } else {
+                        formatter.write_str(key)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Path {
+    type Err = Error;
+
+    /// Parses either text form of a path: strings that are empty or start
+    /// with `/` are read as JSON pointers, all others as dotted paths.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        if text.is_empty() || text.starts_with('/') {
+            Path::from_pointer(text)
+        } else {
+            Path::from_dotted(text)
+        }
+    }
+}
+
+impl From<Vec<Segment>> for Path {
+    fn from(segments: Vec<Segment>) -> Self {
+        Path {
+            segments: Arc::new(segments),
+        }
+    }
+}
+
+impl FromIterator<Segment> for Path {
+    fn from_iter<I: IntoIterator<Item = Segment>>(iter: I) -> Self {
+        Path::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl Extend<Segment> for Path {
+    fn extend<I: IntoIterator<Item = Segment>>(&mut self, iter: I) {
+        Arc::make_mut(&mut self.segments).extend(iter);
+    }
+}
+
+/// Converts the path tracked while deserializing. Aliases add no segment.
+/// A path that goes through a mapping key which is not a string has no
+/// `Path`, so it is an error.
+impl TryFrom<&BorrowedPath<'_>> for Path {
+    type Error = Error;
+
+    fn try_from(path: &BorrowedPath<'_>) -> Result<Self, Error> {
+        /// Appends the segments of `path`, failing at an unknown segment.
+        fn collect(
+            path: &BorrowedPath<'_>,
+            segments: &mut Vec<Segment>,
+        ) -> Result<(), Error> {
+            match path {
+                BorrowedPath::Root => Ok(()),
+                BorrowedPath::Seq { parent, index } => {
+                    collect(parent, segments)?;
+                    segments.push(Segment::Index(*index));
+                    Ok(())
+                }
+                BorrowedPath::Map { parent, key } => {
+                    collect(parent, segments)?;
+                    segments.push(Segment::from(*key));
+                    Ok(())
+                }
+                BorrowedPath::Alias { parent } => collect(parent, segments),
+                BorrowedPath::Unknown { .. } => Err(de::Error::custom(
+                    format_args!(
+                        "path `{}` goes through a mapping key that is not a string",
+                        path,
+                    ),
+                )),
+            }
+        }
+
+        let mut segments = Vec::new();
+        collect(path, &mut segments)?;
+        Ok(Path::from(segments))
+    }
+}
+
+impl Serialize for Path {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Path {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(de::Error::custom)
+    }
+}
@@ -2,26 +2,36 @@
 //!
 //! This module provides YAML serialization with the type `Serializer`.
 
+use crate::io;
 use crate::libyml;
 use crate::libyml::emitter::{
-    Emitter, Event, Mapping, Scalar, ScalarStyle, Sequence,
+    Emitter, Encoding, Event, LineBreak, Mapping, Scalar, ScalarStyle,
+    Sequence,
 };
+use crate::libyml::tag::Tag;
 use crate::{
+    de::{self, Progress},
+    lib::*,
+    loader::{Document, Loader},
     modules::error::{self, Error, ErrorImpl},
-    value::tagged::{self, MaybeTag},
+    value::{
+        tagged::{self, MaybeTag},
+        RAW_YAML,
+    },
+};
+use core::{
+    cmp::Ordering,
+    fmt::{self, Display},
+    mem, num,
+    ops::Range,
+    str,
 };
 use serde::{
     de::Visitor,
     ser::{self, Serializer as _},
 };
-use std::{
-    fmt::{self, Display},
-    io,
-    marker::PhantomData,
-    mem, num, str,
-};
 
-type Result<T, E = Error> = std::result::Result<T, E>;
+type Result<T, E = Error> = core::result::Result<T, E>;
 
 /// A structure for serializing Rust values into YAML.
 ///
@@ -53,12 +63,640 @@ pub struct Serializer<W> {
     pub depth: usize,
     /// The current state of the serializer.
     pub state: State,
-    /// The YAML emitter.
-    pub emitter: Emitter<'static>,
-    /// The underlying writer.
-    pub writer: PhantomData<W>,
+    /// The YAML emitter, which owns the underlying writer.
+    pub emitter: Emitter<W>,
+    /// The block style requested for the next string by
+    /// [`with::literal_block`](crate::with::literal_block) or
+    /// [`with::folded_block`](crate::with::folded_block).
+    block_style: Option<ScalarStyle>,
+    /// Whether the next string is the text of a
+    /// [`RawYaml`](crate::value::RawYaml) to be written as YAML.
+    raw_yaml: bool,
+    /// Whether the stream has been started, which happens with the first
+    /// document so that the encoding can still be chosen before it.
+    stream_started: bool,
+    /// The options set through [`Serializer::with_options`].
+    options: SerializerOptions,
+    /// Whether a mapping key is being written.
+    in_key: bool,
+    /// Whether the sequence or mapping being written has no entries and
+    /// was written as an empty scalar, so it has no end event.
+    empty_block: bool,
+    /// The events of the mappings being written, innermost last, held back
+    /// until each mapping ends while [`SerializerOptions::key_order`] is
+    /// set.
+    held: Vec<Vec<HeldEvent>>,
+}
+
+/// An owned copy of a node event, held back by [`Serializer`] until the
+/// entries of its mapping are sorted.
+#[derive(Debug)]
+enum HeldEvent {
+    Scalar {
+        anchor: Option<String>,
+        tag: Option<String>,
+        value: String,
+        style: ScalarStyle,
+    },
+    SequenceStart(Sequence),
+    SequenceEnd,
+    MappingStart(Mapping),
+    MappingEnd,
+}
+
+/// Sorts the events of the entries of a mapping by their keys, keeping
+/// the order of entries whose keys compare equal. Entries with a
+/// collection as key come after the others.
+fn sort_entries(
+    events: Vec<HeldEvent>,
+    key_order: fn(&str, &str) -> Ordering,
+) -> Vec<HeldEvent> {
+    let mut nodes = Vec::new();
+    let mut node = Vec::new();
+    let mut depth = 0usize;
+    for event in events {
+        match event {
+            HeldEvent::SequenceStart(_)
+            | HeldEvent::MappingStart(_) => {
+                depth += 1;
+            }
+            HeldEvent::SequenceEnd | HeldEvent::MappingEnd => {
+                depth -= 1
+            }
+            HeldEvent::Scalar { .. } => {}
+        }
+        node.push(event);
+        if depth == 0 {
+            nodes.push(mem::take(&mut node));
+        }
+    }
+    let mut entries = Vec::with_capacity(nodes.len() / 2);
+    let mut nodes = nodes.into_iter();
+    while let (Some(key), Some(value)) = (nodes.next(), nodes.next()) {
+        entries.push((key, value));
+    }
+    fn key_str(key: &[HeldEvent]) -> Option<&str> {
+        match key {
+            [HeldEvent::Scalar { value, .. }] => Some(value),
+            _ => None,
+        }
+    }
+    entries.sort_by(|(a, _), (b, _)| match (key_str(a), key_str(b)) {
+        (Some(a), Some(b)) => key_order(a, b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    });
+    entries
+        .into_iter()
+        .flat_map(|(key, value)| key.into_iter().chain(value))
+        .collect()
+}
+
+/// Options that tune how a [`Serializer`] writes its output.
+///
+/// The defaults match the output of [`to_string`] and [`to_writer`].
+/// Options are attached to a serializer with [`Serializer::with_options`]
+/// and can be changed between documents with [`Serializer::set_options`].
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::ser::SerializerOptions;
+///
+/// let options = SerializerOptions {
+///     explicit_document_start: true,
+///     explicit_document_end: true,
+///     ..SerializerOptions::default()
+/// };
+/// let yaml = serde_yml::ser::to_string_with_options(&vec![1, 2], options);
+/// assert_eq!(yaml.unwrap(), "---\n- 1\n- 2\n...\n");
+/// ```
+#[allow(missing_copy_implementations)]
+#[derive(Clone, Debug, Default)]
+pub struct SerializerOptions {
+    /// Start every document with `---`. By default the first document has
+    /// no marker and later documents are separated by `---`, which some
+    /// older parsers reject or require.
+    pub explicit_document_start: bool,
+
+    /// End every document with `...`. By default documents end without a
+    /// marker.
+    pub explicit_document_end: bool,
+
+    /// How `None`, `()` and other null values are written.
+    pub null_style: NullStyle,
+
+    /// Leave out struct fields whose value is `None`, as if every `Option`
+    /// field had `#[serde(skip_serializing_if = "Option::is_none")]`.
+    /// Other null values, such as `()` and
+    /// [`Value::Null`](crate::Value::Null), and the `None` values of maps
+    /// are still written.
+    pub skip_none_fields: bool,
+
+    /// How sequences and mappings without entries are written.
+    pub empty_collections: EmptyCollections,
+
+    /// The order in which the fields of structs and the entries of
+    /// mappings are written, given as a comparison of their keys, instead
+    /// of the order in which they are serialized. Entries whose keys
+    /// compare equal keep their order, and entries with a sequence or
+    /// mapping as key come last.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Serialize;
+    /// use serde_yml::ser::SerializerOptions;
+    /// use std::cmp::Ordering;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Manifest {
+    ///     metadata: String,
+    ///     spec: u8,
+    ///     kind: String,
+    ///     #[serde(rename = "apiVersion")]
+    ///     api_version: String,
+    /// }
+    ///
+    /// fn kubernetes_order(a: &str, b: &str) -> Ordering {
+    ///     let rank = |key| {
+    ///         ["apiVersion", "kind", "metadata"]
+    ///             .iter()
+    ///             .position(|first| *first == key)
+    ///             .unwrap_or(3)
+    ///     };
+    ///     rank(a).cmp(&rank(b))
+    /// }
+    ///
+    /// let manifest = Manifest {
+    ///     metadata: "web".to_owned(),
+    ///     spec: 1,
+    ///     kind: "Pod".to_owned(),
+    ///     api_version: "v1".to_owned(),
+    /// };
+    /// let options = SerializerOptions {
+    ///     key_order: Some(kubernetes_order),
+    ///     ..SerializerOptions::default()
+    /// };
+    /// let yaml = serde_yml::ser::to_string_with_options(&manifest, options);
+    /// assert_eq!(
+    ///     yaml.unwrap(),
+    ///     "apiVersion: v1\nkind: Pod\nmetadata: web\nspec: 1\n",
+    /// );
+    /// ```
+    pub key_order: Option<fn(&str, &str) -> Ordering>,
+
+    /// Write the core tag of every scalar, as in `!!int 1`, `!!bool true`
+    /// and `!!str x`, so that readers need not resolve plain scalars to
+    /// types. Scalars carrying a tag of their own, such as the variants of
+    /// an enum, keep it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_yml::ser::SerializerOptions;
+    /// use std::collections::BTreeMap;
+    ///
+    /// let map = BTreeMap::from([("port", 8080)]);
+    /// let options = SerializerOptions {
+    ///     explicit_core_tags: true,
+    ///     ..SerializerOptions::default()
+    /// };
+    /// let yaml = serde_yml::ser::to_string_with_options(&map, options);
+    /// assert_eq!(yaml.unwrap(), "!!str port: !!int 8080\n");
+    /// ```
+    pub explicit_core_tags: bool,
+
+    /// Write canonical YAML, as `yaml --canonical` does: every node is
+    /// tagged, scalars are double-quoted and collections use flow style.
+    /// The output spells out how each scalar resolves, which helps when
+    /// debugging, and depends only on the data, which suits hashing.
+    /// A scalar carrying a tag of its own, such as the newtype variant
+    /// `!Circle 1.5`, keeps that tag and is read back as a string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_yml::ser::SerializerOptions;
+    /// use std::collections::BTreeMap;
+    ///
+    /// let map = BTreeMap::from([("port", 8080)]);
+    /// let options = SerializerOptions {
+    ///     canonical: true,
+    ///     ..SerializerOptions::default()
+    /// };
+    /// let yaml = serde_yml::ser::to_string_with_options(&map, options);
+    /// assert_eq!(
+    ///     yaml.unwrap(),
+    ///     "---\n!!map {\n  ? !!str \"port\"\n  : !!int \"8080\",\n}\n",
+    /// );
+    /// ```
+    pub canonical: bool,
+
+    /// How floating-point numbers are written. By default they are written
+    /// with the shortest digits that read back as the same number.
+    pub float_format: FloatFormat,
+
+    /// How NaN and the infinities are spelled.
+    pub special_float_style: SpecialFloatStyle,
+
+    /// The style of strings that span several lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_yml::ser::{MultilineStyle, SerializerOptions};
+    ///
+    /// let options = SerializerOptions {
+    ///     multiline_style: MultilineStyle::Folded,
+    ///     ..SerializerOptions::default()
+    /// };
+    /// let yaml =
+    ///     serde_yml::ser::to_string_with_options(&["first\nsecond"], options);
+    /// assert_eq!(yaml.unwrap(), "- >-\n  first\n\n  second\n");
+    /// ```
+    pub multiline_style: MultilineStyle,
+
+    /// Escape characters outside ASCII instead of writing them as they are.
+    /// Escaped strings are double-quoted and use `\x`, `\u` or `\U`
+    /// escapes, so the output is plain ASCII.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_yml::ser::SerializerOptions;
+    ///
+    /// let options = SerializerOptions {
+    ///     escape_unicode: true,
+    ///     ..SerializerOptions::default()
+    /// };
+    /// let yaml = serde_yml::ser::to_string_with_options(&["café", "日本"], options);
+    /// assert_eq!(yaml.unwrap(), "- \"caf\\xE9\"\n- \"\\u65E5\\u672C\"\n");
+    /// ```
+    pub escape_unicode: bool,
+
+    /// The line break written at the end of each line, such as
+    /// [`LineBreak::CrLf`] for files edited on Windows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_yml::libyml::emitter::LineBreak;
+    /// use serde_yml::ser::SerializerOptions;
+    ///
+    /// let options = SerializerOptions {
+    ///     line_break: LineBreak::Cr,
+    ///     ..SerializerOptions::default()
+    /// };
+    /// let yaml = serde_yml::ser::to_string_with_options(&["a", "b"], options);
+    /// assert_eq!(yaml.unwrap(), "- a\r- b\r");
+    /// ```
+    pub line_break: LineBreak,
+
+    /// The encoding of the output, such as [`Encoding::Utf16Le`] for tools
+    /// that expect UTF-16 with a byte order mark. The encoding is fixed
+    /// when the first document is written, so changing it afterwards with
+    /// [`Serializer::set_options`] has no effect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Serialize;
+    /// use serde_yml::libyml::emitter::Encoding;
+    /// use serde_yml::ser::SerializerOptions;
+    ///
+    /// let options = SerializerOptions {
+    ///     encoding: Encoding::Utf16Le,
+    ///     ..SerializerOptions::default()
+    /// };
+    /// let mut buffer = Vec::new();
+    /// serde_yml::ser::to_writer_with_options(&mut buffer, "a", options)
+    ///     .unwrap();
+    /// assert_eq!(buffer, b"\xFF\xFEa\0\n\0");
+    /// ```
+    pub encoding: Encoding,
+}
+
+/// How [`Serializer`] writes sequences and mappings without entries.
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::ser::{EmptyCollections, SerializerOptions};
+/// use std::collections::BTreeMap;
+///
+/// let value = BTreeMap::from([("args", vec![]), ("cmd", vec!["run"])]);
+/// let yaml = |empty_collections| {
+///     let options = SerializerOptions {
+///         empty_collections,
+///         ..SerializerOptions::default()
+///     };
+///     serde_yml::ser::to_string_with_options(&value, options).unwrap()
+/// };
+/// assert_eq!(yaml(EmptyCollections::Flow), "args: []\ncmd:\n- run\n");
+/// assert_eq!(yaml(EmptyCollections::Block), "args:\ncmd:\n- run\n");
+/// assert_eq!(yaml(EmptyCollections::Skip), "cmd:\n- run\n");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmptyCollections {
+    /// `[]` and `{}`. This is the default.
+    Flow,
+    /// Nothing after the colon or dash, as a block collection without
+    /// entries would look. Such a node reads back as an empty sequence or
+    /// mapping, or as null through [`Value`](crate::Value). Empty
+    /// collections at the root of a document, in mapping keys or with a
+    /// tag are still written as `[]` and `{}`.
+    Block,
+    /// Leave out struct fields and mapping entries whose value is an empty
+    /// collection. Empty collections elsewhere are written as `[]` and
+    /// `{}`.
+    Skip,
+}
+
+impl Default for EmptyCollections {
+    fn default() -> Self {
+        EmptyCollections::Flow
+    }
+}
+
+/// How [`Serializer`] writes null values, such as `None` fields.
+///
+/// Every style reads back as null.
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::ser::{NullStyle, SerializerOptions};
+/// use std::collections::BTreeMap;
+///
+/// let options = SerializerOptions {
+///     null_style: NullStyle::Empty,
+///     ..SerializerOptions::default()
+/// };
+/// let value = BTreeMap::from([("key", None::<u8>)]);
+/// let yaml = serde_yml::ser::to_string_with_options(&value, options);
+/// assert_eq!(yaml.unwrap(), "key:\n");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NullStyle {
+    /// `key: null`. This is the default.
+    Literal,
+    /// `key: ~`.
+    Tilde,
+    /// `key:`, with nothing after the colon. Null mapping keys are written
+    /// as `~`, since an empty key would read back as an empty string.
+    Empty,
+}
+
+impl Default for NullStyle {
+    fn default() -> Self {
+        NullStyle::Literal
+    }
+}
+
+/// How [`Serializer`] spells NaN and the infinities.
+///
+/// Every style except [`JavaScript`](SpecialFloatStyle::JavaScript) reads
+/// back as the same float.
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::ser::{SerializerOptions, SpecialFloatStyle};
+///
+/// let options = SerializerOptions {
+///     special_float_style: SpecialFloatStyle::Capitalized,
+///     ..SerializerOptions::default()
+/// };
+/// let value = [f64::NAN, f64::INFINITY, f64::NEG_INFINITY];
+/// let yaml = serde_yml::ser::to_string_with_options(&value, options);
+/// assert_eq!(yaml.unwrap(), "- .NaN\n- .Inf\n- -.Inf\n");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpecialFloatStyle {
+    /// `.nan`, `.inf` and `-.inf`. This is the default.
+    Lowercase,
+    /// `.NaN`, `.Inf` and `-.Inf`.
+    Capitalized,
+    /// `.NAN`, `.INF` and `-.INF`.
+    Uppercase,
+    /// `NaN`, `Infinity` and `-Infinity`, as JavaScript, JSON5 and Python
+    /// spell them. These are not floats in YAML: readers following YAML
+    /// 1.2, including this crate, read them back as strings.
+    JavaScript,
+}
+
+impl Default for SpecialFloatStyle {
+    fn default() -> Self {
+        SpecialFloatStyle::Lowercase
+    }
+}
+
+impl SpecialFloatStyle {
+    /// Spells NaN, or an infinity of the sign of `positive`.
+    fn spell(self, nan: bool, positive: bool) -> &'static str {
+        match (self, nan, positive) {
+            (SpecialFloatStyle::Lowercase, true, _) => ".nan",
+            (SpecialFloatStyle::Lowercase, false, true) => ".inf",
+            (SpecialFloatStyle::Lowercase, false, false) => "-.inf",
+            (SpecialFloatStyle::Capitalized, true, _) => ".NaN",
+            (SpecialFloatStyle::Capitalized, false, true) => ".Inf",
+            (SpecialFloatStyle::Capitalized, false, false) => "-.Inf",
+            (SpecialFloatStyle::Uppercase, true, _) => ".NAN",
+            (SpecialFloatStyle::Uppercase, false, true) => ".INF",
+            (SpecialFloatStyle::Uppercase, false, false) => "-.INF",
+            (SpecialFloatStyle::JavaScript, true, _) => "NaN",
+            (SpecialFloatStyle::JavaScript, false, true) => "Infinity",
+            (SpecialFloatStyle::JavaScript, false, false) => {
+                "-Infinity"
+            }
+        }
+    }
 }
 
+/// How [`Serializer`] writes finite floating-point numbers.
+///
+/// Whatever the format, a float is written so that it reads back as a
+/// float: fixed notation always has a decimal point, as in `3.0`. NaN and
+/// the infinities are spelled as [`SpecialFloatStyle`] says.
+///
+/// A number is written in scientific notation, as in `2.5e17`, when the
+/// power of ten of its leading digit is below `scientific_below` or at
+/// least `scientific_from`. The defaults, `-5` and `16`, write `0.00001` and
+/// `1e-6`, `1000000000000000.0` and `1e16`. Set them to `i32::MIN` and
+/// `i32::MAX` to never use scientific notation.
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::ser::{FloatFormat, SerializerOptions};
+///
+/// let yaml = |float_format| {
+///     let options = SerializerOptions {
+///         float_format,
+///         ..SerializerOptions::default()
+///     };
+///     serde_yml::ser::to_string_with_options(&[1e-7, 2.5e17, 2.0 / 3.0], options)
+///         .unwrap()
+/// };
+/// assert_eq!(
+///     yaml(FloatFormat::default()),
+///     "- 1e-7\n- 2.5e17\n- 0.6666666666666666\n",
+/// );
+/// assert_eq!(
+///     yaml(FloatFormat {
+///         max_significant_digits: Some(3),
+///         scientific_below: i32::MIN,
+///         scientific_from: i32::MAX,
+///         ..FloatFormat::default()
+///     }),
+///     "- 0.0000001\n- 250000000000000000.0\n- 0.667\n",
+/// );
+/// assert_eq!(
+///     yaml(FloatFormat {
+///         force_decimal_point: true,
+///         ..FloatFormat::default()
+///     }),
+///     "- 1.0e-7\n- 2.5e+17\n- 0.6666666666666666\n",
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FloatFormat {
+    /// Round to at most this many significant digits, dropping trailing
+    /// zeros. `None`, the default, writes the shortest digits that read
+    /// back as the same number.
+    pub max_significant_digits: Option<u32>,
+    /// Write a decimal point and a signed exponent in scientific notation
+    /// too, as in `1.0e+17` rather than `1e17`, which YAML 1.1 readers
+    /// such as PyYAML need in order to read a float.
+    pub force_decimal_point: bool,
+    /// The power of ten below which numbers are written in scientific
+    /// notation.
+    pub scientific_below: i32,
+    /// The power of ten from which numbers are written in scientific
+    /// notation.
+    pub scientific_from: i32,
+}
+
+impl Default for FloatFormat {
+    fn default() -> Self {
+        FloatFormat {
+            max_significant_digits: None,
+            force_decimal_point: false,
+            scientific_below: -5,
+            scientific_from: 16,
+        }
+    }
+}
+
+impl FloatFormat {
+    /// Writes a finite float as this format says.
+    fn format<F>(&self, v: F) -> String
+    where
+        F: fmt::LowerExp,
+    {
+        // Both forms give the digits and the power of ten of the leading
+        // digit, as in `-1.25e-7`.
+        let scientific = match self.max_significant_digits {
+            Some(digits) => {
+                format!("{:.*e}", digits.max(1) as usize - 1, v)
+            }
+            None => format!("{:e}", v),
+        };
+        let (mantissa, exponent) =
+            scientific.split_once('e').unwrap_or((&scientific, "0"));
+        let exponent: i32 = exponent.parse().unwrap_or(0);
+        let (sign, mantissa) = match mantissa.strip_prefix('-') {
+            Some(mantissa) => ("-", mantissa),
+            None => ("", mantissa),
+        };
+        let mut digits: String =
+            mantissa.chars().filter(|&c| c != '.').collect();
+        while digits.len() > 1 && digits.ends_with('0') {
+            digits.pop();
+        }
+
+        let mut out = String::from(sign);
+        if exponent < self.scientific_below
+            || exponent >= self.scientific_from
+        {
+            out.push_str(&digits[..1]);
+            if digits.len() > 1 {
+                out.push('.');
+                out.push_str(&digits[1..]);
+            } else if self.force_decimal_point {
+                out.push_str(".0");
+            }
+            out.push('e');
+            if self.force_decimal_point && exponent >= 0 {
+                out.push('+');
+            }
+            out.push_str(itoa::Buffer::new().format(exponent));
+        } else if exponent < 0 {
+            out.push_str("0.");
+            for _ in 1..exponent.unsigned_abs() {
+                out.push('0');
+            }
+            out.push_str(&digits);
+        } else {
+            let point = exponent as usize + 1;
+            if digits.len() > point {
+                out.push_str(&digits[..point]);
+                out.push('.');
+                out.push_str(&digits[point..]);
+            } else {
+                out.push_str(&digits);
+                for _ in digits.len()..point {
+                    out.push('0');
+                }
+                out.push_str(".0");
+            }
+        }
+        out
+    }
+}
+
+/// The style in which [`Serializer`] writes strings that span several
+/// lines.
+///
+/// Whichever style is chosen, the string reads back unchanged. For block
+/// scalars this means that the chomping indicator follows the end of the
+/// string: `|` or `>` when it ends with a single line break, `|-` or `>-`
+/// when it ends without one, and `|+` or `>+` when it ends with several.
+/// Strings that cannot be written as a block scalar, such as those with
+/// trailing spaces on a line, or those inside a flow collection, are written
+/// double-quoted instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MultilineStyle {
+    /// Literal block scalars (`|`), keeping every line break. This is the
+    /// default.
+    Literal,
+    /// Folded block scalars (`>`), in which single line breaks are written
+    /// as blank lines.
+    Folded,
+    /// Double-quoted scalars with `\n` escapes.
+    DoubleQuoted,
+}
+
+impl Default for MultilineStyle {
+    fn default() -> Self {
+        MultilineStyle::Literal
+    }
+}
+
+/// Name of the newtype struct through which
+/// [`with::literal_block`](crate::with::literal_block) asks for a literal
+/// block scalar.
+pub(crate) const LITERAL_BLOCK: &str =
+    "$serde_yml::private::LiteralBlock";
+
+/// Name of the newtype struct through which
+/// [`with::folded_block`](crate::with::folded_block) asks for a folded
+/// block scalar.
+pub(crate) const FOLDED_BLOCK: &str =
+    "$serde_yml::private::FoldedBlock";
+
 /// The state of the serializer.
 #[derive(Debug)]
 pub enum State {
@@ -80,20 +718,182 @@ where
 {
     /// Creates a new YAML serializer.
     pub fn new(writer: W) -> Self {
-        let mut emitter = Emitter::new({
-            let writer = Box::new(writer);
-            unsafe {
-                mem::transmute::<Box<dyn io::Write>, Box<dyn io::Write>>(
-                    writer,
-                )
-            }
-        });
-        emitter.emit(Event::StreamStart).unwrap();
         Serializer {
             depth: 0,
             state: State::NothingInParticular,
-            emitter,
-            writer: PhantomData,
+            emitter: Emitter::new(writer),
+            block_style: None,
+            raw_yaml: false,
+            stream_started: false,
+            options: SerializerOptions::default(),
+            in_key: false,
+            empty_block: false,
+            held: Vec::new(),
+        }
+    }
+
+    /// Applies `options` to the documents written by this serializer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Serialize;
+    /// use serde_yml::ser::SerializerOptions;
+    ///
+    /// let options = SerializerOptions {
+    ///     explicit_document_start: true,
+    ///     ..SerializerOptions::default()
+    /// };
+    /// let mut buffer = Vec::new();
+    /// let mut ser =
+    ///     serde_yml::Serializer::new(&mut buffer).with_options(options);
+    /// "a".serialize(&mut ser).unwrap();
+    /// "b".serialize(&mut ser).unwrap();
+    /// assert_eq!(buffer, b"--- a\n--- b\n");
+    /// ```
+    pub fn with_options(mut self, options: SerializerOptions) -> Self {
+        self.set_options(options);
+        self
+    }
+
+    /// Replaces the options of this serializer. The new options apply from
+    /// the next document on, so each document of a stream can be written
+    /// with its own document markers.
+    pub fn set_options(&mut self, options: SerializerOptions) {
+        self.emitter.set_explicit_document_start(
+            options.explicit_document_start,
+        );
+        self.emitter
+            .set_explicit_document_end(options.explicit_document_end);
+        self.emitter.set_canonical(options.canonical);
+        self.emitter.set_unicode(!options.escape_unicode);
+        self.emitter.set_line_break(options.line_break);
+        self.emitter.set_encoding(options.encoding);
+        self.options = options;
+    }
+
+    /// Returns the options of this serializer.
+    pub fn options(&self) -> &SerializerOptions {
+        &self.options
+    }
+
+    /// Starts the stream before the first document or at the end of an
+    /// empty stream.
+    fn start_stream(&mut self) -> Result<()> {
+        if !self.stream_started {
+            self.emitter.emit(Event::StreamStart)?;
+            self.stream_started = true;
+        }
+        Ok(())
+    }
+
+    /// Writes the nodes of a fragment of YAML text, keeping the tag and
+    /// style of every scalar.
+    fn emit_raw_yaml(&mut self, yaml: &str) -> Result<()> {
+        let mut loader = Loader::new(Progress::Str(yaml))?;
+        let document = match loader.next_document() {
+            Some(document) => document,
+            None => return Err(error::new(ErrorImpl::EndOfStream)),
+        };
+        if let Some(parse_error) = document.error {
+            return Err(error::shared(parse_error));
+        }
+        if loader.next_document().is_some() {
+            return Err(error::new(ErrorImpl::MoreThanOneDocument));
+        }
+        self.emit_raw_node(&document, &mut 0)
+    }
+
+    fn emit_raw_node(
+        &mut self,
+        document: &Document<'_>,
+        pos: &mut usize,
+    ) -> Result<()> {
+        let event = match document.events.get(*pos) {
+            Some((event, _)) => event,
+            None => return Err(error::new(ErrorImpl::EndOfStream)),
+        };
+        *pos += 1;
+        let tag = |tag: &Option<Tag>| {
+            tag.as_ref()
+                .map(|tag| String::from_utf8_lossy(tag).into_owned())
+        };
+        match event {
+            de::Event::Alias(id) => {
+                match document.anchor_event_map.get(id) {
+                    Some(&target) => {
+                        self.emit_raw_node(document, &mut { target })
+                    }
+                    None => Err(error::new(ErrorImpl::EndOfStream)),
+                }
+            }
+            de::Event::Scalar(scalar) => {
+                let style = match scalar.style {
+                    libyml::parser::ScalarStyle::Plain => {
+                        ScalarStyle::Plain
+                    }
+                    libyml::parser::ScalarStyle::SingleQuoted => {
+                        ScalarStyle::SingleQuoted
+                    }
+                    libyml::parser::ScalarStyle::DoubleQuoted => {
+                        ScalarStyle::DoubleQuoted
+                    }
+                    libyml::parser::ScalarStyle::Literal => {
+                        ScalarStyle::Literal
+                    }
+                    libyml::parser::ScalarStyle::Folded => {
+                        ScalarStyle::Folded
+                    }
+                };
+                self.emit_scalar(Scalar {
+                    anchor: None,
+                    tag: tag(&scalar.tag),
+                    value: &String::from_utf8_lossy(&scalar.value),
+                    style,
+                })
+            }
+            de::Event::SequenceStart(sequence) => {
+                self.flush_mapping_start()?;
+                self.value_start()?;
+                let tag =
+                    self.take_tag().or_else(|| tag(&sequence.tag));
+                self.emit_event(Event::SequenceStart(Sequence {
+                    anchor: None,
+                    tag,
+                }))?;
+                while !matches!(
+                    document.events.get(*pos),
+                    Some((de::Event::SequenceEnd, _)) | None
+                ) {
+                    self.emit_raw_node(document, pos)?;
+                }
+                *pos += 1;
+                self.emit_sequence_end()
+            }
+            de::Event::MappingStart(mapping) => {
+                self.flush_mapping_start()?;
+                self.value_start()?;
+                let tag = self.take_tag().or_else(|| tag(&mapping.tag));
+                // Entries of raw YAML are written as they are, without
+                // the key order of the options.
+                self.emit_event(Event::MappingStart(Mapping {
+                    anchor: None,
+                    tag,
+                }))?;
+                while !matches!(
+                    document.events.get(*pos),
+                    Some((de::Event::MappingEnd, _)) | None
+                ) {
+                    self.emit_raw_node(document, pos)?;
+                    self.emit_raw_node(document, pos)?;
+                }
+                *pos += 1;
+                self.emit_event(Event::MappingEnd)?;
+                self.value_end()
+            }
+            de::Event::SequenceEnd
+            | de::Event::MappingEnd
+            | de::Event::Void => ser::Serializer::serialize_unit(self),
         }
     }
 
@@ -106,10 +906,10 @@ where
 
     /// Unwrap the underlying `io::Write` object from the `Serializer`.
     pub fn into_inner(mut self) -> Result<W> {
+        self.start_stream()?;
         self.emitter.emit(Event::StreamEnd)?;
         self.emitter.flush()?;
-        let writer = self.emitter.into_inner();
-        Ok(*unsafe { Box::from_raw(Box::into_raw(writer).cast::<W>()) })
+        Ok(self.emitter.into_inner())
     }
 
     /// Emit a scalar value.
@@ -122,7 +922,7 @@ where
             scalar.tag = Some(tag);
         }
         self.value_start()?;
-        self.emitter.emit(Event::Scalar(scalar))?;
+        self.emit_event(Event::Scalar(scalar))?;
         self.value_end()
     }
 
@@ -130,14 +930,18 @@ where
     pub fn emit_sequence_start(&mut self) -> Result<()> {
         self.flush_mapping_start()?;
         self.value_start()?;
-        let tag = self.take_tag();
-        self.emitter.emit(Event::SequenceStart(Sequence { tag }))?;
+        let tag =
+            self.take_tag().or_else(|| self.canonical_tag(Tag::SEQ));
+        self.emit_event(Event::SequenceStart(Sequence {
+            anchor: None,
+            tag,
+        }))?;
         Ok(())
     }
 
     /// Emit a sequence end.
     pub fn emit_sequence_end(&mut self) -> Result<()> {
-        self.emitter.emit(Event::SequenceEnd)?;
+        self.emit_event(Event::SequenceEnd)?;
         self.value_end()
     }
 
@@ -145,20 +949,154 @@ where
     pub fn emit_mapping_start(&mut self) -> Result<()> {
         self.flush_mapping_start()?;
         self.value_start()?;
-        let tag = self.take_tag();
-        self.emitter.emit(Event::MappingStart(Mapping { tag }))?;
+        let tag =
+            self.take_tag().or_else(|| self.canonical_tag(Tag::MAP));
+        self.emit_event(Event::MappingStart(Mapping {
+            anchor: None,
+            tag,
+        }))?;
+        if self.options.key_order.is_some() {
+            self.held.push(Vec::new());
+        }
         Ok(())
     }
 
     /// Emit a mapping end.
     pub fn emit_mapping_end(&mut self) -> Result<()> {
-        self.emitter.emit(Event::MappingEnd)?;
+        if let Some(key_order) = self.options.key_order {
+            let events = self.held.pop().unwrap_or_default();
+            for event in sort_entries(events, key_order) {
+                self.emit_held(event)?;
+            }
+        }
+        self.emit_event(Event::MappingEnd)?;
         self.value_end()
     }
 
+    /// Emits a node event, or holds it back while the entries of a mapping
+    /// wait to be sorted by [`SerializerOptions::key_order`].
+    fn emit_event(&mut self, event: Event<'_>) -> Result<()> {
+        let held = match self.held.last_mut() {
+            Some(held) => held,
+            None => return Ok(self.emitter.emit(event)?),
+        };
+        held.push(match event {
+            Event::Scalar(scalar) => HeldEvent::Scalar {
+                anchor: scalar.anchor,
+                tag: scalar.tag,
+                value: scalar.value.to_owned(),
+                style: scalar.style,
+            },
+            Event::SequenceStart(sequence) => {
+                HeldEvent::SequenceStart(sequence)
+            }
+            Event::SequenceEnd => HeldEvent::SequenceEnd,
+            Event::MappingStart(mapping) => {
+                HeldEvent::MappingStart(mapping)
+            }
+            Event::MappingEnd => HeldEvent::MappingEnd,
+            event => return Ok(self.emitter.emit(event)?),
+        });
+        Ok(())
+    }
+
+    /// Emits an event that was held back.
+    fn emit_held(&mut self, event: HeldEvent) -> Result<()> {
+        self.emit_event(match event {
+            HeldEvent::Scalar {
+                anchor,
+                tag,
+                ref value,
+                style,
+            } => Event::Scalar(Scalar {
+                anchor,
+                tag,
+                value,
+                style,
+            }),
+            HeldEvent::SequenceStart(sequence) => {
+                Event::SequenceStart(sequence)
+            }
+            HeldEvent::SequenceEnd => Event::SequenceEnd,
+            HeldEvent::MappingStart(mapping) => {
+                Event::MappingStart(mapping)
+            }
+            HeldEvent::MappingEnd => Event::MappingEnd,
+        })
+    }
+
+    /// Returns whether a struct field, or a mapping entry if `field` is
+    /// false, with `value` is left out. Only struct fields are left out for
+    /// being `None`, so that maps keep all their keys.
+    fn skips<T>(&self, value: &T, field: bool) -> bool
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        let skip_none = field && self.options.skip_none_fields;
+        let skip_empty =
+            self.options.empty_collections == EmptyCollections::Skip;
+        if !skip_none && !skip_empty {
+            return false;
+        }
+        match shape_of(value) {
+            Shape::None => skip_none,
+            Shape::Empty => skip_empty,
+            Shape::Other => false,
+        }
+    }
+
+    /// Writes a collection of `len` entries as an empty scalar if it has
+    /// none and [`EmptyCollections::Block`] asks for it, returning whether
+    /// it did.
+    fn emit_empty_block(&mut self, len: Option<usize>) -> Result<bool> {
+        if len != Some(0)
+            || self.options.empty_collections != EmptyCollections::Block
+            || self.depth == 0
+            || self.in_key
+            || matches!(self.state, State::FoundTag(_))
+        {
+            return Ok(false);
+        }
+        self.emit_scalar(Scalar {
+            anchor: None,
+            tag: None,
+            value: "",
+            style: ScalarStyle::Plain,
+        })?;
+        self.empty_block = true;
+        Ok(true)
+    }
+
+    /// Serializes a mapping key, noting that it is one.
+    fn serialize_key_node<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        let in_key = mem::replace(&mut self.in_key, true);
+        let result = key.serialize(&mut *self);
+        self.in_key = in_key;
+        result
+    }
+
+    /// Writes the value of an entry written through
+    /// `SerializeMap::serialize_entry`, whose key may have been the tag of
+    /// an enum.
+    fn serialize_entry_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        let tagged = matches!(self.state, State::FoundTag(_));
+        value.serialize(&mut *self)?;
+        if tagged {
+            self.state = State::AlreadyTagged;
+        }
+        Ok(())
+    }
+
     /// Emit a value start.
     pub fn value_start(&mut self) -> Result<()> {
         if self.depth == 0 {
+            self.start_stream()?;
             self.emitter.emit(Event::DocumentStart)?;
         }
         self.depth += 1;
@@ -189,6 +1127,20 @@ where
         }
     }
 
+    /// Returns the core tag `tag` for a scalar when
+    /// [`SerializerOptions::explicit_core_tags`] or
+    /// [`SerializerOptions::canonical`] asks for it.
+    fn core_tag(&self, tag: &str) -> Option<String> {
+        (self.options.explicit_core_tags || self.options.canonical)
+            .then(|| tag.to_owned())
+    }
+
+    /// Returns the core tag `tag` for a collection when
+    /// [`SerializerOptions::canonical`] asks for it.
+    fn canonical_tag(&self, tag: &str) -> Option<String> {
+        self.options.canonical.then(|| tag.to_owned())
+    }
+
     /// Flush the mapping start.
     pub fn flush_mapping_start(&mut self) -> Result<()> {
         if let State::CheckForTag = self.state {
@@ -218,7 +1170,8 @@ where
 
     fn serialize_bool(self, v: bool) -> Result<()> {
         self.emit_scalar(Scalar {
-            tag: None,
+            anchor: None,
+            tag: self.core_tag(Tag::BOOL),
             value: if v { "true" } else { "false" },
             style: ScalarStyle::Plain,
         })
@@ -226,7 +1179,8 @@ where
 
     fn serialize_i8(self, v: i8) -> Result<()> {
         self.emit_scalar(Scalar {
-            tag: None,
+            anchor: None,
+            tag: self.core_tag(Tag::INT),
             value: itoa::Buffer::new().format(v),
             style: ScalarStyle::Plain,
         })
@@ -234,7 +1188,8 @@ where
 
     fn serialize_i16(self, v: i16) -> Result<()> {
         self.emit_scalar(Scalar {
-            tag: None,
+            anchor: None,
+            tag: self.core_tag(Tag::INT),
             value: itoa::Buffer::new().format(v),
             style: ScalarStyle::Plain,
         })
@@ -242,7 +1197,8 @@ where
 
     fn serialize_i32(self, v: i32) -> Result<()> {
         self.emit_scalar(Scalar {
-            tag: None,
+            anchor: None,
+            tag: self.core_tag(Tag::INT),
             value: itoa::Buffer::new().format(v),
             style: ScalarStyle::Plain,
         })
@@ -250,7 +1206,8 @@ where
 
     fn serialize_i64(self, v: i64) -> Result<()> {
         self.emit_scalar(Scalar {
-            tag: None,
+            anchor: None,
+            tag: self.core_tag(Tag::INT),
             value: itoa::Buffer::new().format(v),
             style: ScalarStyle::Plain,
         })
@@ -258,7 +1215,8 @@ where
 
     fn serialize_i128(self, v: i128) -> Result<()> {
         self.emit_scalar(Scalar {
-            tag: None,
+            anchor: None,
+            tag: self.core_tag(Tag::INT),
             value: itoa::Buffer::new().format(v),
             style: ScalarStyle::Plain,
         })
@@ -266,7 +1224,8 @@ where
 
     fn serialize_u8(self, v: u8) -> Result<()> {
         self.emit_scalar(Scalar {
-            tag: None,
+            anchor: None,
+            tag: self.core_tag(Tag::INT),
             value: itoa::Buffer::new().format(v),
             style: ScalarStyle::Plain,
         })
@@ -274,7 +1233,8 @@ where
 
     fn serialize_u16(self, v: u16) -> Result<()> {
         self.emit_scalar(Scalar {
-            tag: None,
+            anchor: None,
+            tag: self.core_tag(Tag::INT),
             value: itoa::Buffer::new().format(v),
             style: ScalarStyle::Plain,
         })
@@ -282,7 +1242,8 @@ where
 
     fn serialize_u32(self, v: u32) -> Result<()> {
         self.emit_scalar(Scalar {
-            tag: None,
+            anchor: None,
+            tag: self.core_tag(Tag::INT),
             value: itoa::Buffer::new().format(v),
             style: ScalarStyle::Plain,
         })
@@ -290,7 +1251,8 @@ where
 
     fn serialize_u64(self, v: u64) -> Result<()> {
         self.emit_scalar(Scalar {
-            tag: None,
+            anchor: None,
+            tag: self.core_tag(Tag::INT),
             value: itoa::Buffer::new().format(v),
             style: ScalarStyle::Plain,
         })
@@ -298,7 +1260,8 @@ where
 
     fn serialize_u128(self, v: u128) -> Result<()> {
         self.emit_scalar(Scalar {
-            tag: None,
+            anchor: None,
+            tag: self.core_tag(Tag::INT),
             value: itoa::Buffer::new().format(v),
             style: ScalarStyle::Plain,
         })
@@ -306,15 +1269,25 @@ where
 
     fn serialize_f32(self, v: f32) -> Result<()> {
         let mut buffer = ryu::Buffer::new();
+        let formatted;
+        let float_format = self.options.float_format;
+        let special_float_style = self.options.special_float_style;
         self.emit_scalar(Scalar {
-            tag: None,
+            anchor: None,
+            tag: self.core_tag(Tag::FLOAT),
             value: match v.classify() {
-                num::FpCategory::Infinite if v.is_sign_positive() => {
-                    ".inf"
+                num::FpCategory::Infinite => special_float_style
+                    .spell(false, v.is_sign_positive()),
+                num::FpCategory::Nan => {
+                    special_float_style.spell(true, true)
+                }
+                _ if float_format == FloatFormat::default() => {
+                    buffer.format_finite(v)
+                }
+                _ => {
+                    formatted = float_format.format(v);
+                    &formatted
                 }
-                num::FpCategory::Infinite => "-.inf",
-                num::FpCategory::Nan => ".nan",
-                _ => buffer.format_finite(v),
             },
             style: ScalarStyle::Plain,
         })
@@ -322,15 +1295,25 @@ where
 
     fn serialize_f64(self, v: f64) -> Result<()> {
         let mut buffer = ryu::Buffer::new();
+        let formatted;
+        let float_format = self.options.float_format;
+        let special_float_style = self.options.special_float_style;
         self.emit_scalar(Scalar {
-            tag: None,
+            anchor: None,
+            tag: self.core_tag(Tag::FLOAT),
             value: match v.classify() {
-                num::FpCategory::Infinite if v.is_sign_positive() => {
-                    ".inf"
+                num::FpCategory::Infinite => special_float_style
+                    .spell(false, v.is_sign_positive()),
+                num::FpCategory::Nan => {
+                    special_float_style.spell(true, true)
+                }
+                _ if float_format == FloatFormat::default() => {
+                    buffer.format_finite(v)
+                }
+                _ => {
+                    formatted = float_format.format(v);
+                    &formatted
                 }
-                num::FpCategory::Infinite => "-.inf",
-                num::FpCategory::Nan => ".nan",
-                _ => buffer.format_finite(v),
             },
             style: ScalarStyle::Plain,
         })
@@ -338,83 +1321,35 @@ where
 
     fn serialize_char(self, value: char) -> Result<()> {
         self.emit_scalar(Scalar {
-            tag: None,
+            anchor: None,
+            tag: self.core_tag(Tag::STR),
             value: value.encode_utf8(&mut [0u8; 4]),
             style: ScalarStyle::SingleQuoted,
         })
     }
 
     fn serialize_str(self, value: &str) -> Result<()> {
-        struct InferScalarStyle;
-
-        impl Visitor<'_> for InferScalarStyle {
-            type Value = ScalarStyle;
-
-            fn expecting(
-                &self,
-                formatter: &mut fmt::Formatter<'_>,
-            ) -> fmt::Result {
-                formatter.write_str("I wonder")
-            }
-
-            fn visit_bool<E>(self, _v: bool) -> Result<Self::Value, E> {
-                Ok(ScalarStyle::SingleQuoted)
-            }
-
-            fn visit_i64<E>(self, _v: i64) -> Result<Self::Value, E> {
-                Ok(ScalarStyle::SingleQuoted)
-            }
-
-            fn visit_i128<E>(self, _v: i128) -> Result<Self::Value, E> {
-                Ok(ScalarStyle::SingleQuoted)
-            }
-
-            fn visit_u64<E>(self, _v: u64) -> Result<Self::Value, E> {
-                Ok(ScalarStyle::SingleQuoted)
-            }
-
-            fn visit_u128<E>(self, _v: u128) -> Result<Self::Value, E> {
-                Ok(ScalarStyle::SingleQuoted)
-            }
-
-            fn visit_f64<E>(self, _v: f64) -> Result<Self::Value, E> {
-                Ok(ScalarStyle::SingleQuoted)
-            }
-
-            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
-                if crate::de::ambiguous_string(v) {
-                    Ok(ScalarStyle::SingleQuoted)
-                } else {
-                    Ok(ScalarStyle::Any)
-                }
-            }
-
-            fn visit_unit<E>(self) -> Result<Self::Value, E> {
-                Ok(ScalarStyle::SingleQuoted)
-            }
+        if mem::take(&mut self.raw_yaml) {
+            return self.emit_raw_yaml(value);
         }
-
-        let style = match value {
-            // Backwards compatibility with old YAML boolean scalars.
-            // See https://yaml.org/type/bool.html
-            "y" | "Y" | "yes" | "Yes" | "YES" | "n" | "N" | "no"
-            | "No" | "NO" | "true" | "True" | "TRUE" | "false"
-            | "False" | "FALSE" | "on" | "On" | "ON" | "off"
-            | "Off" | "OFF" => ScalarStyle::SingleQuoted,
-            _ if value.contains('\n') => ScalarStyle::Literal,
-            _ => {
-                let result = crate::de::visit_untagged_scalar(
-                    InferScalarStyle,
-                    value,
-                    None,
-                    libyml::parser::ScalarStyle::Plain,
-                );
-                result.unwrap_or(ScalarStyle::Any)
-            }
+        let style = match self.block_style.take() {
+            Some(style) => style,
+            None => match infer_scalar_style(value) {
+                ScalarStyle::Literal => {
+                    match self.options.multiline_style {
+                        MultilineStyle::Literal => ScalarStyle::Literal,
+                        MultilineStyle::Folded => ScalarStyle::Folded,
+                        MultilineStyle::DoubleQuoted => {
+                            ScalarStyle::DoubleQuoted
+                        }
+                    }
+                }
+                style => style,
+            },
         };
-
         self.emit_scalar(Scalar {
-            tag: None,
+            anchor: None,
+            tag: self.core_tag(Tag::STR),
             value,
             style,
         })
@@ -426,8 +1361,13 @@ where
 
     fn serialize_unit(self) -> Result<()> {
         self.emit_scalar(Scalar {
-            tag: None,
-            value: "null",
+            anchor: None,
+            tag: self.core_tag(Tag::NULL),
+            value: match self.options.null_style {
+                NullStyle::Literal => "null",
+                NullStyle::Empty if !self.in_key => "",
+                NullStyle::Tilde | NullStyle::Empty => "~",
+            },
             style: ScalarStyle::Plain,
         })
     }
@@ -447,12 +1387,18 @@ where
 
     fn serialize_newtype_struct<T>(
         self,
-        _name: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<()>
     where
         T: ?Sized + ser::Serialize,
     {
+        self.block_style = match name {
+            LITERAL_BLOCK => Some(ScalarStyle::Literal),
+            FOLDED_BLOCK => Some(ScalarStyle::Folded),
+            _ => None,
+        };
+        self.raw_yaml = name == RAW_YAML;
         value.serialize(self)
     }
 
@@ -486,9 +1432,11 @@ where
 
     fn serialize_seq(
         self,
-        _len: Option<usize>,
+        len: Option<usize>,
     ) -> Result<Self::SerializeSeq> {
-        self.emit_sequence_start()?;
+        if !self.emit_empty_block(len)? {
+            self.emit_sequence_start()?;
+        }
         Ok(self)
     }
 
@@ -528,11 +1476,17 @@ where
         self,
         len: Option<usize>,
     ) -> Result<Self::SerializeMap> {
+        if self.emit_empty_block(len)? {
+            return Ok(self);
+        }
         if len == Some(1) {
             self.state = if let State::FoundTag(_) = self.state {
                 self.emit_mapping_start()?;
                 State::CheckForDuplicateTag
             } else {
+                // A mapping used as the key of a pending single-entry
+                // mapping is not a tag, so the outer mapping starts here.
+                self.flush_mapping_start()?;
                 State::CheckForTag
             };
         } else {
@@ -608,6 +1562,9 @@ where
     }
 
     fn end(self) -> Result<()> {
+        if mem::take(&mut self.empty_block) {
+            return Ok(());
+        }
         self.emit_sequence_end()
     }
 }
@@ -681,7 +1638,7 @@ where
         T: ?Sized + ser::Serialize,
     {
         self.flush_mapping_start()?;
-        key.serialize(&mut **self)
+        self.serialize_key_node(key)
     }
 
     fn serialize_value<T>(&mut self, value: &T) -> Result<()>
@@ -700,16 +1657,17 @@ where
         K: ?Sized + ser::Serialize,
         V: ?Sized + ser::Serialize,
     {
-        key.serialize(&mut **self)?;
-        let tagged = matches!(self.state, State::FoundTag(_));
-        value.serialize(&mut **self)?;
-        if tagged {
-            self.state = State::AlreadyTagged;
+        if self.skips(value, false) {
+            return Ok(());
         }
-        Ok(())
+        self.serialize_key_node(key)?;
+        self.serialize_entry_value(value)
     }
 
     fn end(self) -> Result<()> {
+        if mem::take(&mut self.empty_block) {
+            return Ok(());
+        }
         if let State::CheckForTag = self.state {
             self.emit_mapping_start()?;
         }
@@ -736,6 +1694,9 @@ where
     where
         V: ?Sized + ser::Serialize,
     {
+        if self.skips(value, true) {
+            return Ok(());
+        }
         self.serialize_str(key)?;
         value.serialize(&mut **self)
     }
@@ -760,6 +1721,9 @@ where
     where
         V: ?Sized + ser::Serialize,
     {
+        if self.skips(v, true) {
+            return Ok(());
+        }
         self.serialize_str(field)?;
         v.serialize(&mut **self)
     }
@@ -769,8 +1733,370 @@ where
     }
 }
 
-/// Serialize the given data structure as YAML into the IO stream.
-///
+/// What [`Serializer`] needs to know about a value to decide whether to
+/// leave it out.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    /// `None`.
+    None,
+    /// A sequence or mapping known to have no entries.
+    Empty,
+    /// Anything else.
+    Other,
+}
+
+/// Returns the shape of `value`, without serializing any of its contents.
+fn shape_of<T>(value: &T) -> Shape
+where
+    T: ?Sized + ser::Serialize,
+{
+    value.serialize(ShapeOf).unwrap_or(Shape::Other)
+}
+
+/// Serializer that tells the [`Shape`] of a value.
+struct ShapeOf;
+
+/// Error with which [`ShapeOf`] stops at a collection that may have
+/// entries.
+#[derive(Debug)]
+struct NotEmpty;
+
+impl Display for NotEmpty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("collection is not empty")
+    }
+}
+
+impl StdError for NotEmpty {}
+
+impl ser::Error for NotEmpty {
+    fn custom<T: Display>(_msg: T) -> Self {
+        NotEmpty
+    }
+}
+
+type ShapeResult<T = Shape> = core::result::Result<T, NotEmpty>;
+
+/// An empty collection seen by [`ShapeOf`].
+struct EmptyShape;
+
+impl ser::SerializeSeq for EmptyShape {
+    type Ok = Shape;
+    type Error = NotEmpty;
+
+    fn serialize_element<T>(&mut self, _value: &T) -> ShapeResult<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        Err(NotEmpty)
+    }
+
+    fn end(self) -> ShapeResult {
+        Ok(Shape::Empty)
+    }
+}
+
+impl ser::SerializeMap for EmptyShape {
+    type Ok = Shape;
+    type Error = NotEmpty;
+
+    fn serialize_key<T>(&mut self, _key: &T) -> ShapeResult<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        Err(NotEmpty)
+    }
+
+    fn serialize_value<T>(&mut self, _value: &T) -> ShapeResult<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        Err(NotEmpty)
+    }
+
+    fn end(self) -> ShapeResult {
+        Ok(Shape::Empty)
+    }
+}
+
+impl ser::SerializeStruct for EmptyShape {
+    type Ok = Shape;
+    type Error = NotEmpty;
+
+    fn serialize_field<T>(
+        &mut self,
+        _key: &'static str,
+        _value: &T,
+    ) -> ShapeResult<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        Err(NotEmpty)
+    }
+
+    fn end(self) -> ShapeResult {
+        Ok(Shape::Empty)
+    }
+}
+
+macro_rules! other_shape_methods {
+    ($($method:ident($ty:ty);)*) => {
+        $(
+            fn $method(self, _v: $ty) -> ShapeResult {
+                Ok(Shape::Other)
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for ShapeOf {
+    type Ok = Shape;
+    type Error = NotEmpty;
+
+    type SerializeSeq = EmptyShape;
+    type SerializeTuple = ser::Impossible<Shape, NotEmpty>;
+    type SerializeTupleStruct = ser::Impossible<Shape, NotEmpty>;
+    type SerializeTupleVariant = ser::Impossible<Shape, NotEmpty>;
+    type SerializeMap = EmptyShape;
+    type SerializeStruct = EmptyShape;
+    type SerializeStructVariant = ser::Impossible<Shape, NotEmpty>;
+
+    other_shape_methods! {
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_i128(i128);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_u128(u128);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_char(char);
+        serialize_str(&str);
+        serialize_bytes(&[u8]);
+    }
+
+    fn serialize_none(self) -> ShapeResult {
+        Ok(Shape::None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> ShapeResult
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        match value.serialize(self)? {
+            Shape::None => Ok(Shape::Other),
+            shape => Ok(shape),
+        }
+    }
+
+    fn serialize_unit(self) -> ShapeResult {
+        Ok(Shape::Other)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> ShapeResult {
+        Ok(Shape::Other)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> ShapeResult {
+        Ok(Shape::Other)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> ShapeResult
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> ShapeResult
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        Ok(Shape::Other)
+    }
+
+    fn serialize_seq(
+        self,
+        len: Option<usize>,
+    ) -> ShapeResult<Self::SerializeSeq> {
+        match len {
+            Some(0) => Ok(EmptyShape),
+            _ => Err(NotEmpty),
+        }
+    }
+
+    fn serialize_tuple(
+        self,
+        _len: usize,
+    ) -> ShapeResult<Self::SerializeTuple> {
+        Err(NotEmpty)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> ShapeResult<Self::SerializeTupleStruct> {
+        Err(NotEmpty)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> ShapeResult<Self::SerializeTupleVariant> {
+        Err(NotEmpty)
+    }
+
+    fn serialize_map(
+        self,
+        len: Option<usize>,
+    ) -> ShapeResult<Self::SerializeMap> {
+        match len {
+            Some(0) => Ok(EmptyShape),
+            _ => Err(NotEmpty),
+        }
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> ShapeResult<Self::SerializeStruct> {
+        match len {
+            0 => Ok(EmptyShape),
+            _ => Err(NotEmpty),
+        }
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> ShapeResult<Self::SerializeStructVariant> {
+        Err(NotEmpty)
+    }
+
+    fn collect_str<T>(self, _value: &T) -> ShapeResult
+    where
+        T: ?Sized + Display,
+    {
+        Ok(Shape::Other)
+    }
+}
+
+/// Chooses the scalar style used to emit a string so that it reads back as
+/// the same string.
+pub(crate) fn infer_scalar_style(value: &str) -> ScalarStyle {
+    struct InferScalarStyle;
+
+    #[cfg_attr(not(feature = "bigint"), allow(single_use_lifetimes))]
+    impl<'de> Visitor<'de> for InferScalarStyle {
+        type Value = ScalarStyle;
+
+        fn expecting(
+            &self,
+            formatter: &mut fmt::Formatter<'_>,
+        ) -> fmt::Result {
+            formatter.write_str("I wonder")
+        }
+
+        fn visit_bool<E>(self, _v: bool) -> Result<Self::Value, E> {
+            Ok(ScalarStyle::SingleQuoted)
+        }
+
+        fn visit_i64<E>(self, _v: i64) -> Result<Self::Value, E> {
+            Ok(ScalarStyle::SingleQuoted)
+        }
+
+        fn visit_i128<E>(self, _v: i128) -> Result<Self::Value, E> {
+            Ok(ScalarStyle::SingleQuoted)
+        }
+
+        fn visit_u64<E>(self, _v: u64) -> Result<Self::Value, E> {
+            Ok(ScalarStyle::SingleQuoted)
+        }
+
+        fn visit_u128<E>(self, _v: u128) -> Result<Self::Value, E> {
+            Ok(ScalarStyle::SingleQuoted)
+        }
+
+        fn visit_f64<E>(self, _v: f64) -> Result<Self::Value, E> {
+            Ok(ScalarStyle::SingleQuoted)
+        }
+
+        // An integer beyond 128 bits, with the bigint feature.
+        #[cfg(feature = "bigint")]
+        fn visit_newtype_struct<D>(
+            self,
+            _deserializer: D,
+        ) -> Result<Self::Value, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            Ok(ScalarStyle::SingleQuoted)
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
+            if de::ambiguous_string(v) {
+                Ok(ScalarStyle::SingleQuoted)
+            } else {
+                Ok(ScalarStyle::Any)
+            }
+        }
+
+        fn visit_unit<E>(self) -> Result<Self::Value, E> {
+            Ok(ScalarStyle::SingleQuoted)
+        }
+    }
+
+    match value {
+        // Backwards compatibility with old YAML boolean scalars.
+        // See https://yaml.org/type/bool.html
+        "y" | "Y" | "yes" | "Yes" | "YES" | "n" | "N" | "no" | "No"
+        | "NO" | "true" | "True" | "TRUE" | "false" | "False"
+        | "FALSE" | "on" | "On" | "ON" | "off" | "Off" | "OFF" => {
+            ScalarStyle::SingleQuoted
+        }
+        _ if value.contains('\n') => ScalarStyle::Literal,
+        _ => {
+            let result = de::visit_untagged_scalar(
+                InferScalarStyle,
+                value,
+                None,
+                libyml::parser::ScalarStyle::Plain,
+            );
+            result.unwrap_or(ScalarStyle::Any)
+        }
+    }
+}
+
+/// Serialize the given data structure as YAML into the IO stream.
+///
 /// Serialization can fail if `T`'s implementation of `Serialize` decides to
 /// return an error.
 pub fn to_writer<W, T>(writer: W, value: &T) -> Result<()>
@@ -782,16 +2108,736 @@ where
     value.serialize(&mut serializer)
 }
 
-/// Serialize the given data structure as a String of YAML.
+/// Serialize the given data structure as YAML into the IO stream, written
+/// as `options` say.
 ///
 /// Serialization can fail if `T`'s implementation of `Serialize` decides to
 /// return an error.
-pub fn to_string<T>(value: &T) -> Result<String>
+pub fn to_writer_with_options<W, T>(
+    writer: W,
+    value: &T,
+    options: SerializerOptions,
+) -> Result<()>
+where
+    W: io::Write,
+    T: ?Sized + ser::Serialize,
+{
+    let mut serializer = Serializer::new(writer).with_options(options);
+    value.serialize(&mut serializer)
+}
+
+/// Serialize the given data structure as YAML into the file at `path`,
+/// creating it or replacing its contents.
+///
+/// The output goes through a buffer and is flushed before returning, so a
+/// failed write is reported rather than lost. Every error names the file:
+/// its message starts with the path and [`Error::file`] returns it.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be created or written, or if `T`'s
+/// implementation of `Serialize` decides to return an error.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// let path = std::env::temp_dir().join("serde_yml_doc_to_file.yaml");
+/// let config = BTreeMap::from([("port", 8080)]);
+/// serde_yml::to_file(&path, &config).unwrap();
+/// assert_eq!(std::fs::read_to_string(&path).unwrap(), "port: 8080\n");
+///
+/// let read: BTreeMap<String, u16> = serde_yml::from_file(&path).unwrap();
+/// assert_eq!(read["port"], 8080);
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[cfg(feature = "std")]
+pub fn to_file<P, T>(path: P, value: &T) -> Result<()>
+where
+    P: AsRef<std::path::Path>,
+    T: ?Sized + ser::Serialize,
+{
+    use std::io::Write as _;
+
+    let path = path.as_ref();
+    std::fs::File::create(path)
+        .map_err(|err| error::new(ErrorImpl::IoError(err)))
+        .and_then(|file| {
+            let mut writer = std::io::BufWriter::new(file);
+            to_writer(&mut writer, value)?;
+            writer
+                .flush()
+                .map_err(|err| error::new(ErrorImpl::IoError(err)))
+        })
+        .map_err(|err| error::in_file(err, path))
+}
+
+/// Serialize the given data structure as YAML into a [`fmt::Write`]
+/// destination, such as a `String` or the `Formatter` of a `Display`
+/// implementation, through an [`io::FmtWriter`]. The output is passed on
+/// as it is written, without being collected into a byte buffer first.
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to
+/// return an error, or if the destination fails.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use std::fmt;
+///
+/// struct Report(BTreeMap<&'static str, u32>);
+///
+/// impl fmt::Display for Report {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         f.write_str("# report\n")?;
+///         serde_yml::to_fmt_writer(f, &self.0).map_err(|_| fmt::Error)
+///     }
+/// }
+///
+/// let report = Report(BTreeMap::from([("errors", 0), ("warnings", 2)]));
+/// assert_eq!(report.to_string(), "# report\nerrors: 0\nwarnings: 2\n");
+/// ```
+pub fn to_fmt_writer<W, T>(writer: W, value: &T) -> Result<()>
+where
+    W: fmt::Write,
+    T: ?Sized + ser::Serialize,
+{
+    to_writer(io::FmtWriter::new(writer), value)
+}
+
+/// Serialize the given data structure as a String of YAML, written as
+/// `options` say, for example with explicit `---` and `...` document
+/// markers.
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to
+/// return an error.
+pub fn to_string_with_options<T>(
+    value: &T,
+    options: SerializerOptions,
+) -> Result<String>
 where
     T: ?Sized + ser::Serialize,
 {
     let mut vec = Vec::with_capacity(128);
-    to_writer(&mut vec, value)?;
+    to_writer_with_options(&mut vec, value, options)?;
     String::from_utf8(vec)
         .map_err(|error| error::new(ErrorImpl::FromUtf8(error)))
 }
+
+/// Serialize the given data structure as a String of YAML.
+///
+/// Single scalars and small flat mappings of scalars are written directly
+/// without going through the libyml emitter; the output is identical either
+/// way. When a value turns out to need the emitter partway through, the
+/// entries written so far are handed over to it and serialization goes on
+/// there, so `T`'s implementation of `Serialize` is called only once.
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to
+/// return an error.
+pub fn to_string<T>(value: &T) -> Result<String>
+where
+    T: ?Sized + ser::Serialize,
+{
+    let mut output = FastOutput {
+        out: String::new(),
+        map: None,
+        scalars: Vec::new(),
+        slow: None,
+    };
+    value.serialize(FastSerializer {
+        output: &mut output,
+        position: FastPosition::Document,
+    })?;
+    match output.slow {
+        None => {
+            let mut out = output.out;
+            // Mappings terminate every entry; a lone scalar still needs its
+            // newline.
+            if !out.ends_with('\n') {
+                out.push('\n');
+            }
+            Ok(out)
+        }
+        Some(serializer) => {
+            String::from_utf8(serializer.emitter.into_inner())
+                .map_err(|error| error::new(ErrorImpl::FromUtf8(error)))
+        }
+    }
+}
+
+/// Upper bound on the number of entries the small-document fast path will
+/// write before handing the value over to the emitter.
+const FAST_PATH_MAX_ENTRIES: usize = 16;
+
+/// Upper bound on the length of strings written by the small-document fast
+/// path. Longer keys would need the explicit `? key` syntax.
+const FAST_PATH_MAX_STR_LEN: usize = 64;
+
+/// Writes a scalar the way the emitter would for a plain document or a
+/// mapping entry, or returns `None` if the fast path cannot guarantee it.
+fn fast_write_str(out: &mut String, value: &str) -> Option<()> {
+    let simple = value.len() <= FAST_PATH_MAX_STR_LEN
+        && !value.starts_with(' ')
+        && !value.ends_with(' ')
+        && value.bytes().all(|b| {
+            b.is_ascii_alphanumeric()
+                || matches!(b, b'_' | b'-' | b'.' | b'/' | b' ')
+        });
+    if !simple {
+        return None;
+    }
+    match infer_scalar_style(value) {
+        ScalarStyle::Any | ScalarStyle::Plain => out.push_str(value),
+        ScalarStyle::SingleQuoted => {
+            out.push('\'');
+            out.push_str(value);
+            out.push('\'');
+        }
+        _ => return None,
+    }
+    Some(())
+}
+
+/// Returns the scalar the fast path wrote as `text`, which is either plain
+/// or single-quoted.
+fn written_scalar(text: &str) -> Scalar<'_> {
+    let (value, style) = match text
+        .strip_prefix('\'')
+        .and_then(|text| text.strip_suffix('\''))
+    {
+        Some(value) => (value, ScalarStyle::SingleQuoted),
+        None => (text, ScalarStyle::Plain),
+    };
+    Scalar {
+        anchor: None,
+        tag: None,
+        value,
+        style,
+    }
+}
+
+/// The output of [`to_string`]: text written by the fast path, until a
+/// value needs the emitter.
+struct FastOutput {
+    /// The text written by the fast path.
+    out: String,
+    /// How the mapping at the top of the document was started, if it is
+    /// one.
+    map: Option<FastMapStart>,
+    /// Where each key and value of that mapping lies in `out`.
+    scalars: Vec<Range<usize>>,
+    /// The serializer the value was handed over to, if it was.
+    slow: Option<Serializer<Vec<u8>>>,
+}
+
+/// The call that started the mapping at the top of a document written by
+/// the fast path.
+#[derive(Clone, Copy)]
+enum FastMapStart {
+    Map(Option<usize>),
+    Struct(&'static str, usize),
+}
+
+/// Where the fast path writes a node.
+#[derive(Clone, Copy, PartialEq)]
+enum FastPosition {
+    /// The whole document.
+    Document,
+    /// A key written through `SerializeMap::serialize_key`.
+    Key,
+    /// A key written through `SerializeMap::serialize_entry` or
+    /// `SerializeStruct::serialize_field`.
+    EntryKey,
+    /// The value of a mapping entry.
+    Value,
+}
+
+impl FastOutput {
+    /// Appends a scalar written as `text`.
+    fn push(&mut self, text: &str) {
+        let start = self.out.len();
+        self.out.push_str(text);
+        self.scalars.push(start..self.out.len());
+    }
+
+    /// Appends the string `v` if the fast path can write it, returning
+    /// whether it did.
+    fn push_str(&mut self, v: &str) -> bool {
+        let start = self.out.len();
+        if fast_write_str(&mut self.out, v).is_none() {
+            return false;
+        }
+        self.scalars.push(start..self.out.len());
+        true
+    }
+
+    /// Returns the serializer the document was handed over to, handing it
+    /// over on the first call. The mapping started by the fast path and
+    /// its keys and values so far are written to the emitter first.
+    fn slow(&mut self) -> Result<&mut Serializer<Vec<u8>>> {
+        if self.slow.is_none() {
+            let mut serializer =
+                Serializer::new(Vec::with_capacity(128));
+            match self.map {
+                Some(FastMapStart::Map(len)) => {
+                    let _ = ser::Serializer::serialize_map(
+                        &mut serializer,
+                        len,
+                    )?;
+                }
+                Some(FastMapStart::Struct(name, len)) => {
+                    let _ = ser::Serializer::serialize_struct(
+                        &mut serializer,
+                        name,
+                        len,
+                    )?;
+                }
+                None => {}
+            }
+            for range in &self.scalars {
+                serializer.emit_scalar(written_scalar(
+                    &self.out[range.clone()],
+                ))?;
+            }
+            self.slow = Some(serializer);
+        }
+        Ok(self.slow.as_mut().expect("the document was handed over"))
+    }
+}
+
+/// Serializer for a node of a small document: a scalar, or at the top a
+/// flat mapping of scalars. Anything else is handed over to the emitter.
+struct FastSerializer<'a> {
+    output: &'a mut FastOutput,
+    position: FastPosition,
+}
+
+impl<'a> FastSerializer<'a> {
+    /// Appends a scalar written as `text`.
+    fn write(self, text: &str) -> Result<()> {
+        self.output.push(text);
+        Ok(())
+    }
+
+    /// Returns the serializer to hand this node over to.
+    fn slow(self) -> Result<&'a mut Serializer<Vec<u8>>> {
+        let serializer = self.output.slow()?;
+        if self.position == FastPosition::Key {
+            // The mapping is started before the key, as
+            // `SerializeMap::serialize_key` does, so that the key is not
+            // taken for the tag of an enum.
+            serializer.flush_mapping_start()?;
+        }
+        Ok(serializer)
+    }
+}
+
+/// Writes the entries of a flat mapping in the fast path, or passes them on
+/// to the emitter once the document has been handed over.
+struct FastMap<'a> {
+    output: &'a mut FastOutput,
+}
+
+impl FastMap<'_> {
+    /// Returns the serializer the document was handed over to, handing it
+    /// over now if `full` says the fast path has written enough.
+    fn slow(
+        &mut self,
+        full: bool,
+    ) -> Result<Option<&mut Serializer<Vec<u8>>>> {
+        if self.output.slow.is_none() && !full {
+            return Ok(None);
+        }
+        self.output.slow().map(Some)
+    }
+
+    /// Writes a key at `position` in the fast path.
+    fn write_key<T>(
+        &mut self,
+        key: &T,
+        position: FastPosition,
+    ) -> Result<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        key.serialize(FastSerializer {
+            output: self.output,
+            position,
+        })?;
+        if self.output.slow.is_none() {
+            self.output.out.push_str(": ");
+        }
+        Ok(())
+    }
+
+    /// Writes a value in the fast path.
+    fn write_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        value.serialize(FastSerializer {
+            output: self.output,
+            position: FastPosition::Value,
+        })?;
+        if self.output.slow.is_none() {
+            self.output.out.push('\n');
+        }
+        Ok(())
+    }
+}
+
+macro_rules! fast_integer_methods {
+    ($($method:ident($ty:ty);)*) => {
+        $(
+            fn $method(self, v: $ty) -> Result<()> {
+                self.write(itoa::Buffer::new().format(v))
+            }
+        )*
+    };
+}
+
+impl<'a> ser::Serializer for FastSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = &'a mut Serializer<Vec<u8>>;
+    type SerializeTuple = &'a mut Serializer<Vec<u8>>;
+    type SerializeTupleStruct = &'a mut Serializer<Vec<u8>>;
+    type SerializeTupleVariant = &'a mut Serializer<Vec<u8>>;
+    type SerializeMap = FastMap<'a>;
+    type SerializeStruct = FastMap<'a>;
+    type SerializeStructVariant = &'a mut Serializer<Vec<u8>>;
+
+    fast_integer_methods! {
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_i128(i128);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_u128(u128);
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<()> {
+        self.write(if v { "true" } else { "false" })
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<()> {
+        let mut buffer = ryu::Buffer::new();
+        self.write(match v.classify() {
+            num::FpCategory::Infinite if v.is_sign_positive() => ".inf",
+            num::FpCategory::Infinite => "-.inf",
+            num::FpCategory::Nan => ".nan",
+            _ => buffer.format_finite(v),
+        })
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<()> {
+        let mut buffer = ryu::Buffer::new();
+        self.write(match v.classify() {
+            num::FpCategory::Infinite if v.is_sign_positive() => ".inf",
+            num::FpCategory::Infinite => "-.inf",
+            num::FpCategory::Nan => ".nan",
+            _ => buffer.format_finite(v),
+        })
+    }
+
+    fn serialize_char(self, v: char) -> Result<()> {
+        if !v.is_ascii_alphanumeric() {
+            return self.slow()?.serialize_char(v);
+        }
+        self.write(&format!("'{}'", v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<()> {
+        if self.output.push_str(v) {
+            return Ok(());
+        }
+        self.slow()?.serialize_str(v)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+        self.slow()?.serialize_bytes(v)
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        self.write("null")
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<()> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        if name == LITERAL_BLOCK
+            || name == FOLDED_BLOCK
+            || name == RAW_YAML
+        {
+            return self.slow()?.serialize_newtype_struct(name, value);
+        }
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        self.slow()?.serialize_newtype_variant(
+            name,
+            variant_index,
+            variant,
+            value,
+        )
+    }
+
+    fn serialize_seq(
+        self,
+        len: Option<usize>,
+    ) -> Result<Self::SerializeSeq> {
+        self.slow()?.serialize_seq(len)
+    }
+
+    fn serialize_tuple(
+        self,
+        len: usize,
+    ) -> Result<Self::SerializeTuple> {
+        self.slow()?.serialize_tuple(len)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        self.slow()?.serialize_tuple_struct(name, len)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        self.slow()?.serialize_tuple_variant(
+            name,
+            variant_index,
+            variant,
+            len,
+        )
+    }
+
+    fn serialize_map(
+        self,
+        len: Option<usize>,
+    ) -> Result<Self::SerializeMap> {
+        let FastSerializer { output, position } = self;
+        match len {
+            Some(len)
+                if position == FastPosition::Document
+                    && len > 0
+                    && len <= FAST_PATH_MAX_ENTRIES =>
+            {
+                output.map = Some(FastMapStart::Map(Some(len)));
+            }
+            _ => {
+                let serializer = FastSerializer {
+                    output: &mut *output,
+                    position,
+                };
+                let _ = serializer.slow()?.serialize_map(len)?;
+            }
+        }
+        Ok(FastMap { output })
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct> {
+        let FastSerializer { output, position } = self;
+        if position == FastPosition::Document
+            && len > 0
+            && len <= FAST_PATH_MAX_ENTRIES
+        {
+            output.map = Some(FastMapStart::Struct(name, len));
+        } else {
+            let serializer = FastSerializer {
+                output: &mut *output,
+                position,
+            };
+            let _ = serializer.slow()?.serialize_struct(name, len)?;
+        }
+        Ok(FastMap { output })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        self.slow()?.serialize_struct_variant(
+            name,
+            variant_index,
+            variant,
+            len,
+        )
+    }
+
+    fn collect_str<T>(self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Display,
+    {
+        // The emitter reads tags from `collect_str`, so it gets the same
+        // call.
+        let string = value.to_string();
+        if self.output.push_str(&string) {
+            return Ok(());
+        }
+        self.slow()?.collect_str(&string)
+    }
+}
+
+impl ser::SerializeMap for FastMap<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        let full =
+            self.output.scalars.len() >= 2 * FAST_PATH_MAX_ENTRIES;
+        match self.slow(full)? {
+            Some(mut serializer) => {
+                ser::SerializeMap::serialize_key(&mut serializer, key)
+            }
+            None => self.write_key(key, FastPosition::Key),
+        }
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        match self.slow(false)? {
+            Some(mut serializer) => ser::SerializeMap::serialize_value(
+                &mut serializer,
+                value,
+            ),
+            None => self.write_value(value),
+        }
+    }
+
+    fn serialize_entry<K, V>(
+        &mut self,
+        key: &K,
+        value: &V,
+    ) -> Result<()>
+    where
+        K: ?Sized + ser::Serialize,
+        V: ?Sized + ser::Serialize,
+    {
+        let full =
+            self.output.scalars.len() >= 2 * FAST_PATH_MAX_ENTRIES;
+        if let Some(mut serializer) = self.slow(full)? {
+            return ser::SerializeMap::serialize_entry(
+                &mut serializer,
+                key,
+                value,
+            );
+        }
+        self.write_key(key, FastPosition::EntryKey)?;
+        match &mut self.output.slow {
+            Some(serializer) => serializer.serialize_entry_value(value),
+            None => self.write_value(value),
+        }
+    }
+
+    fn end(mut self) -> Result<()> {
+        let empty = self.output.scalars.is_empty();
+        match self.slow(empty)? {
+            Some(serializer) => ser::SerializeMap::end(serializer),
+            None => Ok(()),
+        }
+    }
+}
+
+impl ser::SerializeStruct for FastMap<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        let full =
+            self.output.scalars.len() >= 2 * FAST_PATH_MAX_ENTRIES;
+        match self.slow(full)? {
+            Some(mut serializer) => {
+                ser::SerializeStruct::serialize_field(
+                    &mut serializer,
+                    key,
+                    value,
+                )
+            }
+            None => {
+                self.write_key(key, FastPosition::EntryKey)?;
+                ser::SerializeMap::serialize_value(self, value)
+            }
+        }
+    }
+
+    fn end(mut self) -> Result<()> {
+        let empty = self.output.scalars.is_empty();
+        match self.slow(empty)? {
+            Some(serializer) => ser::SerializeStruct::end(serializer),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,196 @@
+//! Untagged enums that try each variant on the same YAML events.
+//!
+//! Serde deserializes `#[serde(untagged)]` enums by first buffering the
+//! input in a private representation and then trying each variant on the
+//! buffer. That buffer has no room for YAML tags, positions or paths, so a
+//! variant that almost matched only produces `data did not match any
+//! variant of untagged enum`, with no hint of what went wrong. The
+//! buffering happens in the code that `#[derive(Deserialize)]` generates,
+//! before the enum reaches the deserializer, so enums that keep
+//! `#[serde(untagged)]` behave exactly as they do with any other format
+//! and still report that error.
+//!
+//! Enums declared with the [`untagged!`](crate::untagged!) macro, whose
+//! variants each hold a single value, are deserialized differently by this
+//! crate: each variant is tried on the
+//! events of the node, which the [`Deserializer`](crate::Deserializer)
+//! already holds, starting over at the first event of the node after each
+//! failure. Nothing is copied, variants keep their tags and the errors of
+//! every attempt keep their path and location. When no variant matches,
+//! the error lists the attempts, the one that read furthest into the node
+//! first:
+//!
+//! ```
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize, Debug)]
+//! #[serde(deny_unknown_fields)]
+//! pub struct Circle {
+//!     radius: f64,
+//! }
+//!
+//! #[derive(Deserialize, Debug)]
+//! #[serde(deny_unknown_fields)]
+//! pub struct Rect {
+//!     width: f64,
+//!     height: f64,
+//! }
+//!
+//! serde_yml::untagged! {
+//!     #[derive(Debug)]
+//!     pub enum Shape {
+//!         Circle(Circle),
+//!         Rect(Rect),
+//!     }
+//! }
+//!
+//! let shape: Shape = serde_yml::from_str("width: 2\nheight: 3\n").unwrap();
+//! assert!(matches!(shape, Shape::Rect(Rect { width, .. }) if width == 2.0));
+//!
+//! let err = serde_yml::from_str::<Shape>("width: 2\nheight: tall\n").unwrap_err();
+//! assert_eq!(
+//!     err.to_string(),
+//!     "no variant of untagged enum Shape matched; closest attempts were: \
+//!      Rect (height: invalid type: string \"tall\", expected f64 at line 2 column 9), \
+//!      Circle (unknown field `width`, expected `radius`)",
+//! );
+//! ```
+//!
+//! Other deserializers, such as the one of [`Value`], present the enum as
+//! a newtype struct, whose content is buffered in a [`Value`] to try the
+//! variants on.
+
+use crate::{
+    lib::*,
+    modules::error::{self, Error, ErrorImpl, Relative, Result},
+    path::Path,
+    value::Value,
+};
+use alloc::sync::Arc;
+use core::cmp::Reverse;
+use serde::de::{self, Deserialize, DeserializeSeed, Visitor};
+
+/// The prefix of the name of the newtype struct that an enum declared
+/// with [`untagged!`](crate::untagged!) deserializes itself as, followed
+/// by the names of the enum and its variants, separated by `::`.
+pub(crate) const UNTAGGED: &str = "$serde_yml::private::Untagged::";
+
+/// The failed attempts to deserialize the variants of an untagged enum.
+#[derive(Debug)]
+pub(crate) struct Failures {
+    /// The names of the enum and its variants, separated by `::`.
+    names: &'static str,
+    /// The path of the node, left out of the errors of the attempts, or
+    /// `None` if it goes through a mapping key that is not a string.
+    path: Option<Path>,
+    /// For each failed attempt in turn, how far it read into the node and
+    /// its error.
+    attempts: Vec<(usize, Error)>,
+}
+
+impl Failures {
+    pub(crate) fn new(names: &'static str, path: Option<Path>) -> Self {
+        Failures {
+            names,
+            path,
+            attempts: Vec::new(),
+        }
+    }
+
+    /// Records that the next variant failed with `err` after reading
+    /// `progress` events of the node, and returns the error to pass on to
+    /// the visitor.
+    pub(crate) fn push(
+        &mut self,
+        progress: usize,
+        err: Error,
+    ) -> Error {
+        let err = err.shared();
+        self.attempts
+            .push((progress, error::shared(Arc::clone(&err))));
+        error::shared(err)
+    }
+
+    /// Replaces the error of a visitor that tried every variant in vain by
+    /// one listing the attempts, closest first.
+    pub(crate) fn finish<T>(self, result: Result<T>) -> Result<T> {
+        let err = match result {
+            Ok(value) => return Ok(value),
+            Err(err) => err,
+        };
+        let mut names = self.names.split("::");
+        let name = names.next().unwrap_or_default();
+        let variants: Vec<&str> = names.collect();
+        if variants.is_empty() || self.attempts.len() < variants.len() {
+            return Err(err);
+        }
+        let mut attempts: Vec<_> =
+            variants.into_iter().zip(self.attempts).collect();
+        attempts.sort_by_key(|&(_, (progress, _))| Reverse(progress));
+        let base = self.path.as_ref();
+        let attempts: Vec<String> = attempts
+            .into_iter()
+            .map(|(variant, (_, err))| {
+                format!(
+                    "{} ({})",
+                    variant,
+                    Relative { err: &err, base }
+                )
+            })
+            .collect();
+        Err(error::new(ErrorImpl::Message(
+            format!(
+                "no variant of untagged enum {} matched; closest attempts were: {}",
+                name,
+                attempts.join(", "),
+            ),
+            None,
+        )))
+    }
+}
+
+/// Tries the variants of an untagged enum on the content of `deserializer`,
+/// buffered in a [`Value`], for deserializers that present the enum as a
+/// newtype struct.
+#[doc(hidden)]
+pub fn visit_buffered<'de, D, V>(
+    deserializer: D,
+    names: &'static str,
+    visitor: V,
+) -> core::result::Result<V::Value, D::Error>
+where
+    D: de::Deserializer<'de>,
+    V: Visitor<'de>,
+{
+    let value = Value::deserialize(deserializer)?;
+    let mut attempts = BufferedAttempts {
+        value,
+        failures: Failures::new(names, Some(Path::root())),
+    };
+    let result = visitor.visit_seq(&mut attempts);
+    attempts.failures.finish(result).map_err(de::Error::custom)
+}
+
+/// Presents a buffered node as a sequence repeating it, one element for
+/// each variant to try.
+struct BufferedAttempts {
+    value: Value,
+    failures: Failures,
+}
+
+impl<'de> de::SeqAccess<'de> for BufferedAttempts {
+    type Error = Error;
+
+    fn next_element_seed<T>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match seed.deserialize(self.value.clone()) {
+            Ok(value) => Ok(Some(value)),
+            Err(err) => Err(self.failures.push(0, err)),
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! Updating an existing value from YAML that only sets some of its fields.
+//!
+//! A program that reloads its configuration while running, or that layers
+//! an override file on top of built-in settings, has a fully populated
+//! value and YAML holding only the keys to change. Deserializing that YAML
+//! the usual way fails on every missing field, unless every field is
+//! wrapped in `Option` or has a `#[serde(default)]` that forgets the
+//! current value. [`update_from_str`] overwrites only the keys present in
+//! the YAML and keeps everything else:
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Config {
+//!     name: String,
+//!     workers: usize,
+//!     log: Log,
+//! }
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Log {
+//!     level: String,
+//!     file: Option<String>,
+//! }
+//!
+//! let mut config = Config {
+//!     name: "api".to_owned(),
+//!     workers: 4,
+//!     log: Log { level: "info".to_owned(), file: None },
+//! };
+//! serde_yml::update_from_str(&mut config, "log:\n  level: debug\n").unwrap();
+//! assert_eq!(config.name, "api");
+//! assert_eq!(config.workers, 4);
+//! assert_eq!(config.log.level, "debug");
+//! ```
+//!
+//! Mappings are merged key by key at every level, as
+//! [`MergeStrategy::Deep`] merges the files of a directory: a nested
+//! struct or map only has the keys of the YAML replaced, while any other
+//! value, including a sequence, is replaced whole. The updated value is
+//! built before anything is written, so a value that fails to update is
+//! left as it was.
+//!
+//! The value is converted to YAML and back to apply the update, so its
+//! type must implement both `Serialize` and `Deserialize`, and its fields
+//! must read back as they were written. Fields skipped by serde keep their
+//! `#[serde(default)]`, not their current value.
+
+use crate::{
+    dir::{merge, MergeStrategy},
+    modules::error::{self, Result},
+    validate::locate,
+    value::Value,
+};
+use serde::{
+    de::{self, DeserializeOwned, DeserializeSeed},
+    Deserialize, Deserializer, Serialize,
+};
+
+/// Updates `existing` with the keys present in the YAML document `input`,
+/// keeping the values of the keys it leaves out.
+///
+/// An empty document, or one that is only `null`, leaves `existing`
+/// unchanged.
+///
+/// # Errors
+///
+/// Returns an error if `input` is not valid YAML or if the updated value
+/// does not fit `T`, in which case `existing` is left unchanged. An error
+/// about one of the updated values gives the location of that value in
+/// `input`.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// let mut limits = BTreeMap::from([("cpu".to_owned(), 2), ("memory".to_owned(), 512)]);
+/// serde_yml::update_from_str(&mut limits, "memory: 1024\ndisk: 10\n").unwrap();
+/// assert_eq!(limits["cpu"], 2);
+/// assert_eq!(limits["memory"], 1024);
+/// assert_eq!(limits["disk"], 10);
+///
+/// let err = serde_yml::update_from_str(&mut limits, "cpu: 2\nmemory: lots\n").unwrap_err();
+/// assert_eq!(err.location().unwrap().line(), 2);
+/// assert_eq!(limits["memory"], 1024);
+/// ```
+pub fn update_from_str<T>(existing: &mut T, input: &str) -> Result<()>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let overlay: Value = crate::from_str(input)?;
+    update_from_value(existing, overlay).map_err(|err| {
+        let mark = err
+            .path()
+            .and_then(|path| locate(input).ok()?.get(path).copied());
+        error::relocate(err, mark)
+    })
+}
+
+/// Updates `existing` with the keys present in `overlay`, as
+/// [`update_from_str`] does with a YAML document.
+///
+/// # Errors
+///
+/// Returns an error if the updated value does not fit `T`, naming the
+/// path of the value that does not fit. `existing` is then left
+/// unchanged.
+pub fn update_from_value<T>(
+    existing: &mut T,
+    overlay: Value,
+) -> Result<()>
+where
+    T: Serialize + DeserializeOwned,
+{
+    if overlay.is_null() {
+        return Ok(());
+    }
+    let mut merged = crate::to_value(&*existing)?;
+    merge(&mut merged, overlay, MergeStrategy::Deep);
+    // Read back from text rather than with `from_value`, so that errors
+    // name the path of the value that does not fit.
+    let text = crate::to_string(&merged)?;
+    *existing = crate::from_str(&text)
+        .map_err(|err| error::relocate(err, None))?;
+    Ok(())
+}
+
+/// A [`DeserializeSeed`] that updates an existing value with the keys
+/// present in the input, as [`update_from_str`] does.
+///
+/// Use it to update a value from any [`Deserializer`], such as each
+/// document of a multi-document stream in turn, or with
+/// [`DeserializerOptions`](crate::de::DeserializerOptions) set.
+///
+/// # Examples
+///
+/// ```
+/// use serde::de::DeserializeSeed;
+/// use serde_yml::update::Update;
+/// use std::collections::BTreeMap;
+///
+/// let mut settings: BTreeMap<String, String> =
+///     serde_yml::from_str("theme: light\nfont: mono\n").unwrap();
+/// let yaml = "theme: dark\n---\nfont: serif\n---\n";
+/// for document in serde_yml::Deserializer::from_str(yaml) {
+///     Update::new(&mut settings).deserialize(document).unwrap();
+/// }
+/// assert_eq!(settings["theme"], "dark");
+/// assert_eq!(settings["font"], "serif");
+/// ```
+#[derive(Debug)]
+pub struct Update<'a, T> {
+    existing: &'a mut T,
+}
+
+impl<'a, T> Update<'a, T> {
+    /// Returns a seed updating `existing`.
+    pub fn new(existing: &'a mut T) -> Self {
+        Update { existing }
+    }
+}
+
+impl<'de, T> DeserializeSeed<'de> for Update<'_, T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    type Value = ();
+
+    fn deserialize<D>(
+        self,
+        deserializer: D,
+    ) -> core::result::Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let overlay = Value::deserialize(deserializer)?;
+        update_from_value(self.existing, overlay)
+            .map_err(de::Error::custom)
+    }
+}
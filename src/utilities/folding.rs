@@ -0,0 +1,185 @@
+//! Folding and unfolding of YAML folded block scalars (`>`).
+//!
+//! In a folded block scalar a single line break between two lines of text is
+//! read as a space, so long paragraphs can be wrapped freely. The exceptions
+//! are what make folding hard to predict:
+//!
+//! - A line break followed by `n` empty lines is read as `n` newlines rather
+//!   than `n + 1`.
+//! - Line breaks before or after a *more-indented* line, one that starts
+//!   with a space or tab, are never folded; they and any empty lines around
+//!   them are kept.
+//!
+//! The functions in this module work on the content of a block scalar with
+//! its indentation already removed, lines separated by `\n`. Line breaks
+//! after the last line of text are kept unchanged, as with the `+` (keep)
+//! chomping indicator; apply other chomping separately.
+
+/// Reads the content of a folded block scalar the way a YAML parser does.
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::utilities::folding::unfold;
+///
+/// assert_eq!(unfold("folded\ntext\n\nnext paragraph\n"), "folded text\nnext paragraph\n");
+/// assert_eq!(unfold("list:\n  - kept\nas is\n"), "list:\n  - kept\nas is\n");
+/// ```
+pub fn unfold(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut lines = text.split('\n').peekable();
+    // Text of the previous content line, if any, and the number of empty
+    // lines seen since it.
+    let mut previous: Option<&str> = None;
+    let mut empty = 0;
+    while let Some(line) = lines.next() {
+        if lines.peek().is_none() && line.is_empty() {
+            // The text ends with a line break; count it as a trailing break.
+            break;
+        }
+        if line.is_empty() {
+            empty += 1;
+            continue;
+        }
+        match previous {
+            None => push_breaks(&mut output, empty),
+            Some(previous) => {
+                if is_more_indented(previous) || is_more_indented(line)
+                {
+                    push_breaks(&mut output, empty + 1);
+                } else if empty == 0 {
+                    output.push(' ');
+                } else {
+                    push_breaks(&mut output, empty);
+                }
+            }
+        }
+        output.push_str(line);
+        previous = Some(line);
+        empty = 0;
+    }
+    let trailing = text.len() - text.trim_end_matches('\n').len();
+    match previous {
+        Some(_) => push_breaks(&mut output, trailing),
+        None => push_breaks(&mut output, trailing.max(empty)),
+    }
+    output
+}
+
+/// Writes `value` as the content of a folded block scalar, wrapping lines
+/// longer than `width` characters, such that [`unfold`] returns `value`.
+///
+/// Lines are only wrapped at a single space between two non-space
+/// characters; a line without such a space is left longer than `width`.
+/// More-indented lines are never wrapped.
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::utilities::folding::{fold_block_scalar, unfold};
+///
+/// let value = "a long line of text\nand another\n";
+/// let folded = fold_block_scalar(value, 10);
+/// assert_eq!(folded, "a long\nline of\ntext\n\nand\nanother\n");
+/// assert_eq!(unfold(&folded), value);
+/// ```
+pub fn fold_block_scalar(value: &str, width: usize) -> String {
+    let mut output =
+        String::with_capacity(value.len() + value.len() / 8);
+    let body = value.trim_end_matches('\n');
+    let trailing = value.len() - body.len();
+    if body.is_empty() {
+        push_breaks(&mut output, trailing);
+        return output;
+    }
+    let mut previous: Option<&str> = None;
+    let mut empty = 0;
+    for segment in body.split('\n') {
+        if segment.is_empty() {
+            empty += 1;
+            continue;
+        }
+        match previous {
+            None => push_breaks(&mut output, empty),
+            Some(previous) => {
+                let folds = !is_more_indented(previous)
+                    && !is_more_indented(segment);
+                // One line break between the lines, plus one empty line per
+                // newline to keep, plus one more when the break would fold.
+                push_breaks(
+                    &mut output,
+                    1 + empty + usize::from(folds),
+                );
+            }
+        }
+        if is_more_indented(segment) {
+            output.push_str(segment);
+        } else {
+            wrap(&mut output, segment, width);
+        }
+        previous = Some(segment);
+        empty = 0;
+    }
+    push_breaks(&mut output, trailing);
+    output
+}
+
+/// Writes a line that starts with a non-blank character, breaking it at
+/// single spaces so that no piece exceeds `width` characters if possible.
+fn wrap(output: &mut String, line: &str, width: usize) {
+    let chars: Vec<(usize, char)> = line.char_indices().collect();
+    // Spaces with a non-space character on both sides, as (byte, char)
+    // positions. Breaking anywhere else would create a more-indented line
+    // or lose a space.
+    let breaks = (1..chars.len().saturating_sub(1)).filter(|&n| {
+        chars[n].1 == ' '
+            && chars[n - 1].1 != ' '
+            && chars[n + 1].1 != ' '
+    });
+
+    // Byte and character position where the current piece starts.
+    let mut start = (0, 0);
+    let mut fit: Option<(usize, usize)> = None;
+    for n in breaks {
+        let at = (chars[n].0, n);
+        if n - start.1 > width {
+            if let Some(fit) = fit.take() {
+                break_at(output, line, &mut start, fit);
+            }
+            if n - start.1 > width {
+                // No break point fits; accept an overlong piece.
+                break_at(output, line, &mut start, at);
+                continue;
+            }
+        }
+        fit = Some(at);
+    }
+    if chars.len() - start.1 > width {
+        if let Some(fit) = fit {
+            break_at(output, line, &mut start, fit);
+        }
+    }
+    output.push_str(&line[start.0..]);
+}
+
+/// Ends the current piece at the space at `at`, dropping the space.
+fn break_at(
+    output: &mut String,
+    line: &str,
+    start: &mut (usize, usize),
+    at: (usize, usize),
+) {
+    output.push_str(&line[start.0..at.0]);
+    output.push('\n');
+    *start = (at.0 + 1, at.1 + 1);
+}
+
+fn is_more_indented(line: &str) -> bool {
+    line.starts_with(' ') || line.starts_with('\t')
+}
+
+fn push_breaks(output: &mut String, count: usize) {
+    for _ in 0..count {
+        output.push('\n');
+    }
+}
@@ -1,2 +1,4 @@
 /// The `directory` module contains functions related to directory operations.
 pub mod directory;
+/// The `folding` module contains functions for folding and unfolding YAML folded block scalars.
+pub mod folding;
@@ -0,0 +1,885 @@
+//! Validation of YAML documents against a JSON Schema.
+//!
+//! Schemas are ordinary [`Value`]s, so they can be written in YAML or JSON
+//! syntax and loaded with [`from_str`](crate::from_str). Validation reports
+//! every violation rather than stopping at the first one, and when the
+//! document is validated from its source text each [`Violation`] carries the
+//! line and column of the offending node.
+//!
+//! The validator implements the assertion keywords of JSON Schema draft
+//! 2020-12 that do not require regular expressions or external resources:
+//!
+//! - `type`, `enum`, `const`
+//! - `minimum`, `maximum`, `exclusiveMinimum`, `exclusiveMaximum`, `multipleOf`
+//! - `minLength`, `maxLength`
+//! - `items`, `prefixItems`, `minItems`, `maxItems`, `uniqueItems`,
+//!   `contains`, `minContains`, `maxContains`
+//! - `properties`, `required`, `additionalProperties`, `minProperties`,
+//!   `maxProperties`, `propertyNames`, `dependentRequired`
+//! - `allOf`, `anyOf`, `oneOf`, `not`, `if`/`then`/`else`
+//! - `$ref` to locations within the same schema, such as `#/$defs/port`
+//!
+//! Other keywords, including `pattern`, `patternProperties`, `format` and the
+//! `unevaluated*` family, are ignored. `additionalProperties` is not checked
+//! when the same schema uses `patternProperties`.
+//!
+//! YAML tags are not part of the JSON data model; a tagged node is validated
+//! as its untagged value.
+
+use crate::{
+    de::{Event, Progress},
+    libyml::error::Mark,
+    loader::Loader,
+    mapping::Mapping,
+    modules::error::{Error, Location, Result},
+    path::Path,
+    value::Value,
+};
+use serde::de::{DeserializeOwned, Error as _};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+};
+
+/// The maximum number of `$ref` indirections followed for a single node.
+const MAX_REF_DEPTH: usize = 64;
+
+/// A single way in which a document fails to satisfy a schema.
+#[derive(Clone, Debug)]
+pub struct Violation {
+    /// The path of the offending node, such as `servers[0].port`, which
+    /// displays as `.` for the document root.
+    pub path: Path,
+    /// The schema keyword that was violated, such as `type` or `required`.
+    pub keyword: &'static str,
+    /// A human-readable description of the violation.
+    pub message: String,
+    /// The position of the offending node in the source text, if known.
+    pub location: Option<Location>,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)?;
+        if let Some(location) = &self.location {
+            write!(
+                f,
+                " at line {} column {}",
+                location.line(),
+                location.column(),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Validates `instance` against `schema` and returns every violation found.
+///
+/// Values carry no source positions, so the returned violations have no
+/// [`location`](Violation::location). Use [`validate_str`] to validate YAML
+/// text with locations.
+///
+/// # Examples
+///
+/// ```
+/// let schema: serde_yml::Value = serde_yml::from_str(
+///     "type: object\nrequired: [name]\nproperties:\n  port: {type: integer}\n",
+/// ).unwrap();
+/// let instance: serde_yml::Value = serde_yml::from_str("port: http").unwrap();
+///
+/// let violations = serde_yml::validate::validate(&schema, &instance);
+/// assert_eq!(violations.len(), 2);
+/// assert_eq!(violations[0].to_string(), ".: missing required property `name`");
+/// assert_eq!(violations[1].to_string(), "port: expected integer, found string");
+/// ```
+pub fn validate(schema: &Value, instance: &Value) -> Vec<Violation> {
+    let mut validator = Validator::new(schema, None);
+    validator.check(schema, instance, 0);
+    validator.violations
+}
+
+/// Parses `yaml` and validates it against `schema`, returning every
+/// violation found together with its location in `yaml`.
+///
+/// # Errors
+///
+/// Returns an error if `yaml` is not a single valid YAML document. Schema
+/// violations are not errors; they are returned in the vector.
+///
+/// # Examples
+///
+/// ```
+/// let schema: serde_yml::Value = serde_yml::from_str(
+///     "properties:\n  replicas: {type: integer, minimum: 1}\n",
+/// ).unwrap();
+///
+/// let violations =
+///     serde_yml::validate::validate_str(&schema, "name: api\nreplicas: 0\n").unwrap();
+/// assert_eq!(
+///     violations[0].to_string(),
+///     "replicas: 0 is less than the minimum of 1 at line 2 column 11",
+/// );
+/// ```
+pub fn validate_str(
+    schema: &Value,
+    yaml: &str,
+) -> Result<Vec<Violation>> {
+    let instance: Value = crate::from_str(yaml)?;
+    let locations = locate(yaml)?
+        .into_iter()
+        .map(|(path, mark)| (path, Location::from_mark(mark)))
+        .collect();
+    let mut validator = Validator::new(schema, Some(&locations));
+    validator.check(schema, &instance, 0);
+    Ok(validator.violations)
+}
+
+/// Deserializes an instance of type `T` from YAML text after checking that
+/// the text satisfies `schema`.
+///
+/// # Errors
+///
+/// Returns an error if `yaml` is not valid YAML, if it violates the schema,
+/// in which case the message lists every violation, or if it cannot be
+/// deserialized into `T`.
+///
+/// # Examples
+///
+/// ```
+/// let schema: serde_yml::Value =
+///     serde_yml::from_str("items: {type: string}").unwrap();
+///
+/// let err = serde_yml::validate::from_str_validated::<Vec<String>>(
+///     &schema,
+///     "- a\n- [b]\n",
+/// )
+/// .unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     "schema validation failed: [1]: expected string, found array at line 2 column 3",
+/// );
+/// ```
+pub fn from_str_validated<T>(schema: &Value, yaml: &str) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    let violations = validate_str(schema, yaml)?;
+    if !violations.is_empty() {
+        let messages: Vec<String> =
+            violations.iter().map(Violation::to_string).collect();
+        return Err(Error::custom(format_args!(
+            "schema validation failed: {}",
+            messages.join("; "),
+        )));
+    }
+    crate::from_str(yaml)
+}
+
+/// Maps the path of every node in the first document of `yaml` to its
+/// position.
+pub(crate) fn locate(yaml: &str) -> Result<BTreeMap<Path, Mark>> {
+    locate_in(Progress::Str(yaml))
+}
+
+/// Same as [`locate`], for bytes of YAML text in any encoding that
+/// [`from_slice`](crate::from_slice) reads.
+pub(crate) fn locate_slice(
+    yaml: &[u8],
+) -> Result<BTreeMap<Path, Mark>> {
+    locate_in(Progress::Slice(yaml))
+}
+
+fn locate_in(progress: Progress<'_>) -> Result<BTreeMap<Path, Mark>> {
+    let mut locations = BTreeMap::new();
+    let mut loader = Loader::new(progress)?;
+    if let Some(document) = loader.next_document() {
+        let mut pos = 0;
+        if !document.events.is_empty() {
+            walk(
+                &document.events,
+                &mut pos,
+                Some(Path::root()),
+                &mut locations,
+            );
+        }
+    }
+    Ok(locations)
+}
+
+fn walk(
+    events: &[(Event<'_>, Mark)],
+    pos: &mut usize,
+    path: Option<Path>,
+    locations: &mut BTreeMap<Path, Mark>,
+) {
+    let (event, mark) = match events.get(*pos) {
+        Some(entry) => entry,
+        None => return,
+    };
+    *pos += 1;
+    match event {
+        Event::SequenceStart(_) => {
+            let mut index = 0;
+            while let Some((event, _)) = events.get(*pos) {
+                if let Event::SequenceEnd = event {
+                    *pos += 1;
+                    break;
+                }
+                let child = path.as_ref().map(|path| path.join(index));
+                walk(events, pos, child, locations);
+                index += 1;
+            }
+        }
+        Event::MappingStart(_) => {
+            while let Some((event, _)) = events.get(*pos) {
+                let key = match event {
+                    Event::MappingEnd => {
+                        *pos += 1;
+                        break;
+                    }
+                    Event::Scalar(scalar) => Some(
+                        String::from_utf8_lossy(&scalar.value)
+                            .into_owned(),
+                    ),
+                    _ => None,
+                };
+                walk(events, pos, None, locations);
+                let child = match (&path, key) {
+                    (Some(path), Some(key)) => Some(path.join(key)),
+                    _ => None,
+                };
+                walk(events, pos, child, locations);
+            }
+        }
+        _ => {}
+    }
+    if let Some(path) = path {
+        let _ = locations.insert(path, *mark);
+    }
+}
+
+fn key_string(key: &Value) -> String {
+    match key.untag_ref() {
+        Value::Null => "null".to_owned(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.clone(),
+        Value::Sequence(_) | Value::Mapping(_) | Value::Tagged(_) => {
+            "?".to_owned()
+        }
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) if is_integer(value) => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Sequence(_) => "array",
+        Value::Mapping(_) => "object",
+        Value::Tagged(tagged) => type_name(&tagged.value),
+    }
+}
+
+fn is_integer(value: &Value) -> bool {
+    match value {
+        Value::Number(n) if n.is_i64() || n.is_u64() => true,
+        Value::Number(n) => n
+            .as_f64()
+            .map_or(false, |f| f.is_finite() && f.fract() == 0.0),
+        _ => false,
+    }
+}
+
+fn matches_type(value: &Value, ty: &str) -> bool {
+    match ty {
+        "integer" => is_integer(value),
+        "number" => matches!(value, Value::Number(_)),
+        ty => type_name(value) == ty,
+    }
+}
+
+fn as_f64(value: &Value) -> Option<f64> {
+    match value.untag_ref() {
+        Value::Number(n) => n.as_f64(),
+        _ => None,
+    }
+}
+
+fn as_usize(value: &Value) -> Option<usize> {
+    match value.untag_ref() {
+        Value::Number(n) => n.as_u64().map(|n| n as usize),
+        _ => None,
+    }
+}
+
+fn describe_types(types: &[&str]) -> String {
+    match types {
+        [ty] => (*ty).to_owned(),
+        types => format!("one of {}", types.join(", ")),
+    }
+}
+
+struct Validator<'a> {
+    root: &'a Value,
+    locations: Option<&'a BTreeMap<Path, Location>>,
+    stack: Vec<Path>,
+    violations: Vec<Violation>,
+}
+
+impl<'a> Validator<'a> {
+    fn new(
+        root: &'a Value,
+        locations: Option<&'a BTreeMap<Path, Location>>,
+    ) -> Self {
+        Validator {
+            root,
+            locations,
+            stack: vec![Path::root()],
+            violations: Vec::new(),
+        }
+    }
+
+    /// Reports whether `instance` satisfies `schema` without recording any
+    /// violations.
+    fn is_valid(
+        &self,
+        schema: &Value,
+        instance: &Value,
+        depth: usize,
+    ) -> bool {
+        let mut validator = Validator {
+            root: self.root,
+            locations: None,
+            stack: vec![self.path()],
+            violations: Vec::new(),
+        };
+        validator.check(schema, instance, depth);
+        validator.violations.is_empty()
+    }
+
+    fn path(&self) -> Path {
+        self.stack.last().cloned().unwrap_or_default()
+    }
+
+    fn report(&mut self, keyword: &'static str, message: String) {
+        let location = self.locations.and_then(|locations| {
+            self.stack
+                .iter()
+                .rev()
+                .find_map(|path| locations.get(path).copied())
+        });
+        self.violations.push(Violation {
+            path: self.path().clone(),
+            keyword,
+            message,
+            location,
+        });
+    }
+
+    fn check_child(
+        &mut self,
+        path: Path,
+        schema: &Value,
+        instance: &Value,
+        depth: usize,
+    ) {
+        self.stack.push(path);
+        self.check(schema, instance, depth);
+        let _ = self.stack.pop();
+    }
+
+    fn check(
+        &mut self,
+        schema: &Value,
+        instance: &Value,
+        depth: usize,
+    ) {
+        let instance = instance.untag_ref();
+        let schema = match schema.untag_ref() {
+            Value::Bool(true) => return,
+            Value::Bool(false) => {
+                self.report(
+                    "false",
+                    "no value is allowed here".to_owned(),
+                );
+                return;
+            }
+            Value::Mapping(schema) => schema,
+            _ => return,
+        };
+
+        if let Some(reference) =
+            schema.get("$ref").and_then(Value::as_str)
+        {
+            match self.resolve(reference) {
+                Some(_) if depth >= MAX_REF_DEPTH => self.report(
+                    "$ref",
+                    format!(
+                        "too many nested references at `{}`",
+                        reference
+                    ),
+                ),
+                Some(target) => self.check(target, instance, depth + 1),
+                None => self.report(
+                    "$ref",
+                    format!("cannot resolve reference `{}`", reference),
+                ),
+            }
+        }
+
+        self.check_generic(schema, instance, depth);
+        match instance {
+            Value::Number(_) => self.check_number(schema, instance),
+            Value::String(string) => self.check_string(schema, string),
+            Value::Sequence(sequence) => {
+                self.check_array(schema, sequence, depth);
+            }
+            Value::Mapping(mapping) => {
+                self.check_object(schema, mapping, depth);
+            }
+            Value::Null | Value::Bool(_) | Value::Tagged(_) => {}
+        }
+    }
+
+    fn resolve(&self, reference: &str) -> Option<&'a Value> {
+        let pointer = reference.strip_prefix('#')?;
+        if pointer.is_empty() {
+            return Some(self.root);
+        }
+        let mut target = self.root;
+        for token in pointer.strip_prefix('/')?.split('/') {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            target = match target.untag_ref() {
+                Value::Mapping(mapping) => {
+                    mapping.get(token.as_str())?
+                }
+                Value::Sequence(sequence) => {
+                    sequence.get(token.parse::<usize>().ok()?)?
+                }
+                _ => return None,
+            };
+        }
+        Some(target)
+    }
+
+    fn check_generic(
+        &mut self,
+        schema: &Mapping,
+        instance: &Value,
+        depth: usize,
+    ) {
+        if let Some(ty) = schema.get("type") {
+            let types: Vec<&str> = match ty.untag_ref() {
+                Value::String(ty) => vec![ty.as_str()],
+                Value::Sequence(types) => {
+                    types.iter().filter_map(Value::as_str).collect()
+                }
+                _ => Vec::new(),
+            };
+            if !types.is_empty()
+                && !types.iter().any(|ty| matches_type(instance, ty))
+            {
+                self.report(
+                    "type",
+                    format!(
+                        "expected {}, found {}",
+                        describe_types(&types),
+                        type_name(instance),
+                    ),
+                );
+            }
+        }
+
+        if let Some(Value::Sequence(allowed)) =
+            schema.get("enum").map(Value::untag_ref)
+        {
+            if !allowed
+                .iter()
+                .any(|allowed| allowed.untag_ref() == instance)
+            {
+                self.report(
+                    "enum",
+                    "value is not one of the allowed values".to_owned(),
+                );
+            }
+        }
+
+        if let Some(expected) = schema.get("const") {
+            if expected.untag_ref() != instance {
+                self.report(
+                    "const",
+                    "value does not match the expected constant"
+                        .to_owned(),
+                );
+            }
+        }
+
+        if let Some(Value::Sequence(schemas)) =
+            schema.get("allOf").map(Value::untag_ref)
+        {
+            for subschema in schemas {
+                self.check(subschema, instance, depth);
+            }
+        }
+
+        if let Some(Value::Sequence(schemas)) =
+            schema.get("anyOf").map(Value::untag_ref)
+        {
+            if !schemas.iter().any(|subschema| {
+                self.is_valid(subschema, instance, depth)
+            }) {
+                self.report(
+                    "anyOf",
+                    "value does not match any of the allowed schemas"
+                        .to_owned(),
+                );
+            }
+        }
+
+        if let Some(Value::Sequence(schemas)) =
+            schema.get("oneOf").map(Value::untag_ref)
+        {
+            let matching = schemas
+                .iter()
+                .filter(|subschema| {
+                    self.is_valid(subschema, instance, depth)
+                })
+                .count();
+            if matching != 1 {
+                self.report(
+                    "oneOf",
+                    format!(
+                        "value matches {} of the allowed schemas, expected exactly one",
+                        matching,
+                    ),
+                );
+            }
+        }
+
+        if let Some(subschema) = schema.get("not") {
+            if self.is_valid(subschema, instance, depth) {
+                self.report(
+                    "not",
+                    "value matches a schema it must not match"
+                        .to_owned(),
+                );
+            }
+        }
+
+        if let Some(condition) = schema.get("if") {
+            let branch = if self.is_valid(condition, instance, depth) {
+                schema.get("then")
+            } else {
+                schema.get("else")
+            };
+            if let Some(branch) = branch {
+                self.check(branch, instance, depth);
+            }
+        }
+    }
+
+    fn check_number(&mut self, schema: &Mapping, instance: &Value) {
+        let value = match as_f64(instance) {
+            Some(value) => value,
+            None => return,
+        };
+        let bound = |keyword| schema.get(keyword).and_then(as_f64);
+
+        if let Some(minimum) = bound("minimum") {
+            if value < minimum {
+                self.report(
+                    "minimum",
+                    format!(
+                        "{} is less than the minimum of {}",
+                        instance_number(instance),
+                        minimum,
+                    ),
+                );
+            }
+        }
+        if let Some(maximum) = bound("maximum") {
+            if value > maximum {
+                self.report(
+                    "maximum",
+                    format!(
+                        "{} is greater than the maximum of {}",
+                        instance_number(instance),
+                        maximum,
+                    ),
+                );
+            }
+        }
+        if let Some(minimum) = bound("exclusiveMinimum") {
+            if value <= minimum {
+                self.report(
+                    "exclusiveMinimum",
+                    format!(
+                        "{} is not greater than {}",
+                        instance_number(instance),
+                        minimum,
+                    ),
+                );
+            }
+        }
+        if let Some(maximum) = bound("exclusiveMaximum") {
+            if value >= maximum {
+                self.report(
+                    "exclusiveMaximum",
+                    format!(
+                        "{} is not less than {}",
+                        instance_number(instance),
+                        maximum,
+                    ),
+                );
+            }
+        }
+        if let Some(divisor) = bound("multipleOf") {
+            let quotient = value / divisor;
+            if divisor > 0.0
+                && (quotient - quotient.round()).abs()
+                    > f64::EPSILON * quotient.abs().max(1.0)
+            {
+                self.report(
+                    "multipleOf",
+                    format!(
+                        "{} is not a multiple of {}",
+                        instance_number(instance),
+                        divisor,
+                    ),
+                );
+            }
+        }
+    }
+
+    fn check_string(&mut self, schema: &Mapping, string: &str) {
+        let length = string.chars().count();
+        if let Some(min) = schema.get("minLength").and_then(as_usize) {
+            if length < min {
+                self.report(
+                    "minLength",
+                    format!(
+                        "string is shorter than {} characters",
+                        min
+                    ),
+                );
+            }
+        }
+        if let Some(max) = schema.get("maxLength").and_then(as_usize) {
+            if length > max {
+                self.report(
+                    "maxLength",
+                    format!("string is longer than {} characters", max),
+                );
+            }
+        }
+    }
+
+    fn check_array(
+        &mut self,
+        schema: &Mapping,
+        sequence: &[Value],
+        depth: usize,
+    ) {
+        let prefix =
+            match schema.get("prefixItems").map(Value::untag_ref) {
+                Some(Value::Sequence(prefix)) => prefix.as_slice(),
+                _ => &[],
+            };
+        for (index, element) in sequence.iter().enumerate() {
+            let subschema = match prefix.get(index) {
+                Some(subschema) => subschema,
+                None => match schema.get("items") {
+                    Some(items) => items,
+                    None => continue,
+                },
+            };
+            let path = self.path().join(index);
+            self.check_child(path, subschema, element, depth);
+        }
+
+        if let Some(min) = schema.get("minItems").and_then(as_usize) {
+            if sequence.len() < min {
+                self.report(
+                    "minItems",
+                    format!("array has fewer than {} items", min),
+                );
+            }
+        }
+        if let Some(max) = schema.get("maxItems").and_then(as_usize) {
+            if sequence.len() > max {
+                self.report(
+                    "maxItems",
+                    format!("array has more than {} items", max),
+                );
+            }
+        }
+        if let Some(Value::Bool(true)) =
+            schema.get("uniqueItems").map(Value::untag_ref)
+        {
+            let duplicate =
+                sequence.iter().enumerate().any(|(i, a)| {
+                    sequence[i + 1..]
+                        .iter()
+                        .any(|b| a.untag_ref() == b.untag_ref())
+                });
+            if duplicate {
+                self.report(
+                    "uniqueItems",
+                    "array contains duplicate items".to_owned(),
+                );
+            }
+        }
+        if let Some(contains) = schema.get("contains") {
+            let count = sequence
+                .iter()
+                .filter(|element| {
+                    self.is_valid(contains, element, depth)
+                })
+                .count();
+            let min = schema
+                .get("minContains")
+                .and_then(as_usize)
+                .unwrap_or(1);
+            let max = schema.get("maxContains").and_then(as_usize);
+            if count < min {
+                self.report(
+                    "contains",
+                    format!(
+                        "array contains {} matching items, expected at least {}",
+                        count, min,
+                    ),
+                );
+            }
+            if let Some(max) = max.filter(|max| count > *max) {
+                self.report(
+                    "maxContains",
+                    format!(
+                        "array contains {} matching items, expected at most {}",
+                        count, max,
+                    ),
+                );
+            }
+        }
+    }
+
+    fn check_object(
+        &mut self,
+        schema: &Mapping,
+        mapping: &Mapping,
+        depth: usize,
+    ) {
+        if let Some(Value::Sequence(required)) =
+            schema.get("required").map(Value::untag_ref)
+        {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !mapping.contains_key(name) {
+                    self.report(
+                        "required",
+                        format!("missing required property `{}`", name),
+                    );
+                }
+            }
+        }
+
+        if let Some(Value::Mapping(dependent)) =
+            schema.get("dependentRequired").map(Value::untag_ref)
+        {
+            for (name, required) in dependent {
+                let name = key_string(name);
+                if !mapping.contains_key(name.as_str()) {
+                    continue;
+                }
+                if let Value::Sequence(required) = required.untag_ref()
+                {
+                    for other in
+                        required.iter().filter_map(Value::as_str)
+                    {
+                        if !mapping.contains_key(other) {
+                            self.report(
+                                "dependentRequired",
+                                format!(
+                                    "property `{}` requires property `{}`",
+                                    name, other,
+                                ),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(min) =
+            schema.get("minProperties").and_then(as_usize)
+        {
+            if mapping.len() < min {
+                self.report(
+                    "minProperties",
+                    format!("object has fewer than {} properties", min),
+                );
+            }
+        }
+        if let Some(max) =
+            schema.get("maxProperties").and_then(as_usize)
+        {
+            if mapping.len() > max {
+                self.report(
+                    "maxProperties",
+                    format!("object has more than {} properties", max),
+                );
+            }
+        }
+
+        let properties =
+            match schema.get("properties").map(Value::untag_ref) {
+                Some(Value::Mapping(properties)) => Some(properties),
+                _ => None,
+            };
+        let additional = if schema.contains_key("patternProperties") {
+            None
+        } else {
+            schema.get("additionalProperties")
+        };
+        let names = schema.get("propertyNames");
+
+        for (key, value) in mapping {
+            let name = key_string(key);
+            let path = self.path().join(name.as_str());
+            if let Some(names) = names {
+                self.check_child(path.clone(), names, key, depth);
+            }
+            match properties.and_then(|properties| properties.get(key))
+            {
+                Some(subschema) => {
+                    self.check_child(path, subschema, value, depth);
+                }
+                None => match additional.map(Value::untag_ref) {
+                    Some(Value::Bool(false)) => {
+                        self.stack.push(path);
+                        self.report(
+                            "additionalProperties",
+                            format!(
+                                "property `{}` is not allowed",
+                                name
+                            ),
+                        );
+                        let _ = self.stack.pop();
+                    }
+                    Some(subschema) => {
+                        self.check_child(path, subschema, value, depth);
+                    }
+                    None => {}
+                },
+            }
+        }
+    }
+}
+
+fn instance_number(instance: &Value) -> String {
+    match instance {
+        Value::Number(n) => n.to_string(),
+        _ => String::new(),
+    }
+}
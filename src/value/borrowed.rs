@@ -0,0 +1,440 @@
+//! A representation of YAML values that borrows its strings from the input.
+
+use crate::mapping::duplicate_key_error;
+use crate::value::tagged::TagStringVisitor;
+use crate::value::{Tag, TaggedValue};
+use crate::{lib::*, Number, Value};
+use alloc::{borrow::Cow, collections::BTreeSet};
+use core::fmt::{self, Formatter};
+use serde::de::{
+    self, Deserialize, DeserializeSeed, EnumAccess, MapAccess,
+    SeqAccess, VariantAccess, Visitor,
+};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+
+/// A YAML value whose strings borrow from the text it was parsed from.
+///
+/// Plain and quoted scalars without escapes are borrowed, so loading a
+/// document allocates for its sequences and mappings but not for most of
+/// its strings. Strings that differ from their text in the input, such as
+/// double-quoted strings with escapes and block scalars, are owned.
+/// Mappings keep their entries in order in a `Vec`, and looking up a key
+/// with [`BorrowedValue::get`] scans them.
+///
+/// Load YAML into a `BorrowedValue` with
+/// [`from_str_borrowed`](crate::from_str_borrowed), and convert it into a
+/// [`Value`] with `From` once it needs to outlive the input.
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::value::BorrowedValue;
+/// use std::borrow::Cow;
+///
+/// let yaml = String::from("name: web\nmotd: \"hello\\nworld\"\n");
+/// let value = serde_yml::from_str_borrowed(&yaml).unwrap();
+///
+/// assert!(matches!(value.get("name"), Some(BorrowedValue::String(Cow::Borrowed("web")))));
+/// assert!(matches!(value.get("motd"), Some(BorrowedValue::String(Cow::Owned(_)))));
+///
+/// let owned = serde_yml::Value::from(value);
+/// drop(yaml);
+/// assert_eq!(owned["name"], "web");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum BorrowedValue<'a> {
+    /// Represents a YAML null value.
+    Null,
+    /// Represents a YAML boolean.
+    Bool(bool),
+    /// Represents a YAML numerical value, whether integer or floating point.
+    Number(Number),
+    /// Represents a YAML string, borrowed from the input when possible.
+    String(Cow<'a, str>),
+    /// Represents a YAML sequence.
+    Sequence(Vec<BorrowedValue<'a>>),
+    /// Represents a YAML mapping, with its entries in order.
+    Mapping(Vec<(BorrowedValue<'a>, BorrowedValue<'a>)>),
+    /// Represents a tagged value.
+    Tagged(Box<(Tag, BorrowedValue<'a>)>),
+}
+
+impl<'a> BorrowedValue<'a> {
+    /// Returns the value of the first entry of a mapping whose key is the
+    /// string `key`.
+    pub fn get(&self, key: &str) -> Option<&BorrowedValue<'a>> {
+        self.as_mapping()?
+            .iter()
+            .find(|(k, _)| k.as_str() == Some(key))
+            .map(|(_, v)| v)
+    }
+
+    /// Returns the string, if the value is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            BorrowedValue::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements, if the value is a sequence.
+    pub fn as_sequence(&self) -> Option<&[BorrowedValue<'a>]> {
+        match self {
+            BorrowedValue::Sequence(sequence) => Some(sequence),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries, if the value is a mapping.
+    pub fn as_mapping(
+        &self,
+    ) -> Option<&[(BorrowedValue<'a>, BorrowedValue<'a>)]> {
+        match self {
+            BorrowedValue::Mapping(mapping) => Some(mapping),
+            _ => None,
+        }
+    }
+
+    /// Copies the borrowed strings, so that the value no longer borrows
+    /// from the input.
+    pub fn into_owned(self) -> BorrowedValue<'static> {
+        match self {
+            BorrowedValue::Null => BorrowedValue::Null,
+            BorrowedValue::Bool(b) => BorrowedValue::Bool(b),
+            BorrowedValue::Number(n) => BorrowedValue::Number(n),
+            BorrowedValue::String(s) => {
+                BorrowedValue::String(Cow::Owned(s.into_owned()))
+            }
+            BorrowedValue::Sequence(sequence) => {
+                BorrowedValue::Sequence(
+                    sequence
+                        .into_iter()
+                        .map(BorrowedValue::into_owned)
+                        .collect(),
+                )
+            }
+            BorrowedValue::Mapping(mapping) => BorrowedValue::Mapping(
+                mapping
+                    .into_iter()
+                    .map(|(k, v)| (k.into_owned(), v.into_owned()))
+                    .collect(),
+            ),
+            BorrowedValue::Tagged(tagged) => {
+                let (tag, value) = *tagged;
+                BorrowedValue::Tagged(Box::new((
+                    tag,
+                    value.into_owned(),
+                )))
+            }
+        }
+    }
+}
+
+/// Converts a `BorrowedValue` into a `Value`, copying its borrowed
+/// strings.
+impl From<BorrowedValue<'_>> for Value {
+    fn from(value: BorrowedValue<'_>) -> Self {
+        match value {
+            BorrowedValue::Null => Value::Null,
+            BorrowedValue::Bool(b) => Value::Bool(b),
+            BorrowedValue::Number(n) => Value::Number(n),
+            BorrowedValue::String(s) => Value::String(s.into_owned()),
+            BorrowedValue::Sequence(sequence) => Value::Sequence(
+                sequence.into_iter().map(Value::from).collect(),
+            ),
+            BorrowedValue::Mapping(mapping) => Value::Mapping(
+                mapping
+                    .into_iter()
+                    .map(|(k, v)| (Value::from(k), Value::from(v)))
+                    .collect(),
+            ),
+            BorrowedValue::Tagged(tagged) => {
+                let (tag, value) = *tagged;
+                Value::Tagged(Box::new(TaggedValue {
+                    tag,
+                    value: Value::from(value),
+                }))
+            }
+        }
+    }
+}
+
+impl Serialize for BorrowedValue<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            BorrowedValue::Null => serializer.serialize_unit(),
+            BorrowedValue::Bool(b) => serializer.serialize_bool(*b),
+            BorrowedValue::Number(n) => n.serialize(serializer),
+            BorrowedValue::String(s) => serializer.serialize_str(s),
+            BorrowedValue::Sequence(sequence) => {
+                let mut seq =
+                    serializer.serialize_seq(Some(sequence.len()))?;
+                for element in sequence {
+                    seq.serialize_element(element)?;
+                }
+                seq.end()
+            }
+            BorrowedValue::Mapping(mapping) => {
+                let mut map =
+                    serializer.serialize_map(Some(mapping.len()))?;
+                for (k, v) in mapping {
+                    map.serialize_entry(k, v)?;
+                }
+                map.end()
+            }
+            BorrowedValue::Tagged(tagged) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry(
+                    &SerializeTag(&tagged.0),
+                    &tagged.1,
+                )?;
+                map.end()
+            }
+        }
+    }
+}
+
+/// Writes a tag as the key of a single-entry map, which the serializer
+/// turns back into a tag.
+pub(super) struct SerializeTag<'a>(pub(super) &'a Tag);
+
+impl Serialize for SerializeTag<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for BorrowedValue<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(BorrowedValueVisitor)
+    }
+}
+
+/// Builds a `BorrowedValue`, keeping the strings the deserializer lends.
+#[derive(Clone, Copy)]
+struct BorrowedValueVisitor;
+
+impl<'de> DeserializeSeed<'de> for BorrowedValueVisitor {
+    type Value = BorrowedValue<'de>;
+
+    fn deserialize<D>(
+        self,
+        deserializer: D,
+    ) -> Result<BorrowedValue<'de>, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for BorrowedValueVisitor {
+    type Value = BorrowedValue<'de>;
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        formatter.write_str("any YAML value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<BorrowedValue<'de>, E>
+    where
+        E: de::Error,
+    {
+        Ok(BorrowedValue::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<BorrowedValue<'de>, E>
+    where
+        E: de::Error,
+    {
+        Ok(BorrowedValue::Number(value.into()))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<BorrowedValue<'de>, E>
+    where
+        E: de::Error,
+    {
+        Ok(BorrowedValue::Number(value.into()))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<BorrowedValue<'de>, E>
+    where
+        E: de::Error,
+    {
+        Ok(BorrowedValue::Number(value.into()))
+    }
+
+    #[cfg(feature = "bigint")]
+    fn visit_i128<E>(self, value: i128) -> Result<BorrowedValue<'de>, E>
+    where
+        E: de::Error,
+    {
+        Ok(BorrowedValue::Number(value.into()))
+    }
+
+    #[cfg(feature = "bigint")]
+    fn visit_u128<E>(self, value: u128) -> Result<BorrowedValue<'de>, E>
+    where
+        E: de::Error,
+    {
+        Ok(BorrowedValue::Number(value.into()))
+    }
+
+    /// Receives the digits of an integer outside the range of `i128` and
+    /// `u128`, which `Value` knows how to read.
+    #[cfg(feature = "bigint")]
+    fn visit_newtype_struct<D>(
+        self,
+        deserializer: D,
+    ) -> Result<BorrowedValue<'de>, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        Ok(borrow(Value::deserialize(deserializer)?))
+    }
+
+    fn visit_borrowed_str<E>(
+        self,
+        value: &'de str,
+    ) -> Result<BorrowedValue<'de>, E>
+    where
+        E: de::Error,
+    {
+        Ok(BorrowedValue::String(Cow::Borrowed(value)))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<BorrowedValue<'de>, E>
+    where
+        E: de::Error,
+    {
+        Ok(BorrowedValue::String(Cow::Owned(value.to_owned())))
+    }
+
+    fn visit_string<E>(
+        self,
+        value: String,
+    ) -> Result<BorrowedValue<'de>, E>
+    where
+        E: de::Error,
+    {
+        Ok(BorrowedValue::String(Cow::Owned(value)))
+    }
+
+    fn visit_unit<E>(self) -> Result<BorrowedValue<'de>, E>
+    where
+        E: de::Error,
+    {
+        Ok(BorrowedValue::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<BorrowedValue<'de>, E>
+    where
+        E: de::Error,
+    {
+        Ok(BorrowedValue::Null)
+    }
+
+    fn visit_some<D>(
+        self,
+        deserializer: D,
+    ) -> Result<BorrowedValue<'de>, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.deserialize(deserializer)
+    }
+
+    fn visit_seq<A>(
+        self,
+        mut seq: A,
+    ) -> Result<BorrowedValue<'de>, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut elements =
+            Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(element) = seq.next_element_seed(self)? {
+            elements.push(element);
+        }
+        Ok(BorrowedValue::Sequence(elements))
+    }
+
+    fn visit_map<A>(
+        self,
+        mut map: A,
+    ) -> Result<BorrowedValue<'de>, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entries: Vec<(BorrowedValue<'de>, BorrowedValue<'de>)> =
+            Vec::with_capacity(map.size_hint().unwrap_or(0));
+        // String keys are checked for duplicates through a set, and the
+        // rare other keys by comparing them with each other.
+        let mut strings = BTreeSet::new();
+        let mut others: Vec<usize> = Vec::new();
+        while let Some(key) = map.next_key_seed(self)? {
+            let duplicate = match &key {
+                BorrowedValue::String(string) => {
+                    !strings.insert(string.clone())
+                }
+                _ => {
+                    let duplicate =
+                        others.iter().any(|&i| entries[i].0 == key);
+                    others.push(entries.len());
+                    duplicate
+                }
+            };
+            if duplicate {
+                return Err(duplicate_key_error(Value::from(key)));
+            }
+            let value = map.next_value_seed(self)?;
+            entries.push((key, value));
+        }
+        Ok(BorrowedValue::Mapping(entries))
+    }
+
+    fn visit_enum<A>(
+        self,
+        data: A,
+    ) -> Result<BorrowedValue<'de>, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        let (tag, contents) = data.variant_seed(TagStringVisitor)?;
+        let value = contents.newtype_variant_seed(self)?;
+        Ok(BorrowedValue::Tagged(Box::new((tag, value))))
+    }
+}
+
+/// Converts a `Value` read by its own `Deserialize` implementation into a
+/// `BorrowedValue` owning its strings.
+#[cfg(feature = "bigint")]
+fn borrow(value: Value) -> BorrowedValue<'static> {
+    match value {
+        Value::Null => BorrowedValue::Null,
+        Value::Bool(b) => BorrowedValue::Bool(b),
+        Value::Number(n) => BorrowedValue::Number(n),
+        Value::String(s) => BorrowedValue::String(Cow::Owned(s)),
+        Value::Sequence(sequence) => BorrowedValue::Sequence(
+            sequence.into_iter().map(borrow).collect(),
+        ),
+        Value::Mapping(mapping) => BorrowedValue::Mapping(
+            mapping
+                .into_iter()
+                .map(|(k, v)| (borrow(k), borrow(v)))
+                .collect(),
+        ),
+        Value::Tagged(tagged) => {
+            let TaggedValue { tag, value } = *tagged;
+            BorrowedValue::Tagged(Box::new((tag, borrow(value))))
+        }
+    }
+}
@@ -0,0 +1,509 @@
+use crate::{lib::*, mapping::Mapping, number::Number, value::Value};
+use core::mem;
+
+/// The layout of the bytes that [`Value::canonical_bytes`] produces.
+///
+/// The layout of each version is fixed: a value encoded with a given
+/// version produces the same bytes, and so the same hash, with every
+/// release of this crate. Changes to the layout are made by adding a new
+/// version, so the enum is `#[non_exhaustive]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CanonicalVersion {
+    /// The first version of the layout, described on
+    /// [`Value::canonical_bytes`].
+    V1,
+}
+
+/// The hash function applied by [`Value::canonical_hash`].
+///
+/// Every algorithm produces a 32-byte digest. More algorithms may be added
+/// in minor releases, so the enum is `#[non_exhaustive]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum HashAlgorithm {
+    /// SHA-256, as specified in FIPS 180-4.
+    Sha256,
+}
+
+/// The order in which the entries of a mapping are encoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KeyOrder {
+    /// Entries are sorted by the encoded bytes of their keys, so that
+    /// mappings with the same entries encode identically whatever their
+    /// order. This is the default.
+    Sorted,
+    /// Entries are encoded in the order of the mapping, so that reordering
+    /// keys changes the hash.
+    Preserve,
+}
+
+/// Whether tags are part of the encoding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TagPolicy {
+    /// Tags are encoded, so that `!Thing x` and `x` hash differently. This
+    /// is the default.
+    Include,
+    /// Tags are dropped and only the tagged values are encoded.
+    Ignore,
+}
+
+/// Options for [`Value::canonical_bytes`] and [`Value::canonical_hash`].
+///
+/// The version, key order and tag policy are written into the encoded
+/// bytes, so that values encoded with different options never produce the
+/// same bytes.
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::value::{CanonicalOptions, KeyOrder};
+/// use serde_yml::Value;
+///
+/// let a: Value = serde_yml::from_str("{x: 1, y: 2}").unwrap();
+/// let b: Value = serde_yml::from_str("{y: 2, x: 1}").unwrap();
+///
+/// let sorted = CanonicalOptions::default();
+/// assert_eq!(a.canonical_hash_with(&sorted), b.canonical_hash_with(&sorted));
+///
+/// let preserved = CanonicalOptions {
+///     key_order: KeyOrder::Preserve,
+///     ..CanonicalOptions::default()
+/// };
+/// assert_ne!(a.canonical_hash_with(&preserved), b.canonical_hash_with(&preserved));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CanonicalOptions {
+    /// The layout version. The default is [`CanonicalVersion::V1`].
+    pub version: CanonicalVersion,
+    /// The hash function. The default is [`HashAlgorithm::Sha256`].
+    pub algorithm: HashAlgorithm,
+    /// The order of mapping entries. The default is [`KeyOrder::Sorted`].
+    pub key_order: KeyOrder,
+    /// The treatment of tags. The default is [`TagPolicy::Include`].
+    pub tags: TagPolicy,
+}
+
+impl Default for CanonicalOptions {
+    fn default() -> Self {
+        CanonicalOptions {
+            version: CanonicalVersion::V1,
+            algorithm: HashAlgorithm::Sha256,
+            key_order: KeyOrder::Sorted,
+            tags: TagPolicy::Include,
+        }
+    }
+}
+
+impl Value {
+    /// Sorts the entries of every mapping within the value by key,
+    /// recursively, in the order of
+    /// [`EntryOrder::Key`](crate::mapping::EntryOrder::Key).
+    ///
+    /// Mappings keep their entries in the order they were inserted, which
+    /// may differ from one run of a program to the next when they are built
+    /// from a `HashMap`. Canonicalizing a value before serializing it gives
+    /// output that only depends on its contents, as snapshot tests need.
+    /// Sequences keep their order, and keys that are themselves mappings
+    /// are canonicalized too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_yml::Value;
+    ///
+    /// let mut value: Value = serde_yml::from_str("b: {port: 1, host: 2}\na: [{d: 3, c: 4}]\n").unwrap();
+    /// value.canonicalize();
+    /// assert_eq!(
+    ///     serde_yml::to_string(&value).unwrap(),
+    ///     "a:\n- c: 4\n  d: 3\nb:\n  host: 2\n  port: 1\n",
+    /// );
+    /// ```
+    pub fn canonicalize(&mut self) {
+        match self {
+            Value::Null
+            | Value::Bool(_)
+            | Value::Number(_)
+            | Value::String(_) => {}
+            Value::Sequence(sequence) => {
+                sequence.iter_mut().for_each(Value::canonicalize);
+            }
+            Value::Mapping(mapping) => {
+                let nested_keys = mapping.keys().any(|key| {
+                    matches!(
+                        key,
+                        Value::Sequence(_)
+                            | Value::Mapping(_)
+                            | Value::Tagged(_)
+                    )
+                });
+                if nested_keys {
+                    // Keys cannot be modified in place, so the mapping is
+                    // rebuilt. Canonicalizing keys never makes two of them
+                    // equal, since mappings compare equal whatever their
+                    // order.
+                    *mapping = mem::take(mapping)
+                        .into_iter()
+                        .map(|(mut key, mut value)| {
+                            key.canonicalize();
+                            value.canonicalize();
+                            (key, value)
+                        })
+                        .collect();
+                } else {
+                    mapping.values_mut().for_each(Value::canonicalize);
+                }
+                mapping.sort_keys();
+            }
+            Value::Tagged(tagged) => tagged.value.canonicalize(),
+        }
+    }
+
+    /// Encodes the value in a canonical byte layout, in which values that
+    /// compare equal encode identically.
+    ///
+    /// The bytes do not depend on how the value was written: the style of
+    /// scalars, comments, anchors and aliases, and, by default, the order
+    /// of mapping keys do not change them. They are meant to be hashed or
+    /// signed, not read back.
+    ///
+    /// # Layout of version 1
+    ///
+    /// The bytes start with the ASCII header `serde_yml/canonical/v1`,
+    /// followed by one byte for the key order (`s` for sorted, `p` for
+    /// preserved) and one for the tag policy (`t` for included, `u` for
+    /// ignored), and then the encoding of the value. Lengths and counts
+    /// are unsigned 64-bit big-endian integers. Each value starts with a
+    /// one-byte ASCII marker:
+    ///
+    /// - `n`: null, with nothing after it.
+    /// - `b`: a boolean, followed by `0x00` for false or `0x01` for true.
+    /// - `i`: an integer in the range of `i128`, followed by its value as
+    ///   a signed 128-bit big-endian integer.
+    /// - `z`: an integer outside the range of `i128`, followed by its
+    ///   decimal digits, with a leading `-` if it is negative, as a string
+    ///   (length and bytes). Such integers are only held exactly with the
+    ///   `bigint` feature; without it they are read as floats and encoded
+    ///   with `f`.
+    /// - `f`: a floating point number, followed by its IEEE 754 binary64
+    ///   bits in big-endian order. `-0.0` is written as `0.0` and every NaN
+    ///   as `0x7ff8000000000000`, matching how numbers compare.
+    /// - `s`: a string, followed by its length in bytes and its UTF-8
+    ///   bytes.
+    /// - `q`: a sequence, followed by its length and each element.
+    /// - `m`: a mapping, followed by its number of entries and each key
+    ///   followed by its value. With [`KeyOrder::Sorted`] the entries are
+    ///   ordered by the bytewise comparison of the encoded keys.
+    /// - `t`: a tagged value, followed by the tag as a string (length and
+    ///   bytes, in the `!Name` form, as [`Tag`](crate::value::Tag) displays
+    ///   it) and the encoding of the value. With [`TagPolicy::Ignore`] only
+    ///   the value is written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_yml::value::CanonicalOptions;
+    /// use serde_yml::Value;
+    ///
+    /// let value: Value = serde_yml::from_str("[true, 'x']").unwrap();
+    /// let bytes = value.canonical_bytes(&CanonicalOptions::default());
+    /// assert_eq!(
+    ///     bytes,
+    ///     b"serde_yml/canonical/v1st\
+    ///       q\0\0\0\0\0\0\0\x02\
+    ///       b\x01\
+    ///       s\0\0\0\0\0\0\0\x01x",
+    /// );
+    /// ```
+    pub fn canonical_bytes(
+        &self,
+        options: &CanonicalOptions,
+    ) -> Vec<u8> {
+        let mut out = Vec::with_capacity(64);
+        match options.version {
+            CanonicalVersion::V1 => {
+                out.extend_from_slice(b"serde_yml/canonical/v1");
+                out.push(match options.key_order {
+                    KeyOrder::Sorted => b's',
+                    KeyOrder::Preserve => b'p',
+                });
+                out.push(match options.tags {
+                    TagPolicy::Include => b't',
+                    TagPolicy::Ignore => b'u',
+                });
+                encode(self, options, &mut out);
+            }
+        }
+        out
+    }
+
+    /// Hashes the [canonical bytes](Value::canonical_bytes) of the value
+    /// with the default [`CanonicalOptions`]: layout version 1, mapping
+    /// keys sorted and tags included.
+    ///
+    /// The hash is stable across releases of this crate and platforms, so
+    /// it can be stored to detect when a configuration changes, or signed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_yml::value::HashAlgorithm;
+    /// use serde_yml::Value;
+    ///
+    /// let deployed: Value = serde_yml::from_str("replicas: 3\nimage: 'app:1.2'\n").unwrap();
+    /// let desired: Value = serde_yml::from_str("image: app:1.2\nreplicas: 3\n").unwrap();
+    ///
+    /// assert_eq!(
+    ///     deployed.canonical_hash(HashAlgorithm::Sha256),
+    ///     desired.canonical_hash(HashAlgorithm::Sha256),
+    /// );
+    /// ```
+    pub fn canonical_hash(&self, algorithm: HashAlgorithm) -> [u8; 32] {
+        self.canonical_hash_with(&CanonicalOptions {
+            algorithm,
+            ..CanonicalOptions::default()
+        })
+    }
+
+    /// Hashes the [canonical bytes](Value::canonical_bytes) of the value
+    /// with the given options.
+    pub fn canonical_hash_with(
+        &self,
+        options: &CanonicalOptions,
+    ) -> [u8; 32] {
+        let bytes = self.canonical_bytes(options);
+        match options.algorithm {
+            HashAlgorithm::Sha256 => sha256(&bytes),
+        }
+    }
+}
+
+fn encode(
+    value: &Value,
+    options: &CanonicalOptions,
+    out: &mut Vec<u8>,
+) {
+    match value {
+        Value::Null => out.push(b'n'),
+        Value::Bool(boolean) => {
+            out.push(b'b');
+            out.push(u8::from(*boolean));
+        }
+        Value::Number(number) => encode_number(number, out),
+        Value::String(string) => {
+            out.push(b's');
+            encode_str(string, out);
+        }
+        Value::Sequence(sequence) => {
+            out.push(b'q');
+            encode_len(sequence.len(), out);
+            for element in sequence {
+                encode(element, options, out);
+            }
+        }
+        Value::Mapping(mapping) => {
+            encode_mapping(mapping, options, out)
+        }
+        Value::Tagged(tagged) => {
+            if let TagPolicy::Include = options.tags {
+                out.push(b't');
+                encode_str(&tagged.tag.to_string(), out);
+            }
+            encode(&tagged.value, options, out);
+        }
+    }
+}
+
+fn encode_number(number: &Number, out: &mut Vec<u8>) {
+    if number.is_f64() {
+        let float = number.as_f64().unwrap_or_default();
+        let bits = if float == 0.0 {
+            0
+        } else if float.is_nan() {
+            0x7ff8_0000_0000_0000
+        } else {
+            float.to_bits()
+        };
+        out.push(b'f');
+        out.extend_from_slice(&bits.to_be_bytes());
+    } else {
+        let integer = match (number.as_u64(), number.as_i64()) {
+            (Some(unsigned), _) => Some(i128::from(unsigned)),
+            (None, Some(signed)) => Some(i128::from(signed)),
+            // Only integers held by the `bigint` feature are left, which
+            // display as their exact decimal digits.
+            (None, None) => number.to_string().parse::<i128>().ok(),
+        };
+        match integer {
+            Some(integer) => {
+                out.push(b'i');
+                out.extend_from_slice(&integer.to_be_bytes());
+            }
+            None => {
+                out.push(b'z');
+                encode_str(&number.to_string(), out);
+            }
+        }
+    }
+}
+
+fn encode_mapping(
+    mapping: &Mapping,
+    options: &CanonicalOptions,
+    out: &mut Vec<u8>,
+) {
+    out.push(b'm');
+    encode_len(mapping.len(), out);
+    match options.key_order {
+        KeyOrder::Preserve => {
+            for (key, value) in mapping {
+                encode(key, options, out);
+                encode(value, options, out);
+            }
+        }
+        KeyOrder::Sorted => {
+            let mut entries: Vec<(Vec<u8>, &Value)> = mapping
+                .iter()
+                .map(|(key, value)| {
+                    let mut encoded = Vec::new();
+                    encode(key, options, &mut encoded);
+                    (encoded, value)
+                })
+                .collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            for (key, value) in entries {
+                out.extend_from_slice(&key);
+                encode(value, options, out);
+            }
+        }
+    }
+}
+
+fn encode_str(string: &str, out: &mut Vec<u8>) {
+    encode_len(string.len(), out);
+    out.extend_from_slice(string.as_bytes());
+}
+
+fn encode_len(len: usize, out: &mut Vec<u8>) {
+    out.extend_from_slice(&(len as u64).to_be_bytes());
+}
+
+/// The SHA-256 round constants.
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b,
+    0x59f111f1, 0x923f82a4, 0xab1c5ed5, 0xd807aa98, 0x12835b01,
+    0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7,
+    0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc,
+    0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152,
+    0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147,
+    0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+    0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819,
+    0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116, 0x1e376c08,
+    0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f,
+    0x682e6ff3, 0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208,
+    0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Computes the SHA-256 digest of `bytes`.
+fn sha256(bytes: &[u8]) -> [u8; 32] {
+    let mut state: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f,
+        0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+    ];
+    let mut message = bytes.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message
+        .extend_from_slice(&((bytes.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks_exact(64) {
+        let mut w = [0u32; 64];
+        for (word, chunk) in w.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_be_bytes([
+                chunk[0], chunk[1], chunk[2], chunk[3],
+            ]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7)
+                ^ w[i - 15].rotate_right(18)
+                ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17)
+                ^ w[i - 2].rotate_right(19)
+                ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] =
+            state;
+        for (k, w) in K.iter().zip(w.iter()) {
+            let s1 = e.rotate_right(6)
+                ^ e.rotate_right(11)
+                ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(*k)
+                .wrapping_add(*w);
+            let s0 = a.rotate_right(2)
+                ^ a.rotate_right(13)
+                ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (word, add) in
+            state.iter_mut().zip([a, b, c, d, e, f, g, h])
+        {
+            *word = word.wrapping_add(add);
+        }
+    }
+
+    let mut digest = [0u8; 32];
+    for (chunk, word) in digest.chunks_exact_mut(4).zip(state.iter()) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(digest: [u8; 32]) -> String {
+        digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn test_sha256_fips_vectors() {
+        // The examples of FIPS 180-4, from the NIST cryptographic
+        // standards and guidelines, and the digest of the empty message.
+        assert_eq!(
+            hex(sha256(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        );
+        assert_eq!(
+            hex(sha256(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+        );
+        assert_eq!(
+            hex(sha256(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        );
+    }
+}
@@ -0,0 +1,450 @@
+//! A compact representation of YAML values for large documents.
+
+use crate::mapping::duplicate_key_error;
+use crate::value::borrowed::SerializeTag;
+use crate::value::tagged::TagStringVisitor;
+use crate::value::{Tag, TaggedValue};
+use crate::{lib::*, Deserializer, Error, Number, Value};
+use alloc::{collections::BTreeSet, sync::Arc};
+use core::fmt::{self, Formatter};
+use serde::de::{
+    self, DeserializeSeed, EnumAccess, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+
+/// A YAML value stored with fewer allocations than [`Value`].
+///
+/// String mapping keys are interned by an [`Interner`], so a key repeated
+/// across thousands of mappings, like `name` or `apiVersion` in a stream
+/// of manifests, is stored once. Sequences and mappings are boxed slices
+/// sized to their contents, without the spare capacity or hash index of a
+/// `Vec` or [`Mapping`](crate::Mapping). In exchange, looking up a key with
+/// [`CompactValue::get`] scans the entries of the mapping.
+///
+/// Convert to and from [`Value`] with `From`, or load YAML straight into
+/// the compact form with [`Interner::parse`].
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::value::{CompactValue, Interner};
+///
+/// let mut interner = Interner::new();
+/// let pods = interner.parse("- name: a\n  port: 80\n- name: b\n  port: 81\n").unwrap();
+///
+/// assert_eq!(pods.as_sequence().unwrap()[1].get("name").unwrap().as_str(), Some("b"));
+/// assert_eq!(interner.len(), 2);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum CompactValue {
+    /// Represents a YAML null value.
+    Null,
+    /// Represents a YAML boolean.
+    Bool(bool),
+    /// Represents a YAML numerical value, whether integer or floating point.
+    Number(Number),
+    /// Represents a YAML string, shared with other occurrences when it was
+    /// interned.
+    String(Arc<str>),
+    /// Represents a YAML sequence.
+    Sequence(Box<[CompactValue]>),
+    /// Represents a YAML mapping, with its entries in order.
+    Mapping(Box<[(CompactValue, CompactValue)]>),
+    /// Represents a tagged value.
+    Tagged(Box<(Tag, CompactValue)>),
+}
+
+impl CompactValue {
+    /// Returns the value of the first entry of a mapping whose key is the
+    /// string `key`.
+    pub fn get(&self, key: &str) -> Option<&CompactValue> {
+        self.as_mapping()?
+            .iter()
+            .find(|(k, _)| k.as_str() == Some(key))
+            .map(|(_, v)| v)
+    }
+
+    /// Returns the string, if the value is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            CompactValue::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements, if the value is a sequence.
+    pub fn as_sequence(&self) -> Option<&[CompactValue]> {
+        match self {
+            CompactValue::Sequence(sequence) => Some(sequence),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries, if the value is a mapping.
+    pub fn as_mapping(
+        &self,
+    ) -> Option<&[(CompactValue, CompactValue)]> {
+        match self {
+            CompactValue::Mapping(mapping) => Some(mapping),
+            _ => None,
+        }
+    }
+}
+
+/// Converts a `Value` into a `CompactValue`, interning its mapping keys
+/// with a fresh [`Interner`].
+impl From<Value> for CompactValue {
+    fn from(value: Value) -> Self {
+        Interner::new().compact(value)
+    }
+}
+
+/// Converts a `CompactValue` back into a `Value`.
+impl From<CompactValue> for Value {
+    fn from(value: CompactValue) -> Self {
+        match value {
+            CompactValue::Null => Value::Null,
+            CompactValue::Bool(b) => Value::Bool(b),
+            CompactValue::Number(n) => Value::Number(n),
+            CompactValue::String(s) => Value::String(String::from(&*s)),
+            CompactValue::Sequence(sequence) => Value::Sequence(
+                Vec::from(sequence)
+                    .into_iter()
+                    .map(Value::from)
+                    .collect(),
+            ),
+            CompactValue::Mapping(mapping) => Value::Mapping(
+                Vec::from(mapping)
+                    .into_iter()
+                    .map(|(k, v)| (Value::from(k), Value::from(v)))
+                    .collect(),
+            ),
+            CompactValue::Tagged(tagged) => {
+                let (tag, value) = *tagged;
+                Value::Tagged(Box::new(TaggedValue {
+                    tag,
+                    value: Value::from(value),
+                }))
+            }
+        }
+    }
+}
+
+impl Serialize for CompactValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            CompactValue::Null => serializer.serialize_unit(),
+            CompactValue::Bool(b) => serializer.serialize_bool(*b),
+            CompactValue::Number(n) => n.serialize(serializer),
+            CompactValue::String(s) => serializer.serialize_str(s),
+            CompactValue::Sequence(sequence) => {
+                let mut seq =
+                    serializer.serialize_seq(Some(sequence.len()))?;
+                for element in sequence.iter() {
+                    seq.serialize_element(element)?;
+                }
+                seq.end()
+            }
+            CompactValue::Mapping(mapping) => {
+                let mut map =
+                    serializer.serialize_map(Some(mapping.len()))?;
+                for (k, v) in mapping.iter() {
+                    map.serialize_entry(k, v)?;
+                }
+                map.end()
+            }
+            CompactValue::Tagged(tagged) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry(
+                    &SerializeTag(&tagged.0),
+                    &tagged.1,
+                )?;
+                map.end()
+            }
+        }
+    }
+}
+
+/// The set of strings shared by the [`CompactValue`]s it builds.
+///
+/// Keep one interner for all the documents of a stream, or of a set of
+/// files, so that their keys are shared too.
+#[derive(Clone, Debug, Default)]
+pub struct Interner {
+    strings: BTreeSet<Arc<str>>,
+}
+
+impl Interner {
+    /// Creates an empty interner.
+    pub fn new() -> Self {
+        Interner::default()
+    }
+
+    /// Returns the shared copy of `string`, adding it on first use.
+    pub fn intern(&mut self, string: &str) -> Arc<str> {
+        if let Some(interned) = self.strings.get(string) {
+            return Arc::clone(interned);
+        }
+        let interned: Arc<str> = Arc::from(string);
+        self.strings.insert(Arc::clone(&interned));
+        interned
+    }
+
+    /// Returns the number of distinct strings interned.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Returns whether no string has been interned.
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Converts a `Value` into a `CompactValue`, interning its string
+    /// mapping keys.
+    pub fn compact(&mut self, value: Value) -> CompactValue {
+        self.compact_node(value, false)
+    }
+
+    /// Loads a single YAML document into a `CompactValue`, without
+    /// building a `Value` first.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`from_str`](crate::from_str).
+    pub fn parse(&mut self, yaml: &str) -> Result<CompactValue, Error> {
+        Seed {
+            interner: self,
+            key: false,
+        }
+        .deserialize(Deserializer::from_str(yaml))
+    }
+
+    fn compact_node(
+        &mut self,
+        value: Value,
+        key: bool,
+    ) -> CompactValue {
+        match value {
+            Value::Null => CompactValue::Null,
+            Value::Bool(b) => CompactValue::Bool(b),
+            Value::Number(n) => CompactValue::Number(n),
+            Value::String(s) => {
+                CompactValue::String(self.string(&s, key))
+            }
+            Value::Sequence(sequence) => CompactValue::Sequence(
+                sequence
+                    .into_iter()
+                    .map(|element| self.compact_node(element, false))
+                    .collect(),
+            ),
+            Value::Mapping(mapping) => CompactValue::Mapping(
+                mapping
+                    .into_iter()
+                    .map(|(k, v)| {
+                        (
+                            self.compact_node(k, true),
+                            self.compact_node(v, false),
+                        )
+                    })
+                    .collect(),
+            ),
+            Value::Tagged(tagged) => {
+                let TaggedValue { tag, value } = *tagged;
+                let value = self.compact_node(value, key);
+                CompactValue::Tagged(Box::new((tag, value)))
+            }
+        }
+    }
+
+    fn string(&mut self, string: &str, key: bool) -> Arc<str> {
+        if key {
+            self.intern(string)
+        } else {
+            Arc::from(string)
+        }
+    }
+}
+
+/// Deserializes a node into a `CompactValue`, interning its strings if it
+/// is a mapping key.
+struct Seed<'a> {
+    interner: &'a mut Interner,
+    key: bool,
+}
+
+impl<'de> DeserializeSeed<'de> for Seed<'_> {
+    type Value = CompactValue;
+
+    fn deserialize<D>(
+        self,
+        deserializer: D,
+    ) -> Result<CompactValue, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Seed<'_> {
+    type Value = CompactValue;
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        formatter.write_str("any YAML value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<CompactValue, E>
+    where
+        E: de::Error,
+    {
+        Ok(CompactValue::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<CompactValue, E>
+    where
+        E: de::Error,
+    {
+        Ok(CompactValue::Number(value.into()))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<CompactValue, E>
+    where
+        E: de::Error,
+    {
+        Ok(CompactValue::Number(value.into()))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<CompactValue, E>
+    where
+        E: de::Error,
+    {
+        Ok(CompactValue::Number(value.into()))
+    }
+
+    #[cfg(feature = "bigint")]
+    fn visit_i128<E>(self, value: i128) -> Result<CompactValue, E>
+    where
+        E: de::Error,
+    {
+        Ok(CompactValue::Number(value.into()))
+    }
+
+    #[cfg(feature = "bigint")]
+    fn visit_u128<E>(self, value: u128) -> Result<CompactValue, E>
+    where
+        E: de::Error,
+    {
+        Ok(CompactValue::Number(value.into()))
+    }
+
+    /// Receives the digits of an integer outside the range of `i128` and
+    /// `u128`, which `Value` knows how to read.
+    #[cfg(feature = "bigint")]
+    fn visit_newtype_struct<D>(
+        self,
+        deserializer: D,
+    ) -> Result<CompactValue, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let value = de::Deserialize::deserialize(deserializer)?;
+        Ok(self.interner.compact_node(value, self.key))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<CompactValue, E>
+    where
+        E: de::Error,
+    {
+        Ok(CompactValue::String(self.interner.string(value, self.key)))
+    }
+
+    fn visit_unit<E>(self) -> Result<CompactValue, E>
+    where
+        E: de::Error,
+    {
+        Ok(CompactValue::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<CompactValue, E>
+    where
+        E: de::Error,
+    {
+        Ok(CompactValue::Null)
+    }
+
+    fn visit_some<D>(
+        self,
+        deserializer: D,
+    ) -> Result<CompactValue, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.deserialize(deserializer)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<CompactValue, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut elements =
+            Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(element) = seq.next_element_seed(Seed {
+            interner: &mut *self.interner,
+            key: false,
+        })? {
+            elements.push(element);
+        }
+        Ok(CompactValue::Sequence(elements.into_boxed_slice()))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<CompactValue, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entries: Vec<(CompactValue, CompactValue)> =
+            Vec::with_capacity(map.size_hint().unwrap_or(0));
+        // String keys are checked for duplicates through a set, and the
+        // rare other keys by comparing them with each other.
+        let mut strings = BTreeSet::new();
+        let mut others: Vec<usize> = Vec::new();
+        while let Some(key) = map.next_key_seed(Seed {
+            interner: &mut *self.interner,
+            key: true,
+        })? {
+            let duplicate = match &key {
+                CompactValue::String(string) => {
+                    !strings.insert(Arc::clone(string))
+                }
+                _ => {
+                    let duplicate =
+                        others.iter().any(|&i| entries[i].0 == key);
+                    others.push(entries.len());
+                    duplicate
+                }
+            };
+            if duplicate {
+                return Err(duplicate_key_error(Value::from(key)));
+            }
+            let value = map.next_value_seed(Seed {
+                interner: &mut *self.interner,
+                key: false,
+            })?;
+            entries.push((key, value));
+        }
+        Ok(CompactValue::Mapping(entries.into_boxed_slice()))
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<CompactValue, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        let (tag, contents) = data.variant_seed(TagStringVisitor)?;
+        let value = contents.newtype_variant_seed(self)?;
+        Ok(CompactValue::Tagged(Box::new((tag, value))))
+    }
+}
@@ -1,6 +1,10 @@
 use crate::value::tagged::{self, TagStringVisitor};
 use crate::value::TaggedValue;
-use crate::{number, Error, Mapping, Sequence, Value};
+use crate::{lib::*, number, Error, Mapping, Sequence, Value};
+use alloc::vec;
+use core::fmt::Formatter;
+use core::fmt::Result as FmtResult;
+use core::slice;
 use serde::de::value::{BorrowedStrDeserializer, StrDeserializer};
 use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
 use serde::de::{
@@ -9,10 +13,6 @@ use serde::de::{
     VariantAccess, Visitor,
 };
 use serde::forward_to_deserialize_any;
-use std::fmt::Formatter;
-use std::fmt::Result as FmtResult;
-use std::slice;
-use std::vec;
 
 /// Deserializes a `Value` from a given deserializer.
 impl<'de> Deserialize<'de> for Value {
@@ -60,6 +60,43 @@ impl<'de> Deserialize<'de> for Value {
                 Ok(Value::Number(value.into()))
             }
 
+            #[cfg(feature = "bigint")]
+            fn visit_i128<E>(self, value: i128) -> Result<Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(Value::Number(value.into()))
+            }
+
+            #[cfg(feature = "bigint")]
+            fn visit_u128<E>(self, value: u128) -> Result<Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(Value::Number(value.into()))
+            }
+
+            /// Receives the digits of an integer outside the range of
+            /// `i128` and `u128`.
+            #[cfg(feature = "bigint")]
+            fn visit_newtype_struct<D>(
+                self,
+                deserializer: D,
+            ) -> Result<Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                match Value::deserialize(deserializer)? {
+                    Value::String(digits) => {
+                        Ok(match crate::de::parse_big_int(&digits) {
+                            Some(int) => Value::Number(int.into()),
+                            None => Value::String(digits),
+                        })
+                    }
+                    value => Ok(value),
+                }
+            }
+
             fn visit_str<E>(self, value: &str) -> Result<Value, E>
             where
                 E: de::Error,
@@ -154,12 +191,14 @@ impl Value {
 fn visit_sequence<'de, V>(
     sequence: Sequence,
     visitor: V,
+    human_readable: bool,
 ) -> Result<V::Value, Error>
 where
     V: Visitor<'de>,
 {
     let len = sequence.len();
-    let mut deserializer = SeqDeserializer::new(sequence);
+    let mut deserializer =
+        SeqDeserializer::new(sequence, human_readable);
     let seq = visitor.visit_seq(&mut deserializer)?;
     let remaining = deserializer.iter.len();
     if remaining == 0 {
@@ -192,12 +231,14 @@ where
 fn visit_mapping<'de, V>(
     mapping: Mapping,
     visitor: V,
+    human_readable: bool,
 ) -> Result<V::Value, Error>
 where
     V: Visitor<'de>,
 {
     let len = mapping.len();
-    let mut deserializer = MapDeserializer::new(mapping);
+    let mut deserializer =
+        MapDeserializer::new(mapping, human_readable);
     let map = visitor.visit_map(&mut deserializer)?;
     let remaining = deserializer.iter.len();
     if remaining == 0 {
@@ -226,21 +267,223 @@ where
     }
 }
 
+/// Visits a `TaggedValue` with the given `Visitor`.
+///
+/// Serde buffers the input of `#[serde(flatten)]` fields and of untagged
+/// and internally tagged enums in a form that rejects enums, so for it the
+/// tagged value becomes a mapping from the tag to the untagged value.
+fn visit_tagged<'de, V>(
+    tagged: TaggedValue,
+    visitor: V,
+    human_readable: bool,
+) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    if crate::de::buffers_content(&visitor) {
+        let mut mapping = Mapping::new();
+        mapping.insert(
+            Value::String(
+                tagged::nobang(&tagged.tag.string).to_owned(),
+            ),
+            tagged.value,
+        );
+        visit_mapping(mapping, visitor, human_readable)
+    } else {
+        visitor.visit_enum(EnumDeserializer {
+            tag: tagged::nobang(&tagged.tag.string),
+            value: Some(tagged.value),
+            human_readable,
+        })
+    }
+}
+
+/// Deserializer whose input is a `Value`.
+///
+/// This is the deserializer that backs
+/// [`serde_yml::from_value`][crate::from_value], and the one a `Value`
+/// deserializes through when used as a `Deserializer` itself. Its
+/// counterpart is [`value::Serializer`](crate::value::Serializer).
+///
+/// By default the deserializer reports itself as human-readable, so types
+/// such as `IpAddr` expect their text form. Values written by a
+/// `value::Serializer` that is not human-readable, the default, are read
+/// back after [`with_human_readable(false)`](Self::with_human_readable):
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use serde_yml::value::{Deserializer, Serializer};
+/// use std::net::IpAddr;
+///
+/// let addr: IpAddr = "::1".parse().unwrap();
+/// let value = addr.serialize(Serializer::new()).unwrap();
+/// let de = Deserializer::new(value).with_human_readable(false);
+/// assert_eq!(IpAddr::deserialize(de).unwrap(), addr);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ValueDeserializer {
+    value: Value,
+    human_readable: bool,
+}
+
+impl ValueDeserializer {
+    /// Creates a deserializer of `value` with the default options.
+    pub fn new(value: Value) -> Self {
+        ValueDeserializer {
+            value,
+            human_readable: true,
+        }
+    }
+
+    /// Sets whether the deserializer reports itself as human-readable
+    /// through `Deserializer::is_human_readable`, for this value and every
+    /// value nested in it. The default is `true`.
+    pub fn with_human_readable(mut self, human_readable: bool) -> Self {
+        self.human_readable = human_readable;
+        self
+    }
+
+    /// Returns the value the deserializer reads.
+    pub fn into_value(self) -> Value {
+        self.value
+    }
+}
+
+/// Forwards methods of `Deserializer` to a [`ValueDeserializer`] with the
+/// default options.
+macro_rules! forward_to_value_deserializer {
+    ($($method:ident($($arg:ident: $ty:ty),*))*) => {
+        $(
+            fn $method<V>(
+                self,
+                $($arg: $ty,)*
+                visitor: V,
+            ) -> Result<V::Value, Error>
+            where
+                V: Visitor<'de>,
+            {
+                ValueDeserializer::new(self).$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
 impl<'de> Deserializer<'de> for Value {
     type Error = Error;
 
+    forward_to_value_deserializer! {
+        deserialize_any()
+        deserialize_bool()
+        deserialize_i8()
+        deserialize_i16()
+        deserialize_i32()
+        deserialize_i64()
+        deserialize_i128()
+        deserialize_u8()
+        deserialize_u16()
+        deserialize_u32()
+        deserialize_u64()
+        deserialize_u128()
+        deserialize_f32()
+        deserialize_f64()
+        deserialize_char()
+        deserialize_str()
+        deserialize_string()
+        deserialize_bytes()
+        deserialize_byte_buf()
+        deserialize_option()
+        deserialize_unit()
+        deserialize_unit_struct(name: &'static str)
+        deserialize_newtype_struct(name: &'static str)
+        deserialize_seq()
+        deserialize_tuple(len: usize)
+        deserialize_tuple_struct(name: &'static str, len: usize)
+        deserialize_map()
+        deserialize_struct(name: &'static str, fields: &'static [&'static str])
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+        deserialize_identifier()
+        deserialize_ignored_any()
+    }
+}
+
+impl<'de> VariantAccess<'de> for ValueDeserializer {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        if let Value::Sequence(v) = self.value {
+            Deserializer::deserialize_any(
+                SeqDeserializer::new(v, self.human_readable),
+                visitor,
+            )
+        } else {
+            Err(Error::invalid_type(
+                self.value.unexpected(),
+                &"tuple variant",
+            ))
+        }
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        if let Value::Mapping(v) = self.value {
+            Deserializer::deserialize_any(
+                MapDeserializer::new(v, self.human_readable),
+                visitor,
+            )
+        } else {
+            Err(Error::invalid_type(
+                self.value.unexpected(),
+                &"struct variant",
+            ))
+        }
+    }
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer {
+    type Error = Error;
+
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        match self {
+        match self.value {
             Value::Null => visitor.visit_unit(),
             Value::Bool(v) => visitor.visit_bool(v),
             Value::Number(n) => n.deserialize_any(visitor),
             Value::String(v) => visitor.visit_string(v),
-            Value::Sequence(v) => visit_sequence(v, visitor),
-            Value::Mapping(v) => visit_mapping(v, visitor),
-            Value::Tagged(tagged) => visitor.visit_enum(*tagged),
+            Value::Sequence(v) => {
+                visit_sequence(v, visitor, self.human_readable)
+            }
+            Value::Mapping(v) => {
+                visit_mapping(v, visitor, self.human_readable)
+            }
+            Value::Tagged(tagged) => {
+                visit_tagged(*tagged, visitor, self.human_readable)
+            }
         }
     }
 
@@ -248,7 +491,7 @@ impl<'de> Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        match self.untag() {
+        match self.value.untag() {
             Value::Bool(v) => visitor.visit_bool(v),
             other => Err(other.invalid_type(&visitor)),
         }
@@ -258,91 +501,96 @@ impl<'de> Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        self.deserialize_number(visitor)
+        self.value.deserialize_number(visitor)
     }
 
     fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_number(visitor)
+        self.value.deserialize_number(visitor)
     }
 
     fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_number(visitor)
+        self.value.deserialize_number(visitor)
     }
 
     fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_number(visitor)
+        self.value.deserialize_number(visitor)
     }
 
     fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_number(visitor)
+        self.value.deserialize_number(visitor)
     }
 
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_number(visitor)
+        self.value.deserialize_number(visitor)
     }
 
     fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_number(visitor)
+        self.value.deserialize_number(visitor)
     }
 
     fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_number(visitor)
+        self.value.deserialize_number(visitor)
     }
 
     fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_number(visitor)
+        self.value.deserialize_number(visitor)
     }
 
     fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_number(visitor)
+        self.value.deserialize_number(visitor)
     }
 
     fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_number(visitor)
+        self.value.deserialize_number(visitor)
     }
 
     fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_number(visitor)
+        self.value.deserialize_number(visitor)
     }
 
     fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_string(visitor)
+        match self.value.untag() {
+            Value::String(v) => {
+                crate::de::visit_char(visitor, &v, false)
+            }
+            other => Err(other.invalid_type(&visitor)),
+        }
     }
 
     fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Error>
@@ -359,7 +607,7 @@ impl<'de> Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        match self.untag() {
+        match self.value.untag() {
             Value::String(v) => visitor.visit_string(v),
             other => Err(other.invalid_type(&visitor)),
         }
@@ -379,9 +627,11 @@ impl<'de> Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        match self.untag() {
+        match self.value.untag() {
             Value::String(v) => visitor.visit_string(v),
-            Value::Sequence(v) => visit_sequence(v, visitor),
+            Value::Sequence(v) => {
+                visit_sequence(v, visitor, self.human_readable)
+            }
             other => Err(other.invalid_type(&visitor)),
         }
     }
@@ -393,7 +643,7 @@ impl<'de> Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        match self {
+        match self.value {
             Value::Null => visitor.visit_none(),
             _ => visitor.visit_some(self),
         }
@@ -403,9 +653,9 @@ impl<'de> Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        match self {
+        match self.value {
             Value::Null => visitor.visit_unit(),
-            _ => Err(self.invalid_type(&visitor)),
+            _ => Err(self.value.invalid_type(&visitor)),
         }
     }
 
@@ -435,9 +685,15 @@ impl<'de> Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        match self.untag() {
-            Value::Sequence(v) => visit_sequence(v, visitor),
-            Value::Null => visit_sequence(Sequence::new(), visitor),
+        match self.value.untag() {
+            Value::Sequence(v) => {
+                visit_sequence(v, visitor, self.human_readable)
+            }
+            Value::Null => visit_sequence(
+                Sequence::new(),
+                visitor,
+                self.human_readable,
+            ),
             other => Err(other.invalid_type(&visitor)),
         }
     }
@@ -469,9 +725,15 @@ impl<'de> Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        match self.untag() {
-            Value::Mapping(v) => visit_mapping(v, visitor),
-            Value::Null => visit_mapping(Mapping::new(), visitor),
+        match self.value.untag() {
+            Value::Mapping(v) => {
+                visit_mapping(v, visitor, self.human_readable)
+            }
+            Value::Null => visit_mapping(
+                Mapping::new(),
+                visitor,
+                self.human_readable,
+            ),
             other => Err(other.invalid_type(&visitor)),
         }
     }
@@ -498,13 +760,14 @@ impl<'de> Deserializer<'de> for Value {
         V: Visitor<'de>,
     {
         let tag;
-        visitor.visit_enum(match self {
+        visitor.visit_enum(match self.value {
             Value::Tagged(tagged) => EnumDeserializer {
                 tag: {
                     tag = tagged.tag.string;
                     tagged::nobang(&tag)
                 },
                 value: Some(tagged.value),
+                human_readable: self.human_readable,
             },
             Value::String(variant) => EnumDeserializer {
                 tag: {
@@ -512,6 +775,7 @@ impl<'de> Deserializer<'de> for Value {
                     &tag
                 },
                 value: None,
+                human_readable: self.human_readable,
             },
             other => {
                 return Err(Error::invalid_type(
@@ -542,12 +806,17 @@ impl<'de> Deserializer<'de> for Value {
         drop(self);
         visitor.visit_unit()
     }
+
+    fn is_human_readable(&self) -> bool {
+        self.human_readable
+    }
 }
 
 /// Represents an enum deserializer.
 struct EnumDeserializer<'a> {
     tag: &'a str,
     value: Option<Value>,
+    human_readable: bool,
 }
 
 impl<'de> EnumAccess<'de> for EnumDeserializer<'_> {
@@ -563,7 +832,10 @@ impl<'de> EnumAccess<'de> for EnumDeserializer<'_> {
     {
         let str_de = StrDeserializer::<Error>::new(self.tag);
         let variant = seed.deserialize(str_de)?;
-        let visitor = VariantDeserializer { value: self.value };
+        let visitor = VariantDeserializer {
+            value: self.value,
+            human_readable: self.human_readable,
+        };
         Ok((variant, visitor))
     }
 }
@@ -571,13 +843,25 @@ impl<'de> EnumAccess<'de> for EnumDeserializer<'_> {
 /// Represents a variant deserializer.
 struct VariantDeserializer {
     value: Option<Value>,
+    human_readable: bool,
+}
+
+impl VariantDeserializer {
+    /// Returns a deserializer of the value of the variant, if it has one.
+    fn value(self) -> Option<ValueDeserializer> {
+        let human_readable = self.human_readable;
+        self.value.map(|value| {
+            ValueDeserializer::new(value)
+                .with_human_readable(human_readable)
+        })
+    }
 }
 
 impl<'de> VariantAccess<'de> for VariantDeserializer {
     type Error = Error;
 
     fn unit_variant(self) -> Result<(), Error> {
-        match self.value {
+        match self.value() {
             Some(value) => value.unit_variant(),
             None => Ok(()),
         }
@@ -587,7 +871,7 @@ impl<'de> VariantAccess<'de> for VariantDeserializer {
     where
         T: DeserializeSeed<'de>,
     {
-        match self.value {
+        match self.value() {
             Some(value) => value.newtype_variant_seed(seed),
             None => Err(Error::invalid_type(
                 Unexpected::UnitVariant,
@@ -604,7 +888,7 @@ impl<'de> VariantAccess<'de> for VariantDeserializer {
     where
         V: Visitor<'de>,
     {
-        match self.value {
+        match self.value() {
             Some(value) => value.tuple_variant(len, visitor),
             None => Err(Error::invalid_type(
                 Unexpected::UnitVariant,
@@ -621,7 +905,7 @@ impl<'de> VariantAccess<'de> for VariantDeserializer {
     where
         V: Visitor<'de>,
     {
-        match self.value {
+        match self.value() {
             Some(value) => value.struct_variant(fields, visitor),
             None => Err(Error::invalid_type(
                 Unexpected::UnitVariant,
@@ -634,13 +918,15 @@ impl<'de> VariantAccess<'de> for VariantDeserializer {
 /// Represents a sequence deserializer.
 pub(crate) struct SeqDeserializer {
     iter: vec::IntoIter<Value>,
+    human_readable: bool,
 }
 
 impl SeqDeserializer {
     /// Creates a new `SeqDeserializer` from the given vector of `Value`s.
-    pub(crate) fn new(vec: Vec<Value>) -> Self {
+    pub(crate) fn new(vec: Vec<Value>, human_readable: bool) -> Self {
         SeqDeserializer {
             iter: vec.into_iter(),
+            human_readable,
         }
     }
 }
@@ -702,7 +988,12 @@ impl<'de> SeqAccess<'de> for SeqDeserializer {
         T: DeserializeSeed<'de>,
     {
         match self.iter.next() {
-            Some(value) => seed.deserialize(value).map(Some),
+            Some(value) => seed
+                .deserialize(
+                    ValueDeserializer::new(value)
+                        .with_human_readable(self.human_readable),
+                )
+                .map(Some),
             None => Ok(None),
         }
     }
@@ -719,16 +1010,24 @@ impl<'de> SeqAccess<'de> for SeqDeserializer {
 pub(crate) struct MapDeserializer {
     iter: <Mapping as IntoIterator>::IntoIter,
     value: Option<Value>,
+    human_readable: bool,
 }
 
 impl MapDeserializer {
     /// Creates a new `MapDeserializer` from the given `Mapping`.
-    pub(crate) fn new(map: Mapping) -> Self {
+    pub(crate) fn new(map: Mapping, human_readable: bool) -> Self {
         MapDeserializer {
             iter: map.into_iter(),
             value: None,
+            human_readable,
         }
     }
+
+    /// Returns a deserializer of a key or value of the mapping.
+    fn deserializer(&self, value: Value) -> ValueDeserializer {
+        ValueDeserializer::new(value)
+            .with_human_readable(self.human_readable)
+    }
 }
 
 impl<'de> MapAccess<'de> for MapDeserializer {
@@ -744,7 +1043,7 @@ impl<'de> MapAccess<'de> for MapDeserializer {
         match self.iter.next() {
             Some((key, value)) => {
                 self.value = Some(value);
-                seed.deserialize(key).map(Some)
+                seed.deserialize(self.deserializer(key)).map(Some)
             }
             None => Ok(None),
         }
@@ -755,7 +1054,7 @@ impl<'de> MapAccess<'de> for MapDeserializer {
         T: DeserializeSeed<'de>,
     {
         match self.value.take() {
-            Some(value) => seed.deserialize(value),
+            Some(value) => seed.deserialize(self.deserializer(value)),
             None => panic!("visit_value called before visit_key"),
         }
     }
@@ -812,10 +1111,18 @@ impl<'de> Deserializer<'de> for &'de Value {
             Value::Sequence(v) => visit_sequence_ref(v, visitor),
             Value::Mapping(v) => visit_mapping_ref(v, visitor),
             Value::Tagged(tagged) => {
+                if crate::de::buffers_content(&visitor) {
+                    return visit_tagged(
+                        (**tagged).clone(),
+                        visitor,
+                        true,
+                    );
+                }
                 let cloned_tagged = tagged;
                 visitor.visit_enum(EnumDeserializer {
                     tag: tagged::nobang(&cloned_tagged.tag.string),
                     value: Some(cloned_tagged.clone().value),
+                    human_readable: true,
                 })
             }
         }
@@ -919,7 +1226,12 @@ impl<'de> Deserializer<'de> for &'de Value {
     where
         V: Visitor<'de>,
     {
-        self.deserialize_string(visitor)
+        match self.untag_ref() {
+            Value::String(v) => {
+                crate::de::visit_char(visitor, v, false)
+            }
+            other => Err(other.invalid_type(&visitor)),
+        }
     }
 
     fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Error>
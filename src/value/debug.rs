@@ -4,7 +4,7 @@
 
 use crate::mapping::Mapping;
 use crate::value::{Number, Value};
-use std::fmt::{self, Debug, Display};
+use core::fmt::{self, Debug, Display};
 
 /// Implements the `Debug` trait for `Value`.
 /// This allows for customized formatting when debugging `Value` instances.
@@ -1,7 +1,8 @@
 use crate::from_number;
-use crate::{Mapping, Value};
-use std::borrow::Cow;
-use std::iter::FromIterator;
+use crate::{lib::*, modules::error::Error, Mapping, Value};
+use alloc::{borrow::Cow, collections::BTreeMap};
+use core::{convert::TryFrom, iter::FromIterator};
+use serde::Deserialize;
 
 // Implement conversion from number types to `Value`.
 from_number! {
@@ -106,6 +107,67 @@ impl From<Mapping> for Value {
     }
 }
 
+impl<T: Into<Value>> From<Option<T>> for Value {
+    /// Convert an `Option` to `Value`, with `None` becoming `Value::Null`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_yml::Value;
+    ///
+    /// let x: Value = Some("lorem").into();
+    /// assert_eq!(x, Value::String("lorem".to_string()));
+    ///
+    /// let x: Value = None::<&str>.into();
+    /// assert_eq!(x, Value::Null);
+    /// ```
+    fn from(f: Option<T>) -> Self {
+        f.map_or(Value::Null, Into::into)
+    }
+}
+
+impl<K: Into<Value>, V: Into<Value>> From<BTreeMap<K, V>> for Value {
+    /// Convert a `BTreeMap` to a YAML mapping, keeping the order of its keys
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_yml::Value;
+    /// use std::collections::BTreeMap;
+    ///
+    /// let mut m = BTreeMap::new();
+    /// m.insert("b", 2);
+    /// m.insert("a", 1);
+    /// let x: Value = m.into();
+    /// assert_eq!(serde_yml::to_string(&x).unwrap(), "a: 1\nb: 2\n");
+    /// ```
+    fn from(f: BTreeMap<K, V>) -> Self {
+        Value::Mapping(
+            f.into_iter().map(|(k, v)| (k.into(), v.into())).collect(),
+        )
+    }
+}
+
+impl<K: Into<Value>, V: Into<Value>> From<Vec<(K, V)>> for Value {
+    /// Convert a `Vec` of key-value pairs to a YAML mapping, in the order
+    /// of the pairs. A later pair replaces the value of an earlier pair
+    /// with the same key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_yml::Value;
+    ///
+    /// let x: Value = vec![("name", "demo"), ("kind", "app")].into();
+    /// assert_eq!(serde_yml::to_string(&x).unwrap(), "name: demo\nkind: app\n");
+    /// ```
+    fn from(f: Vec<(K, V)>) -> Self {
+        Value::Mapping(
+            f.into_iter().map(|(k, v)| (k.into(), v.into())).collect(),
+        )
+    }
+}
+
 impl<T: Into<Value>> From<Vec<T>> for Value {
     /// Convert a `Vec` to `Value`
     ///
@@ -174,3 +236,26 @@ impl<T: Into<Value>> FromIterator<T> for Value {
         Value::Sequence(vec)
     }
 }
+
+// Implement fallible conversion from `Value` to primitive types, with the
+// same errors as deserializing the value into the type.
+macro_rules! try_from_value {
+    ($($ty:ty)*) => {
+        $(
+            impl TryFrom<Value> for $ty {
+                type Error = Error;
+
+                fn try_from(value: Value) -> Result<Self, Error> {
+                    <$ty>::deserialize(value)
+                }
+            }
+        )*
+    };
+}
+
+try_from_value! {
+    bool String
+    i8 i16 i32 i64 isize
+    u8 u16 u32 u64 usize
+    f32 f64
+}
@@ -1,7 +1,9 @@
 use crate::mapping::Entry;
-use crate::{mapping, private, Mapping, Value};
-use std::fmt::{self, Debug};
-use std::ops;
+use crate::modules::error::{self, Error, ErrorImpl};
+use crate::path::{Path, Segment};
+use crate::{lib::*, mapping, private, Mapping, Value};
+use core::fmt::{self, Debug};
+use core::ops;
 
 /// A type that can be used to index into a `serde_yml::Value`. See the `get`
 /// and `get_mut` methods of `Value`.
@@ -26,6 +28,11 @@ pub trait Index: private::Sealed {
     /// object.
     #[doc(hidden)]
     fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value;
+
+    /// The path segment naming this index, if it is a string key or an
+    /// integer.
+    #[doc(hidden)]
+    fn segment(&self) -> Option<Segment>;
 }
 
 impl Index for usize {
@@ -56,14 +63,17 @@ impl Index for usize {
         &self,
         mut v: &'v mut Value,
     ) -> &'v mut Value {
+        let address = address(v);
         loop {
             match v {
                 Value::Sequence(vec) => {
                     let len = vec.len();
                     return vec.get_mut(*self).unwrap_or_else(|| {
                         panic!(
-                            "cannot access index {} of YAML sequence of length {}",
-                            self, len
+                            "cannot access index {} of YAML sequence of length {}{}",
+                            self,
+                            len,
+                            At::new(address, self)
                         )
                     });
                 }
@@ -73,13 +83,18 @@ impl Index for usize {
                 }
                 Value::Tagged(tagged) => v = &mut tagged.value,
                 _ => panic!(
-                    "cannot access index {} of YAML {}",
+                    "cannot access index {} of YAML {}{}",
                     self,
-                    Type(v)
+                    Type(v),
+                    At::new(address, self)
                 ),
             }
         }
     }
+
+    fn segment(&self) -> Option<Segment> {
+        Some(Segment::Index(*self))
+    }
 }
 
 fn index_into_mapping<'v, I>(
@@ -113,9 +128,10 @@ fn index_or_insert_mapping<'v, I>(
     mut v: &'v mut Value,
 ) -> &'v mut Value
 where
-    I: ?Sized + mapping::Index + ToOwned + Debug,
+    I: ?Sized + Index + mapping::Index + ToOwned + Debug,
     Value: From<I::Owned>,
 {
+    let address = address(v);
     if let Value::Null = *v {
         *v = Value::Mapping(Mapping::new());
         return match v {
@@ -137,9 +153,10 @@ where
             }
             Value::Tagged(tagged) => v = &mut tagged.value,
             _ => panic!(
-                "cannot access key {:?} in YAML {}",
+                "cannot access key {:?} in YAML {}{}",
                 index,
-                Type(v)
+                Type(v),
+                At::new(address, index)
             ),
         }
     }
@@ -160,6 +177,13 @@ impl Index for Value {
     fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value {
         index_or_insert_mapping(self, v)
     }
+
+    fn segment(&self) -> Option<Segment> {
+        match self {
+            Value::String(key) => Some(Segment::Key(key.clone())),
+            _ => None,
+        }
+    }
 }
 
 impl Index for str {
@@ -177,6 +201,10 @@ impl Index for str {
     fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value {
         index_or_insert_mapping(self, v)
     }
+
+    fn segment(&self) -> Option<Segment> {
+        Some(Segment::Key(self.to_owned()))
+    }
 }
 
 impl Index for String {
@@ -194,6 +222,73 @@ impl Index for String {
     fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value {
         self.as_str().index_or_insert(v)
     }
+
+    fn segment(&self) -> Option<Segment> {
+        self.as_str().segment()
+    }
+}
+
+impl Index for bool {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        index_into_mapping(self, v)
+    }
+
+    fn index_into_mut<'v>(
+        &self,
+        v: &'v mut Value,
+    ) -> Option<&'v mut Value> {
+        index_into_mut_mapping(self, v)
+    }
+
+    fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value {
+        index_or_insert_mapping(self, v)
+    }
+
+    fn segment(&self) -> Option<Segment> {
+        None
+    }
+}
+
+impl Index for [Value] {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        index_into_mapping(self, v)
+    }
+
+    fn index_into_mut<'v>(
+        &self,
+        v: &'v mut Value,
+    ) -> Option<&'v mut Value> {
+        index_into_mut_mapping(self, v)
+    }
+
+    fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value {
+        index_or_insert_mapping(self, v)
+    }
+
+    fn segment(&self) -> Option<Segment> {
+        None
+    }
+}
+
+impl Index for Vec<Value> {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        self.as_slice().index_into(v)
+    }
+
+    fn index_into_mut<'v>(
+        &self,
+        v: &'v mut Value,
+    ) -> Option<&'v mut Value> {
+        self.as_slice().index_into_mut(v)
+    }
+
+    fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value {
+        self.as_slice().index_or_insert(v)
+    }
+
+    fn segment(&self) -> Option<Segment> {
+        None
+    }
 }
 
 impl<T> Index for &T
@@ -214,6 +309,140 @@ where
     fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value {
         (**self).index_or_insert(v)
     }
+
+    fn segment(&self) -> Option<Segment> {
+        (**self).segment()
+    }
+}
+
+/// Returns the error of [`Value::try_index`] when `index` is not found
+/// in `v`.
+pub(super) fn index_error<I>(v: &Value, index: &I) -> Error
+where
+    I: ?Sized + Index,
+{
+    let segment = index.segment();
+    let message = match (v.untag_ref(), &segment) {
+        (Value::Sequence(vec), Some(Segment::Index(_))) => format!(
+            "index out of bounds of YAML sequence of length {}",
+            vec.len()
+        ),
+        (Value::Sequence(_), _) => {
+            "cannot access a key of YAML sequence".to_owned()
+        }
+        (Value::Mapping(map), _) if map.is_empty() => {
+            "key not found in empty YAML mapping".to_owned()
+        }
+        (Value::Mapping(map), _) => {
+            format!("key not found; available keys: {}", Keys(map))
+        }
+        (other, _) => format!("cannot index into YAML {}", Type(other)),
+    };
+    let err = error::new(ErrorImpl::Message(message, None));
+    match segment {
+        Some(segment) => {
+            error::with_path(err, &Path::root().join(segment))
+        }
+        None => err,
+    }
+}
+
+/// Used in error messages: the keys of a mapping, up to a limit.
+struct Keys<'a>(&'a Mapping);
+
+impl fmt::Display for Keys<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const LIMIT: usize = 10;
+        for (i, key) in self.0.keys().take(LIMIT).enumerate() {
+            if i > 0 {
+                formatter.write_str(", ")?;
+            }
+            match key.untag_ref() {
+                Value::String(key) => formatter.write_str(key)?,
+                Value::Number(key) => {
+                    fmt::Display::fmt(key, formatter)?
+                }
+                Value::Bool(key) => fmt::Display::fmt(key, formatter)?,
+                Value::Null => formatter.write_str("null")?,
+                Value::Sequence(_) => formatter.write_str("[...]")?,
+                Value::Mapping(_) => formatter.write_str("{...}")?,
+                Value::Tagged(_) => unreachable!(),
+            }
+        }
+        if self.0.len() > LIMIT {
+            write!(formatter, " and {} more", self.0.len() - LIMIT)?;
+        }
+        Ok(())
+    }
+}
+
+// Chained `IndexMut` calls such as `value["spec"]["ports"][2] = port`
+// index each value without knowing how it was reached. To name the whole
+// path in a panic anyway, each call records the address of the value it
+// returns and the path to it, and the next call continues that path if it
+// indexes the value at that address. A value reached any other way starts
+// a new path. Without `std` there is no thread-local storage and panics
+// name the last step only.
+#[cfg(feature = "std")]
+std::thread_local! {
+    static TRAIL: core::cell::RefCell<(usize, Path)> =
+        core::cell::RefCell::new((0, Path::root()));
+}
+
+/// Returns the path to the value at `address`.
+fn trail(address: usize) -> Path {
+    #[cfg(feature = "std")]
+    {
+        TRAIL.with(|trail| match &*trail.borrow() {
+            (last, path) if *last == address => path.clone(),
+            _ => Path::root(),
+        })
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        let _ = address;
+        Path::root()
+    }
+}
+
+/// Records that the value at `address` was reached by `path`.
+fn set_trail(address: usize, path: Path) {
+    #[cfg(feature = "std")]
+    TRAIL.with(|trail| *trail.borrow_mut() = (address, path));
+    #[cfg(not(feature = "std"))]
+    let _ = (address, path);
+}
+
+/// Returns the address of `v`, which identifies it in the trail.
+fn address(v: &Value) -> usize {
+    let ptr: *const Value = v;
+    ptr as usize
+}
+
+/// Used in panic messages: where the value being indexed is, as
+/// ` at `path``, or nothing if the path is unknown.
+struct At(Path);
+
+impl At {
+    /// The path to `index` in the value at `address`.
+    fn new<I>(address: usize, index: &I) -> Self
+    where
+        I: ?Sized + Index,
+    {
+        let mut path = trail(address);
+        path.extend(index.segment());
+        At(path)
+    }
+}
+
+impl fmt::Display for At {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_root() {
+            Ok(())
+        } else {
+            write!(formatter, " at `{}`", self.0)
+        }
+    }
 }
 
 /// Used in panic messages.
@@ -303,6 +532,10 @@ where
 /// object, it will be inserted with a value of null. Indexing into a value
 /// that is neither an object nor null will panic.
 ///
+/// The panic message names the path taken by a chain of indexing such as
+/// `data["a"]["b"][0]`, as in ``cannot access index 0 of YAML string at
+/// `a.b[0]` ``. Use [`Value::try_index_mut`] to get an error instead.
+///
 /// # Examples
 ///
 /// ```
@@ -330,6 +563,10 @@ where
     I: Index,
 {
     fn index_mut(&mut self, index: I) -> &mut Value {
-        index.index_or_insert(self)
+        let mut path = trail(address(self));
+        let value = index.index_or_insert(self);
+        path.extend(index.segment());
+        set_trail(address(value), path);
+        value
     }
 }
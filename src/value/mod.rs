@@ -1,33 +1,81 @@
 //! The Value enum, a loosely typed way of representing any valid YAML value.
 
+mod borrowed;
+mod canonical;
+#[cfg(feature = "compact")]
+mod compact;
 mod de;
 mod debug;
 mod from;
 mod index;
 mod partial_eq;
+mod raw;
 mod ser;
+mod styled;
 /// A representation of YAML's `!Tag` syntax, used for enums.
 pub mod tagged;
 
-use crate::modules::error::{self, Error, ErrorImpl};
-use serde::{
-    de::{Deserialize, DeserializeOwned, IntoDeserializer},
-    Serialize,
+use crate::path::{Path, Segment};
+use crate::{
+    lib::*,
+    modules::error::{self, Error, ErrorImpl},
 };
-use std::{
+use core::{
+    cmp::Ordering,
+    fmt::{self, Display},
     hash::{Hash, Hasher},
     mem,
+    str::FromStr,
+};
+use serde::{
+    de::{Deserialize, DeserializeOwned, IntoDeserializer},
+    Serialize,
 };
 
+pub use self::borrowed::BorrowedValue;
+pub use self::canonical::{
+    CanonicalOptions, CanonicalVersion, HashAlgorithm, KeyOrder,
+    TagPolicy,
+};
+#[cfg(feature = "compact")]
+pub use self::compact::{CompactValue, Interner};
+pub use self::de::ValueDeserializer as Deserializer;
 pub use self::index::Index;
+pub use self::raw::RawYaml;
+pub(crate) use self::raw::RAW_YAML;
 pub use self::ser::Serializer;
-pub use self::tagged::{Tag, TaggedValue};
+pub use self::styled::{IntegerFormat, Radix, StyleMap, StyledValue};
+pub use self::tagged::{Tag, TaggedValue, TaggedValueBuilder};
 #[doc(inline)]
 pub use crate::mapping::Mapping;
 pub use crate::number::Number;
 
 /// Represents any valid YAML value.
-#[derive(Clone, PartialEq, PartialOrd)]
+///
+/// Values are totally ordered, so that they can be sorted and used as keys
+/// of a `BTreeMap`. Values of different kinds are ordered null, booleans,
+/// numbers, strings, sequences, mappings, then tagged values. Numbers are
+/// ordered as described for [`Number`], sequences element by element, and
+/// mappings by their entries taken in key order, so that two mappings that
+/// differ only in the order of their entries are equal. Tagged values are
+/// ordered by tag, then by value.
+///
+/// ```
+/// use serde_yml::Value;
+/// use std::collections::BTreeMap;
+///
+/// let mut values: Vec<Value> =
+///     serde_yml::from_str("[b, .nan, 2, null, 1.5, true, -.inf, [a]]").unwrap();
+/// values.sort();
+/// let sorted: Vec<Value> =
+///     serde_yml::from_str("[null, true, 2, -.inf, 1.5, .nan, b, [a]]").unwrap();
+/// assert_eq!(values, sorted);
+///
+/// let mut counts = BTreeMap::new();
+/// *counts.entry(Value::from("a")).or_insert(0) += 1;
+/// assert_eq!(counts[&Value::from("a")], 1);
+/// ```
+#[derive(Clone, PartialEq)]
 pub enum Value {
     /// Represents a YAML null value.
     Null,
@@ -114,7 +162,7 @@ pub fn to_value<T>(value: T) -> Result<Value, Error>
 where
     T: Serialize,
 {
-    value.serialize(Serializer)
+    value.serialize(Serializer::new())
 }
 
 /// Interpret a `serde_yml::Value` as an instance of type `T`.
@@ -210,6 +258,175 @@ impl Value {
         index.index_into_mut(self)
     }
 
+    /// Index into a YAML sequence or map like [`Value::get`], but returns
+    /// an error describing why nothing was found instead of `None`.
+    ///
+    /// The error names the index as its [path](Error::path) and says
+    /// whether the index is out of bounds of a sequence, the key is missing
+    /// from a mapping, whose keys it lists, or `self` cannot be indexed at
+    /// all. To look up a value several levels down, use
+    /// [`Value::try_get_path`].
+    ///
+    /// ```
+    /// # use serde_yml::Value;
+    /// let value: Value = serde_yml::from_str("host: a\nport: 80\n").unwrap();
+    /// assert_eq!(value.try_index("port").unwrap(), 80);
+    ///
+    /// let err = value.try_index("name").unwrap_err();
+    /// assert_eq!(err.to_string(), "name: key not found; available keys: host, port");
+    ///
+    /// let err = value["port"].try_index(0).unwrap_err();
+    /// assert_eq!(err.to_string(), r"\[0\]: cannot index into YAML number");
+    /// ```
+    pub fn try_index<I: Index>(
+        &self,
+        index: I,
+    ) -> Result<&Value, Error> {
+        index
+            .index_into(self)
+            .ok_or_else(|| index::index_error(self, &index))
+    }
+
+    /// Mutably index into a YAML sequence or map like [`Value::get_mut`],
+    /// but returns an error describing why nothing was found instead of
+    /// `None`, as [`Value::try_index`] does.
+    pub fn try_index_mut<I: Index>(
+        &mut self,
+        index: I,
+    ) -> Result<&mut Value, Error> {
+        // Looked up twice, as the borrow checker does not let the error
+        // borrow `self` after a failed mutable lookup.
+        if index.index_into(self).is_none() {
+            return Err(index::index_error(self, &index));
+        }
+        Ok(index.index_into_mut(self).unwrap())
+    }
+
+    /// Looks up the value at `path` below this one, following one mapping
+    /// key or sequence index per segment the same way as [`Value::get`].
+    ///
+    /// Returns `None` if any segment is missing.
+    ///
+    /// ```
+    /// # use serde_yml::Value;
+    /// use serde_yml::path::Path;
+    ///
+    /// let value: Value = serde_yml::from_str("servers:\n- port: 80\n").unwrap();
+    /// let path: Path = "servers[0].port".parse().unwrap();
+    /// assert_eq!(value.get_path(&path), Some(&Value::from(80)));
+    /// assert_eq!(value.get_path(&"/servers/1".parse().unwrap()), None);
+    /// ```
+    pub fn get_path(&self, path: &Path) -> Option<&Value> {
+        path.segments().iter().try_fold(self, |value, segment| {
+            match segment {
+                Segment::Key(key) => value.get(key.as_str()),
+                Segment::Index(index) => value.get(*index),
+            }
+        })
+    }
+
+    /// Mutably looks up the value at `path` below this one, following one
+    /// mapping key or sequence index per segment the same way as
+    /// [`Value::get_mut`].
+    ///
+    /// Returns `None` if any segment is missing.
+    pub fn get_path_mut(&mut self, path: &Path) -> Option<&mut Value> {
+        path.segments().iter().try_fold(self, |value, segment| {
+            match segment {
+                Segment::Key(key) => value.get_mut(key.as_str()),
+                Segment::Index(index) => value.get_mut(*index),
+            }
+        })
+    }
+
+    /// Looks up the value at `path` below this one like
+    /// [`Value::get_path`], but returns an error naming the first missing
+    /// segment instead of `None`.
+    ///
+    /// ```
+    /// # use serde_yml::Value;
+    /// let value: Value = serde_yml::from_str("server:\n  host: a\n").unwrap();
+    /// let err = value.try_get_path(&"server.port.number".parse().unwrap()).unwrap_err();
+    /// assert_eq!(err.to_string(), "server.port: value not found");
+    /// assert_eq!(err.path().unwrap().to_string(), "server.port");
+    /// ```
+    pub fn try_get_path(&self, path: &Path) -> Result<&Value, Error> {
+        let mut value = self;
+        let mut found = Path::root();
+        for segment in path.segments() {
+            found.push(segment.clone());
+            value = match segment {
+                Segment::Key(key) => value.get(key.as_str()),
+                Segment::Index(index) => value.get(*index),
+            }
+            .ok_or_else(|| {
+                error::with_path(
+                    error::new(ErrorImpl::Message(
+                        "value not found".to_owned(),
+                        None,
+                    )),
+                    &found,
+                )
+            })?;
+        }
+        Ok(value)
+    }
+
+    /// Deserializes the value at `path` below this one into `T`, such as a
+    /// `u16`, a `&str` or a struct.
+    ///
+    /// Returns an error naming `path` if the value is missing or does not
+    /// match `T`. Use [`Value::get_or`] for settings that have a default.
+    ///
+    /// ```
+    /// # use serde_yml::Value;
+    /// use serde_yml::path::Path;
+    ///
+    /// let value: Value = serde_yml::from_str("server:\n  port: http\n").unwrap();
+    /// let path: Path = "server.port".parse().unwrap();
+    /// let err = value.get_as::<u16>(&path).unwrap_err();
+    /// assert_eq!(err.to_string(), "server.port: invalid type: string \"http\", expected u16");
+    /// assert_eq!(value.get_as::<&str>(&path).unwrap(), "http");
+    /// ```
+    pub fn get_as<'de, T>(&'de self, path: &Path) -> Result<T, Error>
+    where
+        T: Deserialize<'de>,
+    {
+        let value = self.try_get_path(path)?;
+        T::deserialize(value).map_err(|err| error::with_path(err, path))
+    }
+
+    /// Deserializes the value at `path` below this one into `T` like
+    /// [`Value::get_as`], or returns `default` if there is no value at
+    /// `path`.
+    ///
+    /// A value that is present but does not match `T` is still an error,
+    /// so that a typo in a setting is not silently replaced by its default.
+    ///
+    /// ```
+    /// # fn main() -> serde_yml::Result<()> {
+    /// # use serde_yml::Value;
+    /// let value: Value = serde_yml::from_str("server:\n  host: a\n")?;
+    /// assert_eq!(value.get_or(&"server.port".parse()?, 8080u16)?, 8080);
+    /// assert_eq!(value.get_or(&"server.host".parse()?, "localhost")?, "a");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_or<'de, T>(
+        &'de self,
+        path: &Path,
+        default: T,
+    ) -> Result<T, Error>
+    where
+        T: Deserialize<'de>,
+    {
+        match self.get_path(path) {
+            Some(value) => T::deserialize(value)
+                .map_err(|err| error::with_path(err, path)),
+            None => Ok(default),
+        }
+    }
+
     /// Returns true if the `Value` is a Null. Returns false otherwise.
     ///
     /// For any Value on which `is_null` returns true, `as_null` is guaranteed
@@ -620,10 +837,82 @@ impl Value {
         }
     }
 
+    /// Returns the boolean if the `Value` is a Boolean, and an error
+    /// describing the value otherwise.
+    ///
+    /// ```
+    /// # use serde_yml::Value;
+    /// let v: Value = serde_yml::from_str("true").unwrap();
+    /// assert!(v.try_as_bool().unwrap());
+    ///
+    /// let v: Value = serde_yml::from_str("yes").unwrap();
+    /// let err = v.try_as_bool().unwrap_err();
+    /// assert_eq!(err.to_string(), "invalid type: string \"yes\", expected a boolean");
+    /// ```
+    pub fn try_as_bool(&self) -> Result<bool, Error> {
+        self.as_bool().ok_or_else(|| self.expected("a boolean"))
+    }
+
+    /// Returns the number as an `i64` if [`Value::as_i64`] would, and an
+    /// error describing the value otherwise.
+    pub fn try_as_i64(&self) -> Result<i64, Error> {
+        self.as_i64().ok_or_else(|| self.expected("i64"))
+    }
+
+    /// Returns the number as a `u64` if [`Value::as_u64`] would, and an
+    /// error describing the value otherwise.
+    pub fn try_as_u64(&self) -> Result<u64, Error> {
+        self.as_u64().ok_or_else(|| self.expected("u64"))
+    }
+
+    /// Returns the number as an `f64` if [`Value::as_f64`] would, and an
+    /// error describing the value otherwise.
+    pub fn try_as_f64(&self) -> Result<f64, Error> {
+        self.as_f64().ok_or_else(|| self.expected("f64"))
+    }
+
+    /// Returns the string if the `Value` is a String, and an error
+    /// describing the value otherwise.
+    ///
+    /// ```
+    /// # use serde_yml::Value;
+    /// let v: Value = serde_yml::from_str("[1, 2]").unwrap();
+    /// let err = v.try_as_str().unwrap_err();
+    /// assert_eq!(err.to_string(), "invalid type: sequence, expected a string");
+    /// ```
+    pub fn try_as_str(&self) -> Result<&str, Error> {
+        self.as_str().ok_or_else(|| self.expected("a string"))
+    }
+
+    /// Returns the sequence if the `Value` is a Sequence, and an error
+    /// describing the value otherwise.
+    pub fn try_as_sequence(&self) -> Result<&Sequence, Error> {
+        self.as_sequence()
+            .ok_or_else(|| self.expected("a sequence"))
+    }
+
+    /// Returns the mapping if the `Value` is a Mapping, and an error
+    /// describing the value otherwise.
+    pub fn try_as_mapping(&self) -> Result<&Mapping, Error> {
+        self.as_mapping().ok_or_else(|| self.expected("a mapping"))
+    }
+
+    /// Returns the error for a `try_as_*` getter expecting `expected`.
+    #[cold]
+    fn expected(&self, expected: &str) -> Error {
+        <Error as serde::de::Error>::invalid_type(
+            self.untag_ref().unexpected(),
+            &expected,
+        )
+    }
+
     /// Performs merging of `<<` keys into the surrounding mapping.
     ///
     /// The intended use of this in YAML is described in
-    /// <https://yaml.org/type/merge.html>.
+    /// <https://yaml.org/type/merge.html>. The `<<` value is a mapping or a
+    /// list of mappings. Keys of the mapping itself take precedence over
+    /// merged ones, and earlier mappings of a list over later ones. Merged
+    /// mappings may have `<<` keys of their own, which are resolved first.
     ///
     /// ```
     /// use serde_yml::Value;
@@ -652,7 +941,11 @@ impl Value {
         while let Some(node) = stack.pop() {
             match node {
                 Value::Mapping(mapping) => {
-                    match mapping.remove("<<") {
+                    let mut merge = mapping.remove("<<");
+                    if let Some(merge) = &mut merge {
+                        merge.apply_merge()?;
+                    }
+                    match merge {
                         Some(Value::Mapping(merge)) => {
                             for (k, v) in merge {
                                 mapping.entry(k).or_insert(v);
@@ -707,6 +1000,40 @@ impl Value {
 
 impl Eq for Value {}
 
+impl Ord for Value {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Value::Null, Value::Null) => Ordering::Equal,
+            (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+            (Value::Number(a), Value::Number(b)) => a.cmp(b),
+            (Value::String(a), Value::String(b)) => a.cmp(b),
+            (Value::Sequence(a), Value::Sequence(b)) => a.cmp(b),
+            (Value::Mapping(a), Value::Mapping(b)) => a.cmp(b),
+            (Value::Tagged(a), Value::Tagged(b)) => a.cmp(b),
+            _ => kind_rank(self).cmp(&kind_rank(other)),
+        }
+    }
+}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Position of the kind of a value in the order of [`Value`].
+fn kind_rank(value: &Value) -> u8 {
+    match value {
+        Value::Null => 0,
+        Value::Bool(_) => 1,
+        Value::Number(_) => 2,
+        Value::String(_) => 3,
+        Value::Sequence(_) => 4,
+        Value::Mapping(_) => 5,
+        Value::Tagged(_) => 6,
+    }
+}
+
 // NOTE: This impl must be kept consistent with HashLikeValue's Hash impl in
 // mapping.rs in order for value[str] indexing to work.
 impl Hash for Value {
@@ -724,6 +1051,66 @@ impl Hash for Value {
     }
 }
 
+/// Writes the value as YAML, as [`serde_yml::to_string`](crate::to_string)
+/// does but without the final newline, so that a value can be interpolated
+/// into a line of text. The newline is kept when the document ends with a
+/// string ending in a line break, whose block scalar would otherwise lose
+/// it when parsed back.
+///
+/// Values written on a single line, such as scalars, honour the width,
+/// fill and alignment of the formatter.
+///
+/// ```
+/// use serde_yml::Value;
+///
+/// let value: Value = "name: web\nports: [80, 443]".parse().unwrap();
+/// assert_eq!(value.to_string(), "name: web\nports:\n- 80\n- 443");
+/// assert_eq!(value.to_string().parse::<Value>().unwrap(), value);
+/// assert_eq!(format!("[{:>5}]", Value::from(42)), "[   42]");
+/// ```
+impl Display for Value {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let yaml = crate::to_string(self).map_err(|_| fmt::Error)?;
+        let yaml = if ends_with_line_break(self) {
+            &yaml[..]
+        } else {
+            yaml.strip_suffix('\n').unwrap_or(&yaml)
+        };
+        if yaml.contains('\n') {
+            formatter.write_str(yaml)
+        } else {
+            formatter.pad(yaml)
+        }
+    }
+}
+
+/// Returns whether the last scalar of `value` in document order is a
+/// string ending in a line break.
+fn ends_with_line_break(value: &Value) -> bool {
+    match value {
+        Value::String(string) => string.ends_with('\n'),
+        Value::Sequence(sequence) => {
+            sequence.last().map_or(false, ends_with_line_break)
+        }
+        Value::Mapping(mapping) => mapping
+            .iter()
+            .last()
+            .map_or(false, |(_, value)| ends_with_line_break(value)),
+        Value::Tagged(tagged) => ends_with_line_break(&tagged.value),
+        _ => false,
+    }
+}
+
+impl FromStr for Value {
+    type Err = Error;
+
+    /// Parses a single YAML document, as
+    /// [`serde_yml::from_str`](crate::from_str) does.
+    fn from_str(yaml: &str) -> Result<Self, Error> {
+        crate::from_str(yaml)
+    }
+}
+
 impl IntoDeserializer<'_, Error> for Value {
     type Deserializer = Self;
 
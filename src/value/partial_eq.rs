@@ -1,5 +1,5 @@
-use crate::partialeq_numeric;
 use crate::Value;
+use crate::{lib::*, partialeq_numeric};
 
 impl PartialEq<str> for Value {
     /// Compare `str` with YAML value
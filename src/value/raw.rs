@@ -0,0 +1,160 @@
+use crate::{lib::*, modules::error::Error, value::Value};
+use core::fmt::{self, Display};
+use serde::{
+    de::{self, Deserialize, Deserializer, IgnoredAny, Visitor},
+    ser::{Serialize, Serializer},
+};
+
+/// Name of the newtype struct through which [`RawYaml`] is recognized by
+/// the serializers and deserializers of this crate.
+pub(crate) const RAW_YAML: &str = "$serde_yml::private::RawYaml";
+
+/// A fragment of YAML text kept as written.
+///
+/// Deserializing a `RawYaml` from YAML text does not build any value:
+/// it captures the text of the node, such as the value of a mapping key, as
+/// it appears in the input, with its tag and anchor and without its
+/// indentation. An alias captures the text of the node it refers to. This lets a program
+/// read the parts of a document that it needs and pass the others on
+/// unchanged, similar to `serde_json::value::RawValue`.
+///
+/// Serializing a `RawYaml` with this crate writes the nodes of the fragment
+/// into the output, keeping every scalar's value, tag and style (plain,
+/// quoted, literal or folded). Comments and the layout of collections
+/// follow the surrounding output. Anchors are expanded, and an alias within
+/// the fragment must refer to an anchor within the same fragment.
+///
+/// Other data formats see a `RawYaml` as the string of YAML text. When it is
+/// deserialized from anything other than YAML text, such as a
+/// [`Value`], the data is written back as YAML.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use serde_yml::value::RawYaml;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Envelope {
+///     route: String,
+///     payload: RawYaml,
+/// }
+///
+/// let yaml = "route: billing\npayload:\n  id: '0042'\n  note: |\n    paid\n";
+/// let envelope: Envelope = serde_yml::from_str(yaml).unwrap();
+/// assert_eq!(envelope.payload.get(), "id: '0042'\nnote: |\n  paid\n");
+///
+/// let forwarded = serde_yml::to_string(&envelope).unwrap();
+/// assert_eq!(forwarded, yaml);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RawYaml {
+    yaml: String,
+}
+
+impl RawYaml {
+    /// Wraps a string of YAML text, checking that it is a single valid
+    /// document.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of [`from_str`](crate::from_str) if `yaml` is not
+    /// a single YAML document.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_yml::value::RawYaml;
+    ///
+    /// let raw = RawYaml::from_string("[1, 2]".to_owned()).unwrap();
+    /// assert_eq!(serde_yml::to_string(&vec![raw]).unwrap(), "- - 1\n  - 2\n");
+    ///
+    /// assert!(RawYaml::from_string("[1, 2".to_owned()).is_err());
+    /// ```
+    pub fn from_string(yaml: String) -> Result<Self, Error> {
+        let _: IgnoredAny = crate::from_str(&yaml)?;
+        Ok(RawYaml { yaml })
+    }
+
+    /// Returns the YAML text.
+    pub fn get(&self) -> &str {
+        &self.yaml
+    }
+
+    /// Returns the YAML text as an owned string.
+    pub fn into_string(self) -> String {
+        self.yaml
+    }
+
+    /// Deserializes the fragment into an instance of type `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`from_str`](crate::from_str).
+    pub fn deserialize_into<'de, T>(&'de self) -> Result<T, Error>
+    where
+        T: Deserialize<'de>,
+    {
+        crate::from_str(&self.yaml)
+    }
+}
+
+impl Display for RawYaml {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(&self.yaml)
+    }
+}
+
+impl Serialize for RawYaml {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer
+            .serialize_newtype_struct(RAW_YAML, self.yaml.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for RawYaml {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct RawYamlVisitor;
+
+        impl<'de> Visitor<'de> for RawYamlVisitor {
+            type Value = RawYaml;
+
+            fn expecting(
+                &self,
+                formatter: &mut fmt::Formatter<'_>,
+            ) -> fmt::Result {
+                formatter.write_str("any YAML node")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<RawYaml, E> {
+                Ok(RawYaml { yaml: v.to_owned() })
+            }
+
+            fn visit_string<E>(self, v: String) -> Result<RawYaml, E> {
+                Ok(RawYaml { yaml: v })
+            }
+
+            fn visit_newtype_struct<D>(
+                self,
+                deserializer: D,
+            ) -> Result<RawYaml, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                let value = Value::deserialize(deserializer)?;
+                let yaml = crate::to_string(&value)
+                    .map_err(de::Error::custom)?;
+                Ok(RawYaml { yaml })
+            }
+        }
+
+        deserializer
+            .deserialize_newtype_struct(RAW_YAML, RawYamlVisitor)
+    }
+}
@@ -1,13 +1,16 @@
-use crate::modules::error::{self, Error, ErrorImpl};
 use crate::value::tagged::{self, MaybeTag};
 use crate::value::{
-    to_value, Mapping, Number, Sequence, Tag, TaggedValue, Value,
+    Mapping, Number, Sequence, Tag, TaggedValue, Value, RAW_YAML,
 };
+use crate::{
+    lib::*,
+    modules::error::{self, Error, ErrorImpl},
+};
+use core::fmt::Display;
+use core::mem;
 use serde::ser::{self, Serialize};
-use std::fmt::Display;
-use std::mem;
 
-type Result<T, E = Error> = std::result::Result<T, E>;
+type Result<T, E = Error> = core::result::Result<T, E>;
 
 impl Serialize for Value {
     // Serializes a `Value` into the given serializer.
@@ -40,7 +43,8 @@ impl Serialize for Value {
 /// This is the serializer that backs [`serde_yml::to_value`][crate::to_value].
 /// Unlike the main serde_yml serializer which goes from some serializable
 /// value of type `T` to YAML text, this one goes from `T` to
-/// `serde_yml::Value`.
+/// `serde_yml::Value`. Its counterpart is
+/// [`value::Deserializer`](crate::value::Deserializer).
 ///
 /// The `to_value` function is implementable as:
 ///
@@ -52,11 +56,48 @@ impl Serialize for Value {
 /// where
 ///     T: Serialize,
 /// {
-///     input.serialize(serde_yml::value::Serializer)
+///     input.serialize(serde_yml::value::Serializer::new())
 /// }
 /// ```
-#[derive(Clone, Copy, Debug)]
-pub struct Serializer;
+///
+/// By default the serializer reports itself as not human-readable, so
+/// types such as `IpAddr` serialize in their compact form. Types that
+/// format themselves as text for human-readable formats do so after
+/// [`with_human_readable(true)`](Serializer::with_human_readable):
+///
+/// ```
+/// use serde::Serialize;
+/// use serde_yml::value::Serializer;
+/// use std::net::Ipv4Addr;
+///
+/// let addr = Ipv4Addr::LOCALHOST;
+/// let value = addr.serialize(Serializer::new().with_human_readable(true)).unwrap();
+/// assert_eq!(value, "127.0.0.1");
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Serializer {
+    human_readable: bool,
+}
+
+impl Serializer {
+    /// Creates a serializer with the default options.
+    pub const fn new() -> Self {
+        Serializer {
+            human_readable: false,
+        }
+    }
+
+    /// Sets whether the serializer reports itself as human-readable
+    /// through `Serializer::is_human_readable`, for this value and every
+    /// value nested in it. The default is `false`.
+    pub const fn with_human_readable(
+        mut self,
+        human_readable: bool,
+    ) -> Self {
+        self.human_readable = human_readable;
+        self
+    }
+}
 
 impl ser::Serializer for Serializer {
     type Ok = Value;
@@ -90,6 +131,12 @@ impl ser::Serializer for Serializer {
         Ok(Value::Number(Number::from(v)))
     }
 
+    #[cfg(feature = "bigint")]
+    fn serialize_i128(self, v: i128) -> Result<Value> {
+        Ok(Value::Number(Number::from(v)))
+    }
+
+    #[cfg(not(feature = "bigint"))]
     fn serialize_i128(self, v: i128) -> Result<Value> {
         if let Ok(v) = u64::try_from(v) {
             self.serialize_u64(v)
@@ -116,6 +163,12 @@ impl ser::Serializer for Serializer {
         Ok(Value::Number(Number::from(v)))
     }
 
+    #[cfg(feature = "bigint")]
+    fn serialize_u128(self, v: u128) -> Result<Value> {
+        Ok(Value::Number(Number::from(v)))
+    }
+
+    #[cfg(not(feature = "bigint"))]
     fn serialize_u128(self, v: u128) -> Result<Value> {
         if let Ok(v) = u64::try_from(v) {
             self.serialize_u64(v)
@@ -170,13 +223,18 @@ impl ser::Serializer for Serializer {
 
     fn serialize_newtype_struct<T>(
         self,
-        _name: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Value>
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(self)
+        match value.serialize(self)? {
+            Value::String(yaml) if name == RAW_YAML => {
+                crate::from_str(&yaml)
+            }
+            value => Ok(value),
+        }
     }
 
     fn serialize_newtype_variant<T>(
@@ -194,7 +252,7 @@ impl ser::Serializer for Serializer {
         }
         Ok(Value::Tagged(Box::new(TaggedValue {
             tag: Tag::new(variant),
-            value: to_value(value)?,
+            value: value.serialize(self)?,
         })))
     }
 
@@ -217,7 +275,10 @@ impl ser::Serializer for Serializer {
             None => Sequence::new(),
             Some(len) => Sequence::with_capacity(len),
         };
-        Ok(SerializeArray { sequence })
+        Ok(SerializeArray {
+            sequence,
+            ser: self,
+        })
     }
 
     fn serialize_tuple(self, len: usize) -> Result<SerializeArray> {
@@ -245,18 +306,20 @@ impl ser::Serializer for Serializer {
         Ok(SerializeTupleVariant {
             tag: variant,
             sequence: Sequence::with_capacity(len),
+            ser: self,
         })
     }
 
     fn serialize_map(self, len: Option<usize>) -> Result<SerializeMap> {
-        if len == Some(1) {
-            Ok(SerializeMap::CheckForTag)
+        let state = if len == Some(1) {
+            MapState::CheckForTag
         } else {
-            Ok(SerializeMap::Untagged {
+            MapState::Untagged {
                 mapping: Mapping::new(),
                 next_key: None,
-            })
-        }
+            }
+        };
+        Ok(SerializeMap { state, ser: self })
     }
 
     fn serialize_struct(
@@ -266,6 +329,7 @@ impl ser::Serializer for Serializer {
     ) -> Result<SerializeStruct> {
         Ok(SerializeStruct {
             mapping: Mapping::new(),
+            ser: self,
         })
     }
 
@@ -282,10 +346,12 @@ impl ser::Serializer for Serializer {
         Ok(SerializeStructVariant {
             tag: variant,
             mapping: Mapping::new(),
+            ser: self,
         })
     }
+
     fn is_human_readable(&self) -> bool {
-        false
+        self.human_readable
     }
 }
 
@@ -293,6 +359,7 @@ impl ser::Serializer for Serializer {
 #[derive(Debug)]
 pub struct SerializeArray {
     sequence: Sequence,
+    ser: Serializer,
 }
 
 impl ser::SerializeSeq for SerializeArray {
@@ -303,7 +370,7 @@ impl ser::SerializeSeq for SerializeArray {
     where
         T: ?Sized + Serialize,
     {
-        self.sequence.push(to_value(elem)?);
+        self.sequence.push(elem.serialize(self.ser)?);
         Ok(())
     }
 
@@ -349,6 +416,7 @@ impl ser::SerializeTupleStruct for SerializeArray {
 pub struct SerializeTupleVariant {
     tag: &'static str,
     sequence: Sequence,
+    ser: Serializer,
 }
 
 impl ser::SerializeTupleVariant for SerializeTupleVariant {
@@ -359,7 +427,7 @@ impl ser::SerializeTupleVariant for SerializeTupleVariant {
     where
         V: ?Sized + Serialize,
     {
-        self.sequence.push(to_value(v)?);
+        self.sequence.push(v.serialize(self.ser)?);
         Ok(())
     }
 
@@ -371,9 +439,16 @@ impl ser::SerializeTupleVariant for SerializeTupleVariant {
     }
 }
 
-/// An enum to handle the serialization of maps.
+/// A helper struct to serialize maps.
 #[derive(Debug)]
-pub enum SerializeMap {
+pub struct SerializeMap {
+    state: MapState,
+    ser: Serializer,
+}
+
+/// The progress of serializing a map.
+#[derive(Debug)]
+enum MapState {
     /// Represents a map with a single key-value pair, used to check for a tag.
     CheckForTag,
     /// Represents a tagged map, storing the tag and the associated value.
@@ -393,26 +468,26 @@ impl ser::SerializeMap for SerializeMap {
     where
         T: ?Sized + Serialize,
     {
-        let key = Some(to_value(key)?);
-        match self {
-            SerializeMap::CheckForTag => {
-                *self = SerializeMap::Untagged {
+        let key = Some(key.serialize(self.ser)?);
+        match &mut self.state {
+            MapState::CheckForTag => {
+                self.state = MapState::Untagged {
                     mapping: Mapping::new(),
                     next_key: key,
                 };
             }
-            SerializeMap::Tagged(tagged) => {
+            MapState::Tagged(tagged) => {
                 let mut mapping = Mapping::new();
                 mapping.insert(
                     Value::String(tagged.tag.to_string()),
                     mem::take(&mut tagged.value),
                 );
-                *self = SerializeMap::Untagged {
+                self.state = MapState::Untagged {
                     mapping,
                     next_key: key,
                 };
             }
-            SerializeMap::Untagged { next_key, .. } => *next_key = key,
+            MapState::Untagged { next_key, .. } => *next_key = key,
         }
         Ok(())
     }
@@ -421,16 +496,18 @@ impl ser::SerializeMap for SerializeMap {
     where
         T: ?Sized + Serialize,
     {
-        let (mapping, key) = match self {
-            SerializeMap::CheckForTag | SerializeMap::Tagged(_) => {
+        let (mapping, key) = match &mut self.state {
+            MapState::CheckForTag | MapState::Tagged(_) => {
                 unreachable!()
             }
-            SerializeMap::Untagged { mapping, next_key } => {
+            MapState::Untagged { mapping, next_key } => {
                 (mapping, next_key)
             }
         };
         match key.take() {
-            Some(key) => mapping.insert(key, to_value(value)?),
+            Some(key) => {
+                mapping.insert(key, value.serialize(self.ser)?)
+            }
             None => {
                 panic!("serialize_value called before serialize_key")
             }
@@ -448,7 +525,7 @@ impl ser::SerializeMap for SerializeMap {
         V: ?Sized + Serialize,
     {
         /// A helper struct to check for the presence of a tag in the serialized value.
-        struct CheckForTag;
+        struct CheckForTag(Serializer);
 
         /// A helper struct to wrap the serialized value when it is not a tag.
         struct NotTag<T> {
@@ -469,80 +546,78 @@ impl ser::SerializeMap for SerializeMap {
                 NotTag<SerializeStructVariant>;
 
             fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
-                Serializer.serialize_bool(v).map(MaybeTag::NotTag)
+                self.0.serialize_bool(v).map(MaybeTag::NotTag)
             }
 
             fn serialize_i8(self, v: i8) -> Result<Self::Ok> {
-                Serializer.serialize_i8(v).map(MaybeTag::NotTag)
+                self.0.serialize_i8(v).map(MaybeTag::NotTag)
             }
 
             fn serialize_i16(self, v: i16) -> Result<Self::Ok> {
-                Serializer.serialize_i16(v).map(MaybeTag::NotTag)
+                self.0.serialize_i16(v).map(MaybeTag::NotTag)
             }
 
             fn serialize_i32(self, v: i32) -> Result<Self::Ok> {
-                Serializer.serialize_i32(v).map(MaybeTag::NotTag)
+                self.0.serialize_i32(v).map(MaybeTag::NotTag)
             }
 
             fn serialize_i64(self, v: i64) -> Result<Self::Ok> {
-                Serializer.serialize_i64(v).map(MaybeTag::NotTag)
+                self.0.serialize_i64(v).map(MaybeTag::NotTag)
             }
 
             fn serialize_i128(self, v: i128) -> Result<Self::Ok> {
-                Serializer.serialize_i128(v).map(MaybeTag::NotTag)
+                self.0.serialize_i128(v).map(MaybeTag::NotTag)
             }
 
             fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
-                Serializer.serialize_u8(v).map(MaybeTag::NotTag)
+                self.0.serialize_u8(v).map(MaybeTag::NotTag)
             }
 
             fn serialize_u16(self, v: u16) -> Result<Self::Ok> {
-                Serializer.serialize_u16(v).map(MaybeTag::NotTag)
+                self.0.serialize_u16(v).map(MaybeTag::NotTag)
             }
 
             fn serialize_u32(self, v: u32) -> Result<Self::Ok> {
-                Serializer.serialize_u32(v).map(MaybeTag::NotTag)
+                self.0.serialize_u32(v).map(MaybeTag::NotTag)
             }
 
             fn serialize_u64(self, v: u64) -> Result<Self::Ok> {
-                Serializer.serialize_u64(v).map(MaybeTag::NotTag)
+                self.0.serialize_u64(v).map(MaybeTag::NotTag)
             }
 
             fn serialize_u128(self, v: u128) -> Result<Self::Ok> {
-                Serializer.serialize_u128(v).map(MaybeTag::NotTag)
+                self.0.serialize_u128(v).map(MaybeTag::NotTag)
             }
 
             fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
-                Serializer.serialize_f32(v).map(MaybeTag::NotTag)
+                self.0.serialize_f32(v).map(MaybeTag::NotTag)
             }
 
             fn serialize_f64(self, v: f64) -> Result<Self::Ok> {
-                Serializer.serialize_f64(v).map(MaybeTag::NotTag)
+                self.0.serialize_f64(v).map(MaybeTag::NotTag)
             }
 
             fn serialize_char(self, value: char) -> Result<Self::Ok> {
-                Serializer.serialize_char(value).map(MaybeTag::NotTag)
+                self.0.serialize_char(value).map(MaybeTag::NotTag)
             }
 
             fn serialize_str(self, value: &str) -> Result<Self::Ok> {
-                Serializer.serialize_str(value).map(MaybeTag::NotTag)
+                self.0.serialize_str(value).map(MaybeTag::NotTag)
             }
 
             fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok> {
-                Serializer.serialize_bytes(value).map(MaybeTag::NotTag)
+                self.0.serialize_bytes(value).map(MaybeTag::NotTag)
             }
 
             fn serialize_unit(self) -> Result<Self::Ok> {
-                Serializer.serialize_unit().map(MaybeTag::NotTag)
+                self.0.serialize_unit().map(MaybeTag::NotTag)
             }
 
             fn serialize_unit_struct(
                 self,
                 name: &'static str,
             ) -> Result<Self::Ok> {
-                Serializer
-                    .serialize_unit_struct(name)
-                    .map(MaybeTag::NotTag)
+                self.0.serialize_unit_struct(name).map(MaybeTag::NotTag)
             }
 
             fn serialize_unit_variant(
@@ -551,7 +626,7 @@ impl ser::SerializeMap for SerializeMap {
                 variant_index: u32,
                 variant: &'static str,
             ) -> Result<Self::Ok> {
-                Serializer
+                self.0
                     .serialize_unit_variant(
                         name,
                         variant_index,
@@ -568,7 +643,7 @@ impl ser::SerializeMap for SerializeMap {
             where
                 T: ?Sized + Serialize,
             {
-                Serializer
+                self.0
                     .serialize_newtype_struct(name, value)
                     .map(MaybeTag::NotTag)
             }
@@ -583,7 +658,7 @@ impl ser::SerializeMap for SerializeMap {
             where
                 T: ?Sized + Serialize,
             {
-                Serializer
+                self.0
                     .serialize_newtype_variant(
                         name,
                         variant_index,
@@ -594,14 +669,14 @@ impl ser::SerializeMap for SerializeMap {
             }
 
             fn serialize_none(self) -> Result<Self::Ok> {
-                Serializer.serialize_none().map(MaybeTag::NotTag)
+                self.0.serialize_none().map(MaybeTag::NotTag)
             }
 
             fn serialize_some<V>(self, value: &V) -> Result<Self::Ok>
             where
                 V: ?Sized + Serialize,
             {
-                Serializer.serialize_some(value).map(MaybeTag::NotTag)
+                self.0.serialize_some(value).map(MaybeTag::NotTag)
             }
 
             fn serialize_seq(
@@ -609,7 +684,7 @@ impl ser::SerializeMap for SerializeMap {
                 len: Option<usize>,
             ) -> Result<Self::SerializeSeq> {
                 Ok(NotTag {
-                    delegate: Serializer.serialize_seq(len)?,
+                    delegate: self.0.serialize_seq(len)?,
                 })
             }
 
@@ -618,7 +693,7 @@ impl ser::SerializeMap for SerializeMap {
                 len: usize,
             ) -> Result<Self::SerializeTuple> {
                 Ok(NotTag {
-                    delegate: Serializer.serialize_tuple(len)?,
+                    delegate: self.0.serialize_tuple(len)?,
                 })
             }
 
@@ -628,7 +703,8 @@ impl ser::SerializeMap for SerializeMap {
                 len: usize,
             ) -> Result<Self::SerializeTupleStruct> {
                 Ok(NotTag {
-                    delegate: Serializer
+                    delegate: self
+                        .0
                         .serialize_tuple_struct(name, len)?,
                 })
             }
@@ -641,7 +717,7 @@ impl ser::SerializeMap for SerializeMap {
                 len: usize,
             ) -> Result<Self::SerializeTupleVariant> {
                 Ok(NotTag {
-                    delegate: Serializer.serialize_tuple_variant(
+                    delegate: self.0.serialize_tuple_variant(
                         name,
                         variant_index,
                         variant,
@@ -655,7 +731,7 @@ impl ser::SerializeMap for SerializeMap {
                 len: Option<usize>,
             ) -> Result<Self::SerializeMap> {
                 Ok(NotTag {
-                    delegate: Serializer.serialize_map(len)?,
+                    delegate: self.0.serialize_map(len)?,
                 })
             }
 
@@ -665,7 +741,7 @@ impl ser::SerializeMap for SerializeMap {
                 len: usize,
             ) -> Result<Self::SerializeStruct> {
                 Ok(NotTag {
-                    delegate: Serializer.serialize_struct(name, len)?,
+                    delegate: self.0.serialize_struct(name, len)?,
                 })
             }
 
@@ -677,7 +753,7 @@ impl ser::SerializeMap for SerializeMap {
                 len: usize,
             ) -> Result<Self::SerializeStructVariant> {
                 Ok(NotTag {
-                    delegate: Serializer.serialize_struct_variant(
+                    delegate: self.0.serialize_struct_variant(
                         name,
                         variant_index,
                         variant,
@@ -838,52 +914,53 @@ impl ser::SerializeMap for SerializeMap {
             }
         }
 
-        match self {
-            SerializeMap::CheckForTag => {
-                let key = key.serialize(CheckForTag)?;
+        let ser = self.ser;
+        match &mut self.state {
+            MapState::CheckForTag => {
+                let key = key.serialize(CheckForTag(ser))?;
                 let mut mapping = Mapping::new();
-                *self = match key {
+                self.state = match key {
                     MaybeTag::Tag(string) => {
-                        SerializeMap::Tagged(TaggedValue {
+                        MapState::Tagged(TaggedValue {
                             tag: Tag::new(string),
-                            value: to_value(value)?,
+                            value: value.serialize(ser)?,
                         })
                     }
                     MaybeTag::NotTag(key) => {
-                        mapping.insert(key, to_value(value)?);
-                        SerializeMap::Untagged {
+                        mapping.insert(key, value.serialize(ser)?);
+                        MapState::Untagged {
                             mapping,
                             next_key: None,
                         }
                     }
                 };
             }
-            SerializeMap::Tagged(tagged) => {
+            MapState::Tagged(tagged) => {
                 let mut mapping = Mapping::new();
                 mapping.insert(
                     Value::String(tagged.tag.to_string()),
                     mem::take(&mut tagged.value),
                 );
-                mapping.insert(to_value(key)?, to_value(value)?);
-                *self = SerializeMap::Untagged {
+                mapping
+                    .insert(key.serialize(ser)?, value.serialize(ser)?);
+                self.state = MapState::Untagged {
                     mapping,
                     next_key: None,
                 };
             }
-            SerializeMap::Untagged { mapping, .. } => {
-                mapping.insert(to_value(key)?, to_value(value)?);
+            MapState::Untagged { mapping, .. } => {
+                mapping
+                    .insert(key.serialize(ser)?, value.serialize(ser)?);
             }
         }
         Ok(())
     }
 
     fn end(self) -> Result<Value> {
-        Ok(match self {
-            SerializeMap::CheckForTag => Value::Mapping(Mapping::new()),
-            SerializeMap::Tagged(tagged) => {
-                Value::Tagged(Box::new(tagged))
-            }
-            SerializeMap::Untagged { mapping, .. } => {
+        Ok(match self.state {
+            MapState::CheckForTag => Value::Mapping(Mapping::new()),
+            MapState::Tagged(tagged) => Value::Tagged(Box::new(tagged)),
+            MapState::Untagged { mapping, .. } => {
                 Value::Mapping(mapping)
             }
         })
@@ -894,6 +971,7 @@ impl ser::SerializeMap for SerializeMap {
 #[derive(Debug)]
 pub struct SerializeStruct {
     mapping: Mapping,
+    ser: Serializer,
 }
 
 impl ser::SerializeStruct for SerializeStruct {
@@ -908,7 +986,10 @@ impl ser::SerializeStruct for SerializeStruct {
     where
         V: ?Sized + Serialize,
     {
-        self.mapping.insert(to_value(key)?, to_value(value)?);
+        self.mapping.insert(
+            key.serialize(self.ser)?,
+            value.serialize(self.ser)?,
+        );
         Ok(())
     }
 
@@ -922,6 +1003,7 @@ impl ser::SerializeStruct for SerializeStruct {
 pub struct SerializeStructVariant {
     tag: &'static str,
     mapping: Mapping,
+    ser: Serializer,
 }
 
 impl ser::SerializeStructVariant for SerializeStructVariant {
@@ -936,7 +1018,8 @@ impl ser::SerializeStructVariant for SerializeStructVariant {
     where
         V: ?Sized + Serialize,
     {
-        self.mapping.insert(to_value(field)?, to_value(v)?);
+        self.mapping
+            .insert(field.serialize(self.ser)?, v.serialize(self.ser)?);
         Ok(())
     }
 
@@ -0,0 +1,539 @@
+use crate::io;
+use crate::{
+    de::{Event, Progress},
+    lib::*,
+    libyml::{
+        emitter::{self, Scalar},
+        parser::ScalarStyle,
+    },
+    loader::{Document, Loader},
+    mapping::Mapping,
+    modules::error::{self, ErrorImpl, Result},
+    number::Number,
+    path::Path,
+    ser::{infer_scalar_style, Serializer, State},
+    value::Value,
+};
+use alloc::collections::{btree_map, BTreeMap};
+use core::str::{self, FromStr};
+use serde::Serialize;
+
+/// A [`Value`] together with the style in which each of its scalars was
+/// written: plain, single-quoted, double-quoted, literal (`|`) or folded
+/// (`>`).
+///
+/// Serializing a [`Value`] picks a style for every string from its content,
+/// so reading a hand-maintained file and writing it back turns block
+/// scalars into quoted strings and quoted strings into plain ones. A
+/// `StyledValue` records the style of each scalar by its [`Path`] when it is
+/// parsed, and writes each string in that style again, unless the style
+/// would change how the string is read back, as a plain `"true"` would.
+/// Strings added or changed after parsing keep the style recorded for their
+/// path, or get the default style if there is none.
+///
+/// Integers written in hexadecimal, octal or binary, such as `0x1A` or
+/// `0b1010`, are written back in the same radix, with the same number of
+/// digits and case, as described by [`IntegerFormat`], so that files such
+/// as hardware register descriptions stay reviewable. This holds for
+/// integers changed after parsing too.
+///
+/// The styles of mapping keys are not recorded. Anchors and aliases are
+/// expanded as in [`Value`], and each copy of an aliased node keeps the
+/// styles of the original.
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::value::StyledValue;
+///
+/// let yaml = "script: |\n  make\n  make install\nname: \"demo\"\nversion: '1.0'\n";
+/// let mut styled: StyledValue = yaml.parse().unwrap();
+/// styled.value["name"] = "renamed".into();
+///
+/// assert_eq!(
+///     styled.to_yaml().unwrap(),
+///     "script: |\n  make\n  make install\nname: \"renamed\"\nversion: '1.0'\n",
+/// );
+/// assert_eq!(
+///     serde_yml::to_string(&styled.value).unwrap(),
+///     "script: |\n  make\n  make install\nname: renamed\nversion: '1.0'\n",
+/// );
+///
+/// let mut registers: StyledValue = "ctrl: 0x00FF\nmask: 0b1010\n".parse().unwrap();
+/// registers.value["ctrl"] = 0x1A.into();
+/// assert_eq!(registers.to_yaml().unwrap(), "ctrl: 0x001A\nmask: 0b1010\n");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StyledValue {
+    /// The value.
+    pub value: Value,
+    /// The style of each scalar, by the path of the scalar within
+    /// [`value`](StyledValue::value).
+    pub styles: StyleMap,
+}
+
+impl StyledValue {
+    /// Wraps a value without any recorded style.
+    pub fn new(value: Value) -> Self {
+        StyledValue {
+            value,
+            styles: StyleMap::new(),
+        }
+    }
+
+    /// Serializes the value as a string of YAML, writing every string in
+    /// its recorded style.
+    pub fn to_yaml(&self) -> Result<String> {
+        let mut vec = Vec::with_capacity(128);
+        self.to_writer(&mut vec)?;
+        String::from_utf8(vec)
+            .map_err(|error| error::new(ErrorImpl::FromUtf8(error)))
+    }
+
+    /// Serializes the value as YAML into the IO stream, writing every
+    /// string in its recorded style.
+    pub fn to_writer<W: io::Write>(&self, writer: W) -> Result<()> {
+        let mut serializer = Serializer::new(writer);
+        self.emit(&mut serializer, &self.value, &mut Path::root())
+    }
+
+    fn emit<W: io::Write>(
+        &self,
+        serializer: &mut Serializer<W>,
+        value: &Value,
+        path: &mut Path,
+    ) -> Result<()> {
+        match value {
+            Value::String(string) => {
+                let style = match self.styles.get(path) {
+                    Some(style) => emit_style(string, style),
+                    None => infer_scalar_style(string),
+                };
+                serializer.emit_scalar(Scalar {
+                    anchor: None,
+                    tag: None,
+                    value: string,
+                    style,
+                })
+            }
+            Value::Sequence(sequence) => {
+                serializer.emit_sequence_start()?;
+                for (index, element) in sequence.iter().enumerate() {
+                    path.push(index);
+                    let result = self.emit(serializer, element, path);
+                    let _ = path.pop();
+                    result?;
+                }
+                serializer.emit_sequence_end()
+            }
+            Value::Mapping(mapping) => {
+                self.emit_mapping(serializer, mapping, path)
+            }
+            Value::Tagged(tagged) => {
+                serializer.state =
+                    State::FoundTag(tagged.tag.to_string());
+                self.emit(serializer, &tagged.value, path)
+            }
+            Value::Number(number) => {
+                match self
+                    .styles
+                    .integer_format(path)
+                    .and_then(|format| format.format(number))
+                {
+                    Some(text) => serializer.emit_scalar(Scalar {
+                        anchor: None,
+                        tag: None,
+                        value: &text,
+                        style: emitter::ScalarStyle::Plain,
+                    }),
+                    None => value.serialize(serializer),
+                }
+            }
+            Value::Null | Value::Bool(_) => value.serialize(serializer),
+        }
+    }
+
+    fn emit_mapping<W: io::Write>(
+        &self,
+        serializer: &mut Serializer<W>,
+        mapping: &Mapping,
+        path: &mut Path,
+    ) -> Result<()> {
+        serializer.emit_mapping_start()?;
+        for (key, value) in mapping {
+            key.serialize(&mut *serializer)?;
+            match key_segment(key) {
+                Some(segment) => {
+                    path.push(segment);
+                    let result = self.emit(serializer, value, path);
+                    let _ = path.pop();
+                    result?;
+                }
+                None => value.serialize(&mut *serializer)?,
+            }
+        }
+        serializer.emit_mapping_end()
+    }
+}
+
+impl From<Value> for StyledValue {
+    fn from(value: Value) -> Self {
+        StyledValue::new(value)
+    }
+}
+
+impl FromStr for StyledValue {
+    type Err = crate::Error;
+
+    /// Parses a single YAML document, recording the style of each scalar.
+    fn from_str(yaml: &str) -> Result<Self> {
+        let value: Value = crate::from_str(yaml)?;
+        let mut loader = Loader::new(Progress::Str(yaml))?;
+        let styles = match loader.next_document() {
+            Some(document) => StyleMap::from_document(&document),
+            None => StyleMap::new(),
+        };
+        Ok(StyledValue { value, styles })
+    }
+}
+
+/// The style in which each scalar of a document was written, by the
+/// [`Path`] of the scalar.
+///
+/// Returned by [`from_str_with_styles`](crate::from_str_with_styles)
+/// alongside the deserialized value, so that policies about how values are
+/// written, such as that passwords must be quoted or that numbers must not
+/// be, can be checked without deserializing into a [`Value`]. Every scalar
+/// of the document is recorded, including those that the target type
+/// ignores. The styles of mapping keys are not recorded, and each copy of
+/// an aliased node has the styles of the original. The map also records
+/// the [`IntegerFormat`] of every integer written in hexadecimal, octal or
+/// binary.
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::{libyml::parser::ScalarStyle, path::Path};
+///
+/// let yaml = "password: hunter2\nport: '8080'\n";
+/// let (_, styles) = serde_yml::from_str_with_styles::<serde_yml::Value>(yaml).unwrap();
+///
+/// let password: Path = "password".parse().unwrap();
+/// assert_eq!(styles.get(&password), Some(ScalarStyle::Plain));
+/// assert!(!styles.is_quoted(&password));
+/// assert!(styles.is_quoted(&"port".parse().unwrap()));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StyleMap {
+    styles: BTreeMap<Path, ScalarStyle>,
+    integers: BTreeMap<Path, IntegerFormat>,
+}
+
+impl StyleMap {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        StyleMap::default()
+    }
+
+    /// Records the style of every scalar of a loaded document.
+    pub(crate) fn from_document(document: &Document<'_>) -> Self {
+        let mut styles = StyleMap::new();
+        if !document.events.is_empty() {
+            record(
+                document,
+                &mut 0,
+                Some(&mut Path::root()),
+                &mut styles,
+            );
+        }
+        styles
+    }
+
+    /// Returns the style of the scalar at `path`, or `None` if there is no
+    /// scalar at that path.
+    pub fn get(&self, path: &Path) -> Option<ScalarStyle> {
+        self.styles.get(path).copied()
+    }
+
+    /// Returns whether the scalar at `path` was single- or double-quoted.
+    pub fn is_quoted(&self, path: &Path) -> bool {
+        matches!(
+            self.get(path),
+            Some(ScalarStyle::SingleQuoted | ScalarStyle::DoubleQuoted)
+        )
+    }
+
+    /// Sets the style of the scalar at `path`, returning the previous one.
+    pub fn insert(
+        &mut self,
+        path: Path,
+        style: ScalarStyle,
+    ) -> Option<ScalarStyle> {
+        self.styles.insert(path, style)
+    }
+
+    /// Removes the style of the scalar at `path`, returning it.
+    pub fn remove(&mut self, path: &Path) -> Option<ScalarStyle> {
+        self.styles.remove(path)
+    }
+
+    /// Returns the number of recorded scalars.
+    pub fn len(&self) -> usize {
+        self.styles.len()
+    }
+
+    /// Returns whether no scalar is recorded.
+    pub fn is_empty(&self) -> bool {
+        self.styles.is_empty()
+    }
+
+    /// Iterates over the recorded paths and styles, ordered by path.
+    pub fn iter(&self) -> btree_map::Iter<'_, Path, ScalarStyle> {
+        self.styles.iter()
+    }
+
+    /// Returns the format of the integer at `path`, or `None` if there is
+    /// no integer written in hexadecimal, octal or binary at that path.
+    pub fn integer_format(&self, path: &Path) -> Option<IntegerFormat> {
+        self.integers.get(path).copied()
+    }
+
+    /// Sets the format of the integer at `path`, returning the previous
+    /// one.
+    pub fn insert_integer_format(
+        &mut self,
+        path: Path,
+        format: IntegerFormat,
+    ) -> Option<IntegerFormat> {
+        self.integers.insert(path, format)
+    }
+
+    /// Removes the format of the integer at `path`, returning it. The
+    /// integer is then written in decimal.
+    pub fn remove_integer_format(
+        &mut self,
+        path: &Path,
+    ) -> Option<IntegerFormat> {
+        self.integers.remove(path)
+    }
+}
+
+/// The radix of an integer written with a `0b`, `0o` or `0x` prefix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Radix {
+    /// Base 2, written as `0b1010`.
+    Binary,
+    /// Base 8, written as `0o755`.
+    Octal,
+    /// Base 16, written as `0x1A`.
+    Hexadecimal,
+}
+
+/// How an integer written in hexadecimal, octal or binary was formatted.
+///
+/// Recorded by [`StyleMap`] so that [`StyledValue`] writes the integer back
+/// the same way.
+///
+/// # Examples
+///
+/// ```
+/// use serde_yml::value::{IntegerFormat, Radix};
+/// use serde_yml::Number;
+///
+/// let format = IntegerFormat::parse("0x00FF").unwrap();
+/// assert_eq!(format.radix, Radix::Hexadecimal);
+/// assert_eq!(format.format(&Number::from(26)).unwrap(), "0x001A");
+/// assert_eq!(format.format(&Number::from(-0x12345)).unwrap(), "-0x12345");
+/// assert_eq!(IntegerFormat::parse("26"), None);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IntegerFormat {
+    /// The radix of the digits.
+    pub radix: Radix,
+    /// The number of digits written, counting leading zeros. Integers that
+    /// need more digits are written in full.
+    pub digits: usize,
+    /// Whether hexadecimal digits are written in upper case.
+    pub uppercase: bool,
+}
+
+impl IntegerFormat {
+    /// Returns the format of `text` if it is an integer written with a
+    /// `0b`, `0o` or `0x` prefix, or `None` otherwise.
+    pub fn parse(text: &str) -> Option<Self> {
+        let unsigned = text.strip_prefix(['-', '+']).unwrap_or(text);
+        let (radix, digits) =
+            if let Some(digits) = unsigned.strip_prefix("0b") {
+                (Radix::Binary, digits)
+            } else if let Some(digits) = unsigned.strip_prefix("0o") {
+                (Radix::Octal, digits)
+            } else if let Some(digits) = unsigned.strip_prefix("0x") {
+                (Radix::Hexadecimal, digits)
+            } else {
+                return None;
+            };
+        let base = match radix {
+            Radix::Binary => 2,
+            Radix::Octal => 8,
+            Radix::Hexadecimal => 16,
+        };
+        if digits.is_empty()
+            || !digits.chars().all(|digit| digit.is_digit(base))
+        {
+            return None;
+        }
+        Some(IntegerFormat {
+            radix,
+            digits: digits.len(),
+            uppercase: digits.bytes().any(|b| b.is_ascii_uppercase()),
+        })
+    }
+
+    /// Writes `number` in this format, or returns `None` if it is not an
+    /// integer within the range of `i64` and `u64`.
+    pub fn format(&self, number: &Number) -> Option<String> {
+        let (sign, magnitude) = match number.as_u64() {
+            Some(unsigned) => ("", unsigned),
+            None => ("-", number.as_i64()?.unsigned_abs()),
+        };
+        let width = self.digits;
+        Some(match self.radix {
+            Radix::Binary => {
+                format!("{}0b{:0width$b}", sign, magnitude)
+            }
+            Radix::Octal => format!("{}0o{:0width$o}", sign, magnitude),
+            Radix::Hexadecimal if self.uppercase => {
+                format!("{}0x{:0width$X}", sign, magnitude)
+            }
+            Radix::Hexadecimal => {
+                format!("{}0x{:0width$x}", sign, magnitude)
+            }
+        })
+    }
+}
+
+impl<'a> IntoIterator for &'a StyleMap {
+    type Item = (&'a Path, &'a ScalarStyle);
+    type IntoIter = btree_map::Iter<'a, Path, ScalarStyle>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Returns the emitter style for a string recorded with `style`, or the
+/// inferred style if writing it in `style` would change its meaning.
+fn emit_style(
+    string: &str,
+    style: ScalarStyle,
+) -> emitter::ScalarStyle {
+    let inferred = infer_scalar_style(string);
+    match style {
+        ScalarStyle::Plain => match inferred {
+            emitter::ScalarStyle::Any => emitter::ScalarStyle::Plain,
+            inferred => inferred,
+        },
+        ScalarStyle::SingleQuoted => emitter::ScalarStyle::SingleQuoted,
+        ScalarStyle::DoubleQuoted => emitter::ScalarStyle::DoubleQuoted,
+        ScalarStyle::Literal => emitter::ScalarStyle::Literal,
+        ScalarStyle::Folded => emitter::ScalarStyle::Folded,
+    }
+}
+
+/// Returns the path segment of a mapping entry, matching the text that
+/// [`record`] reads for its key.
+fn key_segment(key: &Value) -> Option<String> {
+    match key.untag_ref() {
+        Value::String(string) => Some(string.clone()),
+        Value::Bool(boolean) => Some(boolean.to_string()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Null | Value::Sequence(_) | Value::Mapping(_) => None,
+        Value::Tagged(_) => unreachable!(),
+    }
+}
+
+/// Records the style of every scalar of the node starting at `events[*pos]`,
+/// advancing `pos` past it. Scalars under a node without a path, such as a
+/// mapping key, are skipped.
+fn record(
+    document: &Document<'_>,
+    pos: &mut usize,
+    mut path: Option<&mut Path>,
+    styles: &mut StyleMap,
+) {
+    let events = &document.events;
+    let event = match events.get(*pos) {
+        Some((event, _)) => event,
+        None => return,
+    };
+    *pos += 1;
+    match event {
+        Event::Alias(target) => {
+            if let Some(&target) = document.anchor_event_map.get(target)
+            {
+                record(document, &mut { target }, path, styles);
+            }
+        }
+        Event::Scalar(scalar) => {
+            if let Some(path) = path {
+                if scalar.style == ScalarStyle::Plain
+                    && scalar.tag.is_none()
+                {
+                    let format = str::from_utf8(&scalar.value)
+                        .ok()
+                        .and_then(IntegerFormat::parse);
+                    if let Some(format) = format {
+                        let _ = styles.insert_integer_format(
+                            path.clone(),
+                            format,
+                        );
+                    }
+                }
+                let _ = styles.insert(path.clone(), scalar.style);
+            }
+        }
+        Event::SequenceStart(_) => {
+            let mut index = 0;
+            while let Some((event, _)) = events.get(*pos) {
+                if let Event::SequenceEnd = event {
+                    *pos += 1;
+                    break;
+                }
+                match path.as_deref_mut() {
+                    Some(path) => {
+                        path.push(index);
+                        record(document, pos, Some(path), styles);
+                        let _ = path.pop();
+                    }
+                    None => record(document, pos, None, styles),
+                }
+                index += 1;
+            }
+        }
+        Event::MappingStart(_) => {
+            while let Some((event, _)) = events.get(*pos) {
+                let key = match event {
+                    Event::MappingEnd => {
+                        *pos += 1;
+                        break;
+                    }
+                    Event::Scalar(scalar) => Some(
+                        String::from_utf8_lossy(&scalar.value)
+                            .into_owned(),
+                    ),
+                    _ => None,
+                };
+                record(document, pos, None, styles);
+                match (path.as_deref_mut(), key) {
+                    (Some(path), Some(key)) => {
+                        path.push(key);
+                        record(document, pos, Some(path), styles);
+                        let _ = path.pop();
+                    }
+                    _ => record(document, pos, None, styles),
+                }
+            }
+        }
+        Event::SequenceEnd | Event::MappingEnd | Event::Void => {}
+    }
+}
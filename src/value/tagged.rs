@@ -3,11 +3,16 @@
 // Copyright © 2024 Serde YML, Seamless YAML Serialization for Rust. All rights reserved.
 
 use crate::{
+    lib::*,
     modules::error::Error,
-    value::{
-        de::{MapDeserializer, SeqDeserializer},
-        Value,
-    },
+    value::{de::ValueDeserializer, Value},
+};
+use core::{
+    cmp::Ordering,
+    convert::TryFrom,
+    fmt::{self, Debug, Display},
+    hash::{Hash, Hasher},
+    str::from_utf8,
 };
 use serde::{
     de::{
@@ -17,13 +22,6 @@ use serde::{
     forward_to_deserialize_any,
     ser::{Serialize, SerializeMap, Serializer},
 };
-use std::{
-    cmp::Ordering,
-    convert::TryFrom,
-    fmt::{self, Debug, Display},
-    hash::{Hash, Hasher},
-    str::from_utf8,
-};
 
 /// A representation of YAML's `!Tag` syntax, used for enums.
 #[derive(Clone)]
@@ -33,7 +31,7 @@ pub struct Tag {
 }
 
 /// A `Tag` + `Value` representing a tagged YAML scalar, sequence, or mapping.
-#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub struct TaggedValue {
     /// The tag of the tagged value.
     pub tag: Tag,
@@ -49,6 +47,85 @@ impl TaggedValue {
             value: self.value.clone(),
         }
     }
+
+    /// Returns a builder that checks the tag and value of a new
+    /// `TaggedValue`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_yml::value::TaggedValue;
+    ///
+    /// let tagged = TaggedValue::builder().tag("!point").value(vec![1, 2]).build().unwrap();
+    /// assert_eq!(serde_yml::to_string(&tagged).unwrap(), "!point\n- 1\n- 2\n");
+    ///
+    /// let nested = serde_yml::Value::Tagged(Box::new(tagged));
+    /// let err = TaggedValue::builder().tag("!outer").value(nested).build().unwrap_err();
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "cannot tag a value already tagged `!point` with `!outer`; YAML allows only one tag per node",
+    /// );
+    /// ```
+    pub fn builder() -> TaggedValueBuilder {
+        TaggedValueBuilder::default()
+    }
+}
+
+/// A builder for [`TaggedValue`] that rejects values YAML cannot represent.
+///
+/// YAML allows a single tag per node, so a `TaggedValue` whose value is
+/// itself tagged cannot be serialized. The builder reports such chains, as
+/// well as missing and empty tags, as errors.
+#[derive(Clone, Debug, Default)]
+pub struct TaggedValueBuilder {
+    tag: Option<String>,
+    value: Value,
+}
+
+impl TaggedValueBuilder {
+    /// Sets the tag, with or without its leading `!`.
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    /// Sets the value to tag. Defaults to `null`.
+    pub fn value(mut self, value: impl Into<Value>) -> Self {
+        self.value = value.into();
+        self
+    }
+
+    /// Builds the `TaggedValue`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no tag was set, if the tag is empty, or if the
+    /// value is already tagged.
+    pub fn build(self) -> Result<TaggedValue, Error> {
+        let tag = match self.tag {
+            Some(tag) if tag.is_empty() => {
+                return Err(Error::custom(
+                    "empty YAML tag is not allowed",
+                ));
+            }
+            Some(tag) => Tag::new(tag),
+            None => {
+                return Err(Error::custom(
+                    "a tagged value requires a tag",
+                ))
+            }
+        };
+        if let Value::Tagged(inner) = &self.value {
+            return Err(Error::custom(format_args!(
+                "cannot tag a value already tagged `{}` with `{}`; YAML allows only one tag per node",
+                inner.tag, tag,
+            )));
+        }
+        Ok(TaggedValue {
+            tag,
+            value: self.value,
+        })
+    }
 }
 
 impl Tag {
@@ -278,56 +355,36 @@ impl<'de> VariantAccess<'de> for Value {
     type Error = Error;
 
     fn unit_variant(self) -> Result<(), Error> {
-        Deserialize::deserialize(self)
+        ValueDeserializer::new(self).unit_variant()
     }
 
     fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Error>
     where
         T: DeserializeSeed<'de>,
     {
-        seed.deserialize(self)
+        ValueDeserializer::new(self).newtype_variant_seed(seed)
     }
 
     fn tuple_variant<V>(
         self,
-        _len: usize,
+        len: usize,
         visitor: V,
     ) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        if let Value::Sequence(v) = self {
-            Deserializer::deserialize_any(
-                SeqDeserializer::new(v),
-                visitor,
-            )
-        } else {
-            Err(Error::invalid_type(
-                self.unexpected(),
-                &"tuple variant",
-            ))
-        }
+        ValueDeserializer::new(self).tuple_variant(len, visitor)
     }
 
     fn struct_variant<V>(
         self,
-        _fields: &'static [&'static str],
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        if let Value::Mapping(v) = self {
-            Deserializer::deserialize_any(
-                MapDeserializer::new(v),
-                visitor,
-            )
-        } else {
-            Err(Error::invalid_type(
-                self.unexpected(),
-                &"struct variant",
-            ))
-        }
+        ValueDeserializer::new(self).struct_variant(fields, visitor)
     }
 }
 
@@ -1,5 +1,18 @@
 //! Customizations to use with Serde's `#[serde(with = …)]` attribute.
 
+use crate::lib::*;
+use alloc::collections::BTreeMap;
+use core::{fmt, marker::PhantomData, str::FromStr};
+use serde::de::{
+    Deserialize, DeserializeOwned, Deserializer, MapAccess, Visitor,
+};
+use serde::ser::{Serialize, Serializer};
+#[cfg(feature = "std")]
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, Hash},
+};
+
 /// Serialize/deserialize an enum using a YAML map containing one entry in which
 /// the key identifies the variant name.
 ///
@@ -69,17 +82,79 @@
 /// z: !Struct
 ///   value: 1
 /// ```
+///
+/// The same representation is used when converting to and from a
+/// [`Value`](crate::Value):
+///
+/// ```
+/// # use serde::{Deserialize, Serialize};
+/// #
+/// # #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// # enum Enum {
+/// #     Newtype(usize),
+/// # }
+/// #
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Struct {
+///     #[serde(with = "serde_yml::with::singleton_map")]
+///     x: Enum,
+/// }
+///
+/// let object = Struct { x: Enum::Newtype(1) };
+/// let value = serde_yml::to_value(&object).unwrap();
+/// assert_eq!(value["x"]["Newtype"], 1);
+///
+/// let deserialized: Struct = serde_yml::from_value(value).unwrap();
+/// assert_eq!(object, deserialized);
+/// ```
+///
+/// Struct variants with `#[serde(flatten)]` fields, which Serde writes as a
+/// newtype variant holding a map rather than as a struct variant, get the
+/// same single entry, with the flattened fields inlined in its value:
+///
+/// ```
+/// # use serde::{Deserialize, Serialize};
+/// # use std::collections::BTreeMap;
+/// #
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// enum Shape {
+///     Circle {
+///         radius: u32,
+///         #[serde(flatten)]
+///         extra: BTreeMap<String, u32>,
+///     },
+/// }
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Struct {
+///     #[serde(with = "serde_yml::with::singleton_map")]
+///     shape: Shape,
+/// }
+///
+/// let extra = BTreeMap::from([("z".to_owned(), 2)]);
+/// let object = Struct {
+///     shape: Shape::Circle { radius: 1, extra },
+/// };
+/// let yaml = serde_yml::to_string(&object).unwrap();
+/// assert_eq!(yaml, "shape:\n  Circle:\n    radius: 1\n    z: 2\n");
+///
+/// let deserialized: Struct = serde_yml::from_str(&yaml).unwrap();
+/// assert_eq!(object, deserialized);
+/// ```
 pub mod singleton_map {
-    use crate::value::{Mapping, Sequence, Value};
+    use crate::{
+        content::{Content, ContentSerializer},
+        lib::*,
+    };
+    use core::fmt::{self, Display};
     use serde::de::{
         self, Deserialize, DeserializeSeed, Deserializer, EnumAccess,
         IgnoredAny, MapAccess, Unexpected, VariantAccess, Visitor,
     };
     use serde::ser::{
-        self, Serialize, SerializeMap, SerializeStructVariant,
+        Serialize, SerializeMap, SerializeStructVariant,
         SerializeTupleVariant, Serializer,
     };
-    use std::fmt::{self, Display};
 
     /// Serializes a given value using a singleton map representation.
     ///
@@ -370,10 +445,9 @@ pub mod singleton_map {
             variant: &'static str,
             len: usize,
         ) -> Result<Self::SerializeTupleVariant, Self::Error> {
-            let mut map = self.delegate.serialize_map(Some(1))?;
-            map.serialize_key(variant)?;
-            let sequence = Sequence::with_capacity(len);
-            Ok(SerializeTupleVariantAsSingletonMap { map, sequence })
+            Ok(SerializeTupleVariantAsSingletonMap {
+                fields: Fields::start(self.delegate, variant, len)?,
+            })
         }
 
         fn serialize_map(
@@ -398,10 +472,9 @@ pub mod singleton_map {
             variant: &'static str,
             len: usize,
         ) -> Result<Self::SerializeStructVariant, Self::Error> {
-            let mut map = self.delegate.serialize_map(Some(1))?;
-            map.serialize_key(variant)?;
-            let mapping = Mapping::with_capacity(len);
-            Ok(SerializeStructVariantAsSingletonMap { map, mapping })
+            Ok(SerializeStructVariantAsSingletonMap {
+                fields: Fields::start(self.delegate, variant, len)?,
+            })
         }
 
         fn collect_str<T>(
@@ -430,10 +503,96 @@ pub mod singleton_map {
     /// * `M` - The type of the underlying serializer map.
     #[derive(Clone, Debug)]
     pub struct SerializeTupleVariantAsSingletonMap<M> {
-        /// The underlying serializer map to which the tuple variant is serialized.
+        /// The underlying serializer map and the tuple elements.
+        fields: Fields<M, Content>,
+    }
+
+    /// The fields of a tuple or struct variant being serialized as a
+    /// singleton map.
+    ///
+    /// Serde hands the fields over one at a time, while the underlying
+    /// serializer takes the value of a map entry in a single call, so the
+    /// fields are copied as they come and written as that value at the
+    /// end. The copy keeps what each field wrote, bytes and integer
+    /// widths included, rather than converting it to a
+    /// [`Value`](crate::Value).
+    #[derive(Clone, Debug)]
+    pub(super) struct Fields<M, C> {
+        /// The underlying serializer map, with the variant name written as
+        /// its key.
         map: M,
-        /// The YAML sequence that holds the tuple elements.
-        sequence: Sequence,
+        /// The variant name, also written as the struct name of the
+        /// fields of a struct variant.
+        variant: &'static str,
+        /// The fields written so far.
+        pub(super) fields: Vec<C>,
+        /// Whether the underlying serializer is human-readable, which the
+        /// fields are told as they are copied.
+        human_readable: bool,
+    }
+
+    impl<M, C> Fields<M, C> {
+        /// Starts the singleton map of `variant` on `serializer`.
+        pub(super) fn start<S>(
+            serializer: S,
+            variant: &'static str,
+            len: usize,
+        ) -> Result<Self, S::Error>
+        where
+            S: Serializer<SerializeMap = M>,
+            M: SerializeMap<Ok = S::Ok, Error = S::Error>,
+        {
+            let human_readable = serializer.is_human_readable();
+            let mut map = serializer.serialize_map(Some(1))?;
+            map.serialize_key(variant)?;
+            Ok(Fields {
+                map,
+                variant,
+                fields: Vec::with_capacity(len),
+                human_readable,
+            })
+        }
+
+        /// Copies `value` as it would be written to the underlying
+        /// serializer.
+        pub(super) fn copy<T>(
+            &self,
+            value: &T,
+        ) -> Result<Content, M::Error>
+        where
+            T: ?Sized + Serialize,
+            M: SerializeMap,
+        {
+            value.serialize(ContentSerializer::new(self.human_readable))
+        }
+    }
+
+    impl<M> Fields<M, Content>
+    where
+        M: SerializeMap,
+    {
+        /// Writes the elements of a tuple variant as a sequence.
+        pub(super) fn end_tuple(mut self) -> Result<M::Ok, M::Error> {
+            self.map.serialize_value(&Content::Seq(self.fields))?;
+            self.map.end()
+        }
+    }
+
+    impl<M> Fields<M, (&'static str, Content)>
+    where
+        M: SerializeMap,
+    {
+        /// Writes the fields of a struct variant as a struct named after
+        /// the variant, so that they are treated like the fields of any
+        /// other struct, for instance left out when `None` under
+        /// [`SerializerOptions::skip_none_fields`](crate::ser::SerializerOptions::skip_none_fields).
+        pub(super) fn end_struct(mut self) -> Result<M::Ok, M::Error> {
+            self.map.serialize_value(&Content::Struct(
+                self.variant,
+                self.fields,
+            ))?;
+            self.map.end()
+        }
     }
 
     impl<M> SerializeTupleVariant for SerializeTupleVariantAsSingletonMap<M>
@@ -450,16 +609,13 @@ pub mod singleton_map {
         where
             T: ?Sized + Serialize,
         {
-            let value = field
-                .serialize(crate::value::Serializer)
-                .map_err(ser::Error::custom)?;
-            self.sequence.push(value);
+            let content = self.fields.copy(field)?;
+            self.fields.fields.push(content);
             Ok(())
         }
 
-        fn end(mut self) -> Result<Self::Ok, Self::Error> {
-            self.map.serialize_value(&self.sequence)?;
-            self.map.end()
+        fn end(self) -> Result<Self::Ok, Self::Error> {
+            self.fields.end_tuple()
         }
     }
 
@@ -474,10 +630,8 @@ pub mod singleton_map {
     /// * `M` - The type of the underlying serializer map.
     #[derive(Clone, Debug)]
     pub struct SerializeStructVariantAsSingletonMap<M> {
-        /// The underlying serializer map to which the struct variant is serialized.
-        map: M,
-        /// The YAML mapping that holds the struct fields.
-        mapping: Mapping,
+        /// The underlying serializer map and the struct fields.
+        fields: Fields<M, (&'static str, Content)>,
     }
 
     impl<M> SerializeStructVariant
@@ -496,16 +650,13 @@ pub mod singleton_map {
         where
             T: ?Sized + Serialize,
         {
-            let value = field
-                .serialize(crate::value::Serializer)
-                .map_err(ser::Error::custom)?;
-            self.mapping.insert(Value::String(name.to_owned()), value);
+            let content = self.fields.copy(field)?;
+            self.fields.fields.push((name, content));
             Ok(())
         }
 
-        fn end(mut self) -> Result<Self::Ok, Self::Error> {
-            self.map.serialize_value(&self.mapping)?;
-            self.map.end()
+        fn end(self) -> Result<Self::Ok, Self::Error> {
+            self.fields.end_struct()
         }
     }
 
@@ -1368,18 +1519,19 @@ pub mod singleton_map_with {
 /// }
 /// ```
 pub mod singleton_map_recursive {
-    use crate::value::{Mapping, Sequence, Value};
+    use super::singleton_map::Fields;
+    use crate::{content::Content, lib::*};
+    use core::fmt::{self, Display};
     use serde::de::{
         self, Deserialize, DeserializeSeed, Deserializer, EnumAccess,
         IgnoredAny, MapAccess, SeqAccess, Unexpected, VariantAccess,
         Visitor,
     };
     use serde::ser::{
-        self, Serialize, SerializeMap, SerializeSeq, SerializeStruct,
+        Serialize, SerializeMap, SerializeSeq, SerializeStruct,
         SerializeStructVariant, SerializeTuple, SerializeTupleStruct,
         SerializeTupleVariant, Serializer,
     };
-    use std::fmt::{self, Display};
 
     #[allow(missing_docs)]
     pub fn serialize<T, S>(
@@ -1664,12 +1816,8 @@ pub mod singleton_map_recursive {
             variant: &'static str,
             len: usize,
         ) -> Result<Self::SerializeTupleVariant, Self::Error> {
-            let mut map = self.delegate.serialize_map(Some(1))?;
-            map.serialize_key(variant)?;
-            let sequence = Sequence::with_capacity(len);
             Ok(SerializeTupleVariantAsSingletonMapRecursive {
-                map,
-                sequence,
+                fields: Fields::start(self.delegate, variant, len)?,
             })
         }
 
@@ -1699,12 +1847,8 @@ pub mod singleton_map_recursive {
             variant: &'static str,
             len: usize,
         ) -> Result<Self::SerializeStructVariant, Self::Error> {
-            let mut map = self.delegate.serialize_map(Some(1))?;
-            map.serialize_key(variant)?;
-            let mapping = Mapping::with_capacity(len);
             Ok(SerializeStructVariantAsSingletonMapRecursive {
-                map,
-                mapping,
+                fields: Fields::start(self.delegate, variant, len)?,
             })
         }
 
@@ -1796,8 +1940,7 @@ pub mod singleton_map_recursive {
     }
 
     struct SerializeTupleVariantAsSingletonMapRecursive<M> {
-        map: M,
-        sequence: Sequence,
+        fields: Fields<M, Content>,
     }
 
     impl<M> SerializeTupleVariant
@@ -1815,18 +1958,14 @@ pub mod singleton_map_recursive {
         where
             T: ?Sized + Serialize,
         {
-            let value = field
-                .serialize(SingletonMapRecursive {
-                    delegate: crate::value::Serializer,
-                })
-                .map_err(ser::Error::custom)?;
-            self.sequence.push(value);
+            let field = SingletonMapRecursive { delegate: field };
+            let content = self.fields.copy(&field)?;
+            self.fields.fields.push(content);
             Ok(())
         }
 
-        fn end(mut self) -> Result<Self::Ok, Self::Error> {
-            self.map.serialize_value(&self.sequence)?;
-            self.map.end()
+        fn end(self) -> Result<Self::Ok, Self::Error> {
+            self.fields.end_tuple()
         }
     }
 
@@ -1907,8 +2046,7 @@ pub mod singleton_map_recursive {
     }
 
     struct SerializeStructVariantAsSingletonMapRecursive<M> {
-        map: M,
-        mapping: Mapping,
+        fields: Fields<M, (&'static str, Content)>,
     }
 
     impl<M> SerializeStructVariant
@@ -1927,18 +2065,14 @@ pub mod singleton_map_recursive {
         where
             T: ?Sized + Serialize,
         {
-            let value = field
-                .serialize(SingletonMapRecursive {
-                    delegate: crate::value::Serializer,
-                })
-                .map_err(ser::Error::custom)?;
-            self.mapping.insert(Value::String(name.to_owned()), value);
+            let field = SingletonMapRecursive { delegate: field };
+            let content = self.fields.copy(&field)?;
+            self.fields.fields.push((name, content));
             Ok(())
         }
 
-        fn end(mut self) -> Result<Self::Ok, Self::Error> {
-            self.map.serialize_value(&self.mapping)?;
-            self.map.end()
+        fn end(self) -> Result<Self::Ok, Self::Error> {
+            self.fields.end_struct()
         }
     }
 
@@ -2864,6 +2998,90 @@ pub mod singleton_map_recursive {
     }
 }
 
+/// Serialize/deserialize an optional value using `singleton_map_recursive`
+/// for the enums it contains.
+///
+/// This is to `singleton_map_recursive` what `singleton_map_optional` is to
+/// `singleton_map`: `None` is written as `null`, and `Some` has every enum
+/// nested within it written as a YAML map with a single entry.
+///
+/// # Example
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// enum Backend {
+///     Memory,
+///     Disk { path: String },
+/// }
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// enum Cache {
+///     Enabled(Backend),
+///     Disabled,
+/// }
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Config {
+///     #[serde(default, with = "serde_yml::with::singleton_map_recursive_optional")]
+///     cache: Option<Cache>,
+/// }
+///
+/// let config = Config {
+///     cache: Some(Cache::Enabled(Backend::Disk {
+///         path: "/tmp".to_string(),
+///     })),
+/// };
+///
+/// let yaml = serde_yml::to_string(&config).unwrap();
+/// assert_eq!(yaml, "cache:\n  Enabled:\n    Disk:\n      path: /tmp\n");
+///
+/// let deserialized: Config = serde_yml::from_str(&yaml).unwrap();
+/// assert_eq!(config, deserialized);
+///
+/// let missing: Config = serde_yml::from_str("{}").unwrap();
+/// assert_eq!(missing.cache, None);
+/// ```
+pub mod singleton_map_recursive_optional {
+    use super::singleton_map_recursive;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Serializes an optional value using the `singleton_map_recursive`
+    /// representation.
+    ///
+    /// If the value is `None`, it will be serialized as `null`.
+    pub fn serialize<T, S>(
+        value: &Option<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        match value {
+            Some(v) => {
+                singleton_map_recursive::serialize(v, serializer)
+            }
+            None => serializer.serialize_none(),
+        }
+    }
+
+    /// Deserializes an optional value using the `singleton_map_recursive`
+    /// representation.
+    ///
+    /// A `null` input is deserialized as `None`.
+    pub fn deserialize<'de, T, D>(
+        deserializer: D,
+    ) -> Result<Option<T>, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        singleton_map_recursive::deserialize(deserializer)
+    }
+}
+
 /// Serialize/deserialize nested enums using a YAML map containing one entry in which
 /// the key identifies the variant name.
 ///
@@ -2957,3 +3175,3204 @@ pub mod nested_singleton_map {
         singleton_map_recursive::deserialize(deserializer)
     }
 }
+
+/// Serialize/deserialize a map keyed by an enum, writing each key in the
+/// `singleton_map` representation.
+///
+/// Unit variants become plain string keys and variants with data become a
+/// mapping with a single entry, instead of the tagged keys such as
+/// `? !Port 80` that other YAML tools may not understand. Works with
+/// `HashMap`, `BTreeMap` and any other map that can be iterated by reference
+/// and collected from its entries.
+///
+/// # Example
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use std::collections::BTreeMap;
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Debug)]
+/// enum Listener {
+///     Local,
+///     Port(u16),
+/// }
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Config {
+///     #[serde(with = "serde_yml::with::enum_keyed_map")]
+///     routes: BTreeMap<Listener, String>,
+/// }
+///
+/// let mut routes = BTreeMap::new();
+/// routes.insert(Listener::Local, "admin".to_string());
+/// routes.insert(Listener::Port(80), "web".to_string());
+/// let config = Config { routes };
+///
+/// let yaml = serde_yml::to_string(&config).unwrap();
+/// assert_eq!(yaml, "routes:\n  Local: admin\n  ? Port: 80\n  : web\n");
+///
+/// let deserialized: Config = serde_yml::from_str(&yaml).unwrap();
+/// assert_eq!(config, deserialized);
+/// ```
+pub mod enum_keyed_map {
+    use super::singleton_map;
+    use crate::lib::*;
+    use core::{fmt, marker::PhantomData};
+    use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
+    use serde::ser::{Serialize, Serializer};
+
+    /// Serializes a map, writing each key using the `singleton_map`
+    /// representation.
+    pub fn serialize<'a, M, K, V, S>(
+        map: &'a M,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+        K: Serialize + 'a,
+        V: Serialize + 'a,
+        S: Serializer,
+    {
+        serializer.collect_map(
+            map.into_iter()
+                .map(|(key, value)| (SingletonKey(key), value)),
+        )
+    }
+
+    /// Deserializes a map whose keys use the `singleton_map`
+    /// representation.
+    pub fn deserialize<'de, M, K, V, D>(
+        deserializer: D,
+    ) -> Result<M, D::Error>
+    where
+        M: FromIterator<(K, V)>,
+        K: Deserialize<'de>,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(EnumKeyedMapVisitor {
+            marker: PhantomData,
+        })
+    }
+
+    struct SingletonKey<K>(K);
+
+    impl<K> Serialize for SingletonKey<&K>
+    where
+        K: Serialize,
+    {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            singleton_map::serialize(self.0, serializer)
+        }
+    }
+
+    impl<'de, K> Deserialize<'de> for SingletonKey<K>
+    where
+        K: Deserialize<'de>,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            singleton_map::deserialize(deserializer).map(SingletonKey)
+        }
+    }
+
+    struct EnumKeyedMapVisitor<M, K, V> {
+        marker: PhantomData<(M, K, V)>,
+    }
+
+    impl<'de, M, K, V> Visitor<'de> for EnumKeyedMapVisitor<M, K, V>
+    where
+        M: FromIterator<(K, V)>,
+        K: Deserialize<'de>,
+        V: Deserialize<'de>,
+    {
+        type Value = M;
+
+        fn expecting(
+            &self,
+            formatter: &mut fmt::Formatter<'_>,
+        ) -> fmt::Result {
+            formatter.write_str("a map keyed by enum variants")
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<M, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            let mut entries = Vec::with_capacity(
+                map.size_hint().unwrap_or(0).min(4096),
+            );
+            while let Some((SingletonKey(key), value)) =
+                map.next_entry::<SingletonKey<K>, V>()?
+            {
+                entries.push((key, value));
+            }
+            Ok(entries.into_iter().collect())
+        }
+    }
+}
+
+/// Serialize/deserialize an enum as a mapping holding the variant name under
+/// a `type` key, next to the fields of the variant.
+///
+/// This is the representation of Serde's internally tagged enums,
+/// `#[serde(tag = "type")]`, which many YAML formats use to tell documents
+/// apart. Unlike the Serde attribute it also supports variants whose data is
+/// not a mapping: newtype variants holding a scalar or a sequence, and
+/// tuple variants, keep their data under a `value` key. Enums written in
+/// the default YAML representation, such as `!Variant {…}`, are accepted
+/// too when deserializing.
+///
+/// Use [`flatten_tagged!`](crate::flatten_tagged) to generate an equivalent
+/// module with other keys, such as `kind`, and
+/// [`flatten_tagged_recursive`] to apply the representation to every enum
+/// within a data structure.
+///
+/// # Example
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// enum Shape {
+///     Circle { radius: u32 },
+///     Polygon(Vec<(i32, i32)>),
+///     Empty,
+/// }
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Drawing {
+///     #[serde(with = "serde_yml::with::flatten_tagged")]
+///     a: Shape,
+///     #[serde(with = "serde_yml::with::flatten_tagged")]
+///     b: Shape,
+///     #[serde(with = "serde_yml::with::flatten_tagged")]
+///     c: Shape,
+/// }
+///
+/// let drawing = Drawing {
+///     a: Shape::Circle { radius: 2 },
+///     b: Shape::Polygon(vec![(0, 0), (1, 1), (1, 0)]),
+///     c: Shape::Empty,
+/// };
+///
+/// let yaml = serde_yml::to_string(&drawing).unwrap();
+/// assert_eq!(
+///     yaml,
+///     "\
+/// a:
+///   type: Circle
+///   radius: 2
+/// b:
+///   type: Polygon
+///   value:
+///   - - 0
+///     - 0
+///   - - 1
+///     - 1
+///   - - 1
+///     - 0
+/// c:
+///   type: Empty
+/// ",
+/// );
+///
+/// let deserialized: Drawing = serde_yml::from_str(&yaml).unwrap();
+/// assert_eq!(drawing, deserialized);
+/// ```
+pub mod flatten_tagged {
+    use crate::{
+        lib::*,
+        modules::error::Error,
+        value::{tagged::nobang, Mapping, TaggedValue, Value},
+    };
+    use serde::de::{
+        self,
+        value::{MapDeserializer, SeqDeserializer},
+        Deserialize, DeserializeOwned, DeserializeSeed, Deserializer,
+        EnumAccess, IntoDeserializer, Unexpected, VariantAccess,
+        Visitor,
+    };
+    use serde::ser::{self, Serialize, Serializer};
+
+    /// The keys used by the flattened representation.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Keys {
+        /// The key holding the variant name.
+        pub tag: &'static str,
+        /// The key holding the data of newtype variants whose data is not
+        /// a mapping, and of tuple variants.
+        pub content: &'static str,
+    }
+
+    impl Default for Keys {
+        fn default() -> Self {
+            Keys {
+                tag: "type",
+                content: "value",
+            }
+        }
+    }
+
+    /// Serializes an enum using the `type` and `value` keys.
+    pub fn serialize<T, S>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        serialize_with_keys(value, serializer, Keys::default())
+    }
+
+    /// Deserializes an enum using the `type` and `value` keys.
+    pub fn deserialize<'de, T, D>(
+        deserializer: D,
+    ) -> Result<T, D::Error>
+    where
+        T: DeserializeOwned,
+        D: Deserializer<'de>,
+    {
+        deserialize_with_keys(deserializer, Keys::default())
+    }
+
+    /// Serializes an enum using the given keys.
+    ///
+    /// This is the building block used by
+    /// [`flatten_tagged!`](crate::flatten_tagged).
+    pub fn serialize_with_keys<T, S>(
+        value: &T,
+        serializer: S,
+        keys: Keys,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        to_flattened(value, keys, false)
+            .map_err(ser::Error::custom)?
+            .serialize(serializer)
+    }
+
+    /// Deserializes an enum using the given keys.
+    ///
+    /// This is the building block used by
+    /// [`flatten_tagged!`](crate::flatten_tagged).
+    pub fn deserialize_with_keys<'de, T, D>(
+        deserializer: D,
+        keys: Keys,
+    ) -> Result<T, D::Error>
+    where
+        T: DeserializeOwned,
+        D: Deserializer<'de>,
+    {
+        from_flattened(Value::deserialize(deserializer)?, keys, false)
+            .map_err(de::Error::custom)
+    }
+
+    pub(super) fn to_flattened<T>(
+        value: &T,
+        keys: Keys,
+        recursive: bool,
+    ) -> Result<Value, Error>
+    where
+        T: Serialize,
+    {
+        match crate::to_value(value)? {
+            // At the top level a string can only be a unit variant.
+            Value::String(variant) => {
+                Ok(Value::Mapping(tagged_mapping(keys, variant)))
+            }
+            value => flatten(value, keys, recursive),
+        }
+    }
+
+    pub(super) fn from_flattened<T>(
+        value: Value,
+        keys: Keys,
+        recursive: bool,
+    ) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        T::deserialize(Flattened {
+            value,
+            keys: Some(keys),
+            recursive,
+        })
+    }
+
+    fn tagged_mapping(keys: Keys, variant: String) -> Mapping {
+        let mut mapping = Mapping::new();
+        let _ =
+            mapping.insert(Value::from(keys.tag), Value::from(variant));
+        mapping
+    }
+
+    /// Rewrites the enums of a `Value`, which are tagged values, into the
+    /// flattened representation. Nested enums are rewritten only if
+    /// `recursive` is set.
+    fn flatten(
+        value: Value,
+        keys: Keys,
+        recursive: bool,
+    ) -> Result<Value, Error> {
+        let nested = |value| {
+            if recursive {
+                flatten(value, keys, recursive)
+            } else {
+                Ok(value)
+            }
+        };
+        match value {
+            Value::Tagged(tagged) => {
+                let TaggedValue { tag, value } = *tagged;
+                let variant = nobang(&tag.string).to_owned();
+                let mut flattened = tagged_mapping(keys, variant);
+                // Only mappings are flattened; the data of other variants,
+                // including enums, is kept under the content key.
+                match value {
+                    Value::Mapping(fields) => {
+                        for (key, value) in fields {
+                            let value = nested(value)?;
+                            if key == keys.tag {
+                                return Err(de::Error::custom(
+                                    format_args!(
+                                        "variant `{}` has a field named `{}`, which holds the variant name",
+                                        nobang(&tag.string),
+                                        keys.tag,
+                                    ),
+                                ));
+                            }
+                            let _ = flattened.insert(key, value);
+                        }
+                    }
+                    value => {
+                        let _ = flattened.insert(
+                            Value::from(keys.content),
+                            nested(value)?,
+                        );
+                    }
+                }
+                Ok(Value::Mapping(flattened))
+            }
+            Value::Sequence(sequence) if recursive => {
+                Ok(Value::Sequence(
+                    sequence
+                        .into_iter()
+                        .map(nested)
+                        .collect::<Result<_, _>>()?,
+                ))
+            }
+            Value::Mapping(mapping) if recursive => Ok(Value::Mapping(
+                mapping
+                    .into_iter()
+                    .map(|(key, value)| {
+                        Ok((nested(key)?, nested(value)?))
+                    })
+                    .collect::<Result<_, Error>>()?,
+            )),
+            value => Ok(value),
+        }
+    }
+
+    /// Deserializes from a `Value` in which enums may be in the flattened
+    /// representation. With `keys` unset it behaves exactly like the value.
+    struct Flattened {
+        value: Value,
+        keys: Option<Keys>,
+        recursive: bool,
+    }
+
+    impl Flattened {
+        fn nested(&self, value: Value) -> Flattened {
+            Flattened {
+                value,
+                keys: if self.recursive { self.keys } else { None },
+                recursive: self.recursive,
+            }
+        }
+    }
+
+    impl IntoDeserializer<'_, Error> for Flattened {
+        type Deserializer = Self;
+
+        fn into_deserializer(self) -> Self {
+            self
+        }
+    }
+
+    macro_rules! delegate {
+        ($($method:ident)*) => {
+            $(
+                fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
+                where
+                    V: Visitor<'de>,
+                {
+                    self.value.$method(visitor)
+                }
+            )*
+        };
+    }
+
+    impl<'de> Deserializer<'de> for Flattened {
+        type Error = Error;
+
+        fn deserialize_any<V>(
+            self,
+            visitor: V,
+        ) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            if self.keys.is_none() {
+                return self.value.deserialize_any(visitor);
+            }
+            match self.value {
+                Value::Sequence(sequence) => {
+                    let recursive = Flattened {
+                        value: Value::Null,
+                        ..self
+                    };
+                    let mut seq = SeqDeserializer::new(
+                        sequence
+                            .into_iter()
+                            .map(|value| recursive.nested(value)),
+                    );
+                    let value = visitor.visit_seq(&mut seq)?;
+                    seq.end()?;
+                    Ok(value)
+                }
+                Value::Mapping(mapping) => {
+                    let recursive = Flattened {
+                        value: Value::Null,
+                        ..self
+                    };
+                    let mut map = MapDeserializer::new(
+                        mapping.into_iter().map(|(key, value)| {
+                            (
+                                recursive.nested(key),
+                                recursive.nested(value),
+                            )
+                        }),
+                    );
+                    let value = visitor.visit_map(&mut map)?;
+                    map.end()?;
+                    Ok(value)
+                }
+                Value::Tagged(_) => {
+                    self.deserialize_enum("", &[], visitor)
+                }
+                value => value.deserialize_any(visitor),
+            }
+        }
+
+        delegate! {
+            deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32
+            deserialize_i64 deserialize_i128 deserialize_u8 deserialize_u16
+            deserialize_u32 deserialize_u64 deserialize_u128 deserialize_f32
+            deserialize_f64 deserialize_char deserialize_str
+            deserialize_string deserialize_bytes deserialize_byte_buf
+            deserialize_unit deserialize_identifier deserialize_ignored_any
+        }
+
+        fn deserialize_option<V>(
+            self,
+            visitor: V,
+        ) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            match (&self.keys, &self.value) {
+                (None, _) => self.value.deserialize_option(visitor),
+                (Some(_), Value::Null) => visitor.visit_none(),
+                (Some(_), _) => visitor.visit_some(self),
+            }
+        }
+
+        fn deserialize_unit_struct<V>(
+            self,
+            name: &'static str,
+            visitor: V,
+        ) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            self.value.deserialize_unit_struct(name, visitor)
+        }
+
+        fn deserialize_newtype_struct<V>(
+            self,
+            name: &'static str,
+            visitor: V,
+        ) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            if self.keys.is_none() {
+                return self
+                    .value
+                    .deserialize_newtype_struct(name, visitor);
+            }
+            visitor.visit_newtype_struct(self)
+        }
+
+        fn deserialize_seq<V>(
+            self,
+            visitor: V,
+        ) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            match (&self.keys, &self.value) {
+                (Some(_), Value::Sequence(_)) => {
+                    self.deserialize_any(visitor)
+                }
+                _ => self.value.deserialize_seq(visitor),
+            }
+        }
+
+        fn deserialize_tuple<V>(
+            self,
+            _len: usize,
+            visitor: V,
+        ) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            self.deserialize_seq(visitor)
+        }
+
+        fn deserialize_tuple_struct<V>(
+            self,
+            _name: &'static str,
+            _len: usize,
+            visitor: V,
+        ) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            self.deserialize_seq(visitor)
+        }
+
+        fn deserialize_map<V>(
+            self,
+            visitor: V,
+        ) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            match (&self.keys, &self.value) {
+                (Some(_), Value::Mapping(_)) => {
+                    self.deserialize_any(visitor)
+                }
+                _ => self.value.deserialize_map(visitor),
+            }
+        }
+
+        fn deserialize_struct<V>(
+            self,
+            name: &'static str,
+            fields: &'static [&'static str],
+            visitor: V,
+        ) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            match (&self.keys, &self.value) {
+                (Some(_), Value::Mapping(_)) => {
+                    self.deserialize_any(visitor)
+                }
+                _ => {
+                    self.value.deserialize_struct(name, fields, visitor)
+                }
+            }
+        }
+
+        fn deserialize_enum<V>(
+            self,
+            name: &'static str,
+            variants: &'static [&'static str],
+            visitor: V,
+        ) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            let keys = match self.keys {
+                Some(keys) => keys,
+                None => {
+                    return self
+                        .value
+                        .deserialize_enum(name, variants, visitor)
+                }
+            };
+            let (variant, payload) = match self.value {
+                Value::Mapping(mut mapping) => {
+                    match mapping.shift_remove(keys.tag) {
+                        Some(Value::String(variant)) => {
+                            (variant, Payload::Fields(mapping))
+                        }
+                        Some(other) => {
+                            return Err(de::Error::invalid_type(
+                                other.unexpected(),
+                                &"a variant name",
+                            ))
+                        }
+                        None => {
+                            return Err(de::Error::missing_field(
+                                keys.tag,
+                            ))
+                        }
+                    }
+                }
+                Value::Tagged(tagged) => (
+                    nobang(&tagged.tag.string).to_owned(),
+                    Payload::Value(tagged.value),
+                ),
+                Value::String(variant) => (variant, Payload::Unit),
+                other => {
+                    return Err(de::Error::invalid_type(
+                        other.unexpected(),
+                        &"a map holding the variant name",
+                    ))
+                }
+            };
+            visitor.visit_enum(EnumDeserializer {
+                variant,
+                payload: VariantDeserializer {
+                    payload,
+                    keys,
+                    recursive: self.recursive,
+                },
+            })
+        }
+    }
+
+    /// The data of a variant.
+    enum Payload {
+        /// The fields next to the tag key of a flattened variant.
+        Fields(Mapping),
+        /// The value of a tagged value.
+        Value(Value),
+        /// A unit variant written as a string.
+        Unit,
+    }
+
+    struct EnumDeserializer {
+        variant: String,
+        payload: VariantDeserializer,
+    }
+
+    impl<'de> EnumAccess<'de> for EnumDeserializer {
+        type Error = Error;
+        type Variant = VariantDeserializer;
+
+        fn variant_seed<V>(
+            self,
+            seed: V,
+        ) -> Result<(V::Value, VariantDeserializer), Error>
+        where
+            V: DeserializeSeed<'de>,
+        {
+            let variant = seed.deserialize(
+                IntoDeserializer::<Error>::into_deserializer(
+                    self.variant,
+                ),
+            )?;
+            Ok((variant, self.payload))
+        }
+    }
+
+    struct VariantDeserializer {
+        payload: Payload,
+        keys: Keys,
+        recursive: bool,
+    }
+
+    impl VariantDeserializer {
+        fn split(self) -> (Payload, impl Fn(Value) -> Flattened) {
+            let VariantDeserializer {
+                payload,
+                keys,
+                recursive,
+            } = self;
+            let nested = move |value| Flattened {
+                value,
+                keys: if recursive { Some(keys) } else { None },
+                recursive,
+            };
+            (payload, nested)
+        }
+
+        /// Returns the data of a newtype or tuple variant.
+        fn content(self) -> Result<Flattened, Error> {
+            let content_key = self.keys.content;
+            let (payload, nested) = self.split();
+            let value = match payload {
+                Payload::Fields(mut fields) => {
+                    match fields.shift_remove(content_key) {
+                        Some(value) if fields.is_empty() => value,
+                        _ => {
+                            return Err(de::Error::missing_field(
+                                content_key,
+                            ))
+                        }
+                    }
+                }
+                Payload::Value(value) => value,
+                Payload::Unit => {
+                    return Err(de::Error::invalid_type(
+                        Unexpected::UnitVariant,
+                        &"a variant with data",
+                    ))
+                }
+            };
+            Ok(nested(value))
+        }
+    }
+
+    impl<'de> VariantAccess<'de> for VariantDeserializer {
+        type Error = Error;
+
+        fn unit_variant(self) -> Result<(), Error> {
+            match self.payload {
+                Payload::Unit | Payload::Value(Value::Null) => Ok(()),
+                Payload::Fields(fields) if fields.is_empty() => Ok(()),
+                Payload::Fields(_) => Err(de::Error::invalid_type(
+                    Unexpected::Map,
+                    &"a unit variant",
+                )),
+                Payload::Value(value) => Err(de::Error::invalid_type(
+                    value.unexpected(),
+                    &"a unit variant",
+                )),
+            }
+        }
+
+        fn newtype_variant_seed<T>(
+            self,
+            seed: T,
+        ) -> Result<T::Value, Error>
+        where
+            T: DeserializeSeed<'de>,
+        {
+            let content_key = self.keys.content;
+            match self.payload {
+                // A newtype variant holding a struct is flattened like a
+                // struct variant.
+                Payload::Fields(ref fields)
+                    if !(fields.len() == 1
+                        && fields.contains_key(content_key)) =>
+                {
+                    let (payload, nested) = self.split();
+                    match payload {
+                        Payload::Fields(fields) => seed.deserialize(
+                            nested(Value::Mapping(fields)),
+                        ),
+                        _ => unreachable!(),
+                    }
+                }
+                _ => seed.deserialize(self.content()?),
+            }
+        }
+
+        fn tuple_variant<V>(
+            self,
+            _len: usize,
+            visitor: V,
+        ) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            self.content()?.deserialize_seq(visitor)
+        }
+
+        fn struct_variant<V>(
+            self,
+            _fields: &'static [&'static str],
+            visitor: V,
+        ) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            let (payload, nested) = self.split();
+            let value = match payload {
+                Payload::Fields(fields) => Value::Mapping(fields),
+                Payload::Value(value) => value,
+                Payload::Unit => {
+                    return Err(de::Error::invalid_type(
+                        Unexpected::UnitVariant,
+                        &"a struct variant",
+                    ))
+                }
+            };
+            nested(value).deserialize_map(visitor)
+        }
+    }
+}
+
+/// Apply [`flatten_tagged`] to every enum within a data structure.
+///
+/// Unit variants below the top level are written as plain strings, as they
+/// cannot be told apart from strings once serialized; both that form and
+/// the flattened one are accepted when deserializing.
+///
+/// # Example
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// enum Volume {
+///     HostPath { path: String },
+///     Secret { name: String },
+/// }
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Pod {
+///     name: String,
+///     volumes: Vec<Volume>,
+/// }
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// enum Resource {
+///     Pod(Pod),
+///     Namespace { name: String },
+/// }
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Manifest {
+///     #[serde(with = "serde_yml::with::flatten_tagged_recursive")]
+///     resources: Vec<Resource>,
+/// }
+///
+/// let yaml = "\
+/// resources:
+/// - type: Namespace
+///   name: web
+/// - type: Pod
+///   name: nginx
+///   volumes:
+///   - type: Secret
+///     name: tls
+/// ";
+/// let manifest: Manifest = serde_yml::from_str(yaml).unwrap();
+/// assert_eq!(
+///     manifest.resources[1],
+///     Resource::Pod(Pod {
+///         name: "nginx".to_owned(),
+///         volumes: vec![Volume::Secret { name: "tls".to_owned() }],
+///     }),
+/// );
+/// assert_eq!(serde_yml::to_string(&manifest).unwrap(), yaml);
+/// ```
+pub mod flatten_tagged_recursive {
+    use super::flatten_tagged::{from_flattened, to_flattened, Keys};
+    use serde::de::{
+        self, Deserialize, DeserializeOwned, Deserializer,
+    };
+    use serde::ser::{self, Serialize, Serializer};
+
+    /// Serializes every enum using the `type` and `value` keys.
+    pub fn serialize<T, S>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        serialize_with_keys(value, serializer, Keys::default())
+    }
+
+    /// Deserializes every enum using the `type` and `value` keys.
+    pub fn deserialize<'de, T, D>(
+        deserializer: D,
+    ) -> Result<T, D::Error>
+    where
+        T: DeserializeOwned,
+        D: Deserializer<'de>,
+    {
+        deserialize_with_keys(deserializer, Keys::default())
+    }
+
+    /// Serializes every enum using the given keys.
+    pub fn serialize_with_keys<T, S>(
+        value: &T,
+        serializer: S,
+        keys: Keys,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        to_flattened(value, keys, true)
+            .map_err(ser::Error::custom)?
+            .serialize(serializer)
+    }
+
+    /// Deserializes every enum using the given keys.
+    pub fn deserialize_with_keys<'de, T, D>(
+        deserializer: D,
+        keys: Keys,
+    ) -> Result<T, D::Error>
+    where
+        T: DeserializeOwned,
+        D: Deserializer<'de>,
+    {
+        from_flattened(
+            crate::Value::deserialize(deserializer)?,
+            keys,
+            true,
+        )
+        .map_err(de::Error::custom)
+    }
+}
+
+/// Deserialize a field given either as a shorthand scalar or as a full
+/// mapping.
+///
+/// Configuration formats often let a value be abbreviated to a single
+/// scalar, such as `service: redis`, and spelled out as a mapping when more
+/// settings are needed, such as `service: {name: redis, port: 6380}`. A
+/// scalar is parsed with the field type's [`FromStr`](std::str::FromStr)
+/// implementation and a mapping is deserialized as usual. Numbers and
+/// booleans given as the shorthand are passed to `FromStr` in their
+/// canonical text form.
+///
+/// Values are always serialized in the full form.
+///
+/// # Example
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use std::{convert::Infallible, str::FromStr};
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Service {
+///     name: String,
+///     #[serde(default = "default_port")]
+///     port: u16,
+/// }
+///
+/// fn default_port() -> u16 {
+///     6379
+/// }
+///
+/// impl FromStr for Service {
+///     type Err = Infallible;
+///
+///     fn from_str(name: &str) -> Result<Self, Self::Err> {
+///         Ok(Service { name: name.to_owned(), port: default_port() })
+///     }
+/// }
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Config {
+///     #[serde(with = "serde_yml::with::string_or_struct")]
+///     cache: Service,
+///     #[serde(with = "serde_yml::with::string_or_struct")]
+///     queue: Service,
+/// }
+///
+/// let yaml = "cache: redis\nqueue: {name: redis, port: 6380}\n";
+/// let config: Config = serde_yml::from_str(yaml).unwrap();
+/// assert_eq!(config.cache, Service { name: "redis".to_owned(), port: 6379 });
+/// assert_eq!(config.queue, Service { name: "redis".to_owned(), port: 6380 });
+///
+/// let yaml = serde_yml::to_string(&config).unwrap();
+/// assert_eq!(yaml, "cache:\n  name: redis\n  port: 6379\nqueue:\n  name: redis\n  port: 6380\n");
+/// ```
+pub mod string_or_struct {
+    use crate::lib::*;
+    use core::{fmt, marker::PhantomData, str::FromStr};
+    use serde::de::{
+        value::MapAccessDeserializer, Deserialize, Deserializer, Error,
+        MapAccess, Visitor,
+    };
+    use serde::ser::{Serialize, Serializer};
+
+    /// Serializes the value in its full form.
+    pub fn serialize<T, S>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        value.serialize(serializer)
+    }
+
+    /// Deserializes a value from a scalar using `FromStr`, or from a
+    /// mapping using `Deserialize`.
+    pub fn deserialize<'de, T, D>(
+        deserializer: D,
+    ) -> Result<T, D::Error>
+    where
+        T: Deserialize<'de> + FromStr,
+        T::Err: fmt::Display,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(StringOrStructVisitor {
+            marker: PhantomData,
+        })
+    }
+
+    struct StringOrStructVisitor<T> {
+        marker: PhantomData<T>,
+    }
+
+    impl<T> StringOrStructVisitor<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        fn parse<E>(v: &str) -> Result<T, E>
+        where
+            E: Error,
+        {
+            T::from_str(v).map_err(E::custom)
+        }
+    }
+
+    impl<'de, T> Visitor<'de> for StringOrStructVisitor<T>
+    where
+        T: Deserialize<'de> + FromStr,
+        T::Err: fmt::Display,
+    {
+        type Value = T;
+
+        fn expecting(
+            &self,
+            formatter: &mut fmt::Formatter<'_>,
+        ) -> fmt::Result {
+            formatter.write_str("a string or a map")
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<T, E>
+        where
+            E: Error,
+        {
+            Self::parse(v)
+        }
+
+        fn visit_bool<E>(self, v: bool) -> Result<T, E>
+        where
+            E: Error,
+        {
+            Self::parse(if v { "true" } else { "false" })
+        }
+
+        fn visit_i64<E>(self, v: i64) -> Result<T, E>
+        where
+            E: Error,
+        {
+            Self::parse(&v.to_string())
+        }
+
+        fn visit_u64<E>(self, v: u64) -> Result<T, E>
+        where
+            E: Error,
+        {
+            Self::parse(&v.to_string())
+        }
+
+        fn visit_f64<E>(self, v: f64) -> Result<T, E>
+        where
+            E: Error,
+        {
+            Self::parse(&v.to_string())
+        }
+
+        fn visit_map<A>(self, map: A) -> Result<T, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            T::deserialize(MapAccessDeserializer::new(map))
+        }
+    }
+}
+
+/// Deserialize a number that may be written as a string.
+///
+/// YAML produced by other tools often quotes numbers, as in `port: "8080"`,
+/// or groups digits with underscores, as in `size: 1_000_000`, which YAML
+/// 1.2 reads as a string. This module accepts a number, or a string holding
+/// a decimal, hexadecimal (`0x2A`), octal (`0o52`) or binary (`0b101010`)
+/// integer or a float, with optional underscores between digits, leading
+/// zeros and surrounding whitespace.
+///
+/// Values are serialized as numbers.
+///
+/// See also [`DeserializerOptions::lenient_numbers`], which applies the
+/// same rules to every number of a document.
+///
+/// [`DeserializerOptions::lenient_numbers`]: crate::de::DeserializerOptions::lenient_numbers
+///
+/// # Example
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Limits {
+///     #[serde(with = "serde_yml::with::number_from_string")]
+///     port: u16,
+///     #[serde(with = "serde_yml::with::number_from_string")]
+///     size: u64,
+///     #[serde(with = "serde_yml::with::number_from_string")]
+///     mask: u8,
+///     #[serde(with = "serde_yml::with::number_from_string")]
+///     ratio: f64,
+/// }
+///
+/// let yaml = "port: \"8080\"\nsize: 1_000_000\nmask: '0x2A'\nratio: 0.5\n";
+/// let limits: Limits = serde_yml::from_str(yaml).unwrap();
+/// assert_eq!(limits, Limits { port: 8080, size: 1_000_000, mask: 42, ratio: 0.5 });
+///
+/// let yaml = serde_yml::to_string(&limits).unwrap();
+/// assert_eq!(yaml, "port: 8080\nsize: 1000000\nmask: 42\nratio: 0.5\n");
+/// ```
+pub mod number_from_string {
+    use crate::{de::normalize_numeric_string, number::Number};
+    use core::fmt;
+    use serde::de::{
+        self, Deserialize, Deserializer, Unexpected, Visitor,
+    };
+    use serde::ser::{Serialize, Serializer};
+
+    /// Serializes the number unchanged.
+    pub fn serialize<T, S>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        value.serialize(serializer)
+    }
+
+    /// Deserializes a number, or a string containing a number.
+    pub fn deserialize<'de, T, D>(
+        deserializer: D,
+    ) -> Result<T, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        let number =
+            deserializer.deserialize_any(NumberOrStringVisitor)?;
+        T::deserialize(number).map_err(de::Error::custom)
+    }
+
+    struct NumberOrStringVisitor;
+
+    impl Visitor<'_> for NumberOrStringVisitor {
+        type Value = Number;
+
+        fn expecting(
+            &self,
+            formatter: &mut fmt::Formatter<'_>,
+        ) -> fmt::Result {
+            formatter
+                .write_str("a number or a string containing a number")
+        }
+
+        fn visit_i64<E>(self, v: i64) -> Result<Number, E> {
+            Ok(v.into())
+        }
+
+        fn visit_u64<E>(self, v: u64) -> Result<Number, E> {
+            Ok(v.into())
+        }
+
+        fn visit_f64<E>(self, v: f64) -> Result<Number, E> {
+            Ok(v.into())
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Number, E>
+        where
+            E: de::Error,
+        {
+            normalize_numeric_string(v)
+                .and_then(|normalized| normalized.parse().ok())
+                .ok_or_else(|| {
+                    de::Error::invalid_value(Unexpected::Str(v), &self)
+                })
+        }
+    }
+}
+
+/// Deserialize a string that may be written as a number.
+///
+/// Numbers are converted to their canonical text form, so `0x2A` becomes
+/// `"42"` and `1.50` becomes `"1.5"`; quote a value in the document to keep
+/// it exactly as written. The field may be a `String` or any other type
+/// implementing [`FromStr`](std::str::FromStr).
+///
+/// Values are serialized unchanged.
+///
+/// # Example
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Account {
+///     #[serde(with = "serde_yml::with::string_from_number")]
+///     id: String,
+/// }
+///
+/// let account: Account = serde_yml::from_str("id: 1234\n").unwrap();
+/// assert_eq!(account.id, "1234");
+///
+/// let value: serde_yml::Value = serde_yml::from_str("id: 0x38\n").unwrap();
+/// let account: Account = serde_yml::from_value(value).unwrap();
+/// assert_eq!(account.id, "56");
+///
+/// assert_eq!(serde_yml::to_string(&account).unwrap(), "id: '56'\n");
+/// ```
+pub mod string_from_number {
+    use crate::lib::*;
+    use core::{fmt, marker::PhantomData, str::FromStr};
+    use serde::de::{self, Deserializer, Visitor};
+    use serde::ser::{Serialize, Serializer};
+
+    /// Serializes the value unchanged.
+    pub fn serialize<T, S>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        value.serialize(serializer)
+    }
+
+    /// Deserializes a string, or a number converted to its text form.
+    pub fn deserialize<'de, T, D>(
+        deserializer: D,
+    ) -> Result<T, D::Error>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(StringOrNumberVisitor {
+            marker: PhantomData,
+        })
+    }
+
+    struct StringOrNumberVisitor<T> {
+        marker: PhantomData<T>,
+    }
+
+    impl<T> StringOrNumberVisitor<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        fn parse<E>(v: &str) -> Result<T, E>
+        where
+            E: de::Error,
+        {
+            T::from_str(v).map_err(E::custom)
+        }
+    }
+
+    impl<T> Visitor<'_> for StringOrNumberVisitor<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        type Value = T;
+
+        fn expecting(
+            &self,
+            formatter: &mut fmt::Formatter<'_>,
+        ) -> fmt::Result {
+            formatter.write_str("a string or a number")
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<T, E>
+        where
+            E: de::Error,
+        {
+            Self::parse(v)
+        }
+
+        fn visit_i64<E>(self, v: i64) -> Result<T, E>
+        where
+            E: de::Error,
+        {
+            Self::parse(&v.to_string())
+        }
+
+        fn visit_u64<E>(self, v: u64) -> Result<T, E>
+        where
+            E: de::Error,
+        {
+            Self::parse(&v.to_string())
+        }
+
+        fn visit_i128<E>(self, v: i128) -> Result<T, E>
+        where
+            E: de::Error,
+        {
+            Self::parse(&v.to_string())
+        }
+
+        fn visit_u128<E>(self, v: u128) -> Result<T, E>
+        where
+            E: de::Error,
+        {
+            Self::parse(&v.to_string())
+        }
+
+        fn visit_f64<E>(self, v: f64) -> Result<T, E>
+        where
+            E: de::Error,
+        {
+            Self::parse(&crate::Number::from(v).to_string())
+        }
+    }
+}
+
+/// Serialize a collection as a sequence sorted in ascending order.
+///
+/// Standard collections are written as a sequence in their iteration
+/// order: `Vec`, `VecDeque` and `LinkedList` from front to back, and
+/// `BTreeSet` in ascending order, so their output is deterministic and
+/// reading it back restores the same collection. `HashSet` and
+/// `BinaryHeap`, however, iterate in an order that depends on their
+/// internal layout, which changes with the insertion order, the hasher and
+/// the standard library version. This module writes their elements in
+/// ascending order instead, the order of `BTreeSet` and of
+/// [`BinaryHeap::into_sorted_vec`](std::collections::BinaryHeap::into_sorted_vec).
+///
+/// Values are deserialized with the collection's own `Deserialize`
+/// implementation, so any sequence is accepted.
+///
+/// # Example
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use std::collections::{BinaryHeap, HashSet};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Queue {
+///     #[serde(with = "serde_yml::with::sorted")]
+///     priorities: BinaryHeap<u32>,
+///     #[serde(with = "serde_yml::with::sorted")]
+///     labels: HashSet<String>,
+/// }
+///
+/// let queue = Queue {
+///     priorities: BinaryHeap::from(vec![5, 1, 8, 3]),
+///     labels: ["web", "db", "cache"].iter().map(|label| label.to_string()).collect(),
+/// };
+///
+/// let yaml = serde_yml::to_string(&queue).unwrap();
+/// assert_eq!(yaml, "priorities:\n- 1\n- 3\n- 5\n- 8\nlabels:\n- cache\n- db\n- web\n");
+///
+/// let queue: Queue = serde_yml::from_str(&yaml).unwrap();
+/// assert_eq!(queue.priorities.into_sorted_vec(), [1, 3, 5, 8]);
+/// ```
+pub mod sorted {
+    use crate::lib::*;
+    use serde::de::{Deserialize, Deserializer};
+    use serde::ser::{Serialize, Serializer};
+
+    /// Serializes the elements of the collection in ascending order.
+    pub fn serialize<'a, C, T, S>(
+        collection: &'a C,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        &'a C: IntoIterator<Item = &'a T>,
+        T: Ord + Serialize + 'a,
+        S: Serializer,
+    {
+        let mut elements: Vec<&T> = collection.into_iter().collect();
+        elements.sort();
+        serializer.collect_seq(elements)
+    }
+
+    /// Deserializes the collection from a sequence in any order.
+    pub fn deserialize<'de, C, D>(
+        deserializer: D,
+    ) -> Result<C, D::Error>
+    where
+        C: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        C::deserialize(deserializer)
+    }
+}
+
+/// Deserialize a `bool` written in any of the YAML 1.1 boolean forms.
+///
+/// YAML 1.2 only reads `true` and `false` as booleans, but YAML 1.1 also
+/// accepted `y`, `yes`, `on`, `n`, `no` and `off`, and files written for
+/// YAML 1.1 tools such as Ansible use them heavily. This module accepts,
+/// for a single field, each of these words in lowercase, capitalized or
+/// uppercase form, as YAML 1.1 defines them, whatever schema the rest of
+/// the document is read with.
+///
+/// Values are serialized as `true` or `false`.
+///
+/// # Example
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Task {
+///     #[serde(with = "serde_yml::with::bool_yaml11")]
+///     #[serde(rename = "become")]
+///     escalate: bool,
+///     #[serde(with = "serde_yml::with::bool_yaml11")]
+///     gather_facts: bool,
+/// }
+///
+/// let task: Task = serde_yml::from_str("become: yes\ngather_facts: Off\n").unwrap();
+/// assert_eq!(task, Task { escalate: true, gather_facts: false });
+///
+/// let yaml = serde_yml::to_string(&task).unwrap();
+/// assert_eq!(yaml, "become: true\ngather_facts: false\n");
+/// ```
+pub mod bool_yaml11 {
+    use core::fmt;
+    use serde::de::{self, Deserializer, Unexpected, Visitor};
+    use serde::ser::Serializer;
+
+    /// Serializes the value as `true` or `false`.
+    pub fn serialize<S>(
+        value: &bool,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bool(*value)
+    }
+
+    /// Deserializes a boolean, or a string holding a YAML 1.1 boolean.
+    pub fn deserialize<'de, D>(
+        deserializer: D,
+    ) -> Result<bool, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(BoolVisitor)
+    }
+
+    /// Parses the boolean forms of the YAML 1.1 `bool` type.
+    fn parse(v: &str) -> Option<bool> {
+        match v {
+            "y" | "Y" | "yes" | "Yes" | "YES" | "true" | "True"
+            | "TRUE" | "on" | "On" | "ON" => Some(true),
+            "n" | "N" | "no" | "No" | "NO" | "false" | "False"
+            | "FALSE" | "off" | "Off" | "OFF" => Some(false),
+            _ => None,
+        }
+    }
+
+    struct BoolVisitor;
+
+    impl Visitor<'_> for BoolVisitor {
+        type Value = bool;
+
+        fn expecting(
+            &self,
+            formatter: &mut fmt::Formatter<'_>,
+        ) -> fmt::Result {
+            formatter.write_str(
+                "a boolean such as true, false, yes, no, on or off",
+            )
+        }
+
+        fn visit_bool<E>(self, v: bool) -> Result<bool, E> {
+            Ok(v)
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<bool, E>
+        where
+            E: de::Error,
+        {
+            parse(v).ok_or_else(|| {
+                de::Error::invalid_value(Unexpected::Str(v), &self)
+            })
+        }
+    }
+}
+
+/// Serialize a string as a literal block scalar (`|`).
+///
+/// By default a string is only written as a block scalar when it spans
+/// several lines. This module writes the field as a literal block scalar
+/// whatever it holds, which suits scripts, templates and other text that
+/// is expected to grow more lines. Line breaks are kept exactly: the
+/// chomping indicator is chosen from the end of the string, as described
+/// for [`MultilineStyle`](crate::ser::MultilineStyle). Strings that cannot
+/// be written as a block scalar, such as those ending in a space, are
+/// double-quoted instead.
+///
+/// The field is deserialized unchanged, and other serializers write it
+/// as a plain string.
+///
+/// # Example
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Job {
+///     #[serde(with = "serde_yml::with::literal_block")]
+///     script: String,
+/// }
+///
+/// let job = Job { script: "make".to_owned() };
+/// let yaml = serde_yml::to_string(&job).unwrap();
+/// assert_eq!(yaml, "script: |-\n  make\n");
+///
+/// let job: Job = serde_yml::from_str(&yaml).unwrap();
+/// assert_eq!(job.script, "make");
+/// ```
+pub mod literal_block {
+    use serde::de::{Deserialize, Deserializer};
+    use serde::ser::Serializer;
+
+    /// Serializes the string as a literal block scalar.
+    pub fn serialize<T, S>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: ?Sized + AsRef<str>,
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(
+            crate::ser::LITERAL_BLOCK,
+            value.as_ref(),
+        )
+    }
+
+    /// Deserializes the string unchanged.
+    pub fn deserialize<'de, T, D>(
+        deserializer: D,
+    ) -> Result<T, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer)
+    }
+}
+
+/// Serialize a string as a folded block scalar (`>`).
+///
+/// Like [`literal_block`], but the field is written as a folded block
+/// scalar, in which each line break of the string is written as a blank
+/// line and long lines may be wrapped. Reading the scalar back folds the
+/// text into the original string.
+///
+/// # Example
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Release {
+///     #[serde(with = "serde_yml::with::folded_block")]
+///     notes: String,
+/// }
+///
+/// let release = Release { notes: "Faster parsing.\nFewer allocations.\n".to_owned() };
+/// let yaml = serde_yml::to_string(&release).unwrap();
+/// assert_eq!(yaml, "notes: >\n  Faster parsing.\n\n  Fewer allocations.\n");
+///
+/// let parsed: Release = serde_yml::from_str(&yaml).unwrap();
+/// assert_eq!(parsed, release);
+/// ```
+pub mod folded_block {
+    use serde::de::{Deserialize, Deserializer};
+    use serde::ser::Serializer;
+
+    /// Serializes the string as a folded block scalar.
+    pub fn serialize<T, S>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: ?Sized + AsRef<str>,
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(
+            crate::ser::FOLDED_BLOCK,
+            value.as_ref(),
+        )
+    }
+
+    /// Deserializes the string unchanged.
+    pub fn deserialize<'de, T, D>(
+        deserializer: D,
+    ) -> Result<T, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer)
+    }
+}
+
+/// Deserialize a field so that both a missing key and an explicit `null`
+/// produce its default value.
+///
+/// Serde's `#[serde(default)]` only applies when a key is absent; an explicit
+/// `null` is still handed to the field's `Deserialize` implementation and
+/// fails for non-`Option` types. Combining `default` with this module accepts
+/// both forms without wrapping the field in `Option<T>`.
+///
+/// Use [`defaulted!`](crate::defaulted) to generate an equivalent module with
+/// a default other than `T::default()`.
+///
+/// # Example
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Example {
+///     #[serde(default, with = "serde_yml::with::defaulted")]
+///     retries: u32,
+///     #[serde(default, with = "serde_yml::with::defaulted")]
+///     tags: Vec<String>,
+/// }
+///
+/// let example: Example = serde_yml::from_str("retries: null\n").unwrap();
+/// assert_eq!(example, Example { retries: 0, tags: Vec::new() });
+///
+/// let yaml = serde_yml::to_string(&example).unwrap();
+/// assert_eq!(yaml, "retries: 0\ntags: []\n");
+/// ```
+pub mod defaulted {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Serializes the value unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A reference to the value to be serialized.
+    /// * `serializer` - The serializer to use for serializing the value.
+    ///
+    /// # Returns
+    ///
+    /// A result containing the serialization output or an error if serialization fails.
+    pub fn serialize<T, S>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        value.serialize(serializer)
+    }
+
+    /// Deserializes a value, returning `T::default()` for an explicit `null`.
+    ///
+    /// # Arguments
+    ///
+    /// * `deserializer` - The deserializer to use for deserializing the value.
+    ///
+    /// # Returns
+    ///
+    /// A result containing the deserialized value or an error if deserialization fails.
+    pub fn deserialize<'de, T, D>(
+        deserializer: D,
+    ) -> Result<T, D::Error>
+    where
+        T: Deserialize<'de> + Default,
+        D: Deserializer<'de>,
+    {
+        deserialize_or_else(deserializer, T::default)
+    }
+
+    /// Deserializes a value, calling `default` for an explicit `null`.
+    ///
+    /// This is the building block used by [`defaulted!`](crate::defaulted).
+    ///
+    /// # Arguments
+    ///
+    /// * `deserializer` - The deserializer to use for deserializing the value.
+    /// * `default` - A function producing the value to use for `null`.
+    ///
+    /// # Returns
+    ///
+    /// A result containing the deserialized value or an error if deserialization fails.
+    pub fn deserialize_or_else<'de, T, D, F>(
+        deserializer: D,
+        default: F,
+    ) -> Result<T, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+        F: FnOnce() -> T,
+    {
+        Ok(Option::<T>::deserialize(deserializer)?
+            .unwrap_or_else(default))
+    }
+}
+
+/// Serialize/deserialize a mapping carrying a schema version, dispatching on
+/// the version to read older layouts.
+///
+/// The type implements [`Versions`](versioned::Versions), naming the
+/// version field, the version written on serialize and how to read the data
+/// of each supported version. The version field is read first and removed
+/// from the mapping before the rest is handed to
+/// [`Versions::from_version`](versioned::Versions::from_version), so the
+/// types of each version do not need to declare it. On serialize, the latest
+/// version is written as the first entry, followed by the fields of the
+/// value.
+///
+/// The module can be used on a field with `#[serde(with = …)]`, and the
+/// [`Versioned`](versioned::Versioned) wrapper applies it to a whole
+/// document.
+///
+/// The mapping is held as a [`Value`](crate::Value) while the version is
+/// looked up, so errors in the data of a version do not carry a location.
+///
+/// # Example
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use serde_yml::with::versioned::{self, Versioned, Versions};
+/// use serde_yml::{Error, Value};
+///
+/// #[derive(Deserialize)]
+/// struct ConfigV1 {
+///     host: String,
+/// }
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Config {
+///     hosts: Vec<String>,
+/// }
+///
+/// impl Versions for Config {
+///     const LATEST: u64 = 2;
+///
+///     fn from_version(version: u64, value: Value) -> Result<Self, Error> {
+///         match version {
+///             1 => {
+///                 let old: ConfigV1 = serde_yml::from_value(value)?;
+///                 Ok(Config { hosts: vec![old.host] })
+///             }
+///             2 => serde_yml::from_value(value),
+///             _ => Err(versioned::unsupported_version(version, &[1, 2])),
+///         }
+///     }
+/// }
+///
+/// let old: Versioned<Config> =
+///     serde_yml::from_str("version: 1\nhost: a.example\n").unwrap();
+/// assert_eq!(old.0.hosts, ["a.example"]);
+///
+/// let yaml = serde_yml::to_string(&old).unwrap();
+/// assert_eq!(yaml, "version: 2\nhosts:\n- a.example\n");
+///
+/// let error = serde_yml::from_str::<Versioned<Config>>("version: 3\nhosts: []\n")
+///     .unwrap_err();
+/// assert_eq!(error.to_string(), "unsupported version 3, expected one of 1, 2");
+/// ```
+pub mod versioned {
+    use crate::{
+        lib::*,
+        mapping::Mapping,
+        modules::error::Error,
+        value::{to_value, Value},
+    };
+    use core::fmt::Write;
+    use serde::{
+        de,
+        ser::{self, SerializeMap},
+        Deserialize, Deserializer, Serialize, Serializer,
+    };
+
+    /// The versions of the data of a type and how to read each of them.
+    pub trait Versions: Sized {
+        /// The name of the field holding the version.
+        const FIELD: &'static str = "version";
+
+        /// The version written on serialize.
+        const LATEST: u64;
+
+        /// The version assumed for a mapping without the version field. When
+        /// `None`, the field is required.
+        const UNVERSIONED: Option<u64> = None;
+
+        /// Reads the data of a mapping of the given version, without its
+        /// version field.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the version is not supported or if the data
+        /// does not match it.
+        fn from_version(
+            version: u64,
+            value: Value,
+        ) -> Result<Self, Error>;
+    }
+
+    /// Wraps a value so that it is serialized and deserialized with
+    /// [`versioned`](self).
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct Versioned<T>(pub T);
+
+    impl<T> Versioned<T> {
+        /// Returns the wrapped value.
+        pub fn into_inner(self) -> T {
+            self.0
+        }
+    }
+
+    impl<T> Serialize for Versioned<T>
+    where
+        T: Versions + Serialize,
+    {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serialize(&self.0, serializer)
+        }
+    }
+
+    impl<'de, T> Deserialize<'de> for Versioned<T>
+    where
+        T: Versions,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserialize(deserializer).map(Versioned)
+        }
+    }
+
+    /// Returns the error for a version that is not supported, listing the
+    /// supported ones.
+    pub fn unsupported_version(
+        version: u64,
+        supported: &[u64],
+    ) -> Error {
+        let mut message = format!("unsupported version {}", version);
+        for (i, supported) in supported.iter().enumerate() {
+            let separator =
+                if i == 0 { ", expected one of " } else { ", " };
+            let _ = write!(message, "{}{}", separator, supported);
+        }
+        de::Error::custom(message)
+    }
+
+    /// Serializes the value as a mapping starting with the latest version.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A reference to the value to be serialized.
+    /// * `serializer` - The serializer to use for serializing the value.
+    ///
+    /// # Returns
+    ///
+    /// A result containing the serialization output or an error if the value
+    /// does not serialize to a mapping.
+    pub fn serialize<T, S>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: Versions + Serialize,
+        S: Serializer,
+    {
+        let fields =
+            match to_value(value).map_err(ser::Error::custom)? {
+                Value::Mapping(fields) => fields,
+                _ => {
+                    return Err(ser::Error::custom(
+                        "versioned value must serialize to a mapping",
+                    ))
+                }
+            };
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry(T::FIELD, &T::LATEST)?;
+        for (key, value) in &fields {
+            if key.as_str() != Some(T::FIELD) {
+                map.serialize_entry(key, value)?;
+            }
+        }
+        map.end()
+    }
+
+    /// Deserializes a mapping, reading its version field and dispatching to
+    /// [`Versions::from_version`].
+    ///
+    /// # Arguments
+    ///
+    /// * `deserializer` - The deserializer to use for deserializing the value.
+    ///
+    /// # Returns
+    ///
+    /// A result containing the deserialized value or an error if the version
+    /// is missing, invalid or unsupported, or if the data does not match it.
+    pub fn deserialize<'de, T, D>(
+        deserializer: D,
+    ) -> Result<T, D::Error>
+    where
+        T: Versions,
+        D: Deserializer<'de>,
+    {
+        let mut mapping = Mapping::deserialize(deserializer)?;
+        let version = match mapping.shift_remove(T::FIELD) {
+            Some(value) => parse_version(&value)?,
+            None => match T::UNVERSIONED {
+                Some(version) => version,
+                None => return Err(de::Error::missing_field(T::FIELD)),
+            },
+        };
+        T::from_version(version, Value::Mapping(mapping))
+            .map_err(de::Error::custom)
+    }
+
+    fn parse_version<E>(value: &Value) -> Result<u64, E>
+    where
+        E: de::Error,
+    {
+        let version = match value {
+            Value::Number(number) => number.as_u64(),
+            Value::String(string) => string.parse().ok(),
+            _ => None,
+        };
+        version.ok_or_else(|| {
+            E::invalid_value(value.unexpected(), &"a version number")
+        })
+    }
+}
+
+/// Parses a decimal number such as `10`, `1.5` or `.5` and multiplies it by
+/// `unit`, failing on overflow and unless the result is a whole number.
+fn parse_scaled(number: &str, unit: u128) -> Option<u128> {
+    let (whole, fraction) =
+        number.split_once('.').unwrap_or((number, ""));
+    if whole.is_empty() && fraction.is_empty()
+        || !whole
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let mut value: u128 = 0;
+    for digit in whole.bytes() {
+        value = value
+            .checked_mul(10)?
+            .checked_add(u128::from(digit - b'0'))?;
+    }
+    let mut numerator: u128 = 0;
+    let mut denominator: u128 = 1;
+    for digit in fraction.bytes() {
+        numerator = numerator
+            .checked_mul(10)?
+            .checked_add(u128::from(digit - b'0'))?;
+        denominator = denominator.checked_mul(10)?;
+    }
+    let fraction = numerator.checked_mul(unit)?;
+    if fraction % denominator != 0 {
+        return None;
+    }
+    value.checked_mul(unit)?.checked_add(fraction / denominator)
+}
+
+/// Serialize/deserialize a `u64` number of bytes as a human-friendly size
+/// such as `512B`, `10MiB` or `2GB`.
+///
+/// Deserialization accepts a plain integer, or a number followed by an
+/// optional unit, in any case and with optional whitespace between them:
+///
+/// - `B` or no unit for bytes,
+/// - `k`, `kB`, `M`, `MB` and so on up to `EB` for powers of 1000,
+/// - `Ki`, `KiB`, `Mi`, `MiB` and so on up to `EiB` for powers of 1024.
+///
+/// The number may have a fractional part, like `1.5GiB`, as long as the
+/// size comes to a whole number of bytes. Serialization picks the unit
+/// that writes the size with the smallest exact number, preferring the
+/// binary units, so `512k` is written back as `500KiB`.
+///
+/// # Example
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Cache {
+///     #[serde(with = "serde_yml::with::byte_size")]
+///     memory: u64,
+///     #[serde(with = "serde_yml::with::byte_size")]
+///     disk: u64,
+/// }
+///
+/// let cache: Cache = serde_yml::from_str("memory: 10MiB\ndisk: 2GB\n").unwrap();
+/// assert_eq!(cache, Cache { memory: 10 * 1024 * 1024, disk: 2_000_000_000 });
+///
+/// let yaml = serde_yml::to_string(&cache).unwrap();
+/// assert_eq!(yaml, "memory: '10MiB'\ndisk: '2GB'\n");
+/// ```
+pub mod byte_size {
+    use core::fmt;
+    use serde::de::{self, Deserializer, Unexpected, Visitor};
+    use serde::ser::Serializer;
+
+    /// The units written by [`serialize`], in order of preference.
+    const UNITS: [(&str, u64); 13] = [
+        ("B", 1),
+        ("KiB", 1 << 10),
+        ("MiB", 1 << 20),
+        ("GiB", 1 << 30),
+        ("TiB", 1 << 40),
+        ("PiB", 1 << 50),
+        ("EiB", 1 << 60),
+        ("kB", 1_000),
+        ("MB", 1_000_000),
+        ("GB", 1_000_000_000),
+        ("TB", 1_000_000_000_000),
+        ("PB", 1_000_000_000_000_000),
+        ("EB", 1_000_000_000_000_000_000),
+    ];
+
+    /// Serializes the number of bytes with the unit that fits it best.
+    pub fn serialize<S>(
+        value: &u64,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(&ByteSize(*value))
+    }
+
+    /// Deserializes a number of bytes from a size with an optional unit.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<u64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ByteSizeVisitor)
+    }
+
+    struct ByteSize(u64);
+
+    impl fmt::Display for ByteSize {
+        fn fmt(
+            &self,
+            formatter: &mut fmt::Formatter<'_>,
+        ) -> fmt::Result {
+            let (count, unit) = UNITS
+                .iter()
+                .filter(|(_, size)| self.0 % size == 0)
+                .map(|(unit, size)| (self.0 / size, unit))
+                .min_by_key(|(count, _)| *count)
+                .unwrap_or((self.0, &"B"));
+            write!(formatter, "{}{}", count, unit)
+        }
+    }
+
+    /// Returns the number of bytes in a unit such as `kB`, `Ki` or `MiB`.
+    fn multiplier(unit: &str) -> Option<u128> {
+        if unit.is_empty() || unit.eq_ignore_ascii_case("b") {
+            return Some(1);
+        }
+        let mut chars = unit.chars();
+        let prefix = chars.next()?.to_ascii_lowercase();
+        let power = "kmgtpe".find(prefix)? as u32 + 1;
+        let suffix = chars.as_str();
+        if suffix.is_empty() || suffix.eq_ignore_ascii_case("b") {
+            Some(1000u128.pow(power))
+        } else if suffix.eq_ignore_ascii_case("i")
+            || suffix.eq_ignore_ascii_case("ib")
+        {
+            Some(1u128 << (10 * power))
+        } else {
+            None
+        }
+    }
+
+    /// Parses a size such as `512`, `10MiB`, `1.5 GB` or `512k`.
+    fn parse(v: &str) -> Option<u64> {
+        let v = v.trim();
+        let split = v
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(v.len());
+        let (number, unit) = v.split_at(split);
+        let bytes = super::parse_scaled(
+            number,
+            multiplier(unit.trim_start())?,
+        )?;
+        u64::try_from(bytes).ok()
+    }
+
+    struct ByteSizeVisitor;
+
+    impl Visitor<'_> for ByteSizeVisitor {
+        type Value = u64;
+
+        fn expecting(
+            &self,
+            formatter: &mut fmt::Formatter<'_>,
+        ) -> fmt::Result {
+            formatter
+                .write_str("a byte size such as 512, 10MiB or 1.5GB")
+        }
+
+        fn visit_u64<E>(self, v: u64) -> Result<u64, E> {
+            Ok(v)
+        }
+
+        fn visit_i64<E>(self, v: i64) -> Result<u64, E>
+        where
+            E: de::Error,
+        {
+            u64::try_from(v).map_err(|_| {
+                de::Error::invalid_value(Unexpected::Signed(v), &self)
+            })
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<u64, E>
+        where
+            E: de::Error,
+        {
+            parse(v).ok_or_else(|| {
+                de::Error::invalid_value(Unexpected::Str(v), &self)
+            })
+        }
+    }
+}
+
+/// Serialize/deserialize a [`Duration`](core::time::Duration) in a compact
+/// human-friendly form such as `250ms`, `30s` or `1h30m`.
+///
+/// Deserialization accepts a plain integer as a number of seconds, or a
+/// sequence of numbers each followed by a unit, optionally separated by
+/// whitespace: `d` for days, `h`, `m`, `s`, `ms`, `us` or `µs`, and `ns`.
+/// A number may have a fractional part, like `1.5h`. Serialization writes
+/// each unit from days down to nanoseconds that the duration needs, with
+/// no separators, and `0s` for an empty duration.
+///
+/// Unlike `duration_humantime`, this module needs no extra dependency and
+/// also builds without the `std` feature.
+///
+/// # Example
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use std::time::Duration;
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Probe {
+///     #[serde(with = "serde_yml::with::duration")]
+///     interval: Duration,
+///     #[serde(with = "serde_yml::with::duration")]
+///     timeout: Duration,
+/// }
+///
+/// let probe: Probe = serde_yml::from_str("interval: 1h 30m\ntimeout: 250ms\n").unwrap();
+/// assert_eq!(probe.interval, Duration::from_secs(5400));
+/// assert_eq!(probe.timeout, Duration::from_millis(250));
+///
+/// let yaml = serde_yml::to_string(&probe).unwrap();
+/// assert_eq!(yaml, "interval: '1h30m'\ntimeout: '250ms'\n");
+/// ```
+pub mod duration {
+    use core::fmt;
+    use core::time::Duration;
+    use serde::de::{self, Deserializer, Unexpected, Visitor};
+    use serde::ser::Serializer;
+
+    const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+    /// The units written by [`serialize`], from the largest to the smallest.
+    const UNITS: [(&str, u128); 7] = [
+        ("d", 86_400 * NANOS_PER_SEC),
+        ("h", 3_600 * NANOS_PER_SEC),
+        ("m", 60 * NANOS_PER_SEC),
+        ("s", NANOS_PER_SEC),
+        ("ms", 1_000_000),
+        ("us", 1_000),
+        ("ns", 1),
+    ];
+
+    /// Serializes the duration in its compact human-friendly form.
+    pub fn serialize<S>(
+        value: &Duration,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(&HumanDuration(*value))
+    }
+
+    /// Deserializes a duration from its human-friendly form or from a
+    /// number of seconds.
+    pub fn deserialize<'de, D>(
+        deserializer: D,
+    ) -> Result<Duration, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(DurationVisitor)
+    }
+
+    struct HumanDuration(Duration);
+
+    impl fmt::Display for HumanDuration {
+        fn fmt(
+            &self,
+            formatter: &mut fmt::Formatter<'_>,
+        ) -> fmt::Result {
+            let mut nanos = self.0.as_nanos();
+            if nanos == 0 {
+                return formatter.write_str("0s");
+            }
+            for (unit, size) in UNITS {
+                if nanos >= size {
+                    write!(formatter, "{}{}", nanos / size, unit)?;
+                    nanos %= size;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// Returns the number of nanoseconds in a unit such as `ms` or `h`.
+    fn unit_nanos(unit: &str) -> Option<u128> {
+        if unit == "µs" {
+            return Some(1_000);
+        }
+        UNITS
+            .iter()
+            .find(|(name, _)| *name == unit)
+            .map(|(_, size)| *size)
+    }
+
+    /// Parses a duration such as `250ms`, `1h30m` or `1h 30m`.
+    fn parse(v: &str) -> Option<Duration> {
+        let mut rest = v.trim();
+        if rest.is_empty() {
+            return None;
+        }
+        let mut nanos: u128 = 0;
+        while !rest.is_empty() {
+            let split = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(rest.len());
+            let (number, tail) = rest.split_at(split);
+            let tail = tail.trim_start();
+            let split = tail
+                .find(|c: char| !c.is_alphabetic())
+                .unwrap_or(tail.len());
+            let (unit, tail) = tail.split_at(split);
+            nanos = nanos.checked_add(super::parse_scaled(
+                number,
+                unit_nanos(unit)?,
+            )?)?;
+            rest = tail.trim_start();
+        }
+        let secs = u64::try_from(nanos / NANOS_PER_SEC).ok()?;
+        let subsec = u32::try_from(nanos % NANOS_PER_SEC).ok()?;
+        Some(Duration::new(secs, subsec))
+    }
+
+    struct DurationVisitor;
+
+    impl Visitor<'_> for DurationVisitor {
+        type Value = Duration;
+
+        fn expecting(
+            &self,
+            formatter: &mut fmt::Formatter<'_>,
+        ) -> fmt::Result {
+            formatter.write_str(
+                "a duration such as 250ms, 30s or 1h30m, or a number of seconds",
+            )
+        }
+
+        fn visit_u64<E>(self, v: u64) -> Result<Duration, E> {
+            Ok(Duration::from_secs(v))
+        }
+
+        fn visit_i64<E>(self, v: i64) -> Result<Duration, E>
+        where
+            E: de::Error,
+        {
+            u64::try_from(v).map(Duration::from_secs).map_err(|_| {
+                de::Error::invalid_value(Unexpected::Signed(v), &self)
+            })
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Duration, E>
+        where
+            E: de::Error,
+        {
+            parse(v).ok_or_else(|| {
+                de::Error::invalid_value(Unexpected::Str(v), &self)
+            })
+        }
+    }
+}
+
+/// Serialize/deserialize a [`Uuid`](::uuid::Uuid) as its canonical
+/// hyphenated string.
+///
+/// Deserialization accepts every form understood by
+/// [`Uuid::parse_str`](::uuid::Uuid::parse_str), including the simple form
+/// without hyphens and the braced and `urn:uuid:` forms.
+///
+/// Requires the `uuid` feature.
+///
+/// # Example
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use uuid::Uuid;
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Node {
+///     #[serde(with = "serde_yml::with::uuid")]
+///     id: Uuid,
+/// }
+///
+/// let node: Node =
+///     serde_yml::from_str("id: 67E55044-10B1-426F-9247-BB680E5FE0C8\n").unwrap();
+///
+/// let yaml = serde_yml::to_string(&node).unwrap();
+/// assert_eq!(yaml, "id: '67e55044-10b1-426f-9247-bb680e5fe0c8'\n");
+/// ```
+#[cfg(feature = "uuid")]
+pub mod uuid {
+    use ::uuid::Uuid;
+    use core::fmt;
+    use serde::de::{self, Deserializer, Unexpected, Visitor};
+    use serde::ser::Serializer;
+
+    /// Serializes the UUID as a lowercase hyphenated string.
+    pub fn serialize<S>(
+        value: &Uuid,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(&value.hyphenated())
+    }
+
+    /// Deserializes a UUID from a string.
+    pub fn deserialize<'de, D>(
+        deserializer: D,
+    ) -> Result<Uuid, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(UuidVisitor)
+    }
+
+    struct UuidVisitor;
+
+    impl Visitor<'_> for UuidVisitor {
+        type Value = Uuid;
+
+        fn expecting(
+            &self,
+            formatter: &mut fmt::Formatter<'_>,
+        ) -> fmt::Result {
+            formatter.write_str(
+                "a UUID such as 67e55044-10b1-426f-9247-bb680e5fe0c8",
+            )
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Uuid, E>
+        where
+            E: de::Error,
+        {
+            Uuid::parse_str(v).map_err(|_| {
+                de::Error::invalid_value(Unexpected::Str(v), &self)
+            })
+        }
+    }
+}
+
+/// Serialize/deserialize a chrono [`DateTime`](::chrono::DateTime) as an
+/// RFC 3339 timestamp.
+///
+/// The timestamp is written with as many fractional digits as needed and
+/// with `Z` for a zero offset, like `2024-05-01T12:30:00Z`. Deserialization
+/// also accepts a space between the date and the time. The module works for
+/// every time zone whose `DateTime` implements `FromStr`, such as
+/// [`Utc`](::chrono::Utc) and [`FixedOffset`](::chrono::FixedOffset).
+///
+/// Requires the `chrono` feature.
+///
+/// # Example
+///
+/// ```
+/// use chrono::{DateTime, TimeZone, Utc};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Release {
+///     #[serde(with = "serde_yml::with::chrono_datetime")]
+///     published: DateTime<Utc>,
+/// }
+///
+/// let release: Release =
+///     serde_yml::from_str("published: 2024-05-01 14:30:00+02:00\n").unwrap();
+/// assert_eq!(
+///     release.published,
+///     Utc.with_ymd_and_hms(2024, 5, 1, 12, 30, 0).unwrap(),
+/// );
+///
+/// let yaml = serde_yml::to_string(&release).unwrap();
+/// assert_eq!(yaml, "published: '2024-05-01T12:30:00Z'\n");
+/// ```
+#[cfg(feature = "chrono")]
+pub mod chrono_datetime {
+    use ::chrono::{DateTime, ParseError, SecondsFormat, TimeZone};
+    use core::{fmt, marker::PhantomData, str::FromStr};
+    use serde::de::{self, Deserializer, Unexpected, Visitor};
+    use serde::ser::Serializer;
+
+    /// Serializes the date and time as an RFC 3339 timestamp.
+    pub fn serialize<Tz, S>(
+        value: &DateTime<Tz>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        Tz: TimeZone,
+        Tz::Offset: fmt::Display,
+        S: Serializer,
+    {
+        serializer.serialize_str(
+            &value.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        )
+    }
+
+    /// Deserializes a date and time from an RFC 3339 timestamp.
+    pub fn deserialize<'de, Tz, D>(
+        deserializer: D,
+    ) -> Result<DateTime<Tz>, D::Error>
+    where
+        Tz: TimeZone,
+        DateTime<Tz>: FromStr<Err = ParseError>,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(DateTimeVisitor(PhantomData))
+    }
+
+    struct DateTimeVisitor<Tz>(PhantomData<Tz>);
+
+    impl<Tz> Visitor<'_> for DateTimeVisitor<Tz>
+    where
+        Tz: TimeZone,
+        DateTime<Tz>: FromStr<Err = ParseError>,
+    {
+        type Value = DateTime<Tz>;
+
+        fn expecting(
+            &self,
+            formatter: &mut fmt::Formatter<'_>,
+        ) -> fmt::Result {
+            formatter.write_str(
+                "an RFC 3339 date and time such as 2024-05-01T12:30:00Z",
+            )
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<DateTime<Tz>, E>
+        where
+            E: de::Error,
+        {
+            v.parse().map_err(|_| {
+                de::Error::invalid_value(Unexpected::Str(v), &self)
+            })
+        }
+    }
+}
+
+/// Serialize/deserialize a time [`OffsetDateTime`](::time::OffsetDateTime)
+/// as an RFC 3339 timestamp.
+///
+/// The timestamp is written in the offset it holds, like
+/// `2024-05-01T12:30:00Z` or `2024-05-01T14:30:00+02:00`.
+///
+/// Requires the `time` feature.
+///
+/// # Example
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use time::OffsetDateTime;
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Release {
+///     #[serde(with = "serde_yml::with::time_offsetdatetime")]
+///     published: OffsetDateTime,
+/// }
+///
+/// let release: Release =
+///     serde_yml::from_str("published: 2024-05-01T14:30:00+02:00\n").unwrap();
+/// assert_eq!(release.published.unix_timestamp(), 1_714_566_600);
+///
+/// let yaml = serde_yml::to_string(&release).unwrap();
+/// assert_eq!(yaml, "published: '2024-05-01T14:30:00+02:00'\n");
+/// ```
+#[cfg(feature = "time")]
+pub mod time_offsetdatetime {
+    use ::time::{
+        format_description::well_known::Rfc3339, OffsetDateTime,
+    };
+    use core::fmt;
+    use serde::de::{self, Deserializer, Unexpected, Visitor};
+    use serde::ser::{self, Serializer};
+
+    /// Serializes the date and time as an RFC 3339 timestamp.
+    ///
+    /// Fails for years that RFC 3339 cannot express, before 0 or after
+    /// 9999, and for offsets with a seconds component.
+    pub fn serialize<S>(
+        value: &OffsetDateTime,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let formatted =
+            value.format(&Rfc3339).map_err(ser::Error::custom)?;
+        serializer.serialize_str(&formatted)
+    }
+
+    /// Deserializes a date and time from an RFC 3339 timestamp.
+    pub fn deserialize<'de, D>(
+        deserializer: D,
+    ) -> Result<OffsetDateTime, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(OffsetDateTimeVisitor)
+    }
+
+    struct OffsetDateTimeVisitor;
+
+    impl Visitor<'_> for OffsetDateTimeVisitor {
+        type Value = OffsetDateTime;
+
+        fn expecting(
+            &self,
+            formatter: &mut fmt::Formatter<'_>,
+        ) -> fmt::Result {
+            formatter.write_str(
+                "an RFC 3339 date and time such as 2024-05-01T12:30:00Z",
+            )
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<OffsetDateTime, E>
+        where
+            E: de::Error,
+        {
+            OffsetDateTime::parse(v, &Rfc3339).map_err(|_| {
+                de::Error::invalid_value(Unexpected::Str(v), &self)
+            })
+        }
+    }
+}
+
+/// Serialize/deserialize a [`Duration`](std::time::Duration) in the
+/// human-readable form of the `humantime` crate, like `30s`, `5m` or
+/// `1h 30m`.
+///
+/// Deserialization also accepts a plain integer as a number of seconds.
+///
+/// Requires the `humantime` feature.
+///
+/// # Example
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use std::time::Duration;
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Probe {
+///     #[serde(with = "serde_yml::with::duration_humantime")]
+///     interval: Duration,
+///     #[serde(with = "serde_yml::with::duration_humantime")]
+///     timeout: Duration,
+/// }
+///
+/// let probe: Probe = serde_yml::from_str("interval: 1h30m\ntimeout: 10\n").unwrap();
+/// assert_eq!(probe.interval, Duration::from_secs(5400));
+/// assert_eq!(probe.timeout, Duration::from_secs(10));
+///
+/// let yaml = serde_yml::to_string(&probe).unwrap();
+/// assert_eq!(yaml, "interval: '1h 30m'\ntimeout: '10s'\n");
+/// ```
+#[cfg(feature = "humantime")]
+pub mod duration_humantime {
+    use core::fmt;
+    use serde::de::{self, Deserializer, Unexpected, Visitor};
+    use serde::ser::Serializer;
+    use std::time::Duration;
+
+    /// Serializes the duration in its human-readable form.
+    pub fn serialize<S>(
+        value: &Duration,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(&humantime::format_duration(*value))
+    }
+
+    /// Deserializes a duration from its human-readable form or from a
+    /// number of seconds.
+    pub fn deserialize<'de, D>(
+        deserializer: D,
+    ) -> Result<Duration, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(DurationVisitor)
+    }
+
+    struct DurationVisitor;
+
+    impl Visitor<'_> for DurationVisitor {
+        type Value = Duration;
+
+        fn expecting(
+            &self,
+            formatter: &mut fmt::Formatter<'_>,
+        ) -> fmt::Result {
+            formatter.write_str(
+                "a duration such as 30s, 5m or 1h 30m, or a number of seconds",
+            )
+        }
+
+        fn visit_u64<E>(self, v: u64) -> Result<Duration, E> {
+            Ok(Duration::from_secs(v))
+        }
+
+        fn visit_i64<E>(self, v: i64) -> Result<Duration, E>
+        where
+            E: de::Error,
+        {
+            u64::try_from(v).map(Duration::from_secs).map_err(|_| {
+                de::Error::invalid_value(Unexpected::Signed(v), &self)
+            })
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Duration, E>
+        where
+            E: de::Error,
+        {
+            humantime::parse_duration(v).map_err(|_| {
+                de::Error::invalid_value(Unexpected::Str(v), &self)
+            })
+        }
+    }
+}
+
+/// A `with` module as a type, so that it can be handed to [`SeqOf`],
+/// [`MapValues`] and [`OptionOf`] to apply it to the elements of a
+/// container.
+///
+/// Each module of this crate that works on values of any type has one,
+/// named after it: [`SingletonMap`] for [`singleton_map`],
+/// [`NumberFromString`] for [`number_from_string`] and so on. The
+/// combinators have one too, so they nest, as in
+/// `OptionOf::<SeqOf<SingletonMap>>`. For any other module, implement the
+/// trait for a type of your own:
+///
+/// ```
+/// use serde::{Deserialize, Deserializer, Serialize, Serializer};
+/// use serde_yml::with::{bool_yaml11, SeqOf, WithModule};
+///
+/// enum BoolYaml11 {}
+///
+/// impl WithModule<bool> for BoolYaml11 {
+///     fn serialize<S>(value: &bool, serializer: S) -> Result<S::Ok, S::Error>
+///     where
+///         S: Serializer,
+///     {
+///         bool_yaml11::serialize(value, serializer)
+///     }
+///
+///     fn deserialize<'de, D>(deserializer: D) -> Result<bool, D::Error>
+///     where
+///         D: Deserializer<'de>,
+///     {
+///         bool_yaml11::deserialize(deserializer)
+///     }
+/// }
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Features {
+///     #[serde(with = "SeqOf::<BoolYaml11>")]
+///     enabled: Vec<bool>,
+/// }
+///
+/// let features: Features = serde_yml::from_str("enabled: [yes, Off, true]").unwrap();
+/// assert_eq!(features.enabled, [true, false, true]);
+/// ```
+pub trait WithModule<T> {
+    /// Serializes `value` as the module does.
+    fn serialize<S>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer;
+
+    /// Deserializes a value as the module does.
+    fn deserialize<'de, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>;
+}
+
+/// Declares the [`WithModule`] type of each module of this crate that works
+/// on values of any type.
+macro_rules! module_types {
+    ($($name:ident = $module:ident where [$($bound:tt)*];)*) => {$(
+        #[doc = concat!(
+            "The [`", stringify!($module), "`] module as a [`WithModule`], ",
+            "to apply it through [`SeqOf`], [`MapValues`] and [`OptionOf`].",
+        )]
+        #[derive(Clone, Copy, Debug)]
+        pub enum $name {}
+
+        impl<T> WithModule<T> for $name
+        where
+            $($bound)*
+        {
+            fn serialize<S>(
+                value: &T,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                $module::serialize(value, serializer)
+            }
+
+            fn deserialize<'de, D>(deserializer: D) -> Result<T, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                $module::deserialize(deserializer)
+            }
+        }
+    )*};
+}
+
+module_types! {
+    SingletonMap = singleton_map where [T: Serialize + DeserializeOwned];
+    SingletonMapRecursive = singleton_map_recursive
+        where [T: Serialize + DeserializeOwned];
+    FlattenTagged = flatten_tagged where [T: Serialize + DeserializeOwned];
+    StringOrStruct = string_or_struct
+        where [T: Serialize + DeserializeOwned + FromStr, T::Err: fmt::Display];
+    NumberFromString = number_from_string
+        where [T: Serialize + DeserializeOwned];
+    StringFromNumber = string_from_number
+        where [T: Serialize + FromStr, T::Err: fmt::Display];
+    LiteralBlock = literal_block where [T: AsRef<str> + DeserializeOwned];
+    FoldedBlock = folded_block where [T: AsRef<str> + DeserializeOwned];
+}
+
+/// Apply a `with` module to each element of a sequence, such as a `Vec` or
+/// a `BTreeSet`.
+///
+/// `M` is the [`WithModule`] type of the module.
+///
+/// # Example
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use serde_yml::with::{SeqOf, SingletonMap};
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// enum Step {
+///     Run(String),
+///     Sleep { seconds: u32 },
+/// }
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Job {
+///     #[serde(with = "SeqOf::<SingletonMap>")]
+///     steps: Vec<Step>,
+/// }
+///
+/// let job = Job {
+///     steps: vec![Step::Run("make".to_owned()), Step::Sleep { seconds: 5 }],
+/// };
+/// let yaml = serde_yml::to_string(&job).unwrap();
+/// assert_eq!(yaml, "steps:\n- Run: make\n- Sleep:\n    seconds: 5\n");
+///
+/// let deserialized: Job = serde_yml::from_str(&yaml).unwrap();
+/// assert_eq!(job, deserialized);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct SeqOf<M>(PhantomData<M>);
+
+impl<M> SeqOf<M> {
+    /// Serializes each element of the collection through `M`.
+    pub fn serialize<'a, C, T, S>(
+        collection: &'a C,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        &'a C: IntoIterator<Item = &'a T>,
+        T: 'a,
+        M: WithModule<T>,
+        S: Serializer,
+    {
+        serializer.collect_seq(
+            collection.into_iter().map(Serialized::<M, T>::new),
+        )
+    }
+
+    /// Deserializes a sequence, each element through `M`.
+    pub fn deserialize<'de, C, T, D>(
+        deserializer: D,
+    ) -> Result<C, D::Error>
+    where
+        C: FromIterator<T>,
+        M: WithModule<T>,
+        D: Deserializer<'de>,
+    {
+        let elements =
+            Vec::<Deserialized<M, T>>::deserialize(deserializer)?;
+        Ok(elements.into_iter().map(|element| element.value).collect())
+    }
+}
+
+impl<M, T> WithModule<Vec<T>> for SeqOf<M>
+where
+    M: WithModule<T>,
+{
+    fn serialize<S>(
+        value: &Vec<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        SeqOf::<M>::serialize(value, serializer)
+    }
+
+    fn deserialize<'de, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        SeqOf::<M>::deserialize(deserializer)
+    }
+}
+
+/// Apply a `with` module to each value of a map, such as a `HashMap` or a
+/// `BTreeMap`. The keys are serialized and deserialized as they are.
+///
+/// `M` is the [`WithModule`] type of the module.
+///
+/// # Example
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use serde_yml::with::{MapValues, NumberFromString};
+/// use std::collections::BTreeMap;
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Limits {
+///     #[serde(with = "MapValues::<NumberFromString>")]
+///     memory: BTreeMap<String, u64>,
+/// }
+///
+/// let limits: Limits = serde_yml::from_str("memory: {api: '512', worker: 1024}").unwrap();
+/// assert_eq!(limits.memory["api"], 512);
+/// assert_eq!(limits.memory["worker"], 1024);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct MapValues<M>(PhantomData<M>);
+
+impl<M> MapValues<M> {
+    /// Serializes each entry of the map, with its value through `M`.
+    pub fn serialize<'a, C, K, V, S>(
+        map: &'a C,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        &'a C: IntoIterator<Item = (&'a K, &'a V)>,
+        K: Serialize + 'a,
+        V: 'a,
+        M: WithModule<V>,
+        S: Serializer,
+    {
+        serializer.collect_map(
+            map.into_iter().map(|(key, value)| {
+                (key, Serialized::<M, V>::new(value))
+            }),
+        )
+    }
+
+    /// Deserializes a map, each value through `M`.
+    pub fn deserialize<'de, C, K, V, D>(
+        deserializer: D,
+    ) -> Result<C, D::Error>
+    where
+        C: FromIterator<(K, V)>,
+        K: Deserialize<'de>,
+        M: WithModule<V>,
+        D: Deserializer<'de>,
+    {
+        let entries = deserializer.deserialize_map(EntriesVisitor {
+            marker: PhantomData::<(M, K, V)>,
+        })?;
+        Ok(entries.into_iter().collect())
+    }
+}
+
+impl<M, K, V> WithModule<BTreeMap<K, V>> for MapValues<M>
+where
+    K: Ord + Serialize + DeserializeOwned,
+    M: WithModule<V>,
+{
+    fn serialize<S>(
+        value: &BTreeMap<K, V>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        MapValues::<M>::serialize(value, serializer)
+    }
+
+    fn deserialize<'de, D>(
+        deserializer: D,
+    ) -> Result<BTreeMap<K, V>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        MapValues::<M>::deserialize(deserializer)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<M, K, V, H> WithModule<HashMap<K, V, H>> for MapValues<M>
+where
+    K: Eq + Hash + Serialize + DeserializeOwned,
+    H: BuildHasher + Default,
+    M: WithModule<V>,
+{
+    fn serialize<S>(
+        value: &HashMap<K, V, H>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        MapValues::<M>::serialize(value, serializer)
+    }
+
+    fn deserialize<'de, D>(
+        deserializer: D,
+    ) -> Result<HashMap<K, V, H>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        MapValues::<M>::deserialize(deserializer)
+    }
+}
+
+/// Apply a `with` module to the value of an `Option`, if there is one.
+/// `None` is serialized as `null`.
+///
+/// `M` is the [`WithModule`] type of the module. As with any `with` module,
+/// a missing field is an error unless it also has `#[serde(default)]`.
+///
+/// # Example
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use serde_yml::with::{OptionOf, SeqOf, SingletonMap};
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// enum Hook {
+///     Exec(String),
+/// }
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Stage {
+///     #[serde(default, with = "OptionOf::<SeqOf<SingletonMap>>")]
+///     hooks: Option<Vec<Hook>>,
+/// }
+///
+/// let stage: Stage = serde_yml::from_str("hooks:\n- Exec: lint\n").unwrap();
+/// assert_eq!(stage.hooks, Some(vec![Hook::Exec("lint".to_owned())]));
+///
+/// let stage: Stage = serde_yml::from_str("{}").unwrap();
+/// assert_eq!(serde_yml::to_string(&stage).unwrap(), "hooks: null\n");
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct OptionOf<M>(PhantomData<M>);
+
+impl<M> OptionOf<M> {
+    /// Serializes the value through `M`, or `null` for `None`.
+    pub fn serialize<T, S>(
+        value: &Option<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        M: WithModule<T>,
+        S: Serializer,
+    {
+        match value {
+            Some(value) => serializer
+                .serialize_some(&Serialized::<M, T>::new(value)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    /// Deserializes `null` as `None`, and any other value through `M`.
+    pub fn deserialize<'de, T, D>(
+        deserializer: D,
+    ) -> Result<Option<T>, D::Error>
+    where
+        M: WithModule<T>,
+        D: Deserializer<'de>,
+    {
+        let value =
+            Option::<Deserialized<M, T>>::deserialize(deserializer)?;
+        Ok(value.map(|value| value.value))
+    }
+}
+
+impl<M, T> WithModule<Option<T>> for OptionOf<M>
+where
+    M: WithModule<T>,
+{
+    fn serialize<S>(
+        value: &Option<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        OptionOf::<M>::serialize(value, serializer)
+    }
+
+    fn deserialize<'de, D>(
+        deserializer: D,
+    ) -> Result<Option<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        OptionOf::<M>::deserialize(deserializer)
+    }
+}
+
+/// A value serialized through the `with` module `M`.
+struct Serialized<'a, M, T> {
+    value: &'a T,
+    marker: PhantomData<M>,
+}
+
+impl<'a, M, T> Serialized<'a, M, T> {
+    fn new(value: &'a T) -> Self {
+        Serialized {
+            value,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<M, T> Serialize for Serialized<'_, M, T>
+where
+    M: WithModule<T>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        M::serialize(self.value, serializer)
+    }
+}
+
+/// A value deserialized through the `with` module `M`.
+struct Deserialized<M, T> {
+    value: T,
+    marker: PhantomData<M>,
+}
+
+impl<'de, M, T> Deserialize<'de> for Deserialized<M, T>
+where
+    M: WithModule<T>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Deserialized {
+            value: M::deserialize(deserializer)?,
+            marker: PhantomData,
+        })
+    }
+}
+
+/// Collects the entries of a map, with each value deserialized through the
+/// `with` module `M`.
+struct EntriesVisitor<M, K, V> {
+    marker: PhantomData<(M, K, V)>,
+}
+
+impl<'de, M, K, V> Visitor<'de> for EntriesVisitor<M, K, V>
+where
+    K: Deserialize<'de>,
+    M: WithModule<V>,
+{
+    type Value = Vec<(K, V)>;
+
+    fn expecting(
+        &self,
+        formatter: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        formatter.write_str("a map")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entries = Vec::new();
+        while let Some((key, value)) =
+            map.next_entry::<K, Deserialized<M, V>>()?
+        {
+            entries.push((key, value.value));
+        }
+        Ok(entries)
+    }
+}
@@ -0,0 +1,16 @@
+//! Helpers shared by the integration tests that read files.
+
+use std::fs;
+use tempfile::TempDir;
+
+/// Creates a temporary directory holding the given files, along with
+/// their parent directories. The directory is removed when dropped.
+pub(crate) fn tree(files: &[(&str, &str)]) -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    for (file, contents) in files {
+        let path = dir.path().join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+    dir
+}
@@ -1,7 +1,8 @@
 #[cfg(test)]
 mod tests {
     use serde_yml::libyml::emitter::{
-        Emitter, Event, Mapping, Scalar, ScalarStyle, Sequence,
+        Emitter, EmitterBuilder, Event, Mapping, Scalar, ScalarStyle,
+        Sequence,
     };
     use std::io::Cursor;
 
@@ -26,13 +27,7 @@ mod tests {
             let mut emitter = Emitter::new(Box::new(&mut buffer));
             emitter.emit(Event::StreamStart).unwrap();
             emitter.emit(Event::DocumentStart).unwrap();
-            emitter
-                .emit(Event::Scalar(Scalar {
-                    tag: None,
-                    value: "",
-                    style: ScalarStyle::Plain,
-                }))
-                .unwrap();
+            emitter.emit(Event::Scalar(Scalar::plain(""))).unwrap();
             emitter.emit(Event::DocumentEnd).unwrap();
             emitter.emit(Event::StreamEnd).unwrap();
         }
@@ -50,11 +45,7 @@ mod tests {
             emitter.emit(Event::StreamStart).unwrap();
             emitter.emit(Event::DocumentStart).unwrap();
             emitter
-                .emit(Event::Scalar(Scalar {
-                    tag: None,
-                    value: "hello",
-                    style: ScalarStyle::Plain,
-                }))
+                .emit(Event::Scalar(Scalar::plain("hello")))
                 .unwrap();
             emitter.emit(Event::DocumentEnd).unwrap();
             emitter.emit(Event::StreamEnd).unwrap();
@@ -74,21 +65,13 @@ mod tests {
             emitter.emit(Event::StreamStart).unwrap();
             emitter.emit(Event::DocumentStart).unwrap();
             emitter
-                .emit(Event::SequenceStart(Sequence { tag: None }))
+                .emit(Event::SequenceStart(Sequence::new()))
                 .unwrap();
             emitter
-                .emit(Event::Scalar(Scalar {
-                    tag: None,
-                    value: "item1",
-                    style: ScalarStyle::Plain,
-                }))
+                .emit(Event::Scalar(Scalar::plain("item1")))
                 .unwrap();
             emitter
-                .emit(Event::Scalar(Scalar {
-                    tag: None,
-                    value: "item2",
-                    style: ScalarStyle::Plain,
-                }))
+                .emit(Event::Scalar(Scalar::plain("item2")))
                 .unwrap();
             emitter.emit(Event::SequenceEnd).unwrap();
             emitter.emit(Event::DocumentEnd).unwrap();
@@ -108,36 +91,14 @@ mod tests {
             let mut emitter = Emitter::new(Box::new(&mut buffer));
             emitter.emit(Event::StreamStart).unwrap();
             emitter.emit(Event::DocumentStart).unwrap();
+            emitter.emit(Event::MappingStart(Mapping::new())).unwrap();
+            emitter.emit(Event::Scalar(Scalar::plain("key1"))).unwrap();
             emitter
-                .emit(Event::MappingStart(Mapping { tag: None }))
+                .emit(Event::Scalar(Scalar::plain("value1")))
                 .unwrap();
+            emitter.emit(Event::Scalar(Scalar::plain("key2"))).unwrap();
             emitter
-                .emit(Event::Scalar(Scalar {
-                    tag: None,
-                    value: "key1",
-                    style: ScalarStyle::Plain,
-                }))
-                .unwrap();
-            emitter
-                .emit(Event::Scalar(Scalar {
-                    tag: None,
-                    value: "value1",
-                    style: ScalarStyle::Plain,
-                }))
-                .unwrap();
-            emitter
-                .emit(Event::Scalar(Scalar {
-                    tag: None,
-                    value: "key2",
-                    style: ScalarStyle::Plain,
-                }))
-                .unwrap();
-            emitter
-                .emit(Event::Scalar(Scalar {
-                    tag: None,
-                    value: "value2",
-                    style: ScalarStyle::Plain,
-                }))
+                .emit(Event::Scalar(Scalar::plain("value2")))
                 .unwrap();
             emitter.emit(Event::MappingEnd).unwrap();
             emitter.emit(Event::DocumentEnd).unwrap();
@@ -158,11 +119,7 @@ mod tests {
             emitter.emit(Event::StreamStart).unwrap();
             emitter.emit(Event::DocumentStart).unwrap();
             emitter
-                .emit(Event::Scalar(Scalar {
-                    tag: None,
-                    value: "hello",
-                    style: ScalarStyle::Plain,
-                }))
+                .emit(Event::Scalar(Scalar::plain("hello")))
                 .unwrap();
             emitter.flush().unwrap();
             emitter.emit(Event::DocumentEnd).unwrap();
@@ -183,11 +140,10 @@ mod tests {
             emitter.emit(Event::StreamStart).unwrap();
             emitter.emit(Event::DocumentStart).unwrap();
             emitter
-                .emit(Event::Scalar(Scalar {
-                    tag: Some("!mytag".to_string()),
-                    value: "hello",
-                    style: ScalarStyle::Plain,
-                }))
+                .emit(Event::Scalar(
+                    Scalar::tagged("!mytag", "hello")
+                        .with_style(ScalarStyle::Plain),
+                ))
                 .unwrap();
             emitter.emit(Event::DocumentEnd).unwrap();
             emitter.emit(Event::StreamEnd).unwrap();
@@ -207,23 +163,13 @@ mod tests {
             emitter.emit(Event::StreamStart).unwrap();
             emitter.emit(Event::DocumentStart).unwrap();
             emitter
-                .emit(Event::SequenceStart(Sequence {
-                    tag: Some("!mytag".to_string()),
-                }))
+                .emit(Event::SequenceStart(Sequence::tagged("!mytag")))
                 .unwrap();
             emitter
-                .emit(Event::Scalar(Scalar {
-                    tag: None,
-                    value: "item1",
-                    style: ScalarStyle::Plain,
-                }))
+                .emit(Event::Scalar(Scalar::plain("item1")))
                 .unwrap();
             emitter
-                .emit(Event::Scalar(Scalar {
-                    tag: None,
-                    value: "item2",
-                    style: ScalarStyle::Plain,
-                }))
+                .emit(Event::Scalar(Scalar::plain("item2")))
                 .unwrap();
             emitter.emit(Event::SequenceEnd).unwrap();
             emitter.emit(Event::DocumentEnd).unwrap();
@@ -244,37 +190,15 @@ mod tests {
             emitter.emit(Event::StreamStart).unwrap();
             emitter.emit(Event::DocumentStart).unwrap();
             emitter
-                .emit(Event::MappingStart(Mapping {
-                    tag: Some("!mytag".to_string()),
-                }))
+                .emit(Event::MappingStart(Mapping::tagged("!mytag")))
                 .unwrap();
+            emitter.emit(Event::Scalar(Scalar::plain("key1"))).unwrap();
             emitter
-                .emit(Event::Scalar(Scalar {
-                    tag: None,
-                    value: "key1",
-                    style: ScalarStyle::Plain,
-                }))
+                .emit(Event::Scalar(Scalar::plain("value1")))
                 .unwrap();
+            emitter.emit(Event::Scalar(Scalar::plain("key2"))).unwrap();
             emitter
-                .emit(Event::Scalar(Scalar {
-                    tag: None,
-                    value: "value1",
-                    style: ScalarStyle::Plain,
-                }))
-                .unwrap();
-            emitter
-                .emit(Event::Scalar(Scalar {
-                    tag: None,
-                    value: "key2",
-                    style: ScalarStyle::Plain,
-                }))
-                .unwrap();
-            emitter
-                .emit(Event::Scalar(Scalar {
-                    tag: None,
-                    value: "value2",
-                    style: ScalarStyle::Plain,
-                }))
+                .emit(Event::Scalar(Scalar::plain("value2")))
                 .unwrap();
             emitter.emit(Event::MappingEnd).unwrap();
             emitter.emit(Event::DocumentEnd).unwrap();
@@ -295,7 +219,7 @@ mod tests {
             emitter.emit(Event::StreamStart).unwrap();
             emitter.emit(Event::DocumentStart).unwrap();
             emitter
-                .emit(Event::SequenceStart(Sequence { tag: None }))
+                .emit(Event::SequenceStart(Sequence::new()))
                 .unwrap();
             emitter.emit(Event::SequenceEnd).unwrap();
             emitter.emit(Event::DocumentEnd).unwrap();
@@ -315,9 +239,7 @@ mod tests {
             let mut emitter = Emitter::new(Box::new(&mut buffer));
             emitter.emit(Event::StreamStart).unwrap();
             emitter.emit(Event::DocumentStart).unwrap();
-            emitter
-                .emit(Event::MappingStart(Mapping { tag: None }))
-                .unwrap();
+            emitter.emit(Event::MappingStart(Mapping::new())).unwrap();
             emitter.emit(Event::MappingEnd).unwrap();
             emitter.emit(Event::DocumentEnd).unwrap();
             emitter.emit(Event::StreamEnd).unwrap();
@@ -337,17 +259,13 @@ mod tests {
             emitter.emit(Event::StreamStart).unwrap();
             emitter.emit(Event::DocumentStart).unwrap();
             emitter
-                .emit(Event::SequenceStart(Sequence { tag: None }))
+                .emit(Event::SequenceStart(Sequence::new()))
                 .unwrap();
             emitter
-                .emit(Event::SequenceStart(Sequence { tag: None }))
+                .emit(Event::SequenceStart(Sequence::new()))
                 .unwrap();
             emitter
-                .emit(Event::Scalar(Scalar {
-                    tag: None,
-                    value: "nested",
-                    style: ScalarStyle::Plain,
-                }))
+                .emit(Event::Scalar(Scalar::plain("nested")))
                 .unwrap();
             emitter.emit(Event::SequenceEnd).unwrap();
             emitter.emit(Event::SequenceEnd).unwrap();
@@ -368,42 +286,194 @@ mod tests {
             let mut emitter = Emitter::new(Box::new(&mut buffer));
             emitter.emit(Event::StreamStart).unwrap();
             emitter.emit(Event::DocumentStart).unwrap();
+            emitter.emit(Event::MappingStart(Mapping::new())).unwrap();
+            emitter.emit(Event::Scalar(Scalar::plain("key"))).unwrap();
+            emitter.emit(Event::MappingStart(Mapping::new())).unwrap();
             emitter
-                .emit(Event::MappingStart(Mapping { tag: None }))
+                .emit(Event::Scalar(Scalar::plain("nested_key")))
                 .unwrap();
             emitter
-                .emit(Event::Scalar(Scalar {
-                    tag: None,
-                    value: "key",
-                    style: ScalarStyle::Plain,
-                }))
+                .emit(Event::Scalar(Scalar::plain("nested_value")))
                 .unwrap();
+            emitter.emit(Event::MappingEnd).unwrap();
+            emitter.emit(Event::MappingEnd).unwrap();
+            emitter.emit(Event::DocumentEnd).unwrap();
+            emitter.emit(Event::StreamEnd).unwrap();
+        }
+        buffer.set_position(0);
+
+        let result =
+            String::from_utf8_lossy(&buffer.into_inner()).to_string();
+        assert_eq!(result, "key:\n  nested_key: nested_value\n");
+    }
+
+    #[test]
+    fn test_emitter_scalar_builders() {
+        let mut buffer = Cursor::new(Vec::with_capacity(100));
+        {
+            let mut emitter = Emitter::new(Box::new(&mut buffer));
+            emitter.emit(Event::StreamStart).unwrap();
+            emitter.emit(Event::DocumentStart).unwrap();
             emitter
-                .emit(Event::MappingStart(Mapping { tag: None }))
+                .emit(Event::SequenceStart(Sequence::new()))
                 .unwrap();
             emitter
-                .emit(Event::Scalar(Scalar {
-                    tag: None,
-                    value: "nested_key",
-                    style: ScalarStyle::Plain,
-                }))
+                .emit(Event::Scalar(Scalar::new("a").double_quoted()))
                 .unwrap();
             emitter
-                .emit(Event::Scalar(Scalar {
-                    tag: None,
-                    value: "nested_value",
-                    style: ScalarStyle::Plain,
-                }))
+                .emit(Event::Scalar(Scalar::new("b").single_quoted()))
                 .unwrap();
-            emitter.emit(Event::MappingEnd).unwrap();
-            emitter.emit(Event::MappingEnd).unwrap();
+            emitter
+                .emit(Event::Scalar(
+                    Scalar::tagged("!mytag", "c").double_quoted(),
+                ))
+                .unwrap();
+            emitter
+                .emit(Event::Scalar(Scalar::new("d\n").folded()))
+                .unwrap();
+            emitter.emit(Event::SequenceEnd).unwrap();
             emitter.emit(Event::DocumentEnd).unwrap();
             emitter.emit(Event::StreamEnd).unwrap();
         }
-        buffer.set_position(0);
 
         let result =
             String::from_utf8_lossy(&buffer.into_inner()).to_string();
-        assert_eq!(result, "key:\n  nested_key: nested_value\n");
+        assert_eq!(
+            result,
+            "- \"a\"\n- 'b'\n- !mytag \"c\"\n- >\n  d\n"
+        );
+    }
+
+    #[test]
+    fn test_emitter_into_inner_returns_writer() {
+        let mut emitter = Emitter::new(Vec::new());
+        emitter.emit(Event::StreamStart).unwrap();
+        emitter.emit(Event::DocumentStart).unwrap();
+        emitter.emit(Event::Scalar(Scalar::plain("a"))).unwrap();
+        emitter.emit(Event::DocumentEnd).unwrap();
+        emitter.emit(Event::StreamEnd).unwrap();
+        emitter.flush().unwrap();
+
+        let buffer: Vec<u8> = emitter.into_inner();
+        assert_eq!(buffer, b"a\n");
+    }
+
+    #[test]
+    fn test_scalar_builder_fields() {
+        let scalar = Scalar::plain("x").with_tag("!t");
+        assert_eq!(scalar.tag.as_deref(), Some("!t"));
+        assert_eq!(scalar.value, "x");
+        assert!(matches!(scalar.style, ScalarStyle::Plain));
+
+        let scalar = Scalar::new("x").literal();
+        assert!(scalar.tag.is_none());
+        assert!(matches!(scalar.style, ScalarStyle::Literal));
+
+        assert!(Sequence::default().tag.is_none());
+        assert_eq!(Mapping::tagged("!m").tag.as_deref(), Some("!m"));
+    }
+
+    #[test]
+    fn test_emitter_anchors_aliases_and_indent() {
+        let mut emitter = Emitter::new(Vec::new());
+        emitter.set_indent(4);
+        emitter.emit(Event::StreamStart).unwrap();
+        emitter.emit(Event::DocumentStart).unwrap();
+        emitter.emit(Event::MappingStart(Mapping::new())).unwrap();
+        emitter.emit(Event::Scalar(Scalar::plain("base"))).unwrap();
+        emitter
+            .emit(Event::MappingStart(Mapping::new().with_anchor("b")))
+            .unwrap();
+        emitter.emit(Event::Scalar(Scalar::plain("x"))).unwrap();
+        emitter
+            .emit(Event::Scalar(Scalar::plain("1").with_anchor("one")))
+            .unwrap();
+        emitter.emit(Event::MappingEnd).unwrap();
+        emitter.emit(Event::Scalar(Scalar::plain("copy"))).unwrap();
+        emitter.emit(Event::Alias("b")).unwrap();
+        emitter.emit(Event::Scalar(Scalar::plain("list"))).unwrap();
+        emitter
+            .emit(Event::SequenceStart(
+                Sequence::new().with_anchor("l"),
+            ))
+            .unwrap();
+        emitter.emit(Event::Alias("one")).unwrap();
+        emitter.emit(Event::SequenceEnd).unwrap();
+        emitter.emit(Event::MappingEnd).unwrap();
+        emitter.emit(Event::DocumentEnd).unwrap();
+        emitter.emit(Event::StreamEnd).unwrap();
+        emitter.flush().unwrap();
+
+        let result = String::from_utf8(emitter.into_inner()).unwrap();
+        assert_eq!(
+            result,
+            "base: &b\n    x: &one 1\ncopy: *b\nlist: &l\n- *one\n"
+        );
+    }
+
+    fn emit_document(mut emitter: Emitter<Vec<u8>>) -> String {
+        emitter.emit(Event::StreamStart).unwrap();
+        emitter.emit(Event::DocumentStart).unwrap();
+        emitter.emit(Event::MappingStart(Mapping::new())).unwrap();
+        emitter.emit(Event::Scalar(Scalar::plain("text"))).unwrap();
+        emitter
+            .emit(Event::Scalar(Scalar::plain(
+                "the quick brown fox jumps over the lazy dog",
+            )))
+            .unwrap();
+        emitter.emit(Event::Scalar(Scalar::plain("list"))).unwrap();
+        emitter.emit(Event::SequenceStart(Sequence::new())).unwrap();
+        emitter.emit(Event::Scalar(Scalar::plain("a"))).unwrap();
+        emitter
+            .emit(Event::Scalar(Scalar::tagged("!t", "b")))
+            .unwrap();
+        emitter.emit(Event::SequenceEnd).unwrap();
+        emitter.emit(Event::MappingEnd).unwrap();
+        emitter.emit(Event::DocumentEnd).unwrap();
+        emitter.emit(Event::StreamEnd).unwrap();
+        String::from_utf8(emitter.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_emitter_builder_width() {
+        let emitter =
+            EmitterBuilder::new().width(Some(20)).build(Vec::new());
+
+        let result = emit_document(emitter);
+
+        assert_eq!(
+            result,
+            "text: the quick brown\n  fox jumps over the lazy\n  dog\nlist:\n- a\n- !t b\n"
+        );
+        let value: serde_yml::Value =
+            serde_yml::from_str(&result).unwrap();
+        assert_eq!(
+            value["text"],
+            "the quick brown fox jumps over the lazy dog"
+        );
+    }
+
+    #[test]
+    fn test_emitter_builder_canonical() {
+        let emitter =
+            EmitterBuilder::new().canonical(true).build(Vec::new());
+
+        let result = emit_document(emitter);
+
+        assert_eq!(
+            result,
+            concat!(
+                "---\n",
+                "{\n",
+                "  ? \"text\"\n",
+                "  : \"the quick brown fox jumps over the lazy dog\",\n",
+                "  ? \"list\"\n",
+                "  : [\n",
+                "    \"a\",\n",
+                "    !t \"b\",\n",
+                "  ],\n",
+                "}\n",
+            )
+        );
     }
 }
@@ -18,3 +18,9 @@ pub mod test_macro_get_field;
 
 /// The `test_macro_from_number` module contains tests for the from_number module.
 pub mod test_macro_from_number;
+
+/// The `test_macro_yaml_config` module contains tests for the yaml_config module.
+pub mod test_macro_yaml_config;
+
+/// The `test_macro_untagged` module contains tests for the untagged module.
+pub mod test_macro_untagged;
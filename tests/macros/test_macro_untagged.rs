@@ -0,0 +1,251 @@
+/// Tests for the `untagged` macro.
+mod tests {
+    use serde::Deserialize;
+    use serde_yml::{
+        de::DeserializerOptions, untagged, value::TaggedValue,
+        Deserializer, Value,
+    };
+
+    untagged! {
+        #[derive(Debug, PartialEq)]
+        enum Item {
+            Number(i64),
+            Tagged(TaggedValue),
+            Text(String),
+        }
+    }
+
+    untagged! {
+        #[derive(Debug, PartialEq)]
+        enum Items {
+            One(Item),
+            Many(Vec<Item>),
+        }
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct A {
+        x: u8,
+        limit: f64,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct D {
+        x: String,
+    }
+
+    untagged! {
+        #[derive(Debug, PartialEq)]
+        enum Either {
+            A(A),
+            D(D),
+        }
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Document {
+        items: Vec<Item>,
+        count: u8,
+    }
+
+    #[test]
+    // Tests that the first variant matching the node is returned, with the
+    // tag of the node still visible to the variants
+    fn test_untagged_variants() {
+        let items: Vec<Item> =
+            serde_yml::from_str("[42, !env HOME, home]").unwrap();
+        assert_eq!(items[0], Item::Number(42));
+        assert!(
+            matches!(&items[1], Item::Tagged(tagged) if tagged.tag == "env")
+        );
+        assert_eq!(items[2], Item::Text("home".to_owned()));
+    }
+
+    #[test]
+    // Tests that nested untagged enums and aliases replay their events
+    fn test_untagged_nested() {
+        let yaml = "- &one 1\n- [*one, two]\n";
+        let items: Vec<Items> = serde_yml::from_str(yaml).unwrap();
+        assert_eq!(
+            items,
+            [
+                Items::One(Item::Number(1)),
+                Items::Many(vec![
+                    Item::Number(1),
+                    Item::Text("two".to_owned()),
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    // Tests that the error of an unmatched node lists every attempt,
+    // closest first, at the path of the node
+    fn test_untagged_error() {
+        // Arrange
+        let yaml = "items:\n  - 1\n  - {a: b}\ncount: 1\n";
+
+        // Act
+        let err = serde_yml::from_str::<Document>(yaml).unwrap_err();
+
+        // Assert
+        assert_eq!(err.path().unwrap().to_string(), "items[1]");
+        let message = err.to_string();
+        assert!(
+            message.starts_with(
+                "items.\\[1\\]: no variant of untagged enum Item matched; \
+                 closest attempts were: Number (invalid type: map, \
+                 expected i64 at line 3 column 5), Tagged (",
+            ),
+            "{}",
+            message,
+        );
+        let tagged = message.find("Tagged (").unwrap();
+        let text = message.find("Text (").unwrap();
+        assert!(tagged < text, "{}", message);
+    }
+
+    #[test]
+    // Tests that the error of each attempt names its path below the node
+    fn test_untagged_error_relative_path() {
+        // Act
+        let err = serde_yml::from_str::<Vec<Items>>("- [1, {a: b}]\n")
+            .unwrap_err();
+
+        // Assert
+        let message = err.to_string();
+        assert!(
+            message.starts_with(
+                "\\[0\\]: no variant of untagged enum Items matched; \
+                 closest attempts were: Many (\\[1\\]: no variant of \
+                 untagged enum Item matched; closest attempts were: \
+                 Number (invalid type: map, expected i64 at line 1 column 7)",
+            ),
+            "{}",
+            message,
+        );
+    }
+
+    #[test]
+    // Tests that enums keeping `#[serde(untagged)]` are still buffered by
+    // serde, whose error does not say why each variant failed
+    fn test_serde_untagged_unchanged() {
+        #[derive(Deserialize, Debug)]
+        #[serde(untagged)]
+        #[allow(dead_code)]
+        enum Plain {
+            Number(i64),
+            Text(String),
+        }
+
+        let err = serde_yml::from_str::<Plain>("[1]").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "data did not match any variant of untagged enum Plain",
+        );
+    }
+
+    #[test]
+    // Tests that deserializers other than this crate's try the variants on
+    // a buffered copy of the input
+    fn test_untagged_from_value() {
+        let item: Item = serde_yml::from_value(Value::from(7)).unwrap();
+        assert_eq!(item, Item::Number(7));
+
+        let items: Items =
+            serde_yml::from_value(serde_yml::from_str("[a]").unwrap())
+                .unwrap();
+        assert_eq!(
+            items,
+            Items::Many(vec![Item::Text("a".to_owned())])
+        );
+
+        let err = serde_yml::from_value::<Item>(Value::from(vec![1]))
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("no variant of untagged enum Item matched"));
+    }
+
+    #[test]
+    // Tests that variants failing while errors are collected are not
+    // reported, only the node that no variant matches
+    fn test_untagged_collect_errors() {
+        // Arrange
+        let yaml = "items: [1, [x], y]\ncount: many\n";
+        let options = DeserializerOptions {
+            collect_errors: true,
+            ..DeserializerOptions::default()
+        };
+
+        // Act
+        let err = Document::deserialize(
+            Deserializer::from_str(yaml).with_options(options),
+        )
+        .unwrap_err();
+
+        // Assert
+        let paths: Vec<String> = err
+            .errors()
+            .iter()
+            .map(|err| err.path().unwrap().to_string())
+            .collect();
+        assert_eq!(paths, ["items[1]", "count"]);
+    }
+
+    #[test]
+    // Tests that variant payload types may share the names of the type
+    // parameters used inside the macro
+    fn test_untagged_payload_named_like_generics() {
+        let values: Vec<Either> =
+            serde_yml::from_str("[{x: 1, limit: 2}, {x: one}]")
+                .unwrap();
+        assert_eq!(
+            values,
+            [
+                Either::A(A { x: 1, limit: 2.0 }),
+                Either::D(D {
+                    x: "one".to_owned()
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    // Tests that the warnings recorded by failed attempts are dropped,
+    // leaving only those of the variant that matched
+    fn test_untagged_failed_attempts_leave_no_warnings() {
+        use serde_yml::de::WarningKind;
+
+        // Arrange
+        let yaml = "limit: 99999999999999999999\nx: one\n";
+
+        // Act
+        let (value, unused) =
+            serde_yml::from_str_with_unused::<Either>(yaml).unwrap();
+        let output =
+            serde_yml::from_str_with_warnings::<Either>(yaml).unwrap();
+        let (styled, _) =
+            serde_yml::from_str_with_styles::<Either>(yaml).unwrap();
+
+        // Assert
+        let expected = Either::D(D {
+            x: "one".to_owned(),
+        });
+        assert_eq!(value, expected);
+        assert_eq!(output.value, expected);
+        assert_eq!(styled, expected);
+        let unused: Vec<String> =
+            unused.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            unused,
+            ["limit: value is not used at line 1 column 8"]
+        );
+        let kinds: Vec<WarningKind> = output
+            .warnings
+            .iter()
+            .map(|warning| warning.kind)
+            .collect();
+        assert_eq!(kinds, [WarningKind::UnusedValue]);
+    }
+}
@@ -0,0 +1,86 @@
+/// Tests for the `yaml_config` macro.
+mod tests {
+    use serde::Deserialize;
+    use serde_yml::{yaml_config, ErrorKind};
+    use std::{env, fs};
+    use tempfile::tempdir;
+
+    yaml_config! {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct ServerConfig {
+            host: String = "localhost".to_owned();
+            password: String = String::new(),
+                env "SERDE_YML_TEST_CONFIG_PASSWORD";
+            port: u16 = 8080, env "SERDE_YML_TEST_CONFIG_PORT";
+            tags: Vec<String> = vec!["web".to_owned()],
+                env "SERDE_YML_TEST_CONFIG_TAGS";
+        }
+    }
+
+    /// Test that missing keys take the field defaults.
+    #[test]
+    fn test_yaml_config_defaults() {
+        let config = ServerConfig::default();
+        assert_eq!(config.host, "localhost");
+        assert_eq!(config.port, 8080);
+        assert_eq!(config.tags, ["web"]);
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        fs::write(&path, "host: example.com\n").unwrap();
+        let config = ServerConfig::load(&path).unwrap();
+        assert_eq!(
+            config,
+            ServerConfig {
+                host: "example.com".to_owned(),
+                ..ServerConfig::default()
+            }
+        );
+    }
+
+    /// Test that environment variables override the file, that their
+    /// values are read as YAML and that strings take them as they are.
+    #[test]
+    fn test_yaml_config_env_override() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        fs::write(&path, "port: 80\ntags: [a]\n").unwrap();
+        env::set_var("SERDE_YML_TEST_CONFIG_TAGS", "[b, c]");
+        env::set_var("SERDE_YML_TEST_CONFIG_PASSWORD", "#p@ss: *word");
+        let config = ServerConfig::load(&path);
+        env::remove_var("SERDE_YML_TEST_CONFIG_TAGS");
+        env::remove_var("SERDE_YML_TEST_CONFIG_PASSWORD");
+        let config = config.unwrap();
+        assert_eq!(config.port, 80);
+        assert_eq!(config.tags, ["b", "c"]);
+        assert_eq!(config.password, "#p@ss: *word");
+    }
+
+    /// Test that errors name the file, line and column.
+    #[test]
+    fn test_yaml_config_errors() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        fs::write(&path, "host: a\nport: high\n").unwrap();
+        let err = ServerConfig::load(&path).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidType);
+        assert_eq!(
+            err.file(),
+            Some(path.display().to_string().as_str())
+        );
+        let location = err.location().unwrap();
+        assert_eq!((location.line(), location.column()), (2, 7));
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "{}: port: invalid type: string \"high\", expected u16 at line 2 column 7",
+                path.display(),
+            ),
+        );
+
+        let err =
+            ServerConfig::load("tests/data/missing.yaml").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Io);
+        assert_eq!(err.file(), Some("tests/data/missing.yaml"));
+    }
+}
@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use serde_yml::Value;
+    use std::collections::BTreeMap;
+
+    #[test]
+    // Tests that the selected backend emits the same layout for nested data
+    fn test_backend_emits_block_layout() {
+        // Arrange
+        let mut map = BTreeMap::new();
+        map.insert("name", vec!["a: b", "", "plain"]);
+        map.insert("empty", vec![]);
+
+        // Act
+        let yaml = serde_yml::to_string(&map).unwrap();
+
+        // Assert
+        let expected = indoc! {"
+            empty: []
+            name:
+            - 'a: b'
+            - ''
+            - plain
+        "};
+        assert_eq!(yaml, expected);
+    }
+
+    #[test]
+    // Tests that anchors, aliases and block scalars parse the same on every backend
+    fn test_backend_parses_aliases_and_block_scalars() {
+        // Arrange
+        let yaml = indoc! {"
+            base: &base
+              text: |
+                line one
+                line two
+            copy: *base
+        "};
+
+        // Act
+        let value: Value = serde_yml::from_str(yaml).unwrap();
+
+        // Assert
+        assert_eq!(value["base"], value["copy"]);
+        assert_eq!(value["copy"]["text"], "line one\nline two\n");
+    }
+
+    #[test]
+    // Tests that scanner errors keep the libyaml wording and positions
+    fn test_backend_scanner_error_message() {
+        // Arrange
+        let yaml = "key: value\n@invalid";
+
+        // Act
+        let error = serde_yml::from_str::<Value>(yaml).unwrap_err();
+
+        // Assert
+        assert_eq!(
+            error.to_string(),
+            "found character that cannot start any token at line 2 column 1, while scanning for the next token"
+        );
+    }
+}
@@ -0,0 +1,206 @@
+#[cfg(test)]
+mod tests {
+    use serde_yml::{capabilities, Value};
+
+    #[test]
+    // Tests that the reported capabilities match the behaviour of the build
+    fn test_capabilities_match_behaviour() {
+        // Arrange
+        let capabilities = capabilities();
+
+        // Act
+        let word: Value = serde_yml::from_str("yes").unwrap();
+        let mut merged: Value =
+            serde_yml::from_str("base: &b {a: 1}\nuse: {<<: *b}")
+                .unwrap();
+        let merge = merged.apply_merge();
+        let shared = Value::String("x".to_owned());
+        let emitted =
+            serde_yml::to_string(&vec![shared.clone(), shared])
+                .unwrap();
+
+        // Assert
+        assert_eq!(capabilities.yaml_version, "1.2");
+        assert_eq!(
+            capabilities.yaml_1_2_core_schema,
+            word == Value::String("yes".to_owned())
+        );
+        assert_eq!(capabilities.merge_keys, merge.is_ok());
+        assert_eq!(
+            capabilities.anchors_on_parse,
+            merged["use"]["a"] == 1
+        );
+        assert_eq!(capabilities.anchors_on_emit, emitted.contains('&'));
+        assert!(!capabilities.comment_preservation);
+    }
+
+    #[test]
+    // Tests that big integers are reported as kept exactly when an
+    // integer beyond the range of `u128` survives a round trip
+    fn test_capabilities_big_integers() {
+        // Arrange
+        let huge = "340282366920938463463374607431768211456";
+
+        // Act
+        let value: Value = serde_yml::from_str(huge).unwrap();
+        let written = serde_yml::to_string(&value).unwrap();
+
+        // Assert
+        assert_eq!(
+            capabilities().big_integers,
+            written == format!("{}\n", huge)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    // Tests that a build reporting `!include` and JSON Schema
+    // validation resolves included files and checks documents against a
+    // schema
+    fn test_capabilities_include_and_validation() {
+        use serde_yml::include::{DirResolver, Includer};
+
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("main.yaml"),
+            "port: !include port.yaml\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("port.yaml"), "8080").unwrap();
+        let schema: Value = serde_yml::from_str(
+            "{type: object, properties: {port: {type: string}}}",
+        )
+        .unwrap();
+
+        // Act
+        let mut includer = Includer::new(DirResolver::new(dir.path()));
+        let value: Value = includer.from_path("main.yaml").unwrap();
+        let violations = serde_yml::validate::validate(&schema, &value);
+
+        // Assert
+        assert!(capabilities().include_tag);
+        assert!(capabilities().json_schema_validation);
+        assert_eq!(value["port"], 8080);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].keyword, "type");
+    }
+
+    #[test]
+    #[cfg(feature = "compact")]
+    // Tests that a build reporting `CompactValue` converts values to it
+    fn test_capabilities_compact_value() {
+        use serde_yml::value::CompactValue;
+
+        let value: Value = serde_yml::from_str("a: [1, b]").unwrap();
+        let compact = CompactValue::from(value.clone());
+
+        assert!(capabilities().compact_value);
+        assert_eq!(Value::from(compact), value);
+    }
+
+    #[test]
+    #[cfg(feature = "serde_json")]
+    // Tests that a build reporting JSON conversion converts YAML to
+    // JSON
+    fn test_capabilities_json_conversion() {
+        let json =
+            serde_yml::convert::yaml_to_json("a: [1, b]").unwrap();
+
+        assert!(capabilities().json_conversion);
+        assert_eq!(json, r#"{"a":[1,"b"]}"#);
+    }
+
+    #[test]
+    #[cfg(feature = "toml")]
+    // Tests that a build reporting TOML conversion converts values to
+    // TOML
+    fn test_capabilities_toml_conversion() {
+        use serde_yml::convert::toml::to_toml_value;
+
+        let value: Value = serde_yml::from_str("a: 1").unwrap();
+        let toml = to_toml_value(&value).unwrap();
+
+        assert!(capabilities().toml_conversion);
+        assert_eq!(toml["a"].as_integer(), Some(1));
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "uuid",
+        feature = "chrono",
+        feature = "time",
+        feature = "humantime",
+    ))]
+    // Tests that a build reporting the `with` modules of third-party
+    // types reads each of those types
+    fn test_capabilities_with_modules() {
+        #[derive(serde::Deserialize)]
+        struct Record {
+            #[serde(with = "serde_yml::with::uuid")]
+            id: ::uuid::Uuid,
+            #[serde(with = "serde_yml::with::chrono_datetime")]
+            created: ::chrono::DateTime<::chrono::Utc>,
+            #[serde(with = "serde_yml::with::time_offsetdatetime")]
+            updated: ::time::OffsetDateTime,
+            #[serde(with = "serde_yml::with::duration_humantime")]
+            ttl: std::time::Duration,
+        }
+
+        let record: Record = serde_yml::from_str(
+            "id: 67e55044-10b1-426f-9247-bb680e5fe0c8\n\
+             created: 2024-05-01T12:30:00Z\n\
+             updated: 2024-05-01T12:30:00Z\n\
+             ttl: 1h 30m\n",
+        )
+        .unwrap();
+
+        let capabilities = capabilities();
+        assert!(capabilities.uuid);
+        assert!(capabilities.chrono);
+        assert!(capabilities.time);
+        assert!(capabilities.humantime);
+        assert_eq!(record.id.get_version_num(), 4);
+        assert_eq!(record.created.timestamp(), 1_714_566_600);
+        assert_eq!(record.updated.unix_timestamp(), 1_714_566_600);
+        assert_eq!(record.ttl.as_secs(), 5_400);
+    }
+
+    #[test]
+    #[cfg(feature = "miette")]
+    // Tests that a build reporting miette diagnostics gives errors a
+    // source span
+    fn test_capabilities_miette_diagnostics() {
+        use miette::Diagnostic;
+
+        let err = serde_yml::from_str::<Value>("a: [").unwrap_err();
+
+        assert!(capabilities().miette_diagnostics);
+        assert!(err.labels().is_some());
+    }
+
+    #[test]
+    #[cfg(not(any(
+        feature = "std",
+        feature = "bigint",
+        feature = "compact",
+        feature = "uuid",
+        feature = "chrono",
+    )))]
+    // Tests that a build without default features reports none of the
+    // optional capabilities
+    fn test_capabilities_no_default_features() {
+        let capabilities = capabilities();
+        assert!(!capabilities.include_tag);
+        assert!(!capabilities.json_schema_validation);
+        assert!(!capabilities.big_integers);
+        assert!(!capabilities.compact_value);
+        assert!(!capabilities.json_conversion);
+        assert!(!capabilities.toml_conversion);
+        assert!(!capabilities.uuid);
+        assert!(!capabilities.chrono);
+        assert!(!capabilities.time);
+        assert!(!capabilities.humantime);
+        assert!(!capabilities.miette_diagnostics);
+    }
+}
@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_yml::compat::serde_yaml::{self, Mapping, Value};
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    enum Action {
+        Stop,
+        Restart { delay: u32 },
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Step {
+        name: String,
+        #[serde(with = "serde_yaml::with::singleton_map")]
+        action: Action,
+    }
+
+    #[test]
+    // Tests that code written against serde_yaml 0.9 paths round-trips data
+    fn test_serde_yaml_round_trip() {
+        // Arrange
+        let steps = vec![
+            Step {
+                name: "a".to_owned(),
+                action: Action::Stop,
+            },
+            Step {
+                name: "b".to_owned(),
+                action: Action::Restart { delay: 5 },
+            },
+        ];
+
+        // Act
+        let yaml = serde_yaml::to_string(&steps).unwrap();
+        let from_str: Vec<Step> = serde_yaml::from_str(&yaml).unwrap();
+        let from_slice: Vec<Step> =
+            serde_yaml::from_slice(yaml.as_bytes()).unwrap();
+        let from_reader: Vec<Step> =
+            serde_yaml::from_reader(yaml.as_bytes()).unwrap();
+        let value = serde_yaml::to_value(&steps).unwrap();
+
+        // Assert
+        assert_eq!(yaml, serde_yml::to_string(&steps).unwrap());
+        assert_eq!(from_str, steps);
+        assert_eq!(from_slice, steps);
+        assert_eq!(from_reader, steps);
+        assert_eq!(value[1]["action"]["Restart"]["delay"], 5);
+        assert_eq!(
+            serde_yaml::from_value::<Vec<Step>>(value).unwrap(),
+            steps
+        );
+    }
+
+    #[test]
+    // Tests the value, mapping, tag and error types under their serde_yaml paths
+    fn test_serde_yaml_types() {
+        // Arrange
+        let yaml =
+            "base: &base {a: 1}\nderived:\n  <<: *base\n  b: !Tag 2\n";
+
+        // Act
+        let mut value: Value = serde_yaml::from_str(yaml).unwrap();
+        value.apply_merge().unwrap();
+        let mut mapping = Mapping::new();
+        if let serde_yaml::mapping::Entry::Vacant(entry) =
+            mapping.entry(Value::from("k"))
+        {
+            let _ = entry.insert(Value::from(1));
+        }
+        let documents: Vec<Value> =
+            serde_yaml::Deserializer::from_str("1\n---\n2\n")
+                .map(Value::deserialize)
+                .collect::<serde_yaml::Result<_>>()
+                .unwrap();
+        let error: serde_yaml::Error =
+            serde_yaml::from_str::<Value>("a: [1").unwrap_err();
+        let location: serde_yaml::Location = error.location().unwrap();
+
+        // Assert
+        assert_eq!(value["derived"]["a"], 1);
+        match &value["derived"]["b"] {
+            Value::Tagged(tagged) => {
+                let tagged: &serde_yaml::value::TaggedValue = tagged;
+                assert_eq!(
+                    tagged.tag,
+                    serde_yaml::value::Tag::new("Tag")
+                );
+            }
+            other => {
+                panic!("expected a tagged value, found {:?}", other)
+            }
+        }
+        assert_eq!(mapping["k"], 1);
+        assert_eq!(documents, [Value::from(1), Value::from(2)]);
+        assert_eq!(location.line(), 2);
+    }
+}
@@ -0,0 +1,134 @@
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_yml::convert::{json_to_yaml, transcode, yaml_to_json};
+    use serde_yml::Value;
+
+    #[test]
+    // Tests converting YAML with anchors and tags into JSON
+    fn test_yaml_to_json() {
+        // Arrange
+        let yaml = "base: &b {port: 80, tls: false}\nservers:\n- *b\n- !Custom [1.5, ~, 'x']\n";
+
+        // Act
+        let json = yaml_to_json(yaml).unwrap();
+
+        // Assert
+        assert_eq!(
+            json,
+            r#"{"base":{"port":80,"tls":false},"servers":[{"port":80,"tls":false},{"!Custom":[1.5,null,"x"]}]}"#
+        );
+        let value: Value = serde_yml::from_str(yaml).unwrap();
+        assert_eq!(json, serde_json::to_string(&value).unwrap());
+    }
+
+    #[test]
+    // Tests converting JSON into YAML formatted like `to_string`
+    fn test_json_to_yaml() {
+        // Arrange
+        let json = r#"{"name": "demo", "ports": [80, 443], "extra": {"a": null, "b": -1.25}}"#;
+
+        // Act
+        let yaml = json_to_yaml(json).unwrap();
+
+        // Assert
+        let value: Value = serde_json::from_str(json).unwrap();
+        assert_eq!(yaml, serde_yml::to_string(&value).unwrap());
+        assert_eq!(
+            yaml_to_json(&yaml).unwrap(),
+            serde_json::to_string(&value).unwrap()
+        );
+    }
+
+    #[test]
+    // Tests that invalid input and unrepresentable data are reported
+    fn test_convert_errors() {
+        // Arrange
+        let invalid_yaml = "a: [1";
+        let sequence_key = "? [1, 2]\n: x\n";
+
+        // Act
+        let invalid = yaml_to_json(invalid_yaml).unwrap_err();
+        let unrepresentable = yaml_to_json(sequence_key).unwrap_err();
+        let trailing = json_to_yaml("{} {}").unwrap_err();
+
+        // Assert
+        let expected = serde_yml::from_str::<Value>(invalid_yaml)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(invalid.to_string(), expected);
+        assert!(unrepresentable
+            .to_string()
+            .contains("key must be a string"));
+        assert!(trailing.to_string().contains("trailing characters"));
+    }
+
+    #[test]
+    // Tests transcoding between this crate's deserializer and serializer
+    fn test_transcode_yaml_to_yaml() {
+        // Arrange
+        let yaml = "a: 1\nb:\n- x\n- c: true\n";
+        let mut output = Vec::new();
+
+        // Act
+        transcode(
+            serde_yml::Deserializer::from_str(yaml),
+            &mut serde_yml::Serializer::new(&mut output),
+        )
+        .unwrap();
+
+        // Assert
+        assert_eq!(String::from_utf8(output).unwrap(), yaml);
+    }
+}
+
+#[cfg(all(test, feature = "toml"))]
+mod toml_tests {
+    use serde_yml::convert::toml::{from_toml_value, to_toml_value};
+    use serde_yml::Value;
+
+    #[test]
+    // Tests a round trip of every kind of TOML value through YAML
+    fn test_toml_round_trip() {
+        // Arrange
+        let toml: toml::Value = toml::from_str(
+            "title = 'demo'\nratio = 0.5\nenabled = true\nwhen = 1979-05-27T07:32:00Z\n[[servers]]\nport = 80\n",
+        )
+        .unwrap();
+
+        // Act
+        let value = from_toml_value(toml.clone());
+        let yaml = serde_yml::to_string(&value).unwrap();
+        let reparsed: Value = serde_yml::from_str(&yaml).unwrap();
+
+        // Assert
+        assert_eq!(value["servers"][0]["port"], 80);
+        assert_eq!(to_toml_value(&reparsed).unwrap(), toml);
+    }
+
+    #[test]
+    // Tests that values without a TOML equivalent are rejected with a path
+    fn test_to_toml_value_errors() {
+        for (yaml, expected) in [
+            (
+                "a: [1, 18446744073709551615]",
+                "a[1]: integer 18446744073709551615 is out of range for TOML",
+            ),
+            (
+                "{1: x}",
+                "mapping key that is not a string has no TOML equivalent",
+            ),
+            ("a: !Point [1, 2]", "a: tag !Point has no TOML equivalent"),
+            ("a: !datetime yesterday", "a: invalid datetime `yesterday`: "),
+            ("~", "null has no TOML equivalent"),
+        ] {
+            // Arrange
+            let value: Value = serde_yml::from_str(yaml).unwrap();
+
+            // Act
+            let error = to_toml_value(&value).unwrap_err();
+
+            // Assert
+            assert!(error.to_string().starts_with(expected), "{}", error);
+        }
+    }
+}
@@ -354,6 +354,68 @@ fn test_de_mapping() {
     test_de(yaml, &expected);
 }
 
+#[test]
+fn test_de_complex_keys() {
+    use std::collections::HashMap;
+
+    #[derive(
+        Debug, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord,
+    )]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+    enum Shape {
+        Dot,
+        Circle(u32),
+        Rect { w: u32, h: u32 },
+    }
+
+    let yaml = indoc! {"
+        ? [web, 80]
+        : http
+        ? [web, 443]
+        : https
+    "};
+    let mut expected = HashMap::new();
+    expected.insert(("web".to_owned(), 80u16), "http".to_owned());
+    expected.insert(("web".to_owned(), 443), "https".to_owned());
+    test_de(yaml, &expected);
+
+    let yaml = indoc! {"
+        ? x: 1
+          y: 2
+        : a
+        ? {x: 0, y: 0}
+        : origin
+    "};
+    let mut expected = BTreeMap::new();
+    expected.insert(Point { x: 1, y: 2 }, "a".to_owned());
+    expected.insert(Point { x: 0, y: 0 }, "origin".to_owned());
+    test_de(yaml, &expected);
+
+    let yaml = indoc! {"
+        Dot: 0
+        !Circle 3: 1
+        ? !Rect {w: 2, h: 1}
+        : 2
+    "};
+    let mut expected = BTreeMap::new();
+    expected.insert(Shape::Dot, 0);
+    expected.insert(Shape::Circle(3), 1);
+    expected.insert(Shape::Rect { w: 2, h: 1 }, 2);
+    test_de(yaml, &expected);
+
+    let yaml = "? [[1, 2], {a: b}]\n: nested\n";
+    let mut key = BTreeMap::new();
+    key.insert("a".to_owned(), "b".to_owned());
+    let mut expected = BTreeMap::new();
+    expected.insert((vec![1, 2], key), "nested".to_owned());
+    test_de(yaml, &expected);
+}
+
 #[test]
 fn test_byte_order_mark() {
     let yaml = "\u{feff}- 0\n";
@@ -830,3 +892,1131 @@ fn test_seed() {
     let expected = "seed_value".to_string();
     test_de_seed(yaml, MySeed, &expected);
 }
+
+#[test]
+/// Test deserialization of single-character scalars into `char`.
+fn test_char() {
+    test_de("x", &'x');
+    test_de("'7'", &'7');
+    test_de("\"\u{e9}\"", &'\u{e9}');
+}
+
+#[test]
+/// Test composing a base character and combining mark into a `char`.
+fn test_char_compose_graphemes() {
+    use serde::Deserialize;
+    use serde_yml::de::DeserializerOptions;
+
+    let options = DeserializerOptions {
+        compose_graphemes: true,
+        ..DeserializerOptions::default()
+    };
+
+    let de = Deserializer::from_str("e\u{301}")
+        .with_options(options.clone());
+    assert_eq!(char::deserialize(de).unwrap(), '\u{e9}');
+
+    let de = Deserializer::from_str("C\u{327}")
+        .with_options(options.clone());
+    assert_eq!(char::deserialize(de).unwrap(), '\u{c7}');
+
+    let de = Deserializer::from_str("x\u{301}").with_options(options);
+    assert!(char::deserialize(de).is_err());
+
+    assert!(serde_yml::from_str::<char>("e\u{301}").is_err());
+}
+
+#[test]
+/// Test the treatment of empty documents in multi-document streams.
+fn test_empty_documents_option() {
+    use serde::Deserialize;
+    use serde_yml::de::{DeserializerOptions, EmptyDocuments};
+
+    let yaml = "---\n--- # comment\n---\nfoo: 1\n--- ~\n--- ''\n---\n";
+    let documents = |empty_documents| {
+        let options = DeserializerOptions {
+            empty_documents,
+            ..DeserializerOptions::default()
+        };
+        Deserializer::from_str(yaml)
+            .with_options(options)
+            .map(|de| {
+                Value::deserialize(de).map_err(|err| err.to_string())
+            })
+            .collect::<Vec<_>>()
+    };
+    let foo = Value::Mapping(
+        [(Value::from("foo"), Value::from(1))].into_iter().collect(),
+    );
+
+    assert_eq!(EmptyDocuments::default(), EmptyDocuments::Null);
+    assert_eq!(
+        documents(EmptyDocuments::Null),
+        [
+            Ok(Value::Null),
+            Ok(Value::Null),
+            Ok(foo.clone()),
+            Ok(Value::Null),
+            Ok(Value::from("")),
+            Ok(Value::Null),
+        ],
+    );
+    assert_eq!(
+        documents(EmptyDocuments::Skip),
+        [Ok(foo.clone()), Ok(Value::Null), Ok(Value::from(""))],
+    );
+    assert_eq!(
+        documents(EmptyDocuments::Error),
+        [
+            Err("empty document at line 2 column 1".to_owned()),
+            Err("empty document at line 3 column 1".to_owned()),
+            Ok(foo),
+            Ok(Value::Null),
+            Ok(Value::from("")),
+            Err("empty document at line 8 column 1".to_owned()),
+        ],
+    );
+}
+
+#[test]
+/// Test rejecting unknown keys for every struct through the options.
+fn test_deny_unknown_fields_option() {
+    use serde::Deserialize;
+    use serde_yml::de::DeserializerOptions;
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Inner {
+        port: u16,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Outer {
+        name: String,
+        inner: Inner,
+    }
+
+    let options = DeserializerOptions {
+        deny_unknown_fields: true,
+        ..DeserializerOptions::default()
+    };
+    let from_str = |yaml| {
+        Outer::deserialize(
+            Deserializer::from_str(yaml).with_options(options.clone()),
+        )
+    };
+
+    let yaml = "name: api\ninner:\n  port: 80\n";
+    let expected = Outer {
+        name: "api".to_owned(),
+        inner: Inner { port: 80 },
+    };
+    assert_eq!(from_str(yaml).unwrap(), expected);
+
+    let yaml = "name: api\ninner:\n  port: 80\n  prot: tcp\n";
+    assert_eq!(
+        from_str(yaml).unwrap_err().to_string(),
+        "inner.prot: unknown field `prot`, expected `port` at line 4 column 3",
+    );
+
+    let yaml = "name: api\nextra: 1\ninner: {port: 80}\n";
+    assert_eq!(
+        from_str(yaml).unwrap_err().to_string(),
+        "extra: unknown field `extra`, expected `name` or `inner` at line 2 column 1",
+    );
+
+    // Without the option unknown keys are ignored as before.
+    let value: Outer = serde_yml::from_str(yaml).unwrap();
+    assert_eq!(value, expected);
+}
+
+#[test]
+/// Test reporting keys that were not consumed by the target type.
+fn test_from_str_with_unused() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Server {
+        host: String,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Config {
+        retries: u32,
+        servers: Vec<Server>,
+    }
+
+    let yaml = indoc! {"
+        retries: 3
+        retrys: 5
+        servers:
+          - host: a
+            prot: 80
+          - host: b
+        extra:
+          nested: [1, 2]
+    "};
+
+    let (config, unused) =
+        serde_yml::from_str_with_unused::<Config>(yaml).unwrap();
+    assert_eq!(config.retries, 3);
+    assert_eq!(config.servers.len(), 2);
+    let unused: Vec<String> =
+        unused.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        unused,
+        [
+            "retrys: value is not used at line 2 column 9",
+            "servers[0].prot: value is not used at line 5 column 11",
+            "extra: value is not used at line 8 column 3",
+        ]
+    );
+
+    let (_, unused) = serde_yml::from_str_with_unused::<Config>(
+        "retries: 1\nservers: []",
+    )
+    .unwrap();
+    assert!(unused.is_empty());
+}
+
+#[test]
+/// Test that a node with two tags is reported as such rather than as a
+/// generic syntax error or a second document.
+fn test_tag_chain_error() {
+    let cases = [
+        (
+            "k: !outer !inner x",
+            "found tag `!inner` after tag `!outer`; YAML allows only one tag per node at line 1 column 11",
+        ),
+        (
+            "- !a &anchor !b [1]",
+            "found tag `!b` after tag `!a`; YAML allows only one tag per node at line 1 column 14",
+        ),
+        (
+            "!a !b x",
+            "found tag `!b` after tag `!a`; YAML allows only one tag per node at line 1 column 4",
+        ),
+    ];
+    for (yaml, expected) in cases {
+        let err = serde_yml::from_str::<Value>(yaml).unwrap_err();
+        assert_eq!(err.to_string(), expected);
+    }
+}
+
+#[test]
+/// Test keeping the first tag of nodes with several tags.
+fn test_from_str_lenient_tags() {
+    use serde::Deserialize as _;
+    use serde_yml::de::DeserializerOptions;
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    enum Shape {
+        Circle { radius: u32 },
+        Point(i32, i32),
+    }
+
+    let yaml = indoc! {"
+        - !Circle !v1 {radius: 2}
+        - !Point &p !old !older [1, 2]
+        - *p
+    "};
+    let (shapes, dropped) =
+        serde_yml::from_str_lenient_tags::<Vec<Shape>>(yaml).unwrap();
+    assert_eq!(
+        shapes,
+        [
+            Shape::Circle { radius: 2 },
+            Shape::Point(1, 2),
+            Shape::Point(1, 2),
+        ]
+    );
+    let dropped: Vec<String> =
+        dropped.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        dropped,
+        [
+            "dropped tag `!v1` after tag `!Circle` at line 1 column 11",
+            "dropped tag `!old` after tag `!Point` at line 2 column 13",
+            "dropped tag `!older` after tag `!Point` at line 2 column 18",
+        ]
+    );
+
+    let (value, dropped) =
+        serde_yml::from_str_lenient_tags::<Value>("a: !x 1").unwrap();
+    assert_eq!(
+        value["a"],
+        serde_yml::from_str::<Value>("!x 1").unwrap()
+    );
+    assert!(dropped.is_empty());
+
+    let options = DeserializerOptions {
+        lenient_tags: true,
+        ..DeserializerOptions::default()
+    };
+    let values = Deserializer::from_str("!a !b 1\n---\n!c !d 2\n")
+        .with_options(options)
+        .map(Value::deserialize)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(values.len(), 2);
+}
+
+#[test]
+/// Test accepting numbers written as strings when the target type expects a
+/// number.
+fn test_lenient_numbers_option() {
+    use serde::Deserialize as _;
+    use serde_yml::de::DeserializerOptions;
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Limits {
+        port: u16,
+        size: u64,
+        offset: i32,
+        big: u128,
+        ratio: f32,
+        name: String,
+    }
+
+    let yaml = indoc! {"
+        port: '8080'
+        size: 1_000_000
+        offset: \"-0x10\"
+        big: ' 0b1_0000 '
+        ratio: '1_0.5'
+        name: '42'
+    "};
+    let options = DeserializerOptions {
+        lenient_numbers: true,
+        ..DeserializerOptions::default()
+    };
+    let limits = Limits::deserialize(
+        Deserializer::from_str(yaml).with_options(options.clone()),
+    )
+    .unwrap();
+    assert_eq!(
+        limits,
+        Limits {
+            port: 8080,
+            size: 1_000_000,
+            offset: -16,
+            big: 16,
+            ratio: 10.5,
+            name: "42".to_owned(),
+        }
+    );
+
+    // Malformed numbers are still rejected.
+    let err = u64::deserialize(
+        Deserializer::from_str("1__000").with_options(options),
+    )
+    .unwrap_err();
+    assert!(err.to_string().starts_with("invalid type: string"));
+
+    // Without the option numeric strings are rejected as before.
+    let err = serde_yml::from_str::<Limits>(yaml).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "port: invalid type: string \"8080\", expected u16 at line 1 column 7"
+    );
+}
+
+#[test]
+/// Test rejecting lines that end with a lone carriage return.
+fn test_reject_cr_line_endings() {
+    use serde::Deserialize as _;
+    use serde_yml::de::DeserializerOptions;
+
+    let yaml = "a: 1\r\nb: 2\r\nc: 3\rd: 4\r";
+    let options = DeserializerOptions {
+        reject_cr_line_endings: true,
+        ..DeserializerOptions::default()
+    };
+
+    // YAML reads CR as a line break, so the input is accepted by default.
+    let map: BTreeMap<String, u8> = serde_yml::from_str(yaml).unwrap();
+    assert_eq!(map.len(), 4);
+
+    let err = BTreeMap::<String, u8>::deserialize(
+        Deserializer::from_str(yaml).with_options(options.clone()),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "found a line ending written as a lone carriage return (CR), as in classic Mac OS files; convert the line endings to LF or CRLF at line 3 column 5",
+    );
+    assert_eq!(err.location().unwrap().index(), 16);
+
+    let err = Deserializer::from_str("---\né: 1\n---\né: 2\rf: 3\n")
+        .with_options(options.clone())
+        .next()
+        .map(Value::deserialize)
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(err.location().unwrap().index(), 17);
+    assert_eq!(err.location().unwrap().line(), 4);
+    assert_eq!(err.location().unwrap().column(), 5);
+
+    let map = BTreeMap::<String, u8>::deserialize(
+        Deserializer::from_str("a: 1\r\nb: 2\n").with_options(options),
+    )
+    .unwrap();
+    assert_eq!(map.len(), 2);
+}
+
+#[test]
+/// Test recording the style of each scalar during typed deserialization.
+fn test_from_str_with_styles() {
+    use serde_yml::libyml::parser::ScalarStyle;
+    use serde_yml::path::Path;
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Service {
+        password: String,
+        port: u16,
+        command: String,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Config {
+        services: Vec<Service>,
+    }
+
+    let yaml = indoc! {"
+        base: &base {password: 'x', port: 80, command: run}
+        services:
+        - *base
+        - password: hunter2
+          port: 8080
+          command: |
+            make
+    "};
+    let (config, styles) =
+        serde_yml::from_str_with_styles::<Config>(yaml).unwrap();
+    assert_eq!(config.services[1].port, 8080);
+
+    let path = |text: &str| text.parse::<Path>().unwrap();
+    let unquoted: Vec<String> = (0..config.services.len())
+        .map(|i| format!("services[{}].password", i))
+        .filter(|text| !styles.is_quoted(&path(text)))
+        .collect();
+    assert_eq!(unquoted, ["services[1].password"]);
+    assert_eq!(
+        styles.get(&path("services[1].command")),
+        Some(ScalarStyle::Literal)
+    );
+    assert_eq!(
+        styles.get(&path("base.port")),
+        styles.get(&path("services[0].port"))
+    );
+    assert_eq!(styles.get(&path("services")), None);
+    assert_eq!(styles.len(), 9);
+
+    let err = serde_yml::from_str_with_styles::<Value>("a\n---\nb\n")
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "deserializing from YAML containing more than one document is not supported"
+    );
+}
+
+#[test]
+/// Test deserializing many inputs with one reusable deserializer.
+fn test_reusable_deserializer() {
+    use serde_yml::de::{DeserializerOptions, ReusableDeserializer};
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Message {
+        id: u32,
+        tags: Vec<String>,
+    }
+
+    let inputs = [
+        "id: 1\ntags: [a, b]\n",
+        "{id: 2, tags: []}",
+        "id: 3\ntags:\n- &t x\n- *t\n",
+    ];
+    let mut deserializer = ReusableDeserializer::new();
+    for _ in 0..3 {
+        for input in &inputs {
+            let message: Message = deserializer.parse(input).unwrap();
+            assert_eq!(
+                message,
+                serde_yml::from_str::<Message>(input).unwrap()
+            );
+        }
+    }
+
+    let expected = serde_yml::from_str::<Message>("id: [")
+        .unwrap_err()
+        .to_string();
+    let error = deserializer.parse::<Message>("id: [").unwrap_err();
+    assert_eq!(error.to_string(), expected);
+    let error = deserializer.parse::<Value>("a\n---\nb").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "deserializing from YAML containing more than one document is not supported"
+    );
+    let message: Message = deserializer.parse(inputs[0]).unwrap();
+    assert_eq!(message.id, 1);
+    assert!(deserializer.deserialize::<Message>().is_err());
+
+    let options = DeserializerOptions {
+        lenient_tags: true,
+        ..DeserializerOptions::default()
+    };
+    let mut deserializer = ReusableDeserializer::with_options(options);
+    for _ in 0..2 {
+        let value: Value = deserializer.parse("!a !b x").unwrap();
+        assert_eq!(
+            value,
+            serde_yml::from_str::<Value>("!a x").unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_from_slice_encodings() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Config {
+        name: String,
+        port: u16,
+    }
+
+    let expected = Config {
+        name: "Zoë".to_owned(),
+        port: 8080,
+    };
+    let text = "name: Zoë\nport: 8080\n";
+    let utf16 = |big_endian: bool| -> Vec<u8> {
+        text.encode_utf16()
+            .flat_map(|unit| {
+                if big_endian {
+                    unit.to_be_bytes()
+                } else {
+                    unit.to_le_bytes()
+                }
+            })
+            .collect()
+    };
+
+    let mut with_bom = b"\xEF\xBB\xBF".to_vec();
+    with_bom.extend_from_slice(text.as_bytes());
+    let inputs = vec![
+        with_bom,
+        [&b"\xFF\xFE"[..], &utf16(false)].concat(),
+        [&b"\xFE\xFF"[..], &utf16(true)].concat(),
+        utf16(false),
+        utf16(true),
+    ];
+    for input in &inputs {
+        let from_slice: Config = serde_yml::from_slice(input).unwrap();
+        assert_eq!(from_slice, expected);
+        let from_reader: Config =
+            serde_yml::from_reader(input.as_slice()).unwrap();
+        assert_eq!(from_reader, expected);
+    }
+
+    let error =
+        serde_yml::from_slice::<Value>(b"\xFF\xFEa\x00:").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid UTF-16LE input: odd number of bytes"
+    );
+    let error =
+        serde_yml::from_slice::<Value>(b"\xFE\xFF\x00a\xD8\x00\x00b")
+            .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid UTF-16BE input: unpaired surrogate D800 at byte 4"
+    );
+    let error = serde_yml::from_slice::<Value>(
+        b"\x00\x00\xFE\xFF\x00\x00\x00a",
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "UTF-32BE input is not supported; convert it to UTF-8 or UTF-16"
+    );
+}
+
+#[test]
+/// Test that document boundaries come from the parser, not from `---` text.
+fn test_split_documents() {
+    use serde::Deserialize;
+    use serde_yml::de::{document_count, split_documents};
+
+    let yaml = indoc! {"
+        # leading comment
+        --- |
+          ---
+          not a document
+        ...
+        # second
+        ---
+        key: '--- quoted'
+        --- [1, 2] # flow
+        # trailing
+    "};
+    assert_eq!(document_count(yaml).unwrap(), 3);
+
+    let documents: Vec<&str> = split_documents(yaml).unwrap().collect();
+    assert_eq!(
+        documents,
+        [
+            "# leading comment\n--- |\n  ---\n  not a document\n...\n",
+            "# second\n---\nkey: '--- quoted'\n",
+            "--- [1, 2] # flow\n# trailing\n",
+        ],
+    );
+    assert_eq!(documents.concat(), yaml);
+
+    let whole: Vec<Value> = Deserializer::from_str(yaml)
+        .map(|de| Value::deserialize(de).unwrap())
+        .collect();
+    let pieces: Vec<Value> = documents
+        .iter()
+        .map(|document| serde_yml::from_str(document).unwrap())
+        .collect();
+    assert_eq!(whole, pieces);
+
+    let mut documents = split_documents("a\n---\nb\n---\nc\n").unwrap();
+    assert_eq!(documents.len(), 3);
+    assert_eq!(documents.next_back(), Some("---\nc\n"));
+    assert_eq!(documents.next(), Some("a\n"));
+    assert_eq!(documents.len(), 1);
+
+    assert_eq!(document_count("").unwrap(), 0);
+    assert_eq!(document_count("# only a comment\n").unwrap(), 0);
+    assert_eq!(document_count("---\n").unwrap(), 1);
+    assert_eq!(document_count("--- a\n--- b\n--- c").unwrap(), 3);
+
+    let err = document_count("a: 1\n---\n[unclosed\n").unwrap_err();
+    assert!(err.to_string().contains("line 4"), "{}", err);
+    assert!(split_documents("a: [\n").is_err());
+}
+
+#[test]
+fn test_missing_values() {
+    use serde::Deserialize as _;
+    use serde_yml::de::{DeserializerOptions, MissingValues};
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Entry {
+        name: String,
+        note: Option<String>,
+    }
+
+    let yaml = "name:\nnote:\n";
+    let with = |missing_values| {
+        let options = DeserializerOptions {
+            missing_values,
+            ..DeserializerOptions::default()
+        };
+        Deserializer::from_str(yaml).with_options(options)
+    };
+
+    let value = Value::deserialize(with(MissingValues::Null)).unwrap();
+    assert_eq!(value["name"], Value::Null);
+    let entry = Entry::deserialize(with(MissingValues::Null)).unwrap();
+    let expected = Entry {
+        name: String::new(),
+        note: None,
+    };
+    assert_eq!(entry, expected);
+
+    let entry =
+        Entry::deserialize(with(MissingValues::EmptyString)).unwrap();
+    let expected = Entry {
+        name: String::new(),
+        note: Some(String::new()),
+    };
+    assert_eq!(entry, expected);
+
+    let value =
+        Value::deserialize(with(MissingValues::EmptyString)).unwrap();
+    assert_eq!(value["note"], "");
+
+    let options = DeserializerOptions {
+        missing_values: MissingValues::EmptyString,
+        ..DeserializerOptions::default()
+    };
+    let yaml = "a: ~\nb: null\nc: ''\nd: [x, {e: }]\n? f\n";
+    let de = Deserializer::from_str(yaml).with_options(options);
+    let value = Value::deserialize(de).unwrap();
+    let expected: Value = serde_yml::from_str(
+        "a: null\nb: null\nc: ''\nd: [x, {e: ''}]\nf: ''\n",
+    )
+    .unwrap();
+    assert_eq!(value, expected);
+
+    let err =
+        Entry::deserialize(with(MissingValues::Error)).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "missing mapping value at line 1 column 6"
+    );
+    let location = err.location().unwrap();
+    assert_eq!((location.line(), location.column()), (1, 6));
+}
+
+#[test]
+fn test_deny_null_keys() {
+    use serde::Deserialize as _;
+    use serde_yml::de::{DeserializerOptions, ReusableDeserializer};
+
+    let options = DeserializerOptions {
+        deny_null_keys: true,
+        ..DeserializerOptions::default()
+    };
+    let load = |yaml| {
+        Value::deserialize(
+            Deserializer::from_str(yaml).with_options(options.clone()),
+        )
+    };
+
+    for yaml in [
+        "a: 1\n~: 2\n",
+        "a: 1\nnull: 2\n",
+        "a: 1\n!!null : 2\n",
+        "a: 1\n?\n: 2\n",
+    ] {
+        let err = load(yaml).unwrap_err();
+        assert!(
+            err.to_string().starts_with("null mapping key at line"),
+            "{}",
+            err
+        );
+    }
+    assert!(load("'~': 1\n\"null\": 2\n[~]: 3\n").is_ok());
+    assert!(load("- ~\n- a: ~\n").is_ok());
+
+    let value: Value = serde_yml::from_str("~: 1\n").unwrap();
+    assert_eq!(value[&Value::Null], 1);
+
+    let mut de = ReusableDeserializer::with_options(options);
+    de.reset("a: 1\n~: 2\n");
+    assert!(de.deserialize::<Value>().is_err());
+    de.reset("a: 1\n'~': 2\n");
+    assert!(de.deserialize::<Value>().is_ok());
+}
+
+#[test]
+fn test_reject_yaml_1_1_scalars() {
+    use serde::Deserialize as _;
+    use serde_yml::de::{DeserializerOptions, ReusableDeserializer};
+
+    let options = DeserializerOptions {
+        reject_yaml_1_1_scalars: true,
+        ..DeserializerOptions::default()
+    };
+    let load = |yaml: &str| {
+        Value::deserialize(
+            Deserializer::from_str(yaml).with_options(options.clone()),
+        )
+    };
+
+    for (scalar, reading) in [
+        ("0777", "YAML 1.1 reads as an octal integer"),
+        ("-0_17", "YAML 1.1 reads as an octal integer"),
+        ("1:30:00", "YAML 1.1 reads as a sexagesimal number"),
+        ("190:20:30.15", "YAML 1.1 reads as a sexagesimal number"),
+        ("1_000", "YAML 1.1 reads as a number"),
+        ("0x_FF", "YAML 1.1 reads as a number"),
+        ("3.141_5", "YAML 1.1 reads as a number"),
+        ("yes", "YAML 1.1 reads as a boolean"),
+        ("Off", "YAML 1.1 reads as a boolean"),
+        ("NaN", "some YAML parsers read as NaN"),
+        ("-.nan", "some YAML parsers read as NaN"),
+        ("-Infinity", "some YAML parsers read as infinity"),
+    ] {
+        // Accepted as strings by default.
+        let yaml = format!("key: {}\n", scalar);
+        let value: Value = serde_yml::from_str(&yaml).unwrap();
+        assert_eq!(value["key"], *scalar);
+
+        let err = load(&yaml).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "found {:?}, which {} but YAML 1.2 reads as a string; quote it or write it in YAML 1.2 form at line 1 column 6",
+                scalar, reading,
+            ),
+        );
+    }
+
+    let yaml = indoc! {"
+        mode: '0777'
+        octal: 0o777
+        time: !!str 1:30:00
+        count: 1000
+        enabled: true
+        ratio: .NaN
+        limit: -.inf
+        zero: 0
+        version: 1.10
+        name: no way
+        date: 2024-05-01
+    "};
+    assert!(load(yaml).is_ok());
+
+    let err = load("ports:\n  - 22:22\n").unwrap_err();
+    let location = err.location().unwrap();
+    assert_eq!((location.line(), location.column()), (2, 5));
+
+    let mut de = ReusableDeserializer::with_options(options);
+    de.reset("country: no\n");
+    assert!(de.deserialize::<Value>().is_err());
+}
+
+#[test]
+fn test_reject_non_finite_floats() {
+    use serde::Deserialize as _;
+    use serde_yml::de::DeserializerOptions;
+
+    let options = DeserializerOptions {
+        reject_non_finite_floats: true,
+        ..DeserializerOptions::default()
+    };
+    let load = |yaml: &str| {
+        Value::deserialize(
+            Deserializer::from_str(yaml).with_options(options.clone()),
+        )
+    };
+
+    for scalar in
+        [".nan", ".NaN", ".inf", "+.Inf", "-.INF", "!!float .nan"]
+    {
+        // Accepted as floats by default.
+        let yaml = format!("key: {}\n", scalar);
+        let value: Value = serde_yml::from_str(&yaml).unwrap();
+        assert!(!value["key"].as_f64().unwrap().is_finite());
+
+        let err = load(&yaml).unwrap_err();
+        let location = err.location().unwrap();
+        assert_eq!((location.line(), location.column()), (1, 6));
+        assert!(err.to_string().starts_with(&format!(
+            "found {:?}, which is not a finite number",
+            scalar.trim_start_matches("!!float "),
+        )));
+    }
+
+    let yaml = indoc! {"
+        ratio: 0.5
+        label: '.nan'
+        limit: \".inf\"
+        name: NaN
+        big: 1e999
+    "};
+    let value = load(yaml).unwrap();
+    assert_eq!(value["name"], "NaN");
+
+    let err = f64::deserialize(
+        Deserializer::from_str("-.inf").with_options(options),
+    )
+    .unwrap_err();
+    assert_eq!(err.location().unwrap().line(), 1);
+}
+
+#[test]
+fn test_explicit_core_tags() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Data {
+        id: String,
+        count: i64,
+        ratio: f64,
+        enabled: bool,
+        verbose: bool,
+        nothing: Option<u8>,
+    }
+
+    let yaml = indoc! {r#"
+        id: !!str 123
+        count: !!int "42"
+        ratio: !!float '1.5'
+        enabled: !!bool "yes"
+        verbose: !!bool off
+        nothing: !!null null
+    "#};
+    let expected = Data {
+        id: "123".to_owned(),
+        count: 42,
+        ratio: 1.5,
+        enabled: true,
+        verbose: false,
+        nothing: None,
+    };
+    test_de(yaml, &expected);
+
+    // A core tag names the type of the scalar, whatever it reads as plain.
+    assert!(serde_yml::from_str::<i64>("!!str 123").is_err());
+    assert!(serde_yml::from_str::<bool>("!!int true").is_err());
+    assert!(serde_yml::from_str::<bool>("!!bool maybe").is_err());
+    assert_eq!(serde_yml::from_str::<f64>("!!int 2").unwrap(), 2.0);
+    assert_eq!(serde_yml::from_str::<bool>("yes").ok(), None);
+}
+
+#[test]
+fn test_from_str_at() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Template {
+        image: String,
+        replicas: u32,
+    }
+
+    let yaml = indoc! {"
+        metadata:
+          labels: { app: [web, api] }
+        defaults: &defaults
+          image: nginx
+          replicas: 2
+        spec:
+          - name: first
+          - name: second
+            template: *defaults
+          - name: third
+            template: { image: redis, replicas: x }
+    "};
+
+    let template: Template =
+        serde_yml::from_str_at(yaml, "spec[1].template").unwrap();
+    assert_eq!(
+        template,
+        Template {
+            image: "nginx".to_owned(),
+            replicas: 2,
+        }
+    );
+    let replicas: u32 =
+        serde_yml::from_str_at(yaml, "/spec/1/template/replicas")
+            .unwrap();
+    assert_eq!(replicas, 2);
+    let labels: Value =
+        serde_yml::from_str_at(yaml, "metadata").unwrap();
+    assert_eq!(labels["labels"]["app"][1], "api");
+    let root: Value = serde_yml::from_str_at(yaml, ".").unwrap();
+    assert_eq!(root["spec"][2]["name"], "third");
+
+    for (path, expected) in [
+        (
+            "spec[2].template",
+            "spec.\\[2\\].template.replicas: invalid type: string \"x\", expected u32 at line 11 column 41",
+        ),
+        (
+            "spec[3]",
+            "spec: invalid length 3, expected a sequence with more than 3 elements at line 7 column 3",
+        ),
+        (
+            "spec[0].template",
+            "spec.\\[0\\]: missing key `template` at line 7 column 5",
+        ),
+        (
+            "metadata.labels.app.name",
+            "metadata.labels.app: invalid type: sequence, expected a mapping at line 2 column 18",
+        ),
+        ("spec[", "invalid path `spec[`: `[` must be closed by `]`"),
+    ] {
+        let err = serde_yml::from_str_at::<Template>(yaml, path).unwrap_err();
+        assert_eq!(err.to_string(), expected, "{}", path);
+    }
+}
+
+#[test]
+/// Test skipping the mapping entries named by `ignore_paths`.
+fn test_ignore_paths() {
+    use serde::Deserialize as _;
+    use serde_yml::de::DeserializerOptions;
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Metadata {
+        name: String,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Item {
+        id: u32,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Object {
+        metadata: Metadata,
+        items: Vec<Item>,
+    }
+
+    let yaml = indoc! {"
+        metadata:
+          managedFields:
+            - { manager: kubectl, time: not a number }
+          name: web
+        items:
+          - { id: 1, status: { ready: true } }
+          - { id: 2, status: [broken] }
+        status: { phase: Running }
+    "};
+    let options = DeserializerOptions {
+        deny_unknown_fields: true,
+        ignore_paths: vec![
+            "status".parse().unwrap(),
+            "/metadata/managedFields".parse().unwrap(),
+            "items[0].status".parse().unwrap(),
+            "items[1].status".parse().unwrap(),
+        ],
+        ..DeserializerOptions::default()
+    };
+    let de = Deserializer::from_str(yaml).with_options(options.clone());
+    let object = Object::deserialize(de).unwrap();
+    assert_eq!(
+        object,
+        Object {
+            metadata: Metadata {
+                name: "web".to_owned(),
+            },
+            items: vec![Item { id: 1 }, Item { id: 2 }],
+        }
+    );
+
+    // Entries under other paths are still read.
+    let yaml = "items: [{ id: 1 }, { id: 2, status: x }, { id: 3, status: y }]";
+    let de = Deserializer::from_str(yaml).with_options(options);
+    let err = Object::deserialize(de).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "items.\\[2\\].status: unknown field `status`, expected `id` at line 1 column 51",
+    );
+}
+
+#[test]
+fn test_from_str_with_warnings() {
+    use serde_yml::de::WarningKind;
+
+    #[derive(Deserialize, Debug)]
+    struct Config {
+        limit: f64,
+        servers: Vec<BTreeMap<String, String>>,
+    }
+
+    let yaml = indoc! {"
+        limit: 99999999999999999999
+        servers:
+          - &web { host: a }
+          - host: b
+            host: c
+            tls: on
+        shared: &shared [1]
+        uses: *shared
+    "};
+    let output =
+        serde_yml::from_str_with_warnings::<Config>(yaml).unwrap();
+    assert_eq!(output.value.limit, 1e20);
+    assert_eq!(output.value.servers[1]["host"], "c");
+    let warnings: Vec<_> = output
+        .warnings
+        .iter()
+        .map(|warning| {
+            (
+                warning.kind,
+                warning.path.as_ref().unwrap().to_string(),
+                warning.location.line(),
+            )
+        })
+        .collect();
+    let lossy = usize::from(cfg!(feature = "bigint"));
+    assert_eq!(
+        warnings,
+        [
+            (WarningKind::LossyNumber, "limit".to_owned(), 1),
+            (WarningKind::UnusedAnchor, "servers[0]".to_owned(), 3),
+            (
+                WarningKind::DuplicateKey,
+                "servers[1].host".to_owned(),
+                5
+            ),
+            (WarningKind::Yaml11Scalar, "servers[1].tls".to_owned(), 6),
+            (WarningKind::UnusedValue, "shared".to_owned(), 7),
+            (WarningKind::UnusedValue, "uses".to_owned(), 8),
+        ][lossy..],
+    );
+
+    // Integers read exactly are not reported.
+    #[derive(Deserialize, Debug)]
+    struct Wide {
+        exact: f64,
+        wide: u128,
+        any: Value,
+    }
+    let yaml = "exact: 18446744073709551616
+wide: 18446744073709551616
+any: 340282366920938463463374607431768211457
+";
+    let output =
+        serde_yml::from_str_with_warnings::<Wide>(yaml).unwrap();
+    assert_eq!(output.value.exact, 2f64.powi(64));
+    assert_eq!(output.value.wide, 1 << 64);
+    assert!(output.value.any.is_number());
+    let warnings: Vec<_> = output
+        .warnings
+        .iter()
+        .map(|warning| {
+            (warning.kind, warning.path.as_ref().unwrap().to_string())
+        })
+        .collect();
+    if cfg!(feature = "bigint") {
+        assert!(warnings.is_empty());
+    } else {
+        assert_eq!(
+            warnings,
+            [(WarningKind::LossyNumber, "any".to_owned())],
+        );
+    }
+
+    // Clean input has no warnings, and errors are those of `from_str`.
+    let output =
+        serde_yml::from_slice_with_warnings::<Value>(b"a: 1\nb: [x]\n")
+            .unwrap();
+    assert!(output.warnings.is_empty());
+    let err =
+        serde_yml::from_str_with_warnings::<Value>("a: 1\n---\nb: 2\n")
+            .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "deserializing from YAML containing more than one document is not supported",
+    );
+}
+
+#[test]
+fn test_from_file() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Config {
+        name: String,
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.yaml");
+    let expected = Config {
+        name: "démo".to_owned(),
+    };
+
+    std::fs::write(&path, "\u{feff}name: démo\n").unwrap();
+    assert_eq!(
+        serde_yml::from_file::<Config, _>(&path).unwrap(),
+        expected
+    );
+
+    let mut utf16 = vec![0xFF, 0xFE];
+    for unit in "name: démo\n".encode_utf16() {
+        utf16.extend_from_slice(&unit.to_le_bytes());
+    }
+    std::fs::write(&path, utf16).unwrap();
+    assert_eq!(
+        serde_yml::from_file::<Config, _>(&path).unwrap(),
+        expected
+    );
+
+    std::fs::write(&path, "name: 'demo\n").unwrap();
+    let err = serde_yml::from_file::<Config, _>(&path).unwrap_err();
+    let file = path.display().to_string();
+    assert_eq!(err.kind(), serde_yml::ErrorKind::Parse);
+    assert_eq!(err.file(), Some(file.as_str()));
+    assert!(err.location().is_some());
+    assert!(err.to_string().starts_with(&format!("{}: ", file)));
+    assert!(err
+        .render_snippet("name: 'demo\n")
+        .contains(&format!("--> {}:2:1", file)));
+}
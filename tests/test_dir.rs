@@ -0,0 +1,144 @@
+#[cfg(test)]
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::tree;
+    use serde_yml::{from_dir, ErrorKind, MergeStrategy, Value};
+
+    const FILES: &[(&str, &str)] = &[
+        ("20-b.yml", "server: {port: 81}\nlist: [b]\n"),
+        ("10-a.yaml", "server: {host: a, port: 80}\nlist: [a]\n"),
+        ("30-empty.yaml", "# nothing here\n"),
+        (".hidden.yaml", "server: {host: hidden}\n"),
+        ("notes.txt", "server: {host: txt}\n"),
+        ("sub.yaml/40-c.yaml", "server: {host: sub}\n"),
+    ];
+
+    #[test]
+    // Tests that files are merged in lexical order as each strategy says
+    fn test_from_dir_strategies() {
+        // Arrange
+        let dir = tree(FILES);
+        let dir = dir.path();
+
+        // Act
+        let deep: Value = from_dir(dir, MergeStrategy::Deep).unwrap();
+        let shallow: Value =
+            from_dir(dir, MergeStrategy::Shallow).unwrap();
+        let append: Value =
+            from_dir(dir, MergeStrategy::Append).unwrap();
+
+        // Assert
+        let expected: Value = serde_yml::from_str(
+            "server: {host: a, port: 81}\nlist: [b]\n",
+        )
+        .unwrap();
+        assert_eq!(deep, expected);
+        let expected: Value =
+            serde_yml::from_str("server: {port: 81}\nlist: [b]\n")
+                .unwrap();
+        assert_eq!(shallow, expected);
+        let expected: Value = serde_yml::from_str(
+            "server: {host: a, port: 81}\nlist: [a, b]\n",
+        )
+        .unwrap();
+        assert_eq!(append, expected);
+        assert_eq!(MergeStrategy::default(), MergeStrategy::Deep);
+    }
+
+    #[test]
+    // Tests that the merged document keeps the types and tags of its values
+    fn test_from_dir_keeps_types() {
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        enum Mode {
+            Fast(u8),
+        }
+
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct Config {
+            version: String,
+            enabled: bool,
+            ratio: f64,
+            mode: Mode,
+            owner: Option<String>,
+        }
+
+        // Arrange
+        let dir = tree(&[
+            ("10-a.yaml", "version: '1.0'\nenabled: 'no'\n"),
+            (
+                "20-b.yaml",
+                "enabled: false\nratio: -.inf\nmode: !Fast 3\nowner: ~\n",
+            ),
+        ]);
+
+        // Act
+        let config: Config =
+            from_dir(dir.path(), MergeStrategy::Deep).unwrap();
+
+        // Assert
+        assert_eq!(
+            config,
+            Config {
+                version: "1.0".to_owned(),
+                enabled: false,
+                ratio: f64::NEG_INFINITY,
+                mode: Mode::Fast(3),
+                owner: None,
+            }
+        );
+    }
+
+    #[test]
+    // Tests that errors name the file and line they come from
+    fn test_from_dir_errors() {
+        #[derive(serde::Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Server {
+            host: String,
+            port: u16,
+        }
+
+        // Arrange
+        let dir = tree(&[
+            ("10-a.yaml", "host: a\nport: 80\n"),
+            ("20-b.yaml", "# override\nport: http\n"),
+        ]);
+        let dir = dir.path();
+        let broken = tree(&[("10-a.yaml", "host: 'a\n")]);
+        let broken = broken.path();
+        let missing = dir.join("missing");
+
+        // Act
+        let invalid = from_dir::<Server, _>(dir, MergeStrategy::Deep)
+            .unwrap_err();
+        let parse = from_dir::<Server, _>(broken, MergeStrategy::Deep)
+            .unwrap_err();
+        let io = from_dir::<Server, _>(&missing, MergeStrategy::Deep)
+            .unwrap_err();
+
+        // Assert
+        let file = dir.join("20-b.yaml").display().to_string();
+        assert_eq!(invalid.kind(), ErrorKind::InvalidType);
+        assert_eq!(
+            invalid.to_string(),
+            format!(
+                "{}: port: invalid type: string \"http\", expected u16 at line 2 column 7",
+                file
+            )
+        );
+        assert_eq!(parse.kind(), ErrorKind::Parse);
+        assert_eq!(
+            parse.file(),
+            Some(
+                broken.join("10-a.yaml").display().to_string().as_str()
+            )
+        );
+        assert_eq!(io.kind(), ErrorKind::Io);
+        assert_eq!(
+            io.file(),
+            Some(missing.display().to_string().as_str())
+        );
+    }
+}
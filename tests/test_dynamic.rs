@@ -0,0 +1,123 @@
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_yml::dynamic::DynamicDeserializer;
+
+    trait Resource {
+        fn describe(&self) -> String;
+    }
+
+    #[derive(Deserialize)]
+    struct Service {
+        name: String,
+        port: u16,
+    }
+
+    #[derive(Deserialize)]
+    struct Secret {
+        kind: String,
+        keys: Vec<String>,
+    }
+
+    impl Resource for Service {
+        fn describe(&self) -> String {
+            format!("service {}:{}", self.name, self.port)
+        }
+    }
+
+    impl Resource for Secret {
+        fn describe(&self) -> String {
+            format!("{} with {} keys", self.kind, self.keys.len())
+        }
+    }
+
+    fn resources() -> DynamicDeserializer<Box<dyn Resource>> {
+        let mut resources = DynamicDeserializer::new("kind");
+        resources
+            .register(
+                "Service",
+                |service: Service| -> Box<dyn Resource> {
+                    Box::new(service)
+                },
+            )
+            .register(
+                "Secret",
+                |secret: Secret| -> Box<dyn Resource> {
+                    Box::new(secret)
+                },
+            );
+        resources
+    }
+
+    #[test]
+    // Tests dispatching every document of a stream on its discriminator
+    fn test_dynamic_dispatch() {
+        // Arrange
+        let yaml = "\
+metadata: {labels: [a, b], kind: Nested}
+kind: Service
+name: web
+port: 80
+---
+keys: [user, password]
+kind: &k Secret
+";
+
+        // Act
+        let described: Vec<String> = resources()
+            .from_str_all(yaml)
+            .unwrap()
+            .iter()
+            .map(|resource| resource.describe())
+            .collect();
+
+        // Assert
+        assert_eq!(described, ["service web:80", "Secret with 2 keys"]);
+    }
+
+    #[test]
+    // Tests errors for unknown, missing and malformed discriminators
+    fn test_dynamic_errors() {
+        let resources = resources();
+
+        let err =
+            resources.from_str("kind: Pod\nname: x\n").err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "kind: unknown kind `Pod`, expected `Secret` or `Service` at line 1 column 7"
+        );
+
+        let err =
+            resources.from_str("a: 1\nb: {kind: x}\n").err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "missing discriminator field `kind`"
+        );
+
+        let err =
+            resources.from_str("kind: [Service]\n").err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "discriminator field `kind` must be a scalar at line 1 column 7"
+        );
+
+        let err = resources.from_str("- kind\n").err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "expected a mapping with a `kind` field"
+        );
+
+        let err = resources
+            .from_str("kind: Service\nname: web\nport: high\n")
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "port: invalid type: string \"high\", expected u16 at line 3 column 7"
+        );
+
+        let err =
+            resources.from_str("kind: Secret\n---\nkind: Secret\n");
+        assert!(err.is_err());
+    }
+}
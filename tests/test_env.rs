@@ -0,0 +1,205 @@
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_yml::{env, Value};
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "HOST" => Some("example.com".to_owned()),
+            "PORT" => Some("8080".to_owned()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Server {
+        host: String,
+        port: u16,
+        debug: bool,
+    }
+
+    #[test]
+    // Tests that plain references are re-typed after expansion
+    fn test_expand_into_struct() {
+        // Arrange
+        let yaml =
+            "host: ${HOST}\nport: ${PORT}\ndebug: ${DEBUG:-false}\n";
+
+        // Act
+        let server: Server =
+            env::from_str_expanded_with(yaml, lookup).unwrap();
+
+        // Assert
+        assert_eq!(
+            server,
+            Server {
+                host: "example.com".to_owned(),
+                port: 8080,
+                debug: false,
+            }
+        );
+    }
+
+    #[test]
+    // Tests that quoted references stay strings after expansion
+    fn test_expand_quoted_stays_string() {
+        // Arrange
+        let yaml =
+            "port: '${PORT}'\nurl: \"http://${HOST}:${PORT}/\"\n";
+
+        // Act
+        let value: Value =
+            env::from_str_expanded_with(yaml, lookup).unwrap();
+
+        // Assert
+        assert_eq!(value["port"], Value::String("8080".to_owned()));
+        assert_eq!(value["url"], "http://example.com:8080/");
+    }
+
+    #[test]
+    // Tests default values for unset and empty variables
+    fn test_expand_defaults() {
+        let mut lookup = lookup;
+        assert_eq!(
+            env::expand("${MISSING:-fallback}", &mut lookup).unwrap(),
+            "fallback"
+        );
+        assert_eq!(
+            env::expand("${EMPTY:-fallback}", &mut lookup).unwrap(),
+            "fallback"
+        );
+        assert_eq!(env::expand("${EMPTY}", &mut lookup).unwrap(), "");
+    }
+
+    #[test]
+    // Tests escaping and lone dollar signs
+    fn test_expand_escapes() {
+        let mut lookup = lookup;
+        assert_eq!(
+            env::expand("$${HOST} costs $5", &mut lookup).unwrap(),
+            "${HOST} costs $5"
+        );
+    }
+
+    #[test]
+    // Tests error reporting for unset variables and malformed references
+    fn test_expand_errors() {
+        // Arrange
+        let yaml = "a: 1\nb: ${MISSING}\n";
+
+        // Act
+        let err = env::from_str_expanded_with::<Value, _>(yaml, lookup)
+            .unwrap_err();
+
+        // Assert
+        assert_eq!(
+            err.to_string(),
+            "environment variable `MISSING` is not set at line 2 column 4"
+        );
+
+        let mut lookup = lookup;
+        let err = env::expand("${HOST", &mut lookup).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unterminated variable reference in \"${HOST\""
+        );
+        let err = env::expand("${:-x}", &mut lookup).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "empty variable name in reference `${:-x}`"
+        );
+    }
+
+    #[test]
+    // Tests lookups against the process environment
+    fn test_expand_from_process_environment() {
+        std::env::set_var("SERDE_YML_TEST_ENV_NAME", "serde_yml");
+        let value: Value =
+            env::from_str_expanded("name: ${SERDE_YML_TEST_ENV_NAME}")
+                .unwrap();
+        assert_eq!(value["name"], "serde_yml");
+    }
+
+    #[test]
+    // Tests reading a single variable as a YAML value
+    fn test_from_var() {
+        // Arrange
+        let name = "SERDE_YML_TEST_ENV_VAR";
+
+        // Act and assert
+        std::env::remove_var(name);
+        assert_eq!(env::from_var::<u16>(name).unwrap(), None);
+        std::env::set_var(name, "8080");
+        assert_eq!(env::from_var::<u16>(name).unwrap(), Some(8080));
+        assert_eq!(
+            env::from_var::<String>(name).unwrap().as_deref(),
+            Some("8080")
+        );
+        std::env::set_var(name, "");
+        assert_eq!(
+            env::from_var::<String>(name).unwrap().as_deref(),
+            Some("")
+        );
+        std::env::set_var(name, "[1, x]");
+        let err = env::from_var::<Vec<u8>>(name).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "environment variable `SERDE_YML_TEST_ENV_VAR`: \\[1\\]: invalid type: string \"x\", expected u8 at line 1 column 5"
+        );
+        std::env::remove_var(name);
+    }
+
+    #[test]
+    // Tests that strings take the value of a variable as it is set, even
+    // when it is not valid YAML or would be read as something else
+    fn test_from_var_keeps_strings() {
+        let name = "SERDE_YML_TEST_ENV_RAW";
+        for raw in [
+            "&amp",
+            "!tag",
+            "|",
+            ">",
+            " lead",
+            "#secret",
+            "p@ss: word",
+            "*star",
+            "%pct",
+            "@at",
+            "'quoted'",
+            "~",
+        ] {
+            std::env::set_var(name, raw);
+            assert_eq!(
+                env::from_var::<String>(name).unwrap().as_deref(),
+                Some(raw)
+            );
+            assert_eq!(
+                env::from_var::<Option<String>>(name).unwrap(),
+                Some(Some(raw.to_owned()))
+            );
+        }
+
+        std::env::set_var(name, "#secret");
+        assert_eq!(
+            env::from_var::<Value>(name).unwrap(),
+            Some(Value::from("#secret"))
+        );
+        std::env::set_var(name, "&amp");
+        assert_eq!(
+            env::from_var::<Value>(name).unwrap(),
+            Some(Value::from("&amp"))
+        );
+        std::env::set_var(name, "[1, 2]");
+        assert_eq!(
+            env::from_var::<Value>(name).unwrap(),
+            Some(serde_yml::from_str("[1, 2]").unwrap())
+        );
+        std::env::set_var(name, "~");
+        assert_eq!(
+            env::from_var::<Value>(name).unwrap(),
+            Some(Value::Null)
+        );
+        std::env::remove_var(name);
+    }
+}
@@ -73,6 +73,22 @@ fn test_incorrect_nested_type() {
     test_error::<A>(yaml, expected);
 }
 
+#[test]
+fn test_multi_character_char() {
+    let yaml = "ab";
+    let expected = "invalid value: string \"ab\" has length 2, expected a single character";
+    test_error::<char>(yaml, expected);
+
+    #[derive(Deserialize, Debug)]
+    pub(crate) struct Initial {
+        #[allow(dead_code)]
+        pub(crate) initial: char,
+    }
+    let yaml = "initial: abc";
+    let expected = "initial: invalid value: string \"abc\" has length 3, expected a single character at line 1 column 10";
+    test_error::<Initial>(yaml, expected);
+}
+
 #[test]
 fn test_empty() {
     let expected = "EOF while parsing a value";
@@ -106,6 +122,28 @@ fn test_unknown_anchor() {
     test_error::<String>(yaml, expected);
 }
 
+#[test]
+fn test_alias_cycle() {
+    let yaml = indoc! {"
+        a: &x
+          b: [1, *x]
+        c: *x
+    "};
+    let expected =
+        "alias cycle: *x refers to a node that contains it at line 2 column 10";
+    test_error::<Value>(yaml, expected);
+}
+
+#[test]
+fn test_alias_to_sibling_is_not_a_cycle() {
+    let yaml = indoc! {"
+        a: &x [1]
+        b: [*x, *x]
+    "};
+    let value: Value = serde_yml::from_str(yaml).unwrap();
+    assert_eq!(value["b"][1][0], 1);
+}
+
 #[test]
 fn test_ignored_unknown_anchor() {
     #[derive(Deserialize, Debug)]
@@ -362,7 +400,8 @@ fn test_infinite_recursion_objects() {
     }
 
     let yaml = "&a {'x': *a}";
-    let expected = "recursion limit exceeded";
+    let expected =
+        "alias cycle: *a refers to a node that contains it at line 1 column 10";
     test_error::<S>(yaml, expected);
 }
 
@@ -376,7 +415,8 @@ fn test_infinite_recursion_arrays() {
     );
 
     let yaml = "&a [0, *a]";
-    let expected = "recursion limit exceeded";
+    let expected =
+        "alias cycle: *a refers to a node that contains it at line 1 column 8";
     test_error::<S>(yaml, expected);
 }
 
@@ -507,3 +547,252 @@ fn test_duplicate_keys() {
     let expected = "duplicate entry in YAML map at line 2 column 1";
     test_error::<Value>(yaml, expected);
 }
+
+#[test]
+fn test_unknown_variant_suggestion() {
+    #[derive(Deserialize, Debug)]
+    pub(crate) enum Level {
+        Debug,
+        Info,
+        Warning,
+    }
+
+    #[derive(Deserialize, Debug)]
+    pub(crate) struct Config {
+        #[allow(dead_code)]
+        level: Level,
+        #[allow(dead_code)]
+        #[serde(with = "serde_yml::with::singleton_map")]
+        output: Output,
+    }
+
+    #[derive(Deserialize, Debug)]
+    pub(crate) enum Output {
+        File(#[allow(dead_code)] String),
+        Stdout,
+    }
+
+    let yaml = "level: Warnign\noutput: Stdout\n";
+    let expected = "level: unknown variant `Warnign`, expected one of `Debug`, `Info`, `Warning`; did you mean `Warning`? at line 1 column 8";
+    test_error::<Config>(yaml, expected);
+
+    let yaml = "level: Info\noutput:\n  Fiel: out.log\n";
+    let expected = "output: unknown variant `Fiel`, expected `File` or `Stdout`; did you mean `File`? at line 3 column 3";
+    test_error::<Config>(yaml, expected);
+
+    let yaml = "level: Verbose\noutput: Stdout\n";
+    let expected = "level: unknown variant `Verbose`, expected one of `Debug`, `Info`, `Warning` at line 1 column 8";
+    test_error::<Config>(yaml, expected);
+}
+
+#[test]
+fn test_unknown_field_suggestion() {
+    #[derive(Deserialize, Debug)]
+    #[serde(deny_unknown_fields)]
+    pub(crate) struct Config {
+        #[allow(dead_code)]
+        retries: u32,
+        #[allow(dead_code)]
+        timeout: u32,
+    }
+
+    let yaml = "retrys: 3\ntimeout: 1\n";
+    let expected = "unknown field `retrys`, expected `retries` or `timeout`; did you mean `retries`?";
+    test_error::<Config>(yaml, expected);
+}
+
+#[test]
+fn test_error_kind() {
+    use serde_yml::{ErrorKind, Mapping};
+
+    #[derive(Deserialize, Debug)]
+    #[serde(deny_unknown_fields)]
+    pub(crate) struct Config {
+        #[allow(dead_code)]
+        port: u16,
+        #[allow(dead_code)]
+        tags: [String; 2],
+    }
+
+    struct Bytes;
+
+    impl serde::Serialize for Bytes {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            serializer.serialize_bytes(b"raw")
+        }
+    }
+
+    let kind = |yaml: &str| {
+        serde_yml::from_str::<Config>(yaml).unwrap_err().kind()
+    };
+    assert_eq!(
+        kind("port: [1]\ntags: [a, b]\n"),
+        ErrorKind::InvalidType
+    );
+    assert_eq!(
+        kind("port: 70000\ntags: [a, b]\n"),
+        ErrorKind::InvalidValue
+    );
+    assert_eq!(kind("port: 1\ntags: [a]\n"), ErrorKind::InvalidLength);
+    assert_eq!(
+        kind("port: 1\ntags: [a, b]\nx: 1\n"),
+        ErrorKind::UnknownField
+    );
+    assert_eq!(kind("tags: [a, b]\n"), ErrorKind::MissingField);
+    assert_eq!(
+        kind("port: 1\nport: 2\ntags: [a, b]\n"),
+        ErrorKind::DuplicateField
+    );
+    assert_eq!(
+        kind("port: 1\ntags: [a, *b]\n"),
+        ErrorKind::UnknownAnchor
+    );
+    assert_eq!(kind("port: 1\ntags: [a, 'b\n"), ErrorKind::Parse);
+    assert_eq!(
+        kind("port: 1\ntags: [a, b]\n---\nport: 2\n"),
+        ErrorKind::MoreThanOneDocument
+    );
+
+    let err =
+        serde_yml::from_str::<Mapping>("a: 1\na: 2\n").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::DuplicateKey);
+    assert_eq!(err.to_string(), "duplicate entry with key \"a\"");
+
+    let err = serde_yml::to_string(&Bytes).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+}
+
+#[test]
+fn test_render_snippet() {
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Config {
+        name: String,
+        port: u16,
+    }
+
+    let yaml = "name: \"démo\"\n\tport: x\n";
+    let err = serde_yml::from_str::<Config>(yaml).unwrap_err();
+    let location = err.location().unwrap();
+    assert_eq!((location.line(), location.column()), (2, 1));
+    assert_eq!(
+        err.render_snippet(yaml),
+        "error: found character that cannot start any token\n --> 2:1\n  |\n2 | \tport: x\n  | ^\n",
+    );
+
+    let yaml = "name: démo\nport: x\n";
+    let err = serde_yml::from_str::<Config>(yaml).unwrap_err();
+    assert_eq!(
+        err.render_snippet(yaml),
+        indoc! {"
+            error: port: invalid type: string \"x\", expected u16
+             --> 2:7
+              |
+            2 | port: x
+              |       ^
+        "},
+    );
+
+    let err = <serde_yml::Error as serde::de::Error>::custom("bad");
+    assert!(err.location().is_none());
+    assert_eq!(err.render_snippet("name: démo\n"), "error: bad\n");
+}
+
+#[cfg(feature = "miette")]
+#[test]
+fn test_miette_diagnostic() {
+    use miette::Diagnostic;
+
+    let yaml = "name: démo\nlist: [1, 2\n";
+    let err = serde_yml::from_str::<Value>(yaml).unwrap_err();
+    assert_eq!(err.code().unwrap().to_string(), "serde_yml::Parse");
+    let labels: Vec<_> = err
+        .labels()
+        .unwrap()
+        .map(|label| {
+            (
+                label.offset(),
+                label.len(),
+                label.label().map(str::to_owned),
+                label.primary(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        labels,
+        [
+            (24, 0, None, true),
+            (
+                18,
+                0,
+                Some("while parsing a flow sequence".to_owned()),
+                false
+            ),
+        ],
+    );
+
+    let err = <serde_yml::Error as serde::de::Error>::custom("bad");
+    assert!(err.labels().is_none());
+}
+
+#[test]
+fn test_collect_errors() {
+    use serde_yml::de::DeserializerOptions;
+    use serde_yml::ErrorKind;
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Server {
+        host: String,
+        tls: bool,
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Config {
+        name: String,
+        port: u16,
+        servers: Vec<Server>,
+    }
+
+    let yaml = indoc! {"
+        name: demo
+        port: x
+        servers:
+          - tls: true
+          - host: b
+            tls: maybe
+    "};
+    let options = DeserializerOptions {
+        collect_errors: true,
+        ..DeserializerOptions::default()
+    };
+    let de = Deserializer::from_str(yaml).with_options(options.clone());
+    let err = Config::deserialize(de).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Multiple);
+    let messages: Vec<_> =
+        err.errors().iter().map(ToString::to_string).collect();
+    assert_eq!(
+        messages,
+        [
+            "port: invalid type: string \"x\", expected u16 at line 2 column 7",
+            "servers.\\[0\\]: missing field `host` at line 4 column 5",
+            "servers.\\[1\\].tls: invalid type: string \"maybe\", expected a boolean at line 6 column 10",
+        ],
+    );
+    assert_eq!(
+        err.to_string(),
+        format!("found 3 errors\n{}", messages.join("\n")),
+    );
+    assert_eq!(err.location().unwrap().line(), 2);
+
+    let de =
+        Deserializer::from_str("name: demo\nport: x\nservers: []\n")
+            .with_options(options);
+    let err = Config::deserialize(de).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidType);
+    assert_eq!(err.errors().len(), 1);
+}
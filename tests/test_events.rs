@@ -0,0 +1,130 @@
+#[cfg(test)]
+mod tests {
+    use serde_yml::events::{EventKind, Parser, ScalarStyle};
+
+    /// Tests that events come in order with the byte range of their text.
+    #[test]
+    fn test_events_spans() {
+        // Arrange
+        let yaml = "key: [1, 'two']\n";
+
+        // Act
+        let events: Vec<_> = Parser::new(yaml)
+            .map(|event| {
+                let event = event.unwrap();
+                let text = &yaml[event.span()];
+                (event.into_kind(), text)
+            })
+            .collect();
+
+        // Assert
+        let kinds: Vec<_> = events
+            .iter()
+            .map(|(kind, text)| match kind {
+                EventKind::StreamStart => format!("+STR {:?}", text),
+                EventKind::StreamEnd => format!("-STR {:?}", text),
+                EventKind::DocumentStart => format!("+DOC {:?}", text),
+                EventKind::DocumentEnd => format!("-DOC {:?}", text),
+                EventKind::MappingStart(_) => {
+                    format!("+MAP {:?}", text)
+                }
+                EventKind::MappingEnd => format!("-MAP {:?}", text),
+                EventKind::SequenceStart(_) => {
+                    format!("+SEQ {:?}", text)
+                }
+                EventKind::SequenceEnd => format!("-SEQ {:?}", text),
+                EventKind::Scalar(scalar) => {
+                    format!("=VAL {} {:?}", scalar.value(), text)
+                }
+                other => format!("{:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                "+STR \"\"",
+                "+DOC \"\"",
+                "+MAP \"\"",
+                "=VAL key \"key\"",
+                "+SEQ \"[\"",
+                "=VAL 1 \"1\"",
+                "=VAL two \"'two'\"",
+                "-SEQ \"]\"",
+                "-MAP \"\"",
+                "-DOC \"\"",
+                "-STR \"\"",
+            ]
+        );
+    }
+
+    /// Tests that anchors, tags, aliases and scalar styles are reported.
+    #[test]
+    fn test_events_properties() {
+        // Arrange
+        let yaml = "a: &x !!str |\n  text\nb: *x\nc: &m {}\n";
+
+        // Act
+        let kinds: Vec<_> = Parser::new(yaml)
+            .map(|event| event.unwrap().into_kind())
+            .collect();
+
+        // Assert
+        let scalar = match &kinds[4] {
+            EventKind::Scalar(scalar) => scalar,
+            other => panic!("expected a scalar, found {:?}", other),
+        };
+        assert_eq!(scalar.value(), "text\n");
+        assert_eq!(scalar.style(), ScalarStyle::Literal);
+        assert_eq!(scalar.properties().anchor(), Some("x"));
+        assert_eq!(
+            scalar.properties().tag(),
+            Some("tag:yaml.org,2002:str")
+        );
+        assert_eq!(kinds[6], EventKind::Alias("x".to_owned()));
+        match &kinds[8] {
+            EventKind::MappingStart(properties) => {
+                assert_eq!(properties.anchor(), Some("m"));
+                assert_eq!(properties.tag(), None);
+            }
+            other => panic!("expected a mapping, found {:?}", other),
+        }
+    }
+
+    /// Tests that spans are offsets of the original input, counting the
+    /// byte order mark and multi-byte characters in bytes.
+    #[test]
+    fn test_events_spans_in_bytes() {
+        // Arrange
+        let yaml = "\u{feff}é: ü\n";
+
+        // Act
+        let spans: Vec<_> = Parser::new(yaml)
+            .filter_map(|event| {
+                let event = event.unwrap();
+                match event.kind() {
+                    EventKind::Scalar(_) => Some(&yaml[event.span()]),
+                    _ => None,
+                }
+            })
+            .collect();
+
+        // Assert
+        assert_eq!(spans, ["é", "ü"]);
+    }
+
+    /// Tests that a syntax error ends the iteration after the events
+    /// before it.
+    #[test]
+    fn test_events_error() {
+        // Arrange
+        let yaml = "a: [1\nb: 2\n";
+
+        // Act
+        let events: Vec<_> = Parser::new(yaml).collect();
+
+        // Assert
+        let error = events.last().unwrap().as_ref().unwrap_err();
+        assert!(error.location().is_some());
+        assert!(events[..events.len() - 1].iter().all(Result::is_ok));
+    }
+}
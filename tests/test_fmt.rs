@@ -0,0 +1,225 @@
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use serde_yml::fmt::{format_str, FormatOptions, Quotes};
+
+    #[test]
+    // Tests that formatting normalizes the layout and keeps comments and blank lines
+    fn test_format_keeps_comments() {
+        // Arrange
+        let input = indoc! {"
+            # Settings
+            server:
+                host:   example.com   # public name
+                ports: [80, 443]
+
+
+                # Limits below
+                limits: {cpu: 2}
+            script: |   # kept verbatim
+              echo '# not a comment'
+            # end
+        "};
+
+        // Act
+        let formatted =
+            format_str(input, FormatOptions::default()).unwrap();
+
+        // Assert
+        let expected = indoc! {"
+            # Settings
+            server:
+              host: example.com # public name
+              ports:
+              - 80
+              - 443
+
+              # Limits below
+              limits:
+                cpu: 2
+            script: | # kept verbatim
+              echo '# not a comment'
+            # end
+        "};
+        assert_eq!(formatted, expected);
+        assert_eq!(
+            format_str(&formatted, FormatOptions::default()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    // Tests that sorting keys moves each entry along with its comments
+    fn test_format_sort_keys() {
+        // Arrange
+        let input = indoc! {"
+            # Header
+            c: 3
+            # about b
+            b: 2
+            a:
+              # about y
+              y: 1
+              x: 0 # about x
+        "};
+        let options = FormatOptions {
+            indent: 4,
+            sort_keys: true,
+            ..FormatOptions::default()
+        };
+
+        // Act
+        let formatted = format_str(input, options).unwrap();
+
+        // Assert
+        let expected = indoc! {"
+            # Header
+            a:
+                x: 0 # about x
+                # about y
+                y: 1
+            # about b
+            b: 2
+            c: 3
+        "};
+        assert_eq!(formatted, expected);
+    }
+
+    #[test]
+    // Tests each quoting option on plain, quoted and ambiguous strings
+    fn test_format_quotes() {
+        // Arrange
+        let input = "a: \"x\"\nb: 'word'\nc: 'true'\nd: plain\n";
+        let format = |quotes| {
+            let options = FormatOptions {
+                quotes,
+                ..FormatOptions::default()
+            };
+            format_str(input, options).unwrap()
+        };
+
+        // Act & Assert
+        assert_eq!(format(Quotes::Preserve), input);
+        assert_eq!(
+            format(Quotes::Minimal),
+            "a: x\nb: word\nc: 'true'\nd: plain\n"
+        );
+        assert_eq!(
+            format(Quotes::Single),
+            "a: 'x'\nb: 'word'\nc: 'true'\nd: plain\n"
+        );
+        assert_eq!(
+            format(Quotes::Double),
+            "a: \"x\"\nb: \"word\"\nc: \"true\"\nd: plain\n"
+        );
+    }
+
+    #[test]
+    // Tests that anchors, aliases, tags and every document are kept
+    fn test_format_anchors_and_documents() {
+        // Arrange
+        let input = indoc! {"
+            ---
+            base: &b {x: 1}
+            copy: *b
+            tagged: !custom [a]
+            ---
+            # second
+            plain
+        "};
+
+        // Act
+        let formatted =
+            format_str(input, FormatOptions::default()).unwrap();
+
+        // Assert
+        let expected = indoc! {"
+            ---
+            base: &b
+              x: 1
+            copy: *b
+            tagged: !custom
+            - a
+            # second
+            --- plain
+        "};
+        assert_eq!(formatted, expected);
+    }
+
+    #[test]
+    // Tests the errors for invalid input and for aliases moved before their anchor
+    fn test_format_errors() {
+        // Arrange
+        let options = FormatOptions {
+            sort_keys: true,
+            ..FormatOptions::default()
+        };
+
+        // Act
+        let invalid = format_str("key: [1, 2\n", options).unwrap_err();
+        let alias =
+            format_str("b: &x 1\na: *x\n", options).unwrap_err();
+
+        // Assert
+        assert!(invalid
+            .to_string()
+            .starts_with("did not find expected"));
+        assert_eq!(
+            alias.to_string(),
+            "cannot sort the keys: alias *x would come before its anchor"
+        );
+    }
+
+    #[test]
+    // Tests that directives, document markers, tags as written and the final line breaks are kept
+    fn test_format_round_trip() {
+        // Arrange
+        let input = indoc! {"
+            # Shapes, version 2
+
+            %YAML 1.2
+            %TAG !e! tag:example.com,2000:
+            ---
+            origin: &o !e!point {x: 0, y: 0}
+            label: !<tag:yaml.org,2002:str> center
+            ...
+            # Second document
+            ---
+            copy: 1
+            ...
+        "};
+        let expected = indoc! {"
+            # Shapes, version 2
+
+            %YAML 1.2
+            %TAG !e! tag:example.com,2000:
+            ---
+            origin: &o !e!point
+              x: 0
+              y: 0
+            label: !<tag:yaml.org,2002:str> center
+            ...
+            # Second document
+            ---
+            copy: 1
+            ...
+        "};
+
+        // Act
+        let formatted =
+            format_str(input, FormatOptions::default()).unwrap();
+        let bare =
+            format_str("a: 1", FormatOptions::default()).unwrap();
+        let padded =
+            format_str("a: 1\n\n\n", FormatOptions::default()).unwrap();
+
+        // Assert
+        assert_eq!(formatted, expected);
+        assert_eq!(
+            format_str(&formatted, FormatOptions::default()).unwrap(),
+            expected
+        );
+        assert_eq!(bare, "a: 1");
+        assert_eq!(padded, "a: 1\n\n\n");
+    }
+}
@@ -0,0 +1,185 @@
+#[cfg(all(test, feature = "fs"))]
+mod common;
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use super::common::tree;
+    use serde_yml::{load_glob, ErrorKind};
+    use std::path::PathBuf;
+
+    #[test]
+    // Tests that matching files are read in lexical order, one item per
+    // document
+    fn test_load_glob_documents() {
+        // Arrange
+        let dir = tree(&[
+            ("b.yaml", "name: b\n"),
+            ("a/x.yaml", "name: a1\n---\nname: a2\n"),
+            ("a/deep/y.yaml", "name: deep\n"),
+            ("a/empty.yaml", ""),
+            (".git/z.yaml", "name: hidden\n"),
+            ("c.yml", "name: c\n"),
+            ("d1.yaml", "name: d1\n"),
+        ]);
+        let dir = dir.path();
+        let root = dir.display();
+
+        // Act
+        let names = |pattern: String| -> Vec<(PathBuf, String)> {
+            load_glob(&pattern)
+                .map(|document| {
+                    let (path, value) = document.unwrap();
+                    let path =
+                        path.strip_prefix(dir).unwrap().to_owned();
+                    (path, value["name"].as_str().unwrap().to_owned())
+                })
+                .collect()
+        };
+        let all = names(format!("{}/**/*.yaml", root));
+        let top = names(format!("{}/*.y*ml", root));
+        let class = names(format!("{}/[!a-c]*.yaml", root));
+        let literal = names(format!("{}/a/deep/y.yaml", root));
+        let hidden = names(format!("{}/.git/*.yaml", root));
+
+        // Assert
+        let expected =
+            |items: &[(&str, &str)]| -> Vec<(PathBuf, String)> {
+                items
+                    .iter()
+                    .map(|(path, name)| {
+                        (PathBuf::from(path), (*name).to_owned())
+                    })
+                    .collect()
+            };
+        assert_eq!(
+            all,
+            expected(&[
+                ("a/deep/y.yaml", "deep"),
+                ("a/x.yaml", "a1"),
+                ("a/x.yaml", "a2"),
+                ("b.yaml", "b"),
+                ("d1.yaml", "d1"),
+            ])
+        );
+        assert_eq!(
+            top,
+            expected(&[
+                ("b.yaml", "b"),
+                ("c.yml", "c"),
+                ("d1.yaml", "d1")
+            ])
+        );
+        assert_eq!(class, expected(&[("d1.yaml", "d1")]));
+        assert_eq!(literal, expected(&[("a/deep/y.yaml", "deep")]));
+        assert_eq!(hidden, expected(&[(".git/z.yaml", "hidden")]));
+    }
+
+    #[test]
+    // Tests that errors name their file and do not end the iteration
+    fn test_load_glob_errors() {
+        // Arrange
+        let dir = tree(&[
+            ("a.yaml", "ok: 1\n---\nbad: [\n"),
+            ("b.yaml", "ok: 2\n"),
+        ]);
+        let dir = dir.path();
+
+        // Act
+        let items: Vec<_> =
+            load_glob(&format!("{}/*.yaml", dir.display())).collect();
+        let missing: Vec<_> =
+            load_glob(&format!("{}/missing/*.yaml", dir.display()))
+                .collect();
+
+        // Assert
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].as_ref().unwrap().1["ok"], 1);
+        let err = items[1].as_ref().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Parse);
+        let file = dir.join("a.yaml").display().to_string();
+        assert_eq!(err.file(), Some(file.as_str()));
+        assert_eq!(err.location().unwrap().line(), 4);
+        assert_eq!(items[2].as_ref().unwrap().1["ok"], 2);
+        assert_eq!(missing.len(), 1);
+        let err = missing[0].as_ref().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Io);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    // Tests that `**` does not follow symbolic links to directories and
+    // that files reached in several ways are read once
+    fn test_load_glob_links_and_duplicates() {
+        // Arrange
+        let dir = tree(&[
+            ("a/x.yaml", "name: x\n"),
+            ("a/b/y.yaml", "name: y\n"),
+        ]);
+        let dir = dir.path();
+        std::os::unix::fs::symlink("..", dir.join("a/loop")).unwrap();
+
+        // Act
+        let names = |pattern: &str| -> Vec<String> {
+            load_glob(&format!("{}/{}", dir.display(), pattern))
+                .map(|item| {
+                    item.unwrap().1["name"].as_str().unwrap().to_owned()
+                })
+                .collect()
+        };
+        let walked = names("**/*.yaml");
+        let doubled = names("**/**/*.yaml");
+        let linked = names("a/loop/a/*.yaml");
+
+        // Assert
+        assert_eq!(walked, ["y", "x"]);
+        assert_eq!(doubled, ["y", "x"]);
+        assert_eq!(linked, ["x"]);
+    }
+
+    #[test]
+    // Tests that a trailing `**` matches every file below its directory
+    fn test_load_glob_trailing_globstar() {
+        // Arrange
+        let dir = tree(&[
+            ("a/x.yaml", "name: x\n"),
+            ("a/b/y.yml", "name: y\n"),
+            ("a/.hidden/z.yaml", "name: z\n"),
+            ("c.yaml", "name: c\n"),
+        ]);
+        let dir = dir.path();
+
+        // Act
+        let names: Vec<String> =
+            load_glob(&format!("{}/a/**", dir.display()))
+                .map(|item| {
+                    item.unwrap().1["name"].as_str().unwrap().to_owned()
+                })
+                .collect();
+
+        // Assert
+        assert_eq!(names, ["y", "x"]);
+    }
+
+    #[test]
+    // Tests that a pattern with many `*`s is matched without trying every
+    // way of splitting the name between them
+    fn test_load_glob_many_stars() {
+        // Arrange
+        let name = format!("{}.yaml", "a".repeat(64));
+        let dir = tree(&[(name.as_str(), "name: a\n")]);
+        let dir = dir.path();
+        let stars = "*a".repeat(24);
+
+        // Act
+        let missed: Vec<_> =
+            load_glob(&format!("{}/{}b.yaml", dir.display(), stars))
+                .collect();
+        let matched: Vec<_> =
+            load_glob(&format!("{}/{}*.yaml", dir.display(), stars))
+                .collect();
+
+        // Assert
+        assert!(missed.is_empty());
+        assert_eq!(matched.len(), 1);
+    }
+}
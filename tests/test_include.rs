@@ -0,0 +1,231 @@
+#[cfg(test)]
+mod tests {
+    use serde::de::Error as _;
+    use serde::Deserialize;
+    use serde_yml::include::{DirResolver, Includer, Source};
+    use serde_yml::{Result, Value};
+    use std::{collections::BTreeMap, fs};
+
+    /// Builds an in-memory resolver over a fixed set of documents.
+    fn memory(
+        files: &[(&str, &str)],
+    ) -> impl FnMut(Option<&str>, &str) -> Result<Source> {
+        let files: BTreeMap<String, String> = files
+            .iter()
+            .map(|(id, contents)| {
+                ((*id).to_owned(), (*contents).to_owned())
+            })
+            .collect();
+        move |_parent, path| match files.get(path) {
+            Some(contents) => Ok(Source {
+                id: path.to_owned(),
+                contents: contents.clone(),
+            }),
+            None => Err(serde_yml::Error::custom(format_args!(
+                "no such document {}",
+                path
+            ))),
+        }
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Database {
+        host: String,
+        port: u16,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Config {
+        name: String,
+        database: Database,
+        replicas: Vec<String>,
+    }
+
+    #[test]
+    // Tests that includes are expanded in mappings, sequences and nested documents
+    fn test_include_nested() {
+        // Arrange
+        let resolver = memory(&[
+            ("db.yaml", "host: !include host.yaml\nport: 5432\n"),
+            ("host.yaml", "db.internal"),
+            ("eu.yaml", "eu-west"),
+        ]);
+        let yaml = "name: api\ndatabase: !include db.yaml\nreplicas:\n  - !include eu.yaml\n  - us-east\n";
+
+        // Act
+        let config: Config =
+            Includer::new(resolver).from_str(yaml).unwrap();
+
+        // Assert
+        assert_eq!(
+            config,
+            Config {
+                name: "api".to_owned(),
+                database: Database {
+                    host: "db.internal".to_owned(),
+                    port: 5432,
+                },
+                replicas: vec![
+                    "eu-west".to_owned(),
+                    "us-east".to_owned()
+                ],
+            }
+        );
+    }
+
+    #[test]
+    // Tests that include cycles are reported with the full chain, at the
+    // tag closing the cycle
+    fn test_include_cycle() {
+        let resolver = memory(&[
+            ("a.yaml", "next: !include b.yaml"),
+            ("b.yaml", "next: !include a.yaml"),
+        ]);
+        let err = Includer::new(resolver)
+            .from_path::<Value>("a.yaml")
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "b.yaml: next: include cycle detected: a.yaml -> b.yaml -> a.yaml at line 1 column 7"
+        );
+        assert_eq!(err.file(), Some("b.yaml"));
+        assert_eq!(err.path().unwrap().to_string(), "next");
+    }
+
+    #[test]
+    // Tests that the depth limit stops deep include chains
+    fn test_include_depth_limit() {
+        let resolver = memory(&[
+            ("1.yaml", "!include 2.yaml"),
+            ("2.yaml", "!include 3.yaml"),
+            ("3.yaml", "done"),
+        ]);
+        let mut includer = Includer::new(resolver).max_depth(2);
+        let err =
+            includer.from_str::<Value>("!include 1.yaml").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "2.yaml: include depth limit of 2 exceeded while including \"3.yaml\""
+        );
+    }
+
+    #[test]
+    // Tests that the same document may be included several times
+    fn test_include_repeated_is_not_a_cycle() {
+        let resolver = memory(&[("x.yaml", "1")]);
+        let value: Value = Includer::new(resolver)
+            .from_str("[!include x.yaml, !include x.yaml]")
+            .unwrap();
+        assert_eq!(
+            value,
+            serde_yml::from_str::<Value>("[1, 1]").unwrap()
+        );
+    }
+
+    #[test]
+    // Tests errors for non-scalar targets and invalid included documents
+    fn test_include_errors() {
+        let resolver = memory(&[("bad.yaml", "key: [unclosed")]);
+        let mut includer = Includer::new(resolver);
+
+        let err =
+            includer.from_str::<Value>("a: !include [x]").unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("a: !include expects a path string"));
+        assert_eq!(err.location().unwrap().column(), 4);
+
+        let err = includer
+            .from_str::<Value>("a: !include bad.yaml")
+            .unwrap_err();
+        assert!(err.to_string().starts_with("bad.yaml: "));
+        assert_eq!(err.file(), Some("bad.yaml"));
+        assert_eq!(err.location().unwrap().line(), 2);
+
+        let err = includer
+            .from_str::<Value>(
+                "a: 1
+b: [!include missing.yaml]",
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r"b.\[0\]: no such document missing.yaml at line 2 column 5"
+        );
+    }
+
+    #[test]
+    // Tests that DirResolver resolves relative paths and stays inside its root
+    fn test_dir_resolver() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("shared")).unwrap();
+        fs::write(
+            root.join("main.yaml"),
+            "db: !include shared/db.yaml\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("shared/db.yaml"),
+            "port: !include ./port.yaml\nup: !include ../up.yaml\n",
+        )
+        .unwrap();
+        fs::write(root.join("shared/port.yaml"), "5432").unwrap();
+        fs::write(root.join("up.yaml"), "true").unwrap();
+
+        // Act
+        let mut includer = Includer::new(DirResolver::new(root));
+        let value: Value = includer.from_path("main.yaml").unwrap();
+        let escape = includer
+            .from_str::<Value>("x: !include ../outside.yaml")
+            .unwrap_err();
+        let absolute = includer
+            .from_str::<Value>("x: !include /etc/passwd")
+            .unwrap_err();
+
+        // Assert
+        assert_eq!(value["db"]["port"], 5432);
+        assert_eq!(value["db"]["up"], true);
+        assert_eq!(
+            escape.to_string(),
+            "x: include path \"../outside.yaml\" escapes the root directory at line 1 column 4"
+        );
+        assert_eq!(
+            absolute.to_string(),
+            "x: include path \"/etc/passwd\" escapes the root directory at line 1 column 4"
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    // Tests that DirResolver rejects paths leaving its root through a
+    // symbolic link
+    fn test_dir_resolver_symlink() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path();
+        let root = base.join("root");
+        fs::create_dir_all(root.join("inner")).unwrap();
+        fs::write(base.join("secret.yaml"), "token: hunter2").unwrap();
+        fs::write(root.join("inner/ok.yaml"), "ok: true").unwrap();
+        std::os::unix::fs::symlink(base, root.join("out")).unwrap();
+        std::os::unix::fs::symlink("inner", root.join("alias"))
+            .unwrap();
+
+        // Act
+        let mut includer = Includer::new(DirResolver::new(&root));
+        let escape = includer
+            .from_str::<Value>("x: !include out/secret.yaml")
+            .unwrap_err();
+        let inside: Value =
+            includer.from_str("x: !include alias/ok.yaml").unwrap();
+
+        // Assert
+        assert_eq!(
+            escape.to_string(),
+            "x: include path \"out/secret.yaml\" escapes the root directory at line 1 column 4"
+        );
+        assert_eq!(inside["x"]["ok"], true);
+    }
+}
@@ -0,0 +1,153 @@
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use serde_yml::lint::{
+        self, Indentation, LintOptions, Presence, Problem, Rule,
+    };
+    use serde_yml::loader::Severity;
+
+    fn render(problems: &[Problem]) -> Vec<String> {
+        problems.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    // Tests that valid input written in the default style has no problems
+    fn test_lint_clean_input() {
+        // Arrange
+        let yaml = indoc! {"
+            ---
+            name: demo
+            enabled: true
+            servers:
+              - host: a.example.com
+                ports: [80, 443]
+        "};
+
+        // Act
+        let problems = lint::lint(yaml);
+
+        // Assert
+        assert!(problems.is_empty(), "{:?}", render(&problems));
+    }
+
+    #[test]
+    // Tests the problems reported by each default rule, with their spans
+    fn test_lint_default_rules() {
+        // Arrange
+        let yaml = "a: 1\na: on\nb:\n    c: 2\n    d:\n      - x \n";
+
+        // Act
+        let problems = lint::lint(yaml);
+
+        // Assert
+        assert_eq!(
+            render(&problems),
+            [
+                "1:1: warning: missing document start \"---\" (document-start)",
+                "2:1: error: duplication of key \"a\" in mapping (key-duplicates)",
+                "2:4: warning: truthy value should be one of [false, true] (truthy)",
+                "6:1: error: wrong indentation: expected 8 but found 6 (indentation)",
+                "6:10: error: trailing spaces (trailing-spaces)",
+            ],
+        );
+        let spans: Vec<_> = problems
+            .iter()
+            .map(|problem| problem.span.clone())
+            .collect();
+        assert_eq!(spans, [0..0, 5..6, 8..10, 30..36, 39..40]);
+        assert_eq!(problems[1].rule, Rule::KeyDuplicates);
+        assert_eq!(problems[1].severity, Severity::Error);
+    }
+
+    #[test]
+    // Tests that duplicates are found among many keys, in each mapping
+    // separately
+    fn test_lint_key_duplicates_many_keys() {
+        // Arrange
+        let mut yaml: String = (0..5_000)
+            .map(|i| format!("key{}: {{key{}: 1}}\n", i, i))
+            .collect();
+        yaml.insert_str(0, "---\n");
+        yaml.push_str("key42: 2\n");
+
+        // Act
+        let problems = lint::lint(&yaml);
+
+        // Assert
+        assert_eq!(
+            render(&problems),
+            ["5002:1: error: duplication of key \"key42\" in mapping (key-duplicates)"],
+        );
+    }
+
+    #[test]
+    // Tests that long lines are reported unless they hold a single word
+    fn test_lint_line_length() {
+        // Arrange
+        let long_url =
+            format!("# https://example.com/{}\n", "a".repeat(30));
+        let long_text = format!("text: {}\n", "word ".repeat(7));
+        let yaml = format!("---\n{}{}", long_url, long_text);
+        let options = LintOptions {
+            line_length: Some(30),
+            ..LintOptions::default()
+        };
+
+        // Act
+        let problems = lint::lint_with(&yaml, &options);
+
+        // Assert
+        assert_eq!(
+            render(&problems),
+            [
+                "3:31: error: line too long (41 > 30 characters) (line-length)",
+                "3:41: error: trailing spaces (trailing-spaces)",
+            ],
+        );
+    }
+
+    #[test]
+    // Tests the configurable indentation and document marker rules
+    fn test_lint_options() {
+        // Arrange
+        let yaml = "%YAML 1.2\n---\nlist:\n- a\nmap:\n  key: 1\n";
+        let options = LintOptions {
+            indentation: Some(Indentation::Spaces(4)),
+            document_start: Some(Presence::Forbidden),
+            document_end: Some(Presence::Required),
+            ..LintOptions::default()
+        };
+
+        // Act
+        let problems = lint::lint_with(yaml, &options);
+
+        // Assert
+        assert_eq!(
+            render(&problems),
+            [
+                "2:1: warning: found forbidden document start \"---\" (document-start)",
+                "6:1: error: wrong indentation: expected 4 but found 2 (indentation)",
+                "7:1: warning: missing document end \"...\" (document-end)",
+            ],
+        );
+    }
+
+    #[test]
+    // Tests that invalid input is reported once, along with the line rules
+    fn test_lint_syntax_error() {
+        // Arrange
+        let yaml = "---\nkey: [1, 2 \nother: 3\n";
+
+        // Act
+        let problems = lint::lint(yaml);
+
+        // Assert
+        assert_eq!(
+            render(&problems),
+            [
+                "2:11: error: trailing spaces (trailing-spaces)",
+                "3:6: error: syntax error: did not find expected ',' or ']' (syntax)",
+            ],
+        );
+    }
+}
@@ -2,8 +2,9 @@
 mod tests {
     use serde_yml::{
         de::{Event, Progress},
-        loader::Loader,
+        loader::{Loader, Severity},
         modules::error::ErrorImpl,
+        Value,
     };
     use std::io::Cursor;
     use std::str;
@@ -425,4 +426,254 @@ mod tests {
         assert!(document.error.is_none());
         assert_eq!(document.anchor_event_map.len(), 0);
     }
+
+    #[test]
+    // Tests that each document exposes its original text and byte range
+    fn test_loader_document_raw() {
+        // Arrange
+        let input = "%YAML 1.2\n---\nname: é\n...\n# between\n--- !tag\nlist: [1,\n  2]\n";
+
+        // Act
+        let mut loader = Loader::new(Progress::Str(input)).unwrap();
+        let first = loader.next_document().unwrap();
+        let second = loader.next_document().unwrap();
+
+        // Assert
+        assert_eq!(first.raw(), "%YAML 1.2\n---\nname: é\n...");
+        assert_eq!(&input[first.span()], first.raw());
+        assert_eq!(second.raw(), "--- !tag\nlist: [1,\n  2]\n");
+        assert_eq!(&input[second.span()], second.raw());
+        assert!(loader.next_document().is_none());
+    }
+
+    #[test]
+    // Tests raw document text for owned input and other line breaks
+    fn test_loader_document_raw_from_reader() {
+        // Arrange
+        let input =
+            "\u{feff}a: 1\r\n--- \"b\\\r\n  ü\"\r\n---\u{2028}c\n";
+
+        // Act
+        let mut loader =
+            Loader::new(Progress::Read(Box::new(Cursor::new(input))))
+                .unwrap();
+        let mut raw = Vec::new();
+        while let Some(document) = loader.next_document() {
+            assert_eq!(&input[document.span()], document.raw());
+            raw.push(document.raw().to_owned());
+        }
+
+        // Assert
+        assert_eq!(
+            raw,
+            ["a: 1\r\n", "--- \"b\\\r\n  ü\"\r\n", "---\u{2028}c\n"]
+        );
+    }
+
+    #[test]
+    // Tests that a recovering loader skips broken lines in every document
+    fn test_loader_recovering_skips_broken_lines() {
+        // Arrange
+        let input = "a: 1\nb: 2: 3\n---\n- x\n- 'open\n";
+
+        // Act
+        let mut loader =
+            Loader::recovering(Progress::Str(input)).unwrap();
+        let mut raw = Vec::new();
+        while let Some(document) = loader.next_document() {
+            assert!(document.error.is_none());
+            raw.push(document.raw().to_owned());
+        }
+
+        // Assert
+        assert_eq!(raw, ["a: 1\n       \n", "---\n- x\n       \n"]);
+        let lines: Vec<_> = loader
+            .diagnostics()
+            .iter()
+            .map(|diagnostic| diagnostic.location.unwrap().line())
+            .collect();
+        assert_eq!(lines, [2, 6]);
+        assert!(loader
+            .diagnostics()
+            .iter()
+            .all(|diagnostic| diagnostic.severity == Severity::Error));
+    }
+
+    #[test]
+    // Tests the best-effort value and diagnostics of from_str_lossy
+    fn test_from_str_lossy() {
+        // Arrange
+        let input =
+            "name: demo\nlist: [1, 2\nalias: *missing\nport: 80\n";
+
+        // Act
+        let (value, diagnostics) = serde_yml::from_str_lossy(input);
+
+        // Assert
+        let expected: Value =
+            serde_yml::from_str("name: demo\nport: 80\n").unwrap();
+        assert_eq!(value, expected);
+        let messages: Vec<_> =
+            diagnostics.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            [
+                "error: did not find expected ',' or ']' at line 3 column 6, while parsing a flow sequence at line 2 column 7",
+                "error: unknown anchor at line 3 column 8",
+            ]
+        );
+    }
+
+    #[test]
+    // Tests from_str_lossy on valid, empty and multi-document input
+    fn test_from_str_lossy_without_errors() {
+        // Arrange
+        let valid = "a: [1, 2]\n";
+        let several = "a: 1\n---\nb: 2\n";
+
+        // Act
+        let (value, diagnostics) = serde_yml::from_str_lossy(valid);
+        let (empty, empty_diagnostics) = serde_yml::from_str_lossy("");
+        let (first, warnings) = serde_yml::from_str_lossy(several);
+
+        // Assert
+        assert_eq!(value, serde_yml::from_str::<Value>(valid).unwrap());
+        assert!(diagnostics.is_empty());
+        assert_eq!(empty, Value::Null);
+        assert!(empty_diagnostics.is_empty());
+        assert_eq!(first["a"], 1);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].severity, Severity::Warning);
+        assert_eq!(warnings[0].location.unwrap().line(), 3);
+    }
+
+    #[test]
+    // Tests the anchors, tags, version and span reported for each document
+    fn test_document_metadata() {
+        // Arrange
+        let input = "%YAML 1.1\n# settings\n---\na: &x !!str 1\nb: *x\nc: &x [3]\nf: [*x, *x]\nd: !local {}\ne: !local 2\n...\n# comment only\n--- plain\n";
+
+        // Act
+        let metadata = serde_yml::de::document_metadata(input).unwrap();
+        let deserializers: Vec<_> =
+            serde_yml::Deserializer::from_str(input)
+                .map(|de| de.metadata().unwrap())
+                .collect();
+
+        // Assert
+        assert_eq!(metadata.len(), 2);
+        let first = &metadata[0];
+        assert_eq!(first.version, Some((1, 1)));
+        assert_eq!(first.tags, ["tag:yaml.org,2002:str", "!local"]);
+        let anchors: Vec<_> = first
+            .anchors
+            .iter()
+            .map(|anchor| {
+                (
+                    anchor.name.as_str(),
+                    anchor.location.line(),
+                    anchor.aliases,
+                )
+            })
+            .collect();
+        assert_eq!(anchors, [("x", 4, 1), ("x", 6, 2)]);
+        assert_eq!(
+            &input[first.span.clone()],
+            &input[..input.find("\n# comment").unwrap()]
+        );
+
+        let second = &metadata[1];
+        assert_eq!(second.version, None);
+        assert!(second.tags.is_empty());
+        assert!(second.anchors.is_empty());
+        assert_eq!(&input[second.span.clone()], "--- plain\n");
+
+        assert_eq!(deserializers.len(), 2);
+        assert_eq!(deserializers[0].span, first.span);
+        assert_eq!(deserializers[1].span, second.span);
+        assert!(serde_yml::Deserializer::from_str(input)
+            .metadata()
+            .is_none());
+    }
+
+    #[test]
+    // Tests document_metadata on input without documents and on bad input
+    fn test_document_metadata_edge_cases() {
+        // Act
+        let empty = serde_yml::de::document_metadata("").unwrap();
+        let comments =
+            serde_yml::de::document_metadata("# nothing\n").unwrap();
+        let bare = serde_yml::de::document_metadata("---\n").unwrap();
+        let unknown = serde_yml::de::document_metadata("a: *missing\n");
+        let broken = serde_yml::de::document_metadata("a: [1\n");
+
+        // Assert
+        assert!(empty.is_empty());
+        assert!(comments.is_empty());
+        assert_eq!(bare.len(), 1);
+        assert_eq!(
+            unknown.unwrap_err().to_string(),
+            "unknown anchor at line 1 column 4"
+        );
+        assert!(broken.is_err());
+    }
+
+    #[test]
+    // Tests that next_value loads each document as a value, keeping the
+    // documents after one that fails
+    fn test_loader_next_value() {
+        // Arrange
+        let input = "a: &x 1\nb: *x\n---\nc: 1\nc: 2\n---\n[d]\n";
+        let mut loader = Loader::new(Progress::Str(input)).unwrap();
+
+        // Act
+        let mut documents = Vec::new();
+        while let Some(document) = loader.next_value() {
+            documents.push(document);
+        }
+
+        // Assert
+        assert_eq!(documents.len(), 3);
+        let first = documents[0].value.as_ref().unwrap();
+        assert_eq!(first["b"], 1);
+        assert_eq!(documents[0].anchors[0].name, "x");
+        assert_eq!(documents[0].anchors[0].aliases, 1);
+        let error = documents[1].value.as_ref().unwrap_err();
+        assert!(
+            error.to_string().contains("duplicate entry"),
+            "{}",
+            error
+        );
+        assert_eq!(
+            &input[documents[1].span.clone()],
+            "---\nc: 1\nc: 2\n"
+        );
+        let third = documents[2].value.as_ref().unwrap();
+        assert_eq!(third[0], "d");
+        assert!(documents[2].anchors.is_empty());
+    }
+
+    #[test]
+    // Tests that next_value returns nothing for a stream without documents
+    // and stops after a syntax error
+    fn test_loader_next_value_end() {
+        // Arrange
+        let mut empty =
+            Loader::new(Progress::Str("# nothing\n")).unwrap();
+        let mut broken =
+            Loader::new(Progress::Str("a: 1\n---\nb: [1\n---\nc: 3\n"))
+                .unwrap();
+
+        // Act
+        let nothing = empty.next_value();
+        let first = broken.next_value().unwrap();
+        let second = broken.next_value().unwrap();
+        let rest = broken.next_value();
+
+        // Assert
+        assert!(nothing.is_none());
+        assert!(first.value.is_ok());
+        assert!(second.value.is_err());
+        assert!(rest.is_none());
+    }
 }
@@ -550,4 +550,122 @@ mod tests {
             Entry::Vacant(_) => panic!("Expected occupied entry"),
         }
     }
+
+    /// Tests that complex keys parsed from YAML can be looked up, hashed and re-emitted.
+    #[test]
+    fn test_mapping_complex_keys() {
+        use std::collections::HashSet;
+
+        let yaml = "? {a: 1, b: [2]}\n: mapping\n? [1, !t x]\n: sequence\n? !tag {c: 3}\n: tagged\n";
+        let value: Value = serde_yml::from_str(yaml).unwrap();
+
+        let key: Value = serde_yml::from_str("{b: [2], a: 1}").unwrap();
+        assert_eq!(value[&key], "mapping");
+        let key: Value = serde_yml::from_str("[1, !t x]").unwrap();
+        assert_eq!(value[&key], "sequence");
+        let key: Value = serde_yml::from_str("!tag {c: 3}").unwrap();
+        assert_eq!(value[&key], "tagged");
+
+        let keys: HashSet<&Value> =
+            value.as_mapping().unwrap().keys().collect();
+        assert_eq!(keys.len(), 3);
+
+        let yaml = serde_yml::to_string(&value).unwrap();
+        let round_trip: Value = serde_yml::from_str(&yaml).unwrap();
+        assert_eq!(round_trip, value);
+    }
+
+    /// Tests that booleans, integers and sequences look up keys of the same kind.
+    #[test]
+    fn test_mapping_scalar_and_sequence_keys() {
+        let yaml = "5: five\n-1: minus\ntrue: on\n? [1, two]\n: pair\n'7': text\n";
+        let mut map: Mapping = serde_yml::from_str(yaml).unwrap();
+
+        assert_eq!(map[5], "five");
+        assert_eq!(map[5u8], "five");
+        assert_eq!(map.get(-1i64).unwrap(), "minus");
+        assert_eq!(map[true], "on");
+        assert!(!map.contains_key(false));
+        assert!(!map.contains_key(7));
+        assert!(map.contains_key("7"));
+
+        let pair = vec![Value::from(1), Value::from("two")];
+        assert_eq!(map[&pair], "pair");
+        assert_eq!(map[pair.as_slice()], "pair");
+
+        map[5usize] = Value::from("cinq");
+        assert_eq!(map.remove(5), Some(Value::from("cinq")));
+        assert_eq!(map.shift_remove(&pair), Some(Value::from("pair")));
+        assert_eq!(map.len(), 3);
+
+        let value = Value::Mapping(map);
+        assert_eq!(value[true], "on");
+        assert_eq!(value[vec![Value::from(1)]], Value::Null);
+    }
+
+    /// Tests that a single-entry mapping whose key is a mapping serializes correctly.
+    #[test]
+    fn test_mapping_single_complex_key() {
+        for yaml in [
+            "? {a: 1}\n: v\n",
+            "? !t {a: [1]}\n: v\n",
+            "? {? {x: 1} : y}\n: z\n",
+        ] {
+            let value: Value = serde_yml::from_str(yaml).unwrap();
+            let serialized = serde_yml::to_string(&value).unwrap();
+            assert_eq!(
+                serde_yml::from_str::<Value>(&serialized).unwrap(),
+                value
+            );
+            assert_eq!(serde_yml::to_value(&value).unwrap(), value);
+        }
+    }
+
+    /// Tests that equal floats used as keys hash equally.
+    #[test]
+    fn test_mapping_float_keys() {
+        let mut map = Mapping::new();
+        map.insert(Value::from(0.0), Value::from("zero"));
+        map.insert(Value::from(f64::NAN), Value::from("nan"));
+        assert_eq!(
+            map.get(Value::from(-0.0)),
+            Some(&Value::from("zero"))
+        );
+        assert_eq!(
+            map.get(Value::from(-f64::NAN)),
+            Some(&Value::from("nan"))
+        );
+    }
+
+    /// Tests iterating over a mapping in each `EntryOrder`.
+    #[test]
+    fn test_mapping_sorted_iter() {
+        let map: Mapping =
+            serde_yml::from_str("b: 1\n10: 2\na: 3\n'10': 4\n9: 5\n")
+                .unwrap();
+        let values = |order| {
+            map.sorted_iter(order)
+                .map(|(_, value)| value.as_i64().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(values(EntryOrder::Insertion), [1, 2, 3, 4, 5]);
+        assert_eq!(values(EntryOrder::Reversed), [5, 4, 3, 2, 1]);
+        assert_eq!(values(EntryOrder::Key), [5, 2, 4, 3, 1]);
+        assert_eq!(values(EntryOrder::KeyText), [2, 4, 5, 3, 1]);
+        assert_eq!(map.sorted_iter(EntryOrder::Key).len(), 5);
+        assert_eq!(map.iter().next().unwrap().0, "b");
+    }
+
+    /// Tests sorting the entries of a mapping in place.
+    #[test]
+    fn test_mapping_sort_keys() {
+        let mut map: Mapping =
+            serde_yml::from_str("b: 1\na: 2\n3: 3\n").unwrap();
+        map.sort_keys();
+        let keys: Vec<&Value> = map.keys().collect();
+        assert_eq!(
+            keys,
+            [&Value::from(3), &Value::from("a"), &Value::from("b")]
+        );
+    }
 }
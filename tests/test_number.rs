@@ -264,4 +264,85 @@ mod tests {
             serde_yml::from_str(&serialized).unwrap();
         assert_eq!(number, deserialized);
     }
+
+    // Tests for integers beyond 64 bits with the bigint feature
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn test_big_integers() {
+        use num_bigint::BigInt;
+        use serde_yml::Value;
+
+        // Arrange
+        let huge = "340282366920938463463374607431768211456";
+        let yaml = format!(
+            "huge: {huge}\nnegative: -{huge}\nwide: 18446744073709551616\nhex: 0x100000000000000000000000000000000\n",
+        );
+
+        // Act
+        let value: Value = serde_yml::from_str(&yaml).unwrap();
+
+        // Assert
+        let number = |value: &Value| match value {
+            Value::Number(number) => number.clone(),
+            _ => panic!("expected a number, found {:?}", value),
+        };
+        let expected = BigInt::from(u128::MAX) + 1u8;
+        let huge_number = number(&value["huge"]);
+        assert_eq!(huge_number.as_big_int(), Some(expected.clone()));
+        assert_eq!(huge_number.as_u64(), None);
+        assert_eq!(huge_number.to_string(), huge);
+        assert_eq!(
+            number(&value["negative"]).as_big_int(),
+            Some(-expected.clone()),
+        );
+        assert_eq!(value["hex"], value["huge"]);
+        assert_eq!(
+            value["wide"],
+            Value::Number(Number::from(u128::from(u64::MAX) + 1)),
+        );
+        assert_eq!(serde_yml::to_string(&value).unwrap(), format!(
+            "huge: {huge}\nnegative: -{huge}\nwide: 18446744073709551616\nhex: {huge}\n",
+        ));
+
+        let number = Number::from_str(huge).unwrap();
+        let parsed: Number = serde_yml::from_str(huge).unwrap();
+        assert_eq!(parsed, number);
+        assert_eq!(
+            serde_yml::to_value(&number).unwrap(),
+            value["huge"]
+        );
+        assert_eq!(
+            serde_yml::from_value::<Value>(value.clone()).unwrap(),
+            value,
+        );
+        assert_eq!(
+            serde_yml::from_str::<u128>("18446744073709551616")
+                .unwrap(),
+            1 << 64
+        );
+        assert!(serde_yml::from_str::<u128>(huge).is_err());
+
+        let json = serde_json::to_string(&value["huge"]).unwrap();
+        assert_eq!(json, format!("\"{huge}\""));
+
+        let small = Number::from(u64::MAX);
+        let negative = Number::from(i64::MIN);
+        let big = Number::from(expected.clone());
+        let big_negative = Number::from(-expected);
+        assert_eq!(big.partial_cmp(&small), Some(Ordering::Greater));
+        assert_eq!(
+            big_negative.partial_cmp(&negative),
+            Some(Ordering::Less)
+        );
+        assert_eq!(Number::from(BigInt::from(7)), Number::from(7));
+        assert!(Number::from_str("0777").is_err());
+        assert!(Number::from_str("1_000000000000000000000").is_err());
+
+        let string = Value::String(huge.to_owned());
+        let yaml = serde_yml::to_string(&string).unwrap();
+        assert_eq!(
+            serde_yml::from_str::<Value>(&yaml).unwrap(),
+            string
+        );
+    }
 }
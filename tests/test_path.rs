@@ -0,0 +1,188 @@
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_yml::path::{Path, Segment};
+    use serde_yml::Value;
+    use std::collections::BTreeMap;
+
+    #[test]
+    // Tests that both text forms are written and read back unchanged
+    fn test_path_text_forms() {
+        // Arrange
+        let cases = [
+            (vec![], ".", ""),
+            (vec![Segment::from(0)], "[0]", "/0"),
+            (
+                vec![
+                    Segment::from("servers"),
+                    Segment::from(10),
+                    Segment::from("port"),
+                ],
+                "servers[10].port",
+                "/servers/10/port",
+            ),
+            (
+                vec![Segment::from("a.b"), Segment::from("")],
+                r#""a.b"."""#,
+                "/a.b/",
+            ),
+            (
+                vec![
+                    Segment::from("/etc"),
+                    Segment::from(r#"say "hi"\"#),
+                    Segment::from("~x"),
+                ],
+                r#""/etc"."say \"hi\"\\".~x"#,
+                r#"/~1etc/say "hi"\/~0x"#,
+            ),
+        ];
+
+        for (segments, dotted, pointer) in cases {
+            // Act
+            let path = Path::from(segments.clone());
+
+            // Assert
+            assert_eq!(path.segments(), segments.as_slice());
+            assert_eq!(path.to_string(), dotted);
+            assert_eq!(path.to_pointer(), pointer);
+            assert_eq!(
+                dotted.parse::<Path>().unwrap(),
+                path,
+                "{}",
+                dotted
+            );
+            if !segments.is_empty() {
+                assert_eq!(
+                    pointer.parse::<Path>().unwrap(),
+                    path,
+                    "{}",
+                    pointer
+                );
+            }
+        }
+        assert_eq!(
+            ".a[1]".parse::<Path>().unwrap().to_string(),
+            "a[1]"
+        );
+        assert_eq!(
+            Path::from_pointer("/01/1").unwrap().segments(),
+            [Segment::from("01"), Segment::from(1)]
+        );
+    }
+
+    #[test]
+    // Tests the errors for malformed paths
+    fn test_path_parse_errors() {
+        for (text, reason) in [
+            ("a..b", "expected a key after `.`"),
+            ("a.", "expected a key after `.`"),
+            ("a[1", "`[` must be closed by `]`"),
+            ("a[x]", "expected an index between `[` and `]`"),
+            ("a[01]", "expected an index between `[` and `]`"),
+            ("a.]b", "keys containing `.[]\"\\` must be quoted"),
+            ("\"a", "a quoted key must end with `\"`"),
+            ("\"a\"b", "expected `.` or `[` after a key"),
+            ("/a~2", "`~` must be followed by `0` or `1`"),
+        ] {
+            // Act
+            let err = text.parse::<Path>().unwrap_err();
+
+            // Assert
+            assert_eq!(
+                err.to_string(),
+                format!("invalid path `{}`: {}", text, reason)
+            );
+        }
+    }
+
+    #[test]
+    // Tests that clones share segments until modified
+    fn test_path_editing() {
+        // Arrange
+        let mut path = Path::root();
+
+        // Act
+        path.push("a");
+        path.extend(vec![Segment::from(1), Segment::from("b")]);
+        let clone = path.clone();
+        let popped = path.pop();
+
+        // Assert
+        assert_eq!(popped, Some(Segment::from("b")));
+        assert_eq!(path.to_string(), "a[1]");
+        assert_eq!(clone.to_string(), "a[1].b");
+        assert_eq!(clone.last(), Some(&Segment::from("b")));
+        assert_eq!(clone.parent(), Some(path.clone()));
+        assert!(clone.starts_with(&path));
+        assert!(!path.starts_with(&clone));
+        assert!(Path::root().is_root());
+        assert_eq!(Path::root().parent(), None);
+        assert_eq!(Path::root().pop(), None);
+    }
+
+    #[test]
+    // Tests that errors report the path of the value that failed
+    fn test_error_path() {
+        // Arrange
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Config {
+            servers: Vec<Server>,
+        }
+
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Server {
+            port: u16,
+        }
+
+        let yaml =
+            "base: &base {port: x}\nservers:\n- port: 80\n- *base\n";
+
+        // Act
+        let err = serde_yml::from_str::<Config>(yaml).unwrap_err();
+        let syntax =
+            serde_yml::from_str::<Config>("servers: [").unwrap_err();
+        let complex = serde_yml::from_str::<BTreeMap<Vec<u8>, Server>>(
+            "? [1]\n: {port: x}\n",
+        )
+        .unwrap_err();
+
+        // Assert
+        let expected: Path = "servers[1].port".parse().unwrap();
+        assert_eq!(err.path(), Some(&expected));
+        assert!(err.to_string().starts_with("servers.\\[1\\].port: "));
+        let shared = serde_yml::modules::error::shared(err.shared());
+        assert_eq!(shared.path(), Some(&expected));
+        assert_eq!(syntax.path(), None);
+        assert_eq!(complex.path(), None);
+        assert!(complex.to_string().starts_with("?: invalid type"));
+    }
+
+    #[test]
+    // Tests looking up values by path and serializing paths as strings
+    fn test_value_get_path() {
+        // Arrange
+        let mut value: Value = serde_yml::from_str(
+            "a:\n  - b: 1\n  - !Tag {b: 2}\n1: one\n",
+        )
+        .unwrap();
+        let path: Path = "a[1].b".parse().unwrap();
+
+        // Act
+        *value.get_path_mut(&path).unwrap() = Value::from(3);
+        let yaml = serde_yml::to_string(&vec![path.clone()]).unwrap();
+        let parsed: Vec<Path> = serde_yml::from_str(&yaml).unwrap();
+
+        // Assert
+        assert_eq!(value.get_path(&path), Some(&Value::from(3)));
+        assert_eq!(value.get_path(&Path::root()), Some(&value));
+        assert_eq!(
+            value.get_path(&Path::root().join(1)),
+            Some(&Value::from("one"))
+        );
+        assert_eq!(value.get_path(&"a[2]".parse().unwrap()), None);
+        assert_eq!(yaml, "- a[1].b\n");
+        assert_eq!(parsed, [path]);
+    }
+}
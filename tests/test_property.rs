@@ -0,0 +1,208 @@
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use proptest::sample::{select, Index};
+    use serde_yml::value::{Tag, TaggedValue};
+    use serde_yml::{Number, Value};
+
+    /// Pieces of strings that are easy to get wrong when writing YAML:
+    /// scalars that would be read as something else, indicators, line
+    /// breaks and characters that need escaping.
+    const WORDS: &[&str] = &[
+        "",
+        "a",
+        "key",
+        "null",
+        "Null",
+        "~",
+        "true",
+        "no",
+        "yes",
+        "0",
+        "-1",
+        "0x1F",
+        "1e3",
+        ".inf",
+        "-.nan",
+        "1.5",
+        "007",
+        "2001-12-14",
+        "---",
+        "...",
+        "- item",
+        "a: b",
+        "#",
+        "!tag",
+        "&anchor",
+        "*alias",
+        "{",
+        "]",
+        "|",
+        ">",
+        "%",
+        "@",
+        "`",
+        "'",
+        "\"",
+        "\\",
+        " ",
+        "\t",
+        "\n",
+        "\r\n",
+        "é",
+        "日本",
+        "\u{1F600}",
+        "\u{7F}",
+        "\u{0}",
+        "\u{FEFF}",
+        "\u{85}",
+    ];
+
+    /// Strings made of up to three [`WORDS`].
+    fn string() -> impl Strategy<Value = String> {
+        prop::collection::vec(select(WORDS), 0..4)
+            .prop_map(|words| words.concat())
+    }
+
+    /// Finite numbers, integers of every size and small integers alike.
+    fn number() -> impl Strategy<Value = Number> {
+        use prop::num::f64::{
+            NEGATIVE, NORMAL, POSITIVE, SUBNORMAL, ZERO,
+        };
+
+        prop_oneof![
+            any::<i64>().prop_map(Number::from),
+            any::<u64>().prop_map(Number::from),
+            (-50i64..50).prop_map(Number::from),
+            (POSITIVE | NEGATIVE | NORMAL | SUBNORMAL | ZERO)
+                .prop_map(Number::from),
+        ]
+    }
+
+    /// Scalars used as mapping keys.
+    fn key() -> impl Strategy<Value = Value> {
+        prop_oneof![
+            1 => Just(Value::Null),
+            1 => any::<bool>().prop_map(Value::Bool),
+            1 => number().prop_map(Value::Number),
+            3 => string().prop_map(Value::String),
+        ]
+    }
+
+    /// Values nested up to three levels deep. A tag directly on a
+    /// tagged value cannot be written, so tagged values hold untagged
+    /// ones.
+    fn value() -> impl Strategy<Value = Value> {
+        let leaf = prop_oneof![
+            Just(Value::Null),
+            any::<bool>().prop_map(Value::Bool),
+            number().prop_map(Value::Number),
+            string().prop_map(Value::String),
+        ];
+        leaf.prop_recursive(3, 64, 4, |inner| {
+            let untagged = inner
+                .clone()
+                .prop_filter("tag on a tagged value", |value| {
+                    !matches!(value, Value::Tagged(_))
+                });
+            prop_oneof![
+                prop::collection::vec(inner.clone(), 0..4)
+                    .prop_map(Value::Sequence),
+                prop::collection::vec((key(), inner), 0..4).prop_map(
+                    |entries| {
+                        Value::Mapping(entries.into_iter().collect())
+                    }
+                ),
+                (0..3u8, untagged).prop_map(|(tag, value)| {
+                    Value::Tagged(Box::new(TaggedValue {
+                        tag: Tag::new(format!("!t{}", tag)),
+                        value,
+                    }))
+                }),
+            ]
+        })
+    }
+
+    /// One of a few documents with a handful of bytes inserted, removed
+    /// or repeated.
+    fn mutated_document() -> impl Strategy<Value = Vec<u8>> {
+        const SEEDS: &[&[u8]] = &[
+            b"a: [1, {b: c}]\n",
+            b"- &x !t {k: *x}\n- ? [1]\n  : |\n   text\n",
+            b"%YAML 1.2\n--- !!map\n\"q\\x41\": 'a''b'\n...\n",
+            b"k: >-\n  folded\n\n  more\n<<: {m: 1}\n",
+        ];
+        const SPICE: &[u8] =
+            b"-:?,[]{}#&*!|>'\"%@`\\\n\t \xEF\xBB\xBF\x00\x85";
+
+        let edit = (0..4u8, any::<Index>(), select(SPICE), any::<u8>());
+        (select(SEEDS), prop::collection::vec(edit, 1..9)).prop_map(
+            |(seed, edits)| {
+                let mut bytes = seed.to_vec();
+                for (kind, at, spice, byte) in edits {
+                    let at = at.index(bytes.len() + 1);
+                    match kind {
+                        0 => bytes.insert(at, spice),
+                        1 => bytes.insert(at, byte),
+                        2 if at < bytes.len() => {
+                            bytes.remove(at);
+                        }
+                        _ => {
+                            let copy = bytes[at..].to_vec();
+                            bytes.extend_from_slice(&copy);
+                        }
+                    }
+                }
+                bytes
+            },
+        )
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(2_000))]
+
+        /// Tests that any value is read back as written.
+        #[test]
+        fn test_value_round_trip(value in value()) {
+            // Act
+            let yaml = serde_yml::to_string(&value).unwrap();
+            let read: Value =
+                serde_yml::from_str(&yaml).map_err(|err| {
+                    TestCaseError::fail(format!("{}\n{}", err, yaml))
+                })?;
+
+            // Assert
+            prop_assert_eq!(read, value, "{}", yaml);
+        }
+
+        /// Tests that reading arbitrary bytes returns instead of
+        /// panicking.
+        #[test]
+        fn test_from_slice_never_panics(bytes in mutated_document()) {
+            let _ = serde_yml::from_slice::<Value>(&bytes);
+        }
+
+        /// Tests that any document that can be read is read back the
+        /// same after being written.
+        #[test]
+        fn test_document_round_trip(bytes in mutated_document()) {
+            // Arrange
+            let value = match serde_yml::from_slice::<Value>(&bytes) {
+                Ok(value) => value,
+                Err(_) => return Ok(()),
+            };
+
+            // Act
+            let yaml = serde_yml::to_string(&value).map_err(|err| {
+                TestCaseError::fail(format!("{}\n{:?}", err, value))
+            })?;
+            let read: Value =
+                serde_yml::from_str(&yaml).map_err(|err| {
+                    TestCaseError::fail(format!("{}\n{}", err, yaml))
+                })?;
+
+            // Assert
+            prop_assert_eq!(read, value, "{}", yaml);
+        }
+    }
+}
@@ -3,7 +3,8 @@ mod tests {
     use serde::ser::{SerializeTuple, SerializeTupleStruct};
     use serde::{ser::Serializer as _, Serialize};
     use serde_yml::{
-        libyml::emitter::{Scalar, ScalarStyle},
+        libyml::emitter::{Encoding, Scalar},
+        ser::{FloatFormat, SerializerOptions, SpecialFloatStyle},
         Serializer, State,
     };
     use std::{collections::BTreeMap, fmt::Write};
@@ -14,11 +15,7 @@ mod tests {
         // Arrange
         let mut buffer = Vec::new();
         let mut serializer = Serializer::new(&mut buffer);
-        let scalar_value = Scalar {
-            tag: None,
-            value: "test value",
-            style: ScalarStyle::Plain,
-        };
+        let scalar_value = Scalar::plain("test value");
 
         // Act
         serializer.emit_scalar(scalar_value).unwrap();
@@ -226,6 +223,82 @@ mod tests {
         assert_eq!(&*result, &buffer_clone);
     }
 
+    #[test]
+    fn test_into_inner_returns_borrowed_writer() {
+        // Arrange
+        let mut buffer = Vec::new();
+        let mut serializer = Serializer::new(&mut buffer);
+
+        // Act
+        vec!["a", "b"].serialize(&mut serializer).unwrap();
+        let writer: &mut Vec<u8> = serializer.into_inner().unwrap();
+        writer.extend_from_slice(b"# end\n");
+
+        // Assert
+        assert_eq!(buffer, b"- a\n- b\n# end\n");
+    }
+
+    #[test]
+    fn test_serializer_dropped_without_into_inner() {
+        // Arrange
+        let mut buffer = Vec::new();
+        let mut serializer = Serializer::new(&mut buffer);
+
+        // Act
+        "a".serialize(&mut serializer).unwrap();
+        serializer.flush().unwrap();
+
+        // Assert
+        assert_eq!(String::from_utf8(buffer).unwrap(), "a\n");
+    }
+
+    #[test]
+    fn test_escape_unicode_escapes_non_ascii() {
+        // Arrange
+        let mut buffer = Vec::new();
+        let mut serializer = Serializer::new(&mut buffer).with_options(
+            SerializerOptions {
+                escape_unicode: true,
+                ..SerializerOptions::default()
+            },
+        );
+
+        // Act
+        let mut map = BTreeMap::new();
+        map.insert("name", "Zoë 😀");
+        map.insert("ascii", "plain");
+        map.serialize(&mut serializer).unwrap();
+
+        // Assert
+        let yaml = String::from_utf8(buffer).unwrap();
+        assert_eq!(
+            yaml,
+            "ascii: plain\nname: \"Zo\\xEB \\U0001F600\"\n"
+        );
+        let back: BTreeMap<String, String> =
+            serde_yml::from_str(&yaml).unwrap();
+        assert_eq!(back["name"], "Zoë 😀");
+    }
+
+    #[test]
+    fn test_encoding_utf16_be() {
+        // Arrange
+        let mut buffer = Vec::new();
+        let mut serializer = Serializer::new(&mut buffer).with_options(
+            SerializerOptions {
+                encoding: Encoding::Utf16Be,
+                ..SerializerOptions::default()
+            },
+        );
+
+        // Act
+        vec!["é"].serialize(&mut serializer).unwrap();
+        serializer.into_inner().unwrap();
+
+        // Assert
+        assert_eq!(buffer, b"\xFE\xFF\0-\0 \0\xE9\0\n");
+    }
+
     // Test cases for serializing boolean values
     #[test]
     fn test_serialize_bool() {
@@ -851,4 +924,835 @@ mod tests {
             "Serialized custom serializer doesn't match expected output"
         );
     }
+
+    /// Serializes `value` with both `to_string` and the emitter-backed
+    /// `to_writer`, asserting that the outputs are identical.
+    fn assert_same_output<T: Serialize + ?Sized>(value: &T) {
+        let mut buffer = Vec::new();
+        serde_yml::to_writer(&mut buffer, value).unwrap();
+        assert_eq!(
+            serde_yml::to_string(value).unwrap(),
+            String::from_utf8(buffer).unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_to_string_fast_path_matches_emitter() {
+        #[derive(Serialize)]
+        enum Level {
+            Info,
+        }
+
+        #[derive(Serialize)]
+        struct Flat {
+            name: &'static str,
+            port: u16,
+            ratio: f64,
+            enabled: bool,
+            level: Level,
+            missing: Option<i32>,
+            initial: char,
+        }
+
+        let strings = [
+            "",
+            "plain",
+            "two words",
+            "true",
+            "False",
+            "yes",
+            "n",
+            "off",
+            "null",
+            "Null",
+            "123",
+            "-4",
+            "1.5",
+            "1e3",
+            ".inf",
+            "0x1f",
+            "0o17",
+            "a.b/c-d_e",
+            "-",
+            "-x",
+            "--",
+            ".",
+            "..",
+            "/",
+            " lead",
+            "trail ",
+            "has: colon",
+            "hash #",
+            "multi\nline",
+            "~",
+            "<<",
+            "2024-01-01",
+            "caf\u{e9}",
+        ];
+        for s in strings {
+            assert_same_output(s);
+            let mut map = BTreeMap::new();
+            map.insert(s, s);
+            assert_same_output(&map);
+        }
+
+        for f in [0.0, -0.0, 1.5, 1e300, f64::INFINITY, f64::NAN] {
+            assert_same_output(&f);
+        }
+        assert_same_output(&u128::MAX);
+        assert_same_output(&i64::MIN);
+        assert_same_output(&());
+        assert_same_output(&None::<u8>);
+        assert_same_output(&Some('x'));
+        assert_same_output(&'-');
+
+        assert_same_output(&Flat {
+            name: "server",
+            port: 8080,
+            ratio: 0.25,
+            enabled: true,
+            level: Level::Info,
+            missing: None,
+            initial: 'q',
+        });
+
+        // Shapes that always go through the emitter.
+        assert_same_output(&BTreeMap::<u8, u8>::new());
+        assert_same_output(&vec![1, 2, 3]);
+        let nested: BTreeMap<&str, Vec<u8>> =
+            [("k", vec![1])].into_iter().collect();
+        assert_same_output(&nested);
+        let large: BTreeMap<u32, u32> =
+            (0..32).map(|i| (i, i)).collect();
+        assert_same_output(&large);
+
+        // Documents handed over to the emitter partway through a mapping.
+        let long = "x".repeat(100);
+        let mut map = BTreeMap::new();
+        map.insert("a", "1");
+        map.insert("b", long.as_str());
+        map.insert("c", "3");
+        assert_same_output(&map);
+        let mut map = BTreeMap::new();
+        map.insert("a", "1");
+        map.insert(long.as_str(), "2");
+        assert_same_output(&map);
+        let mut map = BTreeMap::new();
+        map.insert("!tag", "1");
+        assert_same_output(&map);
+        let mut map = BTreeMap::new();
+        map.insert(vec!["k"], "1");
+        assert_same_output(&map);
+        let mut map = BTreeMap::new();
+        map.insert("a", vec![]);
+        map.insert("b", vec![1]);
+        assert_same_output(&map);
+        let mixed: BTreeMap<u32, Option<Vec<u32>>> = (0..20)
+            .map(|i| (i, if i == 18 { Some(vec![i]) } else { None }))
+            .collect();
+        assert_same_output(&mixed);
+        assert_same_output(&Flat {
+            name: "two\nlines",
+            port: 1,
+            ratio: 0.5,
+            enabled: false,
+            level: Level::Info,
+            missing: Some(2),
+            initial: '!',
+        });
+    }
+
+    /// Tests that `to_string` serializes the value once, even when it hands
+    /// the document over to the emitter partway.
+    #[test]
+    fn test_to_string_serializes_once() {
+        use serde::ser::SerializeMap;
+        use std::cell::{Cell, RefCell};
+
+        /// A sequence that can only be written once.
+        struct Once(RefCell<Option<std::vec::IntoIter<u8>>>);
+
+        impl Serialize for Once {
+            fn serialize<S: serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                let items =
+                    self.0.borrow_mut().take().unwrap_or_default();
+                serializer.collect_seq(items)
+            }
+        }
+
+        /// A mapping that counts how often it is serialized.
+        struct Counted<'a> {
+            calls: &'a Cell<usize>,
+            items: Once,
+        }
+
+        impl Serialize for Counted<'_> {
+            fn serialize<S: serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                self.calls.set(self.calls.get() + 1);
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("name", "web")?;
+                map.serialize_entry("items", &self.items)?;
+                map.end()
+            }
+        }
+
+        let calls = Cell::new(0);
+        let value = Counted {
+            calls: &calls,
+            items: Once(RefCell::new(Some(vec![1, 2].into_iter()))),
+        };
+        assert_eq!(
+            serde_yml::to_string(&value).unwrap(),
+            "name: web\nitems:\n- 1\n- 2\n",
+        );
+        assert_eq!(calls.get(), 1);
+    }
+
+    /// Tests that documents are written with the chosen line break and read
+    /// back unchanged.
+    #[test]
+    fn test_line_break() {
+        use serde_yml::libyml::emitter::LineBreak;
+
+        // Arrange
+        let mut value = BTreeMap::new();
+        let _ = value.insert("list", vec!["a", "b"]);
+        let _ = value.insert("text", vec!["line one\nline two\n"]);
+
+        for (line_break, expected) in [
+            (LineBreak::Lf, "\n"),
+            (LineBreak::CrLf, "\r\n"),
+            (LineBreak::Cr, "\r"),
+        ] {
+            // Act
+            let mut buffer = Vec::new();
+            let mut serializer = Serializer::new(&mut buffer)
+                .with_options(SerializerOptions {
+                    line_break,
+                    ..SerializerOptions::default()
+                });
+            value.serialize(&mut serializer).unwrap();
+            let yaml = String::from_utf8(buffer).unwrap();
+
+            // Assert
+            assert_eq!(
+                yaml,
+                "list:\n- a\n- b\ntext:\n- |\n  line one\n  line two\n"
+                    .replace('\n', expected)
+            );
+            let parsed: BTreeMap<&str, Vec<String>> =
+                serde_yml::from_str(&yaml).unwrap();
+            assert_eq!(parsed["text"], ["line one\nline two\n"]);
+        }
+    }
+
+    #[test]
+    /// Tests writing multi-line strings in each multiline style.
+    fn test_multiline_style() {
+        use serde_yml::ser::MultilineStyle;
+
+        // Arrange
+        let value = vec!["one\ntwo\n", "one\ntwo", "one\n\n", "short"];
+
+        for (style, expected) in [
+            (
+                MultilineStyle::Literal,
+                "- |\n  one\n  two\n- |-\n  one\n  two\n- |+\n  one\n\n- short\n",
+            ),
+            (
+                MultilineStyle::Folded,
+                "- >\n  one\n\n  two\n- >-\n  one\n\n  two\n- >+\n  one\n\n- short\n",
+            ),
+            (
+                MultilineStyle::DoubleQuoted,
+                "- \"one\\ntwo\\n\"\n- \"one\\ntwo\"\n- \"one\\n\\n\"\n- short\n",
+            ),
+        ] {
+            // Act
+            let mut buffer = Vec::new();
+            let mut serializer =
+                Serializer::new(&mut buffer).with_options(SerializerOptions {
+                    multiline_style: style,
+                    ..SerializerOptions::default()
+                });
+            value.serialize(&mut serializer).unwrap();
+            let yaml = String::from_utf8(buffer).unwrap();
+
+            // Assert
+            assert_eq!(yaml, expected, "{:?}", style);
+            let parsed: Vec<String> = serde_yml::from_str(&yaml).unwrap();
+            assert_eq!(parsed, value, "{:?}", style);
+        }
+    }
+
+    /// Tests that documents are written with the chosen document markers,
+    /// changed between documents, and read back unchanged.
+    #[test]
+    fn test_document_markers() {
+        use serde_yml::ser::{
+            to_string_with_options, SerializerOptions,
+        };
+
+        // Arrange
+        let mut value = BTreeMap::new();
+        let _ = value.insert("k", 1);
+        let both = SerializerOptions {
+            explicit_document_start: true,
+            explicit_document_end: true,
+            ..SerializerOptions::default()
+        };
+        let end_only = SerializerOptions {
+            explicit_document_end: true,
+            ..SerializerOptions::default()
+        };
+
+        // Act
+        let explicit =
+            to_string_with_options(&value, both.clone()).unwrap();
+        let implicit = to_string_with_options(
+            &value,
+            SerializerOptions::default(),
+        )
+        .unwrap();
+        let ended = to_string_with_options(&"text", end_only).unwrap();
+        let mut buffer = Vec::new();
+        let mut serializer =
+            Serializer::new(&mut buffer).with_options(both);
+        value.serialize(&mut serializer).unwrap();
+        serializer.set_options(SerializerOptions::default());
+        value.serialize(&mut serializer).unwrap();
+        value.serialize(&mut serializer).unwrap();
+        let stream = String::from_utf8(buffer).unwrap();
+
+        // Assert
+        assert_eq!(explicit, "---\nk: 1\n...\n");
+        assert_eq!(implicit, serde_yml::to_string(&value).unwrap());
+        assert_eq!(ended, "text\n...\n");
+        assert_eq!(stream, "---\nk: 1\n...\n---\nk: 1\n---\nk: 1\n");
+        let parsed: BTreeMap<String, i32> =
+            serde_yml::from_str(&explicit).unwrap();
+        assert_eq!(
+            parsed,
+            value.into_iter().map(|(k, v)| (k.to_owned(), v)).collect()
+        );
+        assert_eq!(serde_yml::de::document_count(&stream).unwrap(), 3);
+    }
+
+    /// Tests that null values are written in each null style and read back
+    /// as null.
+    #[test]
+    fn test_null_style() {
+        use serde_yml::ser::{
+            to_string_with_options, NullStyle, SerializerOptions,
+        };
+
+        // Arrange
+        let mut value: BTreeMap<Option<&str>, Vec<Option<u8>>> =
+            BTreeMap::new();
+        let _ = value.insert(Some("list"), vec![None, Some(1)]);
+        let _ = value.insert(None, vec![]);
+        let mut field = BTreeMap::new();
+        let _ = field.insert("key", None::<u8>);
+
+        for (null_style, expected, expected_field) in [
+            (
+                NullStyle::Literal,
+                "null: []\nlist:\n- null\n- 1\n",
+                "key: null\n",
+            ),
+            (NullStyle::Tilde, "~: []\nlist:\n- ~\n- 1\n", "key: ~\n"),
+            (NullStyle::Empty, "~: []\nlist:\n-\n- 1\n", "key:\n"),
+        ] {
+            let options = SerializerOptions {
+                null_style,
+                ..SerializerOptions::default()
+            };
+
+            // Act
+            let yaml = to_string_with_options(&value, options.clone())
+                .unwrap();
+            let field_yaml =
+                to_string_with_options(&field, options).unwrap();
+
+            // Assert
+            assert_eq!(yaml, expected, "{:?}", null_style);
+            assert_eq!(field_yaml, expected_field, "{:?}", null_style);
+            let parsed: BTreeMap<Option<String>, Vec<Option<u8>>> =
+                serde_yml::from_str(&yaml).unwrap();
+            assert_eq!(parsed[&None], []);
+            assert_eq!(
+                parsed[&Some("list".to_owned())],
+                [None, Some(1)]
+            );
+            let parsed: serde_yml::Value =
+                serde_yml::from_str(&field_yaml).unwrap();
+            assert!(parsed["key"].is_null());
+        }
+    }
+
+    /// Tests that `None` struct fields are left out when asked, while other
+    /// null values are still written.
+    #[test]
+    fn test_skip_none_fields() {
+        use serde_yml::ser::{
+            to_string_with_options, SerializerOptions,
+        };
+
+        // Arrange
+        #[derive(Serialize)]
+        struct Inner {
+            set: Option<u8>,
+            unset: Option<u8>,
+        }
+
+        #[derive(Serialize)]
+        enum Shape {
+            Square {
+                side: Option<u8>,
+                label: Option<String>,
+            },
+        }
+
+        #[derive(Serialize)]
+        struct Config {
+            name: Option<String>,
+            port: Option<u16>,
+            inner: Inner,
+            empty: Inner,
+            shapes: Vec<Shape>,
+            unit: (),
+            value: serde_yml::Value,
+        }
+
+        let config = Config {
+            name: Some("app".to_owned()),
+            port: None,
+            inner: Inner {
+                set: Some(1),
+                unset: None,
+            },
+            empty: Inner {
+                set: None,
+                unset: None,
+            },
+            shapes: vec![Shape::Square {
+                side: Some(2),
+                label: None,
+            }],
+            unit: (),
+            value: serde_yml::Value::Null,
+        };
+        let options = SerializerOptions {
+            skip_none_fields: true,
+            ..SerializerOptions::default()
+        };
+
+        let map = BTreeMap::from([
+            ("k".to_owned(), None),
+            ("j".to_owned(), Some(1u8)),
+        ]);
+
+        // Act
+        let skipped =
+            to_string_with_options(&config, options.clone()).unwrap();
+        let kept = serde_yml::to_string(&config).unwrap();
+        let map = to_string_with_options(&map, options).unwrap();
+
+        // Assert
+        assert_eq!(
+            skipped,
+            concat!(
+                "name: app\n",
+                "inner:\n",
+                "  set: 1\n",
+                "empty: {}\n",
+                "shapes:\n",
+                "- !Square\n",
+                "  side: 2\n",
+                "unit: null\n",
+                "value: null\n",
+            )
+        );
+        assert!(kept.contains("port: null\n"));
+        assert!(kept.contains("  unset: null\n"));
+        assert_eq!(map, "j: 1\nk: null\n");
+    }
+
+    /// Tests that empty sequences and mappings are written in each style
+    /// and read back empty.
+    #[test]
+    fn test_empty_collections() {
+        use serde::Deserialize;
+        use serde_yml::ser::{
+            to_string_with_options, EmptyCollections, SerializerOptions,
+        };
+
+        // Arrange
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Chart {
+            name: String,
+            #[serde(default)]
+            args: Vec<String>,
+            #[serde(default)]
+            labels: BTreeMap<String, String>,
+            #[serde(default)]
+            nested: Vec<Vec<u8>>,
+        }
+
+        let chart = Chart {
+            name: "web".to_owned(),
+            args: vec![],
+            labels: BTreeMap::new(),
+            nested: vec![vec![], vec![1]],
+        };
+
+        for (empty_collections, expected) in [
+            (
+                EmptyCollections::Flow,
+                "name: web\nargs: []\nlabels: {}\nnested:\n- []\n- - 1\n",
+            ),
+            (
+                EmptyCollections::Block,
+                "name: web\nargs:\nlabels:\nnested:\n-\n- - 1\n",
+            ),
+            (EmptyCollections::Skip, "name: web\nnested:\n- []\n- - 1\n"),
+        ] {
+            let options = SerializerOptions {
+                empty_collections,
+                ..SerializerOptions::default()
+            };
+
+            // Act
+            let yaml = to_string_with_options(&chart, options.clone())
+                .unwrap();
+            let root = to_string_with_options(&Vec::<u8>::new(), options)
+                .unwrap();
+
+            // Assert
+            assert_eq!(yaml, expected, "{:?}", empty_collections);
+            assert_eq!(root, "[]\n", "{:?}", empty_collections);
+            let parsed: Chart = serde_yml::from_str(&yaml).unwrap();
+            assert_eq!(parsed, chart, "{:?}", empty_collections);
+        }
+    }
+
+    /// Tests that struct fields and mapping entries are written in the key
+    /// order of the options at every level.
+    #[test]
+    fn test_key_order() {
+        use serde_yml::ser::{
+            to_string_with_options, SerializerOptions,
+        };
+        use std::cmp::Ordering;
+
+        // Arrange
+        #[derive(Serialize)]
+        enum Volume {
+            Secret { name: String, mode: u16 },
+        }
+
+        #[derive(Serialize)]
+        struct Container {
+            name: String,
+            image: String,
+            env: BTreeMap<String, String>,
+        }
+
+        #[derive(Serialize)]
+        struct Pod {
+            spec: Vec<Container>,
+            volumes: Vec<Volume>,
+            kind: String,
+            name: String,
+        }
+
+        fn name_first(a: &str, b: &str) -> Ordering {
+            (b == "name").cmp(&(a == "name")).then_with(|| b.cmp(a))
+        }
+
+        let mut env = BTreeMap::new();
+        let _ = env.insert("A".to_owned(), "1".to_owned());
+        let _ = env.insert("B".to_owned(), "2".to_owned());
+        let pod = Pod {
+            spec: vec![Container {
+                name: "app".to_owned(),
+                image: "nginx".to_owned(),
+                env,
+            }],
+            volumes: vec![Volume::Secret {
+                name: "tls".to_owned(),
+                mode: 0o400,
+            }],
+            kind: "Pod".to_owned(),
+            name: "web".to_owned(),
+        };
+        let options = SerializerOptions {
+            key_order: Some(name_first),
+            ..SerializerOptions::default()
+        };
+
+        // Act
+        let yaml = to_string_with_options(&pod, options).unwrap();
+
+        // Assert
+        assert_eq!(
+            yaml,
+            concat!(
+                "name: web\n",
+                "volumes:\n",
+                "- !Secret\n",
+                "  name: tls\n",
+                "  mode: 256\n",
+                "spec:\n",
+                "- name: app\n",
+                "  image: nginx\n",
+                "  env:\n",
+                "    B: '2'\n",
+                "    A: '1'\n",
+                "kind: Pod\n",
+            )
+        );
+    }
+
+    /// Tests that scalars are written with their core tags when asked and
+    /// read back as the same values.
+    #[test]
+    fn test_explicit_core_tags() {
+        use serde_yml::{
+            ser::{to_string_with_options, SerializerOptions},
+            Value,
+        };
+
+        #[derive(Serialize)]
+        enum Shape {
+            Circle(f64),
+        }
+
+        // Arrange
+        let value: Value =
+            serde_yml::from_str("[1, -1.5, true, '1', 'yes', ~]")
+                .unwrap();
+        let options = SerializerOptions {
+            explicit_core_tags: true,
+            ..SerializerOptions::default()
+        };
+
+        // Act
+        let yaml =
+            to_string_with_options(&value, options.clone()).unwrap();
+        let shape =
+            to_string_with_options(&Shape::Circle(1.0), options)
+                .unwrap();
+
+        // Assert
+        assert_eq!(
+            yaml,
+            concat!(
+                "- !!int 1\n",
+                "- !!float -1.5\n",
+                "- !!bool true\n",
+                "- !!str '1'\n",
+                "- !!str 'yes'\n",
+                "- !!null null\n",
+            )
+        );
+        assert_eq!(serde_yml::from_str::<Value>(&yaml).unwrap(), value);
+        assert_eq!(shape, "!Circle 1.0\n");
+    }
+
+    /// Tests that canonical output tags and quotes every node and reads
+    /// back as the same value.
+    #[test]
+    fn test_canonical() {
+        use serde_yml::{
+            ser::{to_string_with_options, SerializerOptions},
+            Value,
+        };
+
+        // Arrange
+        let value: Value = serde_yml::from_str(
+            "name: web\nports: [80, 443]\nshape: !Circle round\n",
+        )
+        .unwrap();
+        let options = SerializerOptions {
+            canonical: true,
+            ..SerializerOptions::default()
+        };
+
+        // Act
+        let yaml = to_string_with_options(&value, options).unwrap();
+
+        // Assert
+        assert_eq!(
+            yaml,
+            concat!(
+                "---\n",
+                "!!map {\n",
+                "  ? !!str \"name\"\n",
+                "  : !!str \"web\",\n",
+                "  ? !!str \"ports\"\n",
+                "  : !!seq [\n",
+                "    !!int \"80\",\n",
+                "    !!int \"443\",\n",
+                "  ],\n",
+                "  ? !!str \"shape\"\n",
+                "  : !Circle \"round\",\n",
+                "}\n",
+            )
+        );
+        assert_eq!(serde_yml::from_str::<Value>(&yaml).unwrap(), value);
+    }
+
+    /// Tests that YAML is written into `fmt::Write` destinations, with
+    /// characters split between writes put back together.
+    #[test]
+    fn test_to_fmt_writer() {
+        use serde_yml::io::FmtWriter;
+        use std::io::Write as _;
+
+        // Arrange
+        let value = vec!["ünïcödé ".repeat(5000), "😀".to_owned()];
+        let mut direct = String::new();
+        let mut split = String::new();
+        let mut incomplete = String::new();
+
+        // Act
+        serde_yml::to_fmt_writer(&mut direct, &value).unwrap();
+        let mut writer = FmtWriter::new(&mut split);
+        for byte in "é€😀".bytes() {
+            writer.write_all(&[byte]).unwrap();
+        }
+        writer.flush().unwrap();
+        let mut writer = FmtWriter::new(&mut incomplete);
+        writer.write_all(b"a\xE2\x82").unwrap();
+        let unfinished = writer.flush();
+        let invalid = FmtWriter::new(String::new()).write_all(b"a\xFF");
+
+        // Assert
+        assert_eq!(direct, serde_yml::to_string(&value).unwrap());
+        assert_eq!(split, "é€😀");
+        assert_eq!(incomplete, "a");
+        assert!(unfinished.is_err());
+        assert!(invalid.is_err());
+    }
+
+    /// Tests writing to a file and the errors that name it.
+    #[test]
+    fn test_to_file() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.yaml");
+        let missing = dir.path().join("missing/out.yaml");
+        let value = BTreeMap::from([("name", "demo")]);
+
+        // Act
+        serde_yml::to_file(&path, &value).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        let err = serde_yml::to_file(&missing, &value).unwrap_err();
+
+        // Assert
+        assert_eq!(written, "name: demo\n");
+        assert_eq!(err.kind(), serde_yml::ErrorKind::Io);
+        assert!(err.io_error().is_some());
+        assert_eq!(
+            err.file(),
+            Some(missing.display().to_string().as_str())
+        );
+        assert!(err
+            .to_string()
+            .starts_with(&format!("{}: ", missing.display())));
+    }
+
+    #[test]
+    fn test_float_format() {
+        // Arrange
+        let values = [1e-7, 2.5e17, 2.0 / 3.0, 3.0, -0.0, 12345.678];
+        let yaml = |float_format| {
+            let options = SerializerOptions {
+                float_format,
+                ..SerializerOptions::default()
+            };
+            serde_yml::ser::to_string_with_options(&values, options)
+                .unwrap()
+        };
+
+        // Act
+        let default = yaml(FloatFormat::default());
+        let fixed = yaml(FloatFormat {
+            max_significant_digits: Some(4),
+            scientific_below: i32::MIN,
+            scientific_from: i32::MAX,
+            ..FloatFormat::default()
+        });
+        let yaml_1_1 = yaml(FloatFormat {
+            force_decimal_point: true,
+            scientific_from: 4,
+            ..FloatFormat::default()
+        });
+
+        // Assert
+        assert_eq!(
+            default,
+            "- 1e-7\n- 2.5e17\n- 0.6666666666666666\n- 3.0\n- -0.0\n- 12345.678\n"
+        );
+        assert_eq!(
+            fixed,
+            "- 0.0000001\n- 250000000000000000.0\n- 0.6667\n- 3.0\n- -0.0\n- 12350.0\n"
+        );
+        assert_eq!(
+            yaml_1_1,
+            "- 1.0e-7\n- 2.5e+17\n- 0.6666666666666666\n- 3.0\n- -0.0\n- 1.2345678e+4\n"
+        );
+        for text in [default, fixed, yaml_1_1] {
+            let read: Vec<f64> = serde_yml::from_str(&text).unwrap();
+            assert_eq!(read.len(), values.len());
+            assert_eq!(read[3], 3.0);
+        }
+    }
+
+    #[test]
+    fn test_special_float_style() {
+        // Arrange
+        let values = [f64::NAN, f64::INFINITY, f64::NEG_INFINITY];
+        let yaml = |special_float_style| {
+            let options = SerializerOptions {
+                special_float_style,
+                ..SerializerOptions::default()
+            };
+            serde_yml::ser::to_string_with_options(&values, options)
+                .unwrap()
+        };
+
+        // Act
+        let spelled = [
+            SpecialFloatStyle::Lowercase,
+            SpecialFloatStyle::Capitalized,
+            SpecialFloatStyle::Uppercase,
+            SpecialFloatStyle::JavaScript,
+        ]
+        .map(yaml);
+
+        // Assert
+        assert_eq!(
+            spelled,
+            [
+                "- .nan\n- .inf\n- -.inf\n",
+                "- .NaN\n- .Inf\n- -.Inf\n",
+                "- .NAN\n- .INF\n- -.INF\n",
+                "- NaN\n- Infinity\n- -Infinity\n",
+            ]
+        );
+        assert_eq!(serde_yml::to_string(&values).unwrap(), spelled[0]);
+        for text in &spelled[..3] {
+            let read: Vec<f64> = serde_yml::from_str(text).unwrap();
+            assert!(read[0].is_nan());
+            assert_eq!(read[1..], [f64::INFINITY, f64::NEG_INFINITY]);
+        }
+    }
 }
@@ -191,6 +191,34 @@ fn test_vec() {
     test_serde(&thing, yaml);
 }
 
+#[test]
+fn test_std_collections() {
+    use std::collections::{BinaryHeap, LinkedList, VecDeque};
+
+    let yaml = indoc! {"
+        - 3
+        - 1
+        - 2
+    "};
+    let mut deque = VecDeque::from(vec![1, 2]);
+    deque.push_front(3);
+    test_serde(&deque, yaml);
+    let list: LinkedList<i32> = [3, 1, 2].into_iter().collect();
+    test_serde(&list, yaml);
+
+    // A heap is written in its internal order, which is not sorted, and
+    // comes back holding the same elements.
+    let heap = BinaryHeap::from(vec![1, 5, 3, 4, 2]);
+    let serialized = serde_yml::to_string(&heap).unwrap();
+    let deserialized: BinaryHeap<i32> =
+        serde_yml::from_str(&serialized).unwrap();
+    assert_eq!(deserialized.into_sorted_vec(), [1, 2, 3, 4, 5]);
+    let value = serde_yml::to_value(&heap).unwrap();
+    let deserialized: BinaryHeap<i32> =
+        serde_yml::from_value(value).unwrap();
+    assert_eq!(deserialized.into_sorted_vec(), [1, 2, 3, 4, 5]);
+}
+
 #[test]
 fn test_map() {
     let mut thing = BTreeMap::new();
@@ -692,6 +720,177 @@ fn test_mapping() {
     test_serde(&thing, yaml);
 }
 
+#[test]
+fn test_complex_keys() {
+    #[derive(
+        Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Debug,
+    )]
+    struct Point {
+        row: i32,
+        col: i32,
+    }
+
+    let mut thing = BTreeMap::new();
+    thing.insert(("web".to_owned(), 80u16), "http".to_owned());
+    let yaml = indoc! {"
+        ? - web
+          - 80
+        : http
+    "};
+    test_serde(&thing, yaml);
+
+    let mut thing = BTreeMap::new();
+    thing.insert(Point { row: 1, col: 2 }, vec![1]);
+    let yaml = indoc! {"
+        ? row: 1
+          col: 2
+        : - 1
+    "};
+    test_serde(&thing, yaml);
+}
+
+#[test]
+fn test_flatten_with_tagged_enums() {
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    enum Kind {
+        Unit,
+        Newtype(u32),
+        Tuple(u32, u32),
+        Struct { a: u32 },
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Inner {
+        kind: Kind,
+        size: u32,
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Outer {
+        id: u32,
+        #[serde(flatten)]
+        inner: Inner,
+    }
+
+    let thing = Outer {
+        id: 1,
+        inner: Inner {
+            kind: Kind::Newtype(3),
+            size: 2,
+        },
+    };
+    let yaml = indoc! {"
+        id: 1
+        kind: !Newtype 3
+        size: 2
+    "};
+    test_serde(&thing, yaml);
+
+    let thing = Outer {
+        id: 1,
+        inner: Inner {
+            kind: Kind::Tuple(3, 4),
+            size: 2,
+        },
+    };
+    let yaml = indoc! {"
+        id: 1
+        kind: !Tuple
+        - 3
+        - 4
+        size: 2
+    "};
+    test_serde(&thing, yaml);
+
+    let thing = Outer {
+        id: 1,
+        inner: Inner {
+            kind: Kind::Struct { a: 3 },
+            size: 2,
+        },
+    };
+    let yaml = indoc! {"
+        id: 1
+        kind: !Struct
+          a: 3
+        size: 2
+    "};
+    test_serde(&thing, yaml);
+
+    let thing = Outer {
+        id: 1,
+        inner: Inner {
+            kind: Kind::Unit,
+            size: 2,
+        },
+    };
+    let yaml = indoc! {"
+        id: 1
+        kind: Unit
+        size: 2
+    "};
+    test_serde(&thing, yaml);
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    #[serde(tag = "type")]
+    enum Internal {
+        Point { kind: Kind },
+    }
+
+    let thing = Internal::Point {
+        kind: Kind::Newtype(2),
+    };
+    let yaml = indoc! {"
+        type: Point
+        kind: !Newtype 2
+    "};
+    test_serde(&thing, yaml);
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    #[serde(untagged)]
+    enum Untagged {
+        Point { kind: Kind },
+    }
+
+    let thing = Untagged::Point {
+        kind: Kind::Struct { a: 2 },
+    };
+    let yaml = indoc! {"
+        kind: !Struct
+          a: 2
+    "};
+    test_serde(&thing, yaml);
+}
+
+#[test]
+fn test_flatten_extra_tagged_values() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Extra {
+        id: u32,
+        #[serde(flatten)]
+        extra: BTreeMap<String, Value>,
+    }
+
+    let yaml = indoc! {"
+        id: 1
+        plain: 2
+        tagged: !Custom 3
+    "};
+    let mut extra = BTreeMap::new();
+    extra.insert("plain".to_owned(), Value::from(2));
+    let mut tagged = Mapping::new();
+    tagged.insert(Value::from("Custom"), Value::from(3));
+    extra.insert("tagged".to_owned(), Value::Mapping(tagged));
+    let expected = Extra { id: 1, extra };
+
+    let deserialized: Extra = serde_yml::from_str(yaml).unwrap();
+    assert_eq!(expected, deserialized);
+
+    let value: Value = serde_yml::from_str(yaml).unwrap();
+    let deserialized: Extra = serde_yml::from_value(value).unwrap();
+    assert_eq!(expected, deserialized);
+}
+
 #[test]
 fn test_long_string() {
     #[derive(Serialize, Deserialize, PartialEq, Debug)]
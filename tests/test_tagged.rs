@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod tests {
     use serde_yml::{
-        value::{tagged::nobang, Tag, TaggedValue},
+        value::{tagged::nobang, Tag, TaggedValue, TaggedValueBuilder},
         Value,
     };
 
@@ -42,4 +42,42 @@ mod tests {
         let nobanged = nobang("foo");
         assert_eq!(nobanged, "foo");
     }
+
+    /// Test for building a TaggedValue.
+    #[test]
+    fn test_tagged_value_builder() {
+        let tagged = TaggedValue::builder()
+            .tag("!point")
+            .value(vec![1, 2])
+            .build()
+            .unwrap();
+        assert_eq!(tagged.tag, Tag::new("point"));
+        assert_eq!(tagged.value, Value::from(vec![1, 2]));
+
+        let tagged =
+            TaggedValue::builder().tag("empty").build().unwrap();
+        assert_eq!(tagged.value, Value::Null);
+    }
+
+    /// Test for the errors reported by the TaggedValue builder.
+    #[test]
+    fn test_tagged_value_builder_errors() {
+        let err = TaggedValueBuilder::default().build().unwrap_err();
+        assert_eq!(err.to_string(), "a tagged value requires a tag");
+
+        let err = TaggedValue::builder().tag("").build().unwrap_err();
+        assert_eq!(err.to_string(), "empty YAML tag is not allowed");
+
+        let inner =
+            TaggedValue::builder().tag("!inner").build().unwrap();
+        let err = TaggedValue::builder()
+            .tag("!outer")
+            .value(Value::Tagged(Box::new(inner)))
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "cannot tag a value already tagged `!inner` with `!outer`; YAML allows only one tag per node",
+        );
+    }
 }
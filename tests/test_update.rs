@@ -0,0 +1,139 @@
+#[cfg(test)]
+mod tests {
+    use serde::{de::DeserializeSeed, Deserialize, Serialize};
+    use serde_yml::{
+        de::DeserializerOptions, update::Update, update_from_str,
+        update_from_value, Deserializer, ErrorKind, Value,
+    };
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+    struct Config {
+        name: String,
+        workers: usize,
+        hosts: Vec<String>,
+        log: Log,
+        labels: BTreeMap<String, String>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+    struct Log {
+        level: String,
+        file: Option<String>,
+    }
+
+    fn config() -> Config {
+        Config {
+            name: "api".to_owned(),
+            workers: 4,
+            hosts: vec!["a".to_owned(), "b".to_owned()],
+            log: Log {
+                level: "info".to_owned(),
+                file: Some("api.log".to_owned()),
+            },
+            labels: BTreeMap::from([(
+                "team".to_owned(),
+                "core".to_owned(),
+            )]),
+        }
+    }
+
+    #[test]
+    // Tests that only the keys present in the input are overwritten
+    fn test_update_from_str() {
+        // Arrange
+        let mut config = config();
+        let yaml = "workers: 8\nhosts: [c]\nlog:\n  file: ~\nlabels:\n  tier: web\n";
+
+        // Act
+        update_from_str(&mut config, yaml).unwrap();
+
+        // Assert
+        let mut expected = self::config();
+        expected.workers = 8;
+        expected.hosts = vec!["c".to_owned()];
+        expected.log.file = None;
+        expected.labels.insert("tier".to_owned(), "web".to_owned());
+        assert_eq!(config, expected);
+    }
+
+    #[test]
+    // Tests that empty and null documents leave the value unchanged
+    fn test_update_from_str_empty() {
+        let mut config = config();
+        update_from_str(&mut config, "").unwrap();
+        update_from_str(&mut config, "# nothing\n").unwrap();
+        update_from_str(&mut config, "~\n").unwrap();
+        assert_eq!(config, self::config());
+    }
+
+    #[test]
+    // Tests that a failed update reports the location in the input and
+    // leaves the value unchanged
+    fn test_update_from_str_error() {
+        // Arrange
+        let mut config = config();
+
+        // Act
+        let err = update_from_str(
+            &mut config,
+            "workers: 2\nlog:\n  level: [debug]\n",
+        )
+        .unwrap_err();
+
+        // Assert
+        assert_eq!(err.kind(), ErrorKind::InvalidType);
+        assert_eq!(err.path().unwrap().to_string(), "log.level");
+        let location = err.location().unwrap();
+        assert_eq!((location.line(), location.column()), (3, 10));
+        assert_eq!(config, self::config());
+
+        let err =
+            update_from_str(&mut config, "name: 'api\n").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Parse);
+        assert_eq!(config, self::config());
+    }
+
+    #[test]
+    // Tests that an update replacing the whole value with another type fails
+    fn test_update_from_value() {
+        let mut config = config();
+        let overlay: Value = serde_yml::from_str("name: web").unwrap();
+        update_from_value(&mut config, overlay).unwrap();
+        assert_eq!(config.name, "web");
+
+        let err =
+            update_from_value(&mut config, Value::from(3)).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidType);
+        assert_eq!(config.name, "web");
+    }
+
+    #[test]
+    // Tests that the seed applies each document of a stream in turn and
+    // respects the options of the deserializer
+    fn test_update_seed() {
+        // Arrange
+        let mut config = config();
+        let yaml = "workers: 2\n---\nlog: {level: warn}\n---\n";
+        let options = DeserializerOptions {
+            reject_non_finite_floats: true,
+            ..DeserializerOptions::default()
+        };
+
+        // Act
+        for document in Deserializer::from_str(yaml) {
+            Update::new(&mut config).deserialize(document).unwrap();
+        }
+        let err = Update::new(&mut config)
+            .deserialize(
+                Deserializer::from_str("workers: .inf\n")
+                    .with_options(options),
+            )
+            .unwrap_err();
+
+        // Assert
+        assert_eq!(config.workers, 2);
+        assert_eq!(config.log.level, "warn");
+        assert!(err.to_string().starts_with("found \".inf\""));
+    }
+}
@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use serde::Deserialize;
+    use serde_yml::validate::{
+        from_str_validated, validate, validate_str,
+    };
+    use serde_yml::Value;
+
+    fn schema(yaml: &str) -> Value {
+        serde_yml::from_str(yaml).unwrap()
+    }
+
+    fn messages(schema: &Value, yaml: &str) -> Vec<String> {
+        validate_str(schema, yaml)
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    // Tests that all violations are reported with their locations
+    fn test_validate_reports_all_violations() {
+        // Arrange
+        let schema = schema(indoc! {"
+            type: object
+            required: [name, servers]
+            additionalProperties: false
+            properties:
+              name: {type: string, minLength: 1}
+              servers:
+                type: array
+                minItems: 1
+                items:
+                  type: object
+                  required: [host]
+                  properties:
+                    host: {type: string}
+                    port: {type: integer, minimum: 1, maximum: 65535}
+        "});
+        let yaml = indoc! {"
+            name: ''
+            servers:
+              - host: a.example
+                port: 70000
+              - port: http
+            debug: true
+        "};
+
+        // Act
+        let violations = messages(&schema, yaml);
+
+        // Assert
+        assert_eq!(
+            violations,
+            [
+                "name: string is shorter than 1 characters at line 1 column 7",
+                "servers[0].port: 70000 is greater than the maximum of 65535 at line 4 column 11",
+                "servers[1]: missing required property `host` at line 5 column 5",
+                "servers[1].port: expected integer, found string at line 5 column 11",
+                "debug: property `debug` is not allowed at line 6 column 8",
+            ]
+        );
+    }
+
+    #[test]
+    // Tests combinators, references and conditionals
+    fn test_validate_combinators() {
+        let schema = schema(indoc! {"
+            $defs:
+              port: {type: integer, exclusiveMinimum: 0}
+            properties:
+              port: {$ref: '#/$defs/port'}
+              mode: {enum: [dev, prod]}
+              level: {oneOf: [{type: integer}, {type: number}]}
+              id: {anyOf: [{type: string}, {type: integer}]}
+              name: {not: {const: root}}
+            if: {properties: {mode: {const: prod}}}
+            then: {required: [tls]}
+        "});
+        let instance: Value = serde_yml::from_str(indoc! {"
+            port: 0
+            mode: prod
+            level: 3
+            id: [1]
+            name: root
+        "})
+        .unwrap();
+
+        let violations: Vec<_> = validate(&schema, &instance)
+            .into_iter()
+            .map(|violation| {
+                (violation.path.to_string(), violation.keyword)
+            })
+            .collect();
+
+        assert_eq!(
+            violations,
+            [
+                (".".to_owned(), "required"),
+                ("port".to_owned(), "exclusiveMinimum"),
+                ("level".to_owned(), "oneOf"),
+                ("id".to_owned(), "anyOf"),
+                ("name".to_owned(), "not"),
+            ]
+        );
+    }
+
+    #[test]
+    // Tests array keywords
+    fn test_validate_arrays() {
+        let schema = schema(indoc! {"
+            prefixItems: [{type: string}]
+            items: {type: integer}
+            uniqueItems: true
+            contains: {const: 1}
+            maxContains: 1
+        "});
+        assert!(messages(&schema, "[a, 1, 2]").is_empty());
+        assert_eq!(
+            messages(&schema, "[1, 1, x]"),
+            [
+                "[0]: expected string, found integer at line 1 column 2",
+                "[2]: expected integer, found string at line 1 column 8",
+                ".: array contains duplicate items at line 1 column 1",
+                ".: array contains 2 matching items, expected at most 1 at line 1 column 1",
+            ]
+        );
+    }
+
+    #[test]
+    // Tests that a valid document deserializes and an invalid one fails
+    fn test_from_str_validated() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Config {
+            workers: u8,
+        }
+
+        let schema = schema(
+            "properties: {workers: {type: integer, multipleOf: 2}}",
+        );
+
+        let config: Config =
+            from_str_validated(&schema, "workers: 4").unwrap();
+        assert_eq!(config, Config { workers: 4 });
+
+        let err = from_str_validated::<Config>(&schema, "workers: 3")
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "schema validation failed: workers: 3 is not a multiple of 2 at line 1 column 10"
+        );
+    }
+
+    #[test]
+    // Tests boolean schemas and tagged values
+    fn test_validate_boolean_schema_and_tags() {
+        let instance: Value = serde_yml::from_str("!thing 5").unwrap();
+        assert!(validate(&Value::Bool(true), &instance).is_empty());
+        assert_eq!(validate(&Value::Bool(false), &instance).len(), 1);
+        assert!(
+            validate(&schema("type: integer"), &instance).is_empty()
+        );
+    }
+}
@@ -101,6 +101,19 @@ fn test_merge() {
     for i in 5..=7 {
         assert_eq!(value[4], value[i]);
     }
+
+    // A merged mapping with merge keys of its own.
+    let yaml = indoc! {"
+        base: &base { a: 1, b: 1 }
+        middle: &middle { <<: *base, b: 2 }
+        top: { <<: [*middle, { c: 3 }], c: 4 }
+    "};
+    let mut value: Value = serde_yml::from_str(yaml).unwrap();
+    value.apply_merge().unwrap();
+    let expected: Value =
+        serde_yml::from_str("{ a: 1, b: 2, c: 4 }").unwrap();
+    assert_eq!(value["top"], expected);
+    assert_eq!(value["middle"]["a"], 1);
 }
 
 #[test]
@@ -157,3 +170,271 @@ fn test_tagged() {
     let serialized = serde_yml::to_value(&value).unwrap();
     assert_eq!(value, serialized);
 }
+
+#[test]
+fn test_try_as_getters() {
+    let value: Value = serde_yml::from_str(indoc! {"
+        flag: true
+        count: 3
+        ratio: 0.5
+        name: demo
+        tagged: !id 7
+    "})
+    .unwrap();
+
+    assert!(value["flag"].try_as_bool().unwrap());
+    assert_eq!(value["count"].try_as_u64().unwrap(), 3);
+    assert_eq!(value["count"].try_as_i64().unwrap(), 3);
+    assert_eq!(value["ratio"].try_as_f64().unwrap(), 0.5);
+    assert_eq!(value["name"].try_as_str().unwrap(), "demo");
+    assert_eq!(value["tagged"].try_as_u64().unwrap(), 7);
+    assert_eq!(value.try_as_mapping().unwrap().len(), 5);
+
+    let err = value["name"].try_as_u64().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid type: string \"demo\", expected u64"
+    );
+    let err = value["count"].try_as_sequence().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid type: integer `3`, expected a sequence"
+    );
+    assert!(err.path().is_none());
+}
+
+#[test]
+fn test_typed_path_getters() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Tls {
+        cert: String,
+    }
+
+    let value: Value = serde_yml::from_str(indoc! {"
+        server:
+          port: 8443
+          hosts: [a, b]
+          tls:
+            cert: server.pem
+    "})
+    .unwrap();
+    let path =
+        |text: &str| text.parse::<serde_yml::path::Path>().unwrap();
+
+    assert_eq!(
+        value.get_as::<u16>(&path("server.port")).unwrap(),
+        8443
+    );
+    assert_eq!(
+        value.get_as::<&str>(&path("server.hosts[1]")).unwrap(),
+        "b"
+    );
+    assert_eq!(
+        value.get_as::<Tls>(&path("server.tls")).unwrap(),
+        Tls {
+            cert: "server.pem".to_owned()
+        }
+    );
+    assert_eq!(
+        value.get_or(&path("server.timeout"), 30u32).unwrap(),
+        30
+    );
+    assert_eq!(
+        value.get_or(&path("server.port"), 80u16).unwrap(),
+        8443
+    );
+
+    let err = value.get_as::<u8>(&path("server.port")).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "server.port: invalid value: integer `8443`, expected u8"
+    );
+    assert_eq!(err.path(), Some(&path("server.port")));
+    assert!(err.location().is_none());
+
+    let err = value.get_or(&path("server.hosts"), 0).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "server.hosts: invalid type: sequence, expected i32"
+    );
+
+    let err = value.try_get_path(&path("server.hosts[2]")).unwrap_err();
+    assert_eq!(err.to_string(), r"server.hosts.\[2\]: value not found");
+}
+
+#[test]
+fn test_total_order() {
+    use std::cmp::Ordering;
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::BTreeMap;
+    use std::hash::{Hash, Hasher};
+
+    let hash = |value: &Value| {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    };
+
+    let nan = Value::Number(Number::from(f64::NAN));
+    let one = Value::Number(Number::from(1.0));
+    assert_eq!(nan.cmp(&nan), Ordering::Equal);
+    assert_eq!(nan.partial_cmp(&one), Some(Ordering::Greater));
+    assert_eq!(
+        Value::Number(Number::from(-0.0))
+            .cmp(&Value::Number(0.0.into())),
+        Ordering::Equal,
+    );
+    assert!(Value::Number(2.into()) < one);
+
+    let a: Value =
+        serde_yml::from_str("{x: 1, y: [1, 2], z: !t 3}").unwrap();
+    let b: Value =
+        serde_yml::from_str("{z: !t 3, y: [1, 2], x: 1}").unwrap();
+    let c: Value = serde_yml::from_str("{x: 1, y: [1, 3]}").unwrap();
+    assert_eq!(a.cmp(&b), Ordering::Equal);
+    assert_eq!(a, b);
+    assert_eq!(hash(&a), hash(&b));
+    assert_eq!(a.cmp(&c), Ordering::Less);
+    assert_eq!(c.cmp(&a), Ordering::Greater);
+
+    let tagged: Value =
+        serde_yml::from_str("[!a 2, !b 1, !a 1]").unwrap();
+    let mut tagged = tagged.as_sequence().unwrap().clone();
+    tagged.sort();
+    let expected: Value =
+        serde_yml::from_str("[!a 1, !a 2, !b 1]").unwrap();
+    assert_eq!(Value::Sequence(tagged), expected);
+
+    let mut counts = BTreeMap::new();
+    for key in ["a", "b", "a"] {
+        *counts.entry(Value::from(key)).or_insert(0) += 1;
+    }
+    *counts.entry(nan.clone()).or_insert(0) += 1;
+    *counts.entry(Value::Number(f64::NAN.into())).or_insert(0) += 1;
+    *counts.entry(a).or_insert(0) += 1;
+    *counts.entry(b).or_insert(0) += 1;
+    let counts: Vec<usize> = counts.into_values().collect();
+    assert_eq!(counts, [2, 2, 1, 2]);
+}
+
+#[test]
+fn test_value_serializer_deserializer() {
+    use serde::Serialize;
+    use serde_yml::value::{Deserializer, Serializer};
+    use std::net::{IpAddr, Ipv4Addr};
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Host {
+        addrs: Vec<IpAddr>,
+    }
+
+    // Arrange
+    let host = Host {
+        addrs: vec![IpAddr::V4(Ipv4Addr::LOCALHOST)],
+    };
+
+    // Act
+    let compact = host.serialize(Serializer::new()).unwrap();
+    let readable = host
+        .serialize(Serializer::new().with_human_readable(true))
+        .unwrap();
+
+    // Assert
+    let expected: Value =
+        serde_yml::from_str("addrs: [127.0.0.1]").unwrap();
+    assert_eq!(readable, expected);
+    assert_ne!(compact, expected);
+    let de = Deserializer::new(compact).with_human_readable(false);
+    assert_eq!(Host::deserialize(de).unwrap(), host);
+    assert_eq!(
+        Host::deserialize(Deserializer::new(readable)).unwrap(),
+        host
+    );
+    assert!(Host::deserialize(Value::Null).is_err());
+}
+
+#[test]
+fn test_display_from_str() {
+    let yaml = indoc! {"
+        name: web
+        tags: !set [a, b]
+        text: |
+          two
+          lines
+        empty: {}
+    "};
+
+    let value: Value = yaml.parse().unwrap();
+    assert_eq!(
+        format!("{}\n", value),
+        serde_yml::to_string(&value).unwrap()
+    );
+    assert_eq!(value.to_string().parse::<Value>().unwrap(), value);
+    assert_eq!(format!("{}", Value::from(1)), "1");
+    assert_eq!(format!("[{}]", Value::Null), "[null]");
+    assert_eq!(format!("[{:>6}]", Value::Null), "[  null]");
+    assert_eq!(format!("[{:-<5}]", Value::from("ab")), "[ab---]");
+
+    let text: Value = "text: |\n  two\n  lines\n".parse().unwrap();
+    assert_eq!(text.to_string(), "text: |\n  two\n  lines\n");
+    assert_eq!(text.to_string().parse::<Value>().unwrap(), text);
+
+    let err = "a: 1\n---\nb: 2\n".parse::<Value>().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "deserializing from YAML containing more than one document is not supported"
+    );
+}
+
+#[test]
+fn test_borrowed_value() {
+    use serde_yml::value::BorrowedValue;
+    use std::borrow::Cow;
+
+    let yaml = indoc! {r#"
+        plain: web
+        single: 'a b'
+        escaped: "a\tb"
+        block: |
+          text
+        port: 8080
+        tagged: !Thing x
+        list: [1, ~, true]
+    "#};
+    let value = serde_yml::from_str_borrowed(yaml).unwrap();
+    let borrowed = |key: &str| match value.get(key) {
+        Some(BorrowedValue::String(Cow::Borrowed(s))) => Some(*s),
+        _ => None,
+    };
+    assert_eq!(borrowed("plain"), Some("web"));
+    assert_eq!(borrowed("single"), Some("a b"));
+    assert_eq!(borrowed("escaped"), None);
+    assert_eq!(value.get("escaped").unwrap().as_str(), Some("a\tb"));
+    assert_eq!(value.get("block").unwrap().as_str(), Some("text\n"));
+    assert_eq!(
+        value.get("port"),
+        Some(&BorrowedValue::Number(8080.into()))
+    );
+    assert_eq!(
+        value.get("list").unwrap().as_sequence().unwrap().len(),
+        3
+    );
+
+    let owned = serde_yml::from_str::<Value>(yaml).unwrap();
+    assert_eq!(Value::from(value.clone()), owned);
+    assert_eq!(
+        serde_yml::to_string(&value).unwrap(),
+        serde_yml::to_string(&owned).unwrap()
+    );
+    let detached: BorrowedValue<'static> = value.into_owned();
+    assert_eq!(Value::from(detached), owned);
+
+    let err = serde_yml::from_str_borrowed("a: 1\na: 2\n").unwrap_err();
+    assert_eq!(err.kind(), serde_yml::ErrorKind::DuplicateKey);
+    assert_eq!(
+        err.to_string(),
+        serde_yml::from_str::<Value>("a: 1\na: 2\n")
+            .unwrap_err()
+            .to_string()
+    );
+}
@@ -499,4 +499,1363 @@ mod tests {
             .unwrap();
         assert_eq!(value, deserialized);
     }
+
+    // Test that the singleton_map helpers produce the same layout through
+    // to_value/from_value as through to_string/from_str
+    #[test]
+    fn test_singleton_map_value_round_trip() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        enum OuterEnum {
+            Inner(MyEnum),
+        }
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct TestStruct {
+            #[serde(with = "singleton_map")]
+            a: MyEnum,
+            #[serde(with = "singleton_map_optional")]
+            b: Option<MyEnum>,
+            #[serde(with = "singleton_map_with")]
+            c: MyEnum,
+            #[serde(with = "nested_singleton_map")]
+            d: OuterEnum,
+            #[serde(with = "singleton_map_recursive")]
+            e: Vec<OuterEnum>,
+        }
+
+        let variants = || {
+            vec![
+                MyEnum::Unit,
+                MyEnum::Newtype(1),
+                MyEnum::Tuple(1, 2),
+                MyEnum::Struct { value: 3 },
+            ]
+        };
+        for (variant, (other, inner)) in variants()
+            .into_iter()
+            .zip(variants().into_iter().zip(variants()))
+        {
+            let test_struct = TestStruct {
+                a: variant,
+                b: Some(other),
+                c: MyEnum::Newtype(4),
+                d: OuterEnum::Inner(inner),
+                e: variants()
+                    .into_iter()
+                    .map(OuterEnum::Inner)
+                    .collect(),
+            };
+
+            let value = serde_yml::to_value(&test_struct).unwrap();
+            let yaml = serde_yml::to_string(&test_struct).unwrap();
+            assert_eq!(
+                value,
+                serde_yml::from_str::<serde_yml::Value>(&yaml).unwrap()
+            );
+            assert_eq!(
+                TestStruct::deserialize(&value).unwrap(),
+                test_struct
+            );
+            assert_eq!(
+                serde_yml::from_value::<TestStruct>(value).unwrap(),
+                test_struct
+            );
+        }
+
+        let value: serde_yml::Value = serde_yml::from_str(
+            "a: {Struct: {value: 1}}\nb: null\nc: Unit\nd: {Inner: {Newtype: 2}}\ne: []\n",
+        )
+        .unwrap();
+        assert_eq!(value["a"]["Struct"]["value"], 1);
+        let test_struct: TestStruct =
+            serde_yml::from_value(value).unwrap();
+        assert_eq!(test_struct.b, None);
+        assert_eq!(test_struct.d, OuterEnum::Inner(MyEnum::Newtype(2)));
+    }
+
+    // Test that the fields of tuple and struct variants are written as
+    // they are in a plain struct, without going through a Value
+    #[test]
+    fn test_singleton_map_fields_as_written() {
+        #[derive(Serialize)]
+        enum Shape {
+            Point(f32, f32),
+            Circle { radius: f32, label: Option<String> },
+        }
+
+        #[derive(Serialize)]
+        struct Drawing {
+            #[serde(with = "singleton_map")]
+            first: Shape,
+            #[serde(with = "singleton_map_recursive")]
+            rest: Vec<Shape>,
+        }
+
+        let drawing = Drawing {
+            first: Shape::Point(0.1, 2.5),
+            rest: vec![Shape::Circle {
+                radius: 1.1,
+                label: None,
+            }],
+        };
+
+        let yaml = serde_yml::to_string(&drawing).unwrap();
+        let options = serde_yml::ser::SerializerOptions {
+            skip_none_fields: true,
+            ..Default::default()
+        };
+        let skipped =
+            serde_yml::ser::to_string_with_options(&drawing, options)
+                .unwrap();
+
+        assert_eq!(
+            yaml,
+            "first:\n  Point:\n  - 0.1\n  - 2.5\nrest:\n- Circle:\n    radius: 1.1\n    label: null\n",
+        );
+        assert_eq!(
+            skipped,
+            "first:\n  Point:\n  - 0.1\n  - 2.5\nrest:\n- Circle:\n    radius: 1.1\n",
+        );
+    }
+
+    // Test that fields written to another serializer keep their form for
+    // that serializer instead of the one they would take in a Value
+    #[test]
+    fn test_singleton_map_fields_for_other_serializers() {
+        use std::net::Ipv4Addr;
+
+        #[derive(Serialize)]
+        enum Route {
+            Via(Ipv4Addr, u8),
+            Direct { gateway: Ipv4Addr },
+        }
+
+        #[derive(Serialize)]
+        struct Table {
+            #[serde(with = "singleton_map")]
+            first: Route,
+            #[serde(with = "singleton_map_recursive")]
+            rest: Vec<Route>,
+        }
+
+        let table = Table {
+            first: Route::Via(Ipv4Addr::LOCALHOST, 1),
+            rest: vec![Route::Direct {
+                gateway: Ipv4Addr::new(10, 0, 0, 1),
+            }],
+        };
+
+        let json = serde_json::to_string(&table).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"first":{"Via":["127.0.0.1",1]},"rest":[{"Direct":{"gateway":"10.0.0.1"}}]}"#,
+        );
+    }
+
+    // Test singleton_map_recursive_optional with nested enums, null and a missing field
+    #[test]
+    fn test_singleton_map_recursive_optional() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        enum OuterEnum {
+            Inner(MyEnum),
+            List(Vec<MyEnum>),
+        }
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct TestStruct {
+            #[serde(
+                default,
+                with = "singleton_map_recursive_optional"
+            )]
+            field: Option<OuterEnum>,
+        }
+
+        let test_struct = TestStruct {
+            field: Some(OuterEnum::List(vec![
+                MyEnum::Newtype(1),
+                MyEnum::Struct { value: 2 },
+            ])),
+        };
+        let yaml = serde_yml::to_string(&test_struct).unwrap();
+        assert_eq!(
+            yaml,
+            "field:\n  List:\n  - Newtype: 1\n  - Struct:\n      value: 2\n"
+        );
+        let deserialized: TestStruct =
+            serde_yml::from_str(&yaml).unwrap();
+        assert_eq!(test_struct, deserialized);
+
+        let test_struct = TestStruct {
+            field: Some(OuterEnum::Inner(MyEnum::Unit)),
+        };
+        let value = serde_yml::to_value(&test_struct).unwrap();
+        assert_eq!(value["field"]["Inner"], "Unit");
+        assert_eq!(
+            serde_yml::from_value::<TestStruct>(value).unwrap(),
+            test_struct
+        );
+
+        let test_struct = TestStruct { field: None };
+        let yaml = serde_yml::to_string(&test_struct).unwrap();
+        assert_eq!(yaml, "field: null\n");
+        let deserialized: TestStruct =
+            serde_yml::from_str(&yaml).unwrap();
+        assert_eq!(test_struct, deserialized);
+        let deserialized: TestStruct =
+            serde_yml::from_str("{}").unwrap();
+        assert_eq!(test_struct, deserialized);
+    }
+
+    // Test enum_keyed_map with HashMap and BTreeMap through strings and values
+    #[test]
+    fn test_enum_keyed_map() {
+        use std::collections::{BTreeMap, HashMap};
+
+        #[derive(
+            Serialize,
+            Deserialize,
+            PartialEq,
+            Eq,
+            Hash,
+            PartialOrd,
+            Ord,
+            Debug,
+        )]
+        enum Key {
+            Unit,
+            Newtype(u8),
+            Tuple(u8, u8),
+            Struct { value: u8 },
+        }
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct TestStruct {
+            #[serde(with = "enum_keyed_map")]
+            hash: HashMap<Key, u8>,
+            #[serde(with = "enum_keyed_map")]
+            tree: BTreeMap<Key, u8>,
+        }
+
+        let test_struct = TestStruct {
+            hash: [(Key::Struct { value: 1 }, 1)].into_iter().collect(),
+            tree: [
+                (Key::Unit, 1),
+                (Key::Newtype(2), 2),
+                (Key::Tuple(3, 4), 3),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        let yaml = serde_yml::to_string(&test_struct).unwrap();
+        assert_eq!(
+            yaml,
+            "hash:\n  ? Struct:\n      value: 1\n  : 1\ntree:\n  Unit: 1\n  ? Newtype: 2\n  : 2\n  ? Tuple:\n    - 3\n    - 4\n  : 3\n"
+        );
+        let deserialized: TestStruct =
+            serde_yml::from_str(&yaml).unwrap();
+        assert_eq!(test_struct, deserialized);
+
+        let value = serde_yml::to_value(&test_struct).unwrap();
+        assert_eq!(
+            serde_yml::from_value::<TestStruct>(value).unwrap(),
+            test_struct
+        );
+
+        let deserialized: TestStruct = serde_yml::from_str(
+            "hash: {{Newtype: 1}: 1}\ntree: {Unit: 2, {Struct: {value: 3}}: 3}\n",
+        )
+        .unwrap();
+        assert_eq!(deserialized.hash[&Key::Newtype(1)], 1);
+        assert_eq!(deserialized.tree[&Key::Struct { value: 3 }], 3);
+    }
+
+    // Test that defaulted accepts a missing key, an explicit null and a value
+    #[test]
+    fn test_defaulted() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Config {
+            #[serde(default, with = "defaulted")]
+            retries: u32,
+            #[serde(default, with = "defaulted")]
+            name: String,
+        }
+
+        let config: Config =
+            serde_yml::from_str("retries: null\n").unwrap();
+        assert_eq!(
+            config,
+            Config {
+                retries: 0,
+                name: String::new(),
+            }
+        );
+
+        let config: Config =
+            serde_yml::from_str("retries: 3\nname: ~\n").unwrap();
+        assert_eq!(config.retries, 3);
+        assert_eq!(config.name, "");
+
+        let err = serde_yml::from_str::<Config>("retries: many\n")
+            .unwrap_err();
+        assert!(err.to_string().starts_with("retries: invalid type"));
+    }
+
+    fn default_timeout() -> u64 {
+        30
+    }
+
+    serde_yml::defaulted!(timeout: u64 = default_timeout());
+
+    // Test that the defaulted! macro uses the supplied default function
+    #[test]
+    fn test_defaulted_macro() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Config {
+            #[serde(with = "timeout", default = "timeout::default")]
+            timeout: u64,
+        }
+
+        let missing: Config = serde_yml::from_str("{}").unwrap();
+        let null: Config =
+            serde_yml::from_str("timeout: null").unwrap();
+        let set: Config = serde_yml::from_str("timeout: 5").unwrap();
+        assert_eq!(missing.timeout, 30);
+        assert_eq!(null.timeout, 30);
+        assert_eq!(set.timeout, 5);
+        assert_eq!(serde_yml::to_string(&set).unwrap(), "timeout: 5\n");
+    }
+
+    // Test string_or_struct with shorthand scalars, mappings and values
+    #[test]
+    fn test_string_or_struct() {
+        use std::str::FromStr;
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Port {
+            number: u16,
+            #[serde(default)]
+            udp: bool,
+        }
+
+        impl FromStr for Port {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let (number, udp) = match s.strip_suffix("/udp") {
+                    Some(number) => (number, true),
+                    None => (s, false),
+                };
+                match number.parse() {
+                    Ok(number) => Ok(Port { number, udp }),
+                    Err(_) => Err(format!("invalid port `{}`", s)),
+                }
+            }
+        }
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct TestStruct {
+            #[serde(with = "string_or_struct")]
+            a: Port,
+            #[serde(with = "string_or_struct")]
+            b: Port,
+            #[serde(with = "string_or_struct")]
+            c: Port,
+        }
+
+        let yaml = "a: 53/udp\nb: 80\nc:\n  number: 443\n  udp: true\n";
+        let test_struct: TestStruct =
+            serde_yml::from_str(yaml).unwrap();
+        assert_eq!(
+            test_struct,
+            TestStruct {
+                a: Port {
+                    number: 53,
+                    udp: true
+                },
+                b: Port {
+                    number: 80,
+                    udp: false
+                },
+                c: Port {
+                    number: 443,
+                    udp: true
+                },
+            }
+        );
+
+        let value: serde_yml::Value =
+            serde_yml::from_str(yaml).unwrap();
+        assert_eq!(
+            serde_yml::from_value::<TestStruct>(value).unwrap(),
+            test_struct
+        );
+
+        let yaml = serde_yml::to_string(&test_struct).unwrap();
+        assert_eq!(
+            serde_yml::from_str::<TestStruct>(&yaml).unwrap(),
+            test_struct
+        );
+
+        let err =
+            serde_yml::from_str::<TestStruct>("a: http\nb: 1\nc: 2\n")
+                .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "a: invalid port `http` at line 1 column 4"
+        );
+        let err =
+            serde_yml::from_str::<TestStruct>("a: [1]\nb: 1\nc: 2\n")
+                .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "a: invalid type: sequence, expected a string or a map at line 1 column 4"
+        );
+    }
+
+    // Test flatten_tagged with every kind of variant and both input forms
+    #[test]
+    fn test_flatten_tagged() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Inner {
+            value: u8,
+        }
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct TestStruct {
+            #[serde(with = "flatten_tagged")]
+            unit: MyEnum,
+            #[serde(with = "flatten_tagged")]
+            newtype: MyEnum,
+            #[serde(with = "flatten_tagged")]
+            tuple: MyEnum,
+            #[serde(with = "flatten_tagged")]
+            strukt: MyEnum,
+            #[serde(with = "flatten_tagged")]
+            optional: Option<MyEnum>,
+            #[serde(with = "flatten_tagged")]
+            missing: Option<MyEnum>,
+        }
+
+        let test_struct = TestStruct {
+            unit: MyEnum::Unit,
+            newtype: MyEnum::Newtype(1),
+            tuple: MyEnum::Tuple(2, 3),
+            strukt: MyEnum::Struct { value: 4 },
+            optional: Some(MyEnum::Unit),
+            missing: None,
+        };
+        let yaml = serde_yml::to_string(&test_struct).unwrap();
+        assert_eq!(
+            yaml,
+            "unit:\n  type: Unit\nnewtype:\n  type: Newtype\n  value: 1\ntuple:\n  type: Tuple\n  value:\n  - 2\n  - 3\nstrukt:\n  type: Struct\n  value: 4\noptional:\n  type: Unit\nmissing: null\n"
+        );
+        let deserialized: TestStruct =
+            serde_yml::from_str(&yaml).unwrap();
+        assert_eq!(deserialized, test_struct);
+
+        let value = serde_yml::to_value(&test_struct).unwrap();
+        assert_eq!(
+            serde_yml::from_value::<TestStruct>(value).unwrap(),
+            test_struct
+        );
+
+        // The default representation is accepted as well.
+        let deserialized: TestStruct = serde_yml::from_str(
+            "unit: Unit\nnewtype: !Newtype 1\ntuple: !Tuple [2, 3]\nstrukt: !Struct {value: 4}\noptional: Unit\nmissing: ~\n",
+        )
+        .unwrap();
+        assert_eq!(deserialized, test_struct);
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        enum Wrapper {
+            Inner(Inner),
+        }
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Outer {
+            #[serde(with = "flatten_tagged")]
+            wrapper: Wrapper,
+        }
+
+        let err = serde_yml::from_str::<Outer>("wrapper: {value: 1}")
+            .unwrap_err();
+        assert!(err.to_string().contains("missing field `type`"));
+        let err =
+            serde_yml::from_str::<Outer>("wrapper: {type: Other}")
+                .unwrap_err();
+        assert!(err.to_string().contains("unknown variant `Other`"));
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        enum Clash {
+            Variant {
+                #[serde(rename = "type")]
+                kind: u8,
+            },
+        }
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct HasClash {
+            #[serde(with = "flatten_tagged")]
+            clash: Clash,
+        }
+
+        let err = serde_yml::to_string(&HasClash {
+            clash: Clash::Variant { kind: 1 },
+        })
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "variant `Variant` has a field named `type`, which holds the variant name"
+        );
+    }
+
+    serde_yml::flatten_tagged!(recursive by_kind: tag = "kind", content = "spec");
+
+    // Test flatten_tagged_recursive through nested structures and custom keys
+    #[test]
+    fn test_flatten_tagged_recursive() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Holder {
+            name: String,
+            items: Vec<MyEnum>,
+        }
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        enum Node {
+            Leaf(MyEnum),
+            Branch {
+                holder: Holder,
+                next: Option<Box<Node>>,
+            },
+        }
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct TestStruct {
+            #[serde(with = "flatten_tagged_recursive")]
+            default_keys: Node,
+            #[serde(with = "by_kind")]
+            custom_keys: Node,
+        }
+
+        let node = || Node::Branch {
+            holder: Holder {
+                name: "type".to_owned(),
+                items: vec![MyEnum::Unit, MyEnum::Tuple(1, 2)],
+            },
+            next: Some(Box::new(Node::Leaf(MyEnum::Struct {
+                value: 3,
+            }))),
+        };
+        let test_struct = TestStruct {
+            default_keys: node(),
+            custom_keys: node(),
+        };
+
+        let yaml = serde_yml::to_string(&test_struct).unwrap();
+        assert_eq!(
+            yaml,
+            "default_keys:\n  type: Branch\n  holder:\n    name: type\n    items:\n    - Unit\n    - type: Tuple\n      value:\n      - 1\n      - 2\n  next:\n    type: Leaf\n    value:\n      type: Struct\n      value: 3\ncustom_keys:\n  kind: Branch\n  holder:\n    name: type\n    items:\n    - Unit\n    - kind: Tuple\n      spec:\n      - 1\n      - 2\n  next:\n    kind: Leaf\n    spec:\n      kind: Struct\n      value: 3\n"
+        );
+        let deserialized: TestStruct =
+            serde_yml::from_str(&yaml).unwrap();
+        assert_eq!(deserialized, test_struct);
+
+        let deserialized: TestStruct = serde_yml::from_str(
+            "default_keys: !Leaf {type: Unit}\ncustom_keys:\n  kind: Leaf\n  spec: !Newtype 5\n",
+        )
+        .unwrap();
+        assert_eq!(deserialized.default_keys, Node::Leaf(MyEnum::Unit));
+        assert_eq!(
+            deserialized.custom_keys,
+            Node::Leaf(MyEnum::Newtype(5))
+        );
+    }
+
+    // Test number_from_string with numbers, numeric strings and invalid strings
+    #[test]
+    fn test_number_from_string() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct TestStruct {
+            #[serde(with = "number_from_string")]
+            int: i32,
+            #[serde(with = "number_from_string")]
+            unsigned: u64,
+            #[serde(with = "number_from_string")]
+            float: f64,
+        }
+
+        let cases = [
+            ("int: -42\nunsigned: 42\nfloat: 1.5", (-42, 42, 1.5)),
+            (
+                "int: '-42'\nunsigned: \"42\"\nfloat: '1.5'",
+                (-42, 42, 1.5),
+            ),
+            (
+                "int: '0x2A'\nunsigned: 0o52\nfloat: ' 1_000.5 '",
+                (42, 42, 1000.5),
+            ),
+            (
+                "int: -1_000\nunsigned: '007'\nfloat: 1e3",
+                (-1000, 7, 1000.0),
+            ),
+        ];
+        for (yaml, (int, unsigned, float)) in cases {
+            let expected = TestStruct {
+                int,
+                unsigned,
+                float,
+            };
+            let deserialized: TestStruct =
+                serde_yml::from_str(yaml).unwrap();
+            assert_eq!(deserialized, expected, "{}", yaml);
+            let value: serde_yml::Value =
+                serde_yml::from_str(yaml).unwrap();
+            assert_eq!(
+                serde_yml::from_value::<TestStruct>(value).unwrap(),
+                expected
+            );
+        }
+
+        let yaml = serde_yml::to_string(&TestStruct {
+            int: 1,
+            unsigned: 2,
+            float: 3.5,
+        })
+        .unwrap();
+        assert_eq!(yaml, "int: 1\nunsigned: 2\nfloat: 3.5\n");
+
+        for yaml in [
+            "int: _1\nunsigned: 1\nfloat: 1",
+            "int: 1__0\nunsigned: 1\nfloat: 1",
+            "int: one\nunsigned: 1\nfloat: 1",
+        ] {
+            let err =
+                serde_yml::from_str::<TestStruct>(yaml).unwrap_err();
+            assert!(
+                err.to_string().contains(
+                    "expected a number or a string containing a number"
+                ),
+                "{}",
+                err
+            );
+        }
+        let err = serde_yml::from_str::<TestStruct>(
+            "int: 1\nunsigned: '-1'\nfloat: 1",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("expected u64"), "{}", err);
+    }
+
+    // Test string_from_number with strings, numbers and FromStr types
+    #[test]
+    fn test_string_from_number() {
+        use std::net::Ipv4Addr;
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct TestStruct {
+            #[serde(with = "string_from_number")]
+            int: String,
+            #[serde(with = "string_from_number")]
+            float: String,
+            #[serde(with = "string_from_number")]
+            text: String,
+            #[serde(with = "string_from_number")]
+            address: Ipv4Addr,
+        }
+
+        let yaml =
+            "int: 0x10\nfloat: 2.50\ntext: '007'\naddress: 127.0.0.1\n";
+        let expected = TestStruct {
+            int: "16".to_owned(),
+            float: "2.5".to_owned(),
+            text: "007".to_owned(),
+            address: Ipv4Addr::LOCALHOST,
+        };
+        let deserialized: TestStruct =
+            serde_yml::from_str(yaml).unwrap();
+        assert_eq!(deserialized, expected);
+        let value: serde_yml::Value =
+            serde_yml::from_str(yaml).unwrap();
+        assert_eq!(
+            serde_yml::from_value::<TestStruct>(value).unwrap(),
+            expected
+        );
+
+        let yaml = serde_yml::to_string(&expected).unwrap();
+        assert_eq!(
+            serde_yml::from_str::<TestStruct>(&yaml).unwrap(),
+            expected
+        );
+
+        let err = serde_yml::from_str::<TestStruct>(
+            "int: [1]\nfloat: 1\ntext: a\naddress: 1.2.3.4",
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("expected a string or a number"));
+    }
+
+    // Test bool_yaml11 with every YAML 1.1 form and with invalid words
+    #[test]
+    fn test_bool_yaml11() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct TestStruct {
+            #[serde(with = "bool_yaml11")]
+            flag: bool,
+        }
+
+        for (word, expected) in [
+            ("y", true),
+            ("Y", true),
+            ("yes", true),
+            ("Yes", true),
+            ("YES", true),
+            ("'yes'", true),
+            ("on", true),
+            ("On", true),
+            ("ON", true),
+            ("true", true),
+            ("True", true),
+            ("n", false),
+            ("N", false),
+            ("no", false),
+            ("No", false),
+            ("NO", false),
+            ("off", false),
+            ("Off", false),
+            ("OFF", false),
+            ("false", false),
+            ("FALSE", false),
+        ] {
+            let yaml = format!("flag: {}", word);
+            let deserialized: TestStruct =
+                serde_yml::from_str(&yaml).unwrap();
+            assert_eq!(deserialized.flag, expected, "{}", word);
+            let value: serde_yml::Value =
+                serde_yml::from_str(&yaml).unwrap();
+            assert_eq!(
+                serde_yml::from_value::<TestStruct>(value)
+                    .unwrap()
+                    .flag,
+                expected
+            );
+        }
+
+        let yaml =
+            serde_yml::to_string(&TestStruct { flag: true }).unwrap();
+        assert_eq!(yaml, "flag: true\n");
+
+        for word in ["yEs", "1", "enabled", "~"] {
+            let yaml = format!("flag: {}", word);
+            let err =
+                serde_yml::from_str::<TestStruct>(&yaml).unwrap_err();
+            assert!(
+                err.to_string().contains(
+                    "expected a boolean such as true, false, yes, no, on or off"
+                ),
+                "{}",
+                err
+            );
+        }
+    }
+
+    // Test sorted with unordered collections and with collections that keep
+    // their own order
+    #[test]
+    fn test_sorted() {
+        use std::collections::{
+            BTreeSet, BinaryHeap, HashSet, VecDeque,
+        };
+
+        #[derive(Serialize, Deserialize, Debug)]
+        struct TestStruct {
+            #[serde(with = "sorted")]
+            heap: BinaryHeap<i32>,
+            #[serde(with = "sorted")]
+            set: HashSet<String>,
+            #[serde(with = "sorted")]
+            deque: VecDeque<u8>,
+            #[serde(with = "sorted")]
+            tree: BTreeSet<u8>,
+        }
+
+        let expected = "heap:\n- -2\n- 3\n- 3\n- 10\nset:\n- a\n- b\n- c\ndeque:\n- 1\n- 2\n- 3\ntree:\n- 7\n- 9\n";
+        for (heap, set) in [
+            ([10, 3, -2, 3], ["c", "a", "b"]),
+            ([-2, 3, 3, 10], ["a", "b", "c"]),
+            ([3, 10, 3, -2], ["b", "c", "a"]),
+        ] {
+            let test_struct = TestStruct {
+                heap: heap.into_iter().collect(),
+                set: set.iter().map(|s| s.to_string()).collect(),
+                deque: [3, 1, 2].into_iter().collect(),
+                tree: [9, 7].into_iter().collect(),
+            };
+            let yaml = serde_yml::to_string(&test_struct).unwrap();
+            assert_eq!(yaml, expected);
+            let value = serde_yml::to_value(&test_struct).unwrap();
+            assert_eq!(serde_yml::to_string(&value).unwrap(), expected);
+        }
+
+        let deserialized: TestStruct = serde_yml::from_str(
+            "heap: [3, 10, -2, 3]\nset: [c, a, b]\ndeque: [3, 1]\ntree: [9, 7]\n",
+        )
+        .unwrap();
+        assert_eq!(deserialized.heap.into_sorted_vec(), [-2, 3, 3, 10]);
+        assert_eq!(deserialized.set.len(), 3);
+        assert_eq!(deserialized.deque, [3, 1]);
+        assert_eq!(
+            deserialized.tree.into_iter().collect::<Vec<_>>(),
+            [7, 9]
+        );
+    }
+
+    // Test literal_block and folded_block with single-line, multi-line and
+    // unrepresentable strings
+    #[test]
+    fn test_block_scalars() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct TestStruct {
+            #[serde(with = "literal_block")]
+            literal: String,
+            #[serde(with = "folded_block")]
+            folded: String,
+        }
+
+        for (test_struct, expected) in [
+            (
+                TestStruct {
+                    literal: "echo".to_owned(),
+                    folded: "text".to_owned(),
+                },
+                "literal: |-\n  echo\nfolded: >-\n  text\n",
+            ),
+            (
+                TestStruct {
+                    literal: "a\nb\n".to_owned(),
+                    folded: "a\nb\n\n".to_owned(),
+                },
+                "literal: |\n  a\n  b\nfolded: >+\n  a\n\n  b\n\n",
+            ),
+            (
+                TestStruct {
+                    literal: "true".to_owned(),
+                    folded: "trailing ".to_owned(),
+                },
+                "literal: |-\n  true\nfolded: \"trailing \"\n",
+            ),
+        ] {
+            let yaml = serde_yml::to_string(&test_struct).unwrap();
+            assert_eq!(yaml, expected);
+            let deserialized: TestStruct =
+                serde_yml::from_str(&yaml).unwrap();
+            assert_eq!(deserialized, test_struct);
+        }
+
+        let value = serde_yml::to_value(TestStruct {
+            literal: "a".to_owned(),
+            folded: "b".to_owned(),
+        })
+        .unwrap();
+        assert_eq!(value["literal"], "a");
+        assert_eq!(
+            serde_yml::to_string(&value).unwrap(),
+            "literal: a\nfolded: b\n"
+        );
+    }
+
+    // Test versioned on a field with a custom version field, unversioned
+    // input and invalid versions
+    #[test]
+    fn test_versioned() {
+        use serde_yml::{Error, Value};
+        use versioned::Versions;
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        #[serde(deny_unknown_fields)]
+        struct Limits {
+            max_connections: u32,
+        }
+
+        impl Versions for Limits {
+            const FIELD: &'static str = "schema";
+            const LATEST: u64 = 2;
+            const UNVERSIONED: Option<u64> = Some(1);
+
+            fn from_version(
+                version: u64,
+                value: Value,
+            ) -> Result<Self, Error> {
+                match version {
+                    1 => Ok(Limits {
+                        max_connections: serde_yml::from_value::<u32>(
+                            value["max"].clone(),
+                        )?,
+                    }),
+                    2 => serde_yml::from_value(value),
+                    _ => Err(versioned::unsupported_version(
+                        version,
+                        &[1, 2],
+                    )),
+                }
+            }
+        }
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Server {
+            #[serde(with = "versioned")]
+            limits: Limits,
+        }
+
+        for yaml in [
+            "limits:\n  max: 8\n",
+            "limits:\n  max: 8\n  schema: 1\n",
+            "limits:\n  schema: '2'\n  max_connections: 8\n",
+        ] {
+            let server: Server = serde_yml::from_str(yaml).unwrap();
+            assert_eq!(server.limits.max_connections, 8);
+            assert_eq!(
+                serde_yml::to_string(&server).unwrap(),
+                "limits:\n  schema: 2\n  max_connections: 8\n"
+            );
+        }
+
+        for (yaml, expected) in [
+            (
+                "limits:\n  schema: 9\n",
+                "unsupported version 9, expected one of 1, 2",
+            ),
+            (
+                "limits:\n  schema: [2]\n",
+                "invalid value: sequence, expected a version number",
+            ),
+            (
+                "limits:\n  schema: 2\n  max: 8\n",
+                "unknown field `max`, expected `max_connections`",
+            ),
+        ] {
+            let error =
+                serde_yml::from_str::<Server>(yaml).unwrap_err();
+            assert_eq!(error.to_string(), expected);
+        }
+    }
+
+    // Test byte_size with every unit family and with invalid sizes
+    #[test]
+    fn test_byte_size() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct TestStruct {
+            #[serde(with = "byte_size")]
+            size: u64,
+        }
+
+        for (yaml, expected) in [
+            ("size: 512", 512),
+            ("size: 512B", 512),
+            ("size: 512k", 512_000),
+            ("size: 512 kB", 512_000),
+            ("size: 10MiB", 10 << 20),
+            ("size: 10mib", 10 << 20),
+            ("size: 10Mi", 10 << 20),
+            ("size: 1.5GiB", 3 << 29),
+            ("size: 1.5GB", 1_500_000_000),
+            ("size: 15EiB", 15 << 60),
+        ] {
+            let deserialized: TestStruct =
+                serde_yml::from_str(yaml).unwrap();
+            assert_eq!(deserialized.size, expected, "{}", yaml);
+        }
+
+        for (size, expected) in [
+            (0, "size: 0B\n"),
+            (1500, "size: 1500B\n"),
+            (1024, "size: 1KiB\n"),
+            (3 << 29, "size: 1536MiB\n"),
+            (2_000_000, "size: 2MB\n"),
+            (512_000, "size: 500KiB\n"),
+        ] {
+            let yaml =
+                serde_yml::to_string(&TestStruct { size }).unwrap();
+            assert_eq!(yaml.replace('\'', ""), expected);
+            let deserialized: TestStruct =
+                serde_yml::from_str(&yaml).unwrap();
+            assert_eq!(deserialized.size, size);
+        }
+
+        for yaml in [
+            "size: 10 MiBs",
+            "size: 0.1KiB",
+            "size: 16EiB",
+            "size: -1",
+            "size: MiB",
+        ] {
+            let err =
+                serde_yml::from_str::<TestStruct>(yaml).unwrap_err();
+            assert!(
+                err.to_string().contains(
+                    "expected a byte size such as 512, 10MiB or 1.5GB"
+                ),
+                "{}",
+                err
+            );
+        }
+    }
+
+    // Test duration with compound, fractional and invalid durations
+    #[test]
+    fn test_duration() {
+        use std::time::Duration;
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct TestStruct {
+            #[serde(with = "duration")]
+            timeout: Duration,
+        }
+
+        for (yaml, expected) in [
+            ("timeout: 45", Duration::from_secs(45)),
+            ("timeout: 250ms", Duration::from_millis(250)),
+            ("timeout: 1h30m", Duration::from_secs(5400)),
+            ("timeout: 1h 30m", Duration::from_secs(5400)),
+            ("timeout: 1.5h", Duration::from_secs(5400)),
+            ("timeout: 2d", Duration::from_secs(172_800)),
+            ("timeout: 10µs", Duration::from_micros(10)),
+            ("timeout: 1s 5ns", Duration::new(1, 5)),
+        ] {
+            let deserialized: TestStruct =
+                serde_yml::from_str(yaml).unwrap();
+            assert_eq!(deserialized.timeout, expected, "{}", yaml);
+        }
+
+        for (timeout, expected) in [
+            (Duration::ZERO, "timeout: 0s\n"),
+            (Duration::from_millis(90_500), "timeout: 1m30s500ms\n"),
+            (Duration::new(93_784, 5), "timeout: 1d2h3m4s5ns\n"),
+        ] {
+            let yaml =
+                serde_yml::to_string(&TestStruct { timeout }).unwrap();
+            assert_eq!(yaml.replace('\'', ""), expected);
+            let deserialized: TestStruct =
+                serde_yml::from_str(&yaml).unwrap();
+            assert_eq!(deserialized.timeout, timeout);
+        }
+
+        for yaml in [
+            "timeout: soon",
+            "timeout: '30'",
+            "timeout: 1.5ns",
+            "timeout: 5 minutes",
+            "timeout: -5",
+        ] {
+            let err =
+                serde_yml::from_str::<TestStruct>(yaml).unwrap_err();
+            assert!(
+                err.to_string().contains("expected a duration such as"),
+                "{}",
+                err
+            );
+        }
+    }
+
+    // Test uuid with the forms it parses and with an invalid identifier
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn test_uuid() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct TestStruct {
+            #[serde(with = "uuid")]
+            id: ::uuid::Uuid,
+        }
+
+        let expected = ::uuid::Uuid::from_u128(
+            0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8,
+        );
+        for yaml in [
+            "id: 67e55044-10b1-426f-9247-bb680e5fe0c8",
+            "id: 67e5504410b1426f9247bb680e5fe0c8",
+            "id: '{67e55044-10b1-426f-9247-bb680e5fe0c8}'",
+            "id: urn:uuid:67e55044-10b1-426f-9247-bb680e5fe0c8",
+        ] {
+            let deserialized: TestStruct =
+                serde_yml::from_str(yaml).unwrap();
+            assert_eq!(deserialized.id, expected, "{}", yaml);
+        }
+
+        let yaml =
+            serde_yml::to_string(&TestStruct { id: expected }).unwrap();
+        assert_eq!(
+            yaml,
+            "id: '67e55044-10b1-426f-9247-bb680e5fe0c8'\n"
+        );
+
+        let err = serde_yml::from_str::<TestStruct>("id: 67e55044")
+            .unwrap_err();
+        assert!(
+            err.to_string().starts_with(
+                "id: invalid value: string \"67e55044\", expected a UUID"
+            ),
+            "{}",
+            err
+        );
+    }
+
+    // Test chrono_datetime with UTC and fixed offset date times
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono_datetime() {
+        use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct TestStruct {
+            #[serde(with = "chrono_datetime")]
+            utc: DateTime<Utc>,
+            #[serde(with = "chrono_datetime")]
+            local: DateTime<FixedOffset>,
+        }
+
+        let yaml = "utc: 2024-05-01T12:30:00.250Z\nlocal: 2024-05-01 14:30:00+02:00\n";
+        let deserialized: TestStruct =
+            serde_yml::from_str(yaml).unwrap();
+        let offset = FixedOffset::east_opt(7200).unwrap();
+        assert_eq!(
+            deserialized.utc,
+            Utc.with_ymd_and_hms(2024, 5, 1, 12, 30, 0).unwrap()
+                + chrono::Duration::milliseconds(250)
+        );
+        assert_eq!(
+            deserialized.local,
+            offset.with_ymd_and_hms(2024, 5, 1, 14, 30, 0).unwrap()
+        );
+
+        let yaml = serde_yml::to_string(&deserialized).unwrap();
+        assert_eq!(
+            yaml,
+            "utc: '2024-05-01T12:30:00.250Z'\nlocal: '2024-05-01T14:30:00+02:00'\n"
+        );
+
+        let err = serde_yml::from_str::<TestStruct>(
+            "utc: 2024-05-01\nlocal: 2024-05-01T14:30:00+02:00\n",
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string().starts_with(
+                "utc: invalid value: string \"2024-05-01\", expected an RFC 3339 date and time"
+            ),
+            "{}",
+            err
+        );
+    }
+
+    // Test time_offsetdatetime keeps the offset and rejects other formats
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_time_offsetdatetime() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct TestStruct {
+            #[serde(with = "time_offsetdatetime")]
+            at: ::time::OffsetDateTime,
+        }
+
+        let deserialized: TestStruct =
+            serde_yml::from_str("at: 2024-05-01T14:30:00.5+02:00")
+                .unwrap();
+        assert_eq!(deserialized.at.unix_timestamp(), 1_714_566_600);
+        assert_eq!(deserialized.at.millisecond(), 500);
+
+        let yaml = serde_yml::to_string(&deserialized).unwrap();
+        assert_eq!(yaml, "at: '2024-05-01T14:30:00.5+02:00'\n");
+
+        let err =
+            serde_yml::from_str::<TestStruct>("at: 2024-05-01 14:30")
+                .unwrap_err();
+        assert!(
+            err.to_string().starts_with(
+                "at: invalid value: string \"2024-05-01 14:30\", expected an RFC 3339 date and time"
+            ),
+            "{}",
+            err
+        );
+    }
+
+    // Test duration_humantime with words, numbers of seconds and invalid input
+    #[cfg(feature = "humantime")]
+    #[test]
+    fn test_duration_humantime() {
+        use std::time::Duration;
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct TestStruct {
+            #[serde(with = "duration_humantime")]
+            timeout: Duration,
+        }
+
+        for (yaml, expected) in [
+            ("timeout: 30s", Duration::from_secs(30)),
+            ("timeout: 5m", Duration::from_secs(300)),
+            ("timeout: 1h 30m", Duration::from_secs(5400)),
+            ("timeout: 250ms", Duration::from_millis(250)),
+            ("timeout: 45", Duration::from_secs(45)),
+        ] {
+            let deserialized: TestStruct =
+                serde_yml::from_str(yaml).unwrap();
+            assert_eq!(deserialized.timeout, expected, "{}", yaml);
+        }
+
+        let yaml = serde_yml::to_string(&TestStruct {
+            timeout: Duration::from_millis(90_500),
+        })
+        .unwrap();
+        assert_eq!(yaml, "timeout: '1m 30s 500ms'\n");
+
+        for yaml in ["timeout: soon", "timeout: -5"] {
+            let err =
+                serde_yml::from_str::<TestStruct>(yaml).unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains("expected a duration such as 30s"),
+                "{}",
+                err
+            );
+        }
+    }
+
+    // Test struct variants with flattened fields using singleton_map and singleton_map_recursive
+    #[test]
+    fn test_singleton_map_flatten_in_variant() {
+        use std::collections::BTreeMap;
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Extra {
+            kind: MyEnum,
+            #[serde(flatten)]
+            more: BTreeMap<String, usize>,
+        }
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        enum Shape {
+            Circle {
+                r: usize,
+                #[serde(flatten)]
+                extra: Extra,
+            },
+            Square {
+                #[serde(flatten)]
+                kind: MyEnum,
+            },
+        }
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Flat {
+            #[serde(with = "singleton_map")]
+            shape: Shape,
+        }
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Recursive {
+            #[serde(with = "singleton_map_recursive")]
+            shapes: Vec<Shape>,
+        }
+
+        let circle = || Shape::Circle {
+            r: 1,
+            extra: Extra {
+                kind: MyEnum::Struct { value: 2 },
+                more: BTreeMap::from([("k".to_owned(), 3)]),
+            },
+        };
+        let square = Shape::Square {
+            kind: MyEnum::Newtype(4),
+        };
+
+        // Test singleton_map, through YAML text and through a Value
+        let flat = Flat { shape: circle() };
+        let yaml = serde_yml::to_string(&flat).unwrap();
+        assert_eq!(
+            yaml,
+            "shape:\n  Circle:\n    r: 1\n    kind: !Struct\n      value: 2\n    k: 3\n"
+        );
+        assert_eq!(serde_yml::from_str::<Flat>(&yaml).unwrap(), flat);
+        let value = serde_yml::to_value(&flat).unwrap();
+        assert_eq!(value["shape"]["Circle"]["k"], 3);
+        assert_eq!(serde_yml::from_value::<Flat>(value).unwrap(), flat);
+
+        // Test singleton_map_recursive, which also applies to the flattened fields
+        let recursive = Recursive {
+            shapes: vec![circle(), square],
+        };
+        let yaml = serde_yml::to_string(&recursive).unwrap();
+        assert_eq!(
+            yaml,
+            "shapes:\n- Circle:\n    r: 1\n    kind:\n      Struct:\n        value: 2\n    k: 3\n- Square:\n    Newtype: 4\n"
+        );
+        assert_eq!(
+            serde_yml::from_str::<Recursive>(&yaml).unwrap(),
+            recursive
+        );
+        let value = serde_yml::to_value(&recursive).unwrap();
+        assert_eq!(
+            serde_yml::from_value::<Recursive>(value).unwrap(),
+            recursive
+        );
+    }
+
+    // Test applying with modules to the elements of containers through SeqOf, MapValues and OptionOf
+    #[test]
+    fn test_with_module_combinators() {
+        use std::collections::{BTreeSet, HashMap};
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct TestStruct {
+            #[serde(with = "SeqOf::<SingletonMap>")]
+            list: Vec<MyEnum>,
+            #[serde(with = "SeqOf::<NumberFromString>")]
+            set: BTreeSet<u16>,
+            #[serde(with = "MapValues::<SingletonMapRecursive>")]
+            map: HashMap<String, Vec<MyEnum>>,
+            #[serde(with = "OptionOf::<SeqOf<StringFromNumber>>")]
+            names: Option<Vec<String>>,
+            #[serde(default, with = "OptionOf::<SingletonMap>")]
+            missing: Option<MyEnum>,
+        }
+
+        let yaml = "list:\n- Unit\n- Newtype: 1\nset:\n- '8080'\n- 80\nmap:\n  a:\n  - Tuple:\n    - 2\n    - 3\nnames:\n- 1.5\n- x\n";
+        let test_struct: TestStruct =
+            serde_yml::from_str(yaml).unwrap();
+        assert_eq!(
+            test_struct,
+            TestStruct {
+                list: vec![MyEnum::Unit, MyEnum::Newtype(1)],
+                set: BTreeSet::from([80, 8080]),
+                map: HashMap::from([(
+                    "a".to_owned(),
+                    vec![MyEnum::Tuple(2, 3)],
+                )]),
+                names: Some(vec!["1.5".to_owned(), "x".to_owned()]),
+                missing: None,
+            }
+        );
+
+        // Test serialization, and a round trip through a Value
+        let yaml = serde_yml::to_string(&test_struct).unwrap();
+        assert_eq!(
+            yaml,
+            "list:\n- Unit\n- Newtype: 1\nset:\n- 80\n- 8080\nmap:\n  a:\n  - Tuple:\n    - 2\n    - 3\nnames:\n- '1.5'\n- x\nmissing: null\n"
+        );
+        let value = serde_yml::to_value(&test_struct).unwrap();
+        let deserialized: TestStruct =
+            serde_yml::from_value(value).unwrap();
+        assert_eq!(test_struct, deserialized);
+
+        // Test that errors of the module are reported for the element
+        let err = serde_yml::from_str::<TestStruct>(
+            "list: [Unit]\nset: [x]\nmap: {}\nnames: null\n",
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "set.\\[0\\]: invalid value: string \"x\", expected a number or a string containing a number at line 2 column 7"
+        );
+    }
 }
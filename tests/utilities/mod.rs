@@ -1,2 +1,4 @@
 /// The `test_directory.rs` module contains tests for the directory module.
 pub mod test_directory;
+/// The `test_folding.rs` module contains tests for the folding module.
+pub mod test_folding;
@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod tests {
+    use serde_yml::utilities::folding::{fold_block_scalar, unfold};
+
+    const VALUES: &[&str] = &[
+        "",
+        "\n",
+        "\n\n",
+        "word",
+        "word\n",
+        "a long line of text that needs wrapping\n",
+        "first paragraph\nsecond paragraph\n",
+        "first\n\n\nafter two empty lines\n",
+        "\n\nleading empty lines\n",
+        "trailing newlines\n\n\n",
+        "text\n  more indented\nback to text\n",
+        "text\n\n  indented after an empty line\n\ntext\n",
+        "\tTab indented\nnext\n",
+        "double  spaced  words stay on one line\n",
+        "averyveryverylongwordwithoutanyspace and more\n",
+        "ends with a space \nnext\n",
+        "unicode çà et là, ünïcödé wörds\n",
+    ];
+
+    /// Wraps folded content in a `>2+` block scalar and parses it.
+    fn parse_folded(content: &str) -> String {
+        let mut yaml = String::from(">2+\n");
+        for line in content.split_inclusive('\n') {
+            if line != "\n" {
+                yaml.push_str("  ");
+            }
+            yaml.push_str(line);
+        }
+        serde_yml::from_str(&yaml).unwrap()
+    }
+
+    /// Tests that unfolding the output of `fold_block_scalar` returns the original value.
+    #[test]
+    fn test_fold_round_trip() {
+        for value in VALUES {
+            for width in [1, 5, 10, 20, 80] {
+                let folded = fold_block_scalar(value, width);
+                assert_eq!(unfold(&folded), *value, "{:?}", folded);
+            }
+        }
+    }
+
+    /// Tests that `unfold` agrees with the parser on folded block scalars.
+    #[test]
+    fn test_unfold_matches_parser() {
+        for value in VALUES.iter().filter(|value| !value.is_empty()) {
+            let folded = fold_block_scalar(value, 10);
+            if folded.trim_end_matches('\n').is_empty() {
+                continue;
+            }
+            assert_eq!(
+                unfold(&folded),
+                parse_folded(&folded),
+                "{:?}",
+                folded
+            );
+        }
+        let text = "one\ntwo\n\nthree\n  four\nfive\n";
+        assert_eq!(unfold(text), parse_folded(text));
+    }
+
+    /// Tests that lines are wrapped at the last space that fits the width.
+    #[test]
+    fn test_fold_wraps_lines() {
+        assert_eq!(
+            fold_block_scalar("one two three four\n", 9),
+            "one two\nthree\nfour\n"
+        );
+        assert_eq!(
+            fold_block_scalar("unbreakable word\n", 4),
+            "unbreakable\nword\n"
+        );
+        assert_eq!(
+            fold_block_scalar("  indented lines are not wrapped\n", 4),
+            "  indented lines are not wrapped\n"
+        );
+    }
+
+    /// Tests that blank lines are counted the way the YAML specification folds them.
+    #[test]
+    fn test_unfold_line_breaks() {
+        assert_eq!(unfold("a\nb"), "a b");
+        assert_eq!(unfold("a\n\nb"), "a\nb");
+        assert_eq!(unfold("a\n\n\nb"), "a\n\nb");
+        assert_eq!(unfold("a\n  b\nc"), "a\n  b\nc");
+        assert_eq!(unfold("a\n\n  b"), "a\n\n  b");
+    }
+}
@@ -15,3 +15,16 @@ pub mod test_partial_eq;
 
 /// The `test_tag` module contains tests for the `Tag` trait implementations.
 pub mod test_tag;
+
+/// The `test_styled` module contains tests for the `StyledValue` type.
+pub mod test_styled;
+
+/// The `test_canonical` module contains tests for canonical hashing of values.
+pub mod test_canonical;
+
+/// The `test_raw` module contains tests for the `RawYaml` type.
+pub mod test_raw;
+
+/// The `test_compact` module contains tests for the `CompactValue` type.
+#[cfg(feature = "compact")]
+pub mod test_compact;
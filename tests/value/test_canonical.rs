@@ -0,0 +1,153 @@
+#[cfg(test)]
+mod tests {
+    use serde_yml::value::{
+        CanonicalOptions, HashAlgorithm, KeyOrder, TagPolicy,
+    };
+    use serde_yml::Value;
+
+    fn hex(digest: [u8; 32]) -> String {
+        digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    /// Tests that the hash of version 1 matches the documented layout.
+    #[test]
+    fn test_canonical_hash_is_stable() {
+        // Arrange
+        let value: Value = serde_yml::from_str(
+            "tagged: !T ~\nratio: 0.5\nports: [80, -1]\nname: \"web\"\n",
+        )
+        .unwrap();
+        let long = Value::from("a".repeat(200));
+
+        // Act
+        let hash = value.canonical_hash(HashAlgorithm::Sha256);
+        let long_hash = long.canonical_hash(HashAlgorithm::Sha256);
+
+        // Assert
+        assert_eq!(
+            hex(hash),
+            "e392ce16b958dffcd48cca98872f2953c0300af4fe568aadd0b15137cbfd9170"
+        );
+        assert_eq!(
+            hex(long_hash),
+            "f018ace53933fec72505254fb690d68fc50f1c18fd243f736f6f7ccaf2d98b5b"
+        );
+    }
+
+    /// Tests which differences between values change the hash.
+    #[test]
+    fn test_canonical_hash_equivalence() {
+        // Arrange
+        let hash = |yaml: &str| {
+            serde_yml::from_str::<Value>(yaml)
+                .unwrap()
+                .canonical_hash(HashAlgorithm::Sha256)
+        };
+
+        // Act & Assert
+        assert_eq!(hash("{a: 1, b: 2}"), hash("b: 2\na: 1\n"));
+        assert_eq!(hash("x: &a [1]\ny: *a"), hash("x: [1]\ny: [1]"));
+        assert_eq!(hash("'text'"), hash("text"));
+        assert_eq!(hash("!T x"), hash("!<!T> x"));
+        assert_eq!(hash("-0.0"), hash("0.0"));
+        assert_eq!(hash(".nan"), hash(".NaN"));
+        assert_ne!(hash("1"), hash("1.0"));
+        assert_ne!(hash("1"), hash("'1'"));
+        assert_ne!(hash("[a, b]"), hash("[b, a]"));
+        assert_ne!(hash("!T x"), hash("x"));
+        assert_ne!(hash("[ab]"), hash("[a, b]"));
+        assert_ne!(hash("~"), hash("''"));
+    }
+
+    /// Tests the key order and tag policies.
+    #[test]
+    fn test_canonical_options() {
+        // Arrange
+        let a: Value = serde_yml::from_str("{x: !T 1, y: 2}").unwrap();
+        let b: Value = serde_yml::from_str("{y: 2, x: 1}").unwrap();
+        let ignore_tags = CanonicalOptions {
+            tags: TagPolicy::Ignore,
+            ..CanonicalOptions::default()
+        };
+        let preserve = CanonicalOptions {
+            key_order: KeyOrder::Preserve,
+            tags: TagPolicy::Ignore,
+            ..CanonicalOptions::default()
+        };
+
+        // Act
+        let bytes = a.canonical_bytes(&preserve);
+
+        // Assert
+        assert!(bytes.starts_with(b"serde_yml/canonical/v1pu"));
+        assert_eq!(
+            a.canonical_hash_with(&ignore_tags),
+            b.canonical_hash_with(&ignore_tags)
+        );
+        assert_ne!(
+            a.canonical_hash_with(&preserve),
+            b.canonical_hash_with(&preserve)
+        );
+        assert_ne!(
+            a.canonical_hash_with(&ignore_tags),
+            a.canonical_hash(HashAlgorithm::Sha256)
+        );
+    }
+
+    /// Tests that canonicalizing sorts every mapping, including keys that
+    /// are mappings, and gives output independent of insertion order.
+    #[test]
+    fn test_canonicalize() {
+        let mut a: Value = serde_yml::from_str(
+            "z: !T {q: 1, p: 2}\nv: [{b: 1, a: 2}]\n? {d: 1, c: 2}\n: x\n",
+        )
+        .unwrap();
+        let mut b: Value = serde_yml::from_str(
+            "? {c: 2, d: 1}\n: x\nv: [{a: 2, b: 1}]\nz: !T {p: 2, q: 1}\n",
+        )
+        .unwrap();
+        assert_ne!(
+            serde_yml::to_string(&a).unwrap(),
+            serde_yml::to_string(&b).unwrap()
+        );
+
+        a.canonicalize();
+        b.canonicalize();
+        let yaml = serde_yml::to_string(&a).unwrap();
+        assert_eq!(yaml, serde_yml::to_string(&b).unwrap());
+        assert_eq!(
+            yaml,
+            "v:\n- a: 2\n  b: 1\nz: !T\n  p: 2\n  q: 1\n? c: 2\n  d: 1\n: x\n"
+        );
+    }
+
+    /// Tests that integers beyond 64 bits are encoded by value, in the
+    /// range of `i128` as any other integer.
+    #[test]
+    #[cfg(feature = "bigint")]
+    fn test_canonical_bytes_big_integers() {
+        let options = CanonicalOptions::default();
+        let header = b"serde_yml/canonical/v1st".to_vec();
+
+        let wide: Value =
+            serde_yml::from_str("-18446744073709551617").unwrap();
+        let mut expected = header.clone();
+        expected.push(b'i');
+        expected.extend_from_slice(
+            &(-18446744073709551617i128).to_be_bytes(),
+        );
+        assert_eq!(wide.canonical_bytes(&options), expected);
+
+        let huge: Value = serde_yml::from_str(
+            "340282366920938463463374607431768211456",
+        )
+        .unwrap();
+        let mut expected = header;
+        expected.push(b'z');
+        expected.extend_from_slice(&39u64.to_be_bytes());
+        expected.extend_from_slice(
+            b"340282366920938463463374607431768211456",
+        );
+        assert_eq!(huge.canonical_bytes(&options), expected);
+    }
+}
@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+    use serde_yml::value::{CompactValue, Interner};
+    use serde_yml::Value;
+    use std::mem::size_of;
+    use std::sync::Arc;
+
+    /// Tests that a document loaded compactly converts to the same `Value` as a regular load.
+    #[test]
+    fn test_compact_matches_value() {
+        // Arrange
+        let yaml = concat!(
+            "name: demo\n",
+            "ports: [80, 443]\n",
+            "limits: {cpu: 2.5, enabled: true, extra: ~}\n",
+            "? [1, 2]\n",
+            ": pair\n",
+            "kind: !Service {name: web}\n",
+        );
+        let mut interner = Interner::new();
+
+        // Act
+        let compact = interner.parse(yaml).unwrap();
+
+        // Assert
+        let expected: Value = serde_yml::from_str(yaml).unwrap();
+        assert_eq!(Value::from(compact.clone()), expected);
+        assert_eq!(CompactValue::from(expected), compact);
+        assert_eq!(
+            serde_yml::to_string(&compact).unwrap(),
+            serde_yml::to_string(&Value::from(compact)).unwrap()
+        );
+    }
+
+    /// Tests that keys repeated across mappings share one allocation, while values do not.
+    #[test]
+    fn test_compact_interns_keys() {
+        // Arrange
+        let yaml =
+            "- name: a\n  image: name\n- name: b\n  image: name\n";
+        let mut interner = Interner::new();
+
+        // Act
+        let pods = interner.parse(yaml).unwrap();
+
+        // Assert
+        let pods = pods.as_sequence().unwrap();
+        let key = |pod: &CompactValue| match &pod.as_mapping().unwrap()
+            [0]
+        .0
+        {
+            CompactValue::String(key) => Arc::clone(key),
+            other => panic!("expected a string key, found {:?}", other),
+        };
+        assert!(Arc::ptr_eq(&key(&pods[0]), &key(&pods[1])));
+        assert_eq!(interner.len(), 2);
+        assert_eq!(
+            pods[1].get("image").unwrap().as_str(),
+            Some("name")
+        );
+        assert_eq!(pods[1].get("missing"), None);
+        assert!(size_of::<CompactValue>() < size_of::<Value>());
+    }
+
+    /// Tests that duplicate keys are rejected as when loading a `Value`.
+    #[test]
+    fn test_compact_duplicate_keys() {
+        // Arrange
+        let mut interner = Interner::new();
+
+        let strings = "a: 1\nb: 2\na: 3\n";
+        let sequences = "? [1]\n: x\n? [1]\n: y\n";
+
+        // Act
+        let string_error = interner.parse(strings).unwrap_err();
+        let sequence_error = interner.parse(sequences).unwrap_err();
+
+        // Assert
+        let expected = |yaml| {
+            serde_yml::from_str::<Value>(yaml).unwrap_err().to_string()
+        };
+        assert_eq!(string_error.to_string(), expected(strings));
+        assert!(string_error.to_string().contains("with key \"a\""));
+        assert_eq!(sequence_error.to_string(), expected(sequences));
+    }
+}
@@ -2,6 +2,8 @@
 mod tests {
     use serde_yml::value::{Mapping, Value};
     use std::borrow::Cow;
+    use std::collections::BTreeMap;
+    use std::convert::TryFrom;
 
     // Conversion tests for non-numeric types
 
@@ -294,4 +296,111 @@ mod tests {
         // Then it should be converted correctly.
         assert_eq!(x, Value::Number(42.5.into()));
     }
+
+    // Conversion tests for options and key-value pairs
+
+    #[test]
+    fn test_from_option() {
+        // Verify conversion from Option to Value
+        // Given a present and a missing value,
+        let some = Some(42);
+        let none: Option<i32> = None;
+        // When converting them to Value,
+        let x: Value = some.into();
+        let y: Value = none.into();
+        // Then `None` should become null.
+        assert_eq!(x, Value::Number(42.into()));
+        assert_eq!(y, Value::Null);
+    }
+
+    #[test]
+    fn test_from_btree_map() {
+        // Verify conversion from BTreeMap to Value
+        // Given a map with keys inserted out of order,
+        let mut map = BTreeMap::new();
+        map.insert("b", vec![2]);
+        map.insert("a", vec![1]);
+        // When converting it to Value,
+        let x: Value = map.into();
+        // Then it should become a mapping in key order.
+        let keys: Vec<_> = x.as_mapping().unwrap().keys().collect();
+        assert_eq!(keys, [&Value::from("a"), &Value::from("b")]);
+        assert_eq!(x["b"], Value::Sequence(vec![2.into()]));
+    }
+
+    #[test]
+    fn test_from_pairs() {
+        // Verify conversion from a Vec of pairs to Value
+        // Given pairs with a repeated key,
+        let pairs = vec![("z", 1), ("y", 2), ("z", 3)];
+        // When converting them to Value,
+        let x: Value = pairs.into();
+        // Then the order of first appearance and the last value are kept.
+        let mut expected = Mapping::new();
+        expected.insert("z".into(), 3.into());
+        expected.insert("y".into(), 2.into());
+        assert_eq!(x, Value::Mapping(expected));
+    }
+
+    #[test]
+    fn test_mapping_from_iterator() {
+        // Verify collecting pairs of values into a Mapping
+        // Given an iterator of key-value pairs,
+        let pairs =
+            (1..=2).map(|n| (Value::from(n), Value::from(n * 10)));
+        // When collecting it,
+        let mapping: Mapping = pairs.collect();
+        // Then every pair should be present.
+        assert_eq!(mapping.len(), 2);
+        assert_eq!(mapping[&Value::from(2)], Value::from(20));
+    }
+
+    // Fallible conversion tests from Value
+
+    #[test]
+    fn test_try_from_value() {
+        // Verify conversion from Value to primitive types
+        // Given values of matching types,
+        // When converting them back,
+        // Then the original values should be returned.
+        assert!(bool::try_from(Value::Bool(true)).unwrap());
+        assert_eq!(u8::try_from(Value::from(200)).unwrap(), 200);
+        assert_eq!(i64::try_from(Value::from(-7)).unwrap(), -7);
+        assert_eq!(f64::try_from(Value::from(1.5)).unwrap(), 1.5);
+        assert_eq!(f64::try_from(Value::from(2)).unwrap(), 2.0);
+        assert_eq!(
+            String::try_from(Value::from("lorem")).unwrap(),
+            "lorem"
+        );
+    }
+
+    #[test]
+    fn test_try_from_value_errors() {
+        // Verify the errors of failed conversions from Value
+        // Given values of the wrong type or out of range,
+        // When converting them,
+        let wrong_type =
+            bool::try_from(Value::from("yes")).unwrap_err();
+        let out_of_range = u8::try_from(Value::from(300)).unwrap_err();
+        let negative = usize::try_from(Value::from(-1)).unwrap_err();
+        let sequence =
+            String::try_from(Value::from(vec![1])).unwrap_err();
+        // Then the errors should name the value and the expected type.
+        assert_eq!(
+            wrong_type.to_string(),
+            "invalid type: string \"yes\", expected a boolean"
+        );
+        assert_eq!(
+            out_of_range.to_string(),
+            "invalid value: integer `300`, expected u8"
+        );
+        assert_eq!(
+            negative.to_string(),
+            "invalid value: integer `-1`, expected usize"
+        );
+        assert_eq!(
+            sequence.to_string(),
+            "invalid type: sequence, expected a string"
+        );
+    }
 }
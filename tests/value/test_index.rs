@@ -725,4 +725,118 @@ mod tests {
             })
         );
     }
+
+    // Tests for the checked accessors and panic messages
+
+    /// Test `try_index` with keys and indices that are found and missing.
+    #[test]
+    fn test_try_index() {
+        let value: Value =
+            serde_yml::from_str("host: a\nports: [80]\n").unwrap();
+        assert_eq!(value.try_index("host").unwrap(), "a");
+        assert_eq!(
+            value.try_index("ports").unwrap().try_index(0).unwrap(),
+            80
+        );
+
+        let err = value.try_index("name").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "name: key not found; available keys: host, ports"
+        );
+        assert_eq!(err.path().unwrap().to_string(), "name");
+
+        let err = value["ports"].try_index(3).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r"\[3\]: index out of bounds of YAML sequence of length 1"
+        );
+        let err = value["ports"].try_index("x").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "x: cannot access a key of YAML sequence"
+        );
+        let err = value["host"].try_index("x").unwrap_err();
+        assert_eq!(err.to_string(), "x: cannot index into YAML string");
+        let err = Value::Null.try_index(true).unwrap_err();
+        assert_eq!(err.to_string(), "cannot index into YAML null");
+        assert!(err.path().is_none());
+    }
+
+    /// Test that `try_index` lists at most ten keys of a large mapping.
+    #[test]
+    fn test_try_index_many_keys() {
+        let value: Value = serde_yml::from_str(
+            "{a: 0, b: 1, c: 2, d: 3, e: 4, f: 5, g: 6, h: 7, i: 8, j: 9, 10: 10, ~: 11}",
+        )
+        .unwrap();
+        let err = value.try_index("z").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "z: key not found; available keys: a, b, c, d, e, f, g, h, i, j and 2 more"
+        );
+        let err = Value::Mapping(serde_yml::Mapping::new())
+            .try_index("z")
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "z: key not found in empty YAML mapping"
+        );
+    }
+
+    /// Test `try_index_mut` modifying a found value and reporting a missing one.
+    #[test]
+    fn test_try_index_mut() {
+        let mut value: Value =
+            serde_yml::from_str("ports: [80]\n").unwrap();
+        *value
+            .try_index_mut("ports")
+            .unwrap()
+            .try_index_mut(0)
+            .unwrap() = Value::from(8080);
+        assert_eq!(value["ports"][0], 8080);
+
+        let err = value.try_index_mut("host").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "host: key not found; available keys: ports"
+        );
+        assert!(value.get("host").is_none());
+    }
+
+    /// Test that a panic in a chain of mutable indexing names the whole path.
+    #[test]
+    #[should_panic(
+        expected = "cannot access index 2 of YAML sequence of length 1 at `spec.ports[2]`"
+    )]
+    fn test_index_mut_panic_names_path() {
+        let mut value: Value =
+            serde_yml::from_str("spec:\n  ports: [80]\n").unwrap();
+        value["spec"]["ports"][2] = Value::from(443);
+    }
+
+    /// Test that a panic names the path of a value reached through a reference.
+    #[test]
+    #[should_panic(
+        expected = "cannot access key \"name\" in YAML number at `spec.ports[0].name`"
+    )]
+    fn test_index_mut_panic_names_path_through_reference() {
+        let mut value: Value =
+            serde_yml::from_str("spec:\n  ports: [80]\n").unwrap();
+        let port = &mut value["spec"]["ports"][0];
+        port["name"] = Value::from("http");
+    }
+
+    /// Test that a new chain of mutable indexing starts a new path.
+    #[test]
+    #[should_panic(
+        expected = "cannot access index 5 of YAML sequence of length 0 at `[5]`"
+    )]
+    fn test_index_mut_panic_new_path() {
+        let mut value: Value =
+            serde_yml::from_str("a: {b: 1}\n").unwrap();
+        value["a"]["b"] = Value::from(2);
+        let mut other = Value::Sequence(Vec::new());
+        other[5] = Value::from(3);
+    }
 }
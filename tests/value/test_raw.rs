@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_yml::value::RawYaml;
+    use serde_yml::Value;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    struct Message {
+        #[serde(default)]
+        kind: String,
+        body: RawYaml,
+    }
+
+    /// Tests that nodes of every kind are captured as written.
+    #[test]
+    fn test_raw_yaml_capture() {
+        // Arrange
+        let cases = [
+            ("body: plain text  # comment\n", "plain text"),
+            ("body: !T 'quoted'\n", "!T 'quoted'"),
+            ("body: [1, {a: 2}]\n", "[1, {a: 2}]"),
+            (
+                "body:\n  - a\n  - b: \"é\"\n    c: |+\n      x\n\n  # end\nkind: k\n",
+                "- a\n- b: \"é\"\n  c: |+\n    x\n\n# end\n",
+            ),
+            ("base: &b {x: 1}\nbody: *b\n", "&b {x: 1}"),
+        ];
+
+        for (yaml, expected) in cases {
+            // Act
+            let message: Message = serde_yml::from_str(yaml).unwrap();
+
+            // Assert
+            assert_eq!(message.body.get(), expected, "{}", yaml);
+            assert_eq!(
+                message.body.deserialize_into::<Value>().unwrap(),
+                serde_yml::from_str::<Value>(yaml).unwrap()["body"],
+                "{}",
+                yaml
+            );
+        }
+    }
+
+    /// Tests that fragments are written back with their tags and styles.
+    #[test]
+    fn test_raw_yaml_serialize() {
+        // Arrange
+        let yaml = "kind: k\nbody:\n  a: !T '1'\n  b: \"two\"\n  c: >\n    folded\n  d: [x, y]\n";
+        let message: Message = serde_yml::from_str(yaml).unwrap();
+
+        // Act
+        let written = serde_yml::to_string(&message).unwrap();
+        let value = serde_yml::to_value(&message).unwrap();
+
+        // Assert
+        assert_eq!(
+            written,
+            "kind: k\nbody:\n  a: !T '1'\n  b: \"two\"\n  c: >\n    folded\n  d:\n  - x\n  - y\n"
+        );
+        assert_eq!(value, serde_yml::from_str::<Value>(yaml).unwrap());
+        assert_eq!(
+            serde_json::to_string(&message.body).unwrap(),
+            serde_json::to_string(message.body.get()).unwrap()
+        );
+    }
+
+    /// Tests raw fragments built from text or from other deserializers.
+    #[test]
+    fn test_raw_yaml_conversions() {
+        // Arrange
+        let value: Value =
+            serde_yml::from_str("kind: k\nbody: {a: [1, 2]}\n")
+                .unwrap();
+
+        // Act
+        let message: Message = serde_yml::from_value(value).unwrap();
+        let checked =
+            RawYaml::from_string("a: 1\n".to_owned()).unwrap();
+        let invalid = RawYaml::from_string("a: [1".to_owned());
+        let several = RawYaml::from_string("a\n---\nb\n".to_owned());
+
+        // Assert
+        assert_eq!(message.body.get(), "a:\n- 1\n- 2\n");
+        assert_eq!(checked.to_string(), "a: 1\n");
+        assert_eq!(checked.into_string(), "a: 1\n");
+        assert!(invalid.is_err());
+        assert!(several.is_err());
+    }
+}
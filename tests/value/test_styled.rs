@@ -0,0 +1,144 @@
+#[cfg(test)]
+mod tests {
+    use serde_yml::libyml::parser::ScalarStyle;
+    use serde_yml::path::Path;
+    use serde_yml::value::{IntegerFormat, Radix, StyledValue};
+    use serde_yml::Value;
+
+    /// Tests that a document written in every scalar style is written back unchanged.
+    #[test]
+    fn test_styled_round_trip() {
+        // Arrange
+        let yaml = concat!(
+            "plain: text\n",
+            "single: 'text'\n",
+            "double: \"text\"\n",
+            "literal: |\n",
+            "  first line\n",
+            "  second line\n",
+            "folded: >\n",
+            "  folded text\n",
+            "list:\n",
+            "- 'one'\n",
+            "- two\n",
+            "- !Tag \"three\"\n",
+            "number: 1\n",
+            "quoted number: '1'\n",
+        );
+
+        // Act
+        let styled: StyledValue = yaml.parse().unwrap();
+
+        // Assert
+        assert_eq!(styled.to_yaml().unwrap(), yaml);
+        assert_eq!(
+            styled.value,
+            serde_yml::from_str::<Value>(yaml).unwrap()
+        );
+        assert_eq!(
+            styled.styles.get(&"literal".parse::<Path>().unwrap()),
+            Some(ScalarStyle::Literal)
+        );
+        assert_eq!(
+            styled.styles.get(&"list[2]".parse::<Path>().unwrap()),
+            Some(ScalarStyle::DoubleQuoted)
+        );
+        assert_eq!(
+            styled.styles.get(&"number".parse::<Path>().unwrap()),
+            Some(ScalarStyle::Plain)
+        );
+    }
+
+    /// Tests that recorded styles are kept for edited strings and ignored when unsafe.
+    #[test]
+    fn test_styled_edits() {
+        // Arrange
+        let yaml = "a: plain\nb: 'quoted'\nc: |\n  block\n";
+        let mut styled: StyledValue = yaml.parse().unwrap();
+
+        // Act
+        styled.value["a"] = Value::from("true");
+        styled.value["b"] = Value::from("still quoted");
+        styled.value["c"] = Value::from("new\nblock\n");
+        styled.value["d"] = Value::from("added");
+
+        // Assert
+        assert_eq!(
+            styled.to_yaml().unwrap(),
+            "a: 'true'\nb: 'still quoted'\nc: |\n  new\n  block\nd: added\n"
+        );
+    }
+
+    /// Tests that aliased nodes keep the styles of their anchor.
+    #[test]
+    fn test_styled_aliases() {
+        // Arrange
+        let yaml = "base: &base\n  name: 'web'\ncopy: *base\n";
+
+        // Act
+        let styled: StyledValue = yaml.parse().unwrap();
+
+        // Assert
+        assert_eq!(
+            styled.to_yaml().unwrap(),
+            "base:\n  name: 'web'\ncopy:\n  name: 'web'\n"
+        );
+        assert_eq!(
+            StyledValue::from(styled.value.clone()).to_yaml().unwrap(),
+            serde_yml::to_string(&styled.value).unwrap()
+        );
+    }
+
+    /// Tests that integers written in hexadecimal, octal or binary keep their radix, width and case.
+    #[test]
+    fn test_styled_integer_formats() {
+        // Arrange
+        let yaml = concat!(
+            "ctrl: 0x00FF\n",
+            "status: 0xbeef\n",
+            "mask: 0b1010\n",
+            "mode: 0o755\n",
+            "offset: -0x10\n",
+            "count: 12\n",
+            "quoted: '0x1A'\n",
+        );
+        let mut styled: StyledValue = yaml.parse().unwrap();
+        let ctrl = "ctrl".parse::<Path>().unwrap();
+
+        // Act
+        let unchanged = styled.to_yaml().unwrap();
+        styled.value["ctrl"] = 0x1A.into();
+        styled.value["mask"] = 0b1_0110_0000.into();
+        styled.value["count"] = 13.into();
+        let edited = styled.to_yaml().unwrap();
+        let _ = styled.styles.remove_integer_format(&ctrl);
+
+        // Assert
+        assert_eq!(unchanged, yaml);
+        assert_eq!(
+            styled.styles.integer_format(&"status".parse().unwrap()),
+            Some(IntegerFormat {
+                radix: Radix::Hexadecimal,
+                digits: 4,
+                uppercase: false,
+            })
+        );
+        assert_eq!(
+            styled.styles.integer_format(&"count".parse().unwrap()),
+            None
+        );
+        assert_eq!(
+            edited,
+            concat!(
+                "ctrl: 0x001A\n",
+                "status: 0xbeef\n",
+                "mask: 0b101100000\n",
+                "mode: 0o755\n",
+                "offset: -0x10\n",
+                "count: 13\n",
+                "quoted: '0x1A'\n",
+            )
+        );
+        assert!(styled.to_yaml().unwrap().starts_with("ctrl: 26\n"));
+    }
+}